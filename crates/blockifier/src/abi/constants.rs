@@ -46,3 +46,14 @@ pub const BLOCK_HASH_CONTRACT_ADDRESS: u64 = 1;
 
 // The block number -> block hash mapping is written for the current block number minus this number.
 pub const STORED_BLOCK_HASH_BUFFER: u64 = 10;
+
+// This contract stores the key -> alias mapping used to compress the DA state diff; the alias
+// counter is stored under `ALIAS_COUNTER_STORAGE_KEY`.
+pub const ALIAS_CONTRACT_ADDRESS: u64 = 2;
+pub const ALIAS_COUNTER_STORAGE_KEY: u64 = 0;
+// The first alias to be allocated.
+pub const INITIAL_AVAILABLE_ALIAS: u64 = 128;
+// Keys below this value are cheap enough to be published as is, and are never aliased.
+pub const MIN_VALUE_FOR_ALIAS_ALLOC: u64 = 128;
+// Contract addresses up to this value (system contracts) are never aliased.
+pub const MAX_NON_COMPRESSED_CONTRACT_ADDRESS: u64 = 15;
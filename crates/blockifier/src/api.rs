@@ -0,0 +1,247 @@
+//! A stable facade over the Blockifier, for downstream node software.
+//!
+//! The items below follow semver: within a major version, they are neither removed nor changed
+//! incompatibly, regardless of how the modules defining them are reorganized. Prefer importing
+//! from this module over importing from the defining modules.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use starknet_api::hash::StarkHash;
+use starknet_api::transaction::TransactionHash;
+
+pub use crate::blockifier::audit_log::{AuditEntry, AuditRecord, RecordAuditLog};
+pub use crate::blockifier::block::{
+    pre_process_block, BlockInfo, BlockNumberHashPair, GasPriceBounds, GasPriceBoundsPolicy,
+    GasPriceOutOfBoundsError, GasPrices, GasPricesBounds,
+};
+pub use crate::blockifier::block_replay::{
+    execute_block, BlockExecutionSummary, ReceiptDiscrepancy, ReceiptMismatch, ReplayedEvent,
+    ReplayedReceipt,
+};
+pub use crate::blockifier::intake::{
+    AdmittedTransaction, IncomingTransaction, IntakeMetrics, IntakePipeline, IntakeStage,
+    IntakeStageMetrics, PriorityScorer, SizeLimits, StaticCheck, TipScorer, TransactionIntakeError,
+};
+pub use crate::blockifier::preflight::{CalldataPreflight, ExecuteCalldataLayout, PreflightError};
+pub use crate::blockifier::stateful_validator::StatefulValidator;
+pub use crate::blockifier::telemetry::{
+    ExecutionTelemetry, OtlpSignal, TelemetryError, TelemetryExporter,
+};
+pub use crate::blockifier::transaction_executor::{
+    BlockSummary, RecordTxStateChanges, RecordedExecution, TransactionExecutor,
+    TransactionExecutorError, TransactionExecutorResult,
+};
+pub use crate::context::{BlockContext, ChainInfo, FeeTokenAddresses, TransactionContext};
+pub use crate::execution::cairo_traceback::{
+    CairoTraceback, CairoTracebackFrame, DecodedRevertReason,
+};
+pub use crate::execution::call_info::{CallInfo, CallPath, TopLevelCall};
+pub use crate::execution::contract_class::class_hash::{
+    compiled_class_hash, compiled_class_hash_from_reader, sierra_class_hash, SierraClass,
+    SierraEntryPoint, SierraEntryPointsByType,
+};
+pub use crate::execution::contract_class::sierra_compilation::{
+    class_info_from_declared_sierra, class_info_from_sierra, CairoLangSierraCompiler,
+    SierraCompiler,
+};
+pub use crate::execution::contract_class::{
+    ClassInfo, ContractClass, ContractClassV0, ContractClassV1,
+};
+pub use crate::execution::entry_point::{ExecutionDeadline, MemoryLimits};
+pub use crate::execution::errors::{ClassHashError, SierraCompilationError};
+pub use crate::execution::execution_utils::DeployCollisionPolicy;
+pub use crate::execution::syscalls::gas_schedule::SyscallGasSchedule;
+use crate::fee::fee_utils::calculate_tx_fee;
+pub use crate::fee::gas_usage::{estimate_minimal_fee, estimate_minimal_gas_vector};
+pub use crate::state::cached_state::{
+    CachedState, CommitmentStateDiff, ContractCacheStats, GlobalContractCache,
+};
+pub use crate::state::errors::StateError;
+pub use crate::state::state_api::{State, StateReader, StateResult};
+pub use crate::state::state_update::{StateUpdate, StateUpdateDiff};
+pub use crate::transaction::account_transaction::AccountTransaction;
+pub use crate::transaction::custom_transaction::{
+    CustomTransaction, CustomTransactionHandler, CustomTransactionRegistry,
+};
+pub use crate::transaction::errors::{TransactionExecutionError, TransactionSimulationError};
+/// The receipt of an executed transaction.
+pub use crate::transaction::objects::TransactionExecutionInfo as TransactionReceipt;
+use crate::transaction::objects::{FeeChargeMode, HasRelatedFeeType};
+pub use crate::transaction::objects::{
+    FeeType, GasVector, ResourcesMapping, RevertReason, TransactionExecutionResult,
+};
+pub use crate::transaction::paymaster::{Paymaster, PaymasterConfig};
+pub use crate::transaction::simulation::{
+    simulate_transactions, PriceUnit, SimulatedTransaction, SimulationFeeEstimate, SimulationFlag,
+    SimulationFlags,
+};
+pub use crate::transaction::transaction_execution::Transaction;
+pub use crate::transaction::transactions::{
+    DeclareTransaction, DeployAccountTransaction, ExecutableTransaction, InvokeTransaction,
+    L1HandlerTransaction, L1ToL2Message,
+};
+pub use crate::versioned_constants::{
+    ConstantsPreset, GasCosts, StarknetVersion, VersionedConstants, VersionedConstantsBuilder,
+};
+
+#[cfg(test)]
+#[path = "api_test.rs"]
+pub mod test;
+
+/// Executes the transaction (validating it and charging its fee), and applies its state changes.
+pub fn execute<S: StateReader>(
+    tx: Transaction,
+    state: &mut CachedState<S>,
+    block_context: &BlockContext,
+) -> TransactionExecutionResult<TransactionReceipt> {
+    let (charge_fee, validate) = (true, true);
+    tx.execute(state, block_context, charge_fee, validate)
+}
+
+/// Executes the transaction without applying its state changes.
+pub fn simulate<S: StateReader>(
+    tx: Transaction,
+    state: &mut CachedState<S>,
+    block_context: &BlockContext,
+    flags: SimulationFlags,
+) -> TransactionExecutionResult<TransactionReceipt> {
+    let mut transactional_state = CachedState::create_transactional(state);
+    let receipt = tx.execute_raw(
+        &mut transactional_state,
+        block_context,
+        !flags.skip_fee_charge,
+        !flags.skip_validate,
+    );
+    transactional_state.abort();
+    receipt
+}
+
+/// Simulates the transaction (see `simulate`), returning only the sub-trace rooted at the given
+/// call; `None` if the call was not made. The rest of the trace is dropped as soon as the execution
+/// ends; it is still recorded during the execution, as the transaction's resources depend on it.
+pub fn trace_call<S: StateReader>(
+    tx: Transaction,
+    state: &mut CachedState<S>,
+    block_context: &BlockContext,
+    flags: SimulationFlags,
+    call_path: &CallPath,
+) -> TransactionExecutionResult<Option<CallInfo>> {
+    Ok(simulate(tx, state, block_context, flags)?.into_sub_trace(call_path))
+}
+
+/// Estimates the fee of the transaction: executes it without applying its state changes, and
+/// without checking or charging the fee. The estimate is the receipt's `actual_fee`; for L1
+/// handlers, this is the fee to be paid on L1.
+pub fn estimate_fee<S: StateReader>(
+    tx: Transaction,
+    state: &mut CachedState<S>,
+    block_context: &BlockContext,
+) -> TransactionExecutionResult<TransactionReceipt> {
+    let validate = true;
+    let mut transactional_state = CachedState::create_transactional(state);
+    let receipt = match tx {
+        Transaction::AccountTransaction(account_tx) => account_tx.execute_raw_with_fee_charge_mode(
+            &mut transactional_state,
+            block_context,
+            FeeChargeMode::ComputeOnly,
+            validate,
+        ),
+        Transaction::L1HandlerTransaction(l1_handler_tx) => {
+            let fee_type = l1_handler_tx.fee_type();
+            let charge_fee = false;
+            l1_handler_tx
+                .execute_raw(&mut transactional_state, block_context, charge_fee, validate)
+                .and_then(|receipt| {
+                    // The receipt of an L1 handler reports no (L2) fee.
+                    let actual_fee =
+                        calculate_tx_fee(&receipt.actual_resources, block_context, &fee_type)?;
+                    Ok(TransactionReceipt { actual_fee, ..receipt })
+                })
+        }
+        // Custom transactions are not charged a fee.
+        Transaction::Custom(custom_tx) => {
+            let charge_fee = false;
+            custom_tx.execute_raw(&mut transactional_state, block_context, charge_fee, validate)
+        }
+    };
+    transactional_state.abort();
+    receipt
+}
+
+/// Identifies the state a transaction is executed against (e.g., the global state root), as chosen
+/// by the caller. Must also identify the block context (e.g., by being unique per block).
+pub type StateVersion = StarkHash;
+
+type CacheKey = (TransactionHash, StateVersion, SimulationFlags);
+type CachedReceipts = HashMap<CacheKey, Arc<TransactionReceipt>>;
+
+/// An optional cache of simulation results, for RPC servers serving repeated trace and simulation
+/// requests: a transaction simulated against an already seen state version is not re-executed.
+/// Failed simulations are not cached. Entries are never evicted implicitly; callers should
+/// invalidate state versions that are no longer served (e.g., on a reorg).
+#[derive(Debug, Default)]
+pub struct ExecutionResultCache {
+    receipts: RwLock<CachedReceipts>,
+}
+
+impl ExecutionResultCache {
+    pub fn get(
+        &self,
+        tx_hash: TransactionHash,
+        state_version: StateVersion,
+        flags: SimulationFlags,
+    ) -> Option<Arc<TransactionReceipt>> {
+        self.read_receipts().get(&(tx_hash, state_version, flags)).cloned()
+    }
+
+    /// Returns the memoized receipt of the transaction, if any; otherwise, simulates the
+    /// transaction and memoizes the receipt.
+    pub fn simulate<S: StateReader>(
+        &self,
+        tx: Transaction,
+        state_version: StateVersion,
+        state: &mut CachedState<S>,
+        block_context: &BlockContext,
+        flags: SimulationFlags,
+    ) -> TransactionExecutionResult<Arc<TransactionReceipt>> {
+        let key = (tx.tx_hash(), state_version, flags);
+        if let Some(receipt) = self.read_receipts().get(&key) {
+            return Ok(receipt.clone());
+        }
+
+        let receipt = Arc::new(simulate(tx, state, block_context, flags)?);
+        self.write_receipts().insert(key, receipt.clone());
+        Ok(receipt)
+    }
+
+    /// Removes the results of all transactions executed against the given state version.
+    pub fn invalidate_state_version(&self, state_version: StateVersion) {
+        self.write_receipts().retain(|(_, version, _), _| *version != state_version);
+    }
+
+    /// Removes the results of the given transaction, against all state versions.
+    pub fn invalidate_tx(&self, tx_hash: TransactionHash) {
+        self.write_receipts().retain(|(hash, _, _), _| *hash != tx_hash);
+    }
+
+    pub fn clear(&self) {
+        self.write_receipts().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.read_receipts().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read_receipts().is_empty()
+    }
+
+    fn read_receipts(&self) -> RwLockReadGuard<'_, CachedReceipts> {
+        self.receipts.read().expect("Execution result cache lock is poisoned.")
+    }
+
+    fn write_receipts(&self) -> RwLockWriteGuard<'_, CachedReceipts> {
+        self.receipts.write().expect("Execution result cache lock is poisoned.")
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::Fee;
+
+use crate::api::{
+    estimate_fee, execute, simulate, trace_call, BlockContext, CallPath, ExecutionResultCache,
+    MemoryLimits, SimulationFlags, StateReader, TopLevelCall, Transaction, TransactionReceipt,
+};
+use crate::invoke_tx_args;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_trivial_calldata, CairoVersion, BALANCE};
+use crate::transaction::test_utils::{account_invoke_tx, block_context};
+
+#[rstest]
+fn test_simulate_estimate_and_execute(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let mut state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let sender_address = account_contract.get_instance_address(0);
+    let invoke_tx = || {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+        }))
+    };
+
+    // Simulations and estimations leave the state untouched.
+    let simulation_receipt =
+        simulate(invoke_tx(), &mut state, &block_context, SimulationFlags::default()).unwrap();
+    let estimation_receipt = estimate_fee(invoke_tx(), &mut state, &block_context).unwrap();
+    assert_eq!(state.get_nonce_at(sender_address).unwrap(), Nonce::default());
+    assert!(estimation_receipt.actual_fee > Fee(0));
+    assert_eq!(estimation_receipt.actual_fee, simulation_receipt.actual_fee);
+    assert!(estimation_receipt.fee_transfer_call_info.is_none());
+
+    let receipt = execute(invoke_tx(), &mut state, &block_context).unwrap();
+    assert_eq!(receipt.actual_fee, estimation_receipt.actual_fee);
+    assert_eq!(state.get_nonce_at(sender_address).unwrap(), Nonce(stark_felt!(1_u8)));
+}
+
+#[rstest]
+fn test_receipt_serde(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let mut state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let test_contract_address = test_contract.get_instance_address(0);
+    let invoke_tx = Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+        sender_address: account_contract.get_instance_address(0),
+        calldata: create_trivial_calldata(test_contract_address),
+    }));
+    let receipt = execute(invoke_tx, &mut state, &block_context).unwrap();
+
+    let json = serde_json::to_value(&receipt).unwrap();
+    assert_eq!(
+        json["execute_call_info"]["inner_calls"][0]["call"]["storage_address"],
+        serde_json::to_value(test_contract_address).unwrap()
+    );
+    assert_eq!(serde_json::from_value::<TransactionReceipt>(json).unwrap(), receipt);
+}
+
+#[rstest]
+fn test_traceless_execution(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let mut state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let invoke_tx = || {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address: account_contract.get_instance_address(0),
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+        }))
+    };
+    let full_receipt =
+        simulate(invoke_tx(), &mut state, &block_context, SimulationFlags::default()).unwrap();
+
+    block_context.extensions().insert(MemoryLimits { traceless: true, ..Default::default() });
+    let receipt = execute(invoke_tx(), &mut state, &block_context).unwrap();
+    assert_eq!(receipt.actual_fee, full_receipt.actual_fee);
+    let execute_call_info = receipt.execute_call_info.unwrap();
+    let full_execute_call_info = full_receipt.execute_call_info.unwrap();
+    assert!(execute_call_info.inner_calls.is_empty());
+    assert_eq!(execute_call_info.resources, full_execute_call_info.resources);
+    assert_eq!(
+        execute_call_info.syscall_counter,
+        full_execute_call_info.summarize().syscall_counter
+    );
+}
+
+#[rstest]
+fn test_trace_call(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let mut state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let sender_address = account_contract.get_instance_address(0);
+    let test_contract_address = test_contract.get_instance_address(0);
+    let mut trace = |top_level_call, inner_call_indices: &[usize]| {
+        let tx = Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: create_trivial_calldata(test_contract_address),
+        }));
+        let call_path =
+            CallPath { top_level_call, inner_call_indices: inner_call_indices.to_vec() };
+        let flags = SimulationFlags::default();
+        trace_call(tx, &mut state, &block_context, flags, &call_path).unwrap()
+    };
+
+    let execute_call_info = trace(TopLevelCall::Execute, &[]).unwrap();
+    assert_eq!(execute_call_info.call.storage_address, sender_address);
+    assert_eq!(execute_call_info.inner_calls.len(), 1);
+    let inner_call_info = trace(TopLevelCall::Execute, &[0]).unwrap();
+    assert_eq!(inner_call_info.call.storage_address, test_contract_address);
+    assert_eq!(inner_call_info, execute_call_info.inner_calls.into_iter().next().unwrap());
+    let fee_transfer_call_info = trace(TopLevelCall::FeeTransfer, &[]).unwrap();
+    assert_eq!(fee_transfer_call_info.call.caller_address, sender_address);
+
+    // Calls that were not made.
+    assert!(trace(TopLevelCall::Execute, &[1]).is_none());
+    assert!(trace(TopLevelCall::Execute, &[0, 0]).is_none());
+    assert_eq!(state.get_nonce_at(sender_address).unwrap(), Nonce::default());
+}
+
+#[rstest]
+fn test_execution_result_cache(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let mut state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let invoke_tx = || {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address: account_contract.get_instance_address(0),
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+        }))
+    };
+    let tx_hash = invoke_tx().tx_hash();
+    let (state_version, other_state_version) = (stark_felt!("0x1"), stark_felt!("0x2"));
+    let flags = SimulationFlags::default();
+    let cache = ExecutionResultCache::default();
+
+    let mut simulate_cached = |state_version| {
+        cache.simulate(invoke_tx(), state_version, &mut state, &block_context, flags).unwrap()
+    };
+    let receipt = simulate_cached(state_version);
+    // Repeated requests are served from the cache.
+    assert!(Arc::ptr_eq(&simulate_cached(state_version), &receipt));
+    let other_receipt = simulate_cached(other_state_version);
+    assert!(!Arc::ptr_eq(&other_receipt, &receipt));
+    assert_eq!(other_receipt.actual_fee, receipt.actual_fee);
+    assert_eq!(cache.len(), 2);
+    let other_flags = SimulationFlags { skip_validate: true, ..flags };
+    assert!(cache.get(tx_hash, state_version, other_flags).is_none());
+
+    cache.invalidate_state_version(state_version);
+    assert!(cache.get(tx_hash, state_version, flags).is_none());
+    assert!(cache.get(tx_hash, other_state_version, flags).is_some());
+    cache.invalidate_tx(tx_hash);
+    assert!(cache.is_empty());
+}
@@ -1,3 +1,12 @@
+pub mod audit_log;
 pub mod block;
+pub mod block_builder;
+pub mod block_replay;
+pub mod bloom_filter;
 pub mod bouncer;
+pub mod intake;
+pub mod preflight;
+pub mod revert_metrics;
+pub mod stateful_validator;
+pub mod telemetry;
 pub mod transaction_executor;
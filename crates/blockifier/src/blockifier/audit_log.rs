@@ -0,0 +1,118 @@
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{EventContent, Fee};
+
+use crate::state::cached_state::StateChanges;
+use crate::transaction::objects::TransactionExecutionInfo;
+
+#[cfg(test)]
+#[path = "audit_log_test.rs"]
+pub mod test;
+
+/// Enables the audit log of the transaction executor (see `TransactionExecutor::audit_records`),
+/// when inserted into the block context extensions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RecordAuditLog;
+
+/// An auditable effect of a committed transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuditEntry {
+    Event { from_address: ContractAddress, content: EventContent },
+    FeeTransfer { payer: ContractAddress, fee_token_address: ContractAddress, amount: Fee },
+    StorageWrite { contract_address: ContractAddress, key: StorageKey, value: StarkFelt },
+    NonceUpdate { contract_address: ContractAddress, nonce: Nonce },
+    ClassHashUpdate { contract_address: ContractAddress, class_hash: ClassHash },
+    CompiledClassHashUpdate { class_hash: ClassHash, compiled_class_hash: CompiledClassHash },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditRecord {
+    /// The position of the record in the block's log, starting from zero.
+    pub sequence_number: u64,
+    /// The index of the transaction in the block, among the committed transactions.
+    pub tx_index: usize,
+    pub entry: AuditEntry,
+}
+
+/// Returns the audit entries of a transaction, in canonical order: its events in emission order,
+/// its fee transfer, then its state mutations, grouped by kind and sorted by key.
+pub fn tx_audit_entries(
+    tx_execution_info: &TransactionExecutionInfo,
+    state_changes: &StateChanges,
+) -> Vec<AuditEntry> {
+    let mut entries: Vec<AuditEntry> = tx_execution_info
+        .events()
+        .map(|(from_address, content)| AuditEntry::Event { from_address, content: content.clone() })
+        .collect();
+
+    if let Some(fee_transfer_call_info) = &tx_execution_info.fee_transfer_call_info {
+        entries.push(AuditEntry::FeeTransfer {
+            payer: fee_transfer_call_info.call.caller_address,
+            fee_token_address: fee_transfer_call_info.call.storage_address,
+            amount: tx_execution_info.actual_fee,
+        });
+    }
+
+    let mut storage_writes: Vec<_> = state_changes.storage_updates.iter().collect();
+    storage_writes.sort();
+    for (&(contract_address, key), &value) in storage_writes {
+        entries.push(AuditEntry::StorageWrite { contract_address, key, value });
+    }
+    let mut nonce_updates: Vec<_> = state_changes.nonce_updates.iter().collect();
+    nonce_updates.sort();
+    for (&contract_address, &nonce) in nonce_updates {
+        entries.push(AuditEntry::NonceUpdate { contract_address, nonce });
+    }
+    let mut class_hash_updates: Vec<_> = state_changes.class_hash_updates.iter().collect();
+    class_hash_updates.sort();
+    for (&contract_address, &class_hash) in class_hash_updates {
+        entries.push(AuditEntry::ClassHashUpdate { contract_address, class_hash });
+    }
+    let mut compiled_class_hash_updates: Vec<_> =
+        state_changes.compiled_class_hash_updates.iter().collect();
+    compiled_class_hash_updates.sort();
+    for (&class_hash, &compiled_class_hash) in compiled_class_hash_updates {
+        entries.push(AuditEntry::CompiledClassHashUpdate { class_hash, compiled_class_hash });
+    }
+
+    entries
+}
+
+/// An append-only log of the effects of the committed transactions of a block.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AuditLog {
+    records: Vec<AuditRecord>,
+}
+
+impl AuditLog {
+    /// Appends the entries of the given transaction, assigning them consecutive sequence numbers.
+    pub fn append_tx(&mut self, tx_index: usize, entries: Vec<AuditEntry>) {
+        for entry in entries {
+            let sequence_number =
+                u64::try_from(self.records.len()).expect("usize should fit in u64.");
+            self.records.push(AuditRecord { sequence_number, tx_index, entry });
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, AuditRecord> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a AuditLog {
+    type Item = &'a AuditRecord;
+    type IntoIter = std::slice::Iter<'a, AuditRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{EventContent, EventKey, Fee};
+use starknet_api::{contract_address, patricia_key, stark_felt};
+
+use crate::blockifier::audit_log::{tx_audit_entries, AuditEntry, AuditLog};
+use crate::execution::call_info::{CallExecution, CallInfo, OrderedEvent};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::state::cached_state::StateChanges;
+use crate::transaction::objects::TransactionExecutionInfo;
+
+fn event(order: usize, key: &str) -> OrderedEvent {
+    OrderedEvent {
+        order,
+        event: EventContent { keys: vec![EventKey(stark_felt!(key))], ..Default::default() },
+    }
+}
+
+#[test]
+fn test_tx_audit_entries_order() {
+    let (account_address, contract_address, fee_token_address) =
+        (contract_address!("0x100"), contract_address!("0x200"), contract_address!("0x300"));
+    // The inner call emits its event between the events of its caller.
+    let inner_call_info = CallInfo {
+        call: CallEntryPoint { storage_address: contract_address, ..Default::default() },
+        execution: CallExecution { events: vec![event(1, "0x2")], ..Default::default() },
+        ..Default::default()
+    };
+    let tx_execution_info = TransactionExecutionInfo {
+        execute_call_info: Some(CallInfo {
+            call: CallEntryPoint { storage_address: account_address, ..Default::default() },
+            execution: CallExecution {
+                events: vec![event(0, "0x1"), event(2, "0x3")],
+                ..Default::default()
+            },
+            inner_calls: vec![inner_call_info],
+            ..Default::default()
+        }),
+        fee_transfer_call_info: Some(CallInfo {
+            call: CallEntryPoint {
+                storage_address: fee_token_address,
+                caller_address: account_address,
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+        actual_fee: Fee(7),
+        ..Default::default()
+    };
+    let (key0, key1) = (StorageKey(patricia_key!("0x5")), StorageKey(patricia_key!("0x6")));
+    let state_changes = StateChanges {
+        storage_updates: HashMap::from([
+            ((contract_address, key1), stark_felt!("0xb")),
+            ((contract_address, key0), stark_felt!("0xa")),
+        ]),
+        nonce_updates: HashMap::from([(account_address, Nonce(stark_felt!(1_u8)))]),
+        ..Default::default()
+    };
+
+    let event_entry = |from_address, order, key| AuditEntry::Event {
+        from_address,
+        content: event(order, key).event,
+    };
+    assert_eq!(
+        tx_audit_entries(&tx_execution_info, &state_changes),
+        vec![
+            event_entry(account_address, 0, "0x1"),
+            event_entry(contract_address, 1, "0x2"),
+            event_entry(account_address, 2, "0x3"),
+            AuditEntry::FeeTransfer { payer: account_address, fee_token_address, amount: Fee(7) },
+            AuditEntry::StorageWrite { contract_address, key: key0, value: stark_felt!("0xa") },
+            AuditEntry::StorageWrite { contract_address, key: key1, value: stark_felt!("0xb") },
+            AuditEntry::NonceUpdate {
+                contract_address: account_address,
+                nonce: Nonce(stark_felt!(1_u8)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_audit_log_sequence_numbers() {
+    let entry = |nonce: u8| AuditEntry::NonceUpdate {
+        contract_address: contract_address!("0x100"),
+        nonce: Nonce(stark_felt!(nonce)),
+    };
+    let mut audit_log = AuditLog::default();
+    audit_log.append_tx(0, vec![entry(1), entry(2)]);
+    audit_log.append_tx(1, vec![]);
+    audit_log.append_tx(2, vec![entry(3)]);
+
+    let records: Vec<_> = audit_log
+        .iter()
+        .map(|record| (record.sequence_number, record.tx_index, record.entry.clone()))
+        .collect();
+    assert_eq!(records, vec![(0, 0, entry(1)), (1, 0, entry(2)), (2, 2, entry(3))]);
+}
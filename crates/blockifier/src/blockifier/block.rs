@@ -1,15 +1,22 @@
+use std::collections::HashSet;
 use std::num::NonZeroU128;
+use std::sync::Arc;
 
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 use starknet_api::block::{BlockHash, BlockNumber, BlockTimestamp};
 use starknet_api::core::ContractAddress;
 use starknet_api::hash::StarkFelt;
 use starknet_api::state::StorageKey;
+use starknet_api::transaction::Fee;
+use thiserror::Error;
 
 use crate::abi::constants;
-use crate::context::{BlockContext, ChainInfo};
+use crate::context::{BlockContext, ChainInfo, ExecutionExtensions};
+use crate::execution::execution_utils::stark_felt_to_felt;
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateResult};
-use crate::transaction::objects::FeeType;
+use crate::transaction::objects::{FeeType, TransactionExecutionResult};
 use crate::versioned_constants::VersionedConstants;
 
 #[cfg(test)]
@@ -25,6 +32,82 @@ pub struct BlockInfo {
     pub sequencer_address: ContractAddress,
     pub gas_prices: GasPrices,
     pub use_kzg_da: bool,
+    /// The recipients between which the fees charged in the block are split; [None] if the
+    /// sequencer receives them all. Fees are charged to a fee accumulator, and split at the end of
+    /// the block (see `ConsolidateFees`).
+    pub fee_recipients: Option<FeeRecipients>,
+}
+
+/// A recipient of a share of the fees charged in a block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeeRecipient {
+    pub address: ContractAddress,
+    /// The share of the recipient, relative to those of the other recipients.
+    pub share: NonZeroU128,
+}
+
+/// The recipients of the fees charged in a block; e.g., the sequencer and a protocol treasury.
+/// Each fee is split between them in proportion to their shares, rounding down; the remainder
+/// goes to the first recipient.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeRecipients {
+    recipients: Vec<FeeRecipient>,
+}
+
+impl FeeRecipients {
+    pub fn new(recipients: Vec<FeeRecipient>) -> Result<Self, FeeRecipientsError> {
+        if recipients.is_empty() {
+            return Err(FeeRecipientsError::NoRecipients);
+        }
+        let mut addresses = HashSet::new();
+        if let Some(recipient) =
+            recipients.iter().find(|recipient| !addresses.insert(recipient.address))
+        {
+            return Err(FeeRecipientsError::DuplicateRecipient { address: recipient.address });
+        }
+        recipients
+            .iter()
+            .try_fold(0_u128, |total, recipient| total.checked_add(recipient.share.get()))
+            .ok_or(FeeRecipientsError::SharesOverflow)?;
+
+        Ok(Self { recipients })
+    }
+
+    pub fn recipients(&self) -> &[FeeRecipient] {
+        &self.recipients
+    }
+
+    /// Splits the given fee between the recipients, in their order.
+    pub fn split(&self, fee: Fee) -> Vec<(ContractAddress, Fee)> {
+        let total_shares: u128 =
+            self.recipients.iter().map(|recipient| recipient.share.get()).sum();
+        let mut amounts: Vec<_> = self
+            .recipients
+            .iter()
+            .map(|recipient| {
+                // Computed in big integers, as the product may overflow.
+                let amount = BigUint::from(fee.0) * recipient.share.get() / total_shares;
+                let amount = amount.to_u128().expect("A share of a fee should fit in u128.");
+                (recipient.address, Fee(amount))
+            })
+            .collect();
+        let distributed: u128 = amounts.iter().map(|(_, amount)| amount.0).sum();
+        if let Some((_, first_amount)) = amounts.first_mut() {
+            first_amount.0 += fee.0 - distributed;
+        }
+
+        amounts
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FeeRecipientsError {
+    #[error("Fee recipient {address:?} is listed more than once.")]
+    DuplicateRecipient { address: ContractAddress },
+    #[error("At least one fee recipient is required.")]
+    NoRecipients,
+    #[error("The total share of the fee recipients overflows.")]
+    SharesOverflow,
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +132,102 @@ impl GasPrices {
             FeeType::Eth => self.eth_l1_data_gas_price,
         }
     }
+
+    /// Checks the gas prices against the given sanity bounds, and applies the bounds' policy to
+    /// the prices out of them.
+    pub fn bounded(self, bounds: &GasPricesBounds) -> Result<Self, GasPriceOutOfBoundsError> {
+        let bound = |price_name, price, price_bounds: Option<GasPriceBounds>| match price_bounds {
+            Some(price_bounds) => price_bounds.apply(price_name, price, bounds.policy),
+            None => Ok(price),
+        };
+        Ok(Self {
+            eth_l1_gas_price: bound(
+                "eth_l1_gas_price",
+                self.eth_l1_gas_price,
+                bounds.eth_l1_gas_price,
+            )?,
+            strk_l1_gas_price: bound(
+                "strk_l1_gas_price",
+                self.strk_l1_gas_price,
+                bounds.strk_l1_gas_price,
+            )?,
+            eth_l1_data_gas_price: bound(
+                "eth_l1_data_gas_price",
+                self.eth_l1_data_gas_price,
+                bounds.eth_l1_data_gas_price,
+            )?,
+            strk_l1_data_gas_price: bound(
+                "strk_l1_data_gas_price",
+                self.strk_l1_data_gas_price,
+                bounds.strk_l1_data_gas_price,
+            )?,
+        })
+    }
+}
+
+/// Sanity bounds on a gas price, in the unit of its fee token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GasPriceBounds {
+    pub min: NonZeroU128,
+    pub max: NonZeroU128,
+}
+
+impl GasPriceBounds {
+    fn apply(
+        &self,
+        price_name: &'static str,
+        price: NonZeroU128,
+        policy: GasPriceBoundsPolicy,
+    ) -> Result<NonZeroU128, GasPriceOutOfBoundsError> {
+        let Self { min, max } = *self;
+        if min <= price && price <= max {
+            return Ok(price);
+        }
+
+        match policy {
+            GasPriceBoundsPolicy::Reject => {
+                Err(GasPriceOutOfBoundsError { price_name, price, min, max })
+            }
+            GasPriceBoundsPolicy::Clamp => {
+                let clamped_price = if price < min { min } else { max };
+                log::warn!(
+                    "Gas price {price_name} {price} is out of its bounds [{min}, {max}]; clamped \
+                     to {clamped_price}."
+                );
+                Ok(clamped_price)
+            }
+        }
+    }
+}
+
+/// The policy for gas prices out of their sanity bounds; e.g., due to an oracle glitch.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GasPriceBoundsPolicy {
+    /// Rejects the gas prices.
+    #[default]
+    Reject,
+    /// Clamps the price into its bounds, with a warning.
+    Clamp,
+}
+
+/// Sanity bounds on the gas prices of a block, protecting the fee computation from glitches in
+/// the price sources. Unbounded prices are not checked.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GasPricesBounds {
+    pub eth_l1_gas_price: Option<GasPriceBounds>,
+    pub strk_l1_gas_price: Option<GasPriceBounds>,
+    pub eth_l1_data_gas_price: Option<GasPriceBounds>,
+    pub strk_l1_data_gas_price: Option<GasPriceBounds>,
+    pub policy: GasPriceBoundsPolicy,
+}
+
+#[derive(Debug, Error)]
+#[error("Gas price {price_name} {price} is out of its bounds [{min}, {max}].")]
+pub struct GasPriceOutOfBoundsError {
+    pub price_name: &'static str,
+    pub price: NonZeroU128,
+    pub min: NonZeroU128,
+    pub max: NonZeroU128,
 }
 
 // Block pre-processing.
@@ -65,24 +244,121 @@ pub fn pre_process_block(
 ) -> StateResult<BlockContext> {
     let should_block_hash_be_provided =
         block_info.block_number >= BlockNumber(constants::STORED_BLOCK_HASH_BUFFER);
-    if let Some(BlockNumberHashPair { number: block_number, hash: block_hash }) =
-        old_block_number_and_hash
-    {
-        let block_hash_contract_address =
-            ContractAddress::from(constants::BLOCK_HASH_CONTRACT_ADDRESS);
-        let block_number_as_storage_key = StorageKey::from(block_number.0);
-        state.set_storage_at(
-            block_hash_contract_address,
-            block_number_as_storage_key,
-            block_hash.0,
+    if let Some(old_block_number_and_hash) = old_block_number_and_hash {
+        store_block_hash(
+            state,
+            BlockHashHistory::default().contract_address,
+            &old_block_number_and_hash,
         )?;
     } else if should_block_hash_be_provided {
         return Err(StateError::OldBlockHashNotProvided);
     }
 
-    Ok(BlockContext { block_info, chain_info, versioned_constants })
+    Ok(BlockContext {
+        block_info,
+        chain_info,
+        versioned_constants: Arc::new(versioned_constants),
+        extensions: ExecutionExtensions::default(),
+    })
+}
+
+/// The system contract holding the block hash history, from which the `get_block_hash` syscall
+/// reads. Defaults to the Starknet OS contract; custom setups (e.g., devnets) may override it by
+/// inserting this struct into the block context extensions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BlockHashHistory {
+    pub contract_address: ContractAddress,
+}
+
+impl Default for BlockHashHistory {
+    fn default() -> Self {
+        Self { contract_address: ContractAddress::from(constants::BLOCK_HASH_CONTRACT_ADDRESS) }
+    }
+}
+
+impl BlockHashHistory {
+    pub fn from_extensions(extensions: &ExecutionExtensions) -> Self {
+        extensions.get::<Self>().map(|history| *history).unwrap_or_default()
+    }
+
+    /// Returns the number of the block whose hash is stored in the given storage cell, if the cell
+    /// belongs to the history; lets state readers serve the history from their block headers.
+    pub fn block_number_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> Option<BlockNumber> {
+        if contract_address != self.contract_address {
+            return None;
+        }
+        stark_felt_to_felt(*key.0.key()).to_u64().map(BlockNumber)
+    }
+}
+
+/// Writes the given block hash under its block number in the given block hash history contract.
+pub fn store_block_hash(
+    state: &mut dyn State,
+    block_hash_contract_address: ContractAddress,
+    block_number_and_hash: &BlockNumberHashPair,
+) -> StateResult<()> {
+    let BlockNumberHashPair { number: block_number, hash: block_hash } = block_number_and_hash;
+    let block_number_as_storage_key = StorageKey::from(block_number.0);
+    state.set_storage_at(block_hash_contract_address, block_number_as_storage_key, block_hash.0)
+}
+
+/// A system operation applied to the block state at finalization, after all transactions were
+/// executed.
+pub trait EndOfBlockHook {
+    fn apply(
+        &self,
+        state: &mut dyn State,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<()>;
+}
+
+/// Stores the given block hash in the configured block hash history contract, to be read by the
+/// `get_block_hash` syscall in later blocks.
+pub struct StoreBlockHash(pub BlockNumberHashPair);
+
+impl EndOfBlockHook for StoreBlockHash {
+    fn apply(
+        &self,
+        state: &mut dyn State,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<()> {
+        let history = BlockHashHistory::from_extensions(block_context.extensions());
+        Ok(store_block_hash(state, history.contract_address, &self.0)?)
+    }
+}
+
+/// Maintains the block hash history as the Starknet OS does: given the current block number N,
+/// stores the hash of block N - STORED_BLOCK_HASH_BUFFER, which must be provided once N is large
+/// enough.
+pub struct MaintainBlockHashHistory {
+    pub old_block_number_and_hash: Option<BlockNumberHashPair>,
+}
+
+impl EndOfBlockHook for MaintainBlockHashHistory {
+    fn apply(
+        &self,
+        state: &mut dyn State,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<()> {
+        let should_block_hash_be_provided = block_context.block_info.block_number
+            >= BlockNumber(constants::STORED_BLOCK_HASH_BUFFER);
+        match self.old_block_number_and_hash {
+            Some(old_block_number_and_hash) => {
+                StoreBlockHash(old_block_number_and_hash).apply(state, block_context)
+            }
+            None if should_block_hash_be_provided => {
+                Err(StateError::OldBlockHashNotProvided.into())
+            }
+            None => Ok(()),
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct BlockNumberHashPair {
     pub number: BlockNumber,
     pub hash: BlockHash,
@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use crate::blockifier::bouncer::BouncerInfo;
+use crate::blockifier::transaction_executor::{TransactionExecutor, TransactionExecutorResult};
+use crate::bouncer::Bouncer;
+use crate::state::state_api::StateReader;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::transaction_execution::Transaction;
+
+#[cfg(test)]
+#[path = "block_builder_test.rs"]
+pub mod test;
+
+/// The newest sample is weighted `1 / SMOOTHING_FACTOR` in the moving estimates.
+const SMOOTHING_FACTOR: u128 = 8;
+const PICOS_PER_NANO: u128 = 1000;
+
+/// A moving estimate of the wall-clock execution time per VM step, and of the VM steps per
+/// transaction. Should outlive a single block, so that the first transactions of a block are
+/// estimated as well.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StepTimeEstimator {
+    // In picoseconds, as a step typically takes less than a nanosecond.
+    picos_per_step: Option<u128>,
+    steps_per_tx: Option<u128>,
+}
+
+impl StepTimeEstimator {
+    /// Updates the estimates with an executed transaction.
+    pub fn record(&mut self, n_steps: usize, elapsed: Duration) {
+        let n_steps = u128::try_from(n_steps).expect("usize should fit in u128.");
+        self.steps_per_tx = Some(moving_average(self.steps_per_tx, n_steps));
+        if n_steps > 0 {
+            let picos_per_step = elapsed.as_nanos().saturating_mul(PICOS_PER_NANO) / n_steps;
+            self.picos_per_step = Some(moving_average(self.picos_per_step, picos_per_step));
+        }
+    }
+
+    /// The estimated number of VM steps of the next transaction; zero before any transaction was
+    /// recorded.
+    pub fn estimated_tx_steps(&self) -> usize {
+        self.steps_per_tx.map_or(0, |steps| usize::try_from(steps).unwrap_or(usize::MAX))
+    }
+
+    /// The estimated execution time of the next transaction; zero before any transaction was
+    /// recorded.
+    pub fn estimated_tx_duration(&self) -> Duration {
+        let (Some(picos_per_step), Some(steps_per_tx)) = (self.picos_per_step, self.steps_per_tx)
+        else {
+            return Duration::ZERO;
+        };
+        let nanos = picos_per_step.saturating_mul(steps_per_tx) / PICOS_PER_NANO;
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+}
+
+fn moving_average(current: Option<u128>, sample: u128) -> u128 {
+    match current {
+        None => sample,
+        Some(current) => {
+            current.saturating_mul(SMOOTHING_FACTOR - 1).saturating_add(sample) / SMOOTHING_FACTOR
+        }
+    }
+}
+
+/// The limits under which a block is built, on top of the block capacity of the bouncer.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockBuildingLimits {
+    /// The wall-clock time by which the block should be closed.
+    pub deadline: Instant,
+    pub max_n_txs: usize,
+}
+
+/// The reason the block builder stopped pulling transactions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockClosureReason {
+    /// The next transaction was estimated to end after the deadline.
+    Deadline,
+    /// The next transaction was estimated, or found, not to fit into the block.
+    Capacity,
+    /// No more transactions were available.
+    Exhausted,
+}
+
+/// The outputs of building a block; the block itself is summarized by `finalize_block`.
+#[derive(Debug)]
+pub struct BlockBuildingOutcome {
+    pub closure_reason: BlockClosureReason,
+    /// The execution results, in pulling order; only successfully executed transactions are
+    /// committed.
+    pub results: Vec<TransactionExecutorResult<(TransactionExecutionInfo, BouncerInfo)>>,
+    pub n_steps: usize,
+    /// The pulled transaction that was found not to fit into the block; it was not committed, and
+    /// should be included in the next block.
+    pub deferred_tx: Option<Transaction>,
+}
+
+impl<S: StateReader> TransactionExecutor<S> {
+    /// Pulls transactions and commits them into the block, until the deadline or the block
+    /// capacity is estimated to be reached by the next transaction, the block capacity of the
+    /// given bouncer is exceeded, or the transactions are exhausted. The bouncer accounts the
+    /// committed transactions. Transactions that were not pulled remain in the given iterator; a
+    /// pulled transaction exceeding the block capacity is returned as deferred.
+    pub fn build_block(
+        &mut self,
+        txs: &mut impl Iterator<Item = Transaction>,
+        limits: &BlockBuildingLimits,
+        bouncer: &mut Bouncer,
+        estimator: &mut StepTimeEstimator,
+        charge_fee: bool,
+    ) -> BlockBuildingOutcome {
+        let mut results = Vec::new();
+        let mut n_steps: usize = 0;
+        let mut deferred_tx = None;
+        let closure_reason = loop {
+            if Instant::now() + estimator.estimated_tx_duration() >= limits.deadline {
+                break BlockClosureReason::Deadline;
+            }
+            if self.n_committed_txs >= limits.max_n_txs
+                || estimator.estimated_tx_steps() > bouncer.remaining_capacity().n_steps()
+            {
+                break BlockClosureReason::Capacity;
+            }
+            let Some(tx) = txs.next() else {
+                break BlockClosureReason::Exhausted;
+            };
+
+            let start = Instant::now();
+            let result = self.execute(tx.clone(), charge_fee);
+            if let Ok((_, bouncer_info)) = &result {
+                let tx_n_steps = bouncer_info.execution_resources.n_steps;
+                estimator.record(tx_n_steps, start.elapsed());
+                if bouncer.try_update(bouncer_info).is_err() {
+                    self.abort();
+                    deferred_tx = Some(tx);
+                    break BlockClosureReason::Capacity;
+                }
+                n_steps += tx_n_steps;
+                self.commit();
+            }
+            results.push(result);
+        };
+
+        self.closure_reason = Some(closure_reason);
+        BlockBuildingOutcome { closure_reason, results, n_steps, deferred_tx }
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::transaction::Fee;
+
+use crate::blockifier::block_builder::{
+    BlockBuildingLimits, BlockClosureReason, StepTimeEstimator,
+};
+use crate::blockifier::transaction_executor::TransactionExecutor;
+use crate::bouncer::{Bouncer, BouncerWeights};
+use crate::context::BlockContext;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{CairoVersion, BALANCE};
+use crate::transaction::test_utils::block_context;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::L1HandlerTransaction;
+
+#[test]
+fn test_step_time_estimator() {
+    let mut estimator = StepTimeEstimator::default();
+    assert_eq!(estimator.estimated_tx_duration(), Duration::ZERO);
+    assert_eq!(estimator.estimated_tx_steps(), 0);
+
+    estimator.record(1000, Duration::from_micros(8));
+    assert_eq!(estimator.estimated_tx_steps(), 1000);
+    assert_eq!(estimator.estimated_tx_duration(), Duration::from_micros(8));
+
+    // The newest sample is weighted 1/8: (7 * 1000 + 9000) / 8 = 2000 steps, and
+    // (7 * 8000 + 8000) / 8 = 8000 picoseconds per step.
+    estimator.record(9000, Duration::from_micros(72));
+    assert_eq!(estimator.estimated_tx_steps(), 2000);
+    assert_eq!(estimator.estimated_tx_duration(), Duration::from_micros(16));
+}
+
+#[rstest]
+#[case::deadline(Duration::ZERO, usize::MAX, BlockClosureReason::Deadline, 0)]
+#[case::capacity(Duration::from_secs(3600), 2, BlockClosureReason::Capacity, 2)]
+#[case::exhausted(Duration::from_secs(3600), usize::MAX, BlockClosureReason::Exhausted, 3)]
+fn test_build_block(
+    block_context: BlockContext,
+    #[case] time_to_deadline: Duration,
+    #[case] max_n_txs: usize,
+    #[case] expected_closure_reason: BlockClosureReason,
+    #[case] expected_n_committed_txs: usize,
+) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let state = test_state(&block_context.chain_info, BALANCE, &[(test_contract, 1)]);
+    let mut bouncer = Bouncer::from_config(&block_context.versioned_constants.bouncer_config);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let mut txs = l1_handler_txs(test_contract, 3);
+    let limits = BlockBuildingLimits { deadline: Instant::now() + time_to_deadline, max_n_txs };
+    let mut estimator = StepTimeEstimator::default();
+
+    let outcome = tx_executor.build_block(&mut txs, &limits, &mut bouncer, &mut estimator, true);
+    assert_eq!(outcome.closure_reason, expected_closure_reason);
+    assert_eq!(outcome.results.len(), expected_n_committed_txs);
+    assert!(outcome.results.iter().all(|result| result.is_ok()));
+    assert!(outcome.deferred_tx.is_none());
+    assert_eq!(txs.count(), 3 - expected_n_committed_txs);
+    if expected_n_committed_txs > 0 {
+        assert!(outcome.n_steps > 0);
+        assert_eq!(bouncer.accumulated_weights().n_steps(), outcome.n_steps);
+        assert!(estimator.estimated_tx_steps() > 0);
+    }
+
+    let summary = tx_executor.finalize_block(false, &[]).unwrap();
+    assert_eq!(summary.n_committed_txs, expected_n_committed_txs);
+    assert_eq!(summary.closure_reason, Some(expected_closure_reason));
+}
+
+#[rstest]
+fn test_build_block_defers_tx_exceeding_capacity(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let state = test_state(&block_context.chain_info, BALANCE, &[(test_contract, 1)]);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let mut txs = l1_handler_txs(test_contract, 3);
+    let limits =
+        BlockBuildingLimits { deadline: Instant::now() + Duration::from_secs(3600), max_n_txs: 3 };
+    // No transaction fits into a block of zero capacity; with no estimate yet, the first one is
+    // executed, and found not to fit.
+    let mut bouncer = Bouncer::new(BouncerWeights::default());
+    let mut estimator = StepTimeEstimator::default();
+
+    let outcome = tx_executor.build_block(&mut txs, &limits, &mut bouncer, &mut estimator, true);
+    assert_eq!(outcome.closure_reason, BlockClosureReason::Capacity);
+    assert!(outcome.results.is_empty());
+    assert_matches!(outcome.deferred_tx, Some(Transaction::L1HandlerTransaction(_)));
+    assert_eq!(txs.count(), 2);
+    assert_eq!(bouncer.accumulated_weights(), BouncerWeights::default());
+
+    let summary = tx_executor.finalize_block(false, &[]).unwrap();
+    assert_eq!(summary.n_committed_txs, 0);
+}
+
+fn l1_handler_txs(
+    test_contract: FeatureContract,
+    n_txs: usize,
+) -> impl Iterator<Item = Transaction> {
+    (0..n_txs).map(move |_| {
+        Transaction::L1HandlerTransaction(L1HandlerTransaction::create_for_testing(
+            Fee(1908000000000000),
+            test_contract.get_instance_address(0),
+        ))
+    })
+}
@@ -0,0 +1,268 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ContractAddress;
+use starknet_api::transaction::{EventContent, Fee, TransactionHash};
+
+use crate::blockifier::transaction_executor::{
+    BlockSummary, TransactionExecutor, TransactionExecutorResult,
+};
+use crate::context::BlockContext;
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::StateReader;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::transaction_execution::Transaction;
+use crate::versioned_constants::VersionedConstants;
+
+#[cfg(test)]
+#[path = "block_replay_test.rs"]
+pub mod test;
+
+/// An event emitted by a transaction, as in the Starknet JSON-RPC `EVENT`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplayedEvent {
+    pub from_address: ContractAddress,
+    #[serde(flatten)]
+    pub content: EventContent,
+}
+
+/// The comparable outputs of an executed transaction; also the form of the expected receipts.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplayedReceipt {
+    pub tx_hash: TransactionHash,
+    pub actual_fee: Fee,
+    /// The emitted events, in emission order.
+    pub events: Vec<ReplayedEvent>,
+    /// The revert error; [None] if the transaction succeeded.
+    pub revert_error: Option<String>,
+}
+
+impl ReplayedReceipt {
+    pub fn new(tx_hash: TransactionHash, tx_execution_info: &TransactionExecutionInfo) -> Self {
+        Self {
+            tx_hash,
+            actual_fee: tx_execution_info.actual_fee,
+            events: tx_execution_info
+                .events()
+                .map(|(from_address, content)| ReplayedEvent {
+                    from_address,
+                    content: content.clone(),
+                })
+                .collect(),
+            revert_error: tx_execution_info.revert_error.clone(),
+        }
+    }
+}
+
+/// The outputs of an executed block.
+#[derive(Debug)]
+pub struct BlockExecutionSummary {
+    /// The execution results, in block order; only successfully executed transactions are
+    /// committed.
+    pub results: Vec<TransactionExecutorResult<ReplayedReceipt>>,
+    /// The summary of the block, including its final state diff.
+    pub block_summary: BlockSummary,
+}
+
+/// A difference between an executed transaction and its expected receipt.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReceiptMismatch {
+    /// A transaction was executed, but no receipt was expected for it.
+    UnexpectedTransaction,
+    /// A receipt was expected, but no transaction was executed for it.
+    MissingTransaction,
+    TransactionHash {
+        expected: TransactionHash,
+        actual: TransactionHash,
+    },
+    /// The transaction was rejected (and hence not included in the block).
+    Rejected {
+        error: String,
+    },
+    /// Only the revert status is compared, as revert errors are not stable across versions.
+    RevertStatus {
+        expected_reverted: bool,
+        actual_reverted: bool,
+    },
+    ActualFee {
+        expected: Fee,
+        actual: Fee,
+    },
+    Events {
+        expected: Vec<ReplayedEvent>,
+        actual: Vec<ReplayedEvent>,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptDiscrepancy {
+    /// The index of the transaction in the block.
+    pub tx_index: usize,
+    pub mismatch: ReceiptMismatch,
+}
+
+impl BlockExecutionSummary {
+    /// Compares the execution results against the expected receipts of the block (e.g., taken
+    /// from the chain history), in block order. Returns the discrepancies, ordered by transaction
+    /// index; an empty list means the block was reproduced.
+    pub fn compare(&self, expected_receipts: &[ReplayedReceipt]) -> Vec<ReceiptDiscrepancy> {
+        let mut discrepancies = Vec::new();
+        let n_txs = self.results.len().max(expected_receipts.len());
+        for tx_index in 0..n_txs {
+            let mismatches = match (self.results.get(tx_index), expected_receipts.get(tx_index)) {
+                (Some(_), None) => vec![ReceiptMismatch::UnexpectedTransaction],
+                (None, Some(_)) => vec![ReceiptMismatch::MissingTransaction],
+                (Some(Err(error)), Some(_)) => {
+                    vec![ReceiptMismatch::Rejected { error: error.to_string() }]
+                }
+                (Some(Ok(actual)), Some(expected)) => compare_receipts(expected, actual),
+                (None, None) => unreachable!("Index is within the longer of the two lists."),
+            };
+            discrepancies.extend(
+                mismatches.into_iter().map(|mismatch| ReceiptDiscrepancy { tx_index, mismatch }),
+            );
+        }
+
+        discrepancies
+    }
+}
+
+fn compare_receipts(expected: &ReplayedReceipt, actual: &ReplayedReceipt) -> Vec<ReceiptMismatch> {
+    let mut mismatches = Vec::new();
+    if expected.tx_hash != actual.tx_hash {
+        mismatches.push(ReceiptMismatch::TransactionHash {
+            expected: expected.tx_hash,
+            actual: actual.tx_hash,
+        });
+    }
+    let (expected_reverted, actual_reverted) =
+        (expected.revert_error.is_some(), actual.revert_error.is_some());
+    if expected_reverted != actual_reverted {
+        mismatches.push(ReceiptMismatch::RevertStatus { expected_reverted, actual_reverted });
+    }
+    if expected.actual_fee != actual.actual_fee {
+        mismatches.push(ReceiptMismatch::ActualFee {
+            expected: expected.actual_fee,
+            actual: actual.actual_fee,
+        });
+    }
+    if expected.events != actual.events {
+        mismatches.push(ReceiptMismatch::Events {
+            expected: expected.events.clone(),
+            actual: actual.events.clone(),
+        });
+    }
+
+    mismatches
+}
+
+/// Executes the transactions of a block (charging their fees), on top of the given state, and
+/// finalizes the block with no end-of-block system operations. Pre-block system operations (see
+/// `pre_process_block`) are expected to be reflected in the given state.
+pub fn execute_block<S: StateReader>(
+    block_context: BlockContext,
+    txs: impl IntoIterator<Item = Transaction>,
+    state_reader: S,
+) -> TransactionExecutorResult<BlockExecutionSummary> {
+    let mut tx_executor = TransactionExecutor::new(CachedState::from(state_reader), block_context);
+    let charge_fee = true;
+    let results = txs
+        .into_iter()
+        .map(|tx| {
+            let tx_hash = tx.tx_hash();
+            let (tx_execution_info, _) = tx_executor.execute(tx, charge_fee)?;
+            tx_executor.commit();
+            Ok(ReplayedReceipt::new(tx_hash, &tx_execution_info))
+        })
+        .collect();
+
+    let is_pending_block = false;
+    let block_summary = tx_executor.finalize_block(is_pending_block, &[])?;
+    Ok(BlockExecutionSummary { results, block_summary })
+}
+
+/// A recently executed block, with what is needed to re-execute it.
+pub struct BlockWitness<S: StateReader> {
+    pub block_context: BlockContext,
+    pub txs: Vec<Transaction>,
+    /// The state before the block, with its pre-block system operations applied.
+    pub state_reader: S,
+    /// The receipts of the block as executed, in block order.
+    pub receipts: Vec<ReplayedReceipt>,
+}
+
+/// The way in which a previously successful transaction fails under the candidate constants.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CompatibilityFailure {
+    /// The transaction is rejected; e.g., its validation exceeds the step limit.
+    Rejected { error: String },
+    /// The transaction is reverted; e.g., its execution exceeds the step limit.
+    Reverted { revert_error: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IncompatibleTransaction {
+    pub block_number: BlockNumber,
+    /// The index of the transaction in its block.
+    pub tx_index: usize,
+    pub tx_hash: TransactionHash,
+    pub failure: CompatibilityFailure,
+}
+
+/// The result of re-executing recent blocks under candidate versioned constants.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConstantsCompatibilityReport {
+    pub n_blocks: usize,
+    /// The number of transactions that succeeded in their blocks, and were thus checked.
+    pub n_checked_txs: usize,
+    /// The checked transactions that fail under the candidate constants, in block order.
+    pub incompatible_txs: Vec<IncompatibleTransaction>,
+}
+
+impl ConstantsCompatibilityReport {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatible_txs.is_empty()
+    }
+}
+
+/// Dry-runs an upgrade to the given candidate constants: re-executes the given blocks under them,
+/// and reports the transactions that succeeded in their blocks, but are rejected or reverted under
+/// the candidate. Differences in fees and resources are expected, and are not reported.
+pub fn validate_constants_upgrade<S: StateReader>(
+    candidate: &VersionedConstants,
+    witnesses: impl IntoIterator<Item = BlockWitness<S>>,
+) -> TransactionExecutorResult<ConstantsCompatibilityReport> {
+    let mut report = ConstantsCompatibilityReport::default();
+    let candidate = Arc::new(candidate.clone());
+    for BlockWitness { block_context, txs, state_reader, receipts } in witnesses {
+        let block_number = block_context.block_info.block_number;
+        let block_context =
+            BlockContext { versioned_constants: candidate.clone(), ..block_context };
+        let summary = execute_block(block_context, txs, state_reader)?;
+
+        report.n_blocks += 1;
+        for (tx_index, (receipt, result)) in receipts.iter().zip(&summary.results).enumerate() {
+            if receipt.revert_error.is_some() {
+                continue;
+            }
+
+            report.n_checked_txs += 1;
+            let failure = match result {
+                Ok(ReplayedReceipt { revert_error: None, .. }) => continue,
+                Ok(ReplayedReceipt { revert_error: Some(revert_error), .. }) => {
+                    CompatibilityFailure::Reverted { revert_error: revert_error.clone() }
+                }
+                Err(error) => CompatibilityFailure::Rejected { error: error.to_string() },
+            };
+            report.incompatible_txs.push(IncompatibleTransaction {
+                block_number,
+                tx_index,
+                tx_hash: receipt.tx_hash,
+                failure,
+            });
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,148 @@
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::Fee;
+
+use crate::blockifier::block_replay::{
+    execute_block, validate_constants_upgrade, BlockWitness, CompatibilityFailure,
+    ReceiptDiscrepancy, ReceiptMismatch, ReplayedReceipt,
+};
+use crate::context::BlockContext;
+use crate::invoke_tx_args;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::{create_calldata, CairoVersion};
+use crate::transaction::test_utils::{account_invoke_tx, block_context, trivial_invoke_setup};
+use crate::transaction::transaction_execution::Transaction;
+
+#[rstest]
+fn test_execute_block(
+    block_context: BlockContext,
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let (state, invoke_args) = trivial_invoke_setup(&block_context.chain_info, cairo_version);
+    let sender_address = invoke_args.sender_address;
+    let invoke_tx = |nonce: u8| {
+        let calldata = create_calldata(
+            test_contract.get_instance_address(0),
+            "test_emit_events",
+            &[
+                stark_felt!(1_u8), // events_number.
+                stark_felt!(0_u8), // keys length.
+                stark_felt!(0_u8), // data length.
+            ],
+        );
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            calldata,
+            nonce: Nonce(stark_felt!(nonce)),
+            ..invoke_args.clone()
+        }))
+    };
+
+    // The last transaction reuses a nonce, and is rejected.
+    let txs = [invoke_tx(0), invoke_tx(1), invoke_tx(1)];
+    let summary = execute_block(block_context, txs, state).unwrap();
+    assert_eq!(summary.results.len(), 3);
+    assert_eq!(summary.block_summary.n_committed_txs, 2);
+    assert!(summary.block_summary.state_diff.address_to_nonce.contains_key(&sender_address));
+
+    let mut expected_receipts: Vec<ReplayedReceipt> =
+        summary.results[..2].iter().map(|result| result.as_ref().unwrap().clone()).collect();
+    for receipt in &expected_receipts {
+        assert_eq!(receipt.events.len(), 1);
+        assert_eq!(receipt.events[0].from_address, test_contract.get_instance_address(0));
+        assert_eq!(receipt.revert_error, None);
+    }
+    assert_eq!(
+        summary.compare(&expected_receipts),
+        [ReceiptDiscrepancy { tx_index: 2, mismatch: ReceiptMismatch::UnexpectedTransaction }]
+    );
+
+    // Mismatches are reported per transaction, in block order.
+    let actual_fee = expected_receipts[1].actual_fee;
+    expected_receipts[1].actual_fee = Fee(actual_fee.0 + 1);
+    expected_receipts[1].events.clear();
+    expected_receipts.push(expected_receipts[0].clone());
+    expected_receipts.push(expected_receipts[0].clone());
+    let discrepancies = summary.compare(&expected_receipts);
+    assert_eq!(
+        discrepancies[..2],
+        [
+            ReceiptDiscrepancy {
+                tx_index: 1,
+                mismatch: ReceiptMismatch::ActualFee {
+                    expected: Fee(actual_fee.0 + 1),
+                    actual: actual_fee
+                },
+            },
+            ReceiptDiscrepancy {
+                tx_index: 1,
+                mismatch: ReceiptMismatch::Events {
+                    expected: vec![],
+                    actual: summary.results[1].as_ref().unwrap().events.clone(),
+                },
+            },
+        ]
+    );
+    assert!(matches!(
+        discrepancies[2],
+        ReceiptDiscrepancy { tx_index: 2, mismatch: ReceiptMismatch::Rejected { .. } }
+    ));
+    assert_eq!(
+        discrepancies[3],
+        ReceiptDiscrepancy { tx_index: 3, mismatch: ReceiptMismatch::MissingTransaction }
+    );
+    assert_eq!(discrepancies.len(), 4);
+}
+
+#[rstest]
+fn test_validate_constants_upgrade(block_context: BlockContext) {
+    let setup = || trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo0);
+    let new_state = || setup().0;
+    let invoke_args = setup().1;
+    let txs = || {
+        (0..2_u8)
+            .map(|nonce| {
+                Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+                    nonce: Nonce(stark_felt!(nonce)),
+                    ..invoke_args.clone()
+                }))
+            })
+            .collect::<Vec<_>>()
+    };
+    let receipts: Vec<ReplayedReceipt> = execute_block(block_context.clone(), txs(), new_state())
+        .unwrap()
+        .results
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    let witness = || BlockWitness {
+        block_context: block_context.clone(),
+        txs: txs(),
+        state_reader: new_state(),
+        receipts: receipts.clone(),
+    };
+
+    // The current constants are compatible with the blocks executed under them.
+    let report =
+        validate_constants_upgrade(&block_context.versioned_constants, [witness(), witness()])
+            .unwrap();
+    assert!(report.is_compatible());
+    assert_eq!((report.n_blocks, report.n_checked_txs), (2, 4));
+
+    // Transactions that no longer fit the step limit are reported.
+    let mut candidate = (*block_context.versioned_constants).clone();
+    candidate.invoke_tx_max_n_steps = 1;
+    let report = validate_constants_upgrade(&candidate, [witness()]).unwrap();
+    assert!(!report.is_compatible());
+    assert_eq!(report.n_checked_txs, 2);
+    let incompatible_tx_indices: Vec<_> =
+        report.incompatible_txs.iter().map(|incompatible_tx| incompatible_tx.tx_index).collect();
+    assert_eq!(incompatible_tx_indices, [0, 1]);
+    let incompatible_tx = &report.incompatible_txs[0];
+    assert_eq!(incompatible_tx.block_number, block_context.block_info.block_number);
+    assert_eq!(incompatible_tx.tx_hash, receipts[0].tx_hash);
+    assert!(matches!(incompatible_tx.failure, CompatibilityFailure::Reverted { .. }));
+}
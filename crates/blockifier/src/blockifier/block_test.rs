@@ -1,15 +1,26 @@
+use std::num::NonZeroU128;
+
+use assert_matches::assert_matches;
 use starknet_api::block::BlockNumber;
-use starknet_api::core::ContractAddress;
-use starknet_api::hash::StarkFelt;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
+use starknet_api::transaction::Fee;
+use starknet_api::{contract_address, patricia_key};
 
 use crate::abi::constants;
-use crate::blockifier::block::{pre_process_block, BlockInfo, BlockNumberHashPair};
-use crate::context::ChainInfo;
+use crate::blockifier::block::{
+    pre_process_block, BlockHashHistory, BlockInfo, BlockNumberHashPair, EndOfBlockHook,
+    FeeRecipient, FeeRecipients, FeeRecipientsError, GasPriceBounds, GasPriceBoundsPolicy,
+    GasPriceOutOfBoundsError, GasPricesBounds, MaintainBlockHashHistory,
+};
+use crate::context::{BlockContext, ChainInfo};
+use crate::state::errors::StateError;
 use crate::state::state_api::StateReader;
 use crate::test_utils::contracts::FeatureContract;
 use crate::test_utils::initial_test_state::test_state;
 use crate::test_utils::{CairoVersion, BALANCE};
+use crate::transaction::errors::TransactionExecutionError;
 use crate::versioned_constants::VersionedConstants;
 
 #[test]
@@ -70,3 +81,98 @@ fn test_pre_process_block() {
         format!("{}", error.unwrap_err())
     );
 }
+
+#[test]
+fn test_maintain_block_hash_history() {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let mut state = test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 1)]);
+    let history_contract_address = contract_address!("0x7");
+    let mut block_context = BlockContext::create_for_testing();
+    block_context.block_info.block_number = BlockNumber(constants::STORED_BLOCK_HASH_BUFFER + 5);
+    block_context
+        .extensions()
+        .insert(BlockHashHistory { contract_address: history_contract_address });
+
+    // The old block hash must be provided once the buffer is filled.
+    let hook = MaintainBlockHashHistory { old_block_number_and_hash: None };
+    assert_matches!(
+        hook.apply(&mut state, &block_context).unwrap_err(),
+        TransactionExecutionError::StateError(StateError::OldBlockHashNotProvided)
+    );
+
+    let block_hash = StarkFelt::from(20_u8);
+    let hook = MaintainBlockHashHistory {
+        old_block_number_and_hash: Some(BlockNumberHashPair::new(5, block_hash)),
+    };
+    hook.apply(&mut state, &block_context).unwrap();
+    assert_eq!(
+        state.get_storage_at(history_contract_address, StorageKey::from(5_u64)).unwrap(),
+        block_hash
+    );
+    assert_eq!(
+        state
+            .get_storage_at(
+                ContractAddress::from(constants::BLOCK_HASH_CONTRACT_ADDRESS),
+                StorageKey::from(5_u64)
+            )
+            .unwrap(),
+        StarkHash::ZERO
+    );
+}
+
+#[test]
+fn test_gas_prices_bounds() {
+    let gas_prices = BlockInfo::create_for_testing().gas_prices;
+    let price = |price: u128| NonZeroU128::new(price).unwrap();
+    let eth_l1_gas_price = gas_prices.eth_l1_gas_price.get();
+    let bounds = GasPricesBounds {
+        eth_l1_gas_price: Some(GasPriceBounds {
+            min: price(eth_l1_gas_price + 1),
+            max: price(eth_l1_gas_price + 2),
+        }),
+        ..Default::default()
+    };
+
+    // Unbounded prices are not checked.
+    let unbounded_prices = gas_prices.clone().bounded(&GasPricesBounds::default()).unwrap();
+    assert_eq!(unbounded_prices.eth_l1_gas_price, gas_prices.eth_l1_gas_price);
+
+    assert_matches!(
+        gas_prices.clone().bounded(&bounds),
+        Err(GasPriceOutOfBoundsError { price_name: "eth_l1_gas_price", .. })
+    );
+
+    let clamp_bounds = GasPricesBounds { policy: GasPriceBoundsPolicy::Clamp, ..bounds };
+    let clamped_prices = gas_prices.clone().bounded(&clamp_bounds).unwrap();
+    assert_eq!(clamped_prices.eth_l1_gas_price, price(eth_l1_gas_price + 1));
+    assert_eq!(clamped_prices.strk_l1_gas_price, gas_prices.strk_l1_gas_price);
+}
+
+#[test]
+fn test_fee_recipients() {
+    let (first, second) = (contract_address!("0x1"), contract_address!("0x2"));
+    let recipient =
+        |address, share| FeeRecipient { address, share: NonZeroU128::new(share).unwrap() };
+
+    assert_matches!(FeeRecipients::new(vec![]), Err(FeeRecipientsError::NoRecipients));
+    assert_matches!(
+        FeeRecipients::new(vec![recipient(first, 1), recipient(first, 2)]),
+        Err(FeeRecipientsError::DuplicateRecipient { address }) if address == first
+    );
+    assert_matches!(
+        FeeRecipients::new(vec![recipient(first, u128::MAX), recipient(second, 1)]),
+        Err(FeeRecipientsError::SharesOverflow)
+    );
+
+    // The remainder of the split goes to the first recipient; the product of the fee and the
+    // shares may exceed u128.
+    let fee_recipients =
+        FeeRecipients::new(vec![recipient(first, 1), recipient(second, u128::MAX - 1)]).unwrap();
+    assert_eq!(
+        fee_recipients.split(Fee(u128::MAX)),
+        vec![(first, Fee(1)), (second, Fee(u128::MAX - 1))]
+    );
+    let fee_recipients =
+        FeeRecipients::new(vec![recipient(first, 1), recipient(second, 2)]).unwrap();
+    assert_eq!(fee_recipients.split(Fee(10)), vec![(first, Fee(4)), (second, Fee(6))]);
+}
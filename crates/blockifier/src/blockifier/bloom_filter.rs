@@ -0,0 +1,88 @@
+use sha3::{Digest, Keccak256};
+use starknet_api::core::ClassHash;
+use starknet_api::hash::StarkFelt;
+
+use crate::transaction::objects::TransactionExecutionInfo;
+
+#[cfg(test)]
+#[path = "bloom_filter_test.rs"]
+pub mod test;
+
+pub const BLOOM_FILTER_N_BITS: usize = 2048;
+const BLOOM_FILTER_N_BYTES: usize = BLOOM_FILTER_N_BITS / 8;
+const N_HASH_FUNCTIONS: usize = 3;
+
+/// A 2048-bit bloom filter over felts; each felt sets 3 bits, derived from its Keccak hash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BloomFilter([u8; BLOOM_FILTER_N_BYTES]);
+
+impl Default for BloomFilter {
+    fn default() -> Self {
+        Self([0; BLOOM_FILTER_N_BYTES])
+    }
+}
+
+impl BloomFilter {
+    pub fn insert(&mut self, felt: &StarkFelt) {
+        for bit in Self::bit_indices(felt) {
+            self.0[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Returns false if the felt was definitely not inserted; true otherwise (with a small
+    /// probability of a false positive).
+    pub fn may_contain(&self, felt: &StarkFelt) -> bool {
+        Self::bit_indices(felt).into_iter().all(|bit| self.0[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    pub fn union(&mut self, other: &Self) {
+        self.0.iter_mut().zip(other.0).for_each(|(byte, other_byte)| *byte |= other_byte);
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn bit_indices(felt: &StarkFelt) -> [usize; N_HASH_FUNCTIONS] {
+        let hash = Keccak256::digest(felt.bytes());
+        std::array::from_fn(|i| {
+            usize::from(u16::from_be_bytes([hash[2 * i], hash[2 * i + 1]])) % BLOOM_FILTER_N_BITS
+        })
+    }
+}
+
+/// Per-block bloom filters, allowing light clients and indexers to skip blocks irrelevant to them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockBloomFilters {
+    /// The addresses of the contracts called in the block.
+    pub contract_addresses: BloomFilter,
+    /// The keys of the events emitted in the block.
+    pub event_keys: BloomFilter,
+    /// The hashes of the classes declared in the block.
+    pub class_hashes: BloomFilter,
+}
+
+impl BlockBloomFilters {
+    pub fn add_execution_info(&mut self, tx_execution_info: &TransactionExecutionInfo) {
+        for call_info in
+            tx_execution_info.non_optional_call_infos().flat_map(|call_info| call_info.iter())
+        {
+            self.contract_addresses.insert(call_info.call.storage_address.0.key());
+            for ordered_event in &call_info.execution.events {
+                for key in &ordered_event.event.keys {
+                    self.event_keys.insert(&key.0);
+                }
+            }
+        }
+    }
+
+    pub fn add_declared_class(&mut self, class_hash: ClassHash) {
+        self.class_hashes.insert(&class_hash.0);
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.contract_addresses.union(&other.contract_addresses);
+        self.event_keys.union(&other.event_keys);
+        self.class_hashes.union(&other.class_hashes);
+    }
+}
@@ -0,0 +1,73 @@
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{EventContent, EventKey};
+use starknet_api::{contract_address, patricia_key, stark_felt};
+
+use crate::blockifier::bloom_filter::{BlockBloomFilters, BloomFilter};
+use crate::execution::call_info::{CallExecution, CallInfo, OrderedEvent};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::transaction::objects::TransactionExecutionInfo;
+
+#[test]
+fn test_bloom_filter() {
+    let mut bloom_filter = BloomFilter::default();
+    let inserted: Vec<StarkFelt> = (0_u8..20).map(|i| stark_felt!(i)).collect();
+    for felt in &inserted {
+        bloom_filter.insert(felt);
+    }
+    assert!(inserted.iter().all(|felt| bloom_filter.may_contain(felt)));
+    // With 60 bits set out of 2048, false positives are rare.
+    let n_false_positives =
+        (100_u8..200).filter(|i| bloom_filter.may_contain(&stark_felt!(*i))).count();
+    assert!(n_false_positives < 5);
+
+    let mut other_bloom_filter = BloomFilter::default();
+    other_bloom_filter.insert(&stark_felt!("0x1234"));
+    bloom_filter.union(&other_bloom_filter);
+    assert!(bloom_filter.may_contain(&stark_felt!("0x1234")));
+    assert!(inserted.iter().all(|felt| bloom_filter.may_contain(felt)));
+}
+
+#[test]
+fn test_block_bloom_filters() {
+    let inner_call_info = CallInfo {
+        call: CallEntryPoint { storage_address: contract_address!("0x200"), ..Default::default() },
+        execution: CallExecution {
+            events: vec![OrderedEvent {
+                order: 0,
+                event: EventContent {
+                    keys: vec![EventKey(stark_felt!("0xabc"))],
+                    ..Default::default()
+                },
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let tx_execution_info = TransactionExecutionInfo {
+        execute_call_info: Some(CallInfo {
+            call: CallEntryPoint {
+                storage_address: contract_address!("0x100"),
+                ..Default::default()
+            },
+            inner_calls: vec![inner_call_info],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let mut tx_bloom_filters = BlockBloomFilters::default();
+    tx_bloom_filters.add_execution_info(&tx_execution_info);
+    tx_bloom_filters.add_declared_class(ClassHash(stark_felt!("0x300")));
+    let mut block_bloom_filters = BlockBloomFilters::default();
+    block_bloom_filters.merge(&tx_bloom_filters);
+    assert_eq!(block_bloom_filters, tx_bloom_filters);
+
+    let BlockBloomFilters { contract_addresses, event_keys, class_hashes } = block_bloom_filters;
+    assert!(contract_addresses.may_contain(&stark_felt!("0x100")));
+    assert!(contract_addresses.may_contain(&stark_felt!("0x200")));
+    assert!(event_keys.may_contain(&stark_felt!("0xabc")));
+    assert!(class_hashes.may_contain(&stark_felt!("0x300")));
+    assert!(!class_hashes.may_contain(&stark_felt!("0x100")));
+}
@@ -4,6 +4,7 @@ use cairo_vm::serde::deserialize_program::BuiltinName;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
 
 use crate::abi::constants;
+use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
 use crate::transaction::objects::{GasVector, ResourcesMapping, TransactionExecutionResult};
 use crate::utils::usize_from_u128;
 
@@ -14,6 +15,9 @@ pub struct BouncerInfo {
     pub message_segment_length: usize, // The number of felts needed to store L1<>L2 messages.
     pub execution_resources: VmExecutionResources,
     pub n_events: usize,
+    pub syscall_counter: SyscallCounter,
+    pub n_declares: usize,
+    pub declared_code_size: usize, // The size of the declared class, in bytes.
 }
 
 impl BouncerInfo {
@@ -24,6 +28,8 @@ impl BouncerInfo {
         message_segment_length: usize,
         state_diff_size: usize,
         n_events: usize,
+        syscall_counter: SyscallCounter,
+        declared_code_size: Option<usize>,
     ) -> TransactionExecutionResult<Self> {
         // TODO(Avi, 30/03/2024): Consider removing "l1_gas_usage" from actual resources.
         let gas_weight = usize_from_u128(tx_starknet_gas_usage.l1_gas)
@@ -73,6 +79,9 @@ impl BouncerInfo {
             message_segment_length,
             execution_resources: merged_resources,
             n_events,
+            syscall_counter,
+            n_declares: usize::from(declared_code_size.is_some()),
+            declared_code_size: declared_code_size.unwrap_or_default(),
         })
     }
 }
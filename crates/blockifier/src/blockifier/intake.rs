@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use strum_macros::EnumIter;
+use thiserror::Error;
+
+use crate::blockifier::preflight::{CalldataPreflight, PreflightError};
+use crate::blockifier::stateful_validator::StatefulValidator;
+use crate::context::BlockContext;
+use crate::execution::call_info::CallInfo;
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::StateReader;
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::{TransactionInfo, TransactionInfoCreator};
+
+#[cfg(test)]
+#[path = "intake_test.rs"]
+pub mod test;
+
+#[derive(Debug, Error)]
+pub enum TransactionIntakeError {
+    #[error("Failed to decode the transaction: {0}")]
+    DecodeError(String),
+    #[error("Calldata length {calldata_length} exceeds the maximum of {max_calldata_length}.")]
+    CalldataTooLong { calldata_length: usize, max_calldata_length: usize },
+    #[error("Signature length {signature_length} exceeds the maximum of {max_signature_length}.")]
+    SignatureTooLong { signature_length: usize, max_signature_length: usize },
+    #[error("Static check failed: {0}")]
+    StaticCheckFailed(String),
+    #[error(transparent)]
+    PreflightError(#[from] PreflightError),
+    #[error(transparent)]
+    ValidationError(#[from] TransactionExecutionError),
+}
+
+pub type TransactionIntakeResult<T> = Result<T, TransactionIntakeError>;
+
+/// The stages of the intake pipeline, in order.
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq, Serialize)]
+pub enum IntakeStage {
+    Decode,
+    StaticChecks,
+    Preflight,
+    StatefulValidation,
+    PriorityScoring,
+}
+
+/// A transaction received by a gateway, in the gateway's own format (e.g., an RPC request).
+pub trait IncomingTransaction {
+    /// Decodes the transaction into an executable one; e.g., computes its hash and, for declare
+    /// transactions, verifies and compiles its class (see `class_info_from_declared_sierra`).
+    fn decode(self, block_context: &BlockContext) -> TransactionIntakeResult<AccountTransaction>;
+}
+
+/// Transactions built by the caller need no decoding.
+impl IncomingTransaction for AccountTransaction {
+    fn decode(self, _block_context: &BlockContext) -> TransactionIntakeResult<AccountTransaction> {
+        Ok(self)
+    }
+}
+
+/// A stateless check of a decoded transaction.
+pub trait StaticCheck: Send + Sync {
+    fn check(
+        &self,
+        tx: &AccountTransaction,
+        block_context: &BlockContext,
+    ) -> TransactionIntakeResult<()>;
+}
+
+/// Bounds the calldata and signature lengths of transactions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SizeLimits {
+    pub max_calldata_length: usize,
+    pub max_signature_length: usize,
+}
+
+impl StaticCheck for SizeLimits {
+    fn check(
+        &self,
+        tx: &AccountTransaction,
+        _block_context: &BlockContext,
+    ) -> TransactionIntakeResult<()> {
+        let calldata_length = tx.calldata_length();
+        if calldata_length > self.max_calldata_length {
+            return Err(TransactionIntakeError::CalldataTooLong {
+                calldata_length,
+                max_calldata_length: self.max_calldata_length,
+            });
+        }
+
+        let signature_length = tx.signature_length();
+        if signature_length > self.max_signature_length {
+            return Err(TransactionIntakeError::SignatureTooLong {
+                signature_length,
+                max_signature_length: self.max_signature_length,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Scores validated transactions; mempools order transactions by descending score.
+pub trait PriorityScorer: Send + Sync {
+    fn score(&self, tx: &AccountTransaction, block_context: &BlockContext) -> u128;
+}
+
+/// Scores transactions by their tip. Transactions of versions preceding V3 have no tip, and are
+/// scored 0.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TipScorer;
+
+impl PriorityScorer for TipScorer {
+    fn score(&self, tx: &AccountTransaction, _block_context: &BlockContext) -> u128 {
+        match tx.create_tx_info() {
+            TransactionInfo::Current(tx_info) => tx_info.tip.0.into(),
+            TransactionInfo::Deprecated(_) => 0,
+        }
+    }
+}
+
+/// A transaction that passed the intake pipeline, ready to be added to a mempool.
+#[derive(Debug)]
+pub struct AdmittedTransaction {
+    pub tx: AccountTransaction,
+    pub validate_call_info: Option<CallInfo>,
+    pub priority: u128,
+}
+
+/// The number of transactions that passed and that were rejected by a stage, and the time spent
+/// in it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct IntakeStageMetrics {
+    pub n_passed: usize,
+    pub n_rejected: usize,
+    pub total_duration: Duration,
+}
+
+/// Aggregates per-stage metrics over the transactions taken in; can be shared (e.g., via an
+/// `Arc`) with a metrics exporter.
+#[derive(Debug, Default)]
+pub struct IntakeMetrics {
+    stages: Mutex<HashMap<IntakeStage, IntakeStageMetrics>>,
+}
+
+impl IntakeMetrics {
+    pub fn record(&self, stage: IntakeStage, passed: bool, duration: Duration) {
+        let mut stages = self.lock();
+        let stage_metrics = stages.entry(stage).or_default();
+        if passed {
+            stage_metrics.n_passed += 1;
+        } else {
+            stage_metrics.n_rejected += 1;
+        }
+        stage_metrics.total_duration += duration;
+    }
+
+    pub fn stage(&self, stage: IntakeStage) -> IntakeStageMetrics {
+        self.lock().get(&stage).copied().unwrap_or_default()
+    }
+
+    pub fn summary(&self) -> HashMap<IntakeStage, IntakeStageMetrics> {
+        self.lock().clone()
+    }
+
+    /// Runs the given stage, recording whether it passed and its duration.
+    fn measure<T>(
+        &self,
+        stage: IntakeStage,
+        run_stage: impl FnOnce() -> TransactionIntakeResult<T>,
+    ) -> TransactionIntakeResult<T> {
+        let start = Instant::now();
+        let result = run_stage();
+        self.record(stage, result.is_ok(), start.elapsed());
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<IntakeStage, IntakeStageMetrics>> {
+        self.stages.lock().expect("Intake metrics lock is poisoned.")
+    }
+}
+
+/// The intake logic of a gateway: decodes incoming transactions, runs the static checks over
+/// them (in order), runs the preflight (if set; see `CalldataPreflight`), validates them against
+/// the state (see `StatefulValidator`), and scores them. Transactions are rejected at the first
+/// failing stage.
+pub struct IntakePipeline<S: StateReader> {
+    pub validator: StatefulValidator<S>,
+    pub static_checks: Vec<Box<dyn StaticCheck>>,
+    pub preflight: Option<CalldataPreflight>,
+    pub scorer: Box<dyn PriorityScorer>,
+    pub skip_validate: bool,
+    pub metrics: Arc<IntakeMetrics>,
+}
+
+impl<S: StateReader> IntakePipeline<S> {
+    /// Creates a pipeline with no static checks and no preflight, scoring transactions by their
+    /// tip.
+    pub fn new(state: CachedState<S>, block_context: BlockContext) -> Self {
+        Self {
+            validator: StatefulValidator::new(state, block_context),
+            static_checks: Vec::new(),
+            preflight: None,
+            scorer: Box::new(TipScorer),
+            skip_validate: false,
+            metrics: Arc::new(IntakeMetrics::default()),
+        }
+    }
+
+    pub fn with_static_check(mut self, static_check: impl StaticCheck + 'static) -> Self {
+        self.static_checks.push(Box::new(static_check));
+        self
+    }
+
+    pub fn with_preflight(mut self, preflight: CalldataPreflight) -> Self {
+        self.preflight = Some(preflight);
+        self
+    }
+
+    pub fn with_scorer(mut self, scorer: impl PriorityScorer + 'static) -> Self {
+        self.scorer = Box::new(scorer);
+        self
+    }
+
+    /// Takes the given transaction in, through all stages; returns the admitted transaction, or
+    /// the error of the stage rejecting it. Validated transactions increment their sender's nonce
+    /// in the validator's state.
+    pub fn intake(
+        &mut self,
+        incoming_tx: impl IncomingTransaction,
+    ) -> TransactionIntakeResult<AdmittedTransaction> {
+        let tx = self
+            .metrics
+            .measure(IntakeStage::Decode, || incoming_tx.decode(&self.validator.block_context))?;
+
+        self.metrics.measure(IntakeStage::StaticChecks, || {
+            self.static_checks
+                .iter()
+                .try_for_each(|static_check| static_check.check(&tx, &self.validator.block_context))
+        })?;
+
+        if let Some(preflight) = &self.preflight {
+            self.metrics.measure(IntakeStage::Preflight, || {
+                Ok(preflight.check(&tx, &self.validator.state)?)
+            })?;
+        }
+
+        let validate_call_info = self.metrics.measure(IntakeStage::StatefulValidation, || {
+            Ok(self.validator.perform_validations(tx.clone(), self.skip_validate)?)
+        })?;
+
+        let priority = self.metrics.measure(IntakeStage::PriorityScoring, || {
+            Ok(self.scorer.score(&tx, &self.validator.block_context))
+        })?;
+
+        Ok(AdmittedTransaction { tx, validate_call_info, priority })
+    }
+}
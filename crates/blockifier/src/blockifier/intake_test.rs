@@ -0,0 +1,86 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::{Tip, TransactionVersion};
+
+use crate::blockifier::intake::{
+    IntakePipeline, IntakeStage, IntakeStageMetrics, SizeLimits, TransactionIntakeError,
+};
+use crate::context::BlockContext;
+use crate::invoke_tx_args;
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_trivial_calldata, CairoVersion, BALANCE};
+use crate::transaction::errors::{TransactionExecutionError, TransactionPreValidationError};
+use crate::transaction::test_utils::{account_invoke_tx, block_context, max_resource_bounds};
+
+#[rstest]
+fn test_intake(
+    block_context: BlockContext,
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let account_contract = FeatureContract::AccountWithoutValidations(cairo_version);
+    let state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let sender_address = account_contract.get_instance_address(0);
+    let calldata = create_trivial_calldata(test_contract.get_instance_address(0));
+    let max_calldata_length = calldata.0.len();
+    let invoke_tx = |nonce: u8| {
+        account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: calldata.clone(),
+            version: TransactionVersion::THREE,
+            resource_bounds: max_resource_bounds(),
+            tip: Tip(7),
+            nonce: Nonce(stark_felt!(nonce)),
+        })
+    };
+    let mut pipeline = IntakePipeline::new(state, block_context)
+        .with_static_check(SizeLimits { max_calldata_length, max_signature_length: 0 });
+
+    let admitted_tx = pipeline.intake(invoke_tx(0)).unwrap();
+    assert_eq!(admitted_tx.priority, 7);
+    assert!(admitted_tx.validate_call_info.is_some());
+    assert_eq!(
+        pipeline.validator.state.get_nonce_at(sender_address).unwrap(),
+        Nonce(stark_felt!(1_u8))
+    );
+
+    // Rejected by the stateful validation.
+    assert_matches!(
+        pipeline.intake(invoke_tx(0)).unwrap_err(),
+        TransactionIntakeError::ValidationError(
+            TransactionExecutionError::TransactionPreValidationError(
+                TransactionPreValidationError::InvalidNonce { .. }
+            )
+        )
+    );
+
+    // Rejected by the static checks.
+    let mut pipeline = pipeline.with_static_check(SizeLimits {
+        max_calldata_length: max_calldata_length - 1,
+        max_signature_length: 0,
+    });
+    assert_matches!(
+        pipeline.intake(invoke_tx(1)).unwrap_err(),
+        TransactionIntakeError::CalldataTooLong { calldata_length, max_calldata_length: max }
+        if calldata_length == max_calldata_length && max == max_calldata_length - 1
+    );
+
+    let counts = |stage: IntakeStage| {
+        let IntakeStageMetrics { n_passed, n_rejected, .. } = pipeline.metrics.stage(stage);
+        (n_passed, n_rejected)
+    };
+    assert_eq!(counts(IntakeStage::Decode), (3, 0));
+    assert_eq!(counts(IntakeStage::StaticChecks), (2, 1));
+    assert_eq!(counts(IntakeStage::StatefulValidation), (1, 1));
+    assert_eq!(counts(IntakeStage::PriorityScoring), (1, 0));
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use num_traits::ToPrimitive;
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, PatriciaKey};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use starknet_api::hash::{StarkFelt, StarkHash};
+use thiserror::Error;
+
+use crate::abi::constants::DEFAULT_ENTRY_POINT_SELECTOR;
+use crate::execution::execution_utils::stark_felt_to_felt;
+use crate::state::errors::StateError;
+use crate::state::state_api::StateReader;
+use crate::transaction::account_transaction::AccountTransaction;
+
+#[cfg(test)]
+#[path = "preflight_test.rs"]
+pub mod test;
+
+#[derive(Debug, Error)]
+pub enum PreflightError {
+    #[error("Failed to decode the calls from calldata of length {length}.")]
+    MalformedCalldata { length: usize },
+    #[error("Call target {0:?} is not deployed.")]
+    UndeployedTarget(ContractAddress),
+    #[error(
+        "Entry point {selector:?} is not found in class {class_hash:?} of contract {address:?}."
+    )]
+    EntryPointNotFound {
+        address: ContractAddress,
+        class_hash: ClassHash,
+        selector: EntryPointSelector,
+    },
+    #[error(
+        "Calldata length {calldata_length} of entry point {selector:?} of class {class_hash:?} \
+         does not match its arity {arity}."
+    )]
+    ArityMismatch {
+        class_hash: ClassHash,
+        selector: EntryPointSelector,
+        calldata_length: usize,
+        arity: usize,
+    },
+    #[error(transparent)]
+    StateError(#[from] StateError),
+}
+
+pub type PreflightResult<T> = Result<T, PreflightError>;
+
+/// The layout of the `__execute__` calldata of accounts, from which the calls of invoke
+/// transactions are decoded.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExecuteCalldataLayout {
+    /// A single call: `[to, selector, calldata_len, ...calldata]`.
+    SingleCall,
+    /// An `Array<Call>`: `[n_calls, ...[to, selector, calldata_len, ...calldata]]`.
+    #[default]
+    Multicall,
+}
+
+/// A call made by a transaction, before its execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreflightCall {
+    pub to: ContractAddress,
+    pub selector: EntryPointSelector,
+    pub calldata: Vec<StarkFelt>,
+}
+
+impl ExecuteCalldataLayout {
+    pub fn decode(&self, calldata: &[StarkFelt]) -> PreflightResult<Vec<PreflightCall>> {
+        let malformed = || PreflightError::MalformedCalldata { length: calldata.len() };
+        let mut felts = calldata.iter().copied();
+        let read_len = |felts: &mut dyn Iterator<Item = StarkFelt>| {
+            felts.next().and_then(|felt| stark_felt_to_felt(felt).to_usize()).ok_or_else(malformed)
+        };
+        let n_calls = match self {
+            Self::SingleCall => 1,
+            Self::Multicall => read_len(&mut felts)?,
+        };
+
+        let mut calls = Vec::new();
+        for _ in 0..n_calls {
+            let to = felts.next().ok_or_else(malformed)?;
+            let to = ContractAddress(PatriciaKey::try_from(to).map_err(|_| malformed())?);
+            let selector = EntryPointSelector(felts.next().ok_or_else(malformed)?);
+            let calldata_len = read_len(&mut felts)?;
+            let call_calldata: Vec<StarkFelt> = felts.by_ref().take(calldata_len).collect();
+            if call_calldata.len() != calldata_len {
+                return Err(malformed());
+            }
+            calls.push(PreflightCall { to, selector, calldata: call_calldata });
+        }
+        if felts.next().is_some() {
+            return Err(malformed());
+        }
+
+        Ok(calls)
+    }
+}
+
+/// An optional, execution-free check of the calls of invoke transactions, rejecting obviously
+/// malformed transactions before paying for their validation: the calldata must decode into
+/// calls, each call target must be deployed and have the called external entry point, and the
+/// calldata of entry points with a known arity must match it. Other transactions pass.
+#[derive(Clone, Debug, Default)]
+pub struct CalldataPreflight {
+    pub layout: ExecuteCalldataLayout,
+    /// The serialized calldata length of entry points, as known to the caller (e.g., derived from
+    /// the Sierra ABIs of their classes).
+    pub arities: HashMap<(ClassHash, EntryPointSelector), usize>,
+}
+
+impl CalldataPreflight {
+    pub fn new(layout: ExecuteCalldataLayout) -> Self {
+        Self { layout, arities: HashMap::new() }
+    }
+
+    pub fn with_arity(
+        mut self,
+        class_hash: ClassHash,
+        selector: EntryPointSelector,
+        arity: usize,
+    ) -> Self {
+        self.arities.insert((class_hash, selector), arity);
+        self
+    }
+
+    pub fn check(&self, tx: &AccountTransaction, state: &impl StateReader) -> PreflightResult<()> {
+        let AccountTransaction::Invoke(invoke_tx) = tx else {
+            return Ok(());
+        };
+        let calls = match &invoke_tx.tx {
+            // The outermost call is made directly to the sender.
+            starknet_api::transaction::InvokeTransaction::V0(tx) => vec![PreflightCall {
+                to: tx.contract_address,
+                selector: tx.entry_point_selector,
+                calldata: tx.calldata.0.to_vec(),
+            }],
+            starknet_api::transaction::InvokeTransaction::V1(_)
+            | starknet_api::transaction::InvokeTransaction::V3(_) => {
+                self.layout.decode(&invoke_tx.calldata().0)?
+            }
+        };
+
+        calls.iter().try_for_each(|call| self.check_call(call, state))
+    }
+
+    fn check_call(&self, call: &PreflightCall, state: &impl StateReader) -> PreflightResult<()> {
+        let class_hash = state.get_class_hash_at(call.to)?;
+        if class_hash == ClassHash::default() {
+            return Err(PreflightError::UndeployedTarget(call.to));
+        }
+
+        // Missing selectors may be routed to a default entry point.
+        let default_selector = EntryPointSelector(StarkHash::from(DEFAULT_ENTRY_POINT_SELECTOR));
+        let selectors = state
+            .get_compiled_contract_class(class_hash)?
+            .entry_point_selectors(EntryPointType::External);
+        if !selectors.iter().any(|selector| [call.selector, default_selector].contains(selector)) {
+            return Err(PreflightError::EntryPointNotFound {
+                address: call.to,
+                class_hash,
+                selector: call.selector,
+            });
+        }
+
+        match self.arities.get(&(class_hash, call.selector)) {
+            Some(&arity) if arity != call.calldata.len() => Err(PreflightError::ArityMismatch {
+                class_hash,
+                selector: call.selector,
+                calldata_length: call.calldata.len(),
+                arity,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::blockifier::preflight::{
+    CalldataPreflight, ExecuteCalldataLayout, PreflightCall, PreflightError,
+};
+use crate::context::ChainInfo;
+use crate::invoke_tx_args;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_calldata, create_trivial_calldata, CairoVersion, BALANCE};
+use crate::transaction::test_utils::account_invoke_tx;
+
+#[test]
+fn test_decode_calldata() {
+    let to = contract_address!("0x100");
+    let selector = selector_from_name("foo");
+    let call = PreflightCall { to, selector, calldata: vec![stark_felt!(7_u8)] };
+    let single_call = [*to.0.key(), selector.0, stark_felt!(1_u8), stark_felt!(7_u8)];
+    assert_eq!(ExecuteCalldataLayout::SingleCall.decode(&single_call).unwrap(), [call.clone()]);
+
+    let multicall = [&[stark_felt!(2_u8)], &single_call[..], &single_call[..]].concat();
+    assert_eq!(ExecuteCalldataLayout::Multicall.decode(&multicall).unwrap(), [call.clone(), call]);
+
+    // Truncated and trailing calldata.
+    for (layout, calldata) in [
+        (ExecuteCalldataLayout::SingleCall, &single_call[..3]),
+        (ExecuteCalldataLayout::Multicall, &multicall[..multicall.len() - 1]),
+        (ExecuteCalldataLayout::Multicall, &single_call[..]),
+    ] {
+        assert_matches!(
+            layout.decode(calldata).unwrap_err(),
+            PreflightError::MalformedCalldata { length } if length == calldata.len()
+        );
+    }
+}
+
+#[rstest]
+fn test_preflight(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let account_contract = FeatureContract::AccountWithoutValidations(cairo_version);
+    let state = test_state(
+        &ChainInfo::create_for_testing(),
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let test_contract_address = test_contract.get_instance_address(0);
+    let invoke_tx = |calldata: Calldata| {
+        account_invoke_tx(invoke_tx_args! {
+            sender_address: account_contract.get_instance_address(0),
+            calldata,
+        })
+    };
+    let preflight = CalldataPreflight::new(ExecuteCalldataLayout::SingleCall);
+
+    preflight.check(&invoke_tx(create_trivial_calldata(test_contract_address)), &state).unwrap();
+
+    let undeployed_address = contract_address!("0x1234");
+    assert_matches!(
+        preflight.check(&invoke_tx(create_trivial_calldata(undeployed_address)), &state),
+        Err(PreflightError::UndeployedTarget(address)) if address == undeployed_address
+    );
+
+    let missing_entry_point = create_calldata(test_contract_address, "no_such_entry_point", &[]);
+    assert_matches!(
+        preflight.check(&invoke_tx(missing_entry_point), &state),
+        Err(PreflightError::EntryPointNotFound { selector, .. })
+        if selector == selector_from_name("no_such_entry_point")
+    );
+
+    assert_matches!(
+        preflight.check(&invoke_tx(calldata![stark_felt!(1_u8)]), &state),
+        Err(PreflightError::MalformedCalldata { length: 1 })
+    );
+
+    // `return_result` takes a single argument.
+    let preflight = preflight.with_arity(
+        test_contract.get_class_hash(),
+        selector_from_name("return_result"),
+        1,
+    );
+    preflight.check(&invoke_tx(create_trivial_calldata(test_contract_address)), &state).unwrap();
+    let extra_argument =
+        create_calldata(test_contract_address, "return_result", &[stark_felt!(2_u8); 2]);
+    assert_matches!(
+        preflight.check(&invoke_tx(extra_argument), &state),
+        Err(PreflightError::ArityMismatch { calldata_length: 2, arity: 1, .. })
+    );
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::Serialize;
+
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::{RevertReason, TransactionExecutionInfo};
+
+#[cfg(test)]
+#[path = "revert_metrics_test.rs"]
+pub mod test;
+
+/// The number of reverted and rejected transactions in a block, by error class.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct RevertMetricsSummary {
+    pub n_reverted: HashMap<RevertReason, usize>,
+    pub n_rejected: HashMap<RevertReason, usize>,
+}
+
+impl RevertMetricsSummary {
+    pub fn total_reverted(&self) -> usize {
+        self.n_reverted.values().sum()
+    }
+
+    pub fn total_rejected(&self) -> usize {
+        self.n_rejected.values().sum()
+    }
+}
+
+/// Aggregates revert and rejection reasons over a block; can be shared (e.g., via an `Arc`)
+/// between the workers of a parallel executor.
+#[derive(Debug, Default)]
+pub struct RevertMetrics {
+    summary: Mutex<RevertMetricsSummary>,
+}
+
+impl RevertMetrics {
+    /// Records the outcome of an executed transaction; successful executions are not counted.
+    pub fn record_execution_info(&self, tx_execution_info: &TransactionExecutionInfo) {
+        if let Some(revert_reason) = tx_execution_info.revert_reason {
+            self.record_revert(revert_reason);
+        }
+    }
+
+    pub fn record_revert(&self, revert_reason: RevertReason) {
+        *self.lock().n_reverted.entry(revert_reason).or_default() += 1;
+    }
+
+    pub fn record_rejection(&self, error: &TransactionExecutionError) {
+        *self.lock().n_rejected.entry(RevertReason::from_rejection(error)).or_default() += 1;
+    }
+
+    pub fn summary(&self) -> RevertMetricsSummary {
+        self.lock().clone()
+    }
+
+    /// Returns the summary and resets the counters, e.g., when closing a block.
+    pub fn take_summary(&self) -> RevertMetricsSummary {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RevertMetricsSummary> {
+        self.summary.lock().expect("Revert metrics lock is poisoned.")
+    }
+}
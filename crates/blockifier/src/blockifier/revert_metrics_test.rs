@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use std::thread;
+
+use pretty_assertions::assert_eq;
+use starknet_api::core::ClassHash;
+
+use crate::blockifier::revert_metrics::{RevertMetrics, RevertMetricsSummary};
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::{RevertReason, TransactionExecutionInfo};
+
+fn reverted_execution_info(revert_reason: RevertReason) -> TransactionExecutionInfo {
+    TransactionExecutionInfo {
+        revert_error: Some(format!("{revert_reason:?}")),
+        revert_reason: Some(revert_reason),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_revert_metrics() {
+    let metrics = Arc::new(RevertMetrics::default());
+    let n_threads = 4;
+    let handles: Vec<_> = (0..n_threads)
+        .map(|_| {
+            let metrics = Arc::clone(&metrics);
+            thread::spawn(move || {
+                metrics.record_execution_info(&TransactionExecutionInfo::default());
+                metrics.record_execution_info(&reverted_execution_info(RevertReason::OutOfSteps));
+                metrics.record_execution_info(&reverted_execution_info(RevertReason::Panic));
+                metrics.record_rejection(&TransactionExecutionError::DeclareTransactionError {
+                    class_hash: ClassHash::default(),
+                });
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let summary = metrics.take_summary();
+    assert_eq!(
+        summary,
+        RevertMetricsSummary {
+            n_reverted: [(RevertReason::OutOfSteps, n_threads), (RevertReason::Panic, n_threads)]
+                .into(),
+            n_rejected: [(RevertReason::Other, n_threads)].into(),
+        }
+    );
+    assert_eq!(summary.total_reverted(), 2 * n_threads);
+    assert_eq!(summary.total_rejected(), n_threads);
+
+    // Taking the summary resets the counters.
+    assert_eq!(metrics.summary(), RevertMetricsSummary::default());
+}
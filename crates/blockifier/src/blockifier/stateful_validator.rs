@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+
+use crate::context::{BlockContext, TransactionContext};
+use crate::execution::call_info::CallInfo;
+use crate::execution::entry_point::EntryPointExecutionContext;
+use crate::fee::fee_checks::PostValidationReport;
+use crate::state::cached_state::{CachedState, TransactionalState};
+use crate::state::state_api::StateReader;
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::objects::TransactionExecutionResult;
+use crate::transaction::transactions::ValidatableTransaction;
+
+#[cfg(test)]
+#[path = "stateful_validator_test.rs"]
+pub mod test;
+
+/// Validates account transactions against a state, without executing them; e.g., for a gateway or
+/// a mempool to reject invalid transactions cheaply, before block building.
+pub struct StatefulValidator<S: StateReader> {
+    pub state: CachedState<S>,
+    pub block_context: BlockContext,
+}
+
+impl<S: StateReader> StatefulValidator<S> {
+    pub fn new(state: CachedState<S>, block_context: BlockContext) -> Self {
+        Self { state, block_context }
+    }
+
+    /// Performs the checks preceding the `__execute__` call of the transaction: its version, its
+    /// nonce, its fee bounds and the fee payer's balance, and its `__validate__` call, followed by
+    /// its paymaster's validation, if any (unless skipped), whose cost must be within the fee
+    /// bounds. No fee is charged.
+    ///
+    /// A validated transaction whose nonce is the sender's current one increments the sender's
+    /// nonce in the validator's state, so that the sender's subsequent transactions are validated
+    /// over it; a failed one leaves the state untouched. Nonces ahead of the sender's are accepted,
+    /// as the transactions in between may be pending, but their validation is not persisted. Deploy
+    /// account transactions also run their deployment before `__validate_deploy__`, as their
+    /// validation requires the deployed account.
+    pub fn perform_validations(
+        &mut self,
+        tx: AccountTransaction,
+        skip_validate: bool,
+    ) -> TransactionExecutionResult<Option<CallInfo>> {
+        let tx_context = Arc::new(self.block_context.to_tx_context(&tx));
+        let tx_info = &tx_context.tx_info;
+        let is_current_nonce =
+            self.state.get_nonce_at(tx_info.sender_address())? == tx_info.nonce();
+
+        let mut transactional_state = CachedState::create_transactional(&mut self.state);
+        let validation_result = validate(&mut transactional_state, tx_context, &tx, skip_validate);
+        match validation_result {
+            Ok(_) if is_current_nonce => transactional_state.commit(),
+            _ => transactional_state.abort(),
+        }
+
+        validation_result
+    }
+}
+
+fn validate<S: StateReader>(
+    state: &mut TransactionalState<'_, S>,
+    tx_context: Arc<TransactionContext>,
+    tx: &AccountTransaction,
+    skip_validate: bool,
+) -> TransactionExecutionResult<Option<CallInfo>> {
+    tx.verify_tx_version(tx_context.tx_info.version())?;
+
+    let (charge_fee, strict_nonce_check) = (true, false);
+    tx.perform_pre_validation_stage(state, &tx_context, charge_fee, strict_nonce_check)?;
+
+    let mut execution_resources = ExecutionResources::default();
+    let mut remaining_gas = tx_context.block_context.versioned_constants.tx_initial_gas();
+    let limit_steps_by_resources = true;
+    let deploy_call_info = match tx {
+        // The deployment precedes the validation of deploy account transactions; it runs in a
+        // validation context.
+        AccountTransaction::DeployAccount(_) => {
+            let mut context = EntryPointExecutionContext::new_validate(
+                tx_context.clone(),
+                limit_steps_by_resources,
+            )?;
+            tx.run_execute(state, &mut execution_resources, &mut context, &mut remaining_gas)?
+        }
+        _ => None,
+    };
+    if skip_validate {
+        return Ok(None);
+    }
+
+    let validate_call_info = tx.validate_tx(
+        state,
+        &mut execution_resources,
+        tx_context.clone(),
+        &mut remaining_gas,
+        limit_steps_by_resources,
+    )?;
+    let paymaster_call_info = AccountTransaction::validate_paymaster(
+        state,
+        &mut execution_resources,
+        tx_context.clone(),
+        &mut remaining_gas,
+        limit_steps_by_resources,
+    )?;
+
+    let (actual_cost, _bouncer_resources) = tx
+        .to_actual_cost_builder(tx_context.clone())?
+        .with_validate_call_info(&validate_call_info)
+        .with_paymaster_call_info(&paymaster_call_info)
+        .with_execute_call_info(&deploy_call_info)
+        .try_add_state_changes(state)?
+        .build(&execution_resources)?;
+    PostValidationReport::verify(&tx_context, &actual_cost)?;
+
+    Ok(validate_call_info)
+}
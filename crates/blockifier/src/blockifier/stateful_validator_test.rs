@@ -0,0 +1,98 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::Fee;
+
+use crate::blockifier::stateful_validator::StatefulValidator;
+use crate::context::BlockContext;
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{CairoVersion, NonceManager, BALANCE};
+use crate::transaction::errors::{
+    TransactionExecutionError, TransactionFeeError, TransactionPreValidationError,
+};
+use crate::transaction::objects::FeeType;
+use crate::transaction::test_utils::{
+    account_invoke_tx, block_context, deploy_and_fund_account, max_fee, trivial_invoke_setup,
+};
+use crate::{deploy_account_tx_args, invoke_tx_args};
+
+#[rstest]
+fn test_perform_validations(
+    block_context: BlockContext,
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let (state, invoke_args) = trivial_invoke_setup(&block_context.chain_info, cairo_version);
+    let sender_address = invoke_args.sender_address;
+    let invoke_tx = |nonce: u8, max_fee: Fee| {
+        account_invoke_tx(invoke_tx_args! {
+            max_fee,
+            nonce: Nonce(stark_felt!(nonce)),
+            ..invoke_args.clone()
+        })
+    };
+    let mut validator = StatefulValidator::new(state, block_context);
+
+    let validate_call_info = validator.perform_validations(invoke_tx(0, max_fee()), false).unwrap();
+    assert!(validate_call_info.is_some());
+    // The nonce is incremented; the transaction is not executed.
+    assert_eq!(validator.state.get_nonce_at(sender_address).unwrap(), Nonce(stark_felt!(1_u8)));
+
+    assert_matches!(
+        validator.perform_validations(invoke_tx(0, max_fee()), false).unwrap_err(),
+        TransactionExecutionError::TransactionPreValidationError(
+            TransactionPreValidationError::InvalidNonce { .. }
+        )
+    );
+    assert_matches!(
+        validator.perform_validations(invoke_tx(1, Fee(BALANCE + 1)), false).unwrap_err(),
+        TransactionExecutionError::TransactionPreValidationError(
+            TransactionPreValidationError::TransactionFeeError(
+                TransactionFeeError::MaxFeeExceedsBalance { .. }
+            )
+        )
+    );
+    // Failed validations leave the state untouched.
+    assert_eq!(validator.state.get_nonce_at(sender_address).unwrap(), Nonce(stark_felt!(1_u8)));
+
+    // Nonces ahead of the sender's are accepted, without being persisted; the pending
+    // transaction remains valid.
+    let validate_call_info = validator.perform_validations(invoke_tx(5, max_fee()), true).unwrap();
+    assert!(validate_call_info.is_none());
+    assert_eq!(validator.state.get_nonce_at(sender_address).unwrap(), Nonce(stark_felt!(1_u8)));
+    validator.perform_validations(invoke_tx(1, max_fee()), false).unwrap();
+    assert_eq!(validator.state.get_nonce_at(sender_address).unwrap(), Nonce(stark_felt!(2_u8)));
+}
+
+#[rstest]
+fn test_perform_validations_deploy_account(
+    block_context: BlockContext,
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let account_contract = FeatureContract::AccountWithoutValidations(cairo_version);
+    let class_hash = account_contract.get_class_hash();
+    let chain_info = &block_context.chain_info;
+    let mut state = test_state(chain_info, BALANCE, &[(account_contract, 0)]);
+    let (deploy_account_tx, account_address) = deploy_and_fund_account(
+        &mut state,
+        &mut NonceManager::default(),
+        chain_info,
+        deploy_account_tx_args! { class_hash, max_fee: max_fee() },
+    );
+    let fee_token_address = chain_info.fee_token_address(&FeeType::Eth);
+    let mut validator = StatefulValidator::new(state, block_context);
+
+    let validate_call_info = validator.perform_validations(deploy_account_tx, false).unwrap();
+    assert!(validate_call_info.is_some());
+    // The account is deployed and its nonce incremented; no fee is charged.
+    assert_eq!(validator.state.get_class_hash_at(account_address).unwrap(), class_hash);
+    assert_eq!(validator.state.get_nonce_at(account_address).unwrap(), Nonce(stark_felt!(1_u8)));
+    assert_eq!(
+        validator.state.get_fee_token_balance(account_address, fee_token_address).unwrap(),
+        (stark_felt!(BALANCE), stark_felt!(0_u8))
+    );
+}
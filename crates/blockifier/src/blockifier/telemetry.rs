@@ -0,0 +1,341 @@
+use std::sync::Arc;
+#[cfg(feature = "otlp")]
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+#[cfg(feature = "otlp")]
+use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use starknet_api::block::BlockNumber;
+use thiserror::Error;
+
+use crate::blockifier::transaction_executor::BlockSummary;
+use crate::context::BlockContext;
+
+#[cfg(test)]
+#[path = "telemetry_test.rs"]
+pub mod test;
+
+pub const DEFAULT_SERVICE_NAME: &str = "blockifier";
+/// The instrumentation scope of the exported spans and metrics.
+const SCOPE_NAME: &str = "blockifier";
+// The OTLP span kind of spans internal to the service.
+const SPAN_KIND_INTERNAL: u8 = 1;
+#[cfg(feature = "otlp")]
+const EXPORT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+#[cfg(feature = "otlp")]
+const EXPORT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+// The number of export requests awaiting the background thread; further requests are dropped.
+#[cfg(feature = "otlp")]
+const EXPORT_QUEUE_CAPACITY: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("Failed to reach the collector: {0}.")]
+    Transport(String),
+    #[error("The export queue is full.")]
+    QueueFull,
+    #[error("The export thread has stopped.")]
+    ExporterStopped,
+}
+
+pub type TelemetryResult<T> = Result<T, TelemetryError>;
+
+/// The OpenTelemetry signals exported by the Blockifier.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OtlpSignal {
+    Traces,
+    Metrics,
+}
+
+impl OtlpSignal {
+    /// The path of the signal's OTLP/HTTP endpoint, relative to the collector's base URL.
+    pub fn path(self) -> &'static str {
+        match self {
+            OtlpSignal::Traces => "/v1/traces",
+            OtlpSignal::Metrics => "/v1/metrics",
+        }
+    }
+}
+
+/// Sends OTLP export requests, in the OTLP/JSON encoding, to a collector.
+pub trait TelemetryExporter: Send + Sync {
+    fn export(&self, signal: OtlpSignal, request: Value) -> TelemetryResult<()>;
+}
+
+/// Sends export requests to an OTLP/HTTP collector (e.g., `http://localhost:4318`). Requests are
+/// queued, and posted by a background thread, so that a slow or unreachable collector does not
+/// stall block finalization; the thread exits once the exporter is dropped.
+#[cfg(feature = "otlp")]
+pub struct OtlpHttpExporter {
+    sender: SyncSender<(OtlpSignal, Value)>,
+}
+
+#[cfg(feature = "otlp")]
+impl OtlpHttpExporter {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        let endpoint = endpoint.into();
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(EXPORT_CONNECT_TIMEOUT)
+            .timeout_read(EXPORT_READ_TIMEOUT)
+            .build();
+        let (sender, receiver) = mpsc::sync_channel::<(OtlpSignal, Value)>(EXPORT_QUEUE_CAPACITY);
+        std::thread::spawn(move || {
+            for (signal, request) in receiver {
+                let url = format!("{}{}", endpoint.trim_end_matches('/'), signal.path());
+                if let Err(error) = agent.post(&url).send_json(request) {
+                    log::warn!("Failed to export the {signal:?} to the collector: {error}");
+                }
+            }
+        });
+
+        Self { sender }
+    }
+}
+
+#[cfg(feature = "otlp")]
+impl TelemetryExporter for OtlpHttpExporter {
+    fn export(&self, signal: OtlpSignal, request: Value) -> TelemetryResult<()> {
+        self.sender.try_send((signal, request)).map_err(|error| match error {
+            TrySendError::Full(_) => TelemetryError::QueueFull,
+            TrySendError::Disconnected(_) => TelemetryError::ExporterStopped,
+        })
+    }
+}
+
+/// Exports the spans and metrics of the execution phases of blocks, when registered in the block
+/// context extensions: a span per block, with a child span per executed transaction and for the
+/// block finalization; and per-block transaction counts, DA size and execution time. Traces are
+/// sampled per block; metrics are exported for every block. Exports are handed to the exporter when
+/// the block is finalized; export failures are logged, and do not fail the block.
+pub struct ExecutionTelemetry {
+    pub exporter: Arc<dyn TelemetryExporter>,
+    pub service_name: String,
+    /// Blocks whose number is a multiple of this are traced; 1 traces every block, 0 none.
+    pub trace_every_n_blocks: u64,
+}
+
+impl ExecutionTelemetry {
+    /// Traces every block.
+    pub fn new(exporter: impl TelemetryExporter + 'static) -> Self {
+        Self {
+            exporter: Arc::new(exporter),
+            service_name: DEFAULT_SERVICE_NAME.to_string(),
+            trace_every_n_blocks: 1,
+        }
+    }
+
+    pub fn with_service_name(mut self, service_name: impl Into<String>) -> Self {
+        self.service_name = service_name.into();
+        self
+    }
+
+    pub fn with_trace_every_n_blocks(mut self, trace_every_n_blocks: u64) -> Self {
+        self.trace_every_n_blocks = trace_every_n_blocks;
+        self
+    }
+
+    pub fn is_traced(&self, block_number: BlockNumber) -> bool {
+        self.trace_every_n_blocks != 0 && block_number.0 % self.trace_every_n_blocks == 0
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+}
+
+impl From<usize> for AttributeValue {
+    fn from(value: usize) -> Self {
+        AttributeValue::Int(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl AttributeValue {
+    fn to_otlp(&self) -> Value {
+        match self {
+            // 64-bit integers are encoded as strings.
+            AttributeValue::String(value) => json!({ "stringValue": value }),
+            AttributeValue::Int(value) => json!({ "intValue": value.to_string() }),
+        }
+    }
+}
+
+/// A timed execution phase of a block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExecutionSpan {
+    pub name: &'static str,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    pub attributes: Vec<(&'static str, AttributeValue)>,
+}
+
+impl ExecutionSpan {
+    /// A span starting at the given time and ending now.
+    pub fn ending_now(name: &'static str, start_time: SystemTime) -> Self {
+        Self { name, start_time, end_time: SystemTime::now(), attributes: Vec::new() }
+    }
+
+    pub fn with_attribute(mut self, key: &'static str, value: AttributeValue) -> Self {
+        self.attributes.push((key, value));
+        self
+    }
+}
+
+/// Records the spans of a block under execution; see `ExecutionTelemetry`.
+pub(crate) struct BlockTelemetry {
+    telemetry: Arc<ExecutionTelemetry>,
+    block_number: BlockNumber,
+    block_start_time: SystemTime,
+    // Empty if the block is not traced.
+    spans: Vec<ExecutionSpan>,
+}
+
+impl BlockTelemetry {
+    /// Returns `None` if no telemetry is registered in the block context.
+    pub(crate) fn new(block_context: &BlockContext) -> Option<Self> {
+        Some(Self {
+            telemetry: block_context.extensions().get::<ExecutionTelemetry>()?,
+            block_number: block_context.block_info().block_number,
+            block_start_time: SystemTime::now(),
+            spans: Vec::new(),
+        })
+    }
+
+    pub(crate) fn record_span(&mut self, span: ExecutionSpan) {
+        if self.telemetry.is_traced(self.block_number) {
+            self.spans.push(span);
+        }
+    }
+
+    /// Exports the recorded spans, under a span of the whole block, and the metrics of the block.
+    pub(crate) fn export(self, summary: &BlockSummary) {
+        let end_time = SystemTime::now();
+        if self.telemetry.is_traced(self.block_number) {
+            self.export_signal(OtlpSignal::Traces, self.traces_request(end_time));
+        }
+        self.export_signal(OtlpSignal::Metrics, self.metrics_request(summary, end_time));
+    }
+
+    fn export_signal(&self, signal: OtlpSignal, request: Value) {
+        if let Err(error) = self.telemetry.exporter.export(signal, request) {
+            log::warn!("Failed to export the {signal:?} of block {}: {error}", self.block_number.0);
+        }
+    }
+
+    fn traces_request(&self, end_time: SystemTime) -> Value {
+        // Unique per execution of a block.
+        let trace_id =
+            format!("{:016x}{:016x}", self.block_number.0, unix_nanos(self.block_start_time));
+        let block_span = ExecutionSpan {
+            name: "execute_block",
+            start_time: self.block_start_time,
+            end_time,
+            attributes: vec![("block_number", self.block_number_attribute())],
+        };
+        let block_span_id = span_id(0);
+        let mut spans = vec![span_to_otlp(&block_span, &trace_id, &block_span_id, None)];
+        spans.extend(self.spans.iter().enumerate().map(|(index, span)| {
+            span_to_otlp(span, &trace_id, &span_id(index + 1), Some(&block_span_id))
+        }));
+
+        json!({
+            "resourceSpans": [{
+                "resource": self.resource(),
+                "scopeSpans": [{ "scope": { "name": SCOPE_NAME }, "spans": spans }],
+            }]
+        })
+    }
+
+    fn metrics_request(&self, summary: &BlockSummary, end_time: SystemTime) -> Value {
+        let execution_duration =
+            end_time.duration_since(self.block_start_time).unwrap_or_default().as_millis();
+        let gauges = [
+            ("blockifier.block.committed_transactions", "1", summary.n_committed_txs),
+            (
+                "blockifier.block.reverted_transactions",
+                "1",
+                summary.revert_metrics.total_reverted(),
+            ),
+            (
+                "blockifier.block.rejected_transactions",
+                "1",
+                summary.revert_metrics.total_rejected(),
+            ),
+            ("blockifier.block.estimated_da_size", "By", summary.bouncer_report.estimated_da_bytes),
+            (
+                "blockifier.block.execution_duration",
+                "ms",
+                usize::try_from(execution_duration).unwrap_or(usize::MAX),
+            ),
+        ];
+        let block_number_attribute =
+            json!({ "key": "block_number", "value": self.block_number_attribute().to_otlp() });
+        let metrics: Vec<Value> = gauges
+            .into_iter()
+            .map(|(name, unit, value)| {
+                json!({
+                    "name": name,
+                    "unit": unit,
+                    "gauge": {
+                        "dataPoints": [{
+                            "asInt": i64::try_from(value).unwrap_or(i64::MAX).to_string(),
+                            "timeUnixNano": unix_nanos(end_time).to_string(),
+                            "attributes": [block_number_attribute],
+                        }]
+                    },
+                })
+            })
+            .collect();
+
+        json!({
+            "resourceMetrics": [{
+                "resource": self.resource(),
+                "scopeMetrics": [{ "scope": { "name": SCOPE_NAME }, "metrics": metrics }],
+            }]
+        })
+    }
+
+    fn resource(&self) -> Value {
+        let service_name = AttributeValue::String(self.telemetry.service_name.clone());
+        json!({ "attributes": [{ "key": "service.name", "value": service_name.to_otlp() }] })
+    }
+
+    fn block_number_attribute(&self) -> AttributeValue {
+        AttributeValue::Int(i64::try_from(self.block_number.0).unwrap_or(i64::MAX))
+    }
+}
+
+fn span_to_otlp(
+    span: &ExecutionSpan,
+    trace_id: &str,
+    span_id: &str,
+    parent_span_id: Option<&str>,
+) -> Value {
+    let attributes: Vec<Value> = span
+        .attributes
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "value": value.to_otlp() }))
+        .collect();
+    json!({
+        "traceId": trace_id,
+        "spanId": span_id,
+        "parentSpanId": parent_span_id.unwrap_or_default(),
+        "name": span.name,
+        "kind": SPAN_KIND_INTERNAL,
+        "startTimeUnixNano": unix_nanos(span.start_time).to_string(),
+        "endTimeUnixNano": unix_nanos(span.end_time).to_string(),
+        "attributes": attributes,
+    })
+}
+
+/// Span IDs are unique within a trace, and must not be all zeros.
+fn span_id(index: usize) -> String {
+    format!("{:016x}", index + 1)
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    let nanos = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    u64::try_from(nanos).unwrap_or(u64::MAX)
+}
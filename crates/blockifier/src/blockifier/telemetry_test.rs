@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::{json, Value};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+
+use crate::blockifier::telemetry::{
+    ExecutionTelemetry, OtlpSignal, TelemetryExporter, TelemetryResult,
+};
+use crate::blockifier::transaction_executor::TransactionExecutor;
+use crate::context::BlockContext;
+use crate::invoke_tx_args;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_trivial_calldata, CairoVersion, BALANCE};
+use crate::transaction::test_utils::{account_invoke_tx, block_context};
+use crate::transaction::transaction_execution::Transaction;
+
+#[derive(Clone, Default)]
+struct RecordingExporter(Arc<Mutex<Vec<(OtlpSignal, Value)>>>);
+
+impl RecordingExporter {
+    fn requests(&self, signal: OtlpSignal) -> Vec<Value> {
+        let requests = self.0.lock().unwrap();
+        requests
+            .iter()
+            .filter(|(request_signal, _)| *request_signal == signal)
+            .map(|(_, request)| request.clone())
+            .collect()
+    }
+}
+
+impl TelemetryExporter for RecordingExporter {
+    fn export(&self, signal: OtlpSignal, request: Value) -> TelemetryResult<()> {
+        self.0.lock().unwrap().push((signal, request));
+        Ok(())
+    }
+}
+
+/// Executes a block of a committed and a rejected transaction, with the given telemetry.
+fn execute_block(block_context: BlockContext, telemetry: ExecutionTelemetry) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    block_context.extensions().insert(telemetry);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let invoke_tx = |nonce: u8| {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address: account_contract.get_instance_address(0),
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+            nonce: Nonce(stark_felt!(nonce)),
+        }))
+    };
+
+    tx_executor.execute(invoke_tx(0), true).unwrap();
+    tx_executor.commit();
+    tx_executor.execute(invoke_tx(0), true).unwrap_err();
+    tx_executor.finalize_block(false, &[]).unwrap();
+}
+
+fn gauge(metrics_request: &Value, name: &str) -> String {
+    let metrics =
+        metrics_request["resourceMetrics"][0]["scopeMetrics"][0]["metrics"].as_array().unwrap();
+    let metric = metrics.iter().find(|metric| metric["name"] == name).unwrap();
+    metric["gauge"]["dataPoints"][0]["asInt"].as_str().unwrap().to_string()
+}
+
+#[rstest]
+fn test_execution_telemetry(block_context: BlockContext) {
+    let exporter = RecordingExporter::default();
+    execute_block(
+        block_context,
+        ExecutionTelemetry::new(exporter.clone()).with_service_name("sequencer"),
+    );
+
+    let traces = exporter.requests(OtlpSignal::Traces);
+    assert_eq!(traces.len(), 1);
+    let resource_spans = &traces[0]["resourceSpans"][0];
+    assert_eq!(resource_spans["resource"]["attributes"][0]["value"]["stringValue"], "sequencer");
+    let spans = resource_spans["scopeSpans"][0]["spans"].as_array().unwrap();
+    let names: Vec<&str> = spans.iter().map(|span| span["name"].as_str().unwrap()).collect();
+    assert_eq!(
+        names,
+        ["execute_block", "execute_transaction", "execute_transaction", "finalize_block"]
+    );
+    let outcomes: Vec<&Value> =
+        spans[1..3].iter().map(|span| &span["attributes"][1]["value"]["stringValue"]).collect();
+    assert_eq!(outcomes, [&json!("succeeded"), &json!("rejected")]);
+    // All phases are children of the block span, in the same trace.
+    for span in &spans[1..] {
+        assert_eq!(span["traceId"], spans[0]["traceId"]);
+        assert_eq!(span["parentSpanId"], spans[0]["spanId"]);
+    }
+
+    let metrics = exporter.requests(OtlpSignal::Metrics);
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(gauge(&metrics[0], "blockifier.block.committed_transactions"), "1");
+    assert_eq!(gauge(&metrics[0], "blockifier.block.rejected_transactions"), "1");
+    assert_eq!(gauge(&metrics[0], "blockifier.block.reverted_transactions"), "0");
+}
+
+#[rstest]
+fn test_trace_sampling(block_context: BlockContext) {
+    let block_number = block_context.block_info().block_number;
+    let telemetry = ExecutionTelemetry::new(RecordingExporter::default());
+    assert!(telemetry.is_traced(block_number));
+    let telemetry = telemetry.with_trace_every_n_blocks(0);
+    assert!(!telemetry.is_traced(BlockNumber(0)));
+
+    // Untraced blocks still export their metrics.
+    let exporter = RecordingExporter::default();
+    execute_block(
+        block_context,
+        ExecutionTelemetry::new(exporter.clone()).with_trace_every_n_blocks(block_number.0 + 1),
+    );
+    assert!(exporter.requests(OtlpSignal::Traces).is_empty());
+    assert_eq!(exporter.requests(OtlpSignal::Metrics).len(), 1);
+}
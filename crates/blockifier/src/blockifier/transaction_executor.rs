@@ -1,28 +1,49 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::SystemTime;
 use std::vec::IntoIter;
 
 use cairo_vm::vm::runners::builtin_runner::HASH_BUILTIN_NAME;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
-use starknet_api::core::ClassHash;
+use serde::{Deserialize, Serialize};
+use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkHash;
+use starknet_api::transaction::{Fee, TransactionHash};
 use thiserror::Error;
 
+use crate::blockifier::audit_log::{
+    tx_audit_entries, AuditEntry, AuditLog, AuditRecord, RecordAuditLog,
+};
+use crate::blockifier::block::EndOfBlockHook;
+use crate::blockifier::block_builder::BlockClosureReason;
+use crate::blockifier::bloom_filter::BlockBloomFilters;
 use crate::blockifier::bouncer::BouncerInfo;
+use crate::blockifier::revert_metrics::{RevertMetrics, RevertMetricsSummary};
+use crate::blockifier::telemetry::{AttributeValue, BlockTelemetry, ExecutionSpan};
+use crate::bouncer::TenantId;
 use crate::context::BlockContext;
 use crate::execution::call_info::{CallInfo, MessageL1CostInfo};
+use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
+use crate::execution::entry_point::MemoryLimits;
+use crate::execution::execution_utils::stark_felt_to_felt;
 use crate::fee::actual_cost::ActualCost;
+use crate::fee::fee_sharding::{AccruedFees, ConsolidateFees, FeeAccumulator};
 use crate::fee::gas_usage::{get_messages_gas_usage, get_onchain_data_segment_length};
 use crate::state::cached_state::{
-    CachedState, CommitmentStateDiff, StagedTransactionalState, StateChangesKeys, StorageEntry,
-    TransactionalState,
+    CachedState, CommitmentStateDiff, StagedTransactionalState, StateChanges, StateChangesCount,
+    StateChangesKeys, StorageEntry, TransactionalState,
 };
+use crate::state::da_encoding::{encoded_state_diff_length, DA_BYTES_PER_FELT};
 use crate::state::errors::StateError;
-use crate::state::state_api::{State, StateReader};
+use crate::state::state_api::{DynStateReader, State, StateReader};
 use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::errors::TransactionExecutionError;
-use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::objects::{
+    RevertReason, TransactionExecutionInfo, TransactionExecutionResult,
+};
 use crate::transaction::transaction_execution::Transaction;
 use crate::transaction::transactions::{ExecutableTransaction, ValidatableTransaction};
+use crate::utils::add_mappings;
 
 #[cfg(test)]
 #[path = "transaction_executor_test.rs"]
@@ -34,42 +55,162 @@ pub enum TransactionExecutorError {
     StateError(#[from] StateError),
     #[error(transparent)]
     TransactionExecutionError(#[from] TransactionExecutionError),
+    #[error("Transaction exceeds the block quota of tenant {tenant_id}.")]
+    TenantQuotaExceeded { tenant_id: TenantId },
 }
 
 pub type TransactionExecutorResult<T> = Result<T, TransactionExecutorError>;
 pub type VisitedSegmentsMapping = Vec<(ClassHash, Vec<usize>)>;
 
+/// The resources used by a finalized block, as counted by the executor.
+#[derive(Debug)]
+pub struct BlockBouncerReport {
+    pub state_changes_count: StateChangesCount,
+    pub estimated_da_bytes: usize,
+    pub n_executed_classes: usize,
+    pub n_visited_storage_entries: usize,
+    pub syscall_counter: SyscallCounter,
+    /// The number of instances of each builtin used by the committed transactions, including
+    /// those the OS uses for them; prover capacity is planned per builtin.
+    pub builtin_instance_counter: HashMap<String, usize>,
+}
+
+/// The outputs of a finalized block.
+#[derive(Debug)]
+pub struct BlockSummary {
+    pub n_committed_txs: usize,
+    /// The squashed state diff of the block, including end-of-block system operations.
+    pub state_diff: CommitmentStateDiff,
+    /// The Poseidon hash of the (uncompressed) DA encoding of the state diff.
+    pub state_diff_commitment: StarkHash,
+    pub visited_segments: VisitedSegmentsMapping,
+    pub bloom_filters: BlockBloomFilters,
+    pub bouncer_report: BlockBouncerReport,
+    pub revert_metrics: RevertMetricsSummary,
+    /// The nonces of the L1-to-L2 messages consumed by the committed L1 handler transactions, in
+    /// execution order.
+    pub consumed_l1_to_l2_nonces: Vec<Nonce>,
+    /// Why the block builder stopped pulling transactions; `None` if the block was not built by
+    /// `build_block`.
+    pub closure_reason: Option<BlockClosureReason>,
+}
+
+/// The outputs of an earlier execution of a transaction, to be replayed by the executor.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RecordedExecution {
+    pub execution_info: TransactionExecutionInfo,
+    pub state_changes: StateChanges,
+}
+
+/// Enables recording the state changes of each committed transaction (see
+/// `TransactionExecutor::tx_state_changes`), when inserted into the block context extensions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RecordTxStateChanges;
+
+/// The outputs of an executed transaction, awaiting a commit/abort call; applied to the block on
+/// commit, and dropped on abort.
+pub struct StagedTx {
+    pub state: StagedTransactionalState,
+    // Merged into the block's filters on commit.
+    bloom_filters: BlockBloomFilters,
+    // Appended to the block's audit log on commit, if it is recorded.
+    audit_entries: Option<Vec<AuditEntry>>,
+    // Appended to the block's per-transaction state changes on commit, if they are recorded.
+    state_changes: Option<StateChanges>,
+    // The nonce of the L1-to-L2 message consumed by the transaction, if any.
+    consumed_l1_to_l2_nonce: Option<Nonce>,
+    // The builtin instances used by the transaction, including OS additions.
+    builtin_instance_counter: HashMap<String, usize>,
+    // Counted in the block's revert metrics on commit.
+    revert_reason: Option<RevertReason>,
+    // The fee charged to the fee accumulator of the block, if any, and its fee token.
+    accrued_fee: Option<(ContractAddress, FeeAccumulator, Fee)>,
+    // The execution span of the transaction, if telemetry is registered; recorded on commit.
+    span: Option<ExecutionSpan>,
+}
+
+/// A transaction executor over a state reader selected at runtime; can be moved between threads
+/// (e.g., driven by an async runtime).
+pub type DynTransactionExecutor = TransactionExecutor<DynStateReader>;
+
 // TODO(Gilad): make this hold TransactionContext instead of BlockContext.
+/// Executes the transactions of a block, one at a time.
+///
+/// The executor holds no references or thread-bound state, hence it is `Send` whenever its state
+/// reader is; its interior mutability (the state caches) is not shared, so it is not `Sync`.
 pub struct TransactionExecutor<S: StateReader> {
     pub block_context: BlockContext,
 
     // Maintained for counting purposes.
     pub executed_class_hashes: HashSet<ClassHash>,
     pub visited_storage_entries: HashSet<StorageEntry>,
+    pub syscall_counter: SyscallCounter,
+    pub builtin_instance_counter: HashMap<String, usize>,
+    // Per-block counts of reverted and rejected transactions, by error class.
+    pub revert_metrics: Arc<RevertMetrics>,
+    // Bloom filters over the committed transactions of the block.
+    pub bloom_filters: BlockBloomFilters,
+    // The effects of the committed transactions of the block, in canonical order; recorded only if
+    // `RecordAuditLog` is registered in the block context.
+    pub audit_log: AuditLog,
+    // The state changes of each committed transaction of the block, in execution order; recorded
+    // only if `RecordTxStateChanges` is registered in the block context.
+    tx_state_changes: Vec<StateChanges>,
+    // The nonces of the L1-to-L2 messages consumed by the committed transactions, in order.
+    consumed_l1_to_l2_nonces: Vec<Nonce>,
+    // The fees charged to fee accumulators by the committed transactions; consolidated on
+    // finalization.
+    accrued_fees: AccruedFees,
+    pub n_committed_txs: usize,
+    // Set once the block builder stops pulling transactions.
+    pub closure_reason: Option<BlockClosureReason>,
     // This member should be consistent with the state's modified keys.
     state_changes_keys: StateChangesKeys,
 
     // State-related fields.
     pub state: CachedState<S>,
 
-    // Transactional state, awaiting commit/abort call.
+    // The executed transaction, awaiting commit/abort call.
     // Is `Some` only after transaction has finished executing, and before commit/revert have been
     // called. `None` while a transaction is being executed and in between transactions.
-    pub staged_for_commit_state: Option<StagedTransactionalState>,
+    pub staged_tx: Option<StagedTx>,
+    // Set if execution telemetry is registered in the block context; exported on finalization.
+    telemetry: Option<BlockTelemetry>,
 }
 
+// Guarantees at compile time that the executor remains `Send` for `Send` state readers.
+const _: () = {
+    fn assert_send<T: Send>() {}
+    #[allow(dead_code)]
+    fn assert_executor_send<S: StateReader + Send>() {
+        assert_send::<TransactionExecutor<S>>();
+    }
+};
+
 impl<S: StateReader> TransactionExecutor<S> {
     pub fn new(state: CachedState<S>, block_context: BlockContext) -> Self {
         log::debug!("Initializing Transaction Executor...");
+        let telemetry = BlockTelemetry::new(&block_context);
         let tx_executor = Self {
             block_context,
             executed_class_hashes: HashSet::<ClassHash>::new(),
             visited_storage_entries: HashSet::<StorageEntry>::new(),
+            syscall_counter: SyscallCounter::default(),
+            builtin_instance_counter: HashMap::new(),
+            revert_metrics: Arc::new(RevertMetrics::default()),
+            bloom_filters: BlockBloomFilters::default(),
+            audit_log: AuditLog::default(),
+            tx_state_changes: Vec::new(),
+            consumed_l1_to_l2_nonces: Vec::new(),
+            accrued_fees: AccruedFees::default(),
+            n_committed_txs: 0,
+            closure_reason: None,
             // Note: the state might not be empty even at this point; it is the creator's
             // responsibility to tune the bouncer according to pre and post block process.
             state_changes_keys: StateChangesKeys::default(),
             state,
-            staged_for_commit_state: None,
+            staged_tx: None,
+            telemetry,
         };
         log::debug!("Initialized Transaction Executor.");
 
@@ -83,6 +224,42 @@ impl<S: StateReader> TransactionExecutor<S> {
         &mut self,
         tx: Transaction,
         charge_fee: bool,
+    ) -> TransactionExecutorResult<(TransactionExecutionInfo, BouncerInfo)> {
+        let validate = true;
+        self.stage_tx(tx, |tx, state, block_context| {
+            tx.execute_raw(state, block_context, charge_fee, validate)
+        })
+    }
+
+    /// Re-applies the given transaction from the outputs of an earlier, already verified, execution
+    /// (e.g., to catch up with a chain): writes the recorded state changes, without re-executing
+    /// the transaction, and counts the recorded resources for the bouncer. The outputs are
+    /// trusted, and are not checked against the transaction.
+    pub fn replay(
+        &mut self,
+        tx: Transaction,
+        recorded_execution: RecordedExecution,
+    ) -> TransactionExecutorResult<(TransactionExecutionInfo, BouncerInfo)> {
+        self.stage_tx(tx, |tx, state, _block_context| {
+            let RecordedExecution { execution_info, state_changes } = recorded_execution;
+            if let Transaction::AccountTransaction(AccountTransaction::Declare(declare_tx)) = tx {
+                state.set_contract_class(declare_tx.class_hash(), declare_tx.contract_class())?;
+            }
+            state.apply_state_changes(&state_changes);
+            Ok(execution_info)
+        })
+    }
+
+    /// Runs the given transaction over a transactional state, and stages its state changes for
+    /// commit, along with its counts; returns the execution info and the bouncer info.
+    fn stage_tx(
+        &mut self,
+        tx: Transaction,
+        run_tx: impl FnOnce(
+            Transaction,
+            &mut TransactionalState<'_, S>,
+            &BlockContext,
+        ) -> TransactionExecutionResult<TransactionExecutionInfo>,
     ) -> TransactionExecutorResult<(TransactionExecutionInfo, BouncerInfo)> {
         let l1_handler_payload_size: Option<usize> =
             if let Transaction::L1HandlerTransaction(l1_handler_tx) = &tx {
@@ -90,13 +267,19 @@ impl<S: StateReader> TransactionExecutor<S> {
             } else {
                 None
             };
+        let (declared_class_hash, declared_code_size) =
+            if let Transaction::AccountTransaction(AccountTransaction::Declare(declare_tx)) = &tx {
+                (Some(declare_tx.tx().class_hash()), Some(declare_tx.class_info.code_size()))
+            } else {
+                (None, None)
+            };
+        let start_time = SystemTime::now();
+        let tx_hash = tx.tx_hash();
         let mut transactional_state = CachedState::create_transactional(&mut self.state);
-        let validate = true;
 
-        let tx_execution_result =
-            tx.execute_raw(&mut transactional_state, &self.block_context, charge_fee, validate);
+        let tx_execution_result = run_tx(tx, &mut transactional_state, &self.block_context);
         match tx_execution_result {
-            Ok(tx_execution_info) => {
+            Ok(mut tx_execution_info) => {
                 // Prepare bouncer info; the countings here should be linear in the transactional
                 // state changes and execution info rather than the cumulative state attributes.
 
@@ -104,12 +287,15 @@ impl<S: StateReader> TransactionExecutor<S> {
                 let tx_execution_summary = tx_execution_info.summarize();
 
                 // Count message to L1 resources.
-                let call_infos: IntoIter<&CallInfo> =
-                    [&tx_execution_info.validate_call_info, &tx_execution_info.execute_call_info]
-                        .iter()
-                        .filter_map(|&call_info| call_info.as_ref())
-                        .collect::<Vec<&CallInfo>>()
-                        .into_iter();
+                let call_infos: IntoIter<&CallInfo> = [
+                    &tx_execution_info.validate_call_info,
+                    &tx_execution_info.paymaster_call_info,
+                    &tx_execution_info.execute_call_info,
+                ]
+                .iter()
+                .filter_map(|&call_info| call_info.as_ref())
+                .collect::<Vec<&CallInfo>>()
+                .into_iter();
 
                 let message_cost_info =
                     MessageL1CostInfo::calculate(call_infos, l1_handler_payload_size)?;
@@ -129,8 +315,18 @@ impl<S: StateReader> TransactionExecutor<S> {
                 )?;
 
                 // Count residual state diff size (w.r.t. the OS output encoding).
-                let tx_state_changes_keys =
-                    transactional_state.get_actual_state_changes()?.into_keys();
+                let tx_state_changes = transactional_state.get_actual_state_changes()?;
+                let tx_audit_entries = self
+                    .block_context
+                    .extensions()
+                    .contains::<RecordAuditLog>()
+                    .then(|| tx_audit_entries(&tx_execution_info, &tx_state_changes));
+                let recorded_state_changes = self
+                    .block_context
+                    .extensions()
+                    .contains::<RecordTxStateChanges>()
+                    .then(|| tx_state_changes.clone());
+                let tx_state_changes_keys = tx_state_changes.into_keys();
                 let tx_unique_state_changes_keys =
                     tx_state_changes_keys.difference(&self.state_changes_keys);
                 // Note: block-constant felts are not counted here. so the bouncer needs to
@@ -147,22 +343,84 @@ impl<S: StateReader> TransactionExecutor<S> {
                     message_cost_info.message_segment_length,
                     state_diff_size,
                     tx_execution_summary.n_events,
+                    tx_execution_summary.syscall_counter.clone(),
+                    declared_code_size,
                 )?;
-                self.staged_for_commit_state = Some(transactional_state.stage(
-                    tx_execution_summary.executed_class_hashes,
-                    tx_execution_summary.visited_storage_entries,
-                    tx_unique_state_changes_keys,
-                ));
-
+                let outcome =
+                    if tx_execution_info.is_reverted() { "reverted" } else { "succeeded" };
+                let accrued_fee = match (
+                    FeeAccumulator::for_block(&self.block_context),
+                    &tx_execution_info.fee_transfer_call_info,
+                ) {
+                    // The fee transfer is a call to the fee token contract.
+                    (Some(accumulator), Some(fee_transfer_call_info)) => Some((
+                        fee_transfer_call_info.call.storage_address,
+                        accumulator,
+                        tx_execution_info.actual_fee,
+                    )),
+                    _ => None,
+                };
+                let mut tx_bloom_filters = BlockBloomFilters::default();
+                tx_bloom_filters.add_execution_info(&tx_execution_info);
+                if let Some(class_hash) = declared_class_hash {
+                    tx_bloom_filters.add_declared_class(class_hash);
+                }
+                self.staged_tx = Some(StagedTx {
+                    state: transactional_state.stage(
+                        tx_execution_summary.executed_class_hashes,
+                        tx_execution_summary.visited_storage_entries,
+                        tx_unique_state_changes_keys,
+                        tx_execution_summary.syscall_counter,
+                    ),
+                    bloom_filters: tx_bloom_filters,
+                    audit_entries: tx_audit_entries,
+                    state_changes: recorded_state_changes,
+                    consumed_l1_to_l2_nonce: tx_execution_info
+                        .message_resources
+                        .consumed_l1_to_l2_nonce,
+                    builtin_instance_counter: bouncer_info
+                        .execution_resources
+                        .builtin_instance_counter
+                        .clone(),
+                    revert_reason: tx_execution_info.revert_reason,
+                    accrued_fee,
+                    span: self.tx_span(tx_hash, start_time, outcome),
+                });
+
+                if MemoryLimits::is_traceless(&self.block_context) {
+                    tx_execution_info.drop_traces();
+                }
                 Ok((tx_execution_info, bouncer_info))
             }
             Err(error) => {
+                self.revert_metrics.record_rejection(&error);
                 transactional_state.abort();
+                // Rejected transactions are not staged; their span is recorded right away.
+                let span = self.tx_span(tx_hash, start_time, "rejected");
+                if let (Some(telemetry), Some(span)) = (&mut self.telemetry, span) {
+                    telemetry.record_span(span);
+                }
                 Err(TransactionExecutorError::TransactionExecutionError(error))
             }
         }
     }
 
+    /// Returns the execution span of a transaction, if telemetry is registered.
+    fn tx_span(
+        &self,
+        tx_hash: TransactionHash,
+        start_time: SystemTime,
+        outcome: &str,
+    ) -> Option<ExecutionSpan> {
+        self.telemetry.as_ref()?;
+        let tx_hash = format!("{:#x}", stark_felt_to_felt(tx_hash.0).to_biguint());
+        Some(
+            ExecutionSpan::ending_now("execute_transaction", start_time)
+                .with_attribute("tx_hash", AttributeValue::String(tx_hash))
+                .with_attribute("outcome", AttributeValue::String(outcome.to_string())),
+        )
+    }
+
     pub fn validate(
         &mut self,
         account_tx: &AccountTransaction,
@@ -226,10 +484,74 @@ impl<S: StateReader> TransactionExecutor<S> {
         Ok((self.state.to_state_diff(), visited_segments))
     }
 
+    /// Finalizes the block: moves the fees charged to fee accumulators to the fee recipients of the
+    /// block, applies the given end-of-block system operations to the state (in order), and
+    /// summarizes the block. Well-defined for blocks with no transactions.
+    pub fn finalize_block(
+        &mut self,
+        is_pending_block: bool,
+        end_of_block_hooks: &[&dyn EndOfBlockHook],
+    ) -> TransactionExecutorResult<BlockSummary> {
+        assert!(
+            self.staged_tx.is_none(),
+            "finalize_block called with a transaction awaiting commit/abort"
+        );
+        let start_time = SystemTime::now();
+        let consolidate_fees = ConsolidateFees(std::mem::take(&mut self.accrued_fees));
+        consolidate_fees.apply(&mut self.state, &self.block_context)?;
+        for hook in end_of_block_hooks {
+            hook.apply(&mut self.state, &self.block_context)?;
+        }
+
+        // Counted from the state (rather than the committed transactions), to include the
+        // end-of-block system operations.
+        let state_changes_count = self.state.get_actual_state_changes()?.into_keys().count();
+        let bouncer_report = BlockBouncerReport {
+            state_changes_count,
+            estimated_da_bytes: encoded_state_diff_length(&state_changes_count) * DA_BYTES_PER_FELT,
+            n_executed_classes: self.executed_class_hashes.len(),
+            n_visited_storage_entries: self.visited_storage_entries.len(),
+            syscall_counter: self.syscall_counter.clone(),
+            builtin_instance_counter: self.builtin_instance_counter.clone(),
+        };
+
+        let (state_diff, visited_segments) = self.finalize(is_pending_block)?;
+        let state_diff_commitment = state_diff.commitment();
+
+        let summary = BlockSummary {
+            n_committed_txs: self.n_committed_txs,
+            state_diff,
+            state_diff_commitment,
+            visited_segments,
+            bloom_filters: self.bloom_filters.clone(),
+            bouncer_report,
+            revert_metrics: self.revert_metrics.summary(),
+            consumed_l1_to_l2_nonces: self.consumed_l1_to_l2_nonces.clone(),
+            closure_reason: self.closure_reason,
+        };
+        if let Some(mut telemetry) = self.telemetry.take() {
+            telemetry.record_span(ExecutionSpan::ending_now("finalize_block", start_time));
+            telemetry.export(&summary);
+        }
+
+        Ok(summary)
+    }
+
     pub fn commit(&mut self) {
-        let Some(finalized_transactional_state) = self.staged_for_commit_state.take() else {
-            panic!("commit called without a transactional state")
+        let Some(staged_tx) = self.staged_tx.take() else {
+            panic!("commit called without a staged transaction")
         };
+        let StagedTx {
+            state: finalized_transactional_state,
+            bloom_filters: tx_bloom_filters,
+            audit_entries: tx_audit_entries,
+            state_changes: tx_state_changes,
+            consumed_l1_to_l2_nonce,
+            builtin_instance_counter: tx_builtin_instance_counter,
+            revert_reason,
+            accrued_fee,
+            span,
+        } = staged_tx;
 
         let child_cache = finalized_transactional_state.cache;
         self.state.update_cache(child_cache);
@@ -242,16 +564,53 @@ impl<S: StateReader> TransactionExecutor<S> {
         self.executed_class_hashes.extend(&finalized_transactional_state.tx_executed_class_hashes);
         self.visited_storage_entries
             .extend(&finalized_transactional_state.tx_visited_storage_entries);
+        add_mappings(&mut self.syscall_counter, &finalized_transactional_state.tx_syscall_counter);
 
         // Note: cancelling writes (0 -> 1 -> 0) will not be removed,
         // but it's fine since fee was charged for them.
         self.state_changes_keys.extend(&finalized_transactional_state.tx_unique_state_changes_keys);
-
-        self.staged_for_commit_state = None
+        self.bloom_filters.merge(&tx_bloom_filters);
+        if let Some(tx_audit_entries) = tx_audit_entries {
+            self.audit_log.append_tx(self.n_committed_txs, tx_audit_entries);
+        }
+        self.tx_state_changes.extend(tx_state_changes);
+        self.consumed_l1_to_l2_nonces.extend(consumed_l1_to_l2_nonce);
+        add_mappings(&mut self.builtin_instance_counter, &tx_builtin_instance_counter);
+        if let Some(revert_reason) = revert_reason {
+            self.revert_metrics.record_revert(revert_reason);
+        }
+        if let Some((fee_token_address, accumulator, fee)) = accrued_fee {
+            self.accrued_fees.add(fee_token_address, accumulator, fee);
+        }
+        if let (Some(telemetry), Some(span)) = (&mut self.telemetry, span) {
+            telemetry.record_span(span);
+        }
+        self.n_committed_txs += 1;
     }
 
     pub fn abort(&mut self) {
-        self.staged_for_commit_state = None
+        self.staged_tx = None;
+    }
+
+    /// Returns the nonces of the L1-to-L2 messages consumed by the committed transactions of the
+    /// block, in execution order.
+    pub fn consumed_l1_to_l2_nonces(&self) -> &[Nonce] {
+        &self.consumed_l1_to_l2_nonces
+    }
+
+    /// Returns the audit records of the committed transactions of the block, in order; empty unless
+    /// `RecordAuditLog` is registered in the block context.
+    pub fn audit_records(&self) -> impl Iterator<Item = &AuditRecord> {
+        self.audit_log.iter()
+    }
+
+    /// Returns the state changes of each committed transaction of the block, in execution order
+    /// (e.g., for per-transaction state diffs of indexers); empty unless `RecordTxStateChanges` is
+    /// registered in the block context. Merging them (see
+    /// `StateChanges::merge`) yields the block's state changes, excluding end-of-block system
+    /// operations, and up to writes cancelled by later transactions (e.g., 0 -> 1 -> 0).
+    pub fn tx_state_changes(&self) -> &[StateChanges] {
+        &self.tx_state_changes
     }
 }
 
@@ -1,27 +1,43 @@
+use std::collections::HashMap;
+
+use cairo_vm::serde::deserialize_program::BuiltinName;
 use pretty_assertions::assert_eq;
 use rstest::rstest;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
+use starknet_api::state::StorageKey;
 use starknet_api::transaction::{Fee, TransactionVersion};
 
+use crate::abi::constants;
+use crate::blockifier::audit_log::{AuditEntry, RecordAuditLog};
+use crate::blockifier::block::{BlockNumberHashPair, StoreBlockHash};
 use crate::blockifier::bouncer::BouncerInfo;
-use crate::blockifier::transaction_executor::TransactionExecutor;
+use crate::blockifier::transaction_executor::{
+    DynTransactionExecutor, RecordTxStateChanges, RecordedExecution, TransactionExecutor,
+};
 use crate::context::BlockContext;
-use crate::state::cached_state::CachedState;
-use crate::state::state_api::StateReader;
+use crate::fee::fee_sharding::FeeAccumulator;
+use crate::state::cached_state::{CachedState, StateChanges};
+use crate::state::state_api::{DynStateReader, StateReader};
 use crate::test_utils::contracts::FeatureContract;
 use crate::test_utils::declare::declare_tx;
 use crate::test_utils::deploy_account::deploy_account_tx;
 use crate::test_utils::initial_test_state::test_state;
 use crate::test_utils::{
-    create_calldata, CairoVersion, NonceManager, BALANCE, DEFAULT_STRK_L1_GAS_PRICE,
+    create_calldata, create_trivial_calldata, CairoVersion, NonceManager, BALANCE,
+    DEFAULT_STRK_L1_GAS_PRICE,
 };
 use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::objects::{HasRelatedFeeType, RevertReason};
 use crate::transaction::test_utils::{
     account_invoke_tx, block_context, calculate_class_info_for_testing, l1_resource_bounds,
+    trivial_invoke_setup,
 };
 use crate::transaction::transaction_execution::Transaction;
-use crate::transaction::transactions::L1HandlerTransaction;
+use crate::transaction::transactions::{ExecutableTransaction, L1HandlerTransaction};
+use crate::utils::add_mappings;
 use crate::{declare_tx_args, deploy_account_tx_args, invoke_tx_args};
 
 fn tx_executor_test_body<S: StateReader>(
@@ -40,6 +56,8 @@ fn tx_executor_test_body<S: StateReader>(
     assert_eq!(bouncer_info.state_diff_size, expected_bouncer_info.state_diff_size);
     assert_eq!(bouncer_info.message_segment_length, expected_bouncer_info.message_segment_length);
     assert_eq!(bouncer_info.n_events, expected_bouncer_info.n_events);
+    assert_eq!(bouncer_info.n_declares, expected_bouncer_info.n_declares);
+    assert_eq!(bouncer_info.declared_code_size, expected_bouncer_info.declared_code_size);
 }
 
 #[rstest]
@@ -95,6 +113,12 @@ fn test_declare(
     let declared_contract = FeatureContract::Empty(cairo_version);
     let state = test_state(&block_context.chain_info, BALANCE, &[(account_contract, 1)]);
 
+    let class_info = calculate_class_info_for_testing(declared_contract.get_class());
+    let expected_bouncer_info = BouncerInfo {
+        n_declares: 1,
+        declared_code_size: class_info.code_size(),
+        ..expected_bouncer_info
+    };
     let tx = Transaction::AccountTransaction(declare_tx(
         declare_tx_args! {
             sender_address: account_contract.get_instance_address(0),
@@ -102,7 +126,7 @@ fn test_declare(
             version: transaction_version,
             resource_bounds: l1_resource_bounds(0, DEFAULT_STRK_L1_GAS_PRICE),
         },
-        calculate_class_info_for_testing(declared_contract.get_class()),
+        class_info,
     ));
     tx_executor_test_body(state, block_context, tx, charge_fee, expected_bouncer_info);
 }
@@ -216,3 +240,329 @@ fn test_l1_handler(block_context: BlockContext, #[values(true, false)] charge_fe
     };
     tx_executor_test_body(state, block_context, tx, charge_fee, expected_bouncer_info);
 }
+
+#[rstest]
+fn test_executor_moved_between_threads(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let state = test_state(&block_context.chain_info, BALANCE, &[(test_contract, 1)]);
+    let state_reader: DynStateReader = Box::new(state.state);
+    let mut tx_executor: DynTransactionExecutor =
+        TransactionExecutor::new(CachedState::from(state_reader), block_context);
+
+    let tx = Transaction::L1HandlerTransaction(L1HandlerTransaction::create_for_testing(
+        Fee(1908000000000000),
+        test_contract.get_instance_address(0),
+    ));
+    let tx_executor = std::thread::spawn(move || {
+        tx_executor.execute(tx, true).unwrap();
+        tx_executor.commit();
+        tx_executor
+    })
+    .join()
+    .unwrap();
+    assert!(tx_executor.staged_tx.is_none());
+}
+
+#[rstest]
+fn test_consumed_l1_to_l2_nonces(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let state = test_state(&block_context.chain_info, BALANCE, &[(test_contract, 1)]);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let l1_handler_tx = |nonce: u8| {
+        let mut tx = L1HandlerTransaction::create_for_testing(
+            Fee(1908000000000000),
+            test_contract.get_instance_address(0),
+        );
+        tx.tx.nonce = Nonce(stark_felt!(nonce));
+        Transaction::L1HandlerTransaction(tx)
+    };
+
+    let (tx_execution_info, _) = tx_executor.execute(l1_handler_tx(7), true).unwrap();
+    assert_eq!(
+        tx_execution_info.message_resources.consumed_l1_to_l2_nonce,
+        Some(Nonce(stark_felt!(7_u8)))
+    );
+    tx_executor.commit();
+    // Aborted transactions do not consume their messages.
+    tx_executor.execute(l1_handler_tx(8), true).unwrap();
+    tx_executor.abort();
+    tx_executor.execute(l1_handler_tx(9), true).unwrap();
+    tx_executor.commit();
+
+    let expected_nonces = vec![Nonce(stark_felt!(7_u8)), Nonce(stark_felt!(9_u8))];
+    assert_eq!(tx_executor.consumed_l1_to_l2_nonces(), expected_nonces.as_slice());
+    let summary = tx_executor.finalize_block(false, &[]).unwrap();
+    assert_eq!(summary.consumed_l1_to_l2_nonces, expected_nonces);
+}
+
+#[rstest]
+fn test_finalize_empty_block(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let state = test_state(&block_context.chain_info, BALANCE, &[(test_contract, 1)]);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+
+    let block_number_and_hash =
+        BlockNumberHashPair { number: BlockNumber(0), hash: BlockHash(stark_felt!("0x1234")) };
+    let summary =
+        tx_executor.finalize_block(false, &[&StoreBlockHash(block_number_and_hash)]).unwrap();
+
+    assert_eq!(summary.n_committed_txs, 0);
+    let block_hash_contract_address = ContractAddress::from(constants::BLOCK_HASH_CONTRACT_ADDRESS);
+    assert_eq!(
+        summary.state_diff.storage_updates[&block_hash_contract_address][&StorageKey::from(0_u64)],
+        stark_felt!("0x1234")
+    );
+    assert_eq!(summary.bouncer_report.state_changes_count.n_storage_updates, 1);
+    assert_eq!(summary.bouncer_report.state_changes_count.n_modified_contracts, 1);
+    assert!(summary.bouncer_report.estimated_da_bytes > 0);
+    assert!(summary.visited_segments.is_empty());
+    assert_eq!(summary.closure_reason, None);
+}
+
+#[rstest]
+fn test_consolidate_accrued_fees(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let chain_info = block_context.chain_info.clone();
+    let sequencer_address = block_context.block_info.sequencer_address;
+    let state = test_state(&chain_info, BALANCE, &[(test_contract, 1), (account_contract, 1)]);
+    let accumulator = FeeAccumulator::new(0);
+    block_context.extensions().insert(accumulator);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let sender_address = account_contract.get_instance_address(0);
+    let mut nonce_manager = NonceManager::default();
+    let invoke_tx = |nonce| {
+        account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+            nonce,
+        })
+    };
+    let fee_token_address = chain_info.fee_token_address(&invoke_tx(Nonce::default()).fee_type());
+
+    let tx = Transaction::AccountTransaction(invoke_tx(nonce_manager.next(sender_address)));
+    let (tx_execution_info, _) = tx_executor.execute(tx, true).unwrap();
+    tx_executor.commit();
+    // Aborted transactions accrue no fees.
+    let tx = Transaction::AccountTransaction(invoke_tx(nonce_manager.next(sender_address)));
+    tx_executor.execute(tx, true).unwrap();
+    tx_executor.abort();
+    assert_eq!(
+        tx_executor.state.get_fee_token_balance(sequencer_address, fee_token_address).unwrap(),
+        (StarkFelt::ZERO, StarkFelt::ZERO)
+    );
+
+    tx_executor.finalize_block(false, &[]).unwrap();
+    let actual_fee = tx_execution_info.actual_fee.0;
+    for (address, expected_balance) in [(sequencer_address, actual_fee), (accumulator.address, 0)] {
+        assert_eq!(
+            tx_executor.state.get_fee_token_balance(address, fee_token_address).unwrap(),
+            (stark_felt!(expected_balance), StarkFelt::ZERO)
+        );
+    }
+}
+
+#[rstest]
+fn test_builtin_instance_counter(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let sender_address = account_contract.get_instance_address(0);
+    let mut nonce_manager = NonceManager::default();
+    let invoke_tx = |nonce| {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+            nonce,
+        }))
+    };
+
+    let mut expected_counter = HashMap::new();
+    for _ in 0..2 {
+        let (_, bouncer_info) =
+            tx_executor.execute(invoke_tx(nonce_manager.next(sender_address)), true).unwrap();
+        tx_executor.commit();
+        add_mappings(
+            &mut expected_counter,
+            &bouncer_info.execution_resources.builtin_instance_counter,
+        );
+    }
+    // Aborted transactions are not counted.
+    tx_executor.execute(invoke_tx(nonce_manager.next(sender_address)), true).unwrap();
+    tx_executor.abort();
+
+    let summary = tx_executor.finalize_block(false, &[]).unwrap();
+    // The OS uses builtins for every transaction.
+    assert!(expected_counter[BuiltinName::range_check.name()] > 0);
+    assert_eq!(summary.bouncer_report.builtin_instance_counter, expected_counter);
+}
+
+#[rstest]
+fn test_audit_log(block_context: BlockContext, #[values(false, true)] record_audit_log: bool) {
+    if record_audit_log {
+        block_context.extensions().insert(RecordAuditLog);
+    }
+    let (state, invoke_args) =
+        trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo1);
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let sender_address = invoke_args.sender_address;
+    let mut nonce_manager = NonceManager::default();
+    let invoke_tx = |nonce| {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            nonce,
+            ..invoke_args.clone()
+        }))
+    };
+
+    tx_executor.execute(invoke_tx(nonce_manager.next(sender_address)), true).unwrap();
+    tx_executor.commit();
+    // Aborted transactions are not logged.
+    tx_executor.execute(invoke_tx(nonce_manager.next(sender_address)), true).unwrap();
+    tx_executor.abort();
+    assert!(tx_executor.audit_records().all(|record| record.tx_index == 0));
+    nonce_manager.rollback(sender_address);
+    tx_executor.execute(invoke_tx(nonce_manager.next(sender_address)), true).unwrap();
+    tx_executor.commit();
+
+    let records: Vec<_> = tx_executor.audit_records().collect();
+    if !record_audit_log {
+        // The audit log is opt-in.
+        assert!(records.is_empty());
+        return;
+    }
+    let sequence_numbers: Vec<u64> = records.iter().map(|record| record.sequence_number).collect();
+    assert_eq!(sequence_numbers, (0..u64::try_from(records.len()).unwrap()).collect::<Vec<_>>());
+    for (tx_index, nonce) in [(0, 1_u8), (1, 2_u8)] {
+        let tx_entries: Vec<&AuditEntry> = records
+            .iter()
+            .filter(|record| record.tx_index == tx_index)
+            .map(|record| &record.entry)
+            .collect();
+        let fee_payers: Vec<ContractAddress> = tx_entries
+            .iter()
+            .filter_map(|entry| match entry {
+                AuditEntry::FeeTransfer { payer, .. } => Some(*payer),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fee_payers, vec![sender_address]);
+        let nonce_update = AuditEntry::NonceUpdate {
+            contract_address: sender_address,
+            nonce: Nonce(stark_felt!(nonce)),
+        };
+        assert!(tx_entries.contains(&&nonce_update));
+    }
+}
+
+#[rstest]
+fn test_replay(block_context: BlockContext) {
+    let setup = || trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo1);
+    let new_state = || setup().0;
+    let invoke_args = setup().1;
+    let invoke_tx = || Transaction::AccountTransaction(account_invoke_tx(invoke_args.clone()));
+    let (charge_fee, validate) = (true, true);
+
+    // Record an execution.
+    let mut state = new_state();
+    let mut transactional_state = CachedState::create_transactional(&mut state);
+    let execution_info = invoke_tx()
+        .execute_raw(&mut transactional_state, &block_context, charge_fee, validate)
+        .unwrap();
+    let state_changes = transactional_state.get_actual_state_changes().unwrap();
+    transactional_state.abort();
+
+    let mut tx_executor = TransactionExecutor::new(new_state(), block_context.clone());
+    let executed = tx_executor.execute(invoke_tx(), charge_fee).unwrap();
+    tx_executor.commit();
+    let mut replay_tx_executor = TransactionExecutor::new(new_state(), block_context);
+    let recorded_execution = RecordedExecution { execution_info, state_changes };
+    let replayed = replay_tx_executor.replay(invoke_tx(), recorded_execution).unwrap();
+    replay_tx_executor.commit();
+
+    // The replay is counted and applied as the execution.
+    assert_eq!(replayed, executed);
+    let is_pending_block = false;
+    assert_eq!(
+        replay_tx_executor.finalize(is_pending_block).unwrap().0,
+        tx_executor.finalize(is_pending_block).unwrap().0
+    );
+}
+
+#[rstest]
+fn test_tx_state_changes(
+    block_context: BlockContext,
+    #[values(false, true)] record_tx_state_changes: bool,
+) {
+    if record_tx_state_changes {
+        block_context.extensions().insert(RecordTxStateChanges);
+    }
+    let (state, invoke_args) =
+        trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo1);
+    let sender_address = invoke_args.sender_address;
+    let invoke_tx = |nonce: u8| {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            nonce: Nonce(stark_felt!(nonce)),
+            ..invoke_args.clone()
+        }))
+    };
+    let charge_fee = true;
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+
+    for nonce in 0..2 {
+        tx_executor.execute(invoke_tx(nonce), charge_fee).unwrap();
+        tx_executor.commit();
+    }
+    // Aborted transactions are not recorded.
+    tx_executor.execute(invoke_tx(2), charge_fee).unwrap();
+    tx_executor.abort();
+
+    let tx_state_changes = tx_executor.tx_state_changes();
+    if !record_tx_state_changes {
+        // The per-transaction state changes are opt-in.
+        assert!(tx_state_changes.is_empty());
+        return;
+    }
+    assert_eq!(tx_state_changes.len(), 2);
+    for (nonce, state_changes) in (1_u8..).zip(tx_state_changes) {
+        assert_eq!(state_changes.nonce_updates[&sender_address], Nonce(stark_felt!(nonce)));
+    }
+    assert_eq!(
+        StateChanges::merge(tx_state_changes.to_vec()),
+        tx_executor.state.get_actual_state_changes().unwrap()
+    );
+}
+
+#[rstest]
+fn test_revert_metrics_counted_on_commit(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let state = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let mut tx_executor = TransactionExecutor::new(state, block_context);
+    let sender_address = account_contract.get_instance_address(0);
+    let reverted_tx = |nonce: u8| {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: create_calldata(test_contract.get_instance_address(0), "fail", &[]),
+            nonce: Nonce(stark_felt!(nonce)),
+        }))
+    };
+
+    // An aborted transaction (e.g., retried in the next block) is not counted.
+    let (tx_execution_info, _) = tx_executor.execute(reverted_tx(0), true).unwrap();
+    assert!(tx_execution_info.is_reverted());
+    tx_executor.abort();
+    tx_executor.execute(reverted_tx(0), true).unwrap();
+    tx_executor.commit();
+
+    let revert_metrics = tx_executor.finalize_block(false, &[]).unwrap().revert_metrics;
+    assert_eq!(revert_metrics.n_reverted, HashMap::from([(RevertReason::Panic, 1)]));
+}
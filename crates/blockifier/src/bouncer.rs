@@ -1,10 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
+use cairo_vm::vm::runners::builtin_runner::{
+    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
+    POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
+};
 use serde::Deserialize;
 use starknet_api::core::ClassHash;
+use thiserror::Error;
 
-use crate::blockifier::transaction_executor::TransactionExecutorResult;
+use crate::blockifier::bouncer::BouncerInfo;
+use crate::blockifier::transaction_executor::{
+    TransactionExecutorError, TransactionExecutorResult,
+};
 use crate::state::cached_state::{StateChangesKeys, StorageEntry, TransactionalState};
+use crate::state::da_encoding::{encoded_state_diff_length, DA_BYTES_PER_FELT};
 use crate::state::state_api::StateReader;
 use crate::transaction::objects::TransactionExecutionInfo;
 
@@ -26,29 +35,85 @@ macro_rules! impl_checked_sub {
     };
 }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Sub, Deserialize, PartialEq)]
+#[derive(
+    Clone, Copy, Debug, Default, derive_more::Add, derive_more::Sub, Deserialize, PartialEq,
+)]
 /// Represents the execution resources counted throughout block creation.
 pub struct BouncerWeights {
     builtin_count: BuiltinCount,
+    // The size of the declared classes, in bytes; unlimited if absent from the config.
+    #[serde(default = "unlimited")]
+    declared_code_size: usize,
     gas: usize,
     message_segment_length: usize,
+    // Unlimited if absent from the config.
+    #[serde(default = "unlimited")]
+    n_declares: usize,
     n_events: usize,
     n_steps: usize,
+    // The number of syscalls invoked, of any kind; unlimited if absent from the config.
+    #[serde(default = "unlimited")]
+    n_syscalls: usize,
     state_diff_size: usize,
 }
 
+fn unlimited() -> usize {
+    usize::MAX
+}
+
 impl BouncerWeights {
     impl_checked_sub!(
         builtin_count,
+        declared_code_size,
         gas,
         message_segment_length,
+        n_declares,
         n_events,
         n_steps,
+        n_syscalls,
         state_diff_size
     );
+
+    pub fn max() -> Self {
+        Self {
+            builtin_count: BuiltinCount::max(),
+            declared_code_size: usize::MAX,
+            gas: usize::MAX,
+            message_segment_length: usize::MAX,
+            n_declares: usize::MAX,
+            n_events: usize::MAX,
+            n_steps: usize::MAX,
+            n_syscalls: usize::MAX,
+            state_diff_size: usize::MAX,
+        }
+    }
+
+    pub fn n_steps(&self) -> usize {
+        self.n_steps
+    }
+}
+
+impl From<&BouncerInfo> for BouncerWeights {
+    fn from(bouncer_info: &BouncerInfo) -> Self {
+        Self {
+            builtin_count: BuiltinCount::from(
+                &bouncer_info.execution_resources.builtin_instance_counter,
+            ),
+            declared_code_size: bouncer_info.declared_code_size,
+            gas: bouncer_info.gas_weight,
+            message_segment_length: bouncer_info.message_segment_length,
+            n_declares: bouncer_info.n_declares,
+            n_events: bouncer_info.n_events,
+            n_steps: bouncer_info.execution_resources.n_steps,
+            n_syscalls: bouncer_info.syscall_counter.values().sum(),
+            state_diff_size: bouncer_info.state_diff_size,
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Sub, Deserialize, PartialEq)]
+#[derive(
+    Clone, Copy, Debug, Default, derive_more::Add, derive_more::Sub, Deserialize, PartialEq,
+)]
 pub struct BuiltinCount {
     bitwise: usize,
     ecdsa: usize,
@@ -61,15 +126,82 @@ pub struct BuiltinCount {
 
 impl BuiltinCount {
     impl_checked_sub!(bitwise, ecdsa, ec_op, keccak, pedersen, poseidon, range_check);
+
+    pub fn max() -> Self {
+        Self {
+            bitwise: usize::MAX,
+            ecdsa: usize::MAX,
+            ec_op: usize::MAX,
+            keccak: usize::MAX,
+            pedersen: usize::MAX,
+            poseidon: usize::MAX,
+            range_check: usize::MAX,
+        }
+    }
+}
+
+impl From<&HashMap<String, usize>> for BuiltinCount {
+    fn from(builtin_instance_counter: &HashMap<String, usize>) -> Self {
+        let count = |name: &str| builtin_instance_counter.get(name).copied().unwrap_or_default();
+        Self {
+            bitwise: count(BITWISE_BUILTIN_NAME),
+            ecdsa: count(SIGNATURE_BUILTIN_NAME),
+            ec_op: count(EC_OP_BUILTIN_NAME),
+            keccak: count(KECCAK_BUILTIN_NAME),
+            pedersen: count(HASH_BUILTIN_NAME),
+            poseidon: count(POSEIDON_BUILTIN_NAME),
+            range_check: count(RANGE_CHECK_BUILTIN_NAME),
+        }
+    }
+}
+
+/// The block capacity limits; part of the versioned constants.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct BouncerConfig {
+    pub block_max_capacity: BouncerWeights,
+}
+
+impl BouncerConfig {
+    pub fn max() -> Self {
+        Self { block_max_capacity: BouncerWeights::max() }
+    }
+}
+
+impl Default for BouncerConfig {
+    fn default() -> Self {
+        Self::max()
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error(
+    "Transaction weights {tx_weights:?} exceed the remaining block capacity \
+     {remaining_capacity:?}."
+)]
+pub struct BlockFullError {
+    pub tx_weights: BouncerWeights,
+    pub remaining_capacity: BouncerWeights,
 }
 
+/// Identifies the tenant (e.g., an appchain) a transaction belongs to, when a single blockifier
+/// instance is shared by several tenants.
+#[derive(Clone, Debug, Deserialize, derive_more::Display, Eq, Hash, PartialEq)]
+pub struct TenantId(pub String);
+
 #[derive(Clone)]
 pub struct Bouncer {
     pub executed_class_hashes: HashSet<ClassHash>,
     pub visited_storage_entries: HashSet<StorageEntry>,
     pub state_changes_keys: StateChangesKeys,
+    // The cumulative weights of the transactions of each tenant in the block.
+    pub tenant_usage: HashMap<TenantId, BouncerWeights>,
     // The capacity is calculated based of the values of the other Bouncer fields.
     capacity: BouncerWeights,
+    // The cumulative weights of the transactions in the block.
+    accumulated_weights: BouncerWeights,
+    // Per-block caps on the cumulative weights of each tenant; tenants without a quota are only
+    // bounded by the block capacity.
+    tenant_quotas: HashMap<TenantId, BouncerWeights>,
 }
 
 impl Bouncer {
@@ -78,10 +210,63 @@ impl Bouncer {
             executed_class_hashes: HashSet::new(),
             state_changes_keys: StateChangesKeys::default(),
             visited_storage_entries: HashSet::new(),
+            tenant_usage: HashMap::new(),
             capacity,
+            accumulated_weights: BouncerWeights::default(),
+            tenant_quotas: HashMap::new(),
         }
     }
 
+    pub fn from_config(config: &BouncerConfig) -> Self {
+        Self::new(config.block_max_capacity)
+    }
+
+    pub fn accumulated_weights(&self) -> BouncerWeights {
+        self.accumulated_weights
+    }
+
+    pub fn remaining_capacity(&self) -> BouncerWeights {
+        self.capacity.checked_sub(self.accumulated_weights).unwrap_or_default()
+    }
+
+    /// Accounts the resources of a transaction to the block, failing (without accounting them) if
+    /// they exceed the remaining block capacity.
+    pub fn try_update(&mut self, tx_resources: &BouncerInfo) -> Result<(), BlockFullError> {
+        let tx_weights = BouncerWeights::from(tx_resources);
+        self.accumulated_weights = self.weights_after(tx_weights)?;
+        Ok(())
+    }
+
+    fn weights_after(&self, tx_weights: BouncerWeights) -> Result<BouncerWeights, BlockFullError> {
+        let remaining_capacity = self.remaining_capacity();
+        if remaining_capacity.checked_sub(tx_weights).is_none() {
+            return Err(BlockFullError { tx_weights, remaining_capacity });
+        }
+
+        Ok(self.accumulated_weights + tx_weights)
+    }
+
+    pub fn with_tenant_quotas(mut self, tenant_quotas: HashMap<TenantId, BouncerWeights>) -> Self {
+        self.tenant_quotas = tenant_quotas;
+        self
+    }
+
+    pub fn tenant_usage(&self, tenant_id: &TenantId) -> BouncerWeights {
+        self.tenant_usage.get(tenant_id).copied().unwrap_or_default()
+    }
+
+    /// Returns the weights the tenant may still use in this block, if it has a quota.
+    pub fn tenant_remaining_quota(&self, tenant_id: &TenantId) -> Option<BouncerWeights> {
+        let quota = self.tenant_quotas.get(tenant_id)?;
+        Some(quota.checked_sub(self.tenant_usage(tenant_id)).unwrap_or_default())
+    }
+
+    /// Returns the number of bytes the block state diff, accumulated so far, occupies in the DA
+    /// encoding. Note: cancelling writes (0 -> 1 -> 0) are not deducted.
+    pub fn estimated_da_bytes(&self) -> usize {
+        encoded_state_diff_length(&self.state_changes_keys.count()) * DA_BYTES_PER_FELT
+    }
+
     pub fn create_transactional(self) -> TransactionalBouncer {
         TransactionalBouncer::new(self)
     }
@@ -90,6 +275,11 @@ impl Bouncer {
         self.executed_class_hashes.extend(other.executed_class_hashes);
         self.state_changes_keys.extend(&other.state_changes_keys);
         self.visited_storage_entries.extend(other.visited_storage_entries);
+        for (tenant_id, weights) in other.tenant_usage {
+            let usage = self.tenant_usage.entry(tenant_id).or_default();
+            *usage = *usage + weights;
+        }
+        self.accumulated_weights = self.accumulated_weights + other.accumulated_weights;
         self.capacity = other.capacity;
     }
 }
@@ -105,10 +295,43 @@ pub struct TransactionalBouncer {
 impl TransactionalBouncer {
     pub fn new(parent: Bouncer) -> TransactionalBouncer {
         let capacity = parent.capacity;
-        TransactionalBouncer { bouncer: parent, transactional: Bouncer::new(capacity) }
+        let tenant_quotas = parent.tenant_quotas.clone();
+        TransactionalBouncer {
+            bouncer: parent,
+            transactional: Bouncer::new(capacity).with_tenant_quotas(tenant_quotas),
+        }
     }
 
-    // TODO update function (in the next PR)
+    /// Accounts the weights of a transaction to its tenant, failing if this exceeds the tenant's
+    /// quota for the block.
+    pub fn update_tenant_usage(
+        &mut self,
+        tenant_id: &TenantId,
+        tx_weights: BouncerWeights,
+    ) -> TransactionExecutorResult<()> {
+        let tx_usage = self.transactional.tenant_usage(tenant_id) + tx_weights;
+        if let Some(quota) = self.bouncer.tenant_quotas.get(tenant_id) {
+            let block_usage = self.bouncer.tenant_usage(tenant_id) + tx_usage;
+            if quota.checked_sub(block_usage).is_none() {
+                return Err(TransactionExecutorError::TenantQuotaExceeded {
+                    tenant_id: tenant_id.clone(),
+                });
+            }
+        }
+
+        self.transactional.tenant_usage.insert(tenant_id.clone(), tx_usage);
+        Ok(())
+    }
+
+    /// Accounts the resources of the transaction to the block, failing (without accounting them)
+    /// if they exceed the remaining block capacity.
+    pub fn try_update(&mut self, tx_resources: &BouncerInfo) -> Result<(), BlockFullError> {
+        let tx_weights =
+            self.transactional.accumulated_weights + BouncerWeights::from(tx_resources);
+        self.bouncer.weights_after(tx_weights)?;
+        self.transactional.accumulated_weights = tx_weights;
+        Ok(())
+    }
 
     pub fn update_auxiliary_info<S: StateReader>(
         &mut self,
@@ -126,6 +349,14 @@ impl TransactionalBouncer {
         Ok(())
     }
 
+    /// Returns the DA bytes estimate of the block state diff, assuming the current transaction is
+    /// committed.
+    pub fn estimated_da_bytes(&self) -> usize {
+        let mut state_changes_keys = self.bouncer.state_changes_keys.clone();
+        state_changes_keys.extend(&self.transactional.state_changes_keys);
+        encoded_state_diff_length(&state_changes_keys.count()) * DA_BYTES_PER_FELT
+    }
+
     pub fn commit(mut self) -> Bouncer {
         self.bouncer.merge(self.transactional);
         self.bouncer
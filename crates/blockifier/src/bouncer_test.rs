@@ -1,6 +1,26 @@
+use std::collections::HashMap;
 use std::ops::Sub;
 
-use crate::bouncer::{Bouncer, BouncerWeights, BuiltinCount};
+use assert_matches::assert_matches;
+use cairo_vm::vm::runners::builtin_runner::HASH_BUILTIN_NAME;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{contract_address, patricia_key, stark_felt};
+
+use crate::blockifier::bouncer::BouncerInfo;
+use crate::blockifier::transaction_executor::TransactionExecutorError;
+use crate::bouncer::{
+    BlockFullError, Bouncer, BouncerConfig, BouncerWeights, BuiltinCount, TenantId,
+};
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
+use crate::state::cached_state::CachedState;
+use crate::state::da_encoding::DA_BYTES_PER_FELT;
+use crate::state::state_api::State;
+use crate::test_utils::dict_state_reader::DictStateReader;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::versioned_constants::VersionedConstants;
 
 #[test]
 fn test_block_weights_sub_checked() {
@@ -14,10 +34,13 @@ fn test_block_weights_sub_checked() {
             poseidon: 10,
             range_check: 10,
         },
+        declared_code_size: 10,
         gas: 10,
         message_segment_length: 10,
+        n_declares: 10,
         n_events: 10,
         n_steps: 10,
+        n_syscalls: 10,
         state_diff_size: 10,
     };
 
@@ -31,10 +54,13 @@ fn test_block_weights_sub_checked() {
             poseidon: 9,
             range_check: 10,
         },
+        declared_code_size: 4,
         gas: 7,
         message_segment_length: 10,
+        n_declares: 1,
         n_steps: 0,
         n_events: 2,
+        n_syscalls: 7,
         state_diff_size: 7,
     };
 
@@ -52,10 +78,13 @@ fn test_block_weights_sub_checked() {
             poseidon: 5,
             range_check: 5,
         },
+        declared_code_size: 5,
         gas: 5,
         message_segment_length: 5,
+        n_declares: 5,
         n_steps: 5,
         n_events: 5,
+        n_syscalls: 5,
         state_diff_size: 5,
     };
 
@@ -75,10 +104,13 @@ fn test_transactional_bouncer() {
             poseidon: 10,
             range_check: 10,
         },
+        declared_code_size: 10,
         gas: 10,
         message_segment_length: 10,
+        n_declares: 10,
         n_steps: 10,
         n_events: 10,
+        n_syscalls: 10,
         state_diff_size: 10,
     };
 
@@ -92,10 +124,13 @@ fn test_transactional_bouncer() {
             poseidon: 7,
             range_check: 8,
         },
+        declared_code_size: 3,
         gas: 9,
         message_segment_length: 10,
+        n_declares: 1,
         n_steps: 0,
         n_events: 1,
+        n_syscalls: 2,
         state_diff_size: 2,
     };
 
@@ -111,3 +146,156 @@ fn test_transactional_bouncer() {
     let final_weights = transactional_bouncer.commit();
     assert!(final_weights.capacity == weights_to_commit);
 }
+
+#[test]
+fn test_tenant_quotas() {
+    let limited_tenant = TenantId("limited".to_string());
+    let unlimited_tenant = TenantId("unlimited".to_string());
+    let quota = BouncerWeights { gas: 10, n_steps: 100, ..Default::default() };
+    let tx_weights = BouncerWeights { gas: 4, n_steps: 40, ..Default::default() };
+    let bouncer = Bouncer::new(BouncerWeights::default())
+        .with_tenant_quotas(HashMap::from([(limited_tenant.clone(), quota)]));
+
+    // Two transactions fit in the quota; a third one exceeds it and is not accounted.
+    let mut bouncer = (0..2).fold(bouncer, |bouncer, _| {
+        let mut transactional_bouncer = bouncer.create_transactional();
+        transactional_bouncer.update_tenant_usage(&limited_tenant, tx_weights).unwrap();
+        transactional_bouncer.commit()
+    });
+    let mut transactional_bouncer = bouncer.create_transactional();
+    assert_matches!(
+        transactional_bouncer.update_tenant_usage(&limited_tenant, tx_weights),
+        Err(TransactionExecutorError::TenantQuotaExceeded { tenant_id })
+        if tenant_id == limited_tenant
+    );
+
+    // Tenants without a quota are not limited.
+    transactional_bouncer.update_tenant_usage(&unlimited_tenant, quota + quota).unwrap();
+    bouncer = transactional_bouncer.commit();
+
+    assert_eq!(bouncer.tenant_usage(&limited_tenant), tx_weights + tx_weights);
+    assert_eq!(
+        bouncer.tenant_remaining_quota(&limited_tenant),
+        Some(BouncerWeights { gas: 2, n_steps: 20, ..Default::default() })
+    );
+    assert_eq!(bouncer.tenant_usage(&unlimited_tenant), quota + quota);
+    assert_eq!(bouncer.tenant_remaining_quota(&unlimited_tenant), None);
+}
+
+#[test]
+fn test_estimated_da_bytes() {
+    let mut state = CachedState::from(DictStateReader::default());
+    let bouncer = Bouncer::new(BouncerWeights::default());
+    // Only the modified contracts and declared classes counters.
+    assert_eq!(bouncer.estimated_da_bytes(), 2 * DA_BYTES_PER_FELT);
+
+    let mut transactional_bouncer = bouncer.create_transactional();
+    let mut transactional_state = CachedState::create_transactional(&mut state);
+    let contract_address = contract_address!("0x100");
+    for key in ["0x10", "0x11"] {
+        transactional_state
+            .set_storage_at(contract_address, StorageKey(patricia_key!(key)), stark_felt!(1_u8))
+            .unwrap();
+    }
+    transactional_bouncer
+        .update_auxiliary_info(&TransactionExecutionInfo::default(), &mut transactional_state)
+        .unwrap();
+
+    // The counters, an address and a header, and two key-value pairs.
+    let expected_da_bytes = (2 + 2 + 2 * 2) * DA_BYTES_PER_FELT;
+    assert_eq!(transactional_bouncer.estimated_da_bytes(), expected_da_bytes);
+    assert_eq!(transactional_bouncer.clone().abort().estimated_da_bytes(), 2 * DA_BYTES_PER_FELT);
+    assert_eq!(transactional_bouncer.commit().estimated_da_bytes(), expected_da_bytes);
+}
+
+#[test]
+fn test_bouncer_try_update() {
+    let capacity = BouncerWeights {
+        builtin_count: BuiltinCount { pedersen: 10, ..BuiltinCount::max() },
+        n_steps: 100,
+        ..BouncerWeights::max()
+    };
+    let mut bouncer = Bouncer::from_config(&BouncerConfig { block_max_capacity: capacity });
+    let tx_resources = BouncerInfo {
+        execution_resources: ExecutionResources {
+            n_steps: 40,
+            builtin_instance_counter: HashMap::from([(HASH_BUILTIN_NAME.to_string(), 3)]),
+            ..Default::default()
+        },
+        n_events: 1,
+        ..Default::default()
+    };
+    let tx_weights = BouncerWeights {
+        builtin_count: BuiltinCount { pedersen: 3, ..Default::default() },
+        n_steps: 40,
+        n_events: 1,
+        ..Default::default()
+    };
+    assert_eq!(BouncerWeights::from(&tx_resources), tx_weights);
+
+    // Two transactions fit in the block; a third one exceeds its steps capacity, and is not
+    // accounted.
+    bouncer.try_update(&tx_resources).unwrap();
+    let mut transactional_bouncer = bouncer.create_transactional();
+    transactional_bouncer.try_update(&tx_resources).unwrap();
+    assert_eq!(transactional_bouncer.clone().abort().accumulated_weights(), tx_weights);
+    let mut bouncer = transactional_bouncer.commit();
+    assert_eq!(bouncer.accumulated_weights(), tx_weights + tx_weights);
+    let remaining_capacity = bouncer.remaining_capacity();
+    assert_eq!(
+        bouncer.try_update(&tx_resources),
+        Err(BlockFullError { tx_weights, remaining_capacity })
+    );
+    assert_eq!(bouncer.accumulated_weights(), tx_weights + tx_weights);
+}
+
+#[test]
+fn test_bouncer_declare_capacity() {
+    // The shipped config does not limit declares.
+    let block_max_capacity =
+        VersionedConstants::latest_constants().bouncer_config.block_max_capacity;
+    assert_eq!(
+        (block_max_capacity.n_declares, block_max_capacity.declared_code_size),
+        (usize::MAX, usize::MAX)
+    );
+
+    let capacity =
+        BouncerWeights { n_declares: 2, declared_code_size: 1000, ..BouncerWeights::max() };
+    let mut bouncer = Bouncer::new(capacity);
+    let declare = |declared_code_size| BouncerInfo {
+        n_declares: 1,
+        declared_code_size,
+        ..Default::default()
+    };
+
+    bouncer.try_update(&declare(600)).unwrap();
+    // A large declare exceeds the remaining size capacity, although the block has room for
+    // another declare.
+    assert_matches!(bouncer.try_update(&declare(500)), Err(BlockFullError { .. }));
+    bouncer.try_update(&declare(400)).unwrap();
+    assert_matches!(bouncer.try_update(&declare(0)), Err(BlockFullError { .. }));
+    // Other transactions are not affected.
+    bouncer.try_update(&BouncerInfo::default()).unwrap();
+}
+
+#[test]
+fn test_bouncer_syscall_capacity() {
+    let capacity = BouncerWeights { n_syscalls: 5, ..BouncerWeights::max() };
+    let mut bouncer = Bouncer::new(capacity);
+    let tx_resources = BouncerInfo {
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageRead, 2),
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+        ]),
+        ..Default::default()
+    };
+    assert_eq!(
+        BouncerWeights::from(&tx_resources),
+        BouncerWeights { n_syscalls: 3, ..Default::default() }
+    );
+
+    // The second transaction exceeds the syscall capacity of the block.
+    bouncer.try_update(&tx_resources).unwrap();
+    assert_matches!(bouncer.try_update(&tx_resources), Err(BlockFullError { .. }));
+    assert_eq!(bouncer.accumulated_weights().n_syscalls, 3);
+}
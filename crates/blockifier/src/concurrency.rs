@@ -0,0 +1,275 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::blockifier::block::EndOfBlockHook;
+use crate::context::BlockContext;
+use crate::execution::contract_class::ContractClass;
+use crate::fee::fee_sharding::{AccruedFees, ConsolidateFees, FeeAccumulator};
+use crate::state::cached_state::{CachedState, CachedStateChanges, StorageEntry};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[cfg(test)]
+#[path = "concurrency_test.rs"]
+pub mod test;
+
+/// The values a transaction read from the state it was executed over.
+#[derive(Debug, Default)]
+pub struct ReadSet {
+    storage: HashMap<StorageEntry, StarkFelt>,
+    nonces: HashMap<ContractAddress, Nonce>,
+    class_hashes: HashMap<ContractAddress, ClassHash>,
+    compiled_class_hashes: HashMap<ClassHash, CompiledClassHash>,
+    // Whether each class read was declared.
+    declared_classes: HashMap<ClassHash, bool>,
+}
+
+impl ReadSet {
+    /// Returns whether all the reads give the same values from the given state; that is, whether
+    /// an execution over the given state would be identical.
+    pub fn is_valid(&self, state: &dyn StateReader) -> StateResult<bool> {
+        for (&(contract_address, key), value) in &self.storage {
+            if state.get_storage_at(contract_address, key)? != *value {
+                return Ok(false);
+            }
+        }
+        for (&contract_address, nonce) in &self.nonces {
+            if state.get_nonce_at(contract_address)? != *nonce {
+                return Ok(false);
+            }
+        }
+        for (&contract_address, class_hash) in &self.class_hashes {
+            if state.get_class_hash_at(contract_address)? != *class_hash {
+                return Ok(false);
+            }
+        }
+        for (&class_hash, compiled_class_hash) in &self.compiled_class_hashes {
+            if state.get_compiled_class_hash(class_hash)? != *compiled_class_hash {
+                return Ok(false);
+            }
+        }
+        for (&class_hash, &is_declared) in &self.declared_classes {
+            if is_class_declared(state, class_hash)? != is_declared {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+fn is_class_declared(state: &dyn StateReader, class_hash: ClassHash) -> StateResult<bool> {
+    match state.get_compiled_contract_class(class_hash) {
+        Ok(_) => Ok(true),
+        Err(StateError::UndeclaredClassHash(_)) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Wraps a state reader, recording the values read through it.
+pub struct ReadRecordingReader<'a, S: StateReader> {
+    state: &'a S,
+    reads: RefCell<ReadSet>,
+}
+
+impl<'a, S: StateReader> ReadRecordingReader<'a, S> {
+    pub fn new(state: &'a S) -> Self {
+        Self { state, reads: RefCell::default() }
+    }
+
+    pub fn into_read_set(self) -> ReadSet {
+        self.reads.into_inner()
+    }
+}
+
+impl<'a, S: StateReader> StateReader for ReadRecordingReader<'a, S> {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let value = self.state.get_storage_at(contract_address, key)?;
+        self.reads.borrow_mut().storage.insert((contract_address, key), value);
+        Ok(value)
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let nonce = self.state.get_nonce_at(contract_address)?;
+        self.reads.borrow_mut().nonces.insert(contract_address, nonce);
+        Ok(nonce)
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let class_hash = self.state.get_class_hash_at(contract_address)?;
+        self.reads.borrow_mut().class_hashes.insert(contract_address, class_hash);
+        Ok(class_hash)
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        let contract_class = self.state.get_compiled_contract_class(class_hash);
+        let is_declared = match &contract_class {
+            Ok(_) => true,
+            Err(StateError::UndeclaredClassHash(_)) => false,
+            Err(_) => return contract_class,
+        };
+        self.reads.borrow_mut().declared_classes.insert(class_hash, is_declared);
+        contract_class
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let compiled_class_hash = self.state.get_compiled_class_hash(class_hash)?;
+        self.reads.borrow_mut().compiled_class_hashes.insert(class_hash, compiled_class_hash);
+        Ok(compiled_class_hash)
+    }
+}
+
+/// The outputs of executing a block of transactions in parallel.
+#[derive(Debug)]
+pub struct ParallelExecutionOutput {
+    /// The execution results, in block order; identical to those of a sequential execution, except
+    /// that, when charging fees, fees are transferred to per-transaction fee accumulators.
+    pub results: Vec<TransactionExecutionResult<TransactionExecutionInfo>>,
+    /// The number of transactions whose speculative execution was discarded (due to a conflict
+    /// with a preceding transaction, or a failure), and were re-executed.
+    pub n_reexecuted_txs: usize,
+}
+
+struct SpeculativeExecution {
+    result: TransactionExecutionResult<TransactionExecutionInfo>,
+    read_set: ReadSet,
+    changes: CachedStateChanges,
+}
+
+/// Executes the transactions of a block with optimistic concurrency, and applies them to the
+/// given state, as if they were executed sequentially.
+///
+/// First, all transactions are executed speculatively, in parallel, over the state at the start of
+/// the block, recording their read sets. Then, the transactions are committed in block order: a
+/// transaction whose reads are unaffected by the preceding transactions is committed as is;
+/// otherwise, it is re-executed over the committed state.
+///
+/// When charging fees, each transaction transfers its fee to its own fee accumulator, rather than
+/// to the sequencer, so that transactions do not conflict on the sequencer balance; the accrued
+/// fees are moved to the fee recipients of the block once all transactions are committed.
+pub fn execute_in_parallel<S: StateReader + Sync>(
+    state: &mut CachedState<S>,
+    txs: &[Transaction],
+    block_context: &BlockContext,
+    charge_fee: bool,
+    n_workers: usize,
+) -> TransactionExecutionResult<ParallelExecutionOutput> {
+    let validate = true;
+    let speculative_executions =
+        execute_speculatively(state, txs, block_context, charge_fee, n_workers);
+
+    let mut results = Vec::with_capacity(txs.len());
+    let mut n_reexecuted_txs = 0;
+    let mut accrued_fees = AccruedFees::default();
+    for (tx_index, (tx, speculative_execution)) in
+        txs.iter().zip(speculative_executions).enumerate()
+    {
+        let SpeculativeExecution { result, read_set, changes } = speculative_execution;
+        let tx_block_context = tx_block_context(block_context, tx_index, charge_fee);
+        // Failed executions leave no changes to commit, yet may have failed on a conflict (e.g.,
+        // an invalid nonce); they are re-executed to report the sequential failure.
+        let result = if result.is_ok() && read_set.is_valid(&*state)? {
+            state.apply_changes(changes);
+            result
+        } else {
+            n_reexecuted_txs += 1;
+            tx.clone().execute(state, &tx_block_context, charge_fee, validate)
+        };
+
+        if let (Some(accumulator), Ok(tx_execution_info)) =
+            (FeeAccumulator::for_block(&tx_block_context), &result)
+        {
+            if let Some(fee_transfer_call_info) = &tx_execution_info.fee_transfer_call_info {
+                let fee_token_address = fee_transfer_call_info.call.storage_address;
+                accrued_fees.add(fee_token_address, accumulator, tx_execution_info.actual_fee);
+            }
+        }
+        results.push(result);
+    }
+    ConsolidateFees(accrued_fees).apply(state, block_context)?;
+
+    Ok(ParallelExecutionOutput { results, n_reexecuted_txs })
+}
+
+/// Returns the block context to execute the transaction at the given index under: when charging
+/// fees, it charges the transaction to a fee accumulator of its own.
+fn tx_block_context(
+    block_context: &BlockContext,
+    tx_index: usize,
+    charge_fee: bool,
+) -> Cow<'_, BlockContext> {
+    if !charge_fee {
+        return Cow::Borrowed(block_context);
+    }
+
+    let tx_block_context = block_context.clone();
+    tx_block_context.extensions().insert(FeeAccumulator::new(tx_index));
+    Cow::Owned(tx_block_context)
+}
+
+fn execute_speculatively<S: StateReader + Sync>(
+    state: &CachedState<S>,
+    txs: &[Transaction],
+    block_context: &BlockContext,
+    charge_fee: bool,
+    n_workers: usize,
+) -> Vec<SpeculativeExecution> {
+    let validate = true;
+    // Cached states are not shared between threads; only their underlying state is.
+    let (base_state, global_contract_cache) = (&state.state, state.global_contract_cache());
+    let next_tx_index = AtomicUsize::new(0);
+    let speculative_executions: Mutex<Vec<Option<SpeculativeExecution>>> =
+        Mutex::new(txs.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..n_workers.max(1) {
+            scope.spawn(|| {
+                let mut tx_index = next_tx_index.fetch_add(1, Ordering::Relaxed);
+                while let Some(tx) = txs.get(tx_index) {
+                    let tx_block_context = tx_block_context(block_context, tx_index, charge_fee);
+                    let mut speculative_state = CachedState::new(
+                        ReadRecordingReader::new(base_state),
+                        global_contract_cache.clone(),
+                    );
+                    let result = tx.clone().execute(
+                        &mut speculative_state,
+                        &tx_block_context,
+                        charge_fee,
+                        validate,
+                    );
+                    let (reader, changes) = speculative_state.into_changes();
+                    let speculative_execution =
+                        SpeculativeExecution { result, read_set: reader.into_read_set(), changes };
+                    let mut executions = speculative_executions
+                        .lock()
+                        .expect("Speculative executions lock is poisoned.");
+                    executions[tx_index] = Some(speculative_execution);
+                    tx_index = next_tx_index.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    speculative_executions
+        .into_inner()
+        .expect("Speculative executions lock is poisoned.")
+        .into_iter()
+        .map(|speculative_execution| {
+            speculative_execution.expect("All transactions must be executed speculatively.")
+        })
+        .collect()
+}
@@ -0,0 +1,100 @@
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::transaction::Fee;
+
+use crate::concurrency::execute_in_parallel;
+use crate::context::BlockContext;
+use crate::invoke_tx_args;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_trivial_calldata, CairoVersion, NonceManager, BALANCE, MAX_FEE};
+use crate::transaction::test_utils::{account_invoke_tx, block_context};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[rstest]
+#[case::independent_senders(vec![0, 1, 2], 0)]
+// All but the first transaction fail speculatively, on an invalid nonce.
+#[case::same_sender(vec![0, 0, 0], 2)]
+fn test_execute_in_parallel(
+    block_context: BlockContext,
+    #[case] sender_instances: Vec<u16>,
+    #[case] expected_n_reexecuted_txs: usize,
+) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let chain_info = &block_context.chain_info;
+    let contracts = [(test_contract, 1), (account_contract, 3)];
+    let mut parallel_state = test_state(chain_info, BALANCE, &contracts);
+    let mut sequential_state = test_state(chain_info, BALANCE, &contracts);
+
+    let mut nonce_manager = NonceManager::default();
+    let txs: Vec<Transaction> = sender_instances
+        .into_iter()
+        .map(|instance| {
+            let sender_address = account_contract.get_instance_address(instance);
+            Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+                sender_address,
+                calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+                nonce: nonce_manager.next(sender_address),
+            }))
+        })
+        .collect();
+
+    let charge_fee = false;
+    let n_workers = 3;
+    let output =
+        execute_in_parallel(&mut parallel_state, &txs, &block_context, charge_fee, n_workers)
+            .unwrap();
+    assert_eq!(output.n_reexecuted_txs, expected_n_reexecuted_txs);
+
+    // The outputs are identical to those of a sequential execution.
+    let validate = true;
+    for (tx, result) in txs.into_iter().zip(output.results) {
+        let expected_execution_info =
+            tx.execute(&mut sequential_state, &block_context, charge_fee, validate).unwrap();
+        assert_eq!(result.unwrap(), expected_execution_info);
+    }
+    assert_eq!(parallel_state.to_state_diff(), sequential_state.to_state_diff());
+}
+
+#[rstest]
+fn test_execute_in_parallel_charging_fees(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let chain_info = &block_context.chain_info;
+    let contracts = [(test_contract, 1), (account_contract, 3)];
+    let mut parallel_state = test_state(chain_info, BALANCE, &contracts);
+    let mut sequential_state = test_state(chain_info, BALANCE, &contracts);
+
+    let txs: Vec<Transaction> = (0..3)
+        .map(|instance| {
+            Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+                sender_address: account_contract.get_instance_address(instance),
+                calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+                max_fee: Fee(MAX_FEE),
+            }))
+        })
+        .collect();
+
+    let charge_fee = true;
+    let n_workers = 3;
+    let output =
+        execute_in_parallel(&mut parallel_state, &txs, &block_context, charge_fee, n_workers)
+            .unwrap();
+    // Fees are charged to per-transaction accumulators; hence, no transaction conflicts on the
+    // sequencer balance.
+    assert_eq!(output.n_reexecuted_txs, 0);
+
+    // The fees, and the final state (where the accrued fees are moved to the sequencer), are
+    // identical to those of a sequential execution.
+    let validate = true;
+    for (tx, result) in txs.into_iter().zip(output.results) {
+        let expected_execution_info =
+            tx.execute(&mut sequential_state, &block_context, charge_fee, validate).unwrap();
+        let execution_info = result.unwrap();
+        assert!(execution_info.actual_fee.0 > 0);
+        assert_eq!(execution_info.actual_fee, expected_execution_info.actual_fee);
+    }
+    assert_eq!(parallel_state.to_state_diff(), sequential_state.to_state_diff());
+}
@@ -1,21 +1,64 @@
-use starknet_api::core::{ChainId, ContractAddress};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-use crate::blockifier::block::BlockInfo;
-use crate::transaction::objects::{FeeType, TransactionInfo, TransactionInfoCreator};
+use starknet_api::block::{BlockNumber, BlockTimestamp};
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::StarkFelt;
+use thiserror::Error;
+
+use crate::blockifier::block::{BlockInfo, GasPrices};
+use crate::execution::syscalls::gas_schedule::SyscallGasSchedule;
+use crate::transaction::objects::{
+    FeeType, TransactionFeeResult, TransactionInfo, TransactionInfoCreator,
+};
+use crate::transaction::paymaster::{Paymaster, PaymasterConfig};
 use crate::versioned_constants::VersionedConstants;
 
+#[cfg(test)]
+#[path = "context_test.rs"]
+mod test;
+
 /// Create via [`crate::blockifier::block::pre_process_block`] to ensure correctness.
 #[derive(Clone, Debug)]
 pub struct TransactionContext {
     pub block_context: BlockContext,
     pub tx_info: TransactionInfo,
+    /// Per-transaction state shared by middleware and custom syscalls; seeded from the block
+    /// context's extensions.
+    pub extensions: ExecutionExtensions,
+}
+
+impl TransactionContext {
+    pub fn new(block_context: BlockContext, tx_info: TransactionInfo) -> Self {
+        let extensions = block_context.extensions.clone();
+        Self { block_context, tx_info, extensions }
+    }
+
+    /// Returns the paymaster sponsoring the transaction, if the paymaster flow is enabled (see
+    /// [`PaymasterConfig`]) and the transaction carries paymaster data.
+    pub fn paymaster(&self) -> TransactionFeeResult<Option<Paymaster>> {
+        if !self.extensions.contains::<PaymasterConfig>() {
+            return Ok(None);
+        }
+        Paymaster::from_tx_info(&self.tx_info)
+    }
+
+    /// Returns the account charged the transaction fee: its paymaster, if any, or its sender.
+    pub fn fee_payer_address(&self) -> TransactionFeeResult<ContractAddress> {
+        Ok(self.paymaster()?.map_or(self.tx_info.sender_address(), |paymaster| paymaster.address))
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct BlockContext {
     pub(crate) block_info: BlockInfo,
     pub(crate) chain_info: ChainInfo,
-    pub(crate) versioned_constants: VersionedConstants,
+    // Shared between the contexts of the blocks and transactions executed under it.
+    pub(crate) versioned_constants: Arc<VersionedConstants>,
+    pub(crate) extensions: ExecutionExtensions,
 }
 
 impl BlockContext {
@@ -30,7 +73,8 @@ impl BlockContext {
         BlockContext {
             block_info: block_info.clone(),
             chain_info: chain_info.clone(),
-            versioned_constants: versioned_constants.clone(),
+            versioned_constants: Arc::new(versioned_constants.clone()),
+            extensions: ExecutionExtensions::default(),
         }
     }
 
@@ -45,24 +89,181 @@ impl BlockContext {
     pub fn versioned_constants(&self) -> &VersionedConstants {
         &self.versioned_constants
     }
+
+    /// Extensions visible to every transaction executed in this block. Each transaction context
+    /// receives its own copy, so that writes made during a transaction do not leak into others.
+    pub fn extensions(&self) -> &ExecutionExtensions {
+        &self.extensions
+    }
+
+    /// Overrides the gas costs of syscalls of Cairo 1 contracts executed in this block, on top of
+    /// those of the versioned constants.
+    pub fn with_syscall_gas_schedule(self, syscall_gas_schedule: SyscallGasSchedule) -> Self {
+        self.extensions.insert(syscall_gas_schedule);
+        self
+    }
 }
 
 impl BlockContext {
+    /// Derives the context of the subsequent block: the block number is incremented, while the
+    /// chain info, constants, sequencer address, DA mode and extensions are carried over; the
+    /// constants are shared, not copied.
+    /// Note: block pre-processing (see [`crate::blockifier::block::pre_process_block`]) is still
+    /// the caller's responsibility.
+    pub fn next(
+        &self,
+        new_timestamp: BlockTimestamp,
+        new_gas_prices: GasPrices,
+    ) -> BlockContextResult<Self> {
+        let block_number = self.block_info.block_number;
+        let next_block_number = block_number
+            .0
+            .checked_add(1)
+            .ok_or(BlockContextError::BlockNumberOverflow { block_number })?;
+        let previous_timestamp = self.block_info.block_timestamp;
+        if new_timestamp < previous_timestamp {
+            return Err(BlockContextError::DecreasingTimestamp {
+                previous_timestamp,
+                new_timestamp,
+            });
+        }
+
+        Ok(Self {
+            block_info: BlockInfo {
+                block_number: BlockNumber(next_block_number),
+                block_timestamp: new_timestamp,
+                gas_prices: new_gas_prices,
+                ..self.block_info.clone()
+            },
+            ..self.clone()
+        })
+    }
+
     pub fn to_tx_context(
         &self,
         tx_info_creator: &impl TransactionInfoCreator,
     ) -> TransactionContext {
-        TransactionContext {
-            block_context: self.clone(),
-            tx_info: tx_info_creator.create_tx_info(),
+        TransactionContext::new(self.clone(), tx_info_creator.create_tx_info())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BlockContextError {
+    #[error("Block number {block_number:?} has no successor.")]
+    BlockNumberOverflow { block_number: BlockNumber },
+    #[error(
+        "Block timestamp {new_timestamp:?} precedes the previous block timestamp \
+         {previous_timestamp:?}."
+    )]
+    DecreasingTimestamp { previous_timestamp: BlockTimestamp, new_timestamp: BlockTimestamp },
+}
+
+pub type BlockContextResult<T> = Result<T, BlockContextError>;
+
+pub const MAINNET_CHAIN_ID: &str = "SN_MAIN";
+pub const SEPOLIA_CHAIN_ID: &str = "SN_SEPOLIA";
+// A Cairo short string fits in a felt.
+pub const MAX_CHAIN_ID_LENGTH: usize = 31;
+
+/// A Starknet chain id, with presets for the public networks; appchains use `Other`. Chain ids are
+/// validated as Cairo short strings on construction (see `ChainId::new`).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ChainId {
+    Mainnet,
+    Sepolia,
+    Other(CustomChainId),
+}
+
+impl ChainId {
+    /// Returns the preset named by the given chain id, if any, or a custom chain id; fails if the
+    /// chain id is not a valid Cairo short string.
+    pub fn new(chain_id: String) -> ChainIdResult<Self> {
+        match chain_id.as_str() {
+            MAINNET_CHAIN_ID => Ok(Self::Mainnet),
+            SEPOLIA_CHAIN_ID => Ok(Self::Sepolia),
+            _ => Ok(Self::Other(CustomChainId::new(chain_id)?)),
         }
     }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mainnet => MAINNET_CHAIN_ID,
+            Self::Sepolia => SEPOLIA_CHAIN_ID,
+            Self::Other(chain_id) => chain_id.as_str(),
+        }
+    }
+
+    /// Returns the chain id encoded as a Cairo short string, as exposed to contracts (e.g., in
+    /// `get_execution_info`) and used in transaction hash domains.
+    pub fn to_short_string_felt(&self) -> StarkFelt {
+        let chain_id = self.as_str();
+        let mut bytes = [0_u8; 32];
+        bytes[32 - chain_id.len()..].copy_from_slice(chain_id.as_bytes());
+        StarkFelt::new(bytes).expect("A short string is smaller than the field prime.")
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<String> for ChainId {
+    type Error = ChainIdError;
+
+    fn try_from(chain_id: String) -> ChainIdResult<Self> {
+        Self::new(chain_id)
+    }
 }
 
+impl TryFrom<starknet_api::core::ChainId> for ChainId {
+    type Error = ChainIdError;
+
+    fn try_from(chain_id: starknet_api::core::ChainId) -> ChainIdResult<Self> {
+        Self::new(chain_id.0)
+    }
+}
+
+impl From<ChainId> for starknet_api::core::ChainId {
+    fn from(chain_id: ChainId) -> Self {
+        Self(chain_id.as_str().to_string())
+    }
+}
+
+/// The chain id of an appchain; a Cairo short string, i.e., at most 31 ASCII characters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CustomChainId(String);
+
+impl CustomChainId {
+    pub fn new(chain_id: String) -> ChainIdResult<Self> {
+        if !chain_id.is_ascii() || chain_id.len() > MAX_CHAIN_ID_LENGTH {
+            return Err(ChainIdError::InvalidShortString(chain_id));
+        }
+
+        Ok(Self(chain_id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ChainIdError {
+    #[error(
+        "Chain ID {0:?} is not a valid Cairo short string; expected at most 31 ASCII characters."
+    )]
+    InvalidShortString(String),
+}
+
+pub type ChainIdResult<T> = Result<T, ChainIdError>;
+
 #[derive(Clone, Debug)]
 pub struct ChainInfo {
     pub chain_id: ChainId,
     pub fee_token_addresses: FeeTokenAddresses,
+    pub protected_addresses: ProtectedAddresses,
 }
 
 impl ChainInfo {
@@ -77,12 +278,25 @@ impl ChainInfo {
 impl Default for ChainInfo {
     fn default() -> Self {
         ChainInfo {
-            chain_id: ChainId("0x0".to_string()),
+            chain_id: ChainId::Other(CustomChainId("0x0".to_string())),
             fee_token_addresses: FeeTokenAddresses::default(),
+            protected_addresses: ProtectedAddresses::default(),
         }
     }
 }
 
+/// Address ranges of system contracts, whose storage holds privileged chain state; it may only be
+/// modified outside of contract code (e.g., during block pre-processing). Contract code may not
+/// write to their storage, replace their class, or deploy contracts into these ranges.
+#[derive(Clone, Debug, Default)]
+pub struct ProtectedAddresses(pub Vec<RangeInclusive<ContractAddress>>);
+
+impl ProtectedAddresses {
+    pub fn contains(&self, address: &ContractAddress) -> bool {
+        self.0.iter().any(|range| range.contains(address))
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct FeeTokenAddresses {
     pub strk_fee_token_address: ContractAddress,
@@ -97,3 +311,62 @@ impl FeeTokenAddresses {
         }
     }
 }
+
+/// A type map of embedder-defined values (e.g., a session key cache or a price oracle snapshot),
+/// holding at most one value per type. Values are shared, so cloning the map is cheap; values that
+/// need to be mutated in place should use interior mutability.
+#[derive(Default)]
+pub struct ExecutionExtensions {
+    values: RwLock<ExtensionValues>,
+}
+
+type ExtensionValues = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+impl ExecutionExtensions {
+    /// Inserts a value, returning the previous value of the same type, if any.
+    pub fn insert<T: Any + Send + Sync>(&self, value: T) -> Option<Arc<T>> {
+        self.write()
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .and_then(|previous| previous.downcast::<T>().ok())
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.read().get(&TypeId::of::<T>()).cloned().and_then(|value| value.downcast::<T>().ok())
+    }
+
+    pub fn remove<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.write().remove(&TypeId::of::<T>()).and_then(|value| value.downcast::<T>().ok())
+    }
+
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.read().contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, ExtensionValues> {
+        self.values.read().expect("Execution extensions lock is poisoned.")
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, ExtensionValues> {
+        self.values.write().expect("Execution extensions lock is poisoned.")
+    }
+}
+
+impl Clone for ExecutionExtensions {
+    fn clone(&self) -> Self {
+        Self { values: RwLock::new(self.read().clone()) }
+    }
+}
+
+impl fmt::Debug for ExecutionExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutionExtensions").field("len", &self.len()).finish_non_exhaustive()
+    }
+}
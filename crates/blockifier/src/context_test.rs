@@ -0,0 +1,114 @@
+use std::sync::{Arc, Mutex};
+
+use assert_matches::assert_matches;
+use rstest::rstest;
+use starknet_api::block::{BlockNumber, BlockTimestamp};
+use starknet_api::hash::StarkFelt;
+
+use crate::context::{
+    BlockContext, BlockContextError, ChainId, ChainIdError, CustomChainId, ExecutionExtensions,
+    TransactionContext,
+};
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
+
+#[derive(Debug, PartialEq)]
+struct PriceSnapshot(u128);
+
+#[test]
+fn test_execution_extensions() {
+    let extensions = ExecutionExtensions::default();
+    assert!(extensions.is_empty());
+    assert_eq!(extensions.insert(PriceSnapshot(1)), None);
+    assert_eq!(extensions.insert(PriceSnapshot(2)).as_deref(), Some(&PriceSnapshot(1)));
+    assert_eq!(extensions.get::<PriceSnapshot>().as_deref(), Some(&PriceSnapshot(2)));
+    assert!(!extensions.contains::<Mutex<Vec<u8>>>());
+    assert_eq!(extensions.len(), 1);
+
+    assert_eq!(extensions.remove::<PriceSnapshot>().as_deref(), Some(&PriceSnapshot(2)));
+    assert!(extensions.is_empty());
+}
+
+#[test]
+fn test_transaction_extensions_are_isolated() {
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(PriceSnapshot(1));
+    block_context.extensions().insert(Mutex::new(Vec::<u8>::new()));
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = TransactionContext::new(block_context.clone(), tx_info);
+
+    // Values inserted during the transaction are not visible to the block.
+    tx_context.extensions.insert(PriceSnapshot(2));
+    assert_eq!(tx_context.extensions.get::<PriceSnapshot>().as_deref(), Some(&PriceSnapshot(2)));
+    assert_eq!(
+        block_context.extensions().get::<PriceSnapshot>().as_deref(),
+        Some(&PriceSnapshot(1))
+    );
+
+    // Values themselves are shared.
+    tx_context.extensions.get::<Mutex<Vec<u8>>>().unwrap().lock().unwrap().push(1);
+    assert_eq!(
+        *block_context.extensions().get::<Mutex<Vec<u8>>>().unwrap().lock().unwrap(),
+        vec![1]
+    );
+}
+
+#[test]
+fn test_next_block_context() {
+    let block_context = BlockContext::create_for_testing();
+    let block_info = block_context.block_info();
+    let mut new_gas_prices = block_info.gas_prices.clone();
+    new_gas_prices.eth_l1_gas_price = new_gas_prices.eth_l1_gas_price.saturating_add(1);
+    let new_timestamp = BlockTimestamp(block_info.block_timestamp.0 + 1);
+
+    let next_block_context = block_context.next(new_timestamp, new_gas_prices.clone()).unwrap();
+    let next_block_info = next_block_context.block_info();
+    assert_eq!(next_block_info.block_number, BlockNumber(block_info.block_number.0 + 1));
+    assert_eq!(next_block_info.block_timestamp, new_timestamp);
+    assert_eq!(next_block_info.gas_prices.eth_l1_gas_price, new_gas_prices.eth_l1_gas_price);
+    assert_eq!(next_block_info.sequencer_address, block_info.sequencer_address);
+    assert_eq!(next_block_context.chain_info().chain_id, block_context.chain_info().chain_id);
+    assert!(Arc::ptr_eq(
+        &next_block_context.versioned_constants,
+        &block_context.versioned_constants
+    ));
+
+    // The timestamp must not decrease.
+    let past_timestamp = BlockTimestamp(block_info.block_timestamp.0 - 1);
+    assert_matches!(
+        block_context.next(past_timestamp, new_gas_prices),
+        Err(BlockContextError::DecreasingTimestamp { .. })
+    );
+}
+
+#[rstest]
+#[case::mainnet("SN_MAIN", ChainId::Mainnet)]
+#[case::sepolia("SN_SEPOLIA", ChainId::Sepolia)]
+#[case::custom(
+    "SN_APPCHAIN",
+    ChainId::Other(CustomChainId::new("SN_APPCHAIN".to_string()).unwrap())
+)]
+fn test_chain_id_presets(#[case] raw_chain_id: &str, #[case] expected_chain_id: ChainId) {
+    let chain_id = ChainId::new(raw_chain_id.to_string()).unwrap();
+    assert_eq!(chain_id, expected_chain_id);
+    assert_eq!(chain_id.to_string(), raw_chain_id);
+
+    // Matches the encoding used by the Starknet API.
+    let api_chain_id = starknet_api::core::ChainId::from(chain_id.clone());
+    assert_eq!(
+        chain_id.to_short_string_felt(),
+        StarkFelt::try_from(api_chain_id.as_hex().as_str()).unwrap()
+    );
+}
+
+#[test]
+fn test_chain_id_invalid_short_string() {
+    // Chain ids are validated on construction.
+    let too_long = "A".repeat(32);
+    assert_matches!(ChainId::new(too_long), Err(ChainIdError::InvalidShortString(_)));
+
+    let non_ascii = "SN_\u{1F600}".to_string();
+    assert_matches!(ChainId::new(non_ascii), Err(ChainIdError::InvalidShortString(_)));
+
+    let max_length = "A".repeat(31);
+    assert!(ChainId::new(max_length).is_ok());
+}
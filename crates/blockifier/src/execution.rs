@@ -1,3 +1,4 @@
+pub mod cairo_traceback;
 pub mod call_info;
 pub mod common_hints;
 pub mod contract_address;
@@ -7,6 +8,9 @@ pub mod deprecated_syscalls;
 pub mod entry_point;
 pub mod entry_point_execution;
 pub mod errors;
+pub mod execution_artifacts;
+pub mod execution_observer;
 pub mod execution_utils;
 pub mod hint_code;
+pub mod step_deltas;
 pub mod syscalls;
@@ -0,0 +1,286 @@
+use cairo_lang_runner::casm_run::format_next_item;
+use cairo_lang_utils::byte_array::BYTE_ARRAY_MAGIC;
+use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
+use cairo_vm::vm::errors::hint_errors::HintError;
+use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
+use serde::{Deserialize, Serialize};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
+use starknet_api::hash::StarkFelt;
+
+use crate::execution::deprecated_syscalls::hint_processor::DeprecatedSyscallExecutionError;
+use crate::execution::errors::EntryPointExecutionError;
+use crate::execution::execution_utils::stark_felt_to_felt;
+use crate::execution::syscalls::hint_processor::{SyscallExecutionError, OUT_OF_GAS_ERROR};
+use crate::transaction::errors::TransactionExecutionError;
+
+#[cfg(test)]
+#[path = "cairo_traceback_test.rs"]
+pub mod test;
+
+/// The decoded reason of a failed execution.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DecodedRevertReason {
+    /// A Cairo 1 panic with a message: a short string, or a byte array.
+    Panic(String),
+    /// A Cairo 1 panic with structured data: an error selector (the first felt) and its data.
+    Error {
+        selector: StarkFelt,
+        data: Vec<StarkFelt>,
+    },
+    OutOfGas,
+    OutOfSteps,
+    /// Any other failure (e.g., a Cairo 0 assertion, or a failed syscall); the error message.
+    Other(String),
+}
+
+impl DecodedRevertReason {
+    /// Decodes the panic data of a failed Cairo 1 execution.
+    pub fn from_panic_data(panic_data: &[StarkFelt]) -> Self {
+        let out_of_gas_error =
+            StarkFelt::try_from(OUT_OF_GAS_ERROR).expect("Out of gas error is a valid felt.");
+        match panic_data {
+            [] => Self::Panic(String::new()),
+            [first, ..] if *first == out_of_gas_error => Self::OutOfGas,
+            [felt] => match decode_short_string(felt) {
+                Some(message) => Self::Panic(message),
+                None => Self::Error { selector: *felt, data: vec![] },
+            },
+            [first, ..] if Some(*first) == StarkFelt::try_from(BYTE_ARRAY_MAGIC).ok() => {
+                let mut felts = panic_data.iter().map(|felt| stark_felt_to_felt(*felt));
+                match format_next_item(&mut felts) {
+                    Some(item) if felts.next().is_none() => Self::Panic(item.get()),
+                    _ => Self::Error { selector: *first, data: panic_data[1..].to_vec() },
+                }
+            }
+            [selector, data @ ..] => Self::Error { selector: *selector, data: data.to_vec() },
+        }
+    }
+}
+
+/// A call in the Cairo call stack of a failed execution, outermost first. Calls made by the
+/// `call_contract` syscall have no class hash; library calls have no other storage address than
+/// their caller's. The pc is the one at which the call's own code failed, if it ran.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CairoTracebackFrame {
+    pub storage_address: Option<ContractAddress>,
+    pub class_hash: Option<ClassHash>,
+    pub selector: Option<EntryPointSelector>,
+    pub pc: Option<usize>,
+}
+
+/// The decoded Cairo traceback of a failed execution, and the reason of its failure (raised by
+/// the innermost frame).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CairoTraceback {
+    pub frames: Vec<CairoTracebackFrame>,
+    pub reason: DecodedRevertReason,
+}
+
+impl EntryPointExecutionError {
+    /// Decodes the traceback of the failed entry point, whose call is the outermost frame.
+    pub fn cairo_traceback(&self) -> CairoTraceback {
+        let mut frames = Vec::new();
+        let reason = entry_point_error_traceback(&mut frames, Default::default(), self);
+        CairoTraceback { frames, reason }
+    }
+}
+
+impl TransactionExecutionError {
+    /// Decodes the traceback of a failed entry point of the transaction (`__validate__`,
+    /// `__execute__`, a constructor, or the paymaster validation); `None` for failures outside of
+    /// Cairo execution.
+    pub fn cairo_traceback(&self) -> Option<CairoTraceback> {
+        match self {
+            Self::ExecutionError { error, storage_address }
+            | Self::ValidateTransactionError { error, storage_address }
+            | Self::ValidatePaymasterError { error, storage_address }
+            | Self::ContractConstructorExecutionFailed { error, storage_address } => {
+                let mut traceback = error.cairo_traceback();
+                if let Some(outermost_frame) = traceback.frames.first_mut() {
+                    outermost_frame.storage_address = Some(*storage_address);
+                }
+                Some(traceback)
+            }
+            _ => None,
+        }
+    }
+}
+
+// The functions below follow the error chain, as in `gen_transaction_execution_error_trace`:
+// each takes the frame the error was raised in, pushes the frames it ends, and returns the
+// reason of the innermost error.
+
+fn entry_point_error_traceback(
+    frames: &mut Vec<CairoTracebackFrame>,
+    frame: CairoTracebackFrame,
+    error: &EntryPointExecutionError,
+) -> DecodedRevertReason {
+    match error {
+        EntryPointExecutionError::VirtualMachineExecutionErrorWithTrace { source, .. }
+        | EntryPointExecutionError::CairoRunError(source) => {
+            cairo_run_error_traceback(frames, frame, source)
+        }
+        EntryPointExecutionError::ExecutionFailed { error_data } => {
+            frames.push(frame);
+            DecodedRevertReason::from_panic_data(error_data)
+        }
+        _ => {
+            frames.push(frame);
+            DecodedRevertReason::Other(error.to_string())
+        }
+    }
+}
+
+fn cairo_run_error_traceback(
+    frames: &mut Vec<CairoTracebackFrame>,
+    mut frame: CairoTracebackFrame,
+    error: &CairoRunError,
+) -> DecodedRevertReason {
+    match error {
+        CairoRunError::VmException(vm_exception) => {
+            frame.pc = Some(vm_exception.pc);
+            vm_error_traceback(frames, frame, &vm_exception.inner_exc)
+        }
+        _ => {
+            frames.push(frame);
+            DecodedRevertReason::Other(error.to_string())
+        }
+    }
+}
+
+fn vm_error_traceback(
+    frames: &mut Vec<CairoTracebackFrame>,
+    frame: CairoTracebackFrame,
+    error: &VirtualMachineError,
+) -> DecodedRevertReason {
+    match error {
+        VirtualMachineError::Hint(boxed_hint_error) => match &boxed_hint_error.1 {
+            HintError::Internal(internal_vm_error) => {
+                vm_error_traceback(frames, frame, internal_vm_error)
+            }
+            hint_error => {
+                frames.push(frame);
+                DecodedRevertReason::Other(hint_error.to_string())
+            }
+        },
+        VirtualMachineError::Other(anyhow_error) => {
+            if let Some(syscall_error) = anyhow_error.downcast_ref::<SyscallExecutionError>() {
+                return syscall_error_traceback(frames, frame, syscall_error);
+            }
+            if let Some(syscall_error) =
+                anyhow_error.downcast_ref::<DeprecatedSyscallExecutionError>()
+            {
+                return deprecated_syscall_error_traceback(frames, frame, syscall_error);
+            }
+            frames.push(frame);
+            DecodedRevertReason::Other(anyhow_error.to_string())
+        }
+        VirtualMachineError::UnfinishedExecution => {
+            frames.push(frame);
+            DecodedRevertReason::OutOfSteps
+        }
+        _ => {
+            frames.push(frame);
+            DecodedRevertReason::Other(error.to_string())
+        }
+    }
+}
+
+fn syscall_error_traceback(
+    frames: &mut Vec<CairoTracebackFrame>,
+    frame: CairoTracebackFrame,
+    error: &SyscallExecutionError,
+) -> DecodedRevertReason {
+    match error {
+        SyscallExecutionError::CallContractExecutionError { storage_address, selector, error } => {
+            frames.push(frame);
+            let callee_frame = CairoTracebackFrame {
+                storage_address: Some(*storage_address),
+                selector: Some(*selector),
+                ..Default::default()
+            };
+            syscall_error_traceback(frames, callee_frame, error)
+        }
+        SyscallExecutionError::LibraryCallExecutionError {
+            class_hash,
+            storage_address,
+            selector,
+            error,
+        } => {
+            frames.push(frame);
+            let callee_frame = CairoTracebackFrame {
+                storage_address: Some(*storage_address),
+                class_hash: Some(*class_hash),
+                selector: Some(*selector),
+                pc: None,
+            };
+            syscall_error_traceback(frames, callee_frame, error)
+        }
+        SyscallExecutionError::EntryPointExecutionError(error) => {
+            entry_point_error_traceback(frames, frame, error)
+        }
+        SyscallExecutionError::SyscallError { error_data } => {
+            frames.push(frame);
+            DecodedRevertReason::from_panic_data(error_data)
+        }
+        _ => {
+            frames.push(frame);
+            DecodedRevertReason::Other(error.to_string())
+        }
+    }
+}
+
+fn deprecated_syscall_error_traceback(
+    frames: &mut Vec<CairoTracebackFrame>,
+    frame: CairoTracebackFrame,
+    error: &DeprecatedSyscallExecutionError,
+) -> DecodedRevertReason {
+    match error {
+        DeprecatedSyscallExecutionError::CallContractExecutionError {
+            storage_address,
+            selector,
+            error,
+        } => {
+            frames.push(frame);
+            let callee_frame = CairoTracebackFrame {
+                storage_address: Some(*storage_address),
+                selector: Some(*selector),
+                ..Default::default()
+            };
+            deprecated_syscall_error_traceback(frames, callee_frame, error)
+        }
+        DeprecatedSyscallExecutionError::LibraryCallExecutionError {
+            class_hash,
+            storage_address,
+            selector,
+            error,
+        } => {
+            frames.push(frame);
+            let callee_frame = CairoTracebackFrame {
+                storage_address: Some(*storage_address),
+                class_hash: Some(*class_hash),
+                selector: Some(*selector),
+                pc: None,
+            };
+            deprecated_syscall_error_traceback(frames, callee_frame, error)
+        }
+        DeprecatedSyscallExecutionError::EntryPointExecutionError(error) => {
+            entry_point_error_traceback(frames, frame, error)
+        }
+        _ => {
+            frames.push(frame);
+            DecodedRevertReason::Other(error.to_string())
+        }
+    }
+}
+
+/// Decodes a Cairo short string: up to 31 printable ASCII characters.
+fn decode_short_string(felt: &StarkFelt) -> Option<String> {
+    let bytes: Vec<u8> = felt.bytes().iter().copied().skip_while(|byte| *byte == 0).collect();
+    let is_printable = |byte: &u8| byte.is_ascii_graphic() || *byte == b' ';
+    if bytes.is_empty() || !bytes.iter().all(is_printable) {
+        return None;
+    }
+
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,97 @@
+use cairo_lang_utils::byte_array::BYTE_ARRAY_MAGIC;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::ChainInfo;
+use crate::execution::cairo_traceback::{CairoTracebackFrame, DecodedRevertReason};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::execution::syscalls::hint_processor::OUT_OF_GAS_ERROR;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{
+    create_calldata, trivial_external_entry_point_with_address, CairoVersion, BALANCE,
+};
+
+fn felts(data: &[&str]) -> Vec<StarkFelt> {
+    data.iter().map(|felt| StarkFelt::try_from(*felt).unwrap()).collect()
+}
+
+#[rstest]
+#[case::empty(vec![], DecodedRevertReason::Panic(String::new()))]
+#[case::short_string(felts(&["0x6661696c"]), DecodedRevertReason::Panic("fail".to_string()))]
+#[case::out_of_gas(felts(&[OUT_OF_GAS_ERROR]), DecodedRevertReason::OutOfGas)]
+#[case::byte_array(
+    // "Execution failure": no full words, a pending word of 17 bytes.
+    felts(&[BYTE_ARRAY_MAGIC, "0x00", "0x457865637574696f6e206661696c757265", "0x11"]),
+    DecodedRevertReason::Panic("Execution failure".to_string())
+)]
+#[case::error(
+    felts(&["0x1234", "0x1", "0x2"]),
+    DecodedRevertReason::Error {
+        selector: stark_felt!(0x1234_u16),
+        data: vec![stark_felt!(1_u8), stark_felt!(2_u8)],
+    }
+)]
+fn test_decode_panic_data(
+    #[case] panic_data: Vec<StarkFelt>,
+    #[case] expected_reason: DecodedRevertReason,
+) {
+    assert_eq!(DecodedRevertReason::from_panic_data(&panic_data), expected_reason);
+}
+
+#[rstest]
+fn test_cairo_traceback(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let mut state = test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 3)]);
+    let test_contract_address = test_contract.get_instance_address(0);
+    let test_contract_address_2 = test_contract.get_instance_address(1);
+    let test_contract_address_3 = test_contract.get_instance_address(2);
+
+    // Nest 3 calls: test_call_contract -> test_call_contract -> fail.
+    let call_contract_selector = selector_from_name("test_call_contract");
+    let fail_selector = selector_from_name("fail");
+    let calldata = create_calldata(
+        test_contract_address_2,
+        "test_call_contract",
+        &[*test_contract_address_3.0.key(), fail_selector.0, stark_felt!(0_u8)],
+    );
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: call_contract_selector,
+        calldata,
+        ..trivial_external_entry_point_with_address(test_contract_address)
+    };
+
+    let traceback = entry_point_call.execute_directly(&mut state).unwrap_err().cairo_traceback();
+
+    // The outermost frame is the failed entry point itself, whose call is known to the caller.
+    let (call_contract_pc, fail_pc, expected_reason) = match cairo_version {
+        CairoVersion::Cairo0 => (
+            37,
+            Some(1184),
+            DecodedRevertReason::Other("An ASSERT_EQ instruction failed: 1 != 0.".to_string()),
+        ),
+        CairoVersion::Cairo1 => (4992, None, DecodedRevertReason::Panic("fail".to_string())),
+    };
+    let expected_frames = vec![
+        CairoTracebackFrame { pc: Some(call_contract_pc), ..Default::default() },
+        CairoTracebackFrame {
+            storage_address: Some(test_contract_address_2),
+            selector: Some(call_contract_selector),
+            pc: Some(call_contract_pc),
+            ..Default::default()
+        },
+        CairoTracebackFrame {
+            storage_address: Some(test_contract_address_3),
+            selector: Some(fail_selector),
+            pc: fail_pc,
+            ..Default::default()
+        },
+    ];
+    assert_eq!(traceback.frames, expected_frames);
+    assert_eq!(traceback.reason, expected_reason);
+}
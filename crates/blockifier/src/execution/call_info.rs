@@ -10,13 +10,15 @@ use starknet_api::patricia_key;
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{EventContent, L2ToL1Payload};
 
+use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
 use crate::execution::entry_point::CallEntryPoint;
 use crate::fee::gas_usage::get_message_segment_length;
 use crate::state::cached_state::StorageEntry;
 use crate::transaction::errors::TransactionExecutionError;
 use crate::transaction::objects::TransactionExecutionResult;
+use crate::utils::add_mappings;
 
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Retdata(pub Vec<StarkFelt>);
 
 #[macro_export]
@@ -27,7 +29,7 @@ macro_rules! retdata {
 }
 
 #[cfg_attr(test, derive(Clone))]
-#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct OrderedEvent {
     pub order: usize,
     pub event: EventContent,
@@ -57,14 +59,14 @@ impl MessageL1CostInfo {
 }
 
 #[cfg_attr(test, derive(Clone))]
-#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct MessageToL1 {
     pub to_address: EthAddress,
     pub payload: L2ToL1Payload,
 }
 
 #[cfg_attr(test, derive(Clone))]
-#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct OrderedL2ToL1Message {
     pub order: usize,
     pub message: MessageToL1,
@@ -72,7 +74,7 @@ pub struct OrderedL2ToL1Message {
 
 /// Represents the effects of executing a single entry point.
 #[cfg_attr(test, derive(Clone))]
-#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CallExecution {
     pub retdata: Retdata,
     pub events: Vec<OrderedEvent>,
@@ -95,6 +97,7 @@ pub struct ExecutionSummary {
     pub executed_class_hashes: HashSet<ClassHash>,
     pub visited_storage_entries: HashSet<StorageEntry>,
     pub n_events: usize,
+    pub syscall_counter: SyscallCounter,
 }
 
 impl Add for ExecutionSummary {
@@ -104,6 +107,7 @@ impl Add for ExecutionSummary {
         self.executed_class_hashes.extend(other.executed_class_hashes);
         self.visited_storage_entries.extend(other.visited_storage_entries);
         self.n_events += other.n_events;
+        add_mappings(&mut self.syscall_counter, &other.syscall_counter);
         self
     }
 }
@@ -154,8 +158,26 @@ impl TestExecutionSummary {
     }
 }
 
+/// The top-level calls of a transaction.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TopLevelCall {
+    Validate,
+    PaymasterValidate,
+    Execute,
+    FeeTransfer,
+}
+
+/// Identifies a call in the trace of a transaction: a top-level call, followed by the indices of
+/// the inner calls leading from it to the identified call. E.g., the second inner call of
+/// `__execute__` is `CallPath { top_level_call: TopLevelCall::Execute, inner_call_indices: [1] }`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CallPath {
+    pub top_level_call: TopLevelCall,
+    pub inner_call_indices: Vec<usize>,
+}
+
 /// Represents the full effects of executing an entry point, including the inner calls it invoked.
-#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CallInfo {
     pub call: CallEntryPoint,
     pub execution: CallExecution,
@@ -166,6 +188,8 @@ pub struct CallInfo {
     // Additional information gathered during execution.
     pub storage_read_values: Vec<StarkFelt>,
     pub accessed_storage_keys: HashSet<StorageKey>,
+    // The syscalls invoked by this call, without inner calls.
+    pub syscall_counter: SyscallCounter,
 }
 
 impl CallInfo {
@@ -174,6 +198,41 @@ impl CallInfo {
         CallInfoIter { call_infos }
     }
 
+    /// Returns the events emitted by this call and its inner calls, with their emitting contracts,
+    /// in emission order.
+    pub fn events(&self) -> impl Iterator<Item = (ContractAddress, &EventContent)> {
+        // Event orders are unique within the calls of a top-level call.
+        let mut events: Vec<_> = self
+            .iter()
+            .flat_map(|call_info| {
+                let from_address = call_info.call.storage_address;
+                call_info.execution.events.iter().map(move |event| (from_address, event))
+            })
+            .collect();
+        events.sort_by_key(|(_, ordered_event)| ordered_event.order);
+        events.into_iter().map(|(from_address, ordered_event)| (from_address, &ordered_event.event))
+    }
+
+    /// Returns the L2-to-L1 messages sent by this call and its inner calls, with their sending
+    /// contracts, in the order in which they were sent.
+    pub fn l2_to_l1_messages(&self) -> impl Iterator<Item = (ContractAddress, &MessageToL1)> {
+        let mut messages: Vec<_> = self
+            .iter()
+            .flat_map(|call_info| {
+                let from_address = call_info.call.storage_address;
+                call_info
+                    .execution
+                    .l2_to_l1_messages
+                    .iter()
+                    .map(move |message| (from_address, message))
+            })
+            .collect();
+        messages.sort_by_key(|(_, ordered_message)| ordered_message.order);
+        messages
+            .into_iter()
+            .map(|(from_address, ordered_message)| (from_address, &ordered_message.message))
+    }
+
     /// Returns a list of Starknet L2ToL1Payload length collected during the execution, sorted
     /// by the order in which they were sent.
     pub fn get_sorted_l2_to_l1_payload_lengths(&self) -> TransactionExecutionResult<Vec<usize>> {
@@ -214,6 +273,7 @@ impl CallInfo {
         let mut executed_class_hashes: HashSet<ClassHash> = HashSet::new();
         let mut visited_storage_entries: HashSet<StorageEntry> = HashSet::new();
         let mut n_events: usize = 0;
+        let mut syscall_counter = SyscallCounter::default();
 
         for call_info in self.iter() {
             let class_hash =
@@ -227,9 +287,41 @@ impl CallInfo {
             visited_storage_entries.extend(call_storage_entries);
 
             n_events += call_info.execution.events.len();
+            add_mappings(&mut syscall_counter, &call_info.syscall_counter);
         }
 
-        ExecutionSummary { executed_class_hashes, visited_storage_entries, n_events }
+        ExecutionSummary {
+            executed_class_hashes,
+            visited_storage_entries,
+            n_events,
+            syscall_counter,
+        }
+    }
+
+    /// Returns the resources attributable to this call frame alone, excluding its inner calls
+    /// (unlike `resources`): its VM steps, memory holes and builtin instances, and the OS
+    /// resources of the syscalls it invoked (counted in `syscall_counter`).
+    pub fn frame_resources(&self) -> ExecutionResources {
+        let mut inner_calls_resources = ExecutionResources::default();
+        for inner_call in &self.inner_calls {
+            inner_calls_resources += &inner_call.resources;
+        }
+
+        (&self.resources - &inner_calls_resources).filter_unused_builtins()
+    }
+
+    /// Returns the descendant call reached by following the given inner call indices, dropping the
+    /// rest of the trace; `None` if there is no such call.
+    pub fn into_descendant(self, inner_call_indices: &[usize]) -> Option<CallInfo> {
+        let mut call_info = self;
+        for &index in inner_call_indices {
+            if index >= call_info.inner_calls.len() {
+                return None;
+            }
+            call_info = call_info.inner_calls.swap_remove(index);
+        }
+
+        Some(call_info)
     }
 }
 
@@ -23,6 +23,8 @@ use starknet_api::deprecated_contract_class::{
     ContractClass as DeprecatedContractClass, EntryPoint, EntryPointOffset, EntryPointType,
     Program as DeprecatedProgram,
 };
+use starknet_api::hash::StarkHash;
+use starknet_api::transaction::DeclareTransaction;
 
 use super::execution_utils::poseidon_hash_many_cost;
 use crate::abi::abi_utils::selector_from_name;
@@ -30,7 +32,12 @@ use crate::abi::constants::{self, CONSTRUCTOR_ENTRY_POINT_NAME};
 use crate::execution::entry_point::CallEntryPoint;
 use crate::execution::errors::{ContractClassError, PreExecutionError};
 use crate::execution::execution_utils::{felt_to_stark_felt, sn_api_to_cairo_vm_program};
+use crate::fee::eth_gas_constants;
 use crate::transaction::errors::TransactionExecutionError;
+use crate::versioned_constants::{DeprecatedHintPolicy, MissingSelectorPolicy};
+
+pub mod class_hash;
+pub mod sierra_compilation;
 
 #[cfg(test)]
 #[path = "contract_class_test.rs"]
@@ -82,6 +89,38 @@ impl ContractClass {
             ContractClass::V1(class) => class.bytecode_length(),
         }
     }
+
+    /// Returns the lengths of the (innermost) bytecode segments, in bytecode order; an unsegmented
+    /// bytecode is a single segment.
+    pub fn bytecode_segment_sizes(&self) -> Vec<usize> {
+        match self {
+            ContractClass::V0(class) => vec![class.bytecode_length()],
+            ContractClass::V1(class) => class.bytecode_segment_sizes(),
+        }
+    }
+
+    /// Returns the selectors of the entry points of the given type.
+    pub fn entry_point_selectors(
+        &self,
+        entry_point_type: EntryPointType,
+    ) -> Vec<EntryPointSelector> {
+        match self {
+            ContractClass::V0(class) => class
+                .entry_points_by_type
+                .get(&entry_point_type)
+                .into_iter()
+                .flatten()
+                .map(|entry_point| entry_point.selector)
+                .collect(),
+            ContractClass::V1(class) => class
+                .entry_points_by_type
+                .get(&entry_point_type)
+                .into_iter()
+                .flatten()
+                .map(|entry_point| entry_point.selector)
+                .collect(),
+        }
+    }
 }
 
 // V0.
@@ -134,23 +173,63 @@ impl ContractClassV0 {
         let contract_class: ContractClassV0Inner = serde_json::from_str(raw_contract_class)?;
         Ok(ContractClassV0(Arc::new(contract_class)))
     }
+
+    /// Fails if the class contains hints not allowed by the given policy.
+    pub fn validate_hints(&self, policy: &DeprecatedHintPolicy) -> ContractClassResult<()> {
+        let disallowed_hints: Vec<String> = self
+            .hint_codes
+            .iter()
+            .filter(|hint_code| !policy.is_allowed(hint_code))
+            .cloned()
+            .sorted()
+            .collect();
+        if disallowed_hints.is_empty() {
+            Ok(())
+        } else {
+            Err(ContractClassError::DisallowedHints { hints: disallowed_hints })
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "RawContractClassV0")]
 pub struct ContractClassV0Inner {
-    #[serde(deserialize_with = "deserialize_program")]
     pub program: Program,
     pub entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
+    /// The codes of the program's hints, kept for validation against a hint policy (the VM
+    /// program does not expose them).
+    pub hint_codes: HashSet<String>,
+}
+
+/// The fields of a Cairo 0 class required for execution; other fields (e.g., the ABI) are ignored.
+#[derive(Deserialize)]
+struct RawContractClassV0 {
+    program: DeprecatedProgram,
+    entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
+}
+
+impl TryFrom<RawContractClassV0> for ContractClassV0Inner {
+    type Error = ProgramError;
+
+    fn try_from(class: RawContractClassV0) -> Result<Self, Self::Error> {
+        let hint_codes = deprecated_program_hint_codes(&class.program)?;
+        Ok(Self {
+            program: sn_api_to_cairo_vm_program(class.program)?,
+            entry_points_by_type: class.entry_points_by_type,
+            hint_codes,
+        })
+    }
 }
 
 impl TryFrom<DeprecatedContractClass> for ContractClassV0 {
     type Error = ProgramError;
 
     fn try_from(class: DeprecatedContractClass) -> Result<Self, Self::Error> {
-        Ok(Self(Arc::new(ContractClassV0Inner {
-            program: sn_api_to_cairo_vm_program(class.program)?,
+        let raw_class = RawContractClassV0 {
+            program: class.program,
             entry_points_by_type: class.entry_points_by_type,
-        })))
+        };
+        Ok(Self(Arc::new(ContractClassV0Inner::try_from(raw_class)?)))
     }
 }
 
@@ -178,9 +257,17 @@ impl ContractClassV1 {
         &self.bytecode_segment_lengths
     }
 
+    /// Returns the lengths of the innermost bytecode segments, in bytecode order.
+    pub fn bytecode_segment_sizes(&self) -> Vec<usize> {
+        let mut segment_sizes = Vec::new();
+        flatten_segment_lengths(&self.bytecode_segment_lengths, &mut segment_sizes);
+        segment_sizes
+    }
+
     pub fn get_entry_point(
         &self,
         call: &CallEntryPoint,
+        missing_selector_policy: MissingSelectorPolicy,
     ) -> Result<EntryPointV1, PreExecutionError> {
         if call.entry_point_type == EntryPointType::Constructor
             && call.entry_point_selector != selector_from_name(CONSTRUCTOR_ENTRY_POINT_NAME)
@@ -195,7 +282,19 @@ impl ContractClassV1 {
             .collect();
 
         match &filtered_entry_points[..] {
-            [] => Err(PreExecutionError::EntryPointNotFound(call.entry_point_selector)),
+            [] => {
+                let default_selector =
+                    EntryPointSelector(StarkHash::from(constants::DEFAULT_ENTRY_POINT_SELECTOR));
+                match entry_points_of_same_type.iter().find(|ep| ep.selector == default_selector) {
+                    Some(default_entry_point)
+                        if missing_selector_policy
+                            == MissingSelectorPolicy::RouteToDefaultEntryPoint =>
+                    {
+                        Ok(default_entry_point.clone())
+                    }
+                    _ => Err(PreExecutionError::EntryPointNotFound(call.entry_point_selector)),
+                }
+            }
             [entry_point] => Ok((*entry_point).clone()),
             _ => Err(PreExecutionError::DuplicatedEntryPointSelector {
                 selector: call.entry_point_selector,
@@ -284,6 +383,17 @@ pub fn estimate_casm_hash_computation_resources(
     }
 }
 
+fn flatten_segment_lengths(segment_lengths: &NestedIntList, segment_sizes: &mut Vec<usize>) {
+    match segment_lengths {
+        NestedIntList::Leaf(length) => segment_sizes.push(*length),
+        NestedIntList::Node(segments) => {
+            for segment in segments {
+                flatten_segment_lengths(segment, segment_sizes);
+            }
+        }
+    }
+}
+
 // Returns the set of segments that were visited according to the given visited PCs and segment
 // lengths.
 // Each visited segment must have its starting PC visited, and is represented by it.
@@ -434,6 +544,14 @@ pub fn deserialize_program<'de, D: Deserializer<'de>>(
         .map_err(|err| DeserializationError::custom(err.to_string()))
 }
 
+/// Returns the (distinct) codes of the hints of the given program.
+fn deprecated_program_hint_codes(
+    program: &DeprecatedProgram,
+) -> Result<HashSet<String>, ProgramError> {
+    let hints = HashMap::<usize, Vec<HintParams>>::deserialize(&program.hints)?;
+    Ok(hints.into_values().flatten().map(|hint| hint.code).collect())
+}
+
 // V1 utilities.
 
 // TODO(spapini): Share with cairo-lang-runner.
@@ -488,6 +606,14 @@ impl ClassInfo {
         self.abi_length
     }
 
+    /// Returns the size of the class (Sierra, Casm and ABI), in bytes, as charged for by the
+    /// code-size fee.
+    pub fn code_size(&self) -> usize {
+        // We assume each felt is a word.
+        (self.bytecode_length() + self.sierra_program_length) * eth_gas_constants::WORD_WIDTH
+            + self.abi_length
+    }
+
     pub fn new(
         contract_class: &ContractClass,
         sierra_program_length: usize,
@@ -498,6 +624,15 @@ impl ClassInfo {
             ContractClass::V1(_) => (1, sierra_program_length > 0),
         };
 
+        let segments_length: usize = contract_class.bytecode_segment_sizes().iter().sum();
+        let bytecode_length = contract_class.bytecode_length();
+        if segments_length != bytecode_length {
+            return Err(ContractClassError::BytecodeSegmentationMismatch {
+                segments_length,
+                bytecode_length,
+            });
+        }
+
         if condition {
             Ok(Self { contract_class: contract_class.clone(), sierra_program_length, abi_length })
         } else {
@@ -507,4 +642,51 @@ impl ClassInfo {
             })
         }
     }
+
+    pub fn size_metrics(&self) -> ClassSizeMetrics {
+        ClassSizeMetrics {
+            bytecode_segment_sizes: self.contract_class.bytecode_segment_sizes(),
+            sierra_program_length: self.sierra_program_length,
+            abi_length: self.abi_length,
+        }
+    }
+
+    /// Assembles the class info of a declare transaction from its raw (JSON) compiled class.
+    /// The class is parsed as a Cairo 0 class for declare versions 0 and 1, and as a Cairo 1
+    /// (CASM) class otherwise.
+    pub fn from_raw_contract_class(
+        declare_tx: &DeclareTransaction,
+        raw_contract_class: &str,
+        sierra_program_length: usize,
+        abi_length: usize,
+    ) -> ContractClassResult<Self> {
+        let contract_class: ContractClass = match declare_tx {
+            DeclareTransaction::V0(_) | DeclareTransaction::V1(_) => {
+                ContractClassV0::try_from_json_string(raw_contract_class)?.into()
+            }
+            DeclareTransaction::V2(_) | DeclareTransaction::V3(_) => {
+                ContractClassV1::try_from_json_string(raw_contract_class)?.into()
+            }
+        };
+        Self::new(&contract_class, sierra_program_length, abi_length)
+    }
+}
+
+/// The sizes of a declared class, as accounted for in the code-size fee.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClassSizeMetrics {
+    /// The lengths of the innermost bytecode segments (a single segment if unsegmented).
+    pub bytecode_segment_sizes: Vec<usize>,
+    pub sierra_program_length: usize,
+    pub abi_length: usize,
+}
+
+impl ClassSizeMetrics {
+    pub fn bytecode_length(&self) -> usize {
+        self.bytecode_segment_sizes.iter().sum()
+    }
+
+    pub fn n_bytecode_segments(&self) -> usize {
+        self.bytecode_segment_sizes.len()
+    }
 }
@@ -0,0 +1,139 @@
+use std::io::Read;
+
+use cairo_felt::Felt252;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use cairo_lang_starknet_classes::contract_class::ContractClass as SierraContractClass;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use starknet_api::core::{ClassHash, CompiledClassHash, EntryPointSelector};
+use starknet_api::hash::StarkFelt;
+use starknet_crypto::{poseidon_hash_many, FieldElement};
+
+use crate::abi::abi_utils::starknet_keccak;
+use crate::execution::errors::ClassHashError;
+use crate::execution::execution_utils::felt_to_stark_felt;
+use crate::utils::u128_from_usize;
+
+#[cfg(test)]
+#[path = "class_hash_test.rs"]
+pub mod test;
+
+pub type ClassHashResult<T> = Result<T, ClassHashError>;
+
+/// The prefix of the Sierra class hash domain, followed by the contract class version.
+const SIERRA_CLASS_HASH_PREFIX: &str = "CONTRACT_CLASS_V";
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SierraEntryPoint {
+    pub selector: EntryPointSelector,
+    pub function_idx: usize,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SierraEntryPointsByType {
+    #[serde(rename = "CONSTRUCTOR")]
+    pub constructor: Vec<SierraEntryPoint>,
+    #[serde(rename = "EXTERNAL")]
+    pub external: Vec<SierraEntryPoint>,
+    #[serde(rename = "L1_HANDLER")]
+    pub l1_handler: Vec<SierraEntryPoint>,
+}
+
+/// A Sierra class as declared, in the JSON-RPC format. Unlike the `cairo-lang` class, its ABI is
+/// kept as the declared string, which the class hash commits to.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SierraClass {
+    pub sierra_program: Vec<StarkFelt>,
+    pub contract_class_version: String,
+    pub entry_points_by_type: SierraEntryPointsByType,
+    pub abi: String,
+}
+
+impl SierraClass {
+    /// Deserializes the class from JSON, without buffering the whole input.
+    pub fn from_reader(reader: impl Read) -> ClassHashResult<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn class_hash(&self) -> ClassHashResult<ClassHash> {
+        sierra_class_hash(
+            &self.contract_class_version,
+            &self.entry_points_by_type,
+            &self.abi,
+            self.sierra_program.iter().copied(),
+        )
+    }
+
+    /// Converts the class into the `cairo-lang` class, e.g., for compilation (see
+    /// [`crate::execution::contract_class::sierra_compilation::SierraCompiler`]).
+    pub fn to_cairo_lang_class(&self) -> ClassHashResult<SierraContractClass> {
+        let abi: Value =
+            if self.abi.is_empty() { Value::Null } else { serde_json::from_str(&self.abi)? };
+        Ok(serde_json::from_value(json!({
+            "sierra_program": self.sierra_program,
+            "contract_class_version": self.contract_class_version,
+            "entry_points_by_type": self.entry_points_by_type,
+            "abi": abi,
+        }))?)
+    }
+}
+
+/// Computes the hash of a Sierra class, as committed to by Starknet: the Poseidon hash of the
+/// class version, the hashes of its entry points by type, the Starknet Keccak of its ABI string,
+/// and the hash of its Sierra program (given as a stream of felts).
+pub fn sierra_class_hash(
+    contract_class_version: &str,
+    entry_points_by_type: &SierraEntryPointsByType,
+    abi: &str,
+    sierra_program: impl IntoIterator<Item = StarkFelt>,
+) -> ClassHashResult<ClassHash> {
+    let version = short_string(&format!("{SIERRA_CLASS_HASH_PREFIX}{contract_class_version}"))
+        .ok_or_else(|| {
+            ClassHashError::InvalidContractClassVersion(contract_class_version.to_string())
+        })?;
+    let abi_hash = FieldElement::from(felt_to_stark_felt(&starknet_keccak(abi.as_bytes())));
+    let sierra_program: Vec<FieldElement> =
+        sierra_program.into_iter().map(FieldElement::from).collect();
+
+    let SierraEntryPointsByType { constructor, external, l1_handler } = entry_points_by_type;
+    let class_hash = poseidon_hash_many(&[
+        version,
+        entry_points_hash(external),
+        entry_points_hash(l1_handler),
+        entry_points_hash(constructor),
+        abi_hash,
+        poseidon_hash_many(&sierra_program),
+    ]);
+    Ok(ClassHash(StarkFelt::from(class_hash)))
+}
+
+/// Computes the compiled class hash of a CASM class, as committed to by Starknet.
+pub fn compiled_class_hash(casm_class: &CasmContractClass) -> CompiledClassHash {
+    CompiledClassHash(felt_to_stark_felt(&Felt252::from(casm_class.compiled_class_hash())))
+}
+
+/// Computes the compiled class hash of a CASM class given as JSON, without buffering the whole
+/// input.
+pub fn compiled_class_hash_from_reader(reader: impl Read) -> ClassHashResult<CompiledClassHash> {
+    let casm_class: CasmContractClass = serde_json::from_reader(reader)?;
+    Ok(compiled_class_hash(&casm_class))
+}
+
+fn entry_points_hash(entry_points: &[SierraEntryPoint]) -> FieldElement {
+    let flattened: Vec<FieldElement> = entry_points
+        .iter()
+        .flat_map(|entry_point| {
+            let function_idx = StarkFelt::from(u128_from_usize(entry_point.function_idx));
+            [FieldElement::from(entry_point.selector.0), FieldElement::from(function_idx)]
+        })
+        .collect();
+    poseidon_hash_many(&flattened)
+}
+
+/// Encodes a string of up to 31 bytes as a felt.
+fn short_string(string: &str) -> Option<FieldElement> {
+    if string.len() > 31 {
+        return None;
+    }
+    FieldElement::from_byte_slice_be(string.as_bytes()).ok()
+}
@@ -0,0 +1,91 @@
+use assert_matches::assert_matches;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use starknet_api::core::ClassHash;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_crypto::{poseidon_hash_many, FieldElement};
+
+use crate::abi::abi_utils::{selector_from_name, starknet_keccak};
+use crate::execution::contract_class::class_hash::{
+    compiled_class_hash, compiled_class_hash_from_reader, SierraClass,
+};
+use crate::execution::errors::ClassHashError;
+use crate::execution::execution_utils::felt_to_stark_felt;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::CairoVersion;
+
+const ABI: &str = r#"[{"type": "function", "name": "foo", "inputs": [], "outputs": []}]"#;
+
+fn raw_sierra_class(abi: &str) -> String {
+    json!({
+        "sierra_program": ["0x1", "0x2", "0x3"],
+        "contract_class_version": "0.1.0",
+        "entry_points_by_type": {
+            "CONSTRUCTOR": [],
+            "EXTERNAL": [{ "selector": selector_from_name("foo").0, "function_idx": 1 }],
+            "L1_HANDLER": [],
+        },
+        "abi": abi,
+    })
+    .to_string()
+}
+
+#[test]
+fn test_sierra_class_hash() {
+    let sierra_class = SierraClass::from_reader(raw_sierra_class(ABI).as_bytes()).unwrap();
+
+    let felt = |value: u8| FieldElement::from(stark_felt!(value));
+    let expected_class_hash = poseidon_hash_many(&[
+        FieldElement::from_byte_slice_be(b"CONTRACT_CLASS_V0.1.0").unwrap(),
+        poseidon_hash_many(&[FieldElement::from(selector_from_name("foo").0), felt(1)]),
+        poseidon_hash_many(&[]),
+        poseidon_hash_many(&[]),
+        FieldElement::from(felt_to_stark_felt(&starknet_keccak(ABI.as_bytes()))),
+        poseidon_hash_many(&[felt(1), felt(2), felt(3)]),
+    ]);
+    assert_eq!(sierra_class.class_hash().unwrap(), ClassHash(StarkFelt::from(expected_class_hash)));
+
+    // The hash commits to the ABI string as declared, including its formatting.
+    let reformatted_abi = ABI.replace(' ', "");
+    let reformatted_class =
+        SierraClass::from_reader(raw_sierra_class(&reformatted_abi).as_bytes()).unwrap();
+    assert_ne!(reformatted_class.class_hash().unwrap(), sierra_class.class_hash().unwrap());
+
+    let invalid_version_class = SierraClass {
+        contract_class_version: "0.1.0-too-long-for-a-short-string".into(),
+        ..sierra_class
+    };
+    assert_matches!(
+        invalid_version_class.class_hash().unwrap_err(),
+        ClassHashError::InvalidContractClassVersion(version)
+        if version == invalid_version_class.contract_class_version
+    );
+}
+
+#[test]
+fn test_to_cairo_lang_class() {
+    let sierra_class = SierraClass::from_reader(raw_sierra_class("").as_bytes()).unwrap();
+    let cairo_lang_class = sierra_class.to_cairo_lang_class().unwrap();
+
+    assert_eq!(cairo_lang_class.sierra_program.len(), 3);
+    assert_eq!(cairo_lang_class.contract_class_version, "0.1.0");
+    assert_eq!(cairo_lang_class.entry_points_by_type.external[0].function_idx, 1);
+    assert!(cairo_lang_class.abi.is_none());
+}
+
+#[test]
+fn test_compiled_class_hash_from_reader() {
+    let raw_casm_class = FeatureContract::TestContract(CairoVersion::Cairo1).get_raw_class();
+    let casm_class: CasmContractClass = serde_json::from_str(&raw_casm_class).unwrap();
+
+    assert_eq!(
+        compiled_class_hash_from_reader(raw_casm_class.as_bytes()).unwrap(),
+        compiled_class_hash(&casm_class)
+    );
+    assert_matches!(
+        compiled_class_hash_from_reader(&b"{}"[..]).unwrap_err(),
+        ClassHashError::JsonError(_)
+    );
+}
@@ -0,0 +1,111 @@
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use cairo_lang_starknet_classes::contract_class::ContractClass as SierraContractClass;
+use starknet_api::transaction::DeclareTransaction;
+
+pub use crate::execution::contract_class::class_hash::compiled_class_hash;
+use crate::execution::contract_class::class_hash::SierraClass;
+use crate::execution::contract_class::{ClassInfo, ContractClass, ContractClassV1};
+use crate::execution::errors::{ContractClassError, SierraCompilationError};
+
+#[cfg(test)]
+#[path = "sierra_compilation_test.rs"]
+pub mod test;
+
+pub type SierraCompilationResult<T> = Result<T, SierraCompilationError>;
+
+/// The maximal size of a compiled class bytecode on Starknet, in felts.
+pub const DEFAULT_MAX_BYTECODE_SIZE: usize = 81920;
+
+/// Compiles Sierra classes to CASM. Integrators requiring a compiler version other than the one
+/// the Blockifier is built with (e.g., an external compiler binary) may implement it.
+pub trait SierraCompiler {
+    fn compile(
+        &self,
+        sierra_class: SierraContractClass,
+    ) -> SierraCompilationResult<CasmContractClass>;
+}
+
+/// The Sierra compiler of the `cairo-lang` version the Blockifier is built with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CairoLangSierraCompiler {
+    pub max_bytecode_size: usize,
+}
+
+impl Default for CairoLangSierraCompiler {
+    fn default() -> Self {
+        Self { max_bytecode_size: DEFAULT_MAX_BYTECODE_SIZE }
+    }
+}
+
+impl SierraCompiler for CairoLangSierraCompiler {
+    fn compile(
+        &self,
+        sierra_class: SierraContractClass,
+    ) -> SierraCompilationResult<CasmContractClass> {
+        let add_pythonic_hints = false;
+        CasmContractClass::from_contract_class(
+            sierra_class,
+            add_pythonic_hints,
+            self.max_bytecode_size,
+        )
+        .map_err(|error| SierraCompilationError::CompilationFailed(error.to_string()))
+    }
+}
+
+/// Assembles the class info of a Cairo 1 declare transaction from its Sierra class: compiles the
+/// class with the given compiler, and verifies the compiled class hash declared by the
+/// transaction.
+pub fn class_info_from_sierra(
+    declare_tx: &DeclareTransaction,
+    sierra_class: SierraContractClass,
+    abi_length: usize,
+    compiler: &dyn SierraCompiler,
+) -> SierraCompilationResult<ClassInfo> {
+    let declared_compiled_class_hash = match declare_tx {
+        DeclareTransaction::V0(_) | DeclareTransaction::V1(_) => {
+            return Err(SierraCompilationError::UnsupportedDeclareVersion {
+                declare_version: declare_tx.version(),
+            });
+        }
+        DeclareTransaction::V2(tx) => tx.compiled_class_hash,
+        DeclareTransaction::V3(tx) => tx.compiled_class_hash,
+    };
+
+    let sierra_program_length = sierra_class.sierra_program.len();
+    let casm_class = compiler.compile(sierra_class)?;
+    let actual_compiled_class_hash = compiled_class_hash(&casm_class);
+    if actual_compiled_class_hash != declared_compiled_class_hash {
+        return Err(SierraCompilationError::CompiledClassHashMismatch {
+            declared_compiled_class_hash,
+            actual_compiled_class_hash,
+        });
+    }
+
+    let contract_class: ContractClass =
+        ContractClassV1::try_from(casm_class).map_err(ContractClassError::from)?.into();
+    Ok(ClassInfo::new(&contract_class, sierra_program_length, abi_length)?)
+}
+
+/// Same as [`class_info_from_sierra`], for a Sierra class as declared; also verifies the class hash
+/// declared by the transaction.
+pub fn class_info_from_declared_sierra(
+    declare_tx: &DeclareTransaction,
+    sierra_class: &SierraClass,
+    compiler: &dyn SierraCompiler,
+) -> SierraCompilationResult<ClassInfo> {
+    let declared_class_hash = declare_tx.class_hash();
+    let actual_class_hash = sierra_class.class_hash()?;
+    if actual_class_hash != declared_class_hash {
+        return Err(SierraCompilationError::ClassHashMismatch {
+            declared_class_hash,
+            actual_class_hash,
+        });
+    }
+
+    class_info_from_sierra(
+        declare_tx,
+        sierra_class.to_cairo_lang_class()?,
+        sierra_class.abi.len(),
+        compiler,
+    )
+}
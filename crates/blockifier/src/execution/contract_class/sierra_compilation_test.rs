@@ -0,0 +1,83 @@
+use assert_matches::assert_matches;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use cairo_lang_starknet_classes::contract_class::ContractClass as SierraContractClass;
+use pretty_assertions::assert_eq;
+use starknet_api::core::CompiledClassHash;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::{
+    DeclareTransaction, DeclareTransactionV0V1, DeclareTransactionV2, TransactionVersion,
+};
+
+use crate::execution::contract_class::sierra_compilation::{
+    class_info_from_sierra, compiled_class_hash, SierraCompilationResult, SierraCompiler,
+};
+use crate::execution::contract_class::{ContractClass, ContractClassV1};
+use crate::execution::errors::SierraCompilationError;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::CairoVersion;
+
+/// A compiler returning a fixed CASM class, regardless of the compiled Sierra class.
+struct FixedCompiler(CasmContractClass);
+
+impl SierraCompiler for FixedCompiler {
+    fn compile(&self, _: SierraContractClass) -> SierraCompilationResult<CasmContractClass> {
+        Ok(self.0.clone())
+    }
+}
+
+fn sierra_class() -> SierraContractClass {
+    serde_json::from_value(serde_json::json!({
+        "sierra_program": ["0x1", "0x2", "0x3"],
+        "contract_class_version": "0.1.0",
+        "entry_points_by_type": { "EXTERNAL": [], "L1_HANDLER": [], "CONSTRUCTOR": [] },
+        "abi": null
+    }))
+    .unwrap()
+}
+
+fn declare_tx(compiled_class_hash: CompiledClassHash) -> DeclareTransaction {
+    DeclareTransaction::V2(DeclareTransactionV2 { compiled_class_hash, ..Default::default() })
+}
+
+#[test]
+fn test_class_info_from_sierra() {
+    let raw_casm_class = FeatureContract::TestContract(CairoVersion::Cairo1).get_raw_class();
+    let casm_class: CasmContractClass = serde_json::from_str(&raw_casm_class).unwrap();
+    let casm_class_hash = compiled_class_hash(&casm_class);
+    let compiler = FixedCompiler(casm_class.clone());
+    let abi_length = 10;
+
+    let class_info =
+        class_info_from_sierra(&declare_tx(casm_class_hash), sierra_class(), abi_length, &compiler)
+            .unwrap();
+    assert_eq!(
+        class_info.contract_class(),
+        ContractClass::V1(ContractClassV1::try_from(casm_class).unwrap())
+    );
+    assert_eq!(class_info.sierra_program_length(), 3);
+    assert_eq!(class_info.abi_length(), abi_length);
+
+    let declared_compiled_class_hash = CompiledClassHash(stark_felt!(7_u8));
+    assert_matches!(
+        class_info_from_sierra(
+            &declare_tx(declared_compiled_class_hash),
+            sierra_class(),
+            abi_length,
+            &compiler
+        )
+        .unwrap_err(),
+        SierraCompilationError::CompiledClassHashMismatch {
+            declared_compiled_class_hash: declared,
+            actual_compiled_class_hash: actual,
+        } if declared == declared_compiled_class_hash && actual == casm_class_hash
+    );
+
+    let cairo0_declare_tx = DeclareTransaction::V1(DeclareTransactionV0V1::default());
+    assert_matches!(
+        class_info_from_sierra(&cairo0_declare_tx, sierra_class(), abi_length, &compiler)
+            .unwrap_err(),
+        SierraCompilationError::UnsupportedDeclareVersion { declare_version }
+        if declare_version == TransactionVersion::ONE
+    );
+}
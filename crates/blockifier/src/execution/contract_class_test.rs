@@ -1,12 +1,22 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
 use cairo_lang_starknet_classes::NestedIntList;
 use rstest::rstest;
+use starknet_api::core::EntryPointSelector;
+use starknet_api::deprecated_contract_class::{EntryPointOffset, EntryPointType};
+use starknet_api::hash::StarkFelt;
 
-use crate::execution::contract_class::{ContractClassV1, ContractClassV1Inner};
+use crate::execution::contract_class::{
+    ClassInfo, ContractClass, ContractClassV1, ContractClassV1Inner, EntryPointV1,
+};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::execution::errors::{ContractClassError, PreExecutionError};
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::CairoVersion;
 use crate::transaction::errors::TransactionExecutionError;
+use crate::versioned_constants::{DeprecatedHintPolicy, MissingSelectorPolicy};
 
 #[rstest]
 fn test_get_visited_segments() {
@@ -42,3 +52,101 @@ fn test_get_visited_segments() {
         TransactionExecutionError::InvalidSegmentStructure(907, 807)
     );
 }
+
+#[rstest]
+#[case::route_to_default(MissingSelectorPolicy::RouteToDefaultEntryPoint, true)]
+#[case::fail(MissingSelectorPolicy::Fail, false)]
+fn test_missing_selector_policy(
+    #[case] missing_selector_policy: MissingSelectorPolicy,
+    #[case] expect_default: bool,
+) {
+    let default_entry_point = EntryPointV1 {
+        selector: EntryPointSelector(StarkFelt::ZERO),
+        offset: EntryPointOffset(7),
+        builtins: vec![],
+    };
+    let existing_entry_point = EntryPointV1 {
+        selector: EntryPointSelector(StarkFelt::from(1_u8)),
+        offset: EntryPointOffset(0),
+        builtins: vec![],
+    };
+    let contract_class = ContractClassV1(Arc::new(ContractClassV1Inner {
+        program: Default::default(),
+        entry_points_by_type: HashMap::from([(
+            EntryPointType::External,
+            vec![default_entry_point.clone(), existing_entry_point.clone()],
+        )]),
+        hints: Default::default(),
+        bytecode_segment_lengths: NestedIntList::Leaf(0),
+    }));
+
+    // Existing selectors are resolved regardless of the policy.
+    let call = CallEntryPoint {
+        entry_point_type: EntryPointType::External,
+        entry_point_selector: existing_entry_point.selector,
+        ..Default::default()
+    };
+    assert_eq!(
+        contract_class.get_entry_point(&call, missing_selector_policy).unwrap(),
+        existing_entry_point
+    );
+
+    let missing_selector = EntryPointSelector(StarkFelt::from(2_u8));
+    let call = CallEntryPoint { entry_point_selector: missing_selector, ..call };
+    let result = contract_class.get_entry_point(&call, missing_selector_policy);
+    if expect_default {
+        assert_eq!(result.unwrap(), default_entry_point);
+    } else {
+        assert_matches!(
+            result.unwrap_err(),
+            PreExecutionError::EntryPointNotFound(selector) if selector == missing_selector
+        );
+    }
+}
+
+#[test]
+fn test_deprecated_hint_policy() {
+    let ContractClass::V0(test_contract) =
+        FeatureContract::TestContract(CairoVersion::Cairo0).get_class()
+    else {
+        panic!("Expected a Cairo 0 class.");
+    };
+    let allocation_hint = "memory[ap] = segments.add()";
+    assert!(test_contract.hint_codes.contains(allocation_hint));
+
+    test_contract.validate_hints(&DeprecatedHintPolicy::Permissive).unwrap();
+    test_contract.validate_hints(&DeprecatedHintPolicy::Strict).unwrap();
+
+    let custom_policy = DeprecatedHintPolicy::Custom(HashSet::from([allocation_hint.to_string()]));
+    assert_matches!(
+        test_contract.validate_hints(&custom_policy).unwrap_err(),
+        ContractClassError::DisallowedHints { hints }
+        if hints.len() == test_contract.hint_codes.len() - 1
+            && !hints.contains(&allocation_hint.to_string())
+    );
+}
+
+#[test]
+fn test_bytecode_segment_sizes() {
+    let segmented_class = ContractClass::V1(ContractClassV1(Arc::new(ContractClassV1Inner {
+        program: Default::default(),
+        entry_points_by_type: Default::default(),
+        hints: Default::default(),
+        bytecode_segment_lengths: NestedIntList::Node(vec![
+            NestedIntList::Leaf(2),
+            NestedIntList::Node(vec![NestedIntList::Leaf(3), NestedIntList::Leaf(4)]),
+        ]),
+    })));
+    assert_eq!(segmented_class.bytecode_segment_sizes(), vec![2, 3, 4]);
+    // The segment lengths must cover the bytecode exactly.
+    assert_matches!(
+        ClassInfo::new(&segmented_class, 1, 0).unwrap_err(),
+        ContractClassError::BytecodeSegmentationMismatch { segments_length: 9, bytecode_length: 0 }
+    );
+
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1).get_class();
+    let class_info = ClassInfo::new(&test_contract, 1, 0).unwrap();
+    let size_metrics = class_info.size_metrics();
+    assert_eq!(size_metrics.n_bytecode_segments(), 1);
+    assert_eq!(size_metrics.bytecode_length(), test_contract.bytecode_length());
+}
@@ -14,11 +14,14 @@ use crate::execution::deprecated_syscalls::hint_processor::DeprecatedSyscallHint
 use crate::execution::entry_point::{
     CallEntryPoint, EntryPointExecutionContext, EntryPointExecutionResult,
 };
-use crate::execution::errors::{PostExecutionError, PreExecutionError};
+use crate::execution::errors::{EntryPointExecutionError, PostExecutionError, PreExecutionError};
+use crate::execution::execution_artifacts::ArtifactsExport;
 use crate::execution::execution_utils::{
     read_execution_retdata, stark_felt_to_felt, Args, ReadOnlySegments,
 };
+use crate::execution::step_deltas::StepDeltasRecorder;
 use crate::state::state_api::State;
+use crate::versioned_constants::MissingSelectorPolicy;
 
 pub struct VmExecutionContext<'a> {
     pub runner: CairoRunner,
@@ -36,6 +39,8 @@ pub fn execute_entry_point_call(
     resources: &mut ExecutionResources,
     context: &mut EntryPointExecutionContext,
 ) -> EntryPointExecutionResult<CallInfo> {
+    let artifacts_export = ArtifactsExport::for_call(context, &call);
+
     let VmExecutionContext {
         mut runner,
         mut vm,
@@ -56,7 +61,16 @@ pub fn execute_entry_point_call(
     let previous_resources = syscall_handler.resources.clone();
 
     // Execute.
-    run_entry_point(&mut vm, &mut runner, &mut syscall_handler, entry_point_pc, args)?;
+    let run_result =
+        run_entry_point(&mut vm, &mut runner, &mut syscall_handler, entry_point_pc, args);
+    // Export and record before propagating a run failure, so that crashes can be analyzed as well.
+    if let Some(recorder) = &mut syscall_handler.context.step_deltas {
+        recorder.finish_run(call.storage_address, call.entry_point_selector, &mut vm, &mut runner);
+    }
+    if let Some(artifacts_export) = artifacts_export {
+        artifacts_export.export(&mut vm, &mut runner);
+    }
+    run_result?;
 
     Ok(finalize_execution(
         vm,
@@ -77,13 +91,20 @@ pub fn initialize_execution_context<'a>(
     context: &'a mut EntryPointExecutionContext,
 ) -> Result<VmExecutionContext<'a>, PreExecutionError> {
     // Resolve initial PC from EP indicator.
-    let entry_point_pc = resolve_entry_point_pc(call, &contract_class)?;
+    let entry_point_pc = resolve_entry_point_pc(
+        call,
+        &contract_class,
+        context.versioned_constants().missing_selector_policy,
+    )?;
 
     // Instantiate Cairo runner.
     let proof_mode = false;
     let mut runner = CairoRunner::new(&contract_class.program, "starknet", proof_mode)?;
 
-    let trace_enabled = false;
+    // The trace is only needed when exporting the execution artifacts of this call, or recording
+    // its step deltas.
+    let trace_enabled = ArtifactsExport::is_selected(context, call)
+        || context.step_deltas.as_ref().is_some_and(StepDeltasRecorder::is_recording_call);
     let mut vm = VirtualMachine::new(trace_enabled);
 
     runner.initialize_builtins(&mut vm)?;
@@ -106,6 +127,7 @@ pub fn initialize_execution_context<'a>(
 pub fn resolve_entry_point_pc(
     call: &CallEntryPoint,
     contract_class: &ContractClassV0,
+    missing_selector_policy: MissingSelectorPolicy,
 ) -> Result<usize, PreExecutionError> {
     if call.entry_point_type == EntryPointType::Constructor
         && call.entry_point_selector != selector_from_name(CONSTRUCTOR_ENTRY_POINT_NAME)
@@ -119,12 +141,13 @@ pub fn resolve_entry_point_pc(
         .filter(|ep| ep.selector == call.entry_point_selector)
         .collect();
 
-    // Returns the default entrypoint if the given selector is missing.
+    // Returns the default entrypoint if the given selector is missing (and the policy allows it).
     if filtered_entry_points.is_empty() {
         match entry_points_of_same_type.first() {
             Some(entry_point) => {
-                if entry_point.selector
-                    == EntryPointSelector(StarkHash::from(DEFAULT_ENTRY_POINT_SELECTOR))
+                if missing_selector_policy == MissingSelectorPolicy::RouteToDefaultEntryPoint
+                    && entry_point.selector
+                        == EntryPointSelector(StarkHash::from(DEFAULT_ENTRY_POINT_SELECTOR))
                 {
                     return Ok(entry_point.offset.0);
                 } else {
@@ -174,12 +197,11 @@ pub fn prepare_call_arguments(
 
     // Prepare calldata arguments.
     let calldata = &call.calldata.0;
-    let calldata: Vec<MaybeRelocatable> =
-        calldata.iter().map(|&arg| MaybeRelocatable::from(stark_felt_to_felt(arg))).collect();
     let calldata_length = MaybeRelocatable::from(calldata.len());
     args.push(CairoArg::from(calldata_length));
 
-    let calldata_start_ptr = MaybeRelocatable::from(read_only_segments.allocate(vm, &calldata)?);
+    let calldata_start_ptr =
+        MaybeRelocatable::from(read_only_segments.allocate_felts(vm, calldata)?);
     args.push(CairoArg::from(calldata_start_ptr));
 
     Ok((implicit_args, args))
@@ -203,6 +225,11 @@ pub fn run_entry_point(
         vm,
         hint_processor,
     );
+    if result.is_err() {
+        if let Some(deadline) = hint_processor.context.timed_out() {
+            return Err(EntryPointExecutionError::ExecutionTimeout(deadline));
+        }
+    }
 
     Ok(result?)
 }
@@ -231,6 +258,8 @@ pub fn finalize_execution(
     let implicit_args_end_ptr = (vm.get_ap() - 2)?;
     validate_run(&mut vm, &runner, &syscall_handler, implicit_args, implicit_args_end_ptr)?;
 
+    syscall_handler.context.track_memory_cells(&vm)?;
+
     // Take into account the VM execution resources of the current call, without inner calls.
     // Has to happen after marking holes in segments as accessed.
     let vm_resources_without_inner_calls = runner
@@ -257,6 +286,7 @@ pub fn finalize_execution(
         inner_calls: syscall_handler.inner_calls,
         storage_read_values: syscall_handler.read_values,
         accessed_storage_keys: syscall_handler.accessed_keys,
+        syscall_counter: syscall_handler.syscall_counter,
     })
 }
 
@@ -148,6 +148,10 @@ fn test_nested_library_call() {
         resources: storage_entry_point_resources.clone(),
         storage_read_values: vec![stark_felt!(0_u8), stark_felt!(value + 1)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key + 1))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+            (DeprecatedSyscallSelector::StorageRead, 1),
+        ]),
         ..Default::default()
     };
     let mut library_call_resources = &get_syscall_resources(DeprecatedSyscallSelector::LibraryCall)
@@ -162,6 +166,7 @@ fn test_nested_library_call() {
         execution: CallExecution::from_retdata(retdata![stark_felt!(value + 1)]),
         resources: library_call_resources.clone(),
         inner_calls: vec![nested_storage_call_info],
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::LibraryCall, 1)]),
         ..Default::default()
     };
     let storage_call_info = CallInfo {
@@ -170,6 +175,10 @@ fn test_nested_library_call() {
         resources: storage_entry_point_resources.clone(),
         storage_read_values: vec![stark_felt!(0_u8), stark_felt!(value)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+            (DeprecatedSyscallSelector::StorageRead, 1),
+        ]),
         ..Default::default()
     };
 
@@ -186,6 +195,7 @@ fn test_nested_library_call() {
         execution: CallExecution::from_retdata(retdata![stark_felt!(0_u8)]),
         resources: main_call_resources,
         inner_calls: vec![library_call_info, storage_call_info],
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::LibraryCall, 2)]),
         ..Default::default()
     };
 
@@ -235,6 +245,10 @@ fn test_call_contract() {
         },
         storage_read_values: vec![StarkFelt::ZERO, stark_felt!(value)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+            (DeprecatedSyscallSelector::StorageRead, 1),
+        ]),
         ..Default::default()
     };
     let expected_call_info = CallInfo {
@@ -255,6 +269,7 @@ fn test_call_contract() {
                     3,
                 )]),
             },
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::CallContract, 1)]),
         ..Default::default()
     };
 
@@ -46,6 +46,7 @@ use crate::execution::execution_utils::{
 };
 use crate::execution::hint_code;
 use crate::execution::syscalls::hint_processor::EmitEventError;
+use crate::state::call_frame_state::CallFrameState;
 use crate::state::errors::StateError;
 use crate::state::state_api::State;
 
@@ -60,6 +61,7 @@ pub enum DeprecatedSyscallExecutionError {
     #[error("{error}")]
     CallContractExecutionError {
         storage_address: ContractAddress,
+        selector: EntryPointSelector,
         error: Box<DeprecatedSyscallExecutionError>,
     },
     #[error(transparent)]
@@ -68,12 +70,21 @@ pub enum DeprecatedSyscallExecutionError {
     LibraryCallExecutionError {
         class_hash: ClassHash,
         storage_address: ContractAddress,
+        selector: EntryPointSelector,
         error: Box<DeprecatedSyscallExecutionError>,
     },
     #[error("Invalid syscall input: {input:?}; {info}")]
     InvalidSyscallInput { input: StarkFelt, info: String },
     #[error("Invalid syscall selector: {0:?}.")]
     InvalidDeprecatedSyscallSelector(StarkFelt),
+    #[error(
+        "Contract code may not write to the storage of protected contract {storage_address:?}."
+    )]
+    ProtectedStorageWrite { storage_address: ContractAddress },
+    #[error("Contract code may not replace the class of protected contract {contract_address:?}.")]
+    ProtectedClassReplacement { contract_address: ContractAddress },
+    #[error("Contract code may not deploy a contract at protected address {contract_address:?}.")]
+    ProtectedContractDeployment { contract_address: ContractAddress },
     #[error(transparent)]
     MathError(#[from] cairo_vm::types::errors::math_errors::MathError),
     #[error(transparent)]
@@ -97,9 +108,14 @@ impl From<DeprecatedSyscallExecutionError> for HintError {
 }
 
 impl DeprecatedSyscallExecutionError {
-    pub fn as_call_contract_execution_error(self, storage_address: ContractAddress) -> Self {
+    pub fn as_call_contract_execution_error(
+        self,
+        storage_address: ContractAddress,
+        selector: EntryPointSelector,
+    ) -> Self {
         DeprecatedSyscallExecutionError::CallContractExecutionError {
             storage_address,
+            selector,
             error: Box::new(self),
         }
     }
@@ -108,10 +124,12 @@ impl DeprecatedSyscallExecutionError {
         self,
         class_hash: ClassHash,
         storage_address: ContractAddress,
+        selector: EntryPointSelector,
     ) -> Self {
         DeprecatedSyscallExecutionError::LibraryCallExecutionError {
             class_hash,
             storage_address,
+            selector,
             error: Box::new(self),
         }
     }
@@ -223,8 +241,9 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
 
         let selector = DeprecatedSyscallSelector::try_from(self.read_next_syscall_selector(vm)?)?;
         self.increment_syscall_count(&selector);
+        self.context.notify_syscall(selector);
 
-        match selector {
+        let result = match selector {
             DeprecatedSyscallSelector::CallContract => self.execute_syscall(vm, call_contract),
             DeprecatedSyscallSelector::DelegateCall => self.execute_syscall(vm, delegate_call),
             DeprecatedSyscallSelector::DelegateL1Handler => {
@@ -260,7 +279,10 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
             _ => Err(HintError::UnknownHint(
                 format!("Unsupported syscall selector {selector:?}.").into(),
             )),
-        }
+        };
+        self.context.notify_syscall_exit(selector);
+
+        result
     }
 
     pub fn get_or_allocate_tx_signature_segment(
@@ -344,7 +366,7 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
         vm: &mut VirtualMachine,
     ) -> DeprecatedSyscallResult<Relocatable> {
         let tx_signature_start_ptr = self.get_or_allocate_tx_signature_segment(vm)?;
-        let TransactionContext { block_context, tx_info } = self.context.tx_context.as_ref();
+        let TransactionContext { block_context, tx_info, .. } = self.context.tx_context.as_ref();
         let tx_signature_length = tx_info.signature().0.len();
         let tx_info: Vec<MaybeRelocatable> = vec![
             stark_felt_to_felt(tx_info.signed_version().0).into(),
@@ -353,7 +375,7 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
             tx_signature_length.into(),
             tx_signature_start_ptr.into(),
             stark_felt_to_felt(tx_info.transaction_hash().0).into(),
-            Felt252::from_bytes_be(block_context.chain_info.chain_id.0.as_bytes()).into(),
+            stark_felt_to_felt(block_context.chain_info.chain_id.to_short_string_felt()).into(),
             stark_felt_to_felt(tx_info.nonce().0).into(),
         ];
 
@@ -368,6 +390,9 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
         self.accessed_keys.insert(key);
         let value = self.state.get_storage_at(self.storage_address, key)?;
         self.read_values.push(value);
+        if let Some(observers) = self.context.observers() {
+            observers.notify_storage_read(self.storage_address, key, value);
+        }
 
         Ok(StorageReadResponse { value })
     }
@@ -377,8 +402,31 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
         key: StorageKey,
         value: StarkFelt,
     ) -> DeprecatedSyscallResult<StorageWriteResponse> {
+        let protected_addresses =
+            &self.context.tx_context.block_context.chain_info.protected_addresses;
+        if protected_addresses.contains(&self.storage_address) {
+            return Err(DeprecatedSyscallExecutionError::ProtectedStorageWrite {
+                storage_address: self.storage_address,
+            });
+        }
+
         self.accessed_keys.insert(key);
+        if let Some((recorder, n_remaining_steps)) = self.context.step_deltas_recorder() {
+            if recorder.is_recording() {
+                let previous_value = self.state.get_storage_at(self.storage_address, key)?;
+                recorder.record_storage_write(
+                    n_remaining_steps,
+                    self.storage_address,
+                    key,
+                    previous_value,
+                    value,
+                );
+            }
+        }
         self.state.set_storage_at(self.storage_address, key, value)?;
+        if let Some(observers) = self.context.observers() {
+            observers.notify_storage_write(self.storage_address, key, value);
+        }
 
         Ok(StorageWriteResponse {})
     }
@@ -390,7 +438,7 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
 
 impl ResourceTracker for DeprecatedSyscallHintProcessor<'_> {
     fn consumed(&self) -> bool {
-        self.context.vm_run_resources.consumed()
+        self.context.vm_run_resources.consumed() || self.context.passed_deadline_on_step()
     }
 
     fn consume_step(&mut self) {
@@ -460,8 +508,19 @@ pub fn execute_inner_call(
     vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<ReadOnlySegment> {
+    let failed_call_state_policy =
+        syscall_handler.context.versioned_constants().failed_call_state_policy;
+    // State modifications of the inner call are buffered until it returns.
+    let mut frame_state = CallFrameState::new(syscall_handler.state);
     let call_info =
-        call.execute(syscall_handler.state, syscall_handler.resources, syscall_handler.context)?;
+        match call.execute(&mut frame_state, syscall_handler.resources, syscall_handler.context) {
+            Ok(call_info) => call_info,
+            Err(error) => {
+                frame_state.fail(failed_call_state_policy)?;
+                return Err(error.into());
+            }
+        };
+    frame_state.commit()?;
     let retdata = &call_info.execution.retdata.0;
     let retdata: Vec<MaybeRelocatable> =
         retdata.iter().map(|&x| MaybeRelocatable::from(stark_felt_to_felt(x))).collect();
@@ -492,11 +551,15 @@ pub fn execute_library_call(
         storage_address: syscall_handler.storage_address,
         caller_address: syscall_handler.caller_address,
         call_type: CallType::Delegate,
-        initial_gas: syscall_handler.context.get_gas_cost("initial_gas_cost"),
+        initial_gas: syscall_handler.context.gas_costs().initial_gas_cost,
     };
 
     execute_inner_call(entry_point, vm, syscall_handler).map_err(|error| {
-        error.as_lib_call_execution_error(class_hash, syscall_handler.storage_address)
+        error.as_lib_call_execution_error(
+            class_hash,
+            syscall_handler.storage_address,
+            entry_point_selector,
+        )
     })
 }
 
@@ -1,7 +1,7 @@
 use cairo_felt::Felt252;
 use cairo_vm::types::relocatable::Relocatable;
 use cairo_vm::vm::vm_core::VirtualMachine;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockNumber, BlockTimestamp};
 use starknet_api::core::{
     calculate_contract_address, ClassHash, ContractAddress, EntryPointSelector, EthAddress,
@@ -18,14 +18,15 @@ use self::hint_processor::{
     execute_inner_call, execute_library_call, felt_to_bool, read_call_params, read_calldata,
     read_felt_array, DeprecatedSyscallExecutionError, DeprecatedSyscallHintProcessor,
 };
-use super::syscalls::exceeds_event_size_limit;
+use super::syscalls::{exceeds_event_size_limit, track_cumulative_event_limits};
 use crate::execution::call_info::{MessageToL1, OrderedEvent, OrderedL2ToL1Message};
 use crate::execution::common_hints::ExecutionMode;
 use crate::execution::entry_point::{CallEntryPoint, CallType, ConstructorContext};
 use crate::execution::execution_utils::{
-    execute_deployment, stark_felt_from_ptr, write_maybe_relocatable, write_stark_felt,
-    ReadOnlySegment,
+    check_deploy_collision, execute_deployment, stark_felt_from_ptr, write_maybe_relocatable,
+    write_stark_felt, ReadOnlySegment,
 };
+use crate::state::call_frame_state::CallFrameState;
 
 #[cfg(test)]
 #[path = "deprecated_syscalls_test.rs"]
@@ -35,7 +36,7 @@ pub mod hint_processor;
 pub type DeprecatedSyscallResult<T> = Result<T, DeprecatedSyscallExecutionError>;
 pub type WriteResponseResult = DeprecatedSyscallResult<()>;
 
-#[derive(Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, PartialEq, Serialize)]
 pub enum DeprecatedSyscallSelector {
     CallContract,
     DelegateCall,
@@ -185,28 +186,28 @@ pub fn call_contract(
     vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<CallContractResponse> {
-    let storage_address = request.contract_address;
+    let (storage_address, selector) = (request.contract_address, request.function_selector);
     // Check that the call is legal if in Validate execution mode.
     if syscall_handler.is_validate_mode() && syscall_handler.storage_address != storage_address {
         let error = DeprecatedSyscallExecutionError::InvalidSyscallInExecutionMode {
             syscall_name: "call_contract".to_string(),
             execution_mode: syscall_handler.execution_mode(),
         };
-        return Err(error.as_call_contract_execution_error(storage_address));
+        return Err(error.as_call_contract_execution_error(storage_address, selector));
     }
     let entry_point = CallEntryPoint {
         class_hash: None,
         code_address: Some(storage_address),
         entry_point_type: EntryPointType::External,
-        entry_point_selector: request.function_selector,
+        entry_point_selector: selector,
         calldata: request.calldata,
         storage_address,
         caller_address: syscall_handler.storage_address,
         call_type: CallType::Call,
-        initial_gas: syscall_handler.context.get_gas_cost("initial_gas_cost"),
+        initial_gas: syscall_handler.context.gas_costs().initial_gas_cost,
     };
     let retdata_segment = execute_inner_call(entry_point, vm, syscall_handler)
-        .map_err(|error| error.as_call_contract_execution_error(storage_address))?;
+        .map_err(|error| error.as_call_contract_execution_error(storage_address, selector))?;
 
     Ok(CallContractResponse { segment: retdata_segment })
 }
@@ -319,6 +320,26 @@ pub fn deploy(
         &request.constructor_calldata,
         deployer_address_for_calculation,
     )?;
+    if syscall_handler
+        .context
+        .tx_context
+        .block_context
+        .chain_info
+        .protected_addresses
+        .contains(&deployed_contract_address)
+    {
+        return Err(DeprecatedSyscallExecutionError::ProtectedContractDeployment {
+            contract_address: deployed_contract_address,
+        });
+    }
+    if check_deploy_collision(
+        syscall_handler.state,
+        syscall_handler.context,
+        deployed_contract_address,
+        request.class_hash,
+    )? {
+        return Ok(DeployResponse { contract_address: deployed_contract_address });
+    }
 
     let ctor_context = ConstructorContext {
         class_hash: request.class_hash,
@@ -326,14 +347,26 @@ pub fn deploy(
         storage_address: deployed_contract_address,
         caller_address: deployer_address,
     };
-    let call_info = execute_deployment(
-        syscall_handler.state,
+    let initial_gas = syscall_handler.context.gas_costs().initial_gas_cost;
+    let failed_call_state_policy =
+        syscall_handler.context.versioned_constants().failed_call_state_policy;
+    // As with inner calls, the modifications of the deployment are buffered until it returns.
+    let mut frame_state = CallFrameState::new(syscall_handler.state);
+    let call_info = match execute_deployment(
+        &mut frame_state,
         syscall_handler.resources,
         syscall_handler.context,
         ctor_context,
         request.constructor_calldata,
-        syscall_handler.context.get_gas_cost("initial_gas_cost"),
-    )?;
+        initial_gas,
+    ) {
+        Ok(call_info) => call_info,
+        Err(error) => {
+            frame_state.fail(failed_call_state_policy)?;
+            return Err(error.into());
+        }
+    };
+    frame_state.commit()?;
     syscall_handler.inner_calls.push(call_info);
 
     Ok(DeployResponse { contract_address: deployed_contract_address })
@@ -369,14 +402,19 @@ pub fn emit_event(
     _vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<EmitEventResponse> {
+    let storage_address = syscall_handler.storage_address;
     let execution_context = &mut syscall_handler.context;
     exceeds_event_size_limit(
         execution_context.versioned_constants(),
         execution_context.n_emitted_events + 1,
         &request.content,
     )?;
+    track_cumulative_event_limits(execution_context, &request.content)?;
     let ordered_event =
         OrderedEvent { order: execution_context.n_emitted_events, event: request.content };
+    if let Some(observers) = execution_context.observers() {
+        observers.notify_event(storage_address, &ordered_event);
+    }
     syscall_handler.events.push(ordered_event);
     execution_context.n_emitted_events += 1;
 
@@ -634,9 +672,21 @@ pub fn replace_class(
     _vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<ReplaceClassResponse> {
+    let contract_address = syscall_handler.storage_address;
+    if syscall_handler
+        .context
+        .tx_context
+        .block_context
+        .chain_info
+        .protected_addresses
+        .contains(&contract_address)
+    {
+        return Err(DeprecatedSyscallExecutionError::ProtectedClassReplacement { contract_address });
+    }
+
     // Ensure the class is declared (by reading it).
     syscall_handler.state.get_compiled_contract_class(request.class_hash)?;
-    syscall_handler.state.set_class_hash_at(syscall_handler.storage_address, request.class_hash)?;
+    syscall_handler.state.set_class_hash_at(contract_address, request.class_hash)?;
 
     Ok(ReplaceClassResponse {})
 }
@@ -1,10 +1,12 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::min;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use cairo_vm::vm::runners::cairo_runner::{ExecutionResources, ResourceTracker, RunResources};
+use cairo_vm::vm::vm_core::VirtualMachine;
 use num_traits::{Inv, Zero};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
@@ -13,15 +15,19 @@ use starknet_api::transaction::{Calldata, TransactionVersion};
 use crate::abi::abi_utils::selector_from_name;
 use crate::abi::constants;
 use crate::context::{BlockContext, TransactionContext};
-use crate::execution::call_info::CallInfo;
+use crate::execution::call_info::{CallInfo, TopLevelCall};
 use crate::execution::common_hints::ExecutionMode;
-use crate::execution::errors::{EntryPointExecutionError, PreExecutionError};
+use crate::execution::errors::{EntryPointExecutionError, PostExecutionError, PreExecutionError};
+use crate::execution::execution_observer::{CallFrame, ExecutionBudget, ExecutionObservers};
 use crate::execution::execution_utils::execute_entry_point_call;
+use crate::execution::step_deltas::StepDeltasRecorder;
+use crate::execution::syscalls::gas_schedule::SyscallGasSchedule;
+use crate::execution::syscalls::SyscallSelector;
 use crate::state::state_api::State;
 use crate::transaction::objects::{HasRelatedFeeType, TransactionExecutionResult, TransactionInfo};
 use crate::transaction::transaction_types::TransactionType;
 use crate::utils::{u128_from_usize, usize_from_u128};
-use crate::versioned_constants::VersionedConstants;
+use crate::versioned_constants::{GasCosts, VersionedConstants};
 
 #[cfg(test)]
 #[path = "entry_point_test.rs"]
@@ -33,14 +39,14 @@ pub const FAULTY_CLASS_HASH: &str =
 pub type EntryPointExecutionResult<T> = Result<T, EntryPointExecutionError>;
 
 /// Represents a the type of the call (used for debugging).
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CallType {
     #[default]
     Call = 0,
     Delegate = 1,
 }
 /// Represents a call to an entry point of a Starknet contract.
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CallEntryPoint {
     // The class hash is not given if it can be deduced from the storage address.
     pub class_hash: Option<ClassHash>,
@@ -58,6 +64,51 @@ pub struct CallEntryPoint {
     pub initial_gas: u64,
 }
 
+/// An execution extension bounding the memory used by transaction execution, for
+/// memory-constrained environments. The limits apply to each stage of a transaction (validation,
+/// execution and fee transfer) separately, like the step limits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryLimits {
+    /// The maximal number of VM memory cells used by the call frames of a stage, in total. Checked
+    /// at the end of each call frame.
+    pub max_memory_cells: Option<usize>,
+    /// The maximal number of call frames of a stage, bounding the size of its trace.
+    pub max_call_frames: Option<usize>,
+    /// Whether to drop the execution traces of transactions once executed, keeping only the
+    /// aggregated resources of their top-level calls; see `TransactionExecutionInfo::drop_traces`.
+    pub traceless: bool,
+}
+
+impl MemoryLimits {
+    pub fn is_traceless(block_context: &BlockContext) -> bool {
+        block_context.extensions().get::<Self>().is_some_and(|limits| limits.traceless)
+    }
+}
+
+/// Reading the clock on every VM step is too costly; the execution deadline's duration is only
+/// checked once every this many steps.
+const DEADLINE_CLOCK_CHECK_INTERVAL: usize = 1024;
+
+/// A deadline aborting runaway executions mid-run with an `ExecutionTimeout` error, rather than
+/// waiting for the step limits to be exhausted; registered by inserting it into the block context
+/// extensions. Like the step limits, it applies to each stage of a transaction separately, from
+/// the creation of its execution context.
+/// Note: a timeout depends on the wall clock of the sequencer, so transactions timing out are
+/// rejected rather than reverted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ExecutionDeadline {
+    pub max_duration: Option<Duration>,
+    pub max_steps: Option<usize>,
+}
+
+// An execution deadline, as set for a specific execution context.
+#[derive(Clone, Copy, Debug)]
+struct ContextDeadline {
+    deadline: ExecutionDeadline,
+    expires_at: Option<Instant>,
+    initial_n_steps: usize,
+}
+
 impl CallEntryPoint {
     pub fn execute(
         mut self,
@@ -71,6 +122,7 @@ impl CallEntryPoint {
             context.versioned_constants().max_recursion_depth,
         );
         decrement_when_dropped.try_increment_and_check_depth()?;
+        context.track_call_frame()?;
 
         // Validate contract is deployed.
         let storage_address = self.storage_address;
@@ -96,7 +148,30 @@ impl CallEntryPoint {
         self.class_hash = Some(class_hash);
         let contract_class = state.get_compiled_contract_class(class_hash)?;
 
-        execute_entry_point_call(self, contract_class, state, resources, context).map_err(|error| {
+        let observers = context.observers();
+        let frame = CallFrame {
+            storage_address,
+            entry_point_selector: self.entry_point_selector,
+            depth: *context.current_recursion_depth.borrow() - 1,
+        };
+        if let Some(observers) = &observers {
+            observers.notify_call_start(frame);
+        }
+        if let Some((recorder, n_remaining_steps)) = context.step_deltas_recorder() {
+            recorder.enter_call(n_remaining_steps);
+        }
+        let initial_n_remaining_steps = context.n_remaining_steps();
+        let execution_result =
+            execute_entry_point_call(self, contract_class, state, resources, context);
+        if let Some((recorder, n_remaining_steps)) = context.step_deltas_recorder() {
+            recorder.exit_call(n_remaining_steps);
+        }
+        if let Some(observers) = &observers {
+            let n_steps = initial_n_remaining_steps.saturating_sub(context.n_remaining_steps());
+            observers.notify_call_end(frame, n_steps, execution_result.is_ok());
+        }
+
+        execution_result.map_err(|error| {
             let vm_trace = error.try_to_vm_trace();
             match error {
                 // On VM error, pack the stack trace into the propagated error.
@@ -139,16 +214,31 @@ pub struct EntryPointExecutionContext {
     pub vm_run_resources: RunResources,
     /// Used for tracking events order during the current execution.
     pub n_emitted_events: usize,
+    /// Used for enforcing the cumulative event limits during the current execution.
+    pub n_emitted_event_keys: usize,
+    pub n_emitted_event_data: usize,
     /// Used for tracking L2-to-L1 messages order during the current execution.
     pub n_sent_messages_to_l1: usize,
     /// Used to track error stack for call chain.
     pub error_stack: Vec<(ContractAddress, String)>,
+    /// Used for enforcing the memory limits during the current execution.
+    pub n_call_frames: usize,
+    pub n_memory_cells: usize,
+    /// The Sierra gas left in the running Cairo 1 frame, as of its last syscall (or its start).
+    pub remaining_gas: Option<u64>,
 
     // Managed by dedicated guard object.
     current_recursion_depth: Arc<RefCell<usize>>,
 
     // The execution mode affects the behavior of the hint processor.
     pub execution_mode: ExecutionMode,
+
+    // Set only when recording the step deltas of a call made by this context.
+    pub(crate) step_deltas: Option<StepDeltasRecorder>,
+
+    deadline: Option<ContextDeadline>,
+    // Set once a VM step stopped the execution on passing the deadline.
+    timed_out: Cell<bool>,
 }
 
 impl EntryPointExecutionContext {
@@ -158,14 +248,28 @@ impl EntryPointExecutionContext {
         limit_steps_by_resources: bool,
     ) -> TransactionExecutionResult<Self> {
         let max_steps = Self::max_steps(&tx_context, &mode, limit_steps_by_resources)?;
+        let deadline =
+            tx_context.extensions.get::<ExecutionDeadline>().map(|deadline| ContextDeadline {
+                deadline: *deadline,
+                expires_at: deadline.max_duration.map(|max_duration| Instant::now() + max_duration),
+                initial_n_steps: max_steps,
+            });
         Ok(Self {
             vm_run_resources: RunResources::new(max_steps),
             n_emitted_events: 0,
+            n_emitted_event_keys: 0,
+            n_emitted_event_data: 0,
             n_sent_messages_to_l1: 0,
             error_stack: vec![],
+            n_call_frames: 0,
+            n_memory_cells: 0,
+            remaining_gas: None,
             tx_context: tx_context.clone(),
             current_recursion_depth: Default::default(),
             execution_mode: mode,
+            step_deltas: None,
+            deadline,
+            timed_out: Cell::new(false),
         })
     }
 
@@ -191,7 +295,7 @@ impl EntryPointExecutionContext {
         mode: &ExecutionMode,
         limit_steps_by_resources: bool,
     ) -> TransactionExecutionResult<usize> {
-        let TransactionContext { block_context, tx_info } = tx_context;
+        let TransactionContext { block_context, tx_info, .. } = tx_context;
         let BlockContext { block_info, versioned_constants, .. } = block_context;
         let block_upper_bound = match mode {
             // TODO(Ori, 1/2/2024): Write an indicative expect message explaining why the conversion
@@ -266,6 +370,117 @@ impl EntryPointExecutionContext {
         self.vm_run_resources.get_n_steps().expect("The number of steps must be initialized.")
     }
 
+    /// Records the step deltas of the call selected by the registered `StepDeltasConfig`, if it is
+    /// made by the given top-level call, run with this context.
+    pub fn record_step_deltas(&mut self, top_level_call: TopLevelCall) {
+        self.step_deltas = StepDeltasRecorder::for_top_level_call(&self.tx_context, top_level_call);
+    }
+
+    /// Returns the step deltas recorder, if any, with the available steps in run resources.
+    pub(crate) fn step_deltas_recorder(&mut self) -> Option<(&mut StepDeltasRecorder, usize)> {
+        let recorder = self.step_deltas.as_mut()?;
+        let n_remaining_steps =
+            self.vm_run_resources.get_n_steps().expect("The number of steps must be initialized.");
+        Some((recorder, n_remaining_steps))
+    }
+
+    /// Returns the registered execution deadline, if the execution was stopped on passing it;
+    /// errors of an execution that was not stopped are not timeouts, even if the deadline has
+    /// passed since.
+    pub fn timed_out(&self) -> Option<ExecutionDeadline> {
+        self.deadline.filter(|_| self.timed_out.get()).map(|deadline| deadline.deadline)
+    }
+
+    /// Checks the execution deadline on a VM step, only reading the clock on some of the steps;
+    /// once passed, the execution is stopped for good.
+    pub(crate) fn passed_deadline_on_step(&self) -> bool {
+        if !self.timed_out.get() && self.check_deadline() {
+            self.timed_out.set(true);
+        }
+        self.timed_out.get()
+    }
+
+    fn check_deadline(&self) -> bool {
+        let Some(ContextDeadline { deadline, expires_at, initial_n_steps }) = self.deadline else {
+            return false;
+        };
+        let n_steps = initial_n_steps.saturating_sub(self.n_remaining_steps());
+        let read_clock = n_steps % DEADLINE_CLOCK_CHECK_INTERVAL == 0;
+        deadline.max_steps.is_some_and(|max_steps| n_steps >= max_steps)
+            || (read_clock && expires_at.is_some_and(|expires_at| Instant::now() >= expires_at))
+    }
+
+    /// Returns the execution budget left at this point of the execution.
+    pub fn execution_budget(&self) -> ExecutionBudget {
+        ExecutionBudget {
+            remaining_steps: self.n_remaining_steps(),
+            remaining_gas: self.remaining_gas,
+        }
+    }
+
+    /// Returns the registered execution observers, if any.
+    #[cfg(feature = "tracing")]
+    pub fn observers(&self) -> Option<Arc<ExecutionObservers>> {
+        self.tx_context.extensions.get::<ExecutionObservers>()
+    }
+
+    /// Execution observers are not notified without the `tracing` feature.
+    #[cfg(not(feature = "tracing"))]
+    pub fn observers(&self) -> Option<Arc<ExecutionObservers>> {
+        None
+    }
+
+    /// Returns the registered memory limits, if any.
+    pub fn memory_limits(&self) -> Option<Arc<MemoryLimits>> {
+        self.tx_context.extensions.get::<MemoryLimits>()
+    }
+
+    /// Counts a new call frame, enforcing the call frame limit.
+    pub fn track_call_frame(&mut self) -> EntryPointExecutionResult<()> {
+        self.n_call_frames += 1;
+        match self.memory_limits().and_then(|limits| limits.max_call_frames) {
+            Some(max_call_frames) if self.n_call_frames > max_call_frames => {
+                Err(EntryPointExecutionError::CallFrameLimitExceeded { max_call_frames })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts the VM memory cells used by a finished call frame, enforcing the memory cell limit.
+    pub fn track_memory_cells(&mut self, vm: &VirtualMachine) -> Result<(), PostExecutionError> {
+        let max_memory_cells = self.memory_limits().and_then(|limits| limits.max_memory_cells);
+        let Some(max_memory_cells) = max_memory_cells else {
+            return Ok(());
+        };
+
+        let n_frame_memory_cells: usize = (0..vm.segments.num_segments())
+            .filter_map(|segment_index| vm.get_segment_used_size(segment_index))
+            .sum();
+        self.n_memory_cells += n_frame_memory_cells;
+        if self.n_memory_cells > max_memory_cells {
+            return Err(PostExecutionError::MemoryLimitExceeded {
+                n_memory_cells: self.n_memory_cells,
+                max_memory_cells,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Notifies the registered execution observers, if any, of the given syscall.
+    pub fn notify_syscall(&self, selector: SyscallSelector) {
+        if let Some(observers) = self.observers() {
+            observers.notify_syscall(selector, self.execution_budget());
+        }
+    }
+
+    /// Notifies the registered execution observers, if any, of the end of the given syscall.
+    pub fn notify_syscall_exit(&self, selector: SyscallSelector) {
+        if let Some(observers) = self.observers() {
+            observers.notify_syscall_exit(selector, self.execution_budget());
+        }
+    }
+
     /// Subtracts the given number of steps from the currently available run resources.
     /// Used for limiting the number of steps available during the execution stage, to leave enough
     /// steps available for the fee transfer stage.
@@ -327,6 +542,19 @@ impl EntryPointExecutionContext {
     pub fn get_gas_cost(&self, name: &str) -> u64 {
         self.versioned_constants().gas_cost(name)
     }
+
+    pub fn gas_costs(&self) -> &GasCosts {
+        self.versioned_constants().gas_costs()
+    }
+
+    /// Returns the gas cost of the syscall, taking the registered syscall gas schedule into
+    /// account; `None` if the syscall is not supported by Cairo 1 contracts.
+    pub fn syscall_gas_cost(&self, selector: SyscallSelector) -> Option<u64> {
+        match self.tx_context.extensions.get::<SyscallGasSchedule>() {
+            Some(schedule) => schedule.gas_cost(selector, self.gas_costs()),
+            None => SyscallGasSchedule::default_gas_cost(selector, self.gas_costs()),
+        }
+    }
 }
 
 pub fn execute_constructor_entry_point(
@@ -340,7 +568,11 @@ pub fn execute_constructor_entry_point(
     // Ensure the class is declared (by reading it).
     let contract_class = state.get_compiled_contract_class(ctor_context.class_hash)?;
     let Some(constructor_selector) = contract_class.constructor_selector() else {
-        // Contract has no constructor.
+        // Contract has no constructor; it is still an inner call of the deploying call.
+        if let Some((recorder, n_remaining_steps)) = context.step_deltas_recorder() {
+            recorder.enter_call(n_remaining_steps);
+            recorder.exit_call(n_remaining_steps);
+        }
         return handle_empty_constructor(ctor_context, calldata, remaining_gas);
     };
 
@@ -17,9 +17,9 @@ use crate::execution::entry_point::{
     CallEntryPoint, EntryPointExecutionContext, EntryPointExecutionResult,
 };
 use crate::execution::errors::{EntryPointExecutionError, PostExecutionError, PreExecutionError};
+use crate::execution::execution_artifacts::ArtifactsExport;
 use crate::execution::execution_utils::{
-    read_execution_retdata, stark_felt_to_felt, write_maybe_relocatable, write_stark_felt, Args,
-    ReadOnlySegments,
+    read_execution_retdata, write_maybe_relocatable, write_stark_felt, Args, ReadOnlySegments,
 };
 use crate::execution::syscalls::hint_processor::SyscallHintProcessor;
 use crate::state::state_api::State;
@@ -54,6 +54,8 @@ pub fn execute_entry_point_call(
     let class_hash = call.class_hash.ok_or(EntryPointExecutionError::InternalError(
         "Class hash must not be None when executing an entry point.".into(),
     ))?;
+    let artifacts_export = ArtifactsExport::for_call(context, &call);
+    context.remaining_gas = Some(call.initial_gas);
 
     let VmExecutionContext {
         mut runner,
@@ -79,23 +81,33 @@ pub fn execute_entry_point_call(
     // Execute.
     let bytecode_length = contract_class.bytecode_length();
     let program_segment_size = bytecode_length + program_extra_data_length;
-    run_entry_point(
+    let run_result = run_entry_point(
         &mut vm,
         &mut runner,
         &mut syscall_handler,
         entry_point,
         args,
         program_segment_size,
-    )?;
-
-    // Collect the set PC values that were visited during the entry point execution.
-    register_visited_pcs(
-        &mut vm,
-        syscall_handler.state,
-        class_hash,
-        program_segment_size,
-        bytecode_length,
-    )?;
+    )
+    .and_then(|()| {
+        // Collect the set PC values that were visited during the entry point execution.
+        register_visited_pcs(
+            &mut vm,
+            syscall_handler.state,
+            class_hash,
+            program_segment_size,
+            bytecode_length,
+        )
+    });
+    // Export and record before propagating a run failure, so that crashes can be analyzed as well.
+    if let Some(recorder) = &mut syscall_handler.context.step_deltas {
+        let call = &syscall_handler.call;
+        recorder.finish_run(call.storage_address, call.entry_point_selector, &mut vm, &mut runner);
+    }
+    if let Some(artifacts_export) = artifacts_export {
+        artifacts_export.export(&mut vm, &mut runner);
+    }
+    run_result?;
 
     let call_info = finalize_execution(
         vm,
@@ -153,7 +165,8 @@ pub fn initialize_execution_context<'a>(
     resources: &'a mut ExecutionResources,
     context: &'a mut EntryPointExecutionContext,
 ) -> Result<VmExecutionContext<'a>, PreExecutionError> {
-    let entry_point = contract_class.get_entry_point(&call)?;
+    let entry_point = contract_class
+        .get_entry_point(&call, context.versioned_constants().missing_selector_policy)?;
 
     // Instantiate Cairo runner.
     let proof_mode = false;
@@ -267,10 +280,7 @@ pub fn prepare_call_arguments(
 
     // Prepare calldata arguments.
     let calldata = &call.calldata.0;
-    let calldata: Vec<MaybeRelocatable> =
-        calldata.iter().map(|&arg| MaybeRelocatable::from(stark_felt_to_felt(arg))).collect();
-
-    let calldata_start_ptr = read_only_segments.allocate(vm, &calldata)?;
+    let calldata_start_ptr = read_only_segments.allocate_felts(vm, calldata)?;
     let calldata_end_ptr = MaybeRelocatable::from((calldata_start_ptr + calldata.len())?);
     args.push(CairoArg::Single(MaybeRelocatable::from(calldata_start_ptr)));
     args.push(CairoArg::Single(calldata_end_ptr));
@@ -296,6 +306,11 @@ pub fn run_entry_point(
         vm,
         hint_processor,
     );
+    if result.is_err() {
+        if let Some(deadline) = hint_processor.context.timed_out() {
+            return Err(EntryPointExecutionError::ExecutionTimeout(deadline));
+        }
+    }
 
     Ok(result?)
 }
@@ -325,6 +340,8 @@ pub fn finalize_execution(
 
     let call_result = get_call_result(&vm, &syscall_handler)?;
 
+    syscall_handler.context.track_memory_cells(&vm)?;
+
     // Take into account the VM execution resources of the current call, without inner calls.
     // Has to happen after marking holes in segments as accessed.
     let vm_resources_without_inner_calls = runner
@@ -351,6 +368,7 @@ pub fn finalize_execution(
         inner_calls: syscall_handler.inner_calls,
         storage_read_values: syscall_handler.read_values,
         accessed_storage_keys: syscall_handler.accessed_keys,
+        syscall_counter: syscall_handler.syscall_counter,
     })
 }
 
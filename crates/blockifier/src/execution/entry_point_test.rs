@@ -1,6 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 
+use assert_matches::assert_matches;
 use cairo_vm::serde::deserialize_program::BuiltinName;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use num_bigint::BigInt;
 use pretty_assertions::assert_eq;
 use rstest::rstest;
@@ -12,11 +16,16 @@ use starknet_api::transaction::Calldata;
 use starknet_api::{calldata, patricia_key, stark_felt};
 
 use crate::abi::abi_utils::{get_storage_var_address, selector_from_name};
-use crate::context::ChainInfo;
+use crate::context::{BlockContext, ChainInfo, TransactionContext};
 use crate::execution::call_info::{CallExecution, CallInfo, Retdata};
 use crate::execution::contract_class::ContractClass;
-use crate::execution::entry_point::CallEntryPoint;
-use crate::execution::errors::{gen_error_stack_trace_for_testing, EntryPointExecutionError};
+use crate::execution::entry_point::{
+    CallEntryPoint, EntryPointExecutionContext, ExecutionDeadline, MemoryLimits,
+};
+use crate::execution::errors::{
+    gen_error_stack_trace_for_testing, EntryPointExecutionError, PostExecutionError,
+};
+use crate::execution::syscalls::SyscallSelector;
 use crate::retdata;
 use crate::state::cached_state::CachedState;
 use crate::test_utils::contracts::FeatureContract;
@@ -26,6 +35,7 @@ use crate::test_utils::{
     create_calldata, trivial_external_entry_point_new, trivial_external_entry_point_with_address,
     CairoVersion, BALANCE,
 };
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
 use crate::versioned_constants::VersionedConstants;
 
 const INNER_CALL_CONTRACT_IN_CALL_CHAIN_OFFSET: usize = 65;
@@ -68,6 +78,143 @@ fn test_call_info_iteration() {
     }
 }
 
+#[rstest]
+fn test_frame_resources(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let chain_info = &ChainInfo::create_for_testing();
+    let mut state = test_state(chain_info, BALANCE, &[(test_contract, 1)]);
+    let inner_calldata = [stark_felt!(1234_u16), stark_felt!(18_u8)];
+    let outer_call = CallEntryPoint {
+        calldata: create_calldata(
+            test_contract.get_instance_address(0),
+            "test_storage_read_write",
+            &inner_calldata,
+        ),
+        entry_point_selector: selector_from_name("test_call_contract"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let call_info = outer_call.execute_directly(&mut state).unwrap();
+
+    // A leaf frame is attributed all of its call's resources.
+    let [inner_call_info] = &call_info.inner_calls[..] else {
+        panic!("Expected exactly one inner call.");
+    };
+    assert_eq!(inner_call_info.frame_resources(), inner_call_info.resources);
+    // The frames of a call and its inner calls add up to the call's resources.
+    let outer_frame_resources = call_info.frame_resources();
+    assert!(outer_frame_resources.n_steps > 0);
+    assert_eq!(&outer_frame_resources + &inner_call_info.resources, call_info.resources);
+    assert_eq!(call_info.syscall_counter, HashMap::from([(SyscallSelector::CallContract, 1)]));
+}
+
+#[rstest]
+fn test_memory_limits(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let contract_address = test_contract.get_instance_address(0);
+    let execute_with_limits = |memory_limits: MemoryLimits| {
+        let mut state =
+            test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 1)]);
+        let block_context = BlockContext::create_for_testing();
+        block_context.extensions().insert(memory_limits);
+        let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+        let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+        let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+        // Calls `test_storage_read_write` through `test_call_contract`.
+        let call = CallEntryPoint {
+            calldata: create_calldata(
+                contract_address,
+                "test_storage_read_write",
+                &[stark_felt!(1234_u16), stark_felt!(18_u8)],
+            ),
+            entry_point_selector: selector_from_name("test_call_contract"),
+            ..trivial_external_entry_point_new(test_contract)
+        };
+        let result = call.execute(&mut state, &mut ExecutionResources::default(), &mut context);
+        (result, context.n_memory_cells)
+    };
+
+    // Count the memory cells used by the two call frames.
+    let (result, n_memory_cells) = execute_with_limits(MemoryLimits {
+        max_memory_cells: Some(usize::MAX),
+        ..Default::default()
+    });
+    result.unwrap();
+    assert!(n_memory_cells > 0);
+    let (result, _) = execute_with_limits(MemoryLimits {
+        max_memory_cells: Some(n_memory_cells),
+        max_call_frames: Some(2),
+        ..Default::default()
+    });
+    result.unwrap();
+
+    let (result, _) = execute_with_limits(MemoryLimits {
+        max_memory_cells: Some(n_memory_cells - 1),
+        ..Default::default()
+    });
+    assert_matches!(
+        result.unwrap_err(),
+        EntryPointExecutionError::PostExecutionError(PostExecutionError::MemoryLimitExceeded {
+            n_memory_cells: n,
+            ..
+        }) if n == n_memory_cells
+    );
+    // Errors in inner calls are wrapped by the errors of their callers.
+    let (result, _) =
+        execute_with_limits(MemoryLimits { max_call_frames: Some(1), ..Default::default() });
+    assert!(result.is_err());
+    let (result, _) =
+        execute_with_limits(MemoryLimits { max_call_frames: Some(0), ..Default::default() });
+    assert_matches!(
+        result.unwrap_err(),
+        EntryPointExecutionError::CallFrameLimitExceeded { max_call_frames: 0 }
+    );
+}
+
+#[rstest]
+fn test_execution_deadline(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let execute_with_deadline = |deadline: Option<ExecutionDeadline>| {
+        let mut state =
+            test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 1)]);
+        let block_context = BlockContext::create_for_testing();
+        if let Some(deadline) = deadline {
+            block_context.extensions().insert(deadline);
+        }
+        let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+        let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+        let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+        let call = CallEntryPoint {
+            calldata: calldata![stark_felt!(1234_u16), stark_felt!(18_u8)],
+            entry_point_selector: selector_from_name("test_storage_read_write"),
+            ..trivial_external_entry_point_new(test_contract)
+        };
+        call.execute(&mut state, &mut ExecutionResources::default(), &mut context)
+    };
+
+    execute_with_deadline(None).unwrap();
+    let far_deadline = ExecutionDeadline {
+        max_duration: Some(Duration::from_secs(3600)),
+        max_steps: Some(1_000_000),
+    };
+    execute_with_deadline(Some(far_deadline)).unwrap();
+
+    for deadline in [
+        ExecutionDeadline { max_steps: Some(5), ..Default::default() },
+        ExecutionDeadline { max_duration: Some(Duration::ZERO), ..Default::default() },
+    ] {
+        assert_matches!(
+            execute_with_deadline(Some(deadline)).unwrap_err(),
+            EntryPointExecutionError::ExecutionTimeout(error_deadline) if error_deadline == deadline
+        );
+    }
+}
+
 #[test]
 fn test_entry_point_without_arg() {
     let test_contract = FeatureContract::TestContract(CairoVersion::Cairo0);
@@ -7,13 +7,15 @@ use cairo_vm::vm::errors::trace_errors::TraceError;
 use cairo_vm::vm::errors::vm_errors::{VirtualMachineError, HINT_ERROR_STR};
 use cairo_vm::vm::errors::vm_exception::VmException;
 use num_bigint::{BigInt, TryFromBigIntError};
-use starknet_api::core::{ContractAddress, EntryPointSelector};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::TransactionVersion;
 use thiserror::Error;
 
 use super::deprecated_syscalls::hint_processor::DeprecatedSyscallExecutionError;
 use super::syscalls::hint_processor::SyscallExecutionError;
+use crate::execution::entry_point::ExecutionDeadline;
 use crate::execution::execution_utils::format_panic_data;
 use crate::state::errors::StateError;
 use crate::transaction::errors::TransactionExecutionError;
@@ -68,6 +70,27 @@ pub enum PostExecutionError {
     VirtualMachineError(#[from] VirtualMachineError),
     #[error("Malformed return data : {error_message}.")]
     MalformedReturnData { error_message: String },
+    #[error(
+        "Execution used {n_memory_cells} VM memory cells, exceeding the limit of \
+         {max_memory_cells}."
+    )]
+    MemoryLimitExceeded { n_memory_cells: usize, max_memory_cells: usize },
+}
+
+#[derive(Debug, Error)]
+pub enum ExecutionArtifactsError {
+    #[error("Invalid instruction at pc {pc}.")]
+    InvalidInstruction { pc: usize },
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    MemoryError(#[from] MemoryError),
+    #[error(transparent)]
+    TraceError(#[from] TraceError),
+    #[error(transparent)]
+    TryFromIntError(#[from] std::num::TryFromIntError),
+    #[error(transparent)]
+    VirtualMachineError(#[from] VirtualMachineError),
 }
 
 impl From<RunnerError> for PostExecutionError {
@@ -121,6 +144,10 @@ impl EntryPointExecutionError {
 pub enum EntryPointExecutionError {
     #[error(transparent)]
     CairoRunError(#[from] CairoRunError),
+    #[error("Execution exceeded the limit of {max_call_frames} call frames.")]
+    CallFrameLimitExceeded { max_call_frames: usize },
+    #[error("Execution exceeded its deadline: {0:?}.")]
+    ExecutionTimeout(ExecutionDeadline),
     #[error("Execution failed. Failure reason: {}.", format_panic_data(.error_data))]
     ExecutionFailed { error_data: Vec<StarkFelt> },
     #[error("Internal error: {0}")]
@@ -146,6 +173,22 @@ pub enum EntryPointExecutionError {
     },
 }
 
+impl EntryPointExecutionError {
+    /// Whether the error is local to the failed call, and may be returned to its caller; as opposed
+    /// to errors exhausting the limits of the whole transaction (e.g., running out of steps), or
+    /// internal errors.
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            Self::CallFrameLimitExceeded { .. }
+                | Self::ExecutionTimeout(_)
+                | Self::InternalError(_)
+                | Self::StateError(_)
+                | Self::VirtualMachineExecutionErrorWithTrace { .. }
+        )
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ContractClassError {
     #[error(
@@ -156,6 +199,47 @@ pub enum ContractClassError {
         contract_class_version: u8,
         sierra_program_length: usize,
     },
+    #[error(
+        "Bytecode segment lengths sum up to {segments_length}, but the bytecode length is \
+         {bytecode_length}."
+    )]
+    BytecodeSegmentationMismatch { segments_length: usize, bytecode_length: usize },
+    #[error("Class contains hints that are not allowed by the hint policy: {hints:?}.")]
+    DisallowedHints { hints: Vec<String> },
+    #[error(transparent)]
+    ProgramError(#[from] cairo_vm::types::errors::program_errors::ProgramError),
+}
+
+#[derive(Debug, Error)]
+pub enum ClassHashError {
+    #[error("Invalid contract class version: {0}.")]
+    InvalidContractClassVersion(String),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SierraCompilationError {
+    #[error(
+        "Class hash mismatch; declared: {declared_class_hash:?}, actual: {actual_class_hash:?}."
+    )]
+    ClassHashMismatch { declared_class_hash: ClassHash, actual_class_hash: ClassHash },
+    #[error(transparent)]
+    ClassHashError(#[from] ClassHashError),
+    #[error(
+        "Compiled class hash mismatch; declared: {declared_compiled_class_hash:?}, actual: \
+         {actual_compiled_class_hash:?}."
+    )]
+    CompiledClassHashMismatch {
+        declared_compiled_class_hash: CompiledClassHash,
+        actual_compiled_class_hash: CompiledClassHash,
+    },
+    #[error("Sierra to CASM compilation has failed: {0}")]
+    CompilationFailed(String),
+    #[error(transparent)]
+    ContractClassError(#[from] ContractClassError),
+    #[error("Declare transaction version {declare_version:?} does not declare a Sierra class.")]
+    UnsupportedDeclareVersion { declare_version: TransactionVersion },
 }
 
 // A set of functions used to extract error trace from a recursive error object.
@@ -167,6 +251,7 @@ pub fn gen_transaction_execution_error_trace(error: &TransactionExecutionError)
     match error {
         TransactionExecutionError::ExecutionError { error, storage_address }
         | TransactionExecutionError::ValidateTransactionError { error, storage_address }
+        | TransactionExecutionError::ValidatePaymasterError { error, storage_address }
         | TransactionExecutionError::ContractConstructorExecutionFailed {
             error,
             storage_address,
@@ -284,7 +369,7 @@ fn extract_syscall_execution_error_into_stack_trace(
     syscall_error: &SyscallExecutionError,
 ) {
     match syscall_error {
-        SyscallExecutionError::CallContractExecutionError { storage_address, error } => {
+        SyscallExecutionError::CallContractExecutionError { storage_address, error, .. } => {
             let call_contract_preamble =
                 format!("Error in the called contract ({}):", storage_address.0.key());
             error_stack.push(call_contract_preamble);
@@ -313,7 +398,11 @@ fn extract_deprecated_syscall_execution_error_into_stack_trace(
     syscall_error: &DeprecatedSyscallExecutionError,
 ) {
     match syscall_error {
-        DeprecatedSyscallExecutionError::CallContractExecutionError { storage_address, error } => {
+        DeprecatedSyscallExecutionError::CallContractExecutionError {
+            storage_address,
+            error,
+            ..
+        } => {
             let call_contract_preamble =
                 format!("Error in the called contract ({}):", storage_address.0.key());
             error_stack.push(call_contract_preamble);
@@ -0,0 +1,203 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cairo_felt::Felt252;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use cairo_vm::vm::vm_core::VirtualMachine;
+use starknet_api::core::{ContractAddress, EntryPointSelector};
+use starknet_api::transaction::TransactionHash;
+
+use crate::execution::entry_point::{CallEntryPoint, EntryPointExecutionContext};
+use crate::execution::errors::ExecutionArtifactsError;
+use crate::execution::execution_utils::felt_to_stark_felt;
+
+#[cfg(test)]
+#[path = "execution_artifacts_test.rs"]
+mod test;
+
+pub const TRACE_FILE_EXTENSION: &str = "trace";
+pub const MEMORY_FILE_EXTENSION: &str = "memory";
+
+/// Opt-in export of the relocated VM trace and memory of selected calls, in the binary formats
+/// read by the Cairo tooling (the `--trace_file` and `--memory_file` outputs of `cairo-run`).
+/// Enabled by inserting it into the block context's extensions; all filters are optional.
+#[derive(Debug)]
+pub struct ExecutionArtifactsConfig {
+    pub output_dir: PathBuf,
+    pub transaction_hash: Option<TransactionHash>,
+    pub storage_address: Option<ContractAddress>,
+    pub entry_point_selector: Option<EntryPointSelector>,
+    // Prefixes the artifact file names, keeping them unique and ordered by execution.
+    n_exported_calls: AtomicUsize,
+}
+
+impl ExecutionArtifactsConfig {
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            transaction_hash: None,
+            storage_address: None,
+            entry_point_selector: None,
+            n_exported_calls: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_transaction_hash(mut self, transaction_hash: TransactionHash) -> Self {
+        self.transaction_hash = Some(transaction_hash);
+        self
+    }
+
+    pub fn with_storage_address(mut self, storage_address: ContractAddress) -> Self {
+        self.storage_address = Some(storage_address);
+        self
+    }
+
+    pub fn with_entry_point_selector(mut self, entry_point_selector: EntryPointSelector) -> Self {
+        self.entry_point_selector = Some(entry_point_selector);
+        self
+    }
+
+    pub fn matches(&self, transaction_hash: TransactionHash, call: &CallEntryPoint) -> bool {
+        self.transaction_hash.map_or(true, |hash| hash == transaction_hash)
+            && self.storage_address.map_or(true, |address| address == call.storage_address)
+            && self
+                .entry_point_selector
+                .map_or(true, |selector| selector == call.entry_point_selector)
+    }
+}
+
+/// A call selected for artifacts export.
+#[derive(Debug)]
+pub struct ArtifactsExport {
+    config: Arc<ExecutionArtifactsConfig>,
+    file_stem: String,
+}
+
+impl ArtifactsExport {
+    /// Returns the export of the given call, if the context is configured to export it.
+    pub fn for_call(context: &EntryPointExecutionContext, call: &CallEntryPoint) -> Option<Self> {
+        let config = Self::selecting_config(context, call)?;
+        let transaction_hash = context.tx_context.tx_info.transaction_hash();
+        let index = config.n_exported_calls.fetch_add(1, Ordering::Relaxed);
+        let file_stem = format!(
+            "{index}_{}_{}_{}",
+            transaction_hash.0,
+            call.storage_address.0.key(),
+            call.entry_point_selector.0
+        );
+        Some(Self { config, file_stem })
+    }
+
+    /// Returns whether the context is configured to export the given call.
+    pub fn is_selected(context: &EntryPointExecutionContext, call: &CallEntryPoint) -> bool {
+        Self::selecting_config(context, call).is_some()
+    }
+
+    fn selecting_config(
+        context: &EntryPointExecutionContext,
+        call: &CallEntryPoint,
+    ) -> Option<Arc<ExecutionArtifactsConfig>> {
+        let config = context.tx_context.extensions.get::<ExecutionArtifactsConfig>()?;
+        config.matches(context.tx_context.tx_info.transaction_hash(), call).then_some(config)
+    }
+
+    pub fn trace_path(&self) -> PathBuf {
+        self.artifact_path(TRACE_FILE_EXTENSION)
+    }
+
+    pub fn memory_path(&self) -> PathBuf {
+        self.artifact_path(MEMORY_FILE_EXTENSION)
+    }
+
+    /// Relocates and writes the trace and memory of a run, which may have failed.
+    /// Export failures are logged, and never affect the execution result.
+    pub fn export(self, vm: &mut VirtualMachine, runner: &mut CairoRunner) {
+        if let Err(error) = self.try_export(vm, runner) {
+            log::warn!("Failed to export execution artifacts of {}: {error}", self.file_stem);
+        }
+    }
+
+    fn try_export(
+        &self,
+        vm: &mut VirtualMachine,
+        runner: &mut CairoRunner,
+    ) -> Result<(), ExecutionArtifactsError> {
+        relocate_run(vm, runner)?;
+        fs::create_dir_all(&self.config.output_dir)?;
+        let trace = vm.get_relocated_trace()?.iter().map(|entry| (entry.ap, entry.fp, entry.pc));
+        write_artifact(&self.trace_path(), |dest| write_encoded_trace(trace, dest))?;
+        write_artifact(&self.memory_path(), |dest| {
+            write_encoded_memory(&runner.relocated_memory, dest)
+        })
+    }
+
+    fn artifact_path(&self, extension: &str) -> PathBuf {
+        self.config.output_dir.join(format!("{}.{extension}", self.file_stem))
+    }
+}
+
+/// Relocates the trace and memory of a run, which may have failed; parts that were already
+/// relocated are left as is.
+pub fn relocate_run(
+    vm: &mut VirtualMachine,
+    runner: &mut CairoRunner,
+) -> Result<(), ExecutionArtifactsError> {
+    vm.segments.compute_effective_sizes();
+    let relocation_table = vm.segments.relocate_segments()?;
+    // The trace of a successful Cairo 1 run is already relocated, when collecting visited PCs.
+    if vm.get_relocated_trace().is_err() {
+        vm.relocate_trace(&relocation_table)?;
+    }
+    if runner.relocated_memory.is_empty() {
+        runner.relocate_memory(vm, &relocation_table)?;
+    }
+
+    Ok(())
+}
+
+fn write_artifact(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), ExecutionArtifactsError>,
+) -> Result<(), ExecutionArtifactsError> {
+    let mut dest = BufWriter::new(File::create(path)?);
+    write(&mut dest)?;
+    Ok(dest.flush()?)
+}
+
+/// Writes the relocated trace, given as `(ap, fp, pc)` entries; each register is encoded as a
+/// little-endian 64-bit word.
+pub fn write_encoded_trace(
+    relocated_trace: impl IntoIterator<Item = (usize, usize, usize)>,
+    dest: &mut impl Write,
+) -> Result<(), ExecutionArtifactsError> {
+    for (ap, fp, pc) in relocated_trace {
+        for register in [ap, fp, pc] {
+            dest.write_all(&u64::try_from(register)?.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the relocated memory, skipping holes; each cell is encoded as its address as a
+/// little-endian 64-bit word, followed by its value as a little-endian 256-bit word.
+pub fn write_encoded_memory(
+    relocated_memory: &[Option<Felt252>],
+    dest: &mut impl Write,
+) -> Result<(), ExecutionArtifactsError> {
+    for (address, value) in relocated_memory.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+
+        let mut value_bytes = *felt_to_stark_felt(value).bytes();
+        value_bytes.reverse();
+        dest.write_all(&u64::try_from(address)?.to_le_bytes())?;
+        dest.write_all(&value_bytes)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::sync::Arc;
+
+use cairo_felt::Felt252;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::{BlockContext, ChainInfo, TransactionContext};
+use crate::execution::entry_point::{CallEntryPoint, EntryPointExecutionContext};
+use crate::execution::execution_artifacts::{
+    write_encoded_memory, write_encoded_trace, ExecutionArtifactsConfig,
+};
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{trivial_external_entry_point_new, CairoVersion};
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
+
+const TRACE_ENTRY_SIZE: usize = 3 * 8;
+const MEMORY_CELL_SIZE: usize = 8 + 32;
+
+#[test]
+fn test_write_encoded_trace() {
+    let mut encoded = vec![];
+    write_encoded_trace([(7, 5, 1), (8, 5, 3)], &mut encoded).unwrap();
+
+    let expected: Vec<u8> =
+        [7_u64, 5, 1, 8, 5, 3].iter().flat_map(|register| register.to_le_bytes()).collect();
+    assert_eq!(encoded, expected);
+}
+
+#[test]
+fn test_write_encoded_memory() {
+    let mut encoded = vec![];
+    write_encoded_memory(&[None, Some(Felt252::from(0x0102)), None], &mut encoded).unwrap();
+
+    let mut expected = 1_u64.to_le_bytes().to_vec();
+    expected.extend([0x02, 0x01]);
+    expected.extend([0; 30]);
+    assert_eq!(encoded, expected);
+}
+
+fn execute_with_artifacts_config(
+    call: CallEntryPoint,
+    test_contract: FeatureContract,
+    config: ExecutionArtifactsConfig,
+) {
+    let mut state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(config);
+    let tx_context = TransactionContext::new(
+        block_context,
+        TransactionInfo::Deprecated(DeprecatedTransactionInfo::default()),
+    );
+    let mut context = EntryPointExecutionContext::new_invoke(Arc::new(tx_context), true).unwrap();
+    call.execute(&mut state, &mut ExecutionResources::default(), &mut context).unwrap();
+}
+
+#[rstest]
+fn test_export_execution_artifacts(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let output_dir = std::env::temp_dir()
+        .join(format!("blockifier_artifacts_{cairo_version:?}_{}", std::process::id()));
+    let call = CallEntryPoint {
+        calldata: calldata![stark_felt!(1234_u16), stark_felt!(18_u8)],
+        entry_point_selector: selector_from_name("test_storage_read_write"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+
+    // Calls that do not match the filters are not exported.
+    let config = ExecutionArtifactsConfig::new(&output_dir)
+        .with_entry_point_selector(selector_from_name("test_long_retdata"));
+    execute_with_artifacts_config(call.clone(), test_contract, config);
+    assert!(!output_dir.exists());
+
+    let config = ExecutionArtifactsConfig::new(&output_dir)
+        .with_entry_point_selector(call.entry_point_selector);
+    execute_with_artifacts_config(call, test_contract, config);
+    let mut artifacts: Vec<_> =
+        fs::read_dir(&output_dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    artifacts.sort();
+    assert_eq!(
+        artifacts
+            .iter()
+            .map(|path| path.extension().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>(),
+        vec!["memory", "trace"]
+    );
+
+    let memory = fs::read(&artifacts[0]).unwrap();
+    assert!(!memory.is_empty() && memory.len() % MEMORY_CELL_SIZE == 0);
+    let trace = fs::read(&artifacts[1]).unwrap();
+    assert!(!trace.is_empty() && trace.len() % TRACE_ENTRY_SIZE == 0);
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
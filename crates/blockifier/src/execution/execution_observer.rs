@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use starknet_api::core::{ContractAddress, EntryPointSelector};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{EventContent, EventKey};
+
+use crate::execution::call_info::OrderedEvent;
+use crate::execution::syscalls::SyscallSelector;
+
+#[cfg(all(test, feature = "tracing"))]
+#[path = "execution_observer_test.rs"]
+pub mod test;
+
+/// The execution budget left at some point of the execution.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ExecutionBudget {
+    /// The VM steps left for the transaction (or the validation), shared by all calls.
+    pub remaining_steps: usize,
+    /// The Sierra gas left in the running Cairo 1 frame, as of its last syscall (or its start);
+    /// `None` if no Cairo 1 frame has run yet.
+    pub remaining_gas: Option<u64>,
+}
+
+/// The identity of a call frame, as seen by execution observers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CallFrame {
+    pub storage_address: ContractAddress,
+    pub entry_point_selector: EntryPointSelector,
+    /// The number of enclosing call frames; zero for the transaction's top-level call.
+    pub depth: usize,
+}
+
+/// Selects the events emitted by the given contract whose keys start with the given prefix; an
+/// empty prefix selects all the events of the contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventFilter {
+    pub address: ContractAddress,
+    pub key_prefix: Vec<EventKey>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, from_address: ContractAddress, event: &EventContent) -> bool {
+        from_address == self.address && event.keys.starts_with(&self.key_prefix)
+    }
+}
+
+/// A hook notified during execution, allowing adaptive policies based on the remaining budget
+/// (e.g., reducing the tracing detail when the budget is nearly exhausted), and tooling such as
+/// step-level debuggers.
+/// Observers are registered by inserting `ExecutionObservers` into the block context extensions.
+/// All events but `on_syscall` are optional.
+pub trait ExecutionObserver: Send + Sync {
+    /// Invoked before executing a syscall.
+    fn on_syscall(&self, selector: SyscallSelector, budget: ExecutionBudget);
+
+    /// Invoked after executing a syscall, whether it succeeded or not.
+    fn on_syscall_exit(&self, _selector: SyscallSelector, _budget: ExecutionBudget) {}
+
+    /// Invoked on a storage read syscall, with the value read.
+    fn on_storage_read(&self, _address: ContractAddress, _key: StorageKey, _value: StarkFelt) {}
+
+    /// Invoked on a storage write syscall, with the value written.
+    fn on_storage_write(&self, _address: ContractAddress, _key: StorageKey, _value: StarkFelt) {}
+
+    /// Invoked before running an entry point.
+    fn on_call_start(&self, _frame: CallFrame) {}
+
+    /// Invoked after running an entry point, with the number of VM steps it consumed (including
+    /// its inner calls).
+    fn on_call_end(&self, _frame: CallFrame, _n_steps: usize, _succeeded: bool) {}
+
+    /// The filters selecting the events the observer is notified of; none by default.
+    fn event_filters(&self) -> &[EventFilter] {
+        &[]
+    }
+
+    /// Invoked on the emission of an event matching one of the observer's event filters, as soon
+    /// as it is emitted. Events are recorded in the execution trace regardless of the filters.
+    /// Note: the event may still be discarded later on, if the transaction is reverted.
+    fn on_event(&self, _from_address: ContractAddress, _event: &OrderedEvent) {}
+}
+
+#[derive(Clone, Default)]
+pub struct ExecutionObservers(pub Vec<Arc<dyn ExecutionObserver>>);
+
+impl ExecutionObservers {
+    pub fn notify_syscall(&self, selector: SyscallSelector, budget: ExecutionBudget) {
+        for observer in &self.0 {
+            observer.on_syscall(selector, budget);
+        }
+    }
+
+    pub fn notify_syscall_exit(&self, selector: SyscallSelector, budget: ExecutionBudget) {
+        for observer in &self.0 {
+            observer.on_syscall_exit(selector, budget);
+        }
+    }
+
+    pub fn notify_storage_read(&self, address: ContractAddress, key: StorageKey, value: StarkFelt) {
+        for observer in &self.0 {
+            observer.on_storage_read(address, key, value);
+        }
+    }
+
+    pub fn notify_storage_write(
+        &self,
+        address: ContractAddress,
+        key: StorageKey,
+        value: StarkFelt,
+    ) {
+        for observer in &self.0 {
+            observer.on_storage_write(address, key, value);
+        }
+    }
+
+    pub fn notify_call_start(&self, frame: CallFrame) {
+        for observer in &self.0 {
+            observer.on_call_start(frame);
+        }
+    }
+
+    pub fn notify_call_end(&self, frame: CallFrame, n_steps: usize, succeeded: bool) {
+        for observer in &self.0 {
+            observer.on_call_end(frame, n_steps, succeeded);
+        }
+    }
+
+    pub fn notify_event(&self, from_address: ContractAddress, event: &OrderedEvent) {
+        for observer in &self.0 {
+            let mut filters = observer.event_filters().iter();
+            if filters.any(|filter| filter.matches(from_address, &event.event)) {
+                observer.on_event(from_address, event);
+            }
+        }
+    }
+}
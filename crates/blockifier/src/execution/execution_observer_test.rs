@@ -0,0 +1,264 @@
+use std::sync::{Arc, Mutex};
+
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{Calldata, EventContent, EventData, EventKey};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::{BlockContext, ChainInfo, TransactionContext};
+use crate::execution::call_info::OrderedEvent;
+use crate::execution::entry_point::{CallEntryPoint, EntryPointExecutionContext};
+use crate::execution::execution_observer::{
+    CallFrame, EventFilter, ExecutionBudget, ExecutionObserver, ExecutionObservers,
+};
+use crate::execution::syscalls::SyscallSelector;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_calldata, trivial_external_entry_point_new, CairoVersion};
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
+
+#[derive(Default)]
+struct RecordingObserver {
+    syscalls: Mutex<Vec<(SyscallSelector, ExecutionBudget)>>,
+}
+
+impl ExecutionObserver for RecordingObserver {
+    fn on_syscall(&self, selector: SyscallSelector, budget: ExecutionBudget) {
+        self.syscalls.lock().unwrap().push((selector, budget));
+    }
+}
+
+#[rstest]
+fn test_observe_execution_budget(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let mut state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    let observer = Arc::new(RecordingObserver::default());
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(ExecutionObservers(vec![observer.clone()]));
+    let tx_context = TransactionContext::new(
+        block_context,
+        TransactionInfo::Deprecated(DeprecatedTransactionInfo::default()),
+    );
+    let mut context = EntryPointExecutionContext::new_invoke(Arc::new(tx_context), true).unwrap();
+
+    let call = CallEntryPoint {
+        calldata: calldata![stark_felt!(1234_u16), stark_felt!(18_u8)],
+        entry_point_selector: selector_from_name("test_storage_read_write"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let initial_gas = call.initial_gas;
+    call.execute(&mut state, &mut ExecutionResources::default(), &mut context).unwrap();
+
+    let syscalls = observer.syscalls.lock().unwrap();
+    let selectors: Vec<SyscallSelector> = syscalls.iter().map(|(selector, _)| *selector).collect();
+    assert_eq!(selectors, vec![SyscallSelector::StorageWrite, SyscallSelector::StorageRead]);
+
+    let [(_, write_budget), (_, read_budget)] = syscalls[..] else {
+        panic!("Expected exactly two syscalls.");
+    };
+    assert!(read_budget.remaining_steps < write_budget.remaining_steps);
+    match cairo_version {
+        CairoVersion::Cairo0 => {
+            assert_eq!((write_budget.remaining_gas, read_budget.remaining_gas), (None, None));
+        }
+        CairoVersion::Cairo1 => {
+            let write_gas = write_budget.remaining_gas.unwrap();
+            let read_gas = read_budget.remaining_gas.unwrap();
+            assert!(read_gas < write_gas && write_gas < initial_gas);
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum TraceEvent {
+    SyscallEnter(SyscallSelector),
+    SyscallExit(SyscallSelector),
+    StorageRead(ContractAddress, StorageKey, StarkFelt),
+    StorageWrite(ContractAddress, StorageKey, StarkFelt),
+    CallStart(CallFrame),
+    CallEnd(CallFrame, bool),
+}
+
+#[derive(Default)]
+struct TracingObserver {
+    events: Mutex<Vec<TraceEvent>>,
+    call_steps: Mutex<Vec<usize>>,
+}
+
+impl ExecutionObserver for TracingObserver {
+    fn on_syscall(&self, selector: SyscallSelector, _budget: ExecutionBudget) {
+        self.events.lock().unwrap().push(TraceEvent::SyscallEnter(selector));
+    }
+
+    fn on_syscall_exit(&self, selector: SyscallSelector, _budget: ExecutionBudget) {
+        self.events.lock().unwrap().push(TraceEvent::SyscallExit(selector));
+    }
+
+    fn on_storage_read(&self, address: ContractAddress, key: StorageKey, value: StarkFelt) {
+        self.events.lock().unwrap().push(TraceEvent::StorageRead(address, key, value));
+    }
+
+    fn on_storage_write(&self, address: ContractAddress, key: StorageKey, value: StarkFelt) {
+        self.events.lock().unwrap().push(TraceEvent::StorageWrite(address, key, value));
+    }
+
+    fn on_call_start(&self, frame: CallFrame) {
+        self.events.lock().unwrap().push(TraceEvent::CallStart(frame));
+    }
+
+    fn on_call_end(&self, frame: CallFrame, n_steps: usize, succeeded: bool) {
+        self.events.lock().unwrap().push(TraceEvent::CallEnd(frame, succeeded));
+        self.call_steps.lock().unwrap().push(n_steps);
+    }
+}
+
+#[rstest]
+fn test_trace_execution_events(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let contract_address = test_contract.get_instance_address(0);
+    let mut state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    let observer = Arc::new(TracingObserver::default());
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(ExecutionObservers(vec![observer.clone()]));
+    let tx_context = TransactionContext::new(
+        block_context,
+        TransactionInfo::Deprecated(DeprecatedTransactionInfo::default()),
+    );
+    let mut context = EntryPointExecutionContext::new_invoke(Arc::new(tx_context), true).unwrap();
+
+    let (key, value) = (stark_felt!(1234_u16), stark_felt!(18_u8));
+    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let call = CallEntryPoint {
+        calldata: create_calldata(contract_address, "test_storage_read_write", &[key, value]),
+        entry_point_selector: selector_from_name("test_call_contract"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let outer_frame = CallFrame {
+        storage_address: contract_address,
+        entry_point_selector: call.entry_point_selector,
+        depth: 0,
+    };
+    call.execute(&mut state, &mut ExecutionResources::default(), &mut context).unwrap();
+
+    let inner_frame = CallFrame {
+        storage_address: contract_address,
+        entry_point_selector: inner_entry_point_selector,
+        depth: 1,
+    };
+    let key = StorageKey::try_from(key).unwrap();
+    assert_eq!(
+        *observer.events.lock().unwrap(),
+        vec![
+            TraceEvent::CallStart(outer_frame),
+            TraceEvent::SyscallEnter(SyscallSelector::CallContract),
+            TraceEvent::CallStart(inner_frame),
+            TraceEvent::SyscallEnter(SyscallSelector::StorageWrite),
+            TraceEvent::StorageWrite(contract_address, key, value),
+            TraceEvent::SyscallExit(SyscallSelector::StorageWrite),
+            TraceEvent::SyscallEnter(SyscallSelector::StorageRead),
+            TraceEvent::StorageRead(contract_address, key, value),
+            TraceEvent::SyscallExit(SyscallSelector::StorageRead),
+            TraceEvent::CallEnd(inner_frame, true),
+            TraceEvent::SyscallExit(SyscallSelector::CallContract),
+            TraceEvent::CallEnd(outer_frame, true),
+        ]
+    );
+
+    // The steps of a call frame include those of its inner calls.
+    let call_steps = observer.call_steps.lock().unwrap();
+    let [inner_n_steps, outer_n_steps] = call_steps[..] else {
+        panic!("Expected exactly two calls.");
+    };
+    assert!(0 < inner_n_steps && inner_n_steps < outer_n_steps);
+}
+
+struct SubscribingObserver {
+    filters: Vec<EventFilter>,
+    events: Mutex<Vec<(ContractAddress, EventContent)>>,
+}
+
+impl SubscribingObserver {
+    fn new(address: ContractAddress, key_prefix: &[StarkFelt]) -> Self {
+        let key_prefix = key_prefix.iter().map(|key| EventKey(*key)).collect();
+        Self { filters: vec![EventFilter { address, key_prefix }], events: Mutex::default() }
+    }
+}
+
+impl ExecutionObserver for SubscribingObserver {
+    fn on_syscall(&self, _selector: SyscallSelector, _budget: ExecutionBudget) {}
+
+    fn event_filters(&self) -> &[EventFilter] {
+        &self.filters
+    }
+
+    fn on_event(&self, from_address: ContractAddress, event: &OrderedEvent) {
+        self.events.lock().unwrap().push((from_address, event.event.clone()));
+    }
+}
+
+#[rstest]
+fn test_event_subscriptions(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let contract_address = test_contract.get_instance_address(0);
+    let mut state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    let (first_key, second_key) = (stark_felt!(2019_u16), stark_felt!(2020_u16));
+    let matching_observer = Arc::new(SubscribingObserver::new(contract_address, &[first_key]));
+    let all_events_observer = Arc::new(SubscribingObserver::new(contract_address, &[]));
+    let key_mismatch_observer = Arc::new(SubscribingObserver::new(contract_address, &[second_key]));
+    let address_mismatch_observer =
+        Arc::new(SubscribingObserver::new(contract_address!("0x1234"), &[]));
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(ExecutionObservers(vec![
+        matching_observer.clone(),
+        all_events_observer.clone(),
+        key_mismatch_observer.clone(),
+        address_mismatch_observer.clone(),
+    ]));
+    let tx_context = TransactionContext::new(
+        block_context,
+        TransactionInfo::Deprecated(DeprecatedTransactionInfo::default()),
+    );
+    let mut context = EntryPointExecutionContext::new_invoke(Arc::new(tx_context), true).unwrap();
+
+    // Emits two events, keyed `[first_key, second_key]`.
+    let data = stark_felt!(2021_u16);
+    let call = CallEntryPoint {
+        calldata: calldata![
+            stark_felt!(2_u8),
+            stark_felt!(2_u8),
+            first_key,
+            second_key,
+            stark_felt!(1_u8),
+            data
+        ],
+        entry_point_selector: selector_from_name("test_emit_events"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let call_info =
+        call.execute(&mut state, &mut ExecutionResources::default(), &mut context).unwrap();
+
+    let event = EventContent {
+        keys: vec![EventKey(first_key), EventKey(second_key)],
+        data: EventData(vec![data]),
+    };
+    let expected_events = vec![(contract_address, event.clone()); 2];
+    assert_eq!(*matching_observer.events.lock().unwrap(), expected_events);
+    assert_eq!(*all_events_observer.events.lock().unwrap(), expected_events);
+    assert!(key_mismatch_observer.events.lock().unwrap().is_empty());
+    assert!(address_mismatch_observer.events.lock().unwrap().is_empty());
+    // All events are recorded in the trace.
+    let traced_events: Vec<_> =
+        call_info.execution.events.into_iter().map(|ordered_event| ordered_event.event).collect();
+    assert_eq!(traced_events, vec![event; 2]);
+}
@@ -14,7 +14,7 @@ use cairo_vm::vm::runners::builtin_runner::POSEIDON_BUILTIN_NAME;
 use cairo_vm::vm::runners::cairo_runner::{CairoArg, ExecutionResources};
 use cairo_vm::vm::vm_core::VirtualMachine;
 use num_bigint::BigUint;
-use starknet_api::core::ClassHash;
+use starknet_api::core::{ClassHash, ContractAddress};
 use starknet_api::deprecated_contract_class::Program as DeprecatedProgram;
 use starknet_api::hash::StarkFelt;
 use starknet_api::transaction::Calldata;
@@ -182,6 +182,18 @@ impl ReadOnlySegments {
         Ok(start_ptr)
     }
 
+    /// Allocates a read-only segment holding the given felts (e.g., the calldata shared by the
+    /// transaction and its calls), loading them into the VM memory in a single bulk write.
+    pub fn allocate_felts(
+        &mut self,
+        vm: &mut VirtualMachine,
+        data: &[StarkFelt],
+    ) -> Result<Relocatable, MemoryError> {
+        let data: Vec<MaybeRelocatable> =
+            data.iter().map(|&felt| MaybeRelocatable::from(stark_felt_to_felt(felt))).collect();
+        self.allocate(vm, &data)
+    }
+
     pub fn validate(&self, vm: &VirtualMachine) -> Result<(), PostExecutionError> {
         for segment in &self.0 {
             let used_size = vm
@@ -212,6 +224,43 @@ impl ReadOnlySegments {
     }
 }
 
+/// How the deploy syscall handles deploying to an address that already holds a contract.
+/// Set by inserting it into the block context extensions; rejecting is the default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DeployCollisionPolicy {
+    #[default]
+    Reject,
+    /// Deploying the class already at the address succeeds without running its constructor, for
+    /// idempotent deployments (e.g., on devnets). Other collisions are rejected.
+    AllowSameClass,
+}
+
+/// Checks the address computed by the deploy syscall against the collision policy.
+/// Returns whether the class is already deployed at the address, and the deployment should be
+/// skipped.
+pub fn check_deploy_collision(
+    state: &mut dyn State,
+    context: &EntryPointExecutionContext,
+    deployed_contract_address: ContractAddress,
+    class_hash: ClassHash,
+) -> EntryPointExecutionResult<bool> {
+    let current_class_hash = state.get_class_hash_at(deployed_contract_address)?;
+    if current_class_hash == ClassHash::default() {
+        return Ok(false);
+    }
+
+    let policy = context
+        .tx_context
+        .extensions
+        .get::<DeployCollisionPolicy>()
+        .map(|policy| *policy)
+        .unwrap_or_default();
+    match policy {
+        DeployCollisionPolicy::AllowSameClass if current_class_hash == class_hash => Ok(true),
+        _ => Err(StateError::UnavailableContractAddress(deployed_contract_address).into()),
+    }
+}
+
 /// Instantiates the given class and assigns it an address.
 /// Returns the call info of the deployed class' constructor execution.
 pub fn execute_deployment(
@@ -1,12 +1,14 @@
 use std::iter::zip;
 
 use cairo_felt::Felt252;
+use cairo_vm::vm::vm_core::VirtualMachine;
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 use pretty_assertions::assert_eq;
 use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
 
-use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt, ReadOnlySegments};
 
 fn starkfelt_to_felt_pairs() -> Vec<(StarkFelt, Felt252)> {
     // The STARK prime is 2 ^ 251 + 17 * 2 ^ 192 + 1.
@@ -45,3 +47,19 @@ fn test_felt_to_stark_felt() {
         assert_eq!(felt_to_stark_felt(&felt), equivalent_stark_felt);
     }
 }
+
+#[test]
+fn test_allocate_felts() {
+    let mut vm = VirtualMachine::new(false);
+    let mut read_only_segments = ReadOnlySegments::default();
+    let data = [stark_felt!(1_u8), stark_felt!(2_u8), stark_felt!(3_u8)];
+
+    let start_ptr = read_only_segments.allocate_felts(&mut vm, &data).unwrap();
+    let loaded: Vec<StarkFelt> = vm
+        .get_integer_range(start_ptr, data.len())
+        .unwrap()
+        .iter()
+        .map(|felt| felt_to_stark_felt(felt))
+        .collect();
+    assert_eq!(loaded, data);
+}
@@ -0,0 +1,339 @@
+use std::collections::HashSet;
+use std::iter;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use cairo_felt::Felt252;
+use cairo_vm::types::instruction::{Op1Addr, Register};
+use cairo_vm::vm::decoding::decoder::decode_instruction;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use cairo_vm::vm::vm_core::VirtualMachine;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+use starknet_api::core::{ContractAddress, EntryPointSelector};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::TransactionHash;
+
+use crate::context::TransactionContext;
+use crate::execution::call_info::{CallPath, TopLevelCall};
+use crate::execution::errors::ExecutionArtifactsError;
+use crate::execution::execution_artifacts::relocate_run;
+use crate::execution::execution_utils::felt_to_stark_felt;
+
+#[cfg(test)]
+#[path = "step_deltas_test.rs"]
+pub mod test;
+
+/// Opt-in recording of the execution of a single call, as windows of consecutive VM steps, each
+/// with the memory cells and the storage values it set; allows debuggers to step through the
+/// execution backwards as well as forwards.
+/// Enabled by inserting it into the block context's extensions; when absent, calls are not
+/// tracked at all.
+#[derive(Debug)]
+pub struct StepDeltasConfig {
+    pub transaction_hash: TransactionHash,
+    pub call_path: CallPath,
+    /// The number of VM steps per window.
+    pub window_size: usize,
+    recording: Mutex<Option<StepDeltas>>,
+}
+
+impl StepDeltasConfig {
+    /// Selects the given call of the given transaction, recording a window per step.
+    pub fn new(transaction_hash: TransactionHash, call_path: CallPath) -> Self {
+        Self { transaction_hash, call_path, window_size: 1, recording: Mutex::new(None) }
+    }
+
+    pub fn with_window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size.max(1);
+        self
+    }
+
+    /// Takes the recording of the selected call; `None` if the call did not run (yet).
+    pub fn take_recording(&self) -> Option<StepDeltas> {
+        self.lock().take()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<StepDeltas>> {
+        self.recording.lock().expect("Step deltas lock is poisoned.")
+    }
+}
+
+/// The registers of the VM before running a step, at their relocated addresses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct RegisterState {
+    pub pc: usize,
+    pub ap: usize,
+    pub fp: usize,
+}
+
+/// A memory cell, at its relocated address.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct MemoryCell {
+    pub address: usize,
+    pub value: StarkFelt,
+}
+
+/// A storage write, made by the given step of the recorded call (or by an inner call it made).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct StorageDelta {
+    pub step: usize,
+    pub address: ContractAddress,
+    pub key: StorageKey,
+    pub previous_value: StarkFelt,
+    pub value: StarkFelt,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct StepWindow {
+    pub first_step: usize,
+    /// The registers before each step of the window.
+    pub registers: Vec<RegisterState>,
+    /// The memory cells first accessed by the steps of the window. Memory is write-once, so a
+    /// cell's value is set when it is first accessed (or by a hint, right before).
+    pub memory_deltas: Vec<MemoryCell>,
+    pub storage_deltas: Vec<StorageDelta>,
+}
+
+/// The recorded execution of the selected call; its inner calls are not recorded step by step,
+/// but their storage writes are attributed to the step making them.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct StepDeltas {
+    pub storage_address: ContractAddress,
+    pub entry_point_selector: EntryPointSelector,
+    pub windows: Vec<StepWindow>,
+}
+
+/// Tracks the path of the running call in the trace of the transaction, and records the selected
+/// call. Only created for the selected transaction and top-level call.
+#[derive(Debug)]
+pub struct StepDeltasRecorder {
+    config: Arc<StepDeltasConfig>,
+    // The inner call indices of the running call, and the number of inner calls made by each of
+    // the running calls so far.
+    call_indices: Vec<usize>,
+    n_inner_calls: Vec<usize>,
+    selected_call: Option<SelectedCall>,
+}
+
+#[derive(Debug)]
+struct SelectedCall {
+    depth: usize,
+    initial_remaining_steps: usize,
+    // The steps run by the inner calls that ended, and, for the running inner call, the step
+    // making it and the remaining steps at its start.
+    n_inner_call_steps: usize,
+    running_inner_call: Option<(usize, usize)>,
+    storage_deltas: Vec<StorageDelta>,
+}
+
+impl SelectedCall {
+    fn current_step(&self, n_remaining_steps: usize) -> usize {
+        match self.running_inner_call {
+            Some((step, _)) => step,
+            None => (self.initial_remaining_steps - n_remaining_steps)
+                .saturating_sub(self.n_inner_call_steps),
+        }
+    }
+}
+
+impl StepDeltasRecorder {
+    /// Returns a recorder for the given top-level call of the transaction, if it is selected.
+    pub fn for_top_level_call(
+        tx_context: &TransactionContext,
+        top_level_call: TopLevelCall,
+    ) -> Option<Self> {
+        let config = tx_context.extensions.get::<StepDeltasConfig>()?;
+        let is_selected = config.transaction_hash == tx_context.tx_info.transaction_hash()
+            && config.call_path.top_level_call == top_level_call;
+        is_selected.then(|| Self {
+            config,
+            call_indices: Vec::new(),
+            n_inner_calls: Vec::new(),
+            selected_call: None,
+        })
+    }
+
+    /// Returns whether the selected call, or one of its inner calls, is running.
+    pub fn is_recording(&self) -> bool {
+        self.selected_call.is_some()
+    }
+
+    /// Returns whether the selected call itself is running.
+    pub fn is_recording_call(&self) -> bool {
+        self.selected_call.as_ref().is_some_and(|selected| selected.depth == self.depth())
+    }
+
+    pub fn enter_call(&mut self, n_remaining_steps: usize) {
+        if let Some(parent_n_inner_calls) = self.n_inner_calls.last_mut() {
+            self.call_indices.push(*parent_n_inner_calls);
+            *parent_n_inner_calls += 1;
+        }
+        self.n_inner_calls.push(0);
+
+        let depth = self.depth();
+        if let Some(selected) = &mut self.selected_call {
+            if depth == selected.depth + 1 {
+                let step = selected.current_step(n_remaining_steps);
+                selected.running_inner_call = Some((step, n_remaining_steps));
+            }
+        } else if self.call_indices == self.config.call_path.inner_call_indices {
+            self.selected_call = Some(SelectedCall {
+                depth,
+                initial_remaining_steps: n_remaining_steps,
+                n_inner_call_steps: 0,
+                running_inner_call: None,
+                storage_deltas: Vec::new(),
+            });
+        }
+    }
+
+    pub fn exit_call(&mut self, n_remaining_steps: usize) {
+        let depth = self.depth();
+        if let Some(selected) = &mut self.selected_call {
+            if depth == selected.depth {
+                self.selected_call = None;
+            } else if depth == selected.depth + 1 {
+                if let Some((_, initial_remaining_steps)) = selected.running_inner_call.take() {
+                    selected.n_inner_call_steps += initial_remaining_steps - n_remaining_steps;
+                }
+            }
+        }
+
+        self.n_inner_calls.pop();
+        self.call_indices.pop();
+    }
+
+    pub fn record_storage_write(
+        &mut self,
+        n_remaining_steps: usize,
+        address: ContractAddress,
+        key: StorageKey,
+        previous_value: StarkFelt,
+        value: StarkFelt,
+    ) {
+        if let Some(selected) = &mut self.selected_call {
+            let step = selected.current_step(n_remaining_steps);
+            selected.storage_deltas.push(StorageDelta {
+                step,
+                address,
+                key,
+                previous_value,
+                value,
+            });
+        }
+    }
+
+    /// Records the run of the selected call, which may have failed; other runs are ignored.
+    /// Recording failures are logged, and never affect the execution result.
+    pub fn finish_run(
+        &mut self,
+        storage_address: ContractAddress,
+        entry_point_selector: EntryPointSelector,
+        vm: &mut VirtualMachine,
+        runner: &mut CairoRunner,
+    ) {
+        if !self.is_recording_call() {
+            return;
+        }
+
+        let storage_deltas = self
+            .selected_call
+            .as_mut()
+            .map(|selected| std::mem::take(&mut selected.storage_deltas))
+            .unwrap_or_default();
+        let windows = relocate_run(vm, runner).and_then(|()| {
+            step_windows(
+                vm.get_relocated_trace()?,
+                &runner.relocated_memory,
+                storage_deltas,
+                self.config.window_size,
+            )
+        });
+        match windows {
+            Ok(windows) => {
+                *self.config.lock() =
+                    Some(StepDeltas { storage_address, entry_point_selector, windows });
+            }
+            Err(error) => log::warn!("Failed to record the step deltas of a call: {error}"),
+        }
+    }
+
+    // The depth of the running call; zero for the top-level call.
+    fn depth(&self) -> usize {
+        self.n_inner_calls.len().saturating_sub(1)
+    }
+}
+
+fn step_windows(
+    relocated_trace: &[RelocatedTraceEntry],
+    relocated_memory: &[Option<Felt252>],
+    storage_deltas: Vec<StorageDelta>,
+    window_size: usize,
+) -> Result<Vec<StepWindow>, ExecutionArtifactsError> {
+    let mut accessed_addresses = HashSet::new();
+    let mut storage_deltas = storage_deltas.into_iter().peekable();
+    relocated_trace
+        .chunks(window_size)
+        .enumerate()
+        .map(|(window_index, entries)| {
+            let first_step = window_index * window_size;
+            let mut memory_deltas = Vec::new();
+            for entry in entries {
+                for address in operand_addresses(entry, relocated_memory)? {
+                    let Some(value) = relocated_memory.get(address).and_then(Option::as_ref) else {
+                        continue;
+                    };
+                    if accessed_addresses.insert(address) {
+                        memory_deltas
+                            .push(MemoryCell { address, value: felt_to_stark_felt(value) });
+                    }
+                }
+            }
+
+            let end_step = first_step + entries.len();
+            let registers = entries
+                .iter()
+                .map(|entry| RegisterState { pc: entry.pc, ap: entry.ap, fp: entry.fp })
+                .collect();
+            let storage_deltas =
+                iter::from_fn(|| storage_deltas.next_if(|delta| delta.step < end_step)).collect();
+            Ok(StepWindow { first_step, registers, memory_deltas, storage_deltas })
+        })
+        .collect()
+}
+
+/// Returns the relocated addresses of the operands of the instruction run by the given step: its
+/// destination, and its first and second operands (unless the latter is an immediate).
+fn operand_addresses(
+    entry: &RelocatedTraceEntry,
+    relocated_memory: &[Option<Felt252>],
+) -> Result<impl Iterator<Item = usize>, ExecutionArtifactsError> {
+    let invalid_instruction = || ExecutionArtifactsError::InvalidInstruction { pc: entry.pc };
+    let cell = |address: usize| relocated_memory.get(address).and_then(Option::as_ref);
+    let add_offset = |base: usize, offset: isize| {
+        base.checked_add_signed(offset).ok_or_else(invalid_instruction)
+    };
+    let register = |register: &Register| match register {
+        Register::AP => entry.ap,
+        Register::FP => entry.fp,
+    };
+
+    let encoded_instruction =
+        cell(entry.pc).and_then(|felt| felt.to_u64()).ok_or_else(invalid_instruction)?;
+    let instruction = decode_instruction(encoded_instruction, cell(entry.pc + 1))?;
+    let dst_address = add_offset(register(&instruction.dst_register), instruction.off0)?;
+    let op0_address = add_offset(register(&instruction.op0_register), instruction.off1)?;
+    let op1_address = match instruction.op1_addr {
+        Op1Addr::Imm => None,
+        Op1Addr::AP => Some(add_offset(entry.ap, instruction.off2)?),
+        Op1Addr::FP => Some(add_offset(entry.fp, instruction.off2)?),
+        Op1Addr::Op0 => {
+            let op0 = cell(op0_address).and_then(|felt| felt.to_usize());
+            Some(add_offset(op0.ok_or_else(invalid_instruction)?, instruction.off2)?)
+        }
+    };
+
+    Ok([Some(dst_address), Some(op0_address), op1_address].into_iter().flatten())
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::TransactionHash;
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::{BlockContext, ChainInfo, TransactionContext};
+use crate::execution::call_info::{CallPath, TopLevelCall};
+use crate::execution::entry_point::{CallEntryPoint, EntryPointExecutionContext};
+use crate::execution::step_deltas::{StepDeltas, StepDeltasConfig};
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{
+    create_calldata, trivial_external_entry_point_with_address, CairoVersion, BALANCE,
+};
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
+
+const KEY: u16 = 1234;
+const VALUE: u8 = 18;
+
+/// Makes `test_call_contract` call `test_storage_read_write` on another instance of the test
+/// contract, as the `__execute__` call of a transaction whose hash is zero, and returns the
+/// recording of the selected call.
+fn record_step_deltas(
+    cairo_version: CairoVersion,
+    transaction_hash: TransactionHash,
+    call_path: CallPath,
+) -> Option<StepDeltas> {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let mut state = test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 2)]);
+    let call = CallEntryPoint {
+        entry_point_selector: selector_from_name("test_call_contract"),
+        calldata: create_calldata(
+            test_contract.get_instance_address(1),
+            "test_storage_read_write",
+            &[stark_felt!(KEY), stark_felt!(VALUE)],
+        ),
+        ..trivial_external_entry_point_with_address(test_contract.get_instance_address(0))
+    };
+
+    let block_context = BlockContext::create_for_testing();
+    block_context
+        .extensions()
+        .insert(StepDeltasConfig::new(transaction_hash, call_path).with_window_size(10));
+    let config = block_context.extensions().get::<StepDeltasConfig>().unwrap();
+    let tx_context = TransactionContext::new(
+        block_context,
+        TransactionInfo::Deprecated(DeprecatedTransactionInfo::default()),
+    );
+    let mut context = EntryPointExecutionContext::new_invoke(Arc::new(tx_context), true).unwrap();
+    context.record_step_deltas(TopLevelCall::Execute);
+    call.execute(&mut state, &mut ExecutionResources::default(), &mut context).unwrap();
+
+    config.take_recording()
+}
+
+#[rstest]
+fn test_step_deltas(
+    #[values(CairoVersion::Cairo0, CairoVersion::Cairo1)] cairo_version: CairoVersion,
+    #[values(vec![], vec![0])] inner_call_indices: Vec<usize>,
+) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let call_path = CallPath {
+        top_level_call: TopLevelCall::Execute,
+        inner_call_indices: inner_call_indices.clone(),
+    };
+    let step_deltas =
+        record_step_deltas(cairo_version, TransactionHash::default(), call_path).unwrap();
+
+    let (storage_address, selector) = match inner_call_indices.as_slice() {
+        [] => (test_contract.get_instance_address(0), selector_from_name("test_call_contract")),
+        _ => (test_contract.get_instance_address(1), selector_from_name("test_storage_read_write")),
+    };
+    assert_eq!(step_deltas.storage_address, storage_address);
+    assert_eq!(step_deltas.entry_point_selector, selector);
+
+    // Windows are consecutive, and hold up to 10 steps each.
+    let mut next_step = 0;
+    for window in &step_deltas.windows {
+        assert_eq!(window.first_step, next_step);
+        assert!(!window.registers.is_empty() && window.registers.len() <= 10);
+        next_step += window.registers.len();
+    }
+    assert!(step_deltas.windows.iter().any(|window| !window.memory_deltas.is_empty()));
+
+    // The storage write (of the recorded call, or of its inner call) is attributed to one step.
+    let storage_deltas: Vec<_> =
+        step_deltas.windows.iter().flat_map(|window| window.storage_deltas.clone()).collect();
+    assert_eq!(storage_deltas.len(), 1);
+    let storage_delta = storage_deltas[0];
+    assert_eq!(
+        (
+            storage_delta.address,
+            storage_delta.key,
+            storage_delta.previous_value,
+            storage_delta.value
+        ),
+        (
+            test_contract.get_instance_address(1),
+            StorageKey::try_from(stark_felt!(KEY)).unwrap(),
+            StarkFelt::ZERO,
+            stark_felt!(VALUE)
+        )
+    );
+    assert!(storage_delta.step < next_step);
+}
+
+#[rstest]
+#[case::other_transaction(
+    TransactionHash(stark_felt!(1_u8)),
+    CallPath { top_level_call: TopLevelCall::Execute, inner_call_indices: vec![] }
+)]
+#[case::other_top_level_call(
+    TransactionHash::default(),
+    CallPath { top_level_call: TopLevelCall::Validate, inner_call_indices: vec![] }
+)]
+#[case::missing_call(
+    TransactionHash::default(),
+    CallPath { top_level_call: TopLevelCall::Execute, inner_call_indices: vec![1] }
+)]
+fn test_unselected_call(#[case] transaction_hash: TransactionHash, #[case] call_path: CallPath) {
+    assert_eq!(record_step_deltas(CairoVersion::Cairo1, transaction_hash, call_path), None);
+}
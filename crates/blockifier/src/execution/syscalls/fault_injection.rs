@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::StarkFelt;
+
+use crate::execution::syscalls::SyscallSelector;
+
+#[cfg(test)]
+#[path = "fault_injection_test.rs"]
+pub mod test;
+
+/// A fault replacing the execution of a syscall handler.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SyscallFault {
+    /// The syscall fails with the given error data, returned to the calling contract (e.g., an
+    /// elliptic curve operation rejecting its input).
+    Failure(Vec<StarkFelt>),
+    /// The syscall runs out of gas, regardless of the gas left; no gas is charged.
+    OutOfGas,
+    /// The syscall handler fails with the given reason, aborting the execution of the call.
+    Fatal(String),
+}
+
+/// A test hook replacing the behavior of syscall handlers, e.g., forcing failures of the
+/// `secp256k1`/`secp256r1` curve operations or an exhausted gas budget, to exercise the failure
+/// handling of paths that real inputs cannot reach.
+/// Invoked before executing each syscall of Cairo 1 contracts, once its gas cost is covered.
+pub trait SyscallFaultInjector: Send + Sync {
+    /// Returns the fault replacing the execution of the syscall, if any.
+    fn inject(
+        &self,
+        selector: SyscallSelector,
+        storage_address: ContractAddress,
+    ) -> Option<SyscallFault>;
+}
+
+impl<F> SyscallFaultInjector for F
+where
+    F: Fn(SyscallSelector, ContractAddress) -> Option<SyscallFault> + Send + Sync,
+{
+    fn inject(
+        &self,
+        selector: SyscallSelector,
+        storage_address: ContractAddress,
+    ) -> Option<SyscallFault> {
+        self(selector, storage_address)
+    }
+}
+
+/// The syscall fault injector, registered by inserting it into the block context extensions.
+/// Available with the `testing` feature only.
+#[derive(Clone)]
+pub struct SyscallFaultInjection(pub Arc<dyn SyscallFaultInjector>);
+
+impl SyscallFaultInjection {
+    pub fn new(injector: impl SyscallFaultInjector + 'static) -> Self {
+        Self(Arc::new(injector))
+    }
+
+    /// Fails every call of the given syscall with the given fault.
+    pub fn always(selector: SyscallSelector, fault: SyscallFault) -> Self {
+        Self::new(move |syscall_selector, _| (syscall_selector == selector).then(|| fault.clone()))
+    }
+}
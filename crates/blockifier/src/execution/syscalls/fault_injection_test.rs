@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::{BlockContext, ChainInfo, TransactionContext};
+use crate::execution::call_info::CallInfo;
+use crate::execution::entry_point::{CallEntryPoint, EntryPointExecutionContext};
+use crate::execution::errors::EntryPointExecutionError;
+use crate::execution::syscalls::fault_injection::{SyscallFault, SyscallFaultInjection};
+use crate::execution::syscalls::hint_processor::OUT_OF_GAS_ERROR;
+use crate::execution::syscalls::SyscallSelector;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{trivial_external_entry_point_new, CairoVersion, BALANCE};
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
+
+fn execute_with_faults(
+    entry_point_name: &str,
+    calldata: Calldata,
+    injection: SyscallFaultInjection,
+) -> Result<CallInfo, EntryPointExecutionError> {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let mut state = test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 1)]);
+    let entry_point_call = CallEntryPoint {
+        calldata,
+        entry_point_selector: selector_from_name(entry_point_name),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(injection);
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+    let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+
+    entry_point_call.execute(&mut state, &mut ExecutionResources::default(), &mut context)
+}
+
+#[test]
+fn test_injected_failure() {
+    let error_data = vec![stark_felt!("0x1234")];
+    let injection = SyscallFaultInjection::always(
+        SyscallSelector::Secp256k1New,
+        SyscallFault::Failure(error_data.clone()),
+    );
+
+    // The contract unwraps the result of the (otherwise successful) syscall.
+    let error = execute_with_faults("test_secp256k1", calldata![], injection).unwrap_err();
+    assert_matches!(
+        error,
+        EntryPointExecutionError::ExecutionFailed { error_data: actual_error_data }
+        if actual_error_data == error_data
+    );
+}
+
+#[test]
+fn test_injected_out_of_gas() {
+    let injection =
+        SyscallFaultInjection::always(SyscallSelector::StorageRead, SyscallFault::OutOfGas);
+    let calldata = calldata![stark_felt!(1234_u16), stark_felt!(18_u8)];
+
+    let error = execute_with_faults("test_storage_read_write", calldata, injection).unwrap_err();
+    assert_matches!(
+        error,
+        EntryPointExecutionError::ExecutionFailed { error_data }
+        if error_data == vec![stark_felt!(OUT_OF_GAS_ERROR)]
+    );
+}
+
+#[test]
+fn test_injected_fatal_fault() {
+    let injection = SyscallFaultInjection::always(
+        SyscallSelector::StorageWrite,
+        SyscallFault::Fatal("storage is unavailable".into()),
+    );
+    let calldata = calldata![stark_felt!(1234_u16), stark_felt!(18_u8)];
+
+    let error = execute_with_faults("test_storage_read_write", calldata, injection).unwrap_err();
+    assert_matches!(error, EntryPointExecutionError::VirtualMachineExecutionErrorWithTrace { .. });
+    assert!(error.to_string().contains("Injected syscall fault: storage is unavailable"));
+}
+
+#[test]
+fn test_faults_scoped_by_storage_address() {
+    // Faults injected into other contracts do not affect the call.
+    let injection = SyscallFaultInjection::new(|_, storage_address| {
+        (storage_address == contract_address!("0x1234")).then_some(SyscallFault::OutOfGas)
+    });
+    let value = stark_felt!(18_u8);
+    let calldata = calldata![stark_felt!(1234_u16), value];
+
+    let call_info = execute_with_faults("test_storage_read_write", calldata, injection).unwrap();
+    assert_eq!(call_info.execution.retdata.0, vec![value]);
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::execution::syscalls::SyscallSelector;
+use crate::versioned_constants::GasCosts;
+
+#[cfg(test)]
+#[path = "gas_schedule_test.rs"]
+pub mod test;
+
+/// Per-syscall gas cost overrides for Cairo 1 contracts, on top of the gas costs of the versioned
+/// constants; register in the block context (see `BlockContext::with_syscall_gas_schedule`).
+/// Syscalls that are not overridden cost as in the versioned constants.
+///
+/// Note: the syscall base gas cost is pre-charged by the compiled code, hence syscalls cost at
+/// least it, even if overridden with a lower cost.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SyscallGasSchedule {
+    overrides: HashMap<SyscallSelector, u64>,
+}
+
+impl SyscallGasSchedule {
+    pub fn new(overrides: impl IntoIterator<Item = (SyscallSelector, u64)>) -> Self {
+        Self { overrides: overrides.into_iter().collect() }
+    }
+
+    pub fn with_override(mut self, selector: SyscallSelector, gas_cost: u64) -> Self {
+        self.overrides.insert(selector, gas_cost);
+        self
+    }
+
+    pub fn overrides(&self) -> &HashMap<SyscallSelector, u64> {
+        &self.overrides
+    }
+
+    /// Returns the gas cost of the syscall; `None` if it is not supported by Cairo 1 contracts.
+    pub fn gas_cost(&self, selector: SyscallSelector, gas_costs: &GasCosts) -> Option<u64> {
+        let default_gas_cost = Self::default_gas_cost(selector, gas_costs)?;
+        Some(self.overrides.get(&selector).copied().unwrap_or(default_gas_cost))
+    }
+
+    /// Returns the gas cost of the syscall in the versioned constants; `None` if it is not
+    /// supported by Cairo 1 contracts.
+    pub fn default_gas_cost(selector: SyscallSelector, gas_costs: &GasCosts) -> Option<u64> {
+        let gas_cost = match selector {
+            SyscallSelector::CallContract => gas_costs.call_contract_gas_cost,
+            SyscallSelector::Deploy => gas_costs.deploy_gas_cost,
+            SyscallSelector::EmitEvent => gas_costs.emit_event_gas_cost,
+            SyscallSelector::GetBlockHash => gas_costs.get_block_hash_gas_cost,
+            SyscallSelector::GetExecutionInfo => gas_costs.get_execution_info_gas_cost,
+            SyscallSelector::Keccak => gas_costs.keccak_gas_cost,
+            SyscallSelector::LibraryCall | SyscallSelector::LibraryCallL1Handler => {
+                gas_costs.library_call_gas_cost
+            }
+            SyscallSelector::ReplaceClass => gas_costs.replace_class_gas_cost,
+            SyscallSelector::Secp256k1Add => gas_costs.secp256k1_add_gas_cost,
+            SyscallSelector::Secp256k1GetPointFromX => {
+                gas_costs.secp256k1_get_point_from_x_gas_cost
+            }
+            SyscallSelector::Secp256k1GetXy => gas_costs.secp256k1_get_xy_gas_cost,
+            SyscallSelector::Secp256k1Mul => gas_costs.secp256k1_mul_gas_cost,
+            SyscallSelector::Secp256k1New => gas_costs.secp256k1_new_gas_cost,
+            SyscallSelector::Secp256r1Add => gas_costs.secp256r1_add_gas_cost,
+            SyscallSelector::Secp256r1GetPointFromX => {
+                gas_costs.secp256r1_get_point_from_x_gas_cost
+            }
+            SyscallSelector::Secp256r1GetXy => gas_costs.secp256r1_get_xy_gas_cost,
+            SyscallSelector::Secp256r1Mul => gas_costs.secp256r1_mul_gas_cost,
+            SyscallSelector::Secp256r1New => gas_costs.secp256r1_new_gas_cost,
+            SyscallSelector::SendMessageToL1 => gas_costs.send_message_to_l1_gas_cost,
+            SyscallSelector::StorageRead => gas_costs.storage_read_gas_cost,
+            SyscallSelector::StorageWrite => gas_costs.storage_write_gas_cost,
+            SyscallSelector::DelegateCall
+            | SyscallSelector::DelegateL1Handler
+            | SyscallSelector::GetBlockNumber
+            | SyscallSelector::GetBlockTimestamp
+            | SyscallSelector::GetCallerAddress
+            | SyscallSelector::GetContractAddress
+            | SyscallSelector::GetSequencerAddress
+            | SyscallSelector::GetTxInfo
+            | SyscallSelector::GetTxSignature => return None,
+        };
+
+        Some(gas_cost)
+    }
+}
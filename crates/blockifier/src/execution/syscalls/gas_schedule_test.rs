@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::{BlockContext, ChainInfo, TransactionContext};
+use crate::execution::entry_point::{CallEntryPoint, EntryPointExecutionContext};
+use crate::execution::syscalls::gas_schedule::SyscallGasSchedule;
+use crate::execution::syscalls::syscalls_test::REQUIRED_GAS_STORAGE_READ_WRITE_TEST;
+use crate::execution::syscalls::SyscallSelector;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{trivial_external_entry_point_new, CairoVersion, BALANCE};
+use crate::transaction::objects::{DeprecatedTransactionInfo, TransactionInfo};
+use crate::versioned_constants::VersionedConstants;
+
+#[test]
+fn test_gas_cost() {
+    let gas_costs = VersionedConstants::create_for_testing().gas_costs().clone();
+    let schedule = SyscallGasSchedule::default().with_override(SyscallSelector::StorageWrite, 7);
+
+    assert_eq!(schedule.gas_cost(SyscallSelector::StorageWrite, &gas_costs), Some(7));
+    assert_eq!(
+        schedule.gas_cost(SyscallSelector::StorageRead, &gas_costs),
+        Some(gas_costs.storage_read_gas_cost)
+    );
+    assert_eq!(
+        schedule.gas_cost(SyscallSelector::LibraryCallL1Handler, &gas_costs),
+        Some(gas_costs.library_call_gas_cost)
+    );
+    // Deprecated syscalls are not supported by Cairo 1 contracts, even if overridden.
+    let schedule = schedule.with_override(SyscallSelector::GetCallerAddress, 7);
+    assert_eq!(schedule.gas_cost(SyscallSelector::GetCallerAddress, &gas_costs), None);
+}
+
+#[test]
+fn test_overridden_syscall_gas_cost() {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let mut state = test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 1)]);
+    let entry_point_call = CallEntryPoint {
+        calldata: calldata![stark_felt!(1234_u16), stark_felt!(18_u8)],
+        entry_point_selector: selector_from_name("test_storage_read_write"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+
+    let block_context = BlockContext::create_for_testing();
+    let storage_write_gas_cost =
+        block_context.versioned_constants().gas_costs().storage_write_gas_cost;
+    let extra_gas_cost = 1000;
+    let block_context = block_context.with_syscall_gas_schedule(
+        SyscallGasSchedule::default()
+            .with_override(SyscallSelector::StorageWrite, storage_write_gas_cost + extra_gas_cost),
+    );
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+    let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+
+    let call_info = entry_point_call
+        .execute(&mut state, &mut ExecutionResources::default(), &mut context)
+        .unwrap();
+    // The entry point writes to storage once.
+    assert_eq!(
+        call_info.execution.gas_consumed,
+        REQUIRED_GAS_STORAGE_READ_WRITE_TEST + extra_gas_cost
+    );
+}
@@ -15,7 +15,7 @@ use cairo_vm::vm::errors::memory_errors::MemoryError;
 use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
 use cairo_vm::vm::runners::cairo_runner::{ExecutionResources, ResourceTracker, RunResources};
 use cairo_vm::vm::vm_core::VirtualMachine;
-use num_traits::Zero;
+use num_traits::{ToPrimitive, Zero};
 use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
@@ -33,21 +33,27 @@ use crate::execution::execution_utils::{
     felt_range_from_ptr, max_fee_for_execution_info, stark_felt_from_ptr, stark_felt_to_felt,
     write_maybe_relocatable, ReadOnlySegment, ReadOnlySegments,
 };
+#[cfg(any(feature = "testing", test))]
+use crate::execution::syscalls::fault_injection::{SyscallFault, SyscallFaultInjection};
 use crate::execution::syscalls::secp::{
     secp256k1_add, secp256k1_get_point_from_x, secp256k1_get_xy, secp256k1_mul, secp256k1_new,
     secp256r1_add, secp256r1_get_point_from_x, secp256r1_get_xy, secp256r1_mul, secp256r1_new,
     SecpHintProcessor,
 };
+#[cfg(any(feature = "testing", test))]
+use crate::execution::syscalls::EmptyResponse;
 use crate::execution::syscalls::{
     call_contract, deploy, emit_event, get_block_hash, get_execution_info, keccak, library_call,
     library_call_l1_handler, replace_class, send_message_to_l1, storage_read, storage_write,
     StorageReadResponse, StorageWriteResponse, SyscallRequest, SyscallRequestWrapper,
     SyscallResponse, SyscallResponseWrapper, SyscallResult, SyscallSelector,
 };
+use crate::state::call_frame_state::CallFrameState;
 use crate::state::errors::StateError;
 use crate::state::state_api::State;
 use crate::transaction::objects::{CurrentTransactionInfo, TransactionInfo};
 use crate::transaction::transaction_utils::update_remaining_gas;
+use crate::versioned_constants::InnerCallFailurePolicy;
 
 pub type SyscallCounter = HashMap<SyscallSelector, usize>;
 
@@ -66,18 +72,28 @@ pub enum SyscallExecutionError {
     #[error("{error}")]
     CallContractExecutionError {
         storage_address: ContractAddress,
+        selector: EntryPointSelector,
         error: Box<SyscallExecutionError>,
     },
     #[error("{error}")]
     LibraryCallExecutionError {
         class_hash: ClassHash,
         storage_address: ContractAddress,
+        selector: EntryPointSelector,
         error: Box<SyscallExecutionError>,
     },
     #[error("Invalid syscall input: {input:?}; {info}")]
     InvalidSyscallInput { input: StarkFelt, info: String },
     #[error("Invalid syscall selector: {0:?}.")]
     InvalidSyscallSelector(StarkFelt),
+    #[error(
+        "Contract code may not write to the storage of protected contract {storage_address:?}."
+    )]
+    ProtectedStorageWrite { storage_address: ContractAddress },
+    #[error("Contract code may not replace the class of protected contract {contract_address:?}.")]
+    ProtectedClassReplacement { contract_address: ContractAddress },
+    #[error("Contract code may not deploy a contract at protected address {contract_address:?}.")]
+    ProtectedContractDeployment { contract_address: ContractAddress },
     #[error("Unauthorized syscall {syscall_name} in execution mode {execution_mode}.")]
     InvalidSyscallInExecutionMode { syscall_name: String, execution_mode: ExecutionMode },
     #[error(transparent)]
@@ -113,6 +129,16 @@ pub enum EmitEventError {
          events: {max_n_emitted_events}."
     )]
     ExceedsMaxNumberOfEmittedEvents { n_emitted_events: usize, max_n_emitted_events: usize },
+    #[error(
+        "Exceeded the maximum total keys length of emitted events, total keys length: \
+         {total_keys_length}, max total keys length: {max_total_keys_length}."
+    )]
+    ExceedsMaxTotalKeysLength { total_keys_length: usize, max_total_keys_length: usize },
+    #[error(
+        "Exceeded the maximum total data length of emitted events, total data length: \
+         {total_data_length}, max total data length: {max_total_data_length}."
+    )]
+    ExceedsMaxTotalDataLength { total_data_length: usize, max_total_data_length: usize },
 }
 
 // Needed for custom hint implementations (in our case, syscall hints) which must comply with the
@@ -124,18 +150,28 @@ impl From<SyscallExecutionError> for HintError {
 }
 
 impl SyscallExecutionError {
-    pub fn as_call_contract_execution_error(self, storage_address: ContractAddress) -> Self {
-        SyscallExecutionError::CallContractExecutionError { storage_address, error: Box::new(self) }
+    pub fn as_call_contract_execution_error(
+        self,
+        storage_address: ContractAddress,
+        selector: EntryPointSelector,
+    ) -> Self {
+        SyscallExecutionError::CallContractExecutionError {
+            storage_address,
+            selector,
+            error: Box::new(self),
+        }
     }
 
     pub fn as_lib_call_execution_error(
         self,
         class_hash: ClassHash,
         storage_address: ContractAddress,
+        selector: EntryPointSelector,
     ) -> Self {
         SyscallExecutionError::LibraryCallExecutionError {
             class_hash,
             storage_address,
+            selector,
             error: Box::new(self),
         }
     }
@@ -155,6 +191,9 @@ pub const INVALID_INPUT_LENGTH_ERROR: &str =
 // "Invalid argument";
 pub const INVALID_ARGUMENT: &str =
     "0x00000000000000000000000000000000496e76616c696420617267756d656e74";
+// "ENTRYPOINT_FAILED";
+pub const ENTRYPOINT_FAILED_ERROR: &str =
+    "0x000000000000000000000000000000454e545259504f494e545f4641494c4544";
 // "L1_GAS";
 pub const L1_GAS: &str = "0x00000000000000000000000000000000000000000000000000004c315f474153";
 // "L2_GAS";
@@ -192,6 +231,9 @@ pub struct SyscallHintProcessor<'a> {
     hints: &'a HashMap<String, Hint>,
     // Transaction info. and signature segments; allocated on-demand.
     execution_info_ptr: Option<Relocatable>,
+    // The fault replacing the execution of the current syscall, if any.
+    #[cfg(any(feature = "testing", test))]
+    injected_fault: Option<SyscallFault>,
 }
 
 impl<'a> SyscallHintProcessor<'a> {
@@ -219,6 +261,8 @@ impl<'a> SyscallHintProcessor<'a> {
             accessed_keys: HashSet::new(),
             hints,
             execution_info_ptr: None,
+            #[cfg(any(feature = "testing", test))]
+            injected_fault: None,
             secp256k1_hint_processor: SecpHintProcessor::default(),
             secp256r1_hint_processor: SecpHintProcessor::default(),
         }
@@ -278,92 +322,56 @@ impl<'a> SyscallHintProcessor<'a> {
             self.increment_syscall_count(&selector);
         }
 
-        let get_gas_cost = |name: &str| -> u64 { self.context.get_gas_cost(name) };
-        match selector {
-            SyscallSelector::CallContract => {
-                self.execute_syscall(vm, call_contract, get_gas_cost("call_contract_gas_cost"))
-            }
-            SyscallSelector::Deploy => {
-                self.execute_syscall(vm, deploy, get_gas_cost("deploy_gas_cost"))
-            }
-            SyscallSelector::EmitEvent => {
-                self.execute_syscall(vm, emit_event, get_gas_cost("emit_event_gas_cost"))
-            }
-            SyscallSelector::GetBlockHash => {
-                self.execute_syscall(vm, get_block_hash, get_gas_cost("get_block_hash_gas_cost"))
-            }
-            SyscallSelector::GetExecutionInfo => self.execute_syscall(
-                vm,
-                get_execution_info,
-                get_gas_cost("get_execution_info_gas_cost"),
-            ),
-            SyscallSelector::Keccak => {
-                self.execute_syscall(vm, keccak, get_gas_cost("keccak_gas_cost"))
-            }
-            SyscallSelector::LibraryCall => {
-                self.execute_syscall(vm, library_call, get_gas_cost("library_call_gas_cost"))
-            }
-            SyscallSelector::LibraryCallL1Handler => self.execute_syscall(
-                vm,
-                library_call_l1_handler,
-                get_gas_cost("library_call_gas_cost"),
-            ),
-            SyscallSelector::ReplaceClass => {
-                self.execute_syscall(vm, replace_class, get_gas_cost("replace_class_gas_cost"))
-            }
-            SyscallSelector::Secp256k1Add => {
-                self.execute_syscall(vm, secp256k1_add, get_gas_cost("secp256k1_add_gas_cost"))
-            }
-            SyscallSelector::Secp256k1GetPointFromX => self.execute_syscall(
-                vm,
-                secp256k1_get_point_from_x,
-                get_gas_cost("secp256k1_get_point_from_x_gas_cost"),
-            ),
-            SyscallSelector::Secp256k1GetXy => self.execute_syscall(
-                vm,
-                secp256k1_get_xy,
-                get_gas_cost("secp256k1_get_xy_gas_cost"),
-            ),
-            SyscallSelector::Secp256k1Mul => {
-                self.execute_syscall(vm, secp256k1_mul, get_gas_cost("secp256k1_mul_gas_cost"))
-            }
-            SyscallSelector::Secp256k1New => {
-                self.execute_syscall(vm, secp256k1_new, get_gas_cost("secp256k1_new_gas_cost"))
-            }
-            SyscallSelector::Secp256r1Add => {
-                self.execute_syscall(vm, secp256r1_add, get_gas_cost("secp256r1_add_gas_cost"))
+        // The gas counter is the first field of every syscall request.
+        self.context.remaining_gas = vm.get_integer(self.syscall_ptr)?.to_u64();
+        self.context.notify_syscall(selector);
+        #[cfg(any(feature = "testing", test))]
+        {
+            self.injected_fault = self.inject_fault(selector);
+        }
+
+        // Unsupported syscalls fail below, without being charged.
+        let gas_cost = self.context.syscall_gas_cost(selector).unwrap_or_default();
+        let result = match selector {
+            SyscallSelector::CallContract => self.execute_syscall(vm, call_contract, gas_cost),
+            SyscallSelector::Deploy => self.execute_syscall(vm, deploy, gas_cost),
+            SyscallSelector::EmitEvent => self.execute_syscall(vm, emit_event, gas_cost),
+            SyscallSelector::GetBlockHash => self.execute_syscall(vm, get_block_hash, gas_cost),
+            SyscallSelector::GetExecutionInfo => {
+                self.execute_syscall(vm, get_execution_info, gas_cost)
             }
-            SyscallSelector::Secp256r1GetPointFromX => self.execute_syscall(
-                vm,
-                secp256r1_get_point_from_x,
-                get_gas_cost("secp256r1_get_point_from_x_gas_cost"),
-            ),
-            SyscallSelector::Secp256r1GetXy => self.execute_syscall(
-                vm,
-                secp256r1_get_xy,
-                get_gas_cost("secp256r1_get_xy_gas_cost"),
-            ),
-            SyscallSelector::Secp256r1Mul => {
-                self.execute_syscall(vm, secp256r1_mul, get_gas_cost("secp256r1_mul_gas_cost"))
+            SyscallSelector::Keccak => self.execute_syscall(vm, keccak, gas_cost),
+            SyscallSelector::LibraryCall => self.execute_syscall(vm, library_call, gas_cost),
+            SyscallSelector::LibraryCallL1Handler => {
+                self.execute_syscall(vm, library_call_l1_handler, gas_cost)
             }
-            SyscallSelector::Secp256r1New => {
-                self.execute_syscall(vm, secp256r1_new, get_gas_cost("secp256r1_new_gas_cost"))
+            SyscallSelector::ReplaceClass => self.execute_syscall(vm, replace_class, gas_cost),
+            SyscallSelector::Secp256k1Add => self.execute_syscall(vm, secp256k1_add, gas_cost),
+            SyscallSelector::Secp256k1GetPointFromX => {
+                self.execute_syscall(vm, secp256k1_get_point_from_x, gas_cost)
             }
-            SyscallSelector::SendMessageToL1 => self.execute_syscall(
-                vm,
-                send_message_to_l1,
-                get_gas_cost("send_message_to_l1_gas_cost"),
-            ),
-            SyscallSelector::StorageRead => {
-                self.execute_syscall(vm, storage_read, get_gas_cost("storage_read_gas_cost"))
+            SyscallSelector::Secp256k1GetXy => self.execute_syscall(vm, secp256k1_get_xy, gas_cost),
+            SyscallSelector::Secp256k1Mul => self.execute_syscall(vm, secp256k1_mul, gas_cost),
+            SyscallSelector::Secp256k1New => self.execute_syscall(vm, secp256k1_new, gas_cost),
+            SyscallSelector::Secp256r1Add => self.execute_syscall(vm, secp256r1_add, gas_cost),
+            SyscallSelector::Secp256r1GetPointFromX => {
+                self.execute_syscall(vm, secp256r1_get_point_from_x, gas_cost)
             }
-            SyscallSelector::StorageWrite => {
-                self.execute_syscall(vm, storage_write, get_gas_cost("storage_write_gas_cost"))
+            SyscallSelector::Secp256r1GetXy => self.execute_syscall(vm, secp256r1_get_xy, gas_cost),
+            SyscallSelector::Secp256r1Mul => self.execute_syscall(vm, secp256r1_mul, gas_cost),
+            SyscallSelector::Secp256r1New => self.execute_syscall(vm, secp256r1_new, gas_cost),
+            SyscallSelector::SendMessageToL1 => {
+                self.execute_syscall(vm, send_message_to_l1, gas_cost)
             }
+            SyscallSelector::StorageRead => self.execute_syscall(vm, storage_read, gas_cost),
+            SyscallSelector::StorageWrite => self.execute_syscall(vm, storage_write, gas_cost),
             _ => Err(HintError::UnknownHint(
                 format!("Unsupported syscall selector {selector:?}.").into(),
             )),
-        }
+        };
+        self.context.notify_syscall_exit(selector);
+
+        result
     }
 
     pub fn get_or_allocate_execution_info_segment(
@@ -424,8 +432,10 @@ impl<'a> SyscallHintProcessor<'a> {
             &mut u64, // Remaining gas.
         ) -> SyscallResult<Response>,
     {
-        // Refund `SYSCALL_BASE_GAS_COST` as it was pre-charged.
-        let required_gas = syscall_gas_cost - self.context.get_gas_cost("syscall_base_gas_cost");
+        // Refund `SYSCALL_BASE_GAS_COST` as it was pre-charged; overridden costs below it are
+        // effectively raised to it.
+        let required_gas =
+            syscall_gas_cost.saturating_sub(self.context.gas_costs().syscall_base_gas_cost);
 
         let SyscallRequestWrapper { gas_counter, request } =
             SyscallRequestWrapper::<Request>::read(vm, &mut self.syscall_ptr)?;
@@ -441,6 +451,11 @@ impl<'a> SyscallHintProcessor<'a> {
             return Ok(());
         }
 
+        #[cfg(any(feature = "testing", test))]
+        if let Some(fault) = self.injected_fault.take() {
+            return self.write_fault(vm, fault, gas_counter, required_gas);
+        }
+
         // Execute.
         let mut remaining_gas = gas_counter - required_gas;
         let original_response = execute_callback(request, vm, self, &mut remaining_gas);
@@ -459,6 +474,44 @@ impl<'a> SyscallHintProcessor<'a> {
         Ok(())
     }
 
+    /// Returns the fault injected into the syscall by the registered fault injector, if any.
+    #[cfg(any(feature = "testing", test))]
+    fn inject_fault(&self, selector: SyscallSelector) -> Option<SyscallFault> {
+        let injection = self.context.tx_context.extensions.get::<SyscallFaultInjection>()?;
+        injection.0.inject(selector, self.storage_address())
+    }
+
+    /// Writes the response of a syscall replaced by the given fault.
+    #[cfg(any(feature = "testing", test))]
+    fn write_fault(
+        &mut self,
+        vm: &mut VirtualMachine,
+        fault: SyscallFault,
+        gas_counter: u64,
+        required_gas: u64,
+    ) -> HintExecutionResult {
+        // Failures carry no response; the response type is arbitrary.
+        let response: SyscallResponseWrapper<EmptyResponse> = match fault {
+            SyscallFault::Failure(error_data) => SyscallResponseWrapper::Failure {
+                gas_counter: gas_counter - required_gas,
+                error_data,
+            },
+            SyscallFault::OutOfGas => {
+                let out_of_gas_error =
+                    StarkFelt::try_from(OUT_OF_GAS_ERROR).map_err(SyscallExecutionError::from)?;
+                SyscallResponseWrapper::Failure { gas_counter, error_data: vec![out_of_gas_error] }
+            }
+            SyscallFault::Fatal(reason) => {
+                return Err(HintError::Internal(VirtualMachineError::Other(anyhow::anyhow!(
+                    "Injected syscall fault: {reason}"
+                ))));
+            }
+        };
+        response.write(vm, &mut self.syscall_ptr)?;
+
+        Ok(())
+    }
+
     fn read_next_syscall_selector(&mut self, vm: &mut VirtualMachine) -> SyscallResult<StarkFelt> {
         let selector = stark_felt_from_ptr(vm, &mut self.syscall_ptr)?;
 
@@ -553,8 +606,8 @@ impl<'a> SyscallHintProcessor<'a> {
             tx_signature_start_ptr.into(),
             tx_signature_end_ptr.into(),
             stark_felt_to_felt((tx_info).transaction_hash().0).into(),
-            Felt252::from_bytes_be(
-                self.context.tx_context.block_context.chain_info.chain_id.0.as_bytes(),
+            stark_felt_to_felt(
+                self.context.tx_context.block_context.chain_info.chain_id.to_short_string_felt(),
             )
             .into(),
             stark_felt_to_felt((tx_info).nonce().0).into(),
@@ -610,6 +663,9 @@ impl<'a> SyscallHintProcessor<'a> {
         self.accessed_keys.insert(key);
         let value = self.state.get_storage_at(self.storage_address(), key)?;
         self.read_values.push(value);
+        if let Some(observers) = self.context.observers() {
+            observers.notify_storage_read(self.storage_address(), key, value);
+        }
 
         Ok(StorageReadResponse { value })
     }
@@ -619,8 +675,30 @@ impl<'a> SyscallHintProcessor<'a> {
         key: StorageKey,
         value: StarkFelt,
     ) -> SyscallResult<StorageWriteResponse> {
+        let storage_address = self.storage_address();
+        let protected_addresses =
+            &self.context.tx_context.block_context.chain_info.protected_addresses;
+        if protected_addresses.contains(&storage_address) {
+            return Err(SyscallExecutionError::ProtectedStorageWrite { storage_address });
+        }
+
         self.accessed_keys.insert(key);
-        self.state.set_storage_at(self.storage_address(), key, value)?;
+        if let Some((recorder, n_remaining_steps)) = self.context.step_deltas_recorder() {
+            if recorder.is_recording() {
+                let previous_value = self.state.get_storage_at(storage_address, key)?;
+                recorder.record_storage_write(
+                    n_remaining_steps,
+                    storage_address,
+                    key,
+                    previous_value,
+                    value,
+                );
+            }
+        }
+        self.state.set_storage_at(storage_address, key, value)?;
+        if let Some(observers) = self.context.observers() {
+            observers.notify_storage_write(storage_address, key, value);
+        }
 
         Ok(StorageWriteResponse {})
     }
@@ -648,7 +726,7 @@ fn get_ptr_from_res_operand_unchecked(vm: &mut VirtualMachine, res: &ResOperand)
 
 impl ResourceTracker for SyscallHintProcessor<'_> {
     fn consumed(&self) -> bool {
-        self.context.vm_run_resources.consumed()
+        self.context.vm_run_resources.consumed() || self.context.passed_deadline_on_step()
     }
 
     fn consume_step(&mut self) {
@@ -721,15 +799,33 @@ pub fn execute_inner_call(
     syscall_handler: &mut SyscallHintProcessor<'_>,
     remaining_gas: &mut u64,
 ) -> SyscallResult<ReadOnlySegment> {
+    let versioned_constants = syscall_handler.context.versioned_constants();
+    let failure_policy = versioned_constants.inner_call_failure_policy;
+    let failed_call_state_policy = versioned_constants.failed_call_state_policy;
+    // State modifications of the inner call are buffered until it returns.
+    let mut frame_state = CallFrameState::new(syscall_handler.state);
     let call_info =
-        call.execute(syscall_handler.state, syscall_handler.resources, syscall_handler.context)?;
+        match call.execute(&mut frame_state, syscall_handler.resources, syscall_handler.context) {
+            Ok(call_info) => call_info,
+            Err(error)
+                if failure_policy == InnerCallFailurePolicy::ReturnAllFailures
+                    && error.is_recoverable() =>
+            {
+                frame_state.fail(failed_call_state_policy)?;
+                let error_data = vec![StarkFelt::try_from(ENTRYPOINT_FAILED_ERROR)?];
+                return Err(SyscallExecutionError::SyscallError { error_data });
+            }
+            Err(error) => return Err(error.into()),
+        };
     let raw_retdata = &call_info.execution.retdata.0;
 
     if call_info.execution.failed {
+        frame_state.fail(failed_call_state_policy)?;
         // TODO(spapini): Append an error word according to starknet spec if needed.
         // Something like "EXECUTION_ERROR".
         return Err(SyscallExecutionError::SyscallError { error_data: raw_retdata.clone() });
     }
+    frame_state.commit()?;
 
     let retdata_segment = create_retdata_segment(vm, syscall_handler, raw_retdata)?;
     update_remaining_gas(remaining_gas, &call_info);
@@ -774,7 +870,11 @@ pub fn execute_library_call(
     };
 
     execute_inner_call(entry_point, vm, syscall_handler, remaining_gas).map_err(|error| {
-        error.as_lib_call_execution_error(class_hash, syscall_handler.storage_address())
+        error.as_lib_call_execution_error(
+            class_hash,
+            syscall_handler.storage_address(),
+            entry_point_selector,
+        )
     })
 }
 
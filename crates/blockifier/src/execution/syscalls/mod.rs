@@ -19,18 +19,26 @@ use self::hint_processor::{
     SyscallExecutionError, SyscallHintProcessor, BLOCK_NUMBER_OUT_OF_RANGE_ERROR,
 };
 use crate::abi::constants;
+use crate::blockifier::block::BlockHashHistory;
 use crate::execution::call_info::{MessageToL1, OrderedEvent, OrderedL2ToL1Message};
 use crate::execution::contract_class::ContractClass;
 use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
-use crate::execution::entry_point::{CallEntryPoint, CallType, ConstructorContext};
+use crate::execution::entry_point::{
+    CallEntryPoint, CallType, ConstructorContext, EntryPointExecutionContext,
+};
 use crate::execution::execution_utils::{
-    execute_deployment, felt_from_ptr, felt_to_stark_felt, stark_felt_from_ptr, stark_felt_to_felt,
-    write_felt, write_maybe_relocatable, write_stark_felt, ReadOnlySegment,
+    check_deploy_collision, execute_deployment, felt_from_ptr, felt_to_stark_felt,
+    stark_felt_from_ptr, stark_felt_to_felt, write_felt, write_maybe_relocatable, write_stark_felt,
+    ReadOnlySegment,
 };
 use crate::execution::syscalls::hint_processor::{INVALID_INPUT_LENGTH_ERROR, OUT_OF_GAS_ERROR};
+use crate::state::call_frame_state::CallFrameState;
 use crate::transaction::transaction_utils::update_remaining_gas;
 use crate::versioned_constants::{EventLimits, VersionedConstants};
 
+#[cfg(any(feature = "testing", test))]
+pub mod fault_injection;
+pub mod gas_schedule;
 pub mod hint_processor;
 mod secp;
 
@@ -159,19 +167,19 @@ pub fn call_contract(
     syscall_handler: &mut SyscallHintProcessor<'_>,
     remaining_gas: &mut u64,
 ) -> SyscallResult<CallContractResponse> {
-    let storage_address = request.contract_address;
+    let (storage_address, selector) = (request.contract_address, request.function_selector);
     if syscall_handler.is_validate_mode() && syscall_handler.storage_address() != storage_address {
         let error = SyscallExecutionError::InvalidSyscallInExecutionMode {
             syscall_name: "call_contract".to_string(),
             execution_mode: syscall_handler.execution_mode(),
         };
-        return Err(error.as_call_contract_execution_error(storage_address));
+        return Err(error.as_call_contract_execution_error(storage_address, selector));
     }
     let entry_point = CallEntryPoint {
         class_hash: None,
         code_address: Some(storage_address),
         entry_point_type: EntryPointType::External,
-        entry_point_selector: request.function_selector,
+        entry_point_selector: selector,
         calldata: request.calldata,
         storage_address,
         caller_address: syscall_handler.storage_address(),
@@ -179,7 +187,7 @@ pub fn call_contract(
         initial_gas: *remaining_gas,
     };
     let retdata_segment = execute_inner_call(entry_point, vm, syscall_handler, remaining_gas)
-        .map_err(|error| error.as_call_contract_execution_error(storage_address))?;
+        .map_err(|error| error.as_call_contract_execution_error(storage_address, selector))?;
 
     Ok(CallContractResponse { segment: retdata_segment })
 }
@@ -243,6 +251,30 @@ pub fn deploy(
         &request.constructor_calldata,
         deployer_address_for_calculation,
     )?;
+    if syscall_handler
+        .context
+        .tx_context
+        .block_context
+        .chain_info
+        .protected_addresses
+        .contains(&deployed_contract_address)
+    {
+        return Err(SyscallExecutionError::ProtectedContractDeployment {
+            contract_address: deployed_contract_address,
+        });
+    }
+    if check_deploy_collision(
+        syscall_handler.state,
+        syscall_handler.context,
+        deployed_contract_address,
+        request.class_hash,
+    )? {
+        let constructor_retdata = create_retdata_segment(vm, syscall_handler, &[])?;
+        return Ok(DeployResponse {
+            contract_address: deployed_contract_address,
+            constructor_retdata,
+        });
+    }
 
     let ctor_context = ConstructorContext {
         class_hash: request.class_hash,
@@ -250,14 +282,25 @@ pub fn deploy(
         storage_address: deployed_contract_address,
         caller_address: deployer_address,
     };
-    let call_info = execute_deployment(
-        syscall_handler.state,
+    let failed_call_state_policy =
+        syscall_handler.context.versioned_constants().failed_call_state_policy;
+    // As with inner calls, the modifications of the deployment are buffered until it returns.
+    let mut frame_state = CallFrameState::new(syscall_handler.state);
+    let call_info = match execute_deployment(
+        &mut frame_state,
         syscall_handler.resources,
         syscall_handler.context,
         ctor_context,
         request.constructor_calldata,
         *remaining_gas,
-    )?;
+    ) {
+        Ok(call_info) => call_info,
+        Err(error) => {
+            frame_state.fail(failed_call_state_policy)?;
+            return Err(error.into());
+        }
+    };
+    frame_state.commit()?;
 
     let constructor_retdata =
         create_retdata_segment(vm, syscall_handler, &call_info.execution.retdata.0)?;
@@ -293,7 +336,7 @@ pub fn exceeds_event_size_limit(
     n_emitted_events: usize,
     event: &EventContent,
 ) -> Result<(), EmitEventError> {
-    let EventLimits { max_data_length, max_keys_length, max_n_emitted_events } =
+    let EventLimits { max_data_length, max_keys_length, max_n_emitted_events, .. } =
         versioned_constants.tx_event_limits;
     if n_emitted_events > max_n_emitted_events {
         return Err(EmitEventError::ExceedsMaxNumberOfEmittedEvents {
@@ -313,20 +356,53 @@ pub fn exceeds_event_size_limit(
     Ok(())
 }
 
+/// Accounts for the given event in the cumulative event counters of the execution, failing as
+/// soon as the cumulative limits are crossed; the event is not counted in that case.
+pub fn track_cumulative_event_limits(
+    context: &mut EntryPointExecutionContext,
+    event: &EventContent,
+) -> Result<(), EmitEventError> {
+    let EventLimits { max_total_data_length, max_total_keys_length, .. } =
+        context.versioned_constants().tx_event_limits;
+    let total_keys_length = context.n_emitted_event_keys + event.keys.len();
+    if total_keys_length > max_total_keys_length {
+        return Err(EmitEventError::ExceedsMaxTotalKeysLength {
+            total_keys_length,
+            max_total_keys_length,
+        });
+    }
+    let total_data_length = context.n_emitted_event_data + event.data.0.len();
+    if total_data_length > max_total_data_length {
+        return Err(EmitEventError::ExceedsMaxTotalDataLength {
+            total_data_length,
+            max_total_data_length,
+        });
+    }
+
+    context.n_emitted_event_keys = total_keys_length;
+    context.n_emitted_event_data = total_data_length;
+    Ok(())
+}
+
 pub fn emit_event(
     request: EmitEventRequest,
     _vm: &mut VirtualMachine,
     syscall_handler: &mut SyscallHintProcessor<'_>,
     _remaining_gas: &mut u64,
 ) -> SyscallResult<EmitEventResponse> {
+    let storage_address = syscall_handler.storage_address();
     let execution_context = &mut syscall_handler.context;
     exceeds_event_size_limit(
         execution_context.versioned_constants(),
         execution_context.n_emitted_events + 1,
         &request.content,
     )?;
+    track_cumulative_event_limits(execution_context, &request.content)?;
     let ordered_event =
         OrderedEvent { order: execution_context.n_emitted_events, event: request.content };
+    if let Some(observers) = execution_context.observers() {
+        observers.notify_event(storage_address, &ordered_event);
+    }
     syscall_handler.events.push(ordered_event);
     execution_context.n_emitted_events += 1;
 
@@ -397,7 +473,8 @@ pub fn get_block_hash(
 
     let key = StorageKey::try_from(StarkFelt::from(requested_block_number))?;
     let block_hash_contract_address =
-        ContractAddress::try_from(StarkFelt::from(constants::BLOCK_HASH_CONTRACT_ADDRESS))?;
+        BlockHashHistory::from_extensions(&syscall_handler.context.tx_context.extensions)
+            .contract_address;
     let block_hash =
         BlockHash(syscall_handler.state.get_storage_at(block_hash_contract_address, key)?);
     Ok(GetBlockHashResponse { block_hash })
@@ -514,6 +591,18 @@ pub fn replace_class(
     syscall_handler: &mut SyscallHintProcessor<'_>,
     _remaining_gas: &mut u64,
 ) -> SyscallResult<ReplaceClassResponse> {
+    let contract_address = syscall_handler.storage_address();
+    if syscall_handler
+        .context
+        .tx_context
+        .block_context
+        .chain_info
+        .protected_addresses
+        .contains(&contract_address)
+    {
+        return Err(SyscallExecutionError::ProtectedClassReplacement { contract_address });
+    }
+
     // Ensure the class is declared (by reading it), and of type V1.
     let class_hash = request.class_hash;
     let class = syscall_handler.state.get_compiled_contract_class(class_hash)?;
@@ -523,9 +612,7 @@ pub fn replace_class(
             Err(SyscallExecutionError::ForbiddenClassReplacement { class_hash })
         }
         ContractClass::V1(_) => {
-            syscall_handler
-                .state
-                .set_class_hash_at(syscall_handler.storage_address(), class_hash)?;
+            syscall_handler.state.set_class_hash_at(contract_address, class_hash)?;
             Ok(ReplaceClassResponse {})
         }
     }
@@ -694,8 +781,7 @@ pub fn keccak(
 
     // TODO(Ori, 1/2/2024): Write an indicative expect message explaining why the conversion works.
     let n_rounds_as_u64 = u64::try_from(n_rounds).expect("Failed to convert usize to u64.");
-    let gas_cost =
-        n_rounds_as_u64 * syscall_handler.context.get_gas_cost("keccak_round_cost_gas_cost");
+    let gas_cost = n_rounds_as_u64 * syscall_handler.context.gas_costs().keccak_round_cost_gas_cost;
     if gas_cost > *remaining_gas {
         let out_of_gas_error =
             StarkFelt::try_from(OUT_OF_GAS_ERROR).map_err(SyscallExecutionError::from)?;
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
 use assert_matches::assert_matches;
 use cairo_felt::Felt252;
@@ -9,7 +10,7 @@ use num_traits::Pow;
 use pretty_assertions::assert_eq;
 use rstest::rstest;
 use starknet_api::core::{
-    calculate_contract_address, ChainId, ContractAddress, EthAddress, Nonce, PatriciaKey,
+    calculate_contract_address, ChainId, ClassHash, ContractAddress, EthAddress, Nonce, PatriciaKey,
 };
 use starknet_api::data_availability::DataAvailabilityMode;
 use starknet_api::hash::{StarkFelt, StarkHash};
@@ -24,18 +25,22 @@ use test_case::test_case;
 
 use crate::abi::abi_utils::selector_from_name;
 use crate::abi::constants;
-use crate::context::ChainInfo;
+use crate::blockifier::block::BlockInfo;
+use crate::context::{BlockContext, ChainInfo, ProtectedAddresses, TransactionContext};
 use crate::execution::call_info::{
     CallExecution, CallInfo, MessageToL1, OrderedEvent, OrderedL2ToL1Message, Retdata,
 };
 use crate::execution::common_hints::ExecutionMode;
-use crate::execution::entry_point::{CallEntryPoint, CallType};
+use crate::execution::entry_point::{CallEntryPoint, CallType, EntryPointExecutionContext};
 use crate::execution::errors::EntryPointExecutionError;
-use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use crate::execution::execution_utils::{
+    felt_to_stark_felt, stark_felt_to_felt, DeployCollisionPolicy,
+};
 use crate::execution::syscalls::hint_processor::{
-    EmitEventError, BLOCK_NUMBER_OUT_OF_RANGE_ERROR, L1_GAS, L2_GAS, OUT_OF_GAS_ERROR,
+    EmitEventError, BLOCK_NUMBER_OUT_OF_RANGE_ERROR, ENTRYPOINT_FAILED_ERROR, L1_GAS, L2_GAS,
+    OUT_OF_GAS_ERROR,
 };
-use crate::execution::syscalls::SyscallSelector;
+use crate::execution::syscalls::{track_cumulative_event_limits, SyscallSelector};
 use crate::state::state_api::{State, StateReader};
 use crate::test_utils::contracts::FeatureContract;
 use crate::test_utils::initial_test_state::test_state;
@@ -49,7 +54,7 @@ use crate::transaction::constants::QUERY_VERSION_BASE_BIT;
 use crate::transaction::objects::{
     CommonAccountFields, CurrentTransactionInfo, DeprecatedTransactionInfo, TransactionInfo,
 };
-use crate::versioned_constants::VersionedConstants;
+use crate::versioned_constants::{InnerCallFailurePolicy, VersionedConstants};
 use crate::{check_entry_point_execution_error_for_custom_hint, retdata};
 
 pub const REQUIRED_GAS_STORAGE_READ_WRITE_TEST: u64 = 34650;
@@ -85,6 +90,33 @@ fn test_storage_read_write() {
     assert_eq!(value_from_state, value);
 }
 
+#[test_case(CairoVersion::Cairo0; "cairo0")]
+#[test_case(CairoVersion::Cairo1; "cairo1")]
+fn test_storage_write_to_protected_contract(cairo_version: CairoVersion) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let mut state = test_state(&ChainInfo::create_for_testing(), BALANCE, &[(test_contract, 1)]);
+    let entry_point_call = CallEntryPoint {
+        calldata: calldata![stark_felt!(1234_u16), stark_felt!(18_u8)],
+        entry_point_selector: selector_from_name("test_storage_read_write"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let storage_address = entry_point_call.storage_address;
+
+    let mut block_context = BlockContext::create_for_testing();
+    block_context.chain_info.protected_addresses =
+        ProtectedAddresses(vec![storage_address..=storage_address]);
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+    let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+
+    let error = entry_point_call
+        .execute(&mut state, &mut ExecutionResources::default(), &mut context)
+        .unwrap_err();
+    assert!(error.to_string().contains(&format!(
+        "Contract code may not write to the storage of protected contract {storage_address:?}."
+    )));
+}
+
 #[test]
 fn test_call_contract() {
     let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
@@ -115,6 +147,50 @@ fn test_call_contract() {
     );
 }
 
+#[rstest]
+#[case::return_panics(InnerCallFailurePolicy::ReturnPanics)]
+#[case::return_all_failures(InnerCallFailurePolicy::ReturnAllFailures)]
+fn test_inner_call_failure_policy(#[case] inner_call_failure_policy: InnerCallFailurePolicy) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let chain_info = &ChainInfo::create_for_testing();
+    let mut state = test_state(chain_info, BALANCE, &[(test_contract, 1)]);
+
+    // Calls a selector the inner contract does not expose.
+    let calldata = create_calldata(test_contract.get_instance_address(0), "no_such_function", &[]);
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector_from_name("test_call_contract"),
+        calldata,
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let versioned_constants = VersionedConstants {
+        inner_call_failure_policy,
+        ..VersionedConstants::create_for_testing()
+    };
+    let block_context = BlockContext::new_unchecked(
+        &BlockInfo::create_for_testing(),
+        chain_info,
+        &versioned_constants,
+    );
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+    let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+    let error = entry_point_call
+        .execute(&mut state, &mut ExecutionResources::default(), &mut context)
+        .unwrap_err();
+
+    match inner_call_failure_policy {
+        InnerCallFailurePolicy::ReturnPanics => {
+            assert!(error.to_string().contains("not found in contract"));
+        }
+        // The failure is returned to the caller, which unwraps it, and panics.
+        InnerCallFailurePolicy::ReturnAllFailures => assert_matches!(
+            error,
+            EntryPointExecutionError::ExecutionFailed { error_data }
+            if error_data == vec![stark_felt!(ENTRYPOINT_FAILED_ERROR)]
+        ),
+    }
+}
+
 #[test]
 fn test_emit_event() {
     let versioned_constants = VersionedConstants::create_for_testing();
@@ -170,6 +246,35 @@ fn test_emit_event() {
     assert!(error.to_string().contains(format!("{}", expected_error).as_str()));
 }
 
+#[test]
+fn test_cumulative_event_limits() {
+    let mut block_context = BlockContext::create_for_testing();
+    let tx_event_limits =
+        &mut Arc::make_mut(&mut block_context.versioned_constants).tx_event_limits;
+    tx_event_limits.max_total_keys_length = 3;
+    tx_event_limits.max_total_data_length = 4;
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+    let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+
+    let event = EventContent {
+        keys: vec![EventKey(stark_felt!(1_u8))],
+        data: EventData(vec![stark_felt!(2_u8); 2]),
+    };
+    track_cumulative_event_limits(&mut context, &event).unwrap();
+    track_cumulative_event_limits(&mut context, &event).unwrap();
+
+    // The third event crosses the cumulative data limit, and is not counted.
+    assert_matches!(
+        track_cumulative_event_limits(&mut context, &event).unwrap_err(),
+        EmitEventError::ExceedsMaxTotalDataLength {
+            total_data_length: 6,
+            max_total_data_length: 4
+        }
+    );
+    assert_eq!((context.n_emitted_event_keys, context.n_emitted_event_data), (2, 4));
+}
+
 fn emit_events(
     n_emitted_events: &[StarkFelt],
     keys: &[StarkFelt],
@@ -624,6 +729,10 @@ fn test_nested_library_call() {
         resources: storage_entry_point_resources.clone(),
         storage_read_values: vec![stark_felt!(value + 1)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key + 1))]),
+        syscall_counter: HashMap::from([
+            (SyscallSelector::StorageWrite, 1),
+            (SyscallSelector::StorageRead, 1),
+        ]),
         ..Default::default()
     };
     let library_call_resources = &get_syscall_resources(SyscallSelector::LibraryCall)
@@ -641,6 +750,7 @@ fn test_nested_library_call() {
         },
         resources: library_call_resources,
         inner_calls: vec![nested_storage_call_info],
+        syscall_counter: HashMap::from([(SyscallSelector::LibraryCall, 1)]),
         ..Default::default()
     };
     let storage_call_info = CallInfo {
@@ -653,6 +763,10 @@ fn test_nested_library_call() {
         resources: storage_entry_point_resources,
         storage_read_values: vec![stark_felt!(value)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key))]),
+        syscall_counter: HashMap::from([
+            (SyscallSelector::StorageWrite, 1),
+            (SyscallSelector::StorageRead, 1),
+        ]),
         ..Default::default()
     };
 
@@ -671,6 +785,7 @@ fn test_nested_library_call() {
         },
         resources: main_call_resources,
         inner_calls: vec![library_call_info, storage_call_info],
+        syscall_counter: HashMap::from([(SyscallSelector::LibraryCall, 2)]),
         ..Default::default()
     };
 
@@ -894,6 +1009,114 @@ fn test_deploy(
     assert_eq!(state.get_class_hash_at(contract_address).unwrap(), class_hash);
 }
 
+#[rstest]
+fn test_deploy_collision_policy(
+    #[values(DeployCollisionPolicy::Reject, DeployCollisionPolicy::AllowSameClass)]
+    policy: DeployCollisionPolicy,
+) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let mut state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(policy);
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+
+    let class_hash = test_contract.get_class_hash();
+    let constructor_calldata = vec![stark_felt!(1_u8), stark_felt!(1_u8)];
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector_from_name("test_deploy"),
+        calldata: calldata_for_deploy_test(class_hash, &constructor_calldata, true),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+    let mut deploy = || {
+        let mut context = EntryPointExecutionContext::new_invoke(tx_context.clone(), true).unwrap();
+        entry_point_call.clone().execute(
+            &mut state,
+            &mut ExecutionResources::default(),
+            &mut context,
+        )
+    };
+
+    assert_eq!(deploy().unwrap().inner_calls.len(), 1);
+    let result = deploy();
+    match policy {
+        DeployCollisionPolicy::Reject => {
+            assert!(result.unwrap_err().to_string().contains("is unavailable for deployment."));
+        }
+        // The second deployment is a no-op; the constructor does not run again.
+        DeployCollisionPolicy::AllowSameClass => assert!(result.unwrap().inner_calls.is_empty()),
+    }
+}
+
+fn execute_with_protected_addresses(
+    state: &mut dyn State,
+    entry_point_call: CallEntryPoint,
+    protected_address: ContractAddress,
+) -> String {
+    let mut block_context = BlockContext::create_for_testing();
+    block_context.chain_info.protected_addresses =
+        ProtectedAddresses(vec![protected_address..=protected_address]);
+    let tx_info = TransactionInfo::Deprecated(DeprecatedTransactionInfo::default());
+    let tx_context = Arc::new(TransactionContext::new(block_context, tx_info));
+    let mut context = EntryPointExecutionContext::new_invoke(tx_context, true).unwrap();
+
+    entry_point_call
+        .execute(state, &mut ExecutionResources::default(), &mut context)
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn test_replace_class_of_protected_contract() {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let empty_contract = FeatureContract::Empty(CairoVersion::Cairo1);
+    let mut state = test_state(
+        &ChainInfo::create_for_testing(),
+        BALANCE,
+        &[(test_contract, 1), (empty_contract, 0)],
+    );
+    let contract_address = test_contract.get_instance_address(0);
+    let entry_point_call = CallEntryPoint {
+        calldata: calldata![empty_contract.get_class_hash().0],
+        entry_point_selector: selector_from_name("test_replace_class"),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+
+    let error = execute_with_protected_addresses(&mut state, entry_point_call, contract_address);
+    assert!(error.contains(&format!(
+        "Contract code may not replace the class of protected contract {contract_address:?}."
+    )));
+    assert_eq!(state.get_class_hash_at(contract_address).unwrap(), test_contract.get_class_hash());
+}
+
+#[test]
+fn test_deploy_to_protected_address() {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let mut state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    let class_hash = test_contract.get_class_hash();
+    let constructor_calldata = vec![stark_felt!(1_u8), stark_felt!(1_u8)];
+    let deployed_contract_address = calculate_contract_address(
+        ContractAddressSalt::default(),
+        class_hash,
+        &Calldata(constructor_calldata.clone().into()),
+        test_contract.get_instance_address(0),
+    )
+    .unwrap();
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector_from_name("test_deploy"),
+        calldata: calldata_for_deploy_test(class_hash, &constructor_calldata, true),
+        ..trivial_external_entry_point_new(test_contract)
+    };
+
+    let error =
+        execute_with_protected_addresses(&mut state, entry_point_call, deployed_contract_address);
+    assert!(error.contains(&format!(
+        "Contract code may not deploy a contract at protected address \
+         {deployed_contract_address:?}."
+    )));
+    assert_eq!(state.get_class_hash_at(deployed_contract_address).unwrap(), ClassHash::default());
+}
+
 #[test]
 fn test_out_of_gas() {
     let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
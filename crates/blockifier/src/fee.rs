@@ -1,5 +1,8 @@
 pub mod actual_cost;
 pub mod eth_gas_constants;
 pub mod fee_checks;
+pub mod fee_sharding;
 pub mod fee_utils;
 pub mod gas_usage;
+pub mod pricing_simulation;
+pub mod resource_bounds;
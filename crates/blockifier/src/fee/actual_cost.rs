@@ -2,20 +2,31 @@ use std::iter;
 use std::sync::Arc;
 
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
-use starknet_api::core::ContractAddress;
+use serde::{Deserialize, Serialize};
+use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::data_availability::DataAvailabilityMode;
 use starknet_api::transaction::Fee;
 
 use crate::abi::constants as abi_constants;
-use crate::context::TransactionContext;
-use crate::execution::call_info::CallInfo;
+use crate::context::{BlockContext, TransactionContext};
+use crate::execution::call_info::{CallInfo, MessageL1CostInfo, OrderedEvent};
 use crate::execution::contract_class::ClassInfo;
+use crate::fee::fee_utils::{
+    calculate_l1_gas_by_resource_usage, calculate_tx_gas_vector, extract_vm_resources,
+    get_fee_by_gas_vector,
+};
 use crate::state::cached_state::{CachedState, StateChanges, StateChangesCount};
 use crate::state::state_api::{StateReader, StateResult};
+use crate::transaction::errors::TransactionFeeError;
 use crate::transaction::objects::{
-    GasVector, HasRelatedFeeType, ResourcesMapping, StarknetResources, TransactionExecutionResult,
+    FeeChargeMode, FeeResource, FeeType, GasVector, GasVectorBreakdown, HasRelatedFeeType,
+    ResourceGasUsage, ResourcesMapping, StarknetResources, TransactionExecutionResult,
+    TransactionFeeResult,
 };
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transaction_utils::calculate_tx_resources;
+use crate::utils::u128_from_usize;
+use crate::versioned_constants::{ResourceCost, VersionedConstants};
 
 #[cfg(test)]
 #[path = "actual_cost_test.rs"]
@@ -23,11 +34,261 @@ pub mod test;
 
 // TODO(Gilad): Use everywhere instead of passing the `actual_{fee,resources}` tuple, which often
 // get passed around together.
-#[derive(Default)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ActualCost {
     pub actual_fee: Fee,
     pub da_gas: GasVector,
     pub actual_resources: ResourcesMapping,
+    /// A detailed breakdown of the fee; computed only if `DetailedFeeTrace` is enabled.
+    pub fee_trace: Option<FeeTrace>,
+    #[serde(default)]
+    pub message_resources: MessageResources,
+}
+
+/// The L1<>L2 messaging component of the resources of a transaction.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MessageResources {
+    /// The payload lengths of the sent L2-to-L1 messages, by call and then by order.
+    pub l2_to_l1_payload_lengths: Vec<usize>,
+    /// The number of felts the messages occupy in the OS output, including the consumed L1-to-L2
+    /// message.
+    pub message_segment_length: usize,
+    /// The nonce of the consumed L1-to-L2 message; set for L1 handler transactions.
+    pub consumed_l1_to_l2_nonce: Option<Nonce>,
+    /// The gas attributable to the messages; included in the gas the transaction is charged for.
+    pub gas: GasVector,
+}
+
+impl MessageResources {
+    pub fn new(
+        starknet_resources: &StarknetResources,
+        consumed_l1_to_l2_nonce: Option<Nonce>,
+    ) -> Self {
+        let MessageL1CostInfo { l2_to_l1_payload_lengths, message_segment_length } =
+            starknet_resources.message_cost_info.clone();
+        Self {
+            l2_to_l1_payload_lengths,
+            message_segment_length,
+            consumed_l1_to_l2_nonce,
+            gas: starknet_resources.get_messages_cost(),
+        }
+    }
+}
+
+/// The cost of a transaction, estimated by executing it without checking or charging its fee.
+#[derive(Debug)]
+pub struct FeeEstimate {
+    pub actual_cost: ActualCost,
+    /// The gas charged for the transaction.
+    pub gas_vector: GasVector,
+    pub gas_breakdown: GasVectorBreakdown,
+    /// Set if the execution was reverted; the cost covers the reverted execution.
+    pub revert_error: Option<String>,
+}
+
+/// Enables the computation of a detailed fee trace for each transaction, when inserted into the
+/// block context extensions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DetailedFeeTrace;
+
+/// Determines what a reverted transaction is charged for, and which of its effects are applied;
+/// part of the versioned constants. Defaults to the Starknet policy, under which everything is
+/// charged and applied.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct RevertPolicy {
+    /// Whether the execution steps consumed up to the revert are charged.
+    pub charge_reverted_steps: bool,
+    /// Whether the state changes kept despite the revert (i.e., those of the validation and of the
+    /// nonce bump) are charged.
+    pub charge_state_changes: bool,
+    /// Whether the sender nonce is bumped; if not, the transaction may be resubmitted, and the
+    /// undone nonce bump is not charged.
+    /// Note: charging a fee without bumping the nonce lets anyone resubmit the signed transaction,
+    /// charging its sender again and again; only do so if resubmissions are otherwise prevented
+    /// (e.g., by deduplicating transaction hashes). Rejected by strict parsing.
+    pub bump_nonce: bool,
+    /// Whether any fee is charged.
+    pub charge_fee: bool,
+}
+
+impl Default for RevertPolicy {
+    fn default() -> Self {
+        Self {
+            charge_reverted_steps: true,
+            charge_state_changes: true,
+            bump_nonce: true,
+            charge_fee: true,
+        }
+    }
+}
+
+impl RevertPolicy {
+    /// Charges no fee for reverted transactions; their sender nonce is still bumped.
+    pub fn waive_fee() -> Self {
+        Self {
+            charge_reverted_steps: false,
+            charge_state_changes: false,
+            bump_nonce: true,
+            charge_fee: false,
+        }
+    }
+
+    /// Returns the fee to charge a reverted transaction, given the fee computed for it.
+    pub fn charged_fee(&self, fee: Fee) -> Fee {
+        if self.charge_fee { fee } else { Fee(0) }
+    }
+}
+
+/// The L1 gas charged for a single emitted event.
+/// Amounts are exact; the transaction's total L1 gas is rounded down only after summation.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventCostTrace {
+    pub emitter_address: ContractAddress,
+    /// The order of the event within its emitting call.
+    pub order: usize,
+    pub n_keys: usize,
+    pub data_length: usize,
+    /// The factor by which keys are priced relative to data felts.
+    pub event_key_factor: ResourceCost,
+    pub keys_gas: ResourceCost,
+    pub data_gas: ResourceCost,
+}
+
+impl EventCostTrace {
+    pub fn total_gas(&self) -> ResourceCost {
+        self.keys_gas + self.data_gas
+    }
+}
+
+/// The contribution of a single resource to the fee of a transaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceFeeContribution {
+    #[serde(flatten)]
+    pub usage: ResourceGasUsage,
+    /// The fee for the resource's gas. Of the Cairo resources, only the heaviest is charged; the
+    /// fee of the others is zero.
+    pub fee: Fee,
+}
+
+/// The fee of a transaction, broken down by resource, priced at the block's gas prices.
+/// Amounts are rounded per resource, so they may add up to slightly less than the totals.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FeeBreakdown {
+    /// The steps and each builtin used, followed by the starknet resources.
+    pub resources: Vec<ResourceFeeContribution>,
+    pub total_gas: GasVector,
+    /// The fee of the transaction, whether or not it is charged.
+    pub total_fee: Fee,
+}
+
+impl FeeBreakdown {
+    pub fn new(
+        starknet_resources: &StarknetResources,
+        actual_resources: &ResourcesMapping,
+        block_context: &BlockContext,
+        fee_type: &FeeType,
+    ) -> TransactionFeeResult<Self> {
+        let versioned_constants = &block_context.versioned_constants;
+        let vm_resources = extract_vm_resources(actual_resources);
+        let mut builtins: Vec<_> = vm_resources.builtin_instance_counter.into_iter().collect();
+        builtins.sort();
+
+        let n_steps = (abi_constants::N_STEPS_RESOURCE.to_string(), vm_resources.n_steps);
+        let vm_usages = iter::once(n_steps)
+            .chain(builtins)
+            .map(|(name, count)| Self::vm_resource_gas_usage(versioned_constants, name, count))
+            .collect::<TransactionFeeResult<Vec<_>>>()?;
+        // Only the heaviest Cairo resource is charged; the first one, on a tie.
+        let max_vm_l1_gas = vm_usages.iter().map(|usage| usage.gas.l1_gas).max();
+        let charged_vm_resource =
+            vm_usages.iter().position(|usage| Some(usage.gas.l1_gas) == max_vm_l1_gas);
+
+        let block_info = &block_context.block_info;
+        let fee_of = |gas| get_fee_by_gas_vector(block_info, gas, fee_type);
+        let resources = vm_usages
+            .into_iter()
+            .enumerate()
+            .map(|(index, usage)| {
+                let fee =
+                    if Some(index) == charged_vm_resource { fee_of(usage.gas) } else { Fee(0) };
+                ResourceFeeContribution { usage, fee }
+            })
+            .chain(
+                starknet_resources
+                    .gas_usage_breakdown(versioned_constants, block_info.use_kzg_da)
+                    .into_iter()
+                    .map(|usage| ResourceFeeContribution { fee: fee_of(usage.gas), usage }),
+            )
+            .collect();
+
+        let total_gas = calculate_tx_gas_vector(actual_resources, versioned_constants)?;
+        Ok(Self { resources, total_gas, total_fee: fee_of(total_gas) })
+    }
+
+    fn vm_resource_gas_usage(
+        versioned_constants: &VersionedConstants,
+        name: String,
+        count: usize,
+    ) -> TransactionFeeResult<ResourceGasUsage> {
+        let weight = match versioned_constants.vm_resource_fee_cost().get(&name) {
+            Some(weight) => *weight,
+            // Steps are free if they have no fee cost, as in `calculate_l1_gas_per_vm_resource`.
+            None if name == abi_constants::N_STEPS_RESOURCE => ResourceCost::default(),
+            None => return Err(TransactionFeeError::CairoResourcesNotContainedInFeeCosts),
+        };
+        let l1_gas = calculate_l1_gas_by_resource_usage(versioned_constants, &name, count)
+            .unwrap_or_default();
+        let resource = match name.as_str() {
+            abi_constants::N_STEPS_RESOURCE => FeeResource::Steps,
+            _ => FeeResource::Builtin(name),
+        };
+
+        Ok(ResourceGasUsage { resource, count, weight, gas: GasVector::from_l1_gas(l1_gas) })
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FeeTrace {
+    pub events: Vec<EventCostTrace>,
+    /// Set once the transaction resources are known.
+    #[serde(default)]
+    pub fee_breakdown: FeeBreakdown,
+}
+
+impl FeeTrace {
+    /// Traces the cost of the events emitted by the given calls, as priced in
+    /// `StarknetResources::get_events_cost`.
+    pub fn new<'a>(
+        call_infos: impl Iterator<Item = &'a CallInfo>,
+        versioned_constants: &VersionedConstants,
+    ) -> Self {
+        let l2_resource_gas_costs = &versioned_constants.l2_resource_gas_costs;
+        let (event_key_factor, data_word_cost) =
+            (l2_resource_gas_costs.event_key_factor, l2_resource_gas_costs.gas_per_data_felt);
+        let events = call_infos
+            .flat_map(|call_info| {
+                call_info.execution.events.iter().map(|OrderedEvent { order, event }| {
+                    let (n_keys, data_length) = (event.keys.len(), event.data.0.len());
+                    EventCostTrace {
+                        emitter_address: call_info.call.storage_address,
+                        order: *order,
+                        n_keys,
+                        data_length,
+                        event_key_factor,
+                        keys_gas: data_word_cost * event_key_factor * u128_from_usize(n_keys),
+                        data_gas: data_word_cost * u128_from_usize(data_length),
+                    }
+                })
+            })
+            .collect();
+
+        Self { events, fee_breakdown: FeeBreakdown::default() }
+    }
+
+    pub fn total_events_gas(&self) -> ResourceCost {
+        self.events.iter().map(EventCostTrace::total_gas).sum()
+    }
 }
 
 impl ActualCost {
@@ -54,10 +315,14 @@ pub struct ActualCostBuilder<'a> {
     pub tx_type: TransactionType,
     starknet_resources: StarknetResources,
     validate_call_info: Option<&'a CallInfo>,
+    paymaster_call_info: Option<&'a CallInfo>,
     execute_call_info: Option<&'a CallInfo>,
     state_changes: StateChanges,
-    sender_address: Option<ContractAddress>,
+    // The sender and the fee payer of account transactions.
+    sender_and_fee_payer: Option<(ContractAddress, ContractAddress)>,
+    consumed_l1_to_l2_nonce: Option<Nonce>,
     n_reverted_steps: usize,
+    always_compute_fee: bool,
 }
 
 impl<'a> ActualCostBuilder<'a> {
@@ -77,18 +342,24 @@ impl<'a> ActualCostBuilder<'a> {
                 None,
                 iter::empty(),
             )?,
-            sender_address: Some(tx_context.tx_info.sender_address()),
+            sender_and_fee_payer: Some((
+                tx_context.tx_info.sender_address(),
+                tx_context.fee_payer_address()?,
+            )),
             tx_context,
             tx_type,
             validate_call_info: None,
+            paymaster_call_info: None,
             execute_call_info: None,
             state_changes: StateChanges::default(),
+            consumed_l1_to_l2_nonce: None,
             n_reverted_steps: 0,
+            always_compute_fee: false,
         })
     }
 
     pub fn without_sender_address(mut self) -> Self {
-        self.sender_address = None;
+        self.sender_and_fee_payer = None;
         self
     }
 
@@ -110,6 +381,11 @@ impl<'a> ActualCostBuilder<'a> {
         self
     }
 
+    pub fn with_paymaster_call_info(mut self, paymaster_call_info: &'a Option<CallInfo>) -> Self {
+        self.paymaster_call_info = paymaster_call_info.as_ref();
+        self
+    }
+
     pub fn with_execute_call_info(mut self, execute_call_info: &'a Option<CallInfo>) -> Self {
         self.execute_call_info = execute_call_info.as_ref();
         self
@@ -134,8 +410,30 @@ impl<'a> ActualCostBuilder<'a> {
         self
     }
 
+    pub fn with_consumed_l1_to_l2_nonce(mut self, nonce: Nonce) -> Self {
+        self.consumed_l1_to_l2_nonce = Some(nonce);
+        self
+    }
+
+    /// Charges the execution steps consumed up to a revert, unless waived by the `RevertPolicy`.
     pub fn with_reverted_steps(mut self, n_reverted_steps: usize) -> Self {
-        self.n_reverted_steps = n_reverted_steps;
+        if self.tx_context.block_context.versioned_constants.revert_policy.charge_reverted_steps {
+            self.n_reverted_steps = n_reverted_steps;
+        }
+        self
+    }
+
+    /// Excludes the nonce bump of the sender from the state changes (e.g., when it is undone on
+    /// revert, by the `RevertPolicy`).
+    pub fn without_sender_nonce_update(mut self) -> Self {
+        if let Some((sender_address, _)) = self.sender_and_fee_payer {
+            self.state_changes.nonce_updates.remove(&sender_address);
+        }
+        self
+    }
+
+    pub fn with_fee_charge_mode(mut self, fee_charge_mode: FeeChargeMode) -> Self {
+        self.always_compute_fee = fee_charge_mode.always_compute_fee();
         self
     }
 
@@ -151,20 +449,40 @@ impl<'a> ActualCostBuilder<'a> {
         execution_resources: &ExecutionResources,
     ) -> TransactionExecutionResult<(ActualCost, ResourcesMapping)> {
         let use_kzg_da = self.use_kzg_da();
-        self.starknet_resources.state_changes_count = self.state_changes.count_for_fee_charge(
-            self.sender_address,
-            self.tx_context
-                .block_context
-                .chain_info
-                .fee_token_address(&self.tx_context.tx_info.fee_type()),
-        );
+        let block_context = &self.tx_context.block_context;
+        let tx_info = &self.tx_context.tx_info;
+        let (nonce_data_availability_mode, fee_data_availability_mode) =
+            if block_context.versioned_constants.enable_l2_data_availability {
+                (tx_info.nonce_data_availability_mode(), tx_info.fee_data_availability_mode())
+            } else {
+                (DataAvailabilityMode::L1, DataAvailabilityMode::L1)
+            };
+        self.starknet_resources.state_changes_count =
+            self.state_changes.count_for_fee_charge_by_payer(
+                self.sender_and_fee_payer,
+                block_context.chain_info.fee_token_address(&tx_info.fee_type()),
+                nonce_data_availability_mode,
+                fee_data_availability_mode,
+            );
         // TODO(Dafna, 1/6/2024): Compute the DA size and pass it instead of state_changes_count.
         let da_gas = self.starknet_resources.get_state_changes_cost(use_kzg_da);
-        let non_optional_call_infos =
-            self.validate_call_info.into_iter().chain(self.execute_call_info);
+        let non_optional_call_infos = self
+            .validate_call_info
+            .into_iter()
+            .chain(self.paymaster_call_info)
+            .chain(self.execute_call_info);
+
+        let mut fee_trace = self.tx_context.extensions.contains::<DetailedFeeTrace>().then(|| {
+            FeeTrace::new(
+                non_optional_call_infos.clone(),
+                &self.tx_context.block_context.versioned_constants,
+            )
+        });
 
         // Set the events and messages resources from the transaction's call infos.
         self.starknet_resources.set_events_and_messages_resources(non_optional_call_infos)?;
+        let message_resources =
+            MessageResources::new(&self.starknet_resources, self.consumed_l1_to_l2_nonce);
 
         let mut actual_resources = calculate_tx_resources(
             &self.tx_context.block_context.versioned_constants,
@@ -181,8 +499,17 @@ impl<'a> ActualCostBuilder<'a> {
         *actual_resources.0.get_mut(&abi_constants::N_STEPS_RESOURCE.to_string()).unwrap() +=
             self.n_reverted_steps;
 
-        let tx_info = &self.tx_context.tx_info;
-        let actual_fee = if tx_info.enforce_fee()?
+        if let Some(fee_trace) = &mut fee_trace {
+            fee_trace.fee_breakdown = FeeBreakdown::new(
+                &self.starknet_resources,
+                &actual_resources,
+                &self.tx_context.block_context,
+                &tx_info.fee_type(),
+            )?;
+        }
+
+        let actual_fee = if self.always_compute_fee
+            || tx_info.enforce_fee()?
         // L1 handler transactions are not charged an L2 fee but it is compared to the L1 fee.
             || self.tx_type == TransactionType::L1Handler
         {
@@ -191,6 +518,9 @@ impl<'a> ActualCostBuilder<'a> {
             Fee(0)
         };
 
-        Ok((ActualCost { actual_fee, da_gas, actual_resources }, bouncer_resources))
+        Ok((
+            ActualCost { actual_fee, da_gas, actual_resources, fee_trace, message_resources },
+            bouncer_resources,
+        ))
     }
 }
@@ -1,12 +1,24 @@
+use std::collections::HashMap;
+
+use cairo_vm::vm::runners::builtin_runner::{RANGE_CHECK_BUILTIN_NAME, SEGMENT_ARENA_BUILTIN_NAME};
 use rstest::{fixture, rstest};
 use starknet_api::core::Nonce;
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
-use starknet_api::transaction::{Fee, L2ToL1Payload, TransactionVersion};
+use starknet_api::transaction::{
+    EventContent, EventData, EventKey, Fee, L2ToL1Payload, TransactionVersion,
+};
 
+use crate::abi::constants::{BLOB_GAS_USAGE, L1_GAS_USAGE, N_STEPS_RESOURCE};
 use crate::context::BlockContext;
-use crate::execution::call_info::{CallExecution, CallInfo, MessageToL1, OrderedL2ToL1Message};
+use crate::execution::call_info::{
+    CallExecution, CallInfo, MessageToL1, OrderedEvent, OrderedL2ToL1Message,
+};
+use crate::fee::actual_cost::{ActualCost, FeeBreakdown, FeeTrace, MessageResources};
 use crate::fee::eth_gas_constants;
+use crate::fee::fee_utils::{
+    calculate_l1_gas_by_resource_usage, calculate_tx_gas_vector, get_fee_by_gas_vector,
+};
 use crate::fee::gas_usage::{
     get_consumed_message_to_l2_emissions_cost, get_log_message_to_l1_emissions_cost,
     get_message_segment_length,
@@ -17,7 +29,9 @@ use crate::test_utils::contracts::FeatureContract;
 use crate::test_utils::initial_test_state::test_state;
 use crate::test_utils::{create_calldata, create_trivial_calldata, CairoVersion, BALANCE, MAX_FEE};
 use crate::transaction::constants;
-use crate::transaction::objects::{GasVector, HasRelatedFeeType, StarknetResources};
+use crate::transaction::objects::{
+    FeeResource, FeeType, GasVector, HasRelatedFeeType, ResourcesMapping, StarknetResources,
+};
 use crate::transaction::test_utils::{account_invoke_tx, calculate_class_info_for_testing};
 use crate::transaction::transactions::ExecutableTransaction;
 use crate::utils::{u128_from_usize, usize_from_u128};
@@ -385,3 +399,171 @@ fn test_calculate_tx_gas_usage(#[values(false, true)] use_kzg_da: bool) {
         l1_blob_gas_usage
     );
 }
+
+#[rstest]
+fn test_fee_trace_events(versioned_constants: &VersionedConstants) {
+    let event = |order: usize, n_keys: u8, data_length: u8| OrderedEvent {
+        order,
+        event: EventContent {
+            keys: (0..n_keys).map(|key| EventKey(stark_felt!(key))).collect(),
+            data: EventData((0..data_length).map(|felt| stark_felt!(felt)).collect()),
+        },
+    };
+    let call_info = CallInfo {
+        execution: CallExecution {
+            events: vec![event(0, 1, 3), event(1, 2, 0)],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let fee_trace = FeeTrace::new(std::iter::once(&call_info), versioned_constants);
+    let l2_resource_gas_costs = &versioned_constants.l2_resource_gas_costs;
+    let (event_key_factor, data_word_cost) =
+        (l2_resource_gas_costs.event_key_factor, l2_resource_gas_costs.gas_per_data_felt);
+    let first_event = &fee_trace.events[0];
+    assert_eq!((first_event.n_keys, first_event.data_length), (1, 3));
+    assert_eq!(first_event.keys_gas, data_word_cost * event_key_factor);
+    assert_eq!(first_event.data_gas, data_word_cost * 3);
+    assert_eq!(fee_trace.events[1].data_gas, 0.into());
+
+    // The trace adds up to the charged events cost.
+    let mut starknet_resources = StarknetResources::default();
+    starknet_resources.set_events_and_messages_resources(std::iter::once(&call_info)).unwrap();
+    assert_eq!(
+        starknet_resources.get_events_cost(versioned_constants),
+        GasVector::from_l1_gas(fee_trace.total_events_gas().to_integer())
+    );
+}
+
+#[rstest]
+fn test_fee_breakdown(versioned_constants: &VersionedConstants) {
+    let block_context = BlockContext::create_for_testing();
+    let starknet_resources = StarknetResources::new(
+        3,
+        2,
+        None,
+        StateChangesCount { n_storage_updates: 2, n_modified_contracts: 1, ..Default::default() },
+        None,
+        std::iter::empty(),
+    )
+    .unwrap();
+    let use_kzg_da = block_context.block_info.use_kzg_da;
+    let starknet_gas = starknet_resources.to_gas_vector(versioned_constants, use_kzg_da);
+    let actual_resources = ResourcesMapping(HashMap::from([
+        (L1_GAS_USAGE.to_string(), usize_from_u128(starknet_gas.l1_gas).unwrap()),
+        (BLOB_GAS_USAGE.to_string(), usize_from_u128(starknet_gas.l1_data_gas).unwrap()),
+        (N_STEPS_RESOURCE.to_string(), 1000),
+        (RANGE_CHECK_BUILTIN_NAME.to_string(), 10),
+        (SEGMENT_ARENA_BUILTIN_NAME.to_string(), 2),
+    ]));
+
+    let fee_breakdown =
+        FeeBreakdown::new(&starknet_resources, &actual_resources, &block_context, &FeeType::Eth)
+            .unwrap();
+    let fee_of = |gas| get_fee_by_gas_vector(&block_context.block_info, gas, &FeeType::Eth);
+    let [steps, range_check, calldata, ..] = fee_breakdown.resources.as_slice() else {
+        panic!("Unexpected breakdown: {fee_breakdown:?}.");
+    };
+
+    // The segment arena builtin is charged as steps.
+    assert_eq!((&steps.usage.resource, steps.usage.count), (&FeeResource::Steps, 1000 + 2 * 10));
+    assert_eq!(
+        range_check.usage.resource,
+        FeeResource::Builtin(RANGE_CHECK_BUILTIN_NAME.to_string())
+    );
+    assert_eq!(
+        range_check.usage.gas.l1_gas,
+        calculate_l1_gas_by_resource_usage(versioned_constants, RANGE_CHECK_BUILTIN_NAME, 10)
+            .unwrap()
+    );
+    // Only the heaviest Cairo resource is charged.
+    assert_eq!(steps.fee, fee_of(steps.usage.gas));
+    assert_eq!(range_check.fee, Fee(0));
+
+    assert_eq!(
+        (&calldata.usage.resource, calldata.usage.count, calldata.usage.weight),
+        (
+            &FeeResource::CalldataAndSignature,
+            5,
+            versioned_constants.l2_resource_gas_costs.gas_per_data_felt
+        )
+    );
+    assert_eq!(
+        calldata.usage.gas,
+        starknet_resources.get_calldata_and_signature_cost(versioned_constants)
+    );
+    let state_diff = fee_breakdown
+        .resources
+        .iter()
+        .find(|contribution| contribution.usage.resource == FeeResource::StateDiff)
+        .unwrap();
+    // Two felts for the modified contract, and two for each storage update.
+    assert_eq!(state_diff.usage.count, 2 + 2 * 2);
+    assert_eq!(state_diff.usage.gas, starknet_resources.get_state_changes_cost(use_kzg_da));
+
+    // The contributions add up to the totals.
+    let total_gas = calculate_tx_gas_vector(&actual_resources, versioned_constants).unwrap();
+    assert_eq!(fee_breakdown.total_gas, total_gas);
+    assert_eq!(fee_breakdown.total_fee, fee_of(total_gas));
+    let total_contributions: u128 =
+        fee_breakdown.resources.iter().map(|contribution| contribution.fee.0).sum();
+    assert!(total_contributions <= fee_breakdown.total_fee.0);
+}
+
+#[rstest]
+fn test_actual_cost_serde(versioned_constants: &VersionedConstants) {
+    let event = OrderedEvent {
+        order: 0,
+        event: EventContent {
+            keys: vec![EventKey(stark_felt!(1_u8))],
+            data: EventData(vec![stark_felt!(2_u8)]),
+        },
+    };
+    let call_info = CallInfo {
+        execution: CallExecution { events: vec![event], ..Default::default() },
+        ..Default::default()
+    };
+    let actual_cost = ActualCost {
+        actual_fee: Fee(1000),
+        da_gas: GasVector { l1_gas: 10, l1_data_gas: 20 },
+        actual_resources: ResourcesMapping(HashMap::from([(L1_GAS_USAGE.to_string(), 30)])),
+        fee_trace: Some(FeeTrace::new(std::iter::once(&call_info), versioned_constants)),
+        message_resources: MessageResources {
+            l2_to_l1_payload_lengths: vec![2],
+            message_segment_length: 7,
+            consumed_l1_to_l2_nonce: Some(Nonce(stark_felt!(3_u8))),
+            gas: GasVector::from_l1_gas(100),
+        },
+    };
+
+    let json = serde_json::to_value(&actual_cost).unwrap();
+    assert_eq!(json["da_gas"], serde_json::json!({ "l1_gas": 10, "l1_data_gas": 20 }));
+    assert_eq!(serde_json::from_value::<ActualCost>(json).unwrap(), actual_cost);
+}
+
+#[rstest]
+fn test_code_cost_per_segment(versioned_constants: &VersionedConstants) {
+    let class_info = calculate_class_info_for_testing(
+        FeatureContract::TestContract(CairoVersion::Cairo1).get_class(),
+    );
+    let starknet_resources = StarknetResources::new(
+        0,
+        0,
+        Some(&class_info),
+        StateChangesCount::default(),
+        None,
+        std::iter::empty(),
+    )
+    .unwrap();
+    let flat_code_cost = starknet_resources.get_code_cost(versioned_constants);
+
+    // Each bytecode segment is charged on top of the code bytes.
+    let mut segment_charging_constants = versioned_constants.clone();
+    segment_charging_constants.l2_resource_gas_costs.gas_per_code_segment = 100.into();
+    let n_segments = u128_from_usize(class_info.size_metrics().n_bytecode_segments());
+    assert_eq!(
+        starknet_resources.get_code_cost(&segment_charging_constants),
+        flat_code_cost + GasVector::from_l1_gas(100 * n_segments)
+    );
+}
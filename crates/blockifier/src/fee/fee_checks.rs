@@ -1,3 +1,5 @@
+use std::fmt;
+
 use starknet_api::hash::StarkFelt;
 use starknet_api::transaction::Fee;
 use thiserror::Error;
@@ -16,9 +18,15 @@ use crate::transaction::objects::{
 
 #[derive(Clone, Copy, Debug, Error)]
 pub enum FeeCheckError {
-    #[error("Insufficient max L1 gas: max amount: {max_amount}, actual used: {actual_amount}.")]
+    #[error(
+        "Insufficient max L1 gas: max amount: {max_amount}, actual used: {actual_amount}. {}",
+        BoundsReport::new(BoundDimension::L1Gas, *.max_amount, *.actual_amount)
+    )]
     MaxL1GasAmountExceeded { max_amount: u128, actual_amount: u128 },
-    #[error("Insufficient max fee: max fee: {max_fee:?}, actual fee: {actual_fee:?}")]
+    #[error(
+        "Insufficient max fee: max fee: {max_fee:?}, actual fee: {actual_fee:?}. {}",
+        BoundsReport::new(BoundDimension::MaxFee, .max_fee.0, .actual_fee.0)
+    )]
     MaxFeeExceeded { max_fee: Fee, actual_fee: Fee },
     #[error(
         "Insufficient fee token balance. Fee: {fee:?}, balance: low/high \
@@ -27,6 +35,67 @@ pub enum FeeCheckError {
     InsufficientFeeTokenBalance { fee: Fee, balance_low: StarkFelt, balance_high: StarkFelt },
 }
 
+impl FeeCheckError {
+    /// Returns the comparison between the exceeded bound and the required amount, for resource
+    /// overdraft errors; allows senders to retry with corrected bounds.
+    pub fn bounds_report(&self) -> Option<BoundsReport> {
+        match *self {
+            Self::MaxL1GasAmountExceeded { max_amount, actual_amount } => {
+                Some(BoundsReport::new(BoundDimension::L1Gas, max_amount, actual_amount))
+            }
+            Self::MaxFeeExceeded { max_fee, actual_fee } => {
+                Some(BoundsReport::new(BoundDimension::MaxFee, max_fee.0, actual_fee.0))
+            }
+            Self::InsufficientFeeTokenBalance { .. } => None,
+        }
+    }
+}
+
+/// A transaction bound declared by the sender.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BoundDimension {
+    /// The max fee of deprecated transactions.
+    MaxFee,
+    /// The max L1 gas amount in the resource bounds.
+    L1Gas,
+}
+
+/// A comparison between a bound declared by the sender and the amount required by the execution.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BoundsReport {
+    pub dimension: BoundDimension,
+    pub declared: u128,
+    pub required: u128,
+    /// The minimal percentage by which to increase the declared bound to cover the
+    /// required amount; `None` if the declared bound is zero.
+    pub suggested_bump_percent: Option<u128>,
+}
+
+impl BoundsReport {
+    pub fn new(dimension: BoundDimension, declared: u128, required: u128) -> Self {
+        let suggested_bump_percent = match declared {
+            0 => None,
+            _ => {
+                // Rounded up, so that the bumped bound covers the required amount.
+                let missing_percent = required.saturating_sub(declared).saturating_mul(100);
+                Some(missing_percent.saturating_add(declared - 1) / declared)
+            }
+        };
+        Self { dimension, declared, required, suggested_bump_percent }
+    }
+}
+
+impl fmt::Display for BoundsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { dimension, declared, required, suggested_bump_percent } = self;
+        write!(f, "Bound {dimension:?}: declared {declared}, required {required}")?;
+        match suggested_bump_percent {
+            Some(bump_percent) => write!(f, ", suggested bump: {bump_percent}%."),
+            None => write!(f, "."),
+        }
+    }
+}
+
 /// This struct holds the result of fee checks: recommended fee to charge (useful in post-execution
 /// revert flow) and an error if the check failed.
 struct FeeCheckReport {
@@ -91,7 +160,7 @@ impl FeeCheckReport {
         actual_cost: &ActualCost,
     ) -> TransactionExecutionResult<()> {
         let ActualCost { actual_fee, actual_resources, .. } = actual_cost;
-        let TransactionContext { tx_info, block_context } = tx_context;
+        let TransactionContext { tx_info, block_context, .. } = tx_context;
 
         // First, compare the actual resources used against the upper bound(s) defined by the
         // sender.
@@ -131,7 +200,7 @@ impl FeeCheckReport {
         Ok(())
     }
 
-    /// If the actual cost exceeds the sender's balance, returns a fee check error.
+    /// If the actual cost exceeds the fee payer's balance, returns a fee check error.
     fn check_can_pay_fee<S: StateReader>(
         state: &mut S,
         tx_context: &TransactionContext,
@@ -0,0 +1,171 @@
+use cairo_felt::Felt252;
+use indexmap::IndexMap;
+use num_bigint::BigUint;
+use num_traits::{CheckedSub, ToPrimitive};
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Fee;
+
+use crate::abi::abi_utils::{get_fee_token_var_address, get_storage_var_address};
+use crate::abi::sierra_types::next_storage_key;
+use crate::blockifier::block::EndOfBlockHook;
+use crate::context::BlockContext;
+use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use crate::state::errors::StateError;
+use crate::state::state_api::State;
+use crate::transaction::errors::TransactionFeeError;
+use crate::transaction::objects::{TransactionExecutionResult, TransactionFeeResult};
+
+#[cfg(test)]
+#[path = "fee_sharding_test.rs"]
+pub mod test;
+
+/// An execution extension redirecting fee transfers to an accumulator address, instead of the
+/// sequencer address. This removes the write contention on the sequencer fee-token balance
+/// between transactions executed in parallel. The executor records the fees charged to
+/// accumulators (see `AccruedFees`), and moves them to the fee recipients of the block at its end
+/// (see `ConsolidateFees`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeeAccumulator {
+    pub address: ContractAddress,
+}
+
+impl FeeAccumulator {
+    pub fn new(slot: usize) -> Self {
+        Self { address: fee_accumulator_address(slot) }
+    }
+
+    /// Returns the accumulator charged the fees of the transactions executed under the given block
+    /// context, if any. A block splitting its fees between several recipients charges them to the
+    /// first accumulator by default, so that each transaction makes a single fee transfer.
+    pub fn for_block(block_context: &BlockContext) -> Option<Self> {
+        match block_context.extensions().get::<FeeAccumulator>() {
+            Some(accumulator) => Some(*accumulator),
+            None => block_context.block_info.fee_recipients.as_ref().map(|_| Self::new(0)),
+        }
+    }
+}
+
+/// Returns the (deterministic) address holding the fees accumulated by the given slot.
+/// The address is not controlled by any contract; tokens transferred to it by others are not
+/// consolidated, as only the fees recorded in `AccruedFees` are moved out of it.
+pub fn fee_accumulator_address(slot: usize) -> ContractAddress {
+    let slot = StarkFelt::from(u64::try_from(slot).expect("Slot index should fit in u64."));
+    ContractAddress(get_storage_var_address("fee_accumulator", &[slot]).0)
+}
+
+/// The fees charged to fee accumulators during a block, by fee token and accumulator.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AccruedFees(IndexMap<(ContractAddress, ContractAddress), BigUint>);
+
+impl AccruedFees {
+    /// Records a fee, in the given fee token, charged to the given accumulator.
+    pub fn add(
+        &mut self,
+        fee_token_address: ContractAddress,
+        accumulator: FeeAccumulator,
+        fee: Fee,
+    ) {
+        *self.0.entry((fee_token_address, accumulator.address)).or_default() += fee.0;
+    }
+
+    /// Records the fees accrued by another part of the block (e.g., by another worker).
+    pub fn merge(&mut self, other: AccruedFees) {
+        for (key, amount) in other.0 {
+            *self.0.entry(key).or_default() += amount;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// An end-of-block hook moving the given fees from their accumulators to the fee recipients of the
+/// block (by default, the sequencer); applied by the executor before any other hook.
+#[derive(Clone, Debug, Default)]
+pub struct ConsolidateFees(pub AccruedFees);
+
+impl EndOfBlockHook for ConsolidateFees {
+    fn apply(
+        &self,
+        state: &mut dyn State,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<()> {
+        Ok(consolidate_fee_accumulators(state, block_context, &self.0)?)
+    }
+}
+
+/// Moves the given accrued fees from their accumulators to the fee recipients of the block (by
+/// default, the sequencer). Other balances of the accumulators are left untouched.
+pub fn consolidate_fee_accumulators(
+    state: &mut dyn State,
+    block_context: &BlockContext,
+    accrued_fees: &AccruedFees,
+) -> TransactionFeeResult<()> {
+    let mut totals: IndexMap<ContractAddress, BigUint> = IndexMap::new();
+    for (&(fee_token_address, accumulator_address), accrued) in &accrued_fees.0 {
+        let balance = read_balance(state, fee_token_address, accumulator_address)?;
+        let insufficient_balance_error = TransactionFeeError::InsufficientAccumulatorBalance {
+            accumulator_address,
+            fee_token_address,
+        };
+        let remaining_balance = balance.checked_sub(accrued).ok_or(insufficient_balance_error)?;
+        write_balance(state, fee_token_address, accumulator_address, &remaining_balance)?;
+        *totals.entry(fee_token_address).or_default() += accrued;
+    }
+
+    let block_info = &block_context.block_info;
+    for (fee_token_address, total_accrued) in totals {
+        let overflow_error = TransactionFeeError::FeeAccumulationOverflow { fee_token_address };
+        let credits = match &block_info.fee_recipients {
+            None => vec![(block_info.sequencer_address, total_accrued)],
+            Some(fee_recipients) => {
+                // Accrued fees are split like any other fee, and thus must fit in a fee.
+                let total_accrued = total_accrued.to_u128().ok_or(overflow_error)?;
+                fee_recipients
+                    .split(Fee(total_accrued))
+                    .into_iter()
+                    .map(|(recipient, amount)| (recipient, BigUint::from(amount.0)))
+                    .collect()
+            }
+        };
+        for (recipient, amount) in credits {
+            let balance = read_balance(state, fee_token_address, recipient)? + amount;
+            if balance.bits() > 256 {
+                return Err(TransactionFeeError::FeeAccumulationOverflow { fee_token_address });
+            }
+            write_balance(state, fee_token_address, recipient, &balance)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_balance(
+    state: &mut dyn State,
+    fee_token_address: ContractAddress,
+    address: ContractAddress,
+) -> TransactionFeeResult<BigUint> {
+    let (low, high) = state.get_fee_token_balance(address, fee_token_address)?;
+    Ok((stark_felt_to_felt(high).to_biguint() << 128) + stark_felt_to_felt(low).to_biguint())
+}
+
+fn write_balance(
+    state: &mut dyn State,
+    fee_token_address: ContractAddress,
+    address: ContractAddress,
+    balance: &BigUint,
+) -> TransactionFeeResult<()> {
+    let to_stark_felt = |value: BigUint| felt_to_stark_felt(&Felt252::from(value));
+    let low_key = get_fee_token_var_address(address);
+    let high_key = next_storage_key(&low_key).map_err(StateError::from)?;
+    state.set_storage_at(
+        fee_token_address,
+        low_key,
+        to_stark_felt(balance & BigUint::from(u128::MAX)),
+    )?;
+    state.set_storage_at(fee_token_address, high_key, to_stark_felt(balance >> 128))?;
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::Fee;
+
+use crate::abi::abi_utils::get_fee_token_var_address;
+use crate::abi::sierra_types::next_storage_key;
+use crate::blockifier::block::EndOfBlockHook;
+use crate::context::BlockContext;
+use crate::fee::fee_sharding::{
+    consolidate_fee_accumulators, fee_accumulator_address, AccruedFees, ConsolidateFees,
+    FeeAccumulator,
+};
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::{State, StateReader};
+use crate::test_utils::dict_state_reader::DictStateReader;
+use crate::transaction::errors::TransactionFeeError;
+use crate::transaction::objects::FeeType;
+
+#[test]
+fn test_fee_accumulator_address() {
+    assert_eq!(fee_accumulator_address(0), fee_accumulator_address(0));
+    assert_ne!(fee_accumulator_address(0), fee_accumulator_address(1));
+    assert_eq!(FeeAccumulator::new(3).address, fee_accumulator_address(3));
+}
+
+#[test]
+fn test_consolidate_fee_accumulators() {
+    let block_context = BlockContext::create_for_account_testing();
+    let sequencer_address = block_context.block_info.sequencer_address;
+    let accumulators = [FeeAccumulator::new(0), FeeAccumulator::new(1)];
+    let mut state = CachedState::from(DictStateReader::default());
+
+    let eth_fee_token_address = block_context.chain_info.fee_token_address(&FeeType::Eth);
+    let set_low_balance = |state: &mut CachedState<DictStateReader>, address, balance: u128| {
+        state
+            .set_storage_at(
+                eth_fee_token_address,
+                get_fee_token_var_address(address),
+                stark_felt!(balance),
+            )
+            .unwrap();
+    };
+    set_low_balance(&mut state, sequencer_address, 10);
+    set_low_balance(&mut state, accumulators[0].address, u128::MAX);
+    // The second accumulator also holds tokens transferred to it outside of fee charging.
+    set_low_balance(&mut state, accumulators[1].address, 5 + 7);
+    let mut accrued_fees = AccruedFees::default();
+    accrued_fees.add(eth_fee_token_address, accumulators[0], Fee(u128::MAX - 3));
+    accrued_fees.add(eth_fee_token_address, accumulators[0], Fee(3));
+    accrued_fees.add(eth_fee_token_address, accumulators[1], Fee(5));
+
+    ConsolidateFees(accrued_fees).apply(&mut state, &block_context).unwrap();
+
+    // The low word overflows into the high word: 10 + (2^128 - 1) + 5 = 2^128 + 14.
+    assert_eq!(
+        state.get_fee_token_balance(sequencer_address, eth_fee_token_address).unwrap(),
+        (stark_felt!(14_u8), stark_felt!(1_u8))
+    );
+    // Only the accrued fees are moved.
+    for (accumulator, expected_balance) in [(accumulators[0], 0_u8), (accumulators[1], 7)] {
+        assert_eq!(
+            state.get_fee_token_balance(accumulator.address, eth_fee_token_address).unwrap(),
+            (stark_felt!(expected_balance), StarkFelt::ZERO)
+        );
+    }
+}
+
+#[test]
+fn test_consolidate_fee_accumulators_overflow() {
+    let block_context = BlockContext::create_for_account_testing();
+    let sequencer_address = block_context.block_info.sequencer_address;
+    let accumulator = FeeAccumulator::new(0);
+    let mut state = CachedState::from(DictStateReader::default());
+
+    let eth_fee_token_address = block_context.chain_info.fee_token_address(&FeeType::Eth);
+    let sequencer_low_key = get_fee_token_var_address(sequencer_address);
+    let max_u128 = stark_felt!(u128::MAX);
+    state.set_storage_at(eth_fee_token_address, sequencer_low_key, max_u128).unwrap();
+    state
+        .set_storage_at(
+            eth_fee_token_address,
+            next_storage_key(&sequencer_low_key).unwrap(),
+            max_u128,
+        )
+        .unwrap();
+    state
+        .set_storage_at(
+            eth_fee_token_address,
+            get_fee_token_var_address(accumulator.address),
+            stark_felt!(1_u8),
+        )
+        .unwrap();
+    let mut accrued_fees = AccruedFees::default();
+    accrued_fees.add(eth_fee_token_address, accumulator, Fee(1));
+
+    assert_matches!(
+        consolidate_fee_accumulators(&mut state, &block_context, &accrued_fees).unwrap_err(),
+        TransactionFeeError::FeeAccumulationOverflow { fee_token_address }
+        if fee_token_address == eth_fee_token_address
+    );
+}
+
+#[test]
+fn test_consolidate_fee_accumulators_insufficient_balance() {
+    let block_context = BlockContext::create_for_account_testing();
+    let accumulator = FeeAccumulator::new(0);
+    let mut state = CachedState::from(DictStateReader::default());
+
+    let eth_fee_token_address = block_context.chain_info.fee_token_address(&FeeType::Eth);
+    let mut accrued_fees = AccruedFees::default();
+    accrued_fees.add(eth_fee_token_address, accumulator, Fee(1));
+
+    assert_matches!(
+        consolidate_fee_accumulators(&mut state, &block_context, &accrued_fees).unwrap_err(),
+        TransactionFeeError::InsufficientAccumulatorBalance {
+            accumulator_address,
+            fee_token_address,
+        }
+        if accumulator_address == accumulator.address && fee_token_address == eth_fee_token_address
+    );
+}
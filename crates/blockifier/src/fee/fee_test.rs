@@ -7,12 +7,15 @@ use cairo_vm::vm::runners::builtin_runner::{
 };
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use rstest::rstest;
+use starknet_api::hash::StarkFelt;
 use starknet_api::transaction::{Fee, TransactionVersion};
 
 use crate::abi::constants;
 use crate::context::BlockContext;
 use crate::fee::actual_cost::ActualCost;
-use crate::fee::fee_checks::{FeeCheckError, FeeCheckReportFields, PostExecutionReport};
+use crate::fee::fee_checks::{
+    BoundDimension, BoundsReport, FeeCheckError, FeeCheckReportFields, PostExecutionReport,
+};
 use crate::fee::fee_utils::calculate_l1_gas_by_vm_usage;
 use crate::invoke_tx_args;
 use crate::test_utils::contracts::FeatureContract;
@@ -119,3 +122,44 @@ fn test_discounted_gas_overdraft(
         assert_matches!(report.error(), None);
     }
 }
+
+#[rstest]
+#[case::exact_bump(100, 120, Some(20))]
+#[case::rounded_up_bump(3, 4, Some(34))]
+#[case::within_bound(100, 90, Some(0))]
+#[case::zero_bound(0, 10, None)]
+fn test_bounds_report(
+    #[case] declared: u128,
+    #[case] required: u128,
+    #[case] expected_bump_percent: Option<u128>,
+) {
+    let report = BoundsReport::new(BoundDimension::L1Gas, declared, required);
+    assert_eq!(report.suggested_bump_percent, expected_bump_percent);
+    if let Some(bump_percent) = expected_bump_percent {
+        assert!(declared * (100 + bump_percent) >= required * 100);
+    }
+}
+
+#[test]
+fn test_fee_check_error_bounds_report() {
+    let error = FeeCheckError::MaxFeeExceeded { max_fee: Fee(50), actual_fee: Fee(60) };
+    assert_eq!(
+        error.bounds_report(),
+        Some(BoundsReport {
+            dimension: BoundDimension::MaxFee,
+            declared: 50,
+            required: 60,
+            suggested_bump_percent: Some(20),
+        })
+    );
+    assert!(
+        error.to_string().ends_with("Bound MaxFee: declared 50, required 60, suggested bump: 20%.")
+    );
+
+    let error = FeeCheckError::InsufficientFeeTokenBalance {
+        fee: Fee(60),
+        balance_low: StarkFelt::ZERO,
+        balance_high: StarkFelt::ZERO,
+    };
+    assert_eq!(error.bounds_report(), None);
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use cairo_vm::vm::runners::builtin_runner::SEGMENT_ARENA_BUILTIN_NAME;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
@@ -11,10 +11,11 @@ use crate::context::{BlockContext, TransactionContext};
 use crate::state::state_api::StateReader;
 use crate::transaction::errors::TransactionFeeError;
 use crate::transaction::objects::{
-    FeeType, GasVector, HasRelatedFeeType, ResourcesMapping, TransactionFeeResult, TransactionInfo,
+    FeeType, GasVector, GasVectorBreakdown, HasRelatedFeeType, ResourcesMapping,
+    TransactionFeeResult, TransactionInfo,
 };
 use crate::utils::u128_from_usize;
-use crate::versioned_constants::VersionedConstants;
+use crate::versioned_constants::{ResourceCost, VersionedConstants};
 
 #[cfg(test)]
 #[path = "fee_test.rs"]
@@ -55,38 +56,78 @@ pub fn calculate_l1_gas_by_vm_usage(
     versioned_constants: &VersionedConstants,
     vm_resource_usage: &ExecutionResources,
 ) -> TransactionFeeResult<GasVector> {
+    let vm_l1_gas_usage = calculate_l1_gas_per_vm_resource(versioned_constants, vm_resource_usage)?
+        .into_values()
+        .max()
+        .unwrap_or_default();
+
+    Ok(GasVector::from_l1_gas(vm_l1_gas_usage))
+}
+
+/// Calculates the L1 gas weight of each Cairo resource: the steps, and each builtin with a fee
+/// cost.
+pub fn calculate_l1_gas_per_vm_resource(
+    versioned_constants: &VersionedConstants,
+    vm_resource_usage: &ExecutionResources,
+) -> TransactionFeeResult<HashMap<String, u128>> {
     let vm_resource_fee_costs = versioned_constants.vm_resource_fee_cost();
     let vm_resource_names =
         HashSet::<&String>::from_iter(vm_resource_usage.builtin_instance_counter.keys());
     if !vm_resource_names.is_subset(&HashSet::from_iter(vm_resource_fee_costs.keys())) {
         return Err(TransactionFeeError::CairoResourcesNotContainedInFeeCosts);
     };
-    let n_steps_gas_usage = u128_from_usize(vm_resource_usage.n_steps)
-        * vm_resource_fee_costs
-            .get(constants::N_STEPS_RESOURCE)
-            .cloned()
-            .unwrap_or_default()
-            .ceil()
-            .to_integer();
+    let n_steps_gas_usage = calculate_l1_gas_by_resource_usage(
+        versioned_constants,
+        constants::N_STEPS_RESOURCE,
+        vm_resource_usage.n_steps,
+    )
+    .unwrap_or_default();
 
     // Convert Cairo usage to L1 gas usage.
-    let vm_l1_gas_usage = vm_resource_fee_costs
+    let builtins_gas_usage = vm_resource_fee_costs
         .iter()
+        .filter(|(key, _)| key.as_str() != constants::N_STEPS_RESOURCE)
         .map(|(key, resource_val)| {
-            ((*resource_val)
-                * u128_from_usize(
-                    vm_resource_usage
-                        .builtin_instance_counter
-                        .get(key)
-                        .cloned()
-                        .unwrap_or_default(),
-                ))
-            .ceil()
-            .to_integer()
-        })
-        .fold(n_steps_gas_usage, u128::max);
+            let n_instances =
+                vm_resource_usage.builtin_instance_counter.get(key).cloned().unwrap_or_default();
+            (key.clone(), l1_gas_by_resource_usage(key, *resource_val, n_instances))
+        });
+
+    Ok(builtins_gas_usage
+        .chain([(constants::N_STEPS_RESOURCE.to_string(), n_steps_gas_usage)])
+        .collect())
+}
 
-    Ok(GasVector::from_l1_gas(vm_l1_gas_usage))
+/// Converts the usage of a single Cairo resource - the number of steps, or the number of instances
+/// of a builtin - to L1 gas, rounded as in the fee computation. Returns `None` if the resource has
+/// no fee cost.
+pub fn calculate_l1_gas_by_resource_usage(
+    versioned_constants: &VersionedConstants,
+    resource: &str,
+    usage: usize,
+) -> Option<u128> {
+    let weight = versioned_constants.vm_resource_fee_cost().get(resource)?;
+    Some(l1_gas_by_resource_usage(resource, *weight, usage))
+}
+
+fn l1_gas_by_resource_usage(resource: &str, weight: ResourceCost, usage: usize) -> u128 {
+    if resource == constants::N_STEPS_RESOURCE {
+        // The step weight is rounded up before it is multiplied.
+        u128_from_usize(usage) * weight.ceil().to_integer()
+    } else {
+        (weight * u128_from_usize(usage)).ceil().to_integer()
+    }
+}
+
+/// Returns the Cairo resources charged for in the given transaction resources; the steps include
+/// the OS steps of the segment arena builtin.
+pub fn extract_vm_resources(resources: &ResourcesMapping) -> ExecutionResources {
+    let (_, vm_resources) = extract_l1_gas_and_vm_usage(resources);
+    let (_, vm_resources) = extract_l1_blob_gas_usage(&vm_resources);
+    let (n_steps, vm_resources) = extract_n_steps(&vm_resources);
+    // Memory holes are always zero at this point, it's counted as n_steps when `resources` were
+    // created.
+    ExecutionResources { n_steps, n_memory_holes: 0, builtin_instance_counter: vm_resources.0 }
 }
 
 /// Computes and returns the total L1 gas consumption.
@@ -96,21 +137,30 @@ pub fn calculate_tx_gas_vector(
     resources: &ResourcesMapping,
     versioned_constants: &VersionedConstants,
 ) -> TransactionFeeResult<GasVector> {
+    Ok(calculate_tx_gas_breakdown(resources, versioned_constants)?.total())
+}
+
+/// Computes the L1 gas consumption of each resource; see `calculate_tx_gas_vector`.
+pub fn calculate_tx_gas_breakdown(
+    resources: &ResourcesMapping,
+    versioned_constants: &VersionedConstants,
+) -> TransactionFeeResult<GasVectorBreakdown> {
     let (l1_gas_usage, vm_resources) = extract_l1_gas_and_vm_usage(resources);
-    let (l1_blob_gas_usage, vm_resources) = extract_l1_blob_gas_usage(&vm_resources);
-    let (n_steps, vm_resources) = extract_n_steps(&vm_resources);
-    // Memory holes are always zero at this point, it's counted as n_steps when `resources` were
-    // created.
+    let (l1_blob_gas_usage, _) = extract_l1_blob_gas_usage(&vm_resources);
     // TODO(Nimrod, 25/3/2024): Change function's input type to `ExecutionResources`.
-    let execution_resources =
-        ExecutionResources { n_steps, n_memory_holes: 0, builtin_instance_counter: vm_resources.0 };
-    let vm_usage_gas_vector =
-        calculate_l1_gas_by_vm_usage(versioned_constants, &execution_resources)?;
-
-    Ok(GasVector {
-        l1_gas: u128_from_usize(l1_gas_usage),
-        l1_data_gas: u128_from_usize(l1_blob_gas_usage),
-    } + vm_usage_gas_vector)
+    let execution_resources = extract_vm_resources(resources);
+    let vm_resources = calculate_l1_gas_per_vm_resource(versioned_constants, &execution_resources)?
+        .into_iter()
+        .map(|(resource, l1_gas)| (resource, GasVector::from_l1_gas(l1_gas)))
+        .collect();
+
+    Ok(GasVectorBreakdown {
+        l1_usage: GasVector {
+            l1_gas: u128_from_usize(l1_gas_usage),
+            l1_data_gas: u128_from_usize(l1_blob_gas_usage),
+        },
+        vm_resources,
+    })
 }
 
 /// Converts the gas vector to a fee.
@@ -135,7 +185,8 @@ pub fn calculate_tx_fee(
     Ok(get_fee_by_gas_vector(&block_context.block_info, gas_vector, fee_type))
 }
 
-/// Returns the current fee balance and a boolean indicating whether the balance covers the fee.
+/// Returns the current fee balance of the fee payer (see `TransactionContext::fee_payer_address`)
+/// and a boolean indicating whether the balance covers the fee.
 pub fn get_balance_and_if_covers_fee(
     state: &mut dyn StateReader,
     tx_context: &TransactionContext,
@@ -143,7 +194,7 @@ pub fn get_balance_and_if_covers_fee(
 ) -> TransactionFeeResult<(StarkFelt, StarkFelt, bool)> {
     let tx_info = &tx_context.tx_info;
     let (balance_low, balance_high) = state.get_fee_token_balance(
-        tx_info.sender_address(),
+        tx_context.fee_payer_address()?,
         tx_context.block_context.chain_info.fee_token_address(&tx_info.fee_type()),
     )?;
     Ok((
@@ -155,7 +206,7 @@ pub fn get_balance_and_if_covers_fee(
     ))
 }
 
-/// Verifies that, given the current state, the account can cover the resource upper bounds.
+/// Verifies that, given the current state, the fee payer can cover the resource upper bounds.
 /// Error may indicate insufficient balance, or some other error.
 pub fn verify_can_pay_committed_bounds(
     state: &mut dyn StateReader,
@@ -1,14 +1,17 @@
 use std::collections::HashMap;
 
+use starknet_api::transaction::Fee;
+
 use crate::abi::constants;
 use crate::context::{BlockContext, TransactionContext};
 use crate::execution::call_info::MessageL1CostInfo;
 use crate::fee::eth_gas_constants;
-use crate::fee::fee_utils::calculate_tx_gas_vector;
+use crate::fee::fee_utils::{calculate_tx_gas_vector, get_fee_by_gas_vector};
 use crate::state::cached_state::StateChangesCount;
 use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::objects::{
-    GasVector, HasRelatedFeeType, ResourcesMapping, TransactionPreValidationResult,
+    GasVector, HasRelatedFeeType, ResourcesMapping, StarknetResources,
+    TransactionPreValidationResult,
 };
 use crate::utils::{u128_from_usize, usize_from_u128};
 
@@ -166,7 +169,10 @@ fn get_event_emission_cost(n_topics: usize, data_length: usize) -> GasVector {
     ))
 }
 
-/// Return an estimated lower bound for the L1 gas on an account transaction.
+/// Returns an estimated lower bound for the gas of an account transaction, computed before its
+/// execution: the OS overhead of its type, the cost of its calldata, signature and declared class
+/// (if any), and the cost of the state changes made by every transaction of its type. Usable for
+/// pre-validation (e.g., in a mempool); execution rejects transactions whose bounds are below it.
 pub fn estimate_minimal_gas_vector(
     block_context: &BlockContext,
     tx: &AccountTransaction,
@@ -197,8 +203,21 @@ pub fn estimate_minimal_gas_vector(
             n_modified_contracts: 1,
         },
     };
+    let class_info = match tx {
+        AccountTransaction::Declare(tx) => Some(&tx.class_info),
+        AccountTransaction::DeployAccount(_) | AccountTransaction::Invoke(_) => None,
+    };
+    let starknet_resources = StarknetResources::new(
+        tx.calldata_length(),
+        tx.signature_length(),
+        class_info,
+        state_changes_by_account_transaction,
+        None,
+        std::iter::empty(),
+    )
+    .expect("Starknet resources without call infos are infallible.");
     let GasVector { l1_gas: gas_cost, l1_data_gas: blob_gas_cost } =
-        get_da_gas_cost(&state_changes_by_account_transaction, block_info.use_kzg_da);
+        starknet_resources.to_gas_vector(versioned_constants, block_info.use_kzg_da);
 
     let data_segment_length =
         get_onchain_data_segment_length(&state_changes_by_account_transaction);
@@ -222,6 +241,16 @@ pub fn estimate_minimal_gas_vector(
     Ok(calculate_tx_gas_vector(&resources, versioned_constants)?)
 }
 
+/// Returns an estimated lower bound for the fee of an account transaction, computed before its
+/// execution (see `estimate_minimal_gas_vector`); priced in the transaction's fee token.
+pub fn estimate_minimal_fee(
+    block_context: &BlockContext,
+    tx: &AccountTransaction,
+) -> TransactionPreValidationResult<Fee> {
+    let minimal_gas_vector = estimate_minimal_gas_vector(block_context, tx)?;
+    Ok(get_fee_by_gas_vector(&block_context.block_info, minimal_gas_vector, &tx.fee_type()))
+}
+
 /// Compute l1_gas estimation from gas_vector using the following formula:
 /// One byte of data costs either 1 data gas (in blob mode) or 16 gas (in calldata
 /// mode). For gas price GP and data gas price DGP, the discount for using blobs
@@ -1,16 +1,26 @@
 use pretty_assertions::assert_eq;
 use rstest::{fixture, rstest};
 use starknet_api::hash::StarkFelt;
-use starknet_api::transaction::{EventContent, EventData, EventKey};
+use starknet_api::stark_felt;
+use starknet_api::transaction::{Calldata, EventContent, EventData, EventKey, Fee};
 
 use crate::abi::constants;
+use crate::context::BlockContext;
 use crate::execution::call_info::{CallExecution, CallInfo, OrderedEvent};
 use crate::fee::eth_gas_constants;
-use crate::fee::gas_usage::{get_da_gas_cost, get_message_segment_length};
+use crate::fee::fee_utils::get_fee_by_gas_vector;
+use crate::fee::gas_usage::{
+    estimate_minimal_fee, estimate_minimal_gas_vector, get_da_gas_cost, get_message_segment_length,
+};
 use crate::state::cached_state::StateChangesCount;
-use crate::transaction::objects::{GasVector, StarknetResources};
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::declare::declare_tx;
+use crate::test_utils::{CairoVersion, MAX_FEE};
+use crate::transaction::objects::{FeeType, GasVector, StarknetResources};
+use crate::transaction::test_utils::{account_invoke_tx, calculate_class_info_for_testing};
 use crate::utils::u128_from_usize;
 use crate::versioned_constants::{ResourceCost, VersionedConstants};
+use crate::{declare_tx_args, invoke_tx_args};
 #[fixture]
 fn versioned_constants() -> &'static VersionedConstants {
     VersionedConstants::latest_constants()
@@ -193,3 +203,47 @@ fn test_get_message_segment_length(
 
     assert_eq!(result, expected_result);
 }
+
+#[test]
+fn test_estimate_minimal_gas_vector() {
+    let block_context = BlockContext::create_for_account_testing();
+    let versioned_constants = &block_context.versioned_constants;
+    let account = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo0);
+    let sender_address = account.get_instance_address(0);
+
+    // The calldata is charged before execution.
+    let invoke_tx = |calldata_length: u8| {
+        account_invoke_tx(invoke_tx_args! {
+            sender_address,
+            calldata: Calldata(vec![stark_felt!(7_u8); usize::from(calldata_length)].into()),
+            max_fee: Fee(MAX_FEE),
+        })
+    };
+    let short_tx_gas = estimate_minimal_gas_vector(&block_context, &invoke_tx(1)).unwrap();
+    let long_tx_gas = estimate_minimal_gas_vector(&block_context, &invoke_tx(100)).unwrap();
+    assert!(long_tx_gas.l1_gas > short_tx_gas.l1_gas);
+
+    // So is the declared class.
+    let class_info = calculate_class_info_for_testing(account.get_class());
+    let code_cost = StarknetResources::new(
+        0,
+        0,
+        Some(&class_info),
+        StateChangesCount::default(),
+        None,
+        std::iter::empty(),
+    )
+    .unwrap()
+    .get_code_cost(versioned_constants);
+    let tx = declare_tx(
+        declare_tx_args! { sender_address, max_fee: Fee(MAX_FEE), class_hash: account.get_class_hash() },
+        class_info,
+    );
+    let declare_tx_gas = estimate_minimal_gas_vector(&block_context, &tx).unwrap();
+    assert!(declare_tx_gas.l1_gas > code_cost.l1_gas);
+
+    assert_eq!(
+        estimate_minimal_fee(&block_context, &tx).unwrap(),
+        get_fee_by_gas_vector(&block_context.block_info, declare_tx_gas, &FeeType::Eth)
+    );
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use starknet_api::transaction::Fee;
+
+use crate::blockifier::block::BlockInfo;
+use crate::fee::fee_utils::{calculate_tx_gas_vector, get_fee_by_gas_vector};
+use crate::transaction::objects::{
+    FeeType, ResourcesMapping, TransactionExecutionInfo, TransactionFeeResult,
+};
+use crate::versioned_constants::VersionedConstants;
+
+#[cfg(test)]
+#[path = "pricing_simulation_test.rs"]
+pub mod test;
+
+/// A transaction from a recorded corpus, with what is needed to re-price it offline.
+#[derive(Clone)]
+pub struct RecordedTransaction {
+    pub block_info: BlockInfo,
+    pub fee_type: FeeType,
+    pub actual_resources: ResourcesMapping,
+    pub actual_fee: Fee,
+}
+
+impl RecordedTransaction {
+    pub fn new(
+        block_info: BlockInfo,
+        fee_type: FeeType,
+        tx_execution_info: &TransactionExecutionInfo,
+    ) -> Self {
+        Self {
+            block_info,
+            fee_type,
+            actual_resources: tx_execution_info.actual_resources.clone(),
+            actual_fee: tx_execution_info.actual_fee,
+        }
+    }
+}
+
+/// The aggregate fee shift of a corpus under alternative pricing, for a single fee token.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FeeShiftReport {
+    pub n_transactions: usize,
+    pub n_increased: usize,
+    pub n_decreased: usize,
+    pub recorded_revenue: u128,
+    pub simulated_revenue: u128,
+    pub max_increase: u128,
+    pub max_decrease: u128,
+}
+
+impl FeeShiftReport {
+    fn add(&mut self, recorded_fee: Fee, simulated_fee: Fee) {
+        let (recorded_fee, simulated_fee) = (recorded_fee.0, simulated_fee.0);
+        self.n_transactions += 1;
+        self.recorded_revenue = self.recorded_revenue.saturating_add(recorded_fee);
+        self.simulated_revenue = self.simulated_revenue.saturating_add(simulated_fee);
+        if simulated_fee > recorded_fee {
+            self.n_increased += 1;
+            self.max_increase = self.max_increase.max(simulated_fee - recorded_fee);
+        } else if simulated_fee < recorded_fee {
+            self.n_decreased += 1;
+            self.max_decrease = self.max_decrease.max(recorded_fee - simulated_fee);
+        }
+    }
+}
+
+/// Re-prices a corpus of recorded transactions under the given (proposed) versioned constants,
+/// keeping each transaction's recorded gas prices, and reports the fee shift per fee token.
+/// Only the pricing of Cairo resources is re-evaluated; the L1 gas and data gas usages are taken
+/// as recorded.
+pub fn simulate_pricing<'a>(
+    corpus: impl IntoIterator<Item = &'a RecordedTransaction>,
+    versioned_constants: &VersionedConstants,
+) -> TransactionFeeResult<HashMap<FeeType, FeeShiftReport>> {
+    let mut reports: HashMap<FeeType, FeeShiftReport> = HashMap::new();
+    for tx in corpus {
+        let gas_vector = calculate_tx_gas_vector(&tx.actual_resources, versioned_constants)?;
+        let simulated_fee = get_fee_by_gas_vector(&tx.block_info, gas_vector, &tx.fee_type);
+        reports.entry(tx.fee_type).or_default().add(tx.actual_fee, simulated_fee);
+    }
+
+    Ok(reports)
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+
+use crate::abi::constants;
+use crate::blockifier::block::BlockInfo;
+use crate::context::BlockContext;
+use crate::fee::fee_utils::calculate_tx_fee;
+use crate::fee::pricing_simulation::{simulate_pricing, FeeShiftReport, RecordedTransaction};
+use crate::transaction::objects::{FeeType, ResourcesMapping};
+use crate::versioned_constants::VersionedConstants;
+
+fn recorded_transaction(n_steps: usize, fee_type: FeeType) -> RecordedTransaction {
+    let actual_resources = ResourcesMapping(HashMap::from([
+        (constants::L1_GAS_USAGE.to_string(), 100),
+        (constants::BLOB_GAS_USAGE.to_string(), 0),
+        (constants::N_STEPS_RESOURCE.to_string(), n_steps),
+    ]));
+    let actual_fee =
+        calculate_tx_fee(&actual_resources, &BlockContext::create_for_testing(), &fee_type)
+            .unwrap();
+    RecordedTransaction {
+        block_info: BlockInfo::create_for_testing(),
+        fee_type,
+        actual_resources,
+        actual_fee,
+    }
+}
+
+#[test]
+fn test_simulate_pricing() {
+    let corpus = [
+        recorded_transaction(1000, FeeType::Eth),
+        recorded_transaction(5000, FeeType::Eth),
+        recorded_transaction(1000, FeeType::Strk),
+    ];
+
+    // Pricing under the recorded constants does not shift fees.
+    let reports = simulate_pricing(&corpus, &VersionedConstants::create_for_testing()).unwrap();
+    let eth_report = &reports[&FeeType::Eth];
+    assert_eq!(eth_report.n_transactions, 2);
+    assert_eq!(eth_report.recorded_revenue, eth_report.simulated_revenue);
+    assert_eq!((eth_report.n_increased, eth_report.n_decreased), (0, 0));
+
+    // Steps are more expensive under the alternative constants.
+    let alternative_constants = VersionedConstants::create_for_account_testing();
+    let reports = simulate_pricing(&corpus, &alternative_constants).unwrap();
+    let simulated_fee = |tx: &RecordedTransaction| {
+        let block_context = BlockContext::new_unchecked(
+            &tx.block_info,
+            &Default::default(),
+            &alternative_constants,
+        );
+        calculate_tx_fee(&tx.actual_resources, &block_context, &tx.fee_type).unwrap()
+    };
+    let fee_shift = |tx: &RecordedTransaction| simulated_fee(tx).0 - tx.actual_fee.0;
+    assert_eq!(
+        reports[&FeeType::Eth],
+        FeeShiftReport {
+            n_transactions: 2,
+            n_increased: 2,
+            n_decreased: 0,
+            recorded_revenue: corpus[0].actual_fee.0 + corpus[1].actual_fee.0,
+            simulated_revenue: simulated_fee(&corpus[0]).0 + simulated_fee(&corpus[1]).0,
+            max_increase: fee_shift(&corpus[1]),
+            max_decrease: 0,
+        }
+    );
+    assert_eq!(reports[&FeeType::Strk].n_increased, 1);
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use num_rational::Ratio;
+use num_traits::CheckedMul;
+use starknet_api::transaction::{Resource, ResourceBounds, ResourceBoundsMapping};
+
+use crate::context::BlockContext;
+use crate::fee::actual_cost::ActualCost;
+use crate::fee::fee_utils::calculate_tx_gas_vector;
+use crate::transaction::objects::{FeeType, TransactionFeeResult};
+
+#[cfg(test)]
+#[path = "resource_bounds_test.rs"]
+pub mod test;
+
+/// Models the deviation of the actual L1 gas amount and price from their estimates, e.g., as
+/// observed over the history of similar transactions.
+pub trait VarianceModel: Send + Sync {
+    /// Returns the amount to add to the bound of a transaction estimated to use the given L1 gas.
+    fn l1_gas_amount_deviation(&self, estimated_l1_gas: u128) -> u128;
+    /// Returns the amount to add to the bound of the given current L1 gas price.
+    fn l1_gas_price_deviation(&self, l1_gas_price: u128) -> u128;
+}
+
+/// Deviations proportional to the estimates; e.g., a number of standard deviations of the
+/// historical relative estimation errors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RelativeVariance {
+    pub l1_gas_amount: Ratio<u128>,
+    pub l1_gas_price: Ratio<u128>,
+}
+
+impl VarianceModel for RelativeVariance {
+    fn l1_gas_amount_deviation(&self, estimated_l1_gas: u128) -> u128 {
+        scale_ceil(estimated_l1_gas, self.l1_gas_amount)
+    }
+
+    fn l1_gas_price_deviation(&self, l1_gas_price: u128) -> u128 {
+        scale_ceil(l1_gas_price, self.l1_gas_price)
+    }
+}
+
+/// Determines how resource bounds are derived from an estimate.
+#[derive(Clone)]
+pub struct BoundsPolicy {
+    /// The factor by which the estimated L1 gas amount is multiplied.
+    pub l1_gas_amount_margin: Ratio<u128>,
+    /// The factor by which the current L1 gas price is multiplied; covers price increases until
+    /// the transaction is included.
+    pub l1_gas_price_margin: Ratio<u128>,
+    /// If set, its deviations are added on top of the margins.
+    pub variance_model: Option<Arc<dyn VarianceModel>>,
+}
+
+impl Default for BoundsPolicy {
+    fn default() -> Self {
+        Self {
+            l1_gas_amount_margin: Ratio::new(3, 2),
+            l1_gas_price_margin: Ratio::new(3, 2),
+            variance_model: None,
+        }
+    }
+}
+
+/// Returns V3 resource bounds for a transaction with the given estimated cost (see
+/// `AccountTransaction::estimate_fee`), under the given policy and the block's STRK gas prices.
+/// As in the fee checks, L1 data gas is bounded as L1 gas, at the ratio of their prices.
+pub fn suggest_resource_bounds(
+    estimate: &ActualCost,
+    policy: &BoundsPolicy,
+    block_context: &BlockContext,
+) -> TransactionFeeResult<ResourceBoundsMapping> {
+    let gas_vector =
+        calculate_tx_gas_vector(&estimate.actual_resources, &block_context.versioned_constants)?;
+    let gas_prices = &block_context.block_info.gas_prices;
+    let l1_gas_price = gas_prices.get_gas_price_by_fee_type(&FeeType::Strk).get();
+    let l1_data_gas_price = gas_prices.get_data_gas_price_by_fee_type(&FeeType::Strk).get();
+    let estimated_l1_gas = gas_vector
+        .l1_gas
+        .saturating_add(gas_vector.l1_data_gas.saturating_mul(l1_data_gas_price) / l1_gas_price);
+
+    let (amount_deviation, price_deviation) =
+        policy.variance_model.as_ref().map_or((0, 0), |variance_model| {
+            (
+                variance_model.l1_gas_amount_deviation(estimated_l1_gas),
+                variance_model.l1_gas_price_deviation(l1_gas_price),
+            )
+        });
+    let max_amount =
+        scale_ceil(estimated_l1_gas, policy.l1_gas_amount_margin).saturating_add(amount_deviation);
+    let max_price_per_unit =
+        scale_ceil(l1_gas_price, policy.l1_gas_price_margin).saturating_add(price_deviation);
+
+    Ok(ResourceBoundsMapping(BTreeMap::from([
+        (
+            Resource::L1Gas,
+            ResourceBounds {
+                max_amount: u64::try_from(max_amount).unwrap_or(u64::MAX),
+                max_price_per_unit,
+            },
+        ),
+        (Resource::L2Gas, ResourceBounds { max_amount: 0, max_price_per_unit: 0 }),
+    ])))
+}
+
+/// Returns the given value multiplied by the given factor, rounded up; saturates on overflow.
+fn scale_ceil(value: u128, factor: Ratio<u128>) -> u128 {
+    Ratio::from_integer(value)
+        .checked_mul(&factor)
+        .map_or(u128::MAX, |scaled_value| scaled_value.ceil().to_integer())
+}
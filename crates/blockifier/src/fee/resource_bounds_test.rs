@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use num_rational::Ratio;
+use pretty_assertions::assert_eq;
+use starknet_api::transaction::{Resource, ResourceBounds};
+
+use crate::abi::constants;
+use crate::context::BlockContext;
+use crate::fee::actual_cost::ActualCost;
+use crate::fee::resource_bounds::{suggest_resource_bounds, BoundsPolicy, RelativeVariance};
+use crate::transaction::objects::{FeeType, ResourcesMapping};
+
+fn estimate(l1_gas: usize, l1_data_gas: usize) -> ActualCost {
+    let actual_resources = ResourcesMapping(HashMap::from([
+        (constants::L1_GAS_USAGE.to_string(), l1_gas),
+        (constants::BLOB_GAS_USAGE.to_string(), l1_data_gas),
+        (constants::N_STEPS_RESOURCE.to_string(), 0),
+    ]));
+    ActualCost { actual_resources, ..Default::default() }
+}
+
+fn l1_gas_bounds(
+    estimate: &ActualCost,
+    policy: &BoundsPolicy,
+    block_context: &BlockContext,
+) -> ResourceBounds {
+    let resource_bounds = suggest_resource_bounds(estimate, policy, block_context).unwrap();
+    assert_eq!(
+        resource_bounds.0[&Resource::L2Gas],
+        ResourceBounds { max_amount: 0, max_price_per_unit: 0 }
+    );
+    resource_bounds.0[&Resource::L1Gas]
+}
+
+#[test]
+fn test_suggest_resource_bounds() {
+    let block_context = BlockContext::create_for_testing();
+    let gas_prices = &block_context.block_info.gas_prices;
+    let l1_gas_price = gas_prices.get_gas_price_by_fee_type(&FeeType::Strk).get();
+    let l1_data_gas_price = gas_prices.get_data_gas_price_by_fee_type(&FeeType::Strk).get();
+
+    // Exact bounds, without margins.
+    let exact_policy = BoundsPolicy {
+        l1_gas_amount_margin: Ratio::from_integer(1),
+        l1_gas_price_margin: Ratio::from_integer(1),
+        variance_model: None,
+    };
+    let bounds = l1_gas_bounds(&estimate(1000, 0), &exact_policy, &block_context);
+    assert_eq!(bounds, ResourceBounds { max_amount: 1000, max_price_per_unit: l1_gas_price });
+    // Data gas is bounded as L1 gas.
+    let bounds = l1_gas_bounds(&estimate(1000, 10), &exact_policy, &block_context);
+    let discounted_data_gas = u64::try_from(10 * l1_data_gas_price / l1_gas_price).unwrap();
+    assert_eq!(bounds.max_amount, 1000 + discounted_data_gas);
+
+    // The default margins are 50%, rounded up.
+    let bounds = l1_gas_bounds(&estimate(1001, 0), &BoundsPolicy::default(), &block_context);
+    assert_eq!(
+        bounds,
+        ResourceBounds {
+            max_amount: 1502,
+            max_price_per_unit: Ratio::new(3 * l1_gas_price, 2).ceil().to_integer(),
+        }
+    );
+
+    // Deviations are added on top of the margins.
+    let variance_policy = BoundsPolicy {
+        variance_model: Some(Arc::new(RelativeVariance {
+            l1_gas_amount: Ratio::new(1, 10),
+            l1_gas_price: Ratio::new(1, 2),
+        })),
+        ..exact_policy
+    };
+    let bounds = l1_gas_bounds(&estimate(1000, 0), &variance_policy, &block_context);
+    assert_eq!(
+        bounds,
+        ResourceBounds {
+            max_amount: 1100,
+            max_price_per_unit: l1_gas_price + Ratio::new(l1_gas_price, 2).ceil().to_integer(),
+        }
+    );
+}
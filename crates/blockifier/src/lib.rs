@@ -10,8 +10,11 @@
 ))]
 
 pub mod abi;
+pub mod api;
 pub mod blockifier;
 pub mod bouncer;
+#[cfg(feature = "concurrency")]
+pub mod concurrency;
 pub mod context;
 pub mod execution;
 pub mod fee;
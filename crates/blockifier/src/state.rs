@@ -1,3 +1,14 @@
 pub mod cached_state;
+pub mod call_frame_state;
+pub mod da_encoding;
 pub mod errors;
+#[cfg(feature = "papyrus_state_reader")]
+pub mod papyrus_state_reader;
+#[cfg(feature = "pathfinder_state_reader")]
+pub mod pathfinder_state_reader;
+pub mod recording_state_reader;
+#[cfg(feature = "rpc_state_reader")]
+pub mod rpc_state_reader;
 pub mod state_api;
+pub mod state_update;
+pub mod trie_reader;
@@ -1,18 +1,23 @@
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex, MutexGuard};
 
 use cached::{Cached, SizedCache};
 use derive_more::IntoIterator;
 use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
-use starknet_api::hash::StarkFelt;
+use starknet_api::data_availability::DataAvailabilityMode;
+use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 
 use crate::abi::abi_utils::get_fee_token_var_address;
 use crate::execution::contract_class::ContractClass;
+use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
+use crate::state::da_encoding::state_diff_commitment;
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateReader, StateResult};
+use crate::state::trie_reader::{ExecutionWitness, ProofCollector, TrieKey, TrieReader};
 use crate::utils::subtract_mappings;
 
 #[cfg(test)]
@@ -36,6 +41,8 @@ pub struct CachedState<S: StateReader> {
     global_class_hash_to_class: GlobalContractCache,
     /// A map from class hash to the set of PC values that were visited in the class.
     pub visited_pcs: HashMap<ClassHash, HashSet<usize>>,
+    // When set, proofs are collected for all first-time reads from the underlying state.
+    proof_collector: Option<ProofCollector>,
 }
 
 impl<S: StateReader> CachedState<S> {
@@ -46,9 +53,40 @@ impl<S: StateReader> CachedState<S> {
             class_hash_to_class: RefCell::new(HashMap::default()),
             global_class_hash_to_class,
             visited_pcs: HashMap::default(),
+            proof_collector: None,
         }
     }
 
+    /// Configures the state to collect Merkle proofs, via the given trie reader, for all values
+    /// read for the first time from the underlying state.
+    pub fn with_trie_reader(mut self, trie_reader: Arc<dyn TrieReader>) -> Self {
+        self.proof_collector = Some(ProofCollector::new(trie_reader));
+        self
+    }
+
+    /// Returns the proofs collected so far, if a trie reader was configured.
+    pub fn execution_witness(&self) -> Option<ExecutionWitness> {
+        self.proof_collector.as_ref().map(ProofCollector::witness)
+    }
+
+    /// Drains the proofs collected so far, if a trie reader was configured.
+    pub fn take_execution_witness(&mut self) -> Option<ExecutionWitness> {
+        self.proof_collector.as_mut().map(ProofCollector::take_witness)
+    }
+
+    fn record_first_read(&self, key: TrieKey) -> StateResult<()> {
+        match &self.proof_collector {
+            Some(proof_collector) => proof_collector.record(key),
+            None => Ok(()),
+        }
+    }
+
+    /// Overrides the nonce of the given contract; e.g., to undo the nonce bump of a reverted
+    /// transaction.
+    pub(crate) fn set_nonce(&mut self, contract_address: ContractAddress, nonce: Nonce) {
+        self.cache.get_mut().set_nonce_value(contract_address, nonce);
+    }
+
     /// Creates a transactional instance from the given cached state.
     /// It allows performing buffered modifying actions on the given state, which
     /// will either all happen (will be committed) or none of them (will be discarded).
@@ -89,6 +127,29 @@ impl<S: StateReader> CachedState<S> {
         self.global_class_hash_to_class.lock()
     }
 
+    /// Returns a handle to the global class cache, to be shared with other cached states.
+    pub fn global_contract_cache(&self) -> GlobalContractCache {
+        self.global_class_hash_to_class.clone()
+    }
+
+    /// Writes the given state changes (e.g., recorded in an earlier execution) to the cache, as if
+    /// made by an execution.
+    pub fn apply_state_changes(&mut self, state_changes: &StateChanges) {
+        let cache = self.cache.get_mut();
+        for (&(contract_address, key), &value) in &state_changes.storage_updates {
+            cache.set_storage_value(contract_address, key, value);
+        }
+        for (&contract_address, &nonce) in &state_changes.nonce_updates {
+            cache.set_nonce_value(contract_address, nonce);
+        }
+        for (&contract_address, &class_hash) in &state_changes.class_hash_updates {
+            cache.set_class_hash_write(contract_address, class_hash);
+        }
+        for (&class_hash, &compiled_class_hash) in &state_changes.compiled_class_hash_updates {
+            cache.set_compiled_class_hash_write(class_hash, compiled_class_hash);
+        }
+    }
+
     pub fn update_cache(&mut self, cache_updates: StateCache) {
         let mut cache = self.cache.borrow_mut();
 
@@ -130,6 +191,7 @@ impl<S: StateReader> CachedState<S> {
                     *contract_storage_key,
                     self.state.get_storage_at(contract_storage_key.0, contract_storage_key.1)?,
                 );
+                self.record_first_read(TrieKey::Storage(*contract_storage_key))?;
             }
         }
 
@@ -139,6 +201,7 @@ impl<S: StateReader> CachedState<S> {
                 cache
                     .class_hash_initial_values
                     .insert(*contract_address, self.state.get_class_hash_at(*contract_address)?);
+                self.record_first_read(TrieKey::ClassHash(*contract_address))?;
             }
         }
 
@@ -148,6 +211,7 @@ impl<S: StateReader> CachedState<S> {
                 cache
                     .nonce_initial_values
                     .insert(*contract_address, self.state.get_nonce_at(*contract_address)?);
+                self.record_first_read(TrieKey::Nonce(*contract_address))?;
             }
         }
 
@@ -174,6 +238,47 @@ impl<S: StateReader> CachedState<S> {
             address_to_nonce: IndexMap::from_iter(nonces),
         }
     }
+
+    /// Detaches the changes made through this state from its underlying state reader, to be
+    /// applied to another cached state over the same underlying state (see `apply_changes`).
+    pub fn into_changes(self) -> (S, CachedStateChanges) {
+        let changes = CachedStateChanges {
+            cache: self.cache.into_inner(),
+            class_hash_to_class: self.class_hash_to_class.into_inner(),
+            visited_pcs: self.visited_pcs,
+        };
+        (self.state, changes)
+    }
+
+    /// Returns a checkpoint of the state, to which it may later be rolled back; e.g., to undo a
+    /// speculatively applied transaction. Checkpoints are independent snapshots of the changes
+    /// made through the state, hence creating one is linear in their size; the state may be
+    /// rolled back to any of them, in any order.
+    pub fn create_checkpoint(&self) -> StateCheckpoint {
+        StateCheckpoint {
+            cache: self.cache.borrow().clone(),
+            class_hash_to_class: self.class_hash_to_class.borrow().clone(),
+            visited_pcs: self.visited_pcs.clone(),
+        }
+    }
+
+    /// Discards the changes made through the state since the given checkpoint (of this state)
+    /// was created.
+    pub fn rollback_to(&mut self, checkpoint: StateCheckpoint) {
+        let StateCheckpoint { cache, class_hash_to_class, visited_pcs } = checkpoint;
+        *self.cache.get_mut() = cache;
+        *self.class_hash_to_class.get_mut() = class_hash_to_class;
+        self.visited_pcs = visited_pcs;
+    }
+
+    /// Applies changes detached from another cached state, as if they were made through this
+    /// state. The caller is responsible for the reads behind the changes being consistent with
+    /// this state.
+    pub fn apply_changes(&mut self, changes: CachedStateChanges) {
+        self.update_cache(changes.cache);
+        self.class_hash_to_class.get_mut().extend(changes.class_hash_to_class);
+        self.update_visited_pcs_cache(&changes.visited_pcs);
+    }
 }
 
 #[cfg(any(feature = "testing", test))]
@@ -197,6 +302,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
         if cache.get_storage_at(contract_address, key).is_none() {
             let storage_value = self.state.get_storage_at(contract_address, key)?;
             cache.set_storage_initial_value(contract_address, key, storage_value);
+            self.record_first_read(TrieKey::Storage((contract_address, key)))?;
         }
 
         let value = cache.get_storage_at(contract_address, key).unwrap_or_else(|| {
@@ -211,6 +317,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
         if cache.get_nonce_at(contract_address).is_none() {
             let nonce = self.state.get_nonce_at(contract_address)?;
             cache.set_nonce_initial_value(contract_address, nonce);
+            self.record_first_read(TrieKey::Nonce(contract_address))?;
         }
 
         let nonce = cache
@@ -226,6 +333,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
         if cache.get_class_hash_at(contract_address).is_none() {
             let class_hash = self.state.get_class_hash_at(contract_address)?;
             cache.set_class_hash_initial_value(contract_address, class_hash);
+            self.record_first_read(TrieKey::ClassHash(contract_address))?;
         }
 
         let class_hash = cache
@@ -268,6 +376,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
         if cache.get_compiled_class_hash(class_hash).is_none() {
             let compiled_class_hash = self.state.get_compiled_class_hash(class_hash)?;
             cache.set_compiled_class_hash_initial_value(class_hash, compiled_class_hash);
+            self.record_first_read(TrieKey::CompiledClassHash(class_hash))?;
         }
 
         let compiled_class_hash = cache
@@ -307,10 +416,7 @@ impl<S: StateReader> State for CachedState<S> {
         contract_address: ContractAddress,
         class_hash: ClassHash,
     ) -> StateResult<()> {
-        if contract_address == ContractAddress::default() {
-            return Err(StateError::OutOfRangeContractAddress);
-        }
-
+        validate_class_hash_write(contract_address)?;
         self.cache.get_mut().set_class_hash_write(contract_address, class_hash);
         Ok(())
     }
@@ -320,6 +426,9 @@ impl<S: StateReader> State for CachedState<S> {
         class_hash: ClassHash,
         contract_class: ContractClass,
     ) -> StateResult<()> {
+        // A declared class supersedes any cached compilation of it (e.g., of a declaration
+        // reverted in a reorg); the declared class is cached once moved to the global cache.
+        self.global_class_hash_to_class.invalidate_classes(&[class_hash]);
         self.class_hash_to_class.get_mut().insert(class_hash, contract_class);
         Ok(())
     }
@@ -338,6 +447,16 @@ impl<S: StateReader> State for CachedState<S> {
     }
 }
 
+/// Checks that a class hash may be assigned to the given address; shared by the states that buffer
+/// class hash updates, so that a buffered update fails as early as a direct one.
+pub(crate) fn validate_class_hash_write(contract_address: ContractAddress) -> StateResult<()> {
+    if contract_address == ContractAddress::default() {
+        return Err(StateError::OutOfRangeContractAddress);
+    }
+
+    Ok(())
+}
+
 #[cfg(any(feature = "testing", test))]
 impl Default for CachedState<crate::test_utils::dict_state_reader::DictStateReader> {
     fn default() -> Self {
@@ -349,6 +468,7 @@ impl Default for CachedState<crate::test_utils::dict_state_reader::DictStateRead
                 GLOBAL_CONTRACT_CACHE_SIZE_FOR_TEST,
             ),
             visited_pcs: Default::default(),
+            proof_collector: None,
         }
     }
 }
@@ -375,11 +495,19 @@ impl From<StorageView> for IndexMap<ContractAddress, IndexMap<StorageKey, StarkF
     }
 }
 
+/// A snapshot of the changes made through a cached state (see `CachedState::create_checkpoint`).
+#[derive(Clone, Debug)]
+pub struct StateCheckpoint {
+    cache: StateCache,
+    class_hash_to_class: ContractClassMapping,
+    visited_pcs: HashMap<ClassHash, HashSet<usize>>,
+}
+
 /// Caches read and write requests.
 /// The tracked changes are needed for block state commitment.
 
 // Invariant: keys cannot be deleted from fields (only used internally by the cached state).
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct StateCache {
     // Reader's cached information; initial values, read before any write operation (per cell).
     nonce_initial_values: HashMap<ContractAddress, Nonce>,
@@ -590,6 +718,7 @@ impl<'a, S: StateReader> TransactionalState<'a, S> {
         tx_executed_class_hashes: HashSet<ClassHash>,
         tx_visited_storage_entries: HashSet<StorageEntry>,
         tx_unique_state_changes_keys: StateChangesKeys,
+        tx_syscall_counter: SyscallCounter,
     ) -> StagedTransactionalState {
         let TransactionalState {
             cache,
@@ -605,6 +734,7 @@ impl<'a, S: StateReader> TransactionalState<'a, S> {
             tx_executed_class_hashes,
             tx_visited_storage_entries,
             tx_unique_state_changes_keys,
+            tx_syscall_counter,
             visited_pcs,
         }
     }
@@ -637,9 +767,18 @@ pub struct StagedTransactionalState {
     pub tx_executed_class_hashes: HashSet<ClassHash>,
     pub tx_visited_storage_entries: HashSet<StorageEntry>,
     pub tx_unique_state_changes_keys: StateChangesKeys,
+    pub tx_syscall_counter: SyscallCounter,
     pub visited_pcs: HashMap<ClassHash, HashSet<usize>>,
 }
 
+/// The changes made through a cached state, detached from its underlying state reader.
+#[derive(Debug)]
+pub struct CachedStateChanges {
+    cache: StateCache,
+    class_hash_to_class: ContractClassMapping,
+    visited_pcs: HashMap<ClassHash, HashSet<usize>>,
+}
+
 /// Holds uncommitted changes induced on Starknet contracts.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CommitmentStateDiff {
@@ -652,6 +791,32 @@ pub struct CommitmentStateDiff {
     pub class_hash_to_compiled_class_hash: IndexMap<ClassHash, CompiledClassHash>,
 }
 
+impl CommitmentStateDiff {
+    /// Returns the state diff commitment, to be published in the block header.
+    pub fn commitment(&self) -> StarkHash {
+        state_diff_commitment(self)
+    }
+}
+
+impl From<StateChanges> for CommitmentStateDiff {
+    fn from(state_changes: StateChanges) -> Self {
+        let mut storage_updates: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>> =
+            IndexMap::new();
+        for ((contract_address, key), value) in state_changes.storage_updates {
+            storage_updates.entry(contract_address).or_default().insert(key, value);
+        }
+
+        Self {
+            address_to_class_hash: IndexMap::from_iter(state_changes.class_hash_updates),
+            address_to_nonce: IndexMap::from_iter(state_changes.nonce_updates),
+            storage_updates,
+            class_hash_to_compiled_class_hash: IndexMap::from_iter(
+                state_changes.compiled_class_hash_updates,
+            ),
+        }
+    }
+}
+
 /// Used to track the state diff size, which is determined by the number of new keys.
 /// Also, can be used to accuratly measure the contribution of a single (say, transactional)
 /// state to a cumulative state diff - provides set-like functionallities for this porpuse.
@@ -715,14 +880,47 @@ impl StateChangesKeys {
 }
 
 /// Holds the state changes.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct StateChanges {
+    #[serde(deserialize_with = "deserialize_storage_updates")]
+    #[serde(serialize_with = "serialize_storage_updates")]
     pub storage_updates: HashMap<StorageEntry, StarkFelt>,
     pub nonce_updates: HashMap<ContractAddress, Nonce>,
     pub class_hash_updates: HashMap<ContractAddress, ClassHash>,
     pub compiled_class_hash_updates: HashMap<ClassHash, CompiledClassHash>,
 }
 
+type NestedStorageUpdates = BTreeMap<ContractAddress, BTreeMap<StorageKey, StarkFelt>>;
+
+// Storage updates are serialized as a mapping from contract address to the contract's updates,
+// since (e.g., JSON) map keys cannot be tuples.
+fn serialize_storage_updates<S: Serializer>(
+    storage_updates: &HashMap<StorageEntry, StarkFelt>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut nested_storage_updates = NestedStorageUpdates::new();
+    for (&(contract_address, key), &value) in storage_updates {
+        nested_storage_updates.entry(contract_address).or_default().insert(key, value);
+    }
+
+    nested_storage_updates.serialize(serializer)
+}
+
+fn deserialize_storage_updates<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<StorageEntry, StarkFelt>, D::Error> {
+    let mut storage_updates = HashMap::new();
+    for (contract_address, contract_storage_updates) in
+        NestedStorageUpdates::deserialize(deserializer)?
+    {
+        for (key, value) in contract_storage_updates {
+            storage_updates.insert((contract_address, key), value);
+        }
+    }
+
+    Ok(storage_updates)
+}
+
 impl StateChanges {
     /// Merges the given state changes into a single one. Note that the order of the state changes
     /// is important. The state changes are merged in the order they appear in the given vector.
@@ -740,6 +938,11 @@ impl StateChanges {
         merged_state_changes
     }
 
+    /// Returns the state diff commitment of these changes; see `CommitmentStateDiff::commitment`.
+    pub fn commitment(&self) -> StarkHash {
+        CommitmentStateDiff::from(self.clone()).commitment()
+    }
+
     pub fn get_modified_contracts(&self) -> HashSet<ContractAddress> {
         // Storage updates.
         let mut modified_contracts: HashSet<ContractAddress> =
@@ -757,28 +960,74 @@ impl StateChanges {
         sender_address: Option<ContractAddress>,
         fee_token_address: ContractAddress,
     ) -> StateChangesCount {
-        let mut modified_contracts = self.get_modified_contracts();
-        let mut storage_updates = self.storage_updates;
+        self.count_for_fee_charge_by_da_mode(
+            sender_address,
+            fee_token_address,
+            DataAvailabilityMode::L1,
+            DataAvailabilityMode::L1,
+        )
+    }
+
+    /// Counts the state changes published on L1, for a transaction whose nonce and fee token
+    /// balance updates may be published on L2 (volition). Changes published on L2 are not charged
+    /// as L1 data availability.
+    pub fn count_for_fee_charge_by_da_mode(
+        self,
+        sender_address: Option<ContractAddress>,
+        fee_token_address: ContractAddress,
+        nonce_data_availability_mode: DataAvailabilityMode,
+        fee_data_availability_mode: DataAvailabilityMode,
+    ) -> StateChangesCount {
+        self.count_for_fee_charge_by_payer(
+            sender_address.map(|sender_address| (sender_address, sender_address)),
+            fee_token_address,
+            nonce_data_availability_mode,
+            fee_data_availability_mode,
+        )
+    }
 
+    /// Same as [`Self::count_for_fee_charge_by_da_mode`], for a transaction whose fee is paid by
+    /// another account than its sender (e.g., a paymaster); `sender_and_fee_payer` holds the
+    /// addresses of both, if any.
+    pub fn count_for_fee_charge_by_payer(
+        mut self,
+        sender_and_fee_payer: Option<(ContractAddress, ContractAddress)>,
+        fee_token_address: ContractAddress,
+        nonce_data_availability_mode: DataAvailabilityMode,
+        fee_data_availability_mode: DataAvailabilityMode,
+    ) -> StateChangesCount {
         // For account transactions, we need to compute the transaction fee before we can execute
         // the fee transfer, and the fee should cover the state changes that happen in the
         // fee transfer. The fee transfer is going to update the balance of the sequencer
-        // and the balance of the sender contract, but we don't charge the sender for the
+        // and the balance of the fee payer, but we don't charge the fee payer for the
         // sequencer balance change as it is amortized across the block.
-        if let Some(sender_address) = sender_address {
-            let sender_balance_key = get_fee_token_var_address(sender_address);
-            // StarkFelt::default() value is zero, which must be different from the initial balance,
-            // otherwise the transaction would have failed the "max fee lower than
-            // balance" validation.
-            storage_updates.insert((fee_token_address, sender_balance_key), StarkFelt::default());
+        if let Some((sender_address, fee_payer_address)) = sender_and_fee_payer {
+            if nonce_data_availability_mode == DataAvailabilityMode::L2 {
+                self.nonce_updates.remove(&sender_address);
+            }
+
+            let fee_payer_balance_key = get_fee_token_var_address(fee_payer_address);
+            match fee_data_availability_mode {
+                // StarkFelt::default() value is zero, which must be different from the initial
+                // balance, otherwise the transaction would have failed the "max fee lower than
+                // balance" validation.
+                DataAvailabilityMode::L1 => {
+                    self.storage_updates
+                        .insert((fee_token_address, fee_payer_balance_key), StarkFelt::default());
+                }
+                DataAvailabilityMode::L2 => {
+                    self.storage_updates.remove(&(fee_token_address, fee_payer_balance_key));
+                }
+            }
         }
 
         // Exclude the fee token contract modification, since it’s charged once throughout the
         // block.
+        let mut modified_contracts = self.get_modified_contracts();
         modified_contracts.remove(&fee_token_address);
 
         StateChangesCount {
-            n_storage_updates: storage_updates.len(),
+            n_storage_updates: self.storage_updates.len(),
             n_class_hash_updates: self.class_hash_updates.len(),
             n_compiled_class_hash_updates: self.compiled_class_hash_updates.len(),
             n_modified_contracts: modified_contracts.len(),
@@ -815,6 +1064,18 @@ pub struct GlobalContractCache(pub Arc<Mutex<ContractClassLRUCache>>);
 
 pub const GLOBAL_CONTRACT_CACHE_SIZE_FOR_TEST: usize = 100;
 
+/// Usage statistics of the global contract cache, e.g., for tuning its capacity.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ContractCacheStats {
+    /// The number of class lookups served by the cache, since the last reset.
+    pub hits: u64,
+    /// The number of class lookups missing the cache, since the last reset.
+    pub misses: u64,
+    /// The number of cached classes.
+    pub size: usize,
+    pub capacity: usize,
+}
+
 impl GlobalContractCache {
     /// Locks the cache for atomic access. Although conceptually shared, writing to this cache is
     /// only possible for one writer at a time.
@@ -826,7 +1087,34 @@ impl GlobalContractCache {
         self.lock().cache_clear();
     }
 
+    /// Removes the given classes from the cache; e.g., classes whose declaration was reverted in
+    /// a reorg, and thus must not be executed.
+    pub fn invalidate_classes<'a>(
+        &mut self,
+        class_hashes: impl IntoIterator<Item = &'a ClassHash>,
+    ) {
+        let mut cache = self.lock();
+        for class_hash in class_hashes {
+            cache.cache_remove(class_hash);
+        }
+    }
+
     pub fn new(cache_size: usize) -> Self {
         Self(Arc::new(Mutex::new(ContractClassLRUCache::with_size(cache_size))))
     }
+
+    pub fn stats(&self) -> ContractCacheStats {
+        let cache = self.lock();
+        ContractCacheStats {
+            hits: cache.cache_hits().unwrap_or_default(),
+            misses: cache.cache_misses().unwrap_or_default(),
+            size: cache.cache_size(),
+            capacity: cache.cache_capacity().unwrap_or_default(),
+        }
+    }
+
+    /// Resets the hit and miss counters; e.g., between reported blocks.
+    pub fn reset_stats(&self) {
+        self.lock().cache_reset_metrics();
+    }
 }
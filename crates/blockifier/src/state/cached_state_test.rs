@@ -10,6 +10,7 @@ use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
 
 use crate::context::{BlockContext, ChainInfo};
 use crate::state::cached_state::*;
+use crate::state::state_api::DynStateReader;
 use crate::test_utils::contracts::FeatureContract;
 use crate::test_utils::dict_state_reader::DictStateReader;
 use crate::test_utils::initial_test_state::test_state;
@@ -71,6 +72,25 @@ fn get_and_set_storage_value() {
     assert_eq!(state.get_storage_at(contract_address1, key1).unwrap(), modified_storage_value1);
 }
 
+#[test]
+fn dyn_state_backend() {
+    let contract_address = contract_address!("0x100");
+    let key = StorageKey(patricia_key!("0x10"));
+    let storage_val: StarkFelt = stark_felt!("0x1");
+
+    // The backend is selected at runtime and erased behind a trait object.
+    let state_reader: DynStateReader = Box::new(DictStateReader {
+        storage_view: HashMap::from([((contract_address, key), storage_val)]),
+        ..Default::default()
+    });
+    let mut state: Box<dyn State> = Box::new(CachedState::from(state_reader));
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), storage_val);
+
+    let modified_storage_val = stark_felt!("0xA");
+    state.set_storage_at(contract_address, key, modified_storage_val).unwrap();
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), modified_storage_val);
+}
+
 #[test]
 fn cast_between_storage_mapping_types() {
     let empty_map: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>> = IndexMap::default();
@@ -311,6 +331,45 @@ fn test_from_state_changes_for_fee_charge(
     assert_eq!(state_changes_count, expected_state_changes_count);
 }
 
+#[rstest]
+#[case::l1_da(DataAvailabilityMode::L1, DataAvailabilityMode::L1, 2, 2)]
+#[case::l2_nonce_da(DataAvailabilityMode::L2, DataAvailabilityMode::L1, 2, 1)]
+#[case::l2_fee_da(DataAvailabilityMode::L1, DataAvailabilityMode::L2, 1, 2)]
+#[case::l2_da(DataAvailabilityMode::L2, DataAvailabilityMode::L2, 1, 1)]
+fn test_count_for_fee_charge_by_da_mode(
+    #[case] nonce_data_availability_mode: DataAvailabilityMode,
+    #[case] fee_data_availability_mode: DataAvailabilityMode,
+    #[case] expected_n_storage_updates: usize,
+    #[case] expected_n_modified_contracts: usize,
+) {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let sender_address = contract_address!("0x102");
+    let fee_token_address = contract_address!("0x17");
+    let contract_address = contract_address!(CONTRACT_ADDRESS);
+    let key = StorageKey(patricia_key!("0x10"));
+    state.set_storage_at(contract_address, key, stark_felt!("0x1")).unwrap();
+    state.increment_nonce(sender_address).unwrap();
+    let sender_balance_key = get_fee_token_var_address(sender_address);
+    state.set_storage_at(fee_token_address, sender_balance_key, stark_felt!("0x1999")).unwrap();
+
+    let state_changes = state.get_actual_state_changes().unwrap();
+    let state_changes_count = state_changes.count_for_fee_charge_by_da_mode(
+        Some(sender_address),
+        fee_token_address,
+        nonce_data_availability_mode,
+        fee_data_availability_mode,
+    );
+    assert_eq!(
+        state_changes_count,
+        StateChangesCount {
+            n_storage_updates: expected_n_storage_updates,
+            n_class_hash_updates: 0,
+            n_compiled_class_hash_updates: 0,
+            n_modified_contracts: expected_n_modified_contracts,
+        }
+    );
+}
+
 #[rstest]
 fn test_state_changes_merge(
     #[values(Some(contract_address!("0x102")), None)] sender_address: Option<ContractAddress>,
@@ -408,6 +467,104 @@ fn global_contract_cache_is_used() {
     assert_eq!(global_cache.lock().cache_size(), 1);
 }
 
+#[test]
+fn global_contract_cache_invalidate_classes() {
+    let mut global_cache = GlobalContractCache::new(GLOBAL_CONTRACT_CACHE_SIZE_FOR_TEST);
+    let contracts = [
+        FeatureContract::TestContract(CairoVersion::Cairo0),
+        FeatureContract::TestContract(CairoVersion::Cairo1),
+    ];
+    for contract in contracts {
+        global_cache.lock().cache_set(contract.get_class_hash(), contract.get_class());
+    }
+
+    // Invalidating the class declared in a reverted block keeps the rest of the cache.
+    let reverted_class_hash = contracts[0].get_class_hash();
+    global_cache.invalidate_classes(&[reverted_class_hash]);
+    assert_eq!(global_cache.lock().cache_size(), 1);
+    assert!(global_cache.lock().cache_get(&reverted_class_hash).is_none());
+    assert!(global_cache.lock().cache_get(&contracts[1].get_class_hash()).is_some());
+
+    // Invalidating an uncached class is a no-op.
+    global_cache.invalidate_classes(&[reverted_class_hash]);
+    assert_eq!(global_cache.lock().cache_size(), 1);
+}
+
+#[test]
+fn global_contract_cache_stats() {
+    let capacity = 2;
+    let global_cache = GlobalContractCache::new(capacity);
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo0);
+    let class_hash = test_contract.get_class_hash();
+    let state_reader = || DictStateReader {
+        class_hash_to_class: HashMap::from([(class_hash, test_contract.get_class())]),
+        ..Default::default()
+    };
+
+    // A miss, served by the underlying state.
+    let mut state = CachedState::new(state_reader(), global_cache.clone());
+    state.get_compiled_contract_class(class_hash).unwrap();
+    state.move_classes_to_global_cache();
+    // A hit, in another state sharing the cache.
+    let state = CachedState::new(state_reader(), global_cache.clone());
+    state.get_compiled_contract_class(class_hash).unwrap();
+    assert_eq!(global_cache.stats(), ContractCacheStats { hits: 1, misses: 1, size: 1, capacity });
+
+    global_cache.reset_stats();
+    assert_eq!(global_cache.stats(), ContractCacheStats { hits: 0, misses: 0, size: 1, capacity });
+}
+
+#[test]
+fn declare_invalidates_global_contract_cache() {
+    let global_cache = GlobalContractCache::new(GLOBAL_CONTRACT_CACHE_SIZE_FOR_TEST);
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let class_hash = test_contract.get_class_hash();
+    // A stale compilation of the class, e.g., of a declaration reverted in a reorg.
+    let stale_class = FeatureContract::TestContract(CairoVersion::Cairo0).get_class();
+    global_cache.lock().cache_set(class_hash, stale_class);
+
+    let mut state = CachedState::new(DictStateReader::default(), global_cache.clone());
+    state.set_contract_class(class_hash, test_contract.get_class()).unwrap();
+    assert_eq!(global_cache.lock().cache_size(), 0);
+    assert_eq!(state.get_compiled_contract_class(class_hash).unwrap(), test_contract.get_class());
+
+    state.move_classes_to_global_cache();
+    assert_eq!(global_cache.lock().cache_get(&class_hash), Some(&test_contract.get_class()));
+}
+
+#[test]
+fn test_checkpoints() {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let contract_address = contract_address!(CONTRACT_ADDRESS);
+    let key = StorageKey(patricia_key!("0x10"));
+    let write = |state: &mut CachedState<DictStateReader>, value: u8| {
+        state.set_storage_at(contract_address, key, stark_felt!(value)).unwrap();
+        state.increment_nonce(contract_address).unwrap();
+    };
+
+    write(&mut state, 1);
+    let first_checkpoint = state.create_checkpoint();
+    let first_state_diff = state.to_state_diff();
+    write(&mut state, 2);
+    let second_checkpoint = state.create_checkpoint();
+    let second_state_diff = state.to_state_diff();
+    write(&mut state, 3);
+    let third_state_diff = state.to_state_diff();
+
+    state.rollback_to(second_checkpoint.clone());
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), stark_felt!(2_u8));
+    assert_eq!(state.to_state_diff(), second_state_diff);
+    state.rollback_to(first_checkpoint);
+    assert_eq!(state.get_nonce_at(contract_address).unwrap(), Nonce(stark_felt!(1_u8)));
+    assert_eq!(state.to_state_diff(), first_state_diff);
+
+    // Checkpoints are independent; rolling back to an earlier one keeps the later ones valid.
+    state.rollback_to(second_checkpoint);
+    assert_eq!(state.to_state_diff(), second_state_diff);
+    write(&mut state, 3);
+    assert_eq!(state.to_state_diff(), third_state_diff);
+}
+
 #[test]
 fn test_cache_get_write_keys() {
     // Trivial case.
@@ -558,3 +715,28 @@ fn test_state_changes_keys() {
         }
     )
 }
+
+#[test]
+fn test_state_changes_serde() {
+    let contract_address = contract_address!("0x100");
+    let class_hash = class_hash!("0x10");
+    let state_changes = StateChanges {
+        storage_updates: HashMap::from([
+            ((contract_address, StorageKey(patricia_key!("0x1"))), stark_felt!("0x2")),
+            ((contract_address, StorageKey(patricia_key!("0x3"))), stark_felt!("0x4")),
+            ((contract_address!("0x101"), StorageKey(patricia_key!("0x1"))), stark_felt!("0x5")),
+        ]),
+        nonce_updates: HashMap::from([(contract_address, Nonce(stark_felt!(1_u8)))]),
+        class_hash_updates: HashMap::from([(contract_address, class_hash)]),
+        compiled_class_hash_updates: HashMap::from([(
+            class_hash,
+            CompiledClassHash(stark_felt!("0x20")),
+        )]),
+    };
+
+    let json = serde_json::to_value(&state_changes).unwrap();
+    // Storage updates are grouped by contract.
+    let storage_updates = json["storage_updates"].as_object().unwrap();
+    assert_eq!(storage_updates.len(), 2);
+    assert_eq!(serde_json::from_value::<StateChanges>(json).unwrap(), state_changes);
+}
@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::execution::contract_class::ContractClass;
+use crate::state::cached_state::validate_class_hash_write;
+use crate::state::state_api::{State, StateReader, StateResult};
+use crate::versioned_constants::FailedCallStatePolicy;
+
+#[cfg(test)]
+#[path = "call_frame_state_test.rs"]
+pub mod test;
+
+/// Wraps the state of a calling frame, buffering all state modifications of an inner call: storage
+/// writes, nonce increments, class hash updates (i.e., deployments and class replacements),
+/// declared classes and compiled class hashes.
+/// The buffered modifications are visible to the inner call (and to its own inner calls), and are
+/// applied to the wrapped state when the call returns successfully (`commit`). The modifications
+/// of a failed call are applied or discarded (`abort`) according to the `FailedCallStatePolicy`
+/// (see `fail`).
+///
+/// Visited PCs are execution bookkeeping rather than state, and are passed through to the wrapped
+/// state.
+pub struct CallFrameState<'a> {
+    state: &'a mut dyn State,
+    storage_writes: IndexMap<(ContractAddress, StorageKey), StarkFelt>,
+    nonce_increments: IndexMap<ContractAddress, u64>,
+    class_hash_updates: IndexMap<ContractAddress, ClassHash>,
+    contract_classes: IndexMap<ClassHash, ContractClass>,
+    compiled_class_hashes: IndexMap<ClassHash, CompiledClassHash>,
+}
+
+impl<'a> CallFrameState<'a> {
+    pub fn new(state: &'a mut dyn State) -> Self {
+        Self {
+            state,
+            storage_writes: IndexMap::default(),
+            nonce_increments: IndexMap::default(),
+            class_hash_updates: IndexMap::default(),
+            contract_classes: IndexMap::default(),
+            compiled_class_hashes: IndexMap::default(),
+        }
+    }
+
+    /// Returns the storage writes buffered so far, in order of first write.
+    pub fn storage_writes(&self) -> &IndexMap<(ContractAddress, StorageKey), StarkFelt> {
+        &self.storage_writes
+    }
+
+    /// Returns the class hash updates buffered so far, in order of first update.
+    pub fn class_hash_updates(&self) -> &IndexMap<ContractAddress, ClassHash> {
+        &self.class_hash_updates
+    }
+
+    /// Applies the buffered modifications to the wrapped state.
+    pub fn commit(self) -> StateResult<()> {
+        for (class_hash, contract_class) in self.contract_classes {
+            self.state.set_contract_class(class_hash, contract_class)?;
+        }
+        for (class_hash, compiled_class_hash) in self.compiled_class_hashes {
+            self.state.set_compiled_class_hash(class_hash, compiled_class_hash)?;
+        }
+        for (contract_address, class_hash) in self.class_hash_updates {
+            self.state.set_class_hash_at(contract_address, class_hash)?;
+        }
+        for (contract_address, n_increments) in self.nonce_increments {
+            for _ in 0..n_increments {
+                self.state.increment_nonce(contract_address)?;
+            }
+        }
+        for ((contract_address, key), value) in self.storage_writes {
+            self.state.set_storage_at(contract_address, key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Discards the buffered modifications.
+    pub fn abort(self) {}
+
+    /// Ends the frame of a failed call, applying or discarding the buffered modifications according
+    /// to the given policy.
+    pub fn fail(self, policy: FailedCallStatePolicy) -> StateResult<()> {
+        match policy {
+            FailedCallStatePolicy::Keep => self.commit(),
+            FailedCallStatePolicy::Discard => {
+                self.abort();
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> StateReader for CallFrameState<'a> {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        match self.storage_writes.get(&(contract_address, key)) {
+            Some(value) => Ok(*value),
+            None => self.state.get_storage_at(contract_address, key),
+        }
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let nonce = self.state.get_nonce_at(contract_address)?;
+        match self.nonce_increments.get(&contract_address) {
+            Some(n_increments) => {
+                let nonce_as_u64: u64 =
+                    usize::try_from(nonce.0)?.try_into().expect("Failed to convert usize to u64.");
+                Ok(Nonce(StarkFelt::from(nonce_as_u64 + n_increments)))
+            }
+            None => Ok(nonce),
+        }
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        match self.class_hash_updates.get(&contract_address) {
+            Some(class_hash) => Ok(*class_hash),
+            None => self.state.get_class_hash_at(contract_address),
+        }
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        match self.contract_classes.get(&class_hash) {
+            Some(contract_class) => Ok(contract_class.clone()),
+            None => self.state.get_compiled_contract_class(class_hash),
+        }
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        match self.compiled_class_hashes.get(&class_hash) {
+            Some(compiled_class_hash) => Ok(*compiled_class_hash),
+            None => self.state.get_compiled_class_hash(class_hash),
+        }
+    }
+}
+
+impl<'a> State for CallFrameState<'a> {
+    fn set_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        value: StarkFelt,
+    ) -> StateResult<()> {
+        self.storage_writes.insert((contract_address, key), value);
+        Ok(())
+    }
+
+    fn increment_nonce(&mut self, contract_address: ContractAddress) -> StateResult<()> {
+        // Fails on a nonce that cannot be incremented, as the wrapped state would.
+        self.get_nonce_at(contract_address)?;
+        *self.nonce_increments.entry(contract_address).or_default() += 1;
+        Ok(())
+    }
+
+    fn set_class_hash_at(
+        &mut self,
+        contract_address: ContractAddress,
+        class_hash: ClassHash,
+    ) -> StateResult<()> {
+        validate_class_hash_write(contract_address)?;
+        self.class_hash_updates.insert(contract_address, class_hash);
+        Ok(())
+    }
+
+    fn set_contract_class(
+        &mut self,
+        class_hash: ClassHash,
+        contract_class: ContractClass,
+    ) -> StateResult<()> {
+        self.contract_classes.insert(class_hash, contract_class);
+        Ok(())
+    }
+
+    fn set_compiled_class_hash(
+        &mut self,
+        class_hash: ClassHash,
+        compiled_class_hash: CompiledClassHash,
+    ) -> StateResult<()> {
+        self.compiled_class_hashes.insert(class_hash, compiled_class_hash);
+        Ok(())
+    }
+
+    fn add_visited_pcs(&mut self, class_hash: ClassHash, pcs: &HashSet<usize>) {
+        self.state.add_visited_pcs(class_hash, pcs)
+    }
+}
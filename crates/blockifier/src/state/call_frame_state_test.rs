@@ -0,0 +1,120 @@
+use indexmap::indexmap;
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::state::cached_state::CachedState;
+use crate::state::call_frame_state::CallFrameState;
+use crate::state::state_api::{State, StateReader};
+use crate::test_utils::dict_state_reader::DictStateReader;
+use crate::versioned_constants::FailedCallStatePolicy;
+
+#[test]
+fn test_buffered_writes_are_visible_in_frame() {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let contract_address = contract_address!("0x1");
+    let key = StorageKey(patricia_key!("0x10"));
+    state.set_storage_at(contract_address, key, stark_felt!("0x1")).unwrap();
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_storage_at(contract_address, key, stark_felt!("0x2")).unwrap();
+    assert_eq!(frame_state.get_storage_at(contract_address, key).unwrap(), stark_felt!("0x2"));
+
+    // Nested frames read through their parents' buffers.
+    let mut inner_frame_state = CallFrameState::new(&mut frame_state);
+    assert_eq!(
+        inner_frame_state.get_storage_at(contract_address, key).unwrap(),
+        stark_felt!("0x2")
+    );
+    inner_frame_state.set_storage_at(contract_address, key, stark_felt!("0x3")).unwrap();
+    inner_frame_state.abort();
+
+    assert_eq!(frame_state.get_storage_at(contract_address, key).unwrap(), stark_felt!("0x2"));
+    assert_eq!(
+        frame_state.storage_writes(),
+        &indexmap! { (contract_address, key) => stark_felt!("0x2") }
+    );
+}
+
+#[test]
+fn test_commit_and_abort() {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let contract_address = contract_address!("0x1");
+    let key = StorageKey(patricia_key!("0x10"));
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_storage_at(contract_address, key, stark_felt!("0x5")).unwrap();
+    frame_state.abort();
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), StarkFelt::ZERO);
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_storage_at(contract_address, key, stark_felt!("0x5")).unwrap();
+    frame_state.commit().unwrap();
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), stark_felt!("0x5"));
+}
+
+#[test]
+fn test_buffered_class_hash_updates() {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let contract_address = contract_address!("0x1");
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_class_hash_at(contract_address, class_hash!("0x2")).unwrap();
+    assert_eq!(frame_state.get_class_hash_at(contract_address).unwrap(), class_hash!("0x2"));
+    frame_state.abort();
+    assert_eq!(state.get_class_hash_at(contract_address).unwrap(), ClassHash::default());
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_class_hash_at(contract_address, class_hash!("0x2")).unwrap();
+    assert_eq!(
+        frame_state.class_hash_updates(),
+        &indexmap! { contract_address => class_hash!("0x2") }
+    );
+    frame_state.commit().unwrap();
+    assert_eq!(state.get_class_hash_at(contract_address).unwrap(), class_hash!("0x2"));
+}
+
+#[test]
+fn test_fail_policy() {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let contract_address = contract_address!("0x1");
+    let key = StorageKey(patricia_key!("0x10"));
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_storage_at(contract_address, key, stark_felt!("0x5")).unwrap();
+    frame_state.fail(FailedCallStatePolicy::Discard).unwrap();
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), StarkFelt::ZERO);
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.set_storage_at(contract_address, key, stark_felt!("0x5")).unwrap();
+    frame_state.fail(FailedCallStatePolicy::Keep).unwrap();
+    assert_eq!(state.get_storage_at(contract_address, key).unwrap(), stark_felt!("0x5"));
+}
+
+#[test]
+fn test_buffered_nonces_and_classes() {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    let contract_address = contract_address!("0x1");
+    let class_hash = class_hash!("0x2");
+    let compiled_class_hash = CompiledClassHash(stark_felt!("0x3"));
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.increment_nonce(contract_address).unwrap();
+    frame_state.increment_nonce(contract_address).unwrap();
+    frame_state.set_compiled_class_hash(class_hash, compiled_class_hash).unwrap();
+    assert_eq!(frame_state.get_nonce_at(contract_address).unwrap(), Nonce(stark_felt!(2_u8)));
+    assert_eq!(frame_state.get_compiled_class_hash(class_hash).unwrap(), compiled_class_hash);
+    frame_state.abort();
+    assert_eq!(state.get_nonce_at(contract_address).unwrap(), Nonce::default());
+    assert_eq!(state.get_compiled_class_hash(class_hash).unwrap(), CompiledClassHash::default());
+
+    let mut frame_state = CallFrameState::new(&mut state);
+    frame_state.increment_nonce(contract_address).unwrap();
+    frame_state.increment_nonce(contract_address).unwrap();
+    frame_state.set_compiled_class_hash(class_hash, compiled_class_hash).unwrap();
+    frame_state.commit().unwrap();
+    assert_eq!(state.get_nonce_at(contract_address).unwrap(), Nonce(stark_felt!(2_u8)));
+    assert_eq!(state.get_compiled_class_hash(class_hash).unwrap(), compiled_class_hash);
+}
@@ -0,0 +1,252 @@
+use std::collections::{BTreeSet, HashMap};
+
+use cairo_felt::Felt252;
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_crypto::{poseidon_hash_many, FieldElement};
+
+use crate::abi::constants;
+use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use crate::state::cached_state::{CachedState, CommitmentStateDiff, StateChangesCount};
+use crate::state::state_api::{State, StateReader, StateResult};
+use crate::utils::u128_from_usize;
+
+#[cfg(test)]
+#[path = "da_encoding_test.rs"]
+mod test;
+
+/// The number of bytes each encoded felt occupies in the published DA.
+pub const DA_BYTES_PER_FELT: usize = 32;
+
+/// A mapping from a published key (contract address or storage key) to its alias.
+pub type AliasMapping = HashMap<StarkFelt, StarkFelt>;
+
+pub fn alias_contract_address() -> StateResult<ContractAddress> {
+    Ok(ContractAddress::try_from(StarkFelt::from(constants::ALIAS_CONTRACT_ADDRESS))?)
+}
+
+fn alias_counter_storage_key() -> StateResult<StorageKey> {
+    Ok(StorageKey::try_from(StarkFelt::from(constants::ALIAS_COUNTER_STORAGE_KEY))?)
+}
+
+fn is_aliasable_key(key: StarkFelt) -> bool {
+    key >= StarkFelt::from(constants::MIN_VALUE_FOR_ALIAS_ALLOC)
+}
+
+fn is_aliasable_contract(contract_address: ContractAddress) -> bool {
+    *contract_address.0.key() > StarkFelt::from(constants::MAX_NON_COMPRESSED_CONTRACT_ADDRESS)
+}
+
+/// Allocates aliases for new keys, and maintains the alias table stored in the alias contract.
+pub struct AliasUpdater<'a, S: State + ?Sized> {
+    state: &'a mut S,
+    new_aliases: HashMap<StorageKey, StarkFelt>,
+    next_free_alias: StarkFelt,
+}
+
+impl<'a, S: State + ?Sized> AliasUpdater<'a, S> {
+    pub fn new(state: &'a mut S) -> StateResult<Self> {
+        let stored_counter =
+            state.get_storage_at(alias_contract_address()?, alias_counter_storage_key()?)?;
+        let next_free_alias = if stored_counter == StarkFelt::ZERO {
+            StarkFelt::from(constants::INITIAL_AVAILABLE_ALIAS)
+        } else {
+            stored_counter
+        };
+
+        Ok(Self { state, new_aliases: HashMap::new(), next_free_alias })
+    }
+
+    /// Allocates an alias for the given key, if it is aliasable and has no alias yet.
+    pub fn insert_alias(&mut self, key: StorageKey) -> StateResult<()> {
+        if !is_aliasable_key(*key.0.key()) || self.new_aliases.contains_key(&key) {
+            return Ok(());
+        }
+
+        let stored_alias = self.state.get_storage_at(alias_contract_address()?, key)?;
+        if stored_alias != StarkFelt::ZERO {
+            return Ok(());
+        }
+
+        self.new_aliases.insert(key, self.next_free_alias);
+        self.next_free_alias =
+            felt_to_stark_felt(&(stark_felt_to_felt(self.next_free_alias) + Felt252::from(1)));
+        Ok(())
+    }
+
+    /// Writes the newly allocated aliases, and the updated counter, to the alias contract.
+    pub fn finalize_updates(self) -> StateResult<()> {
+        if self.new_aliases.is_empty() {
+            return Ok(());
+        }
+
+        let alias_contract_address = alias_contract_address()?;
+        for (key, alias) in self.new_aliases {
+            self.state.set_storage_at(alias_contract_address, key, alias)?;
+        }
+        self.state.set_storage_at(
+            alias_contract_address,
+            alias_counter_storage_key()?,
+            self.next_free_alias,
+        )
+    }
+}
+
+/// Allocates aliases for all contract addresses and storage keys modified so far.
+/// Keys are allocated in ascending order, so that the allocation is deterministic.
+pub fn allocate_aliases<S: StateReader>(state: &mut CachedState<S>) -> StateResult<()> {
+    let state_changes = state.get_actual_state_changes()?;
+
+    let mut keys_to_alias = BTreeSet::new();
+    for contract_address in state_changes.get_modified_contracts() {
+        if is_aliasable_contract(contract_address) {
+            keys_to_alias.insert(*contract_address.0.key());
+        }
+    }
+    // Note: the alias contract itself is not aliasable, so its storage is published as is.
+    for (contract_address, key) in state_changes.storage_updates.keys() {
+        if is_aliasable_contract(*contract_address) {
+            keys_to_alias.insert(*key.0.key());
+        }
+    }
+
+    let mut alias_updater = AliasUpdater::new(state)?;
+    for key in keys_to_alias {
+        alias_updater.insert_alias(StorageKey::try_from(key)?)?;
+    }
+    alias_updater.finalize_updates()
+}
+
+/// Returns the aliases of all keys published by the given state diff.
+pub fn get_aliases(
+    state: &impl StateReader,
+    state_diff: &CommitmentStateDiff,
+) -> StateResult<AliasMapping> {
+    let alias_contract_address = alias_contract_address()?;
+    let mut aliases = AliasMapping::new();
+    let mut fetch_alias = |key: StarkFelt| -> StateResult<()> {
+        if !is_aliasable_key(key) {
+            return Ok(());
+        }
+        let alias = state.get_storage_at(alias_contract_address, StorageKey::try_from(key)?)?;
+        if alias != StarkFelt::ZERO {
+            aliases.insert(key, alias);
+        }
+        Ok(())
+    };
+
+    for contract_address in modified_contracts(state_diff) {
+        if !is_aliasable_contract(contract_address) {
+            continue;
+        }
+        fetch_alias(*contract_address.0.key())?;
+        if let Some(storage_updates) = state_diff.storage_updates.get(&contract_address) {
+            for key in storage_updates.keys() {
+                fetch_alias(*key.0.key())?;
+            }
+        }
+    }
+
+    Ok(aliases)
+}
+
+fn modified_contracts(state_diff: &CommitmentStateDiff) -> BTreeSet<ContractAddress> {
+    state_diff
+        .address_to_class_hash
+        .keys()
+        .chain(state_diff.address_to_nonce.keys())
+        .chain(state_diff.storage_updates.keys())
+        .copied()
+        .collect()
+}
+
+/// Encodes the given state diff in the DA format published by the OS; if aliases are given,
+/// contract addresses and storage keys are replaced by their aliases.
+///
+/// The encoding is: the number of modified contracts, followed by, for each contract (in
+/// ascending address order): its address, a header word packing the number of storage updates,
+/// the new nonce (0 if unchanged) and a class-update flag, the new class hash (if updated) and the
+/// `(key, value)` storage updates. Then, the number of declared classes, followed by the
+/// `(class_hash, compiled_class_hash)` pairs.
+pub fn encode_state_diff(
+    state_diff: &CommitmentStateDiff,
+    aliases: Option<&AliasMapping>,
+) -> Vec<StarkFelt> {
+    let compress =
+        |key: StarkFelt| aliases.and_then(|aliases| aliases.get(&key)).copied().unwrap_or(key);
+
+    let modified_contracts = modified_contracts(state_diff);
+    let mut encoded = vec![StarkFelt::from(u128_from_usize(modified_contracts.len()))];
+    for contract_address in modified_contracts {
+        let is_aliasable = is_aliasable_contract(contract_address);
+        let address = *contract_address.0.key();
+        encoded.push(if is_aliasable { compress(address) } else { address });
+
+        let storage_updates = state_diff.storage_updates.get(&contract_address);
+        let n_updates = storage_updates.map_or(0, |storage_updates| storage_updates.len());
+        let nonce =
+            state_diff.address_to_nonce.get(&contract_address).map_or(StarkFelt::ZERO, |n| n.0);
+        let class_hash = state_diff.address_to_class_hash.get(&contract_address);
+        let class_flag = if class_hash.is_some() { 1_u8 } else { 0_u8 };
+        let header = Felt252::from(n_updates)
+            + (stark_felt_to_felt(nonce) << 64_u32)
+            + (Felt252::from(class_flag) << 128_u32);
+        encoded.push(felt_to_stark_felt(&header));
+        if let Some(class_hash) = class_hash {
+            encoded.push(class_hash.0);
+        }
+
+        let mut storage_updates: Vec<_> = storage_updates
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (*key.0.key(), *value))
+            .collect();
+        storage_updates.sort();
+        for (key, value) in storage_updates {
+            encoded.push(if is_aliasable { compress(key) } else { key });
+            encoded.push(value);
+        }
+    }
+
+    let mut declared_classes: Vec<_> = state_diff
+        .class_hash_to_compiled_class_hash
+        .iter()
+        .map(|(class_hash, compiled_class_hash)| (class_hash.0, compiled_class_hash.0))
+        .collect();
+    declared_classes.sort();
+    encoded.push(StarkFelt::from(u128_from_usize(declared_classes.len())));
+    for (class_hash, compiled_class_hash) in declared_classes {
+        encoded.push(class_hash);
+        encoded.push(compiled_class_hash);
+    }
+
+    encoded
+}
+
+/// Returns the state diff commitment: the Poseidon hash of the (uncompressed) DA encoding of the
+/// given state diff.
+pub fn state_diff_commitment(state_diff: &CommitmentStateDiff) -> StarkHash {
+    let encoded: Vec<FieldElement> =
+        encode_state_diff(state_diff, None).into_iter().map(FieldElement::from).collect();
+    StarkHash::from(poseidon_hash_many(&encoded))
+}
+
+/// Returns the length (in felts) of the encoding of a state diff with the given counts, as
+/// produced by `encode_state_diff`; aliasing does not affect the length.
+pub fn encoded_state_diff_length(state_changes_count: &StateChangesCount) -> usize {
+    let StateChangesCount {
+        n_storage_updates,
+        n_class_hash_updates,
+        n_compiled_class_hash_updates,
+        n_modified_contracts,
+    } = *state_changes_count;
+
+    // The modified contracts and declared classes counters; an address and a header per modified
+    // contract; a class hash per class update; key-value pairs for storage updates and class
+    // hash - compiled class hash pairs for declared classes.
+    2 + 2 * n_modified_contracts
+        + n_class_hash_updates
+        + 2 * n_storage_updates
+        + 2 * n_compiled_class_hash_updates
+}
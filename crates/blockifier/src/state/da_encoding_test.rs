@@ -0,0 +1,133 @@
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+use starknet_crypto::{poseidon_hash_many, FieldElement};
+
+use crate::state::cached_state::{CachedState, CommitmentStateDiff};
+use crate::state::da_encoding::{
+    alias_contract_address, allocate_aliases, encode_state_diff, encoded_state_diff_length,
+    get_aliases,
+};
+use crate::state::state_api::{State, StateReader};
+use crate::test_utils::dict_state_reader::DictStateReader;
+
+fn get_alias(state: &CachedState<DictStateReader>, key: StarkFelt) -> StarkFelt {
+    state
+        .get_storage_at(alias_contract_address().unwrap(), StorageKey::try_from(key).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn test_alias_allocation() {
+    let contract_address = contract_address!("0x1000");
+    let large_key = StorageKey(patricia_key!("0x500"));
+    let small_key = StorageKey(patricia_key!("0x5"));
+    let mut state = CachedState::from(DictStateReader::default());
+    state.set_storage_at(contract_address, large_key, stark_felt!("0x1")).unwrap();
+    state.set_storage_at(contract_address, small_key, stark_felt!("0x1")).unwrap();
+
+    allocate_aliases(&mut state).unwrap();
+    // Keys are aliased in ascending order, starting from the initial available alias.
+    assert_eq!(get_alias(&state, stark_felt!("0x500")), stark_felt!(128_u8));
+    assert_eq!(get_alias(&state, stark_felt!("0x1000")), stark_felt!(129_u8));
+    assert_eq!(get_alias(&state, stark_felt!("0x5")), StarkFelt::ZERO);
+    // Counter.
+    assert_eq!(get_alias(&state, StarkFelt::ZERO), stark_felt!(130_u8));
+
+    // Existing aliases are kept; only new keys are allocated.
+    let new_key = StorageKey(patricia_key!("0x600"));
+    state.set_storage_at(contract_address, new_key, stark_felt!("0x1")).unwrap();
+    allocate_aliases(&mut state).unwrap();
+    assert_eq!(get_alias(&state, stark_felt!("0x1000")), stark_felt!(129_u8));
+    assert_eq!(get_alias(&state, stark_felt!("0x600")), stark_felt!(130_u8));
+    assert_eq!(get_alias(&state, StarkFelt::ZERO), stark_felt!(131_u8));
+}
+
+#[test]
+fn test_encode_state_diff_with_aliases() {
+    let contract_address = contract_address!("0x1000");
+    let key = StorageKey(patricia_key!("0x500"));
+    let value = stark_felt!("0x7");
+    let mut state = CachedState::from(DictStateReader::default());
+    state.set_storage_at(contract_address, key, value).unwrap();
+    allocate_aliases(&mut state).unwrap();
+
+    let state_diff = state.to_state_diff();
+    let aliases = get_aliases(&state, &state_diff).unwrap();
+    let uncompressed = encode_state_diff(&state_diff, None);
+    let compressed = encode_state_diff(&state_diff, Some(&aliases));
+    assert_eq!(uncompressed.len(), compressed.len());
+
+    // The alias contract (address 2) is published first, uncompressed: its storage holds the
+    // counter and the two new aliases.
+    let alias_contract_encoding = vec![
+        stark_felt!(2_u8),
+        stark_felt!(3_u8),
+        StarkFelt::ZERO,
+        stark_felt!(130_u8),
+        stark_felt!("0x500"),
+        stark_felt!(128_u8),
+        stark_felt!("0x1000"),
+        stark_felt!(129_u8),
+    ];
+    let expected_compressed = [
+        vec![stark_felt!(2_u8)],
+        alias_contract_encoding,
+        vec![stark_felt!(129_u8), stark_felt!(1_u8), stark_felt!(128_u8), value],
+        vec![StarkFelt::ZERO],
+    ]
+    .concat();
+    assert_eq!(compressed, expected_compressed);
+}
+
+#[test]
+fn test_encoded_state_diff_length() {
+    let mut state = CachedState::from(DictStateReader::default());
+    let (contract_address0, contract_address1) =
+        (contract_address!("0x1000"), contract_address!("0x1001"));
+    for key in ["0x500", "0x501"] {
+        state
+            .set_storage_at(contract_address0, StorageKey(patricia_key!(key)), stark_felt!(7_u8))
+            .unwrap();
+    }
+    state.increment_nonce(contract_address0).unwrap();
+    state.set_class_hash_at(contract_address1, class_hash!("0x3")).unwrap();
+    state
+        .set_compiled_class_hash(class_hash!("0x3"), CompiledClassHash(stark_felt!("0x4")))
+        .unwrap();
+
+    let state_changes_count = state.get_actual_state_changes().unwrap().into_keys().count();
+    assert_eq!(
+        encoded_state_diff_length(&state_changes_count),
+        encode_state_diff(&state.to_state_diff(), None).len()
+    );
+}
+
+#[test]
+fn test_state_diff_commitment() {
+    let mut state = CachedState::from(DictStateReader::default());
+    let contract_address = contract_address!("0x1000");
+    state
+        .set_storage_at(contract_address, StorageKey(patricia_key!("0x5")), stark_felt!(7_u8))
+        .unwrap();
+    state.increment_nonce(contract_address).unwrap();
+    state.set_class_hash_at(contract_address, class_hash!("0x3")).unwrap();
+
+    let state_diff = state.to_state_diff();
+    let encoded: Vec<FieldElement> =
+        encode_state_diff(&state_diff, None).into_iter().map(FieldElement::from).collect();
+    let commitment = state_diff.commitment();
+    assert_eq!(commitment, StarkHash::from(poseidon_hash_many(&encoded)));
+
+    // The commitment is independent of the representation of the changes.
+    let state_changes = state.get_actual_state_changes().unwrap();
+    assert_eq!(CommitmentStateDiff::from(state_changes.clone()), state_diff);
+    assert_eq!(state_changes.commitment(), commitment);
+
+    state
+        .set_storage_at(contract_address, StorageKey(patricia_key!("0x5")), stark_felt!(8_u8))
+        .unwrap();
+    assert_ne!(state.to_state_diff().commitment(), commitment);
+}
@@ -0,0 +1,156 @@
+use papyrus_storage::compiled_class::CasmStorageReader;
+use papyrus_storage::db::RO;
+use papyrus_storage::header::HeaderStorageReader;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageError, StorageReader};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::{StateNumber, StorageKey};
+
+use crate::blockifier::block::BlockHashHistory;
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+// Requires the test utilities of the papyrus storage.
+#[cfg(all(test, feature = "testing"))]
+#[path = "papyrus_state_reader_test.rs"]
+mod test;
+
+type RawPapyrusReader<'env> = papyrus_storage::StorageTxn<'env, RO>;
+
+/// A state reader over a papyrus storage, reading the state right before the given block; i.e.,
+/// the state the transactions of that block are executed on.
+pub struct PapyrusReader {
+    storage_reader: StorageReader,
+    latest_block: BlockNumber,
+    block_hash_history: BlockHashHistory,
+}
+
+impl PapyrusReader {
+    pub fn new(storage_reader: StorageReader, latest_block: BlockNumber) -> Self {
+        Self { storage_reader, latest_block, block_hash_history: BlockHashHistory::default() }
+    }
+
+    /// Sets the contract whose reads are served from the stored block headers, if not populated in
+    /// the storage; defaults to the Starknet block hash contract.
+    pub fn with_block_hash_history(self, block_hash_history: BlockHashHistory) -> Self {
+        Self { block_hash_history, ..self }
+    }
+
+    fn reader(&self) -> StateResult<RawPapyrusReader<'_>> {
+        self.storage_reader.begin_ro_txn().map_err(read_error)
+    }
+
+    fn state_number(&self) -> StateNumber {
+        StateNumber(self.latest_block)
+    }
+
+    /// Returns the number of the block declaring the given Cairo 1 class, if declared in the read
+    /// state.
+    fn class_declaration_block_number(
+        &self,
+        reader: &RawPapyrusReader<'_>,
+        class_hash: ClassHash,
+    ) -> StateResult<Option<BlockNumber>> {
+        let block_number = reader
+            .get_state_reader()
+            .and_then(|sr| sr.get_class_definition_block_number(&class_hash))
+            .map_err(read_error)?;
+        Ok(block_number.filter(|block_number| *block_number <= self.latest_block))
+    }
+
+    /// Returns the hash of the given block, if it precedes the read state.
+    fn block_hash(&self, block_number: BlockNumber) -> StateResult<Option<StarkFelt>> {
+        if block_number >= self.latest_block {
+            return Ok(None);
+        }
+        let header = self.reader()?.get_block_header(block_number).map_err(read_error)?;
+        Ok(header.map(|header| header.block_hash.0))
+    }
+}
+
+impl StateReader for PapyrusReader {
+    /// Block hash history cells not populated in the storage are read from the block headers.
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let value = self
+            .reader()?
+            .get_state_reader()
+            .and_then(|sr| sr.get_storage_at(self.state_number(), &contract_address, &key))
+            .map_err(read_error)?;
+        if value != StarkFelt::default() {
+            return Ok(value);
+        }
+
+        match self.block_hash_history.block_number_at(contract_address, key) {
+            Some(block_number) => Ok(self.block_hash(block_number)?.unwrap_or_default()),
+            None => Ok(value),
+        }
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let nonce = self
+            .reader()?
+            .get_state_reader()
+            .and_then(|sr| sr.get_nonce_at(self.state_number(), &contract_address))
+            .map_err(read_error)?;
+        Ok(nonce.unwrap_or_default())
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let class_hash = self
+            .reader()?
+            .get_state_reader()
+            .and_then(|sr| sr.get_class_hash_at(self.state_number(), &contract_address))
+            .map_err(read_error)?;
+        Ok(class_hash.unwrap_or_default())
+    }
+
+    /// Returns a V1 contract if found, or a V0 contract if a V1 contract is not
+    /// found, or an `Error` otherwise.
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        let reader = self.reader()?;
+        if self.class_declaration_block_number(&reader, class_hash)?.is_some() {
+            let casm_contract_class = reader.get_casm(&class_hash).map_err(read_error)?.expect(
+                "Should be able to fetch a Casm class if its definition exists, database is \
+                 inconsistent.",
+            );
+
+            return Ok(ContractClass::V1(ContractClassV1::try_from(casm_contract_class)?));
+        }
+
+        let v0_contract_class = reader
+            .get_state_reader()
+            .and_then(|sr| sr.get_deprecated_class_definition_at(self.state_number(), &class_hash))
+            .map_err(read_error)?;
+
+        match v0_contract_class {
+            Some(starknet_api_contract_class) => {
+                Ok(ContractClassV0::try_from(starknet_api_contract_class)?.into())
+            }
+            None => Err(StateError::UndeclaredClassHash(class_hash)),
+        }
+    }
+
+    /// Read from the state diff declaring the class; zero for Cairo 0 and undeclared classes.
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let reader = self.reader()?;
+        let Some(block_number) = self.class_declaration_block_number(&reader, class_hash)? else {
+            return Ok(CompiledClassHash::default());
+        };
+
+        let state_diff = reader.get_state_diff(block_number).map_err(read_error)?;
+        Ok(state_diff
+            .and_then(|state_diff| state_diff.declared_classes.get(&class_hash).copied())
+            .unwrap_or_default())
+    }
+}
+
+fn read_error(error: StorageError) -> StateError {
+    StateError::StateReadError(error.to_string())
+}
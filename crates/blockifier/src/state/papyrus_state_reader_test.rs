@@ -1,20 +1,23 @@
-use blockifier::abi::abi_utils::selector_from_name;
-use blockifier::execution::call_info::{CallExecution, Retdata};
-use blockifier::execution::entry_point::CallEntryPoint;
-use blockifier::retdata;
-use blockifier::state::cached_state::CachedState;
-use blockifier::state::state_api::StateReader;
-use blockifier::test_utils::contracts::FeatureContract;
-use blockifier::test_utils::{trivial_external_entry_point_new, CairoVersion};
 use indexmap::IndexMap;
+use papyrus_storage::header::HeaderStorageWriter;
 use papyrus_storage::state::StateStorageWriter;
-use starknet_api::block::BlockNumber;
+use starknet_api::block::{BlockHash, BlockHeader, BlockNumber};
+use starknet_api::core::ContractAddress;
 use starknet_api::hash::StarkFelt;
 use starknet_api::state::{StateDiff, StorageKey};
 use starknet_api::transaction::Calldata;
 use starknet_api::{calldata, stark_felt};
 
-use crate::state_readers::papyrus_state::PapyrusReader;
+use crate::abi::abi_utils::selector_from_name;
+use crate::blockifier::block::BlockHashHistory;
+use crate::execution::call_info::{CallExecution, Retdata};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::retdata;
+use crate::state::cached_state::CachedState;
+use crate::state::papyrus_state_reader::PapyrusReader;
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::{trivial_external_entry_point_new, CairoVersion};
 
 #[test]
 fn test_entry_point_with_papyrus_state() -> papyrus_storage::StorageResult<()> {
@@ -61,3 +64,37 @@ fn test_entry_point_with_papyrus_state() -> papyrus_storage::StorageResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_block_hash_history_from_headers() -> papyrus_storage::StorageResult<()> {
+    let ((storage_reader, mut storage_writer), _) = papyrus_storage::test_utils::get_test_storage();
+    let block_hash = BlockHash(stark_felt!(7_u8));
+    let header = BlockHeader { block_hash, block_number: BlockNumber(0), ..Default::default() };
+    storage_writer.begin_rw_txn()?.append_header(BlockNumber(0), &header)?.commit()?;
+
+    let history_address = BlockHashHistory::default().contract_address;
+    let papyrus_reader = PapyrusReader::new(storage_reader.clone(), BlockNumber(1));
+    assert_eq!(
+        papyrus_reader.get_storage_at(history_address, StorageKey::from(0_u64)).unwrap(),
+        block_hash.0
+    );
+    // Unknown blocks, and other contracts, are read from the storage.
+    assert_eq!(
+        papyrus_reader.get_storage_at(history_address, StorageKey::from(1_u64)).unwrap(),
+        StarkFelt::default()
+    );
+    let other_address = ContractAddress::from(2_u64);
+    assert_eq!(
+        papyrus_reader.get_storage_at(other_address, StorageKey::from(0_u64)).unwrap(),
+        StarkFelt::default()
+    );
+
+    // The history of blocks from the executed block on is not known yet.
+    let papyrus_reader = PapyrusReader::new(storage_reader, BlockNumber(0));
+    assert_eq!(
+        papyrus_reader.get_storage_at(history_address, StorageKey::from(0_u64)).unwrap(),
+        StarkFelt::default()
+    );
+
+    Ok(())
+}
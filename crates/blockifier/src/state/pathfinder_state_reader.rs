@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Params};
+use serde_json::Value;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::blockifier::block::BlockHashHistory;
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+#[cfg(test)]
+#[path = "pathfinder_state_reader_test.rs"]
+mod test;
+
+/// A state reader over a pathfinder database, reading the state right before the given block; i.e.,
+/// the state the transactions of that block are executed on.
+///
+/// Reads the following tables of pathfinder's SQLite layout, in which felts are stored as 32-byte
+/// big-endian blobs, and class definitions as zstd-compressed JSON:
+/// * `storage_updates(block_number, contract_address, storage_address, storage_value)`,
+///   `nonce_updates(block_number, contract_address, nonce)` and `contract_updates(block_number,
+///   contract_address, class_hash)`; the state diffs.
+/// * `class_definitions(hash, definition, block_number)`; the declared Sierra and Cairo 0 classes.
+/// * `casm_definitions(hash, definition, compiled_class_hash)`; the compiled Sierra classes.
+/// * `block_headers(number, hash)`.
+pub struct PathfinderReader {
+    connection: Mutex<Connection>,
+    block_number: BlockNumber,
+    block_hash_history: BlockHashHistory,
+}
+
+impl PathfinderReader {
+    pub fn new(connection: Connection, block_number: BlockNumber) -> Self {
+        Self {
+            connection: Mutex::new(connection),
+            block_number,
+            block_hash_history: BlockHashHistory::default(),
+        }
+    }
+
+    /// Opens the database at the given path, read-only.
+    pub fn open(path: impl AsRef<Path>, block_number: BlockNumber) -> StateResult<Self> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(read_error)?;
+        Ok(Self::new(connection, block_number))
+    }
+
+    /// Sets the contract whose reads are served from the stored block headers, if not populated in
+    /// the storage; defaults to the Starknet block hash contract.
+    pub fn with_block_hash_history(self, block_hash_history: BlockHashHistory) -> Self {
+        Self { block_hash_history, ..self }
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().expect("Pathfinder connection lock is poisoned.")
+    }
+
+    /// Returns the blob in the first column of the first row selected by the given query, if any.
+    fn query_blob(&self, sql: &str, params: impl Params) -> StateResult<Option<Vec<u8>>> {
+        self.connection().query_row(sql, params, |row| row.get(0)).optional().map_err(read_error)
+    }
+
+    fn query_felt(&self, sql: &str, params: impl Params) -> StateResult<Option<StarkFelt>> {
+        self.query_blob(sql, params)?.map(|blob| felt_from_blob(&blob)).transpose()
+    }
+
+    fn query_definition(&self, sql: &str, params: impl Params) -> StateResult<Option<String>> {
+        let Some(compressed_definition) = self.query_blob(sql, params)? else {
+            return Ok(None);
+        };
+        let definition = zstd::decode_all(compressed_definition.as_slice())
+            .map_err(|error| StateError::StateReadError(error.to_string()))?;
+        String::from_utf8(definition)
+            .map(Some)
+            .map_err(|error| StateError::StateReadError(error.to_string()))
+    }
+
+    /// Returns the latest update of the given contract's attribute before the read state.
+    fn query_contract_update(
+        &self,
+        table: &str,
+        column: &str,
+        contract_address: ContractAddress,
+    ) -> StateResult<StarkFelt> {
+        let sql = format!(
+            "SELECT {column} FROM {table} WHERE contract_address = ? AND block_number < ? ORDER \
+             BY block_number DESC LIMIT 1"
+        );
+        let value = self.query_felt(
+            &sql,
+            params![contract_address.0.key().bytes().as_slice(), self.block_number.0],
+        )?;
+        Ok(value.unwrap_or_default())
+    }
+
+    /// Returns the hash of the given block, if it precedes the read state.
+    fn block_hash(&self, block_number: BlockNumber) -> StateResult<Option<StarkFelt>> {
+        if block_number >= self.block_number {
+            return Ok(None);
+        }
+        self.query_felt("SELECT hash FROM block_headers WHERE number = ?", params![block_number.0])
+    }
+}
+
+impl StateReader for PathfinderReader {
+    /// Block hash history cells not populated in the storage are read from the block headers.
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let value = self.query_felt(
+            "SELECT storage_value FROM storage_updates WHERE contract_address = ? AND \
+             storage_address = ? AND block_number < ? ORDER BY block_number DESC LIMIT 1",
+            params![
+                contract_address.0.key().bytes().as_slice(),
+                key.0.key().bytes().as_slice(),
+                self.block_number.0
+            ],
+        )?;
+        if let Some(value) = value {
+            return Ok(value);
+        }
+
+        match self.block_hash_history.block_number_at(contract_address, key) {
+            Some(block_number) => Ok(self.block_hash(block_number)?.unwrap_or_default()),
+            None => Ok(StarkFelt::default()),
+        }
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        Ok(Nonce(self.query_contract_update("nonce_updates", "nonce", contract_address)?))
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let class_hash =
+            self.query_contract_update("contract_updates", "class_hash", contract_address)?;
+        Ok(ClassHash(class_hash))
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        let class_hash_bytes = class_hash.0.bytes().as_slice();
+        let definition = self
+            .query_definition(
+                "SELECT definition FROM class_definitions WHERE hash = ? AND block_number < ?",
+                params![class_hash_bytes, self.block_number.0],
+            )?
+            .ok_or(StateError::UndeclaredClassHash(class_hash))?;
+        let is_sierra_class = serde_json::from_str::<Value>(&definition)
+            .map_err(|error| StateError::StateReadError(error.to_string()))?
+            .get("sierra_program")
+            .is_some();
+        if !is_sierra_class {
+            return Ok(ContractClassV0::try_from_json_string(&definition)?.into());
+        }
+
+        let casm = self
+            .query_definition(
+                "SELECT definition FROM casm_definitions WHERE hash = ?",
+                params![class_hash_bytes],
+            )?
+            .ok_or(StateError::UndeclaredClassHash(class_hash))?;
+        Ok(ContractClassV1::try_from_json_string(&casm)?.into())
+    }
+
+    /// Zero for Cairo 0 and undeclared classes.
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let compiled_class_hash = self.query_felt(
+            "SELECT casm_definitions.compiled_class_hash FROM casm_definitions JOIN \
+             class_definitions ON class_definitions.hash = casm_definitions.hash WHERE \
+             casm_definitions.hash = ? AND class_definitions.block_number < ?",
+            params![class_hash.0.bytes().as_slice(), self.block_number.0],
+        )?;
+        Ok(CompiledClassHash(compiled_class_hash.unwrap_or_default()))
+    }
+}
+
+fn felt_from_blob(blob: &[u8]) -> StateResult<StarkFelt> {
+    let bytes: [u8; 32] = blob.try_into().map_err(|_| {
+        StateError::StateReadError(format!("Expected a 32-byte felt, got {} bytes.", blob.len()))
+    })?;
+    Ok(StarkFelt::new(bytes)?)
+}
+
+fn read_error(error: rusqlite::Error) -> StateError {
+    StateError::StateReadError(error.to_string())
+}
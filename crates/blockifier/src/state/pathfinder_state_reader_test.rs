@@ -0,0 +1,152 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rusqlite::{params, Connection};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::blockifier::block::BlockHashHistory;
+use crate::execution::contract_class::ContractClass;
+use crate::state::errors::StateError;
+use crate::state::pathfinder_state_reader::PathfinderReader;
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::CairoVersion;
+
+const SCHEMA: &str = "
+    CREATE TABLE storage_updates (
+        block_number INTEGER, contract_address BLOB, storage_address BLOB, storage_value BLOB
+    );
+    CREATE TABLE nonce_updates (block_number INTEGER, contract_address BLOB, nonce BLOB);
+    CREATE TABLE contract_updates (block_number INTEGER, contract_address BLOB, class_hash BLOB);
+    CREATE TABLE class_definitions (hash BLOB PRIMARY KEY, definition BLOB, block_number INTEGER);
+    CREATE TABLE casm_definitions (
+        hash BLOB PRIMARY KEY, definition BLOB, compiled_class_hash BLOB
+    );
+    CREATE TABLE block_headers (number INTEGER PRIMARY KEY, hash BLOB);
+";
+
+fn compress(definition: &str) -> Vec<u8> {
+    zstd::encode_all(definition.as_bytes(), 0).unwrap()
+}
+
+/// A database of two blocks: the first deploys a Cairo 0 contract and declares a Sierra class, and
+/// the second updates the contract.
+fn test_database(contract_address: ContractAddress) -> Connection {
+    let connection = Connection::open_in_memory().unwrap();
+    connection.execute_batch(SCHEMA).unwrap();
+    let address = contract_address.0.key().bytes().as_slice();
+    let felt = |value: u8| stark_felt!(value).bytes().to_vec();
+
+    let cairo0_contract = FeatureContract::TestContract(CairoVersion::Cairo0);
+    let cairo1_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    connection
+        .execute(
+            "INSERT INTO class_definitions VALUES (?, ?, 0), (?, ?, 0)",
+            params![
+                cairo0_contract.get_class_hash().0.bytes().as_slice(),
+                compress(&cairo0_contract.get_raw_class()),
+                cairo1_contract.get_class_hash().0.bytes().as_slice(),
+                compress(r#"{"sierra_program": []}"#),
+            ],
+        )
+        .unwrap();
+    connection
+        .execute(
+            "INSERT INTO casm_definitions VALUES (?, ?, ?)",
+            params![
+                cairo1_contract.get_class_hash().0.bytes().as_slice(),
+                compress(&cairo1_contract.get_raw_class()),
+                felt(9),
+            ],
+        )
+        .unwrap();
+    for block_number in 0..2_u8 {
+        connection
+            .execute(
+                "INSERT INTO storage_updates VALUES (?, ?, ?, ?)",
+                params![block_number, address, felt(1), felt(block_number + 10)],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO nonce_updates VALUES (?, ?, ?)",
+                params![block_number, address, felt(block_number + 1)],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO block_headers VALUES (?, ?)",
+                params![block_number, felt(block_number + 20)],
+            )
+            .unwrap();
+    }
+    connection
+        .execute(
+            "INSERT INTO contract_updates VALUES (0, ?, ?)",
+            params![address, cairo0_contract.get_class_hash().0.bytes().as_slice()],
+        )
+        .unwrap();
+
+    connection
+}
+
+#[test]
+fn test_pathfinder_reader() {
+    let contract_address = contract_address!("0x100");
+    let reader = PathfinderReader::new(test_database(contract_address), BlockNumber(1));
+    let cairo0_class_hash = FeatureContract::TestContract(CairoVersion::Cairo0).get_class_hash();
+    let cairo1_class_hash = FeatureContract::TestContract(CairoVersion::Cairo1).get_class_hash();
+
+    // Only the updates of the blocks preceding the read state apply.
+    let storage_key = StorageKey::from(1_u64);
+    assert_eq!(reader.get_storage_at(contract_address, storage_key).unwrap(), stark_felt!(10_u8));
+    assert_eq!(reader.get_nonce_at(contract_address).unwrap(), Nonce(stark_felt!(1_u8)));
+    assert_eq!(reader.get_class_hash_at(contract_address).unwrap(), cairo0_class_hash);
+
+    // Untouched values are zero.
+    let other_address = contract_address!("0x200");
+    assert_eq!(reader.get_storage_at(other_address, storage_key).unwrap(), StarkFelt::default());
+    assert_eq!(reader.get_nonce_at(other_address).unwrap(), Nonce::default());
+    assert_eq!(reader.get_class_hash_at(other_address).unwrap(), ClassHash::default());
+
+    assert_matches!(
+        reader.get_compiled_contract_class(cairo0_class_hash).unwrap(),
+        ContractClass::V0(_)
+    );
+    assert_matches!(
+        reader.get_compiled_contract_class(cairo1_class_hash).unwrap(),
+        ContractClass::V1(_)
+    );
+    assert_eq!(
+        reader.get_compiled_class_hash(cairo1_class_hash).unwrap(),
+        CompiledClassHash(stark_felt!(9_u8))
+    );
+    assert_eq!(
+        reader.get_compiled_class_hash(cairo0_class_hash).unwrap(),
+        CompiledClassHash::default()
+    );
+    let undeclared_class_hash = class_hash!("0x300");
+    assert_matches!(
+        reader.get_compiled_contract_class(undeclared_class_hash).unwrap_err(),
+        StateError::UndeclaredClassHash(class_hash) if class_hash == undeclared_class_hash
+    );
+}
+
+#[test]
+fn test_pathfinder_block_hash_history() {
+    let reader = PathfinderReader::new(test_database(contract_address!("0x100")), BlockNumber(1));
+    let history_address = BlockHashHistory::default().contract_address;
+
+    assert_eq!(
+        reader.get_storage_at(history_address, StorageKey::from(0_u64)).unwrap(),
+        stark_felt!(20_u8)
+    );
+    // The hash of the executed block is not known yet.
+    assert_eq!(
+        reader.get_storage_at(history_address, StorageKey::from(1_u64)).unwrap(),
+        StarkFelt::default()
+    );
+}
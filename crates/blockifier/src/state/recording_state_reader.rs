@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::execution::contract_class::ContractClass;
+use crate::state::cached_state::StorageEntry;
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+#[cfg(test)]
+#[path = "recording_state_reader_test.rs"]
+mod test;
+
+/// A read served by a state reader. Classes are recorded by hash only; their contents are supplied
+/// to the replay separately.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum StateRead {
+    Storage { contract_address: ContractAddress, key: StorageKey, value: StarkFelt },
+    Nonce { contract_address: ContractAddress, nonce: Nonce },
+    ClassHash { contract_address: ContractAddress, class_hash: ClassHash },
+    CompiledClass { class_hash: ClassHash, declared: bool },
+    CompiledClassHash { class_hash: ClassHash, compiled_class_hash: CompiledClassHash },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RecordedRead {
+    pub read: StateRead,
+    /// The time the underlying state reader took to serve the read.
+    pub latency: Duration,
+}
+
+/// The reads served by a state reader, in order.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateReadRecording {
+    pub reads: Vec<RecordedRead>,
+}
+
+impl StateReadRecording {
+    pub fn total_latency(&self) -> Duration {
+        self.reads.iter().map(|recorded_read| recorded_read.latency).sum()
+    }
+
+    /// Writes the recording to the given file, as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// Wraps a state reader, recording every read it serves, along with its latency; e.g., to replay
+/// the I/O of an execution with `ReplayStateReader`. Failed reads are not recorded, except for
+/// reads of undeclared classes.
+pub struct RecordingStateReader<S: StateReader> {
+    pub state: S,
+    recording: Mutex<StateReadRecording>,
+}
+
+impl<S: StateReader> RecordingStateReader<S> {
+    pub fn new(state: S) -> Self {
+        Self { state, recording: Mutex::default() }
+    }
+
+    /// Returns the reads recorded so far.
+    pub fn recording(&self) -> StateReadRecording {
+        self.lock_recording().clone()
+    }
+
+    pub fn into_recording(self) -> StateReadRecording {
+        self.recording.into_inner().expect("State read recording lock is poisoned.")
+    }
+
+    fn lock_recording(&self) -> MutexGuard<'_, StateReadRecording> {
+        self.recording.lock().expect("State read recording lock is poisoned.")
+    }
+
+    fn record(&self, read: StateRead, start: Instant) {
+        let latency = start.elapsed();
+        self.lock_recording().reads.push(RecordedRead { read, latency });
+    }
+}
+
+impl<S: StateReader> StateReader for RecordingStateReader<S> {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let start = Instant::now();
+        let value = self.state.get_storage_at(contract_address, key)?;
+        self.record(StateRead::Storage { contract_address, key, value }, start);
+        Ok(value)
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let start = Instant::now();
+        let nonce = self.state.get_nonce_at(contract_address)?;
+        self.record(StateRead::Nonce { contract_address, nonce }, start);
+        Ok(nonce)
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let start = Instant::now();
+        let class_hash = self.state.get_class_hash_at(contract_address)?;
+        self.record(StateRead::ClassHash { contract_address, class_hash }, start);
+        Ok(class_hash)
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        let start = Instant::now();
+        let result = self.state.get_compiled_contract_class(class_hash);
+        let declared = match &result {
+            Ok(_) => true,
+            Err(StateError::UndeclaredClassHash(_)) => false,
+            Err(_) => return result,
+        };
+        self.record(StateRead::CompiledClass { class_hash, declared }, start);
+        result
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let start = Instant::now();
+        let compiled_class_hash = self.state.get_compiled_class_hash(class_hash)?;
+        self.record(StateRead::CompiledClassHash { class_hash, compiled_class_hash }, start);
+        Ok(compiled_class_hash)
+    }
+}
+
+/// A state reader serving the reads of a recording, without any I/O; e.g., to benchmark the
+/// execution in isolation from the storage, or to reproduce a reported execution. Reads that were
+/// not recorded fail.
+#[derive(Debug, Default)]
+pub struct ReplayStateReader {
+    storage: HashMap<StorageEntry, StarkFelt>,
+    nonces: HashMap<ContractAddress, Nonce>,
+    class_hashes: HashMap<ContractAddress, ClassHash>,
+    declared_classes: HashMap<ClassHash, bool>,
+    compiled_class_hashes: HashMap<ClassHash, CompiledClassHash>,
+    classes: HashMap<ClassHash, ContractClass>,
+}
+
+impl ReplayStateReader {
+    /// Serves the reads of the given recording; the contents of the recorded (declared) classes
+    /// are not recorded, and are taken from the given classes.
+    pub fn new(recording: &StateReadRecording, classes: HashMap<ClassHash, ContractClass>) -> Self {
+        let mut replay = Self { classes, ..Default::default() };
+        for recorded_read in &recording.reads {
+            match recorded_read.read {
+                StateRead::Storage { contract_address, key, value } => {
+                    replay.storage.insert((contract_address, key), value);
+                }
+                StateRead::Nonce { contract_address, nonce } => {
+                    replay.nonces.insert(contract_address, nonce);
+                }
+                StateRead::ClassHash { contract_address, class_hash } => {
+                    replay.class_hashes.insert(contract_address, class_hash);
+                }
+                StateRead::CompiledClass { class_hash, declared } => {
+                    replay.declared_classes.insert(class_hash, declared);
+                }
+                StateRead::CompiledClassHash { class_hash, compiled_class_hash } => {
+                    replay.compiled_class_hashes.insert(class_hash, compiled_class_hash);
+                }
+            }
+        }
+
+        replay
+    }
+}
+
+fn not_recorded(read_description: String) -> StateError {
+    StateError::StateReadError(format!("{read_description} was not recorded."))
+}
+
+impl StateReader for ReplayStateReader {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let read_description = || format!("The storage of {contract_address:?} at {key:?}");
+        self.storage
+            .get(&(contract_address, key))
+            .copied()
+            .ok_or_else(|| not_recorded(read_description()))
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        self.nonces
+            .get(&contract_address)
+            .copied()
+            .ok_or_else(|| not_recorded(format!("The nonce of {contract_address:?}")))
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        self.class_hashes
+            .get(&contract_address)
+            .copied()
+            .ok_or_else(|| not_recorded(format!("The class hash of {contract_address:?}")))
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        match self.declared_classes.get(&class_hash) {
+            Some(true) => self.classes.get(&class_hash).cloned().ok_or_else(|| {
+                StateError::StateReadError(format!("The class {class_hash} was not supplied."))
+            }),
+            Some(false) => Err(StateError::UndeclaredClassHash(class_hash)),
+            None => Err(not_recorded(format!("The class {class_hash}"))),
+        }
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        self.compiled_class_hashes
+            .get(&class_hash)
+            .copied()
+            .ok_or_else(|| not_recorded(format!("The compiled class hash of {class_hash}")))
+    }
+}
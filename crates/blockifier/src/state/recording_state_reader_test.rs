@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkHash;
+use starknet_api::{class_hash, contract_address, patricia_key};
+
+use crate::api::execute;
+use crate::context::BlockContext;
+use crate::invoke_tx_args;
+use crate::state::cached_state::CachedState;
+use crate::state::errors::StateError;
+use crate::state::recording_state_reader::{
+    RecordingStateReader, ReplayStateReader, StateRead, StateReadRecording,
+};
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::{create_trivial_calldata, CairoVersion, BALANCE};
+use crate::transaction::test_utils::{account_invoke_tx, block_context};
+use crate::transaction::transaction_execution::Transaction;
+
+#[rstest]
+fn test_record_and_replay(block_context: BlockContext) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let state_reader = test_state(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    )
+    .state;
+    let classes = state_reader.class_hash_to_class.clone();
+    let invoke_tx = || {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            sender_address: account_contract.get_instance_address(0),
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+        }))
+    };
+
+    let mut recorded_state = CachedState::from(RecordingStateReader::new(state_reader));
+    let receipt = execute(invoke_tx(), &mut recorded_state, &block_context).unwrap();
+    let undeclared_class_hash = class_hash!("0x1234");
+    assert_matches!(
+        recorded_state.state.get_compiled_contract_class(undeclared_class_hash),
+        Err(StateError::UndeclaredClassHash(_))
+    );
+    let recording = recorded_state.state.into_recording();
+    assert!(recording.reads.iter().any(|recorded_read| matches!(
+        recorded_read.read,
+        StateRead::CompiledClass { declared: true, .. }
+    )));
+
+    // Recordings are replayed from files.
+    let path = std::env::temp_dir().join(format!("state_reads_{}.json", std::process::id()));
+    recording.save(&path).unwrap();
+    let loaded_recording = StateReadRecording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded_recording, recording);
+
+    let replay = ReplayStateReader::new(&loaded_recording, classes);
+    assert_matches!(
+        replay.get_compiled_contract_class(undeclared_class_hash),
+        Err(StateError::UndeclaredClassHash(_))
+    );
+    assert_matches!(
+        replay.get_nonce_at(contract_address!("0x5678")),
+        Err(StateError::StateReadError(_))
+    );
+    let mut replayed_state = CachedState::from(replay);
+    assert_eq!(execute(invoke_tx(), &mut replayed_state, &block_context).unwrap(), receipt);
+
+    // Classes are not recorded; replays must be supplied with them.
+    let replay_without_classes = ReplayStateReader::new(&recording, HashMap::new());
+    let mut replayed_state = CachedState::from(replay_without_classes);
+    assert!(execute(invoke_tx(), &mut replayed_state, &block_context).is_err());
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use base64::Engine;
+use flate2::read::GzDecoder;
+use serde_json::{json, Value};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use thiserror::Error;
+
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::state::cached_state::StorageEntry;
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+#[cfg(test)]
+#[path = "rpc_state_reader_test.rs"]
+mod test;
+
+// Error codes defined by the Starknet JSON-RPC specification.
+pub const CONTRACT_NOT_FOUND: i64 = 20;
+pub const CLASS_HASH_NOT_FOUND: i64 = 28;
+
+#[derive(Debug, Error)]
+pub enum RpcError {
+    #[error("Invalid node response: {0}.")]
+    InvalidResponse(String),
+    #[error("Node returned error {code}: {message}.")]
+    Node { code: i64, message: String },
+    #[error("Failed to reach the node: {0}.")]
+    Transport(String),
+}
+
+pub type RpcResult<T> = Result<T, RpcError>;
+
+impl From<RpcError> for StateError {
+    fn from(error: RpcError) -> Self {
+        StateError::StateReadError(error.to_string())
+    }
+}
+
+/// The block over whose (post-execution) state the reader reads; to re-execute the transactions of
+/// a block, read the state of its parent block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockId {
+    Hash(BlockHash),
+    Number(BlockNumber),
+    Latest,
+    Pending,
+}
+
+impl BlockId {
+    fn to_json(self) -> Value {
+        match self {
+            BlockId::Hash(block_hash) => json!({ "block_hash": felt_to_hex(block_hash.0) }),
+            BlockId::Number(block_number) => json!({ "block_number": block_number.0 }),
+            BlockId::Latest => json!("latest"),
+            BlockId::Pending => json!("pending"),
+        }
+    }
+}
+
+/// Sends JSON-RPC requests to a Starknet full node.
+pub trait RpcTransport: Send + Sync {
+    /// Returns the result of the given request; node errors are returned as `RpcError::Node`.
+    fn send_request(&self, method: &str, params: Value) -> RpcResult<Value>;
+}
+
+/// Sends requests over HTTP.
+pub struct HttpTransport {
+    url: String,
+    agent: ureq::Agent,
+    next_request_id: AtomicU64,
+}
+
+impl HttpTransport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), agent: ureq::Agent::new(), next_request_id: AtomicU64::new(0) }
+    }
+}
+
+impl RpcTransport for HttpTransport {
+    fn send_request(&self, method: &str, params: Value) -> RpcResult<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_request_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(request)
+            .map_err(|error| RpcError::Transport(error.to_string()))?
+            .into_json()
+            .map_err(|error| RpcError::InvalidResponse(error.to_string()))?;
+
+        parse_response(response)
+    }
+}
+
+/// Extracts the result (or error) of a JSON-RPC response.
+pub fn parse_response(mut response: Value) -> RpcResult<Value> {
+    if let Some(error) = response.get("error") {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+        let message = error.get("message").and_then(Value::as_str).unwrap_or_default().to_string();
+        return Err(RpcError::Node { code, message });
+    }
+
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| RpcError::InvalidResponse("missing result".to_string()))
+}
+
+#[derive(Default)]
+struct RpcStateCache {
+    storage: HashMap<StorageEntry, StarkFelt>,
+    nonces: HashMap<ContractAddress, Nonce>,
+    class_hashes: HashMap<ContractAddress, ClassHash>,
+    // Undeclared classes are cached as `None`.
+    classes: HashMap<ClassHash, Option<ContractClass>>,
+}
+
+/// A state reader fetching the state of a given block from a Starknet full node, over JSON-RPC;
+/// e.g., for re-executing historical transactions. Every value is fetched at most once.
+pub struct RpcStateReader<T: RpcTransport = HttpTransport> {
+    transport: T,
+    block_id: BlockId,
+    cache: Mutex<RpcStateCache>,
+}
+
+impl RpcStateReader<HttpTransport> {
+    /// Reads the state of the given block from the node at the given URL.
+    pub fn new(url: impl Into<String>, block_id: BlockId) -> Self {
+        Self::with_transport(HttpTransport::new(url), block_id)
+    }
+}
+
+impl<T: RpcTransport> RpcStateReader<T> {
+    pub fn with_transport(transport: T, block_id: BlockId) -> Self {
+        Self { transport, block_id, cache: Mutex::default() }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, RpcStateCache> {
+        self.cache.lock().expect("RPC state cache lock is poisoned.")
+    }
+
+    /// Returns the cached value of the given key, or fetches and caches it. The cache is not locked
+    /// while fetching.
+    fn get_or_fetch<K: Eq + Hash, V: Clone>(
+        &self,
+        cached_values: fn(&mut RpcStateCache) -> &mut HashMap<K, V>,
+        key: K,
+        fetch: impl FnOnce() -> StateResult<V>,
+    ) -> StateResult<V> {
+        if let Some(value) = cached_values(&mut self.cache()).get(&key) {
+            return Ok(value.clone());
+        }
+
+        let value = fetch()?;
+        cached_values(&mut self.cache()).insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Sends the given request; returns `None` if the node did not find the requested contract or
+    /// class.
+    fn request(&self, method: &str, params: Value) -> StateResult<Option<Value>> {
+        match self.transport.send_request(method, params) {
+            Ok(result) => Ok(Some(result)),
+            Err(RpcError::Node { code: CONTRACT_NOT_FOUND | CLASS_HASH_NOT_FOUND, .. }) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Requests a felt, defaulting to zero for undeployed contracts.
+    fn request_felt(&self, method: &str, params: Value) -> StateResult<StarkFelt> {
+        match self.request(method, params)? {
+            Some(result) => Ok(felt_from_json(&result)?),
+            None => Ok(StarkFelt::default()),
+        }
+    }
+
+    fn fetch_class(&self, class_hash: ClassHash) -> StateResult<Option<ContractClass>> {
+        let class_hash_hex = felt_to_hex(class_hash.0);
+        let params = json!({ "block_id": self.block_id.to_json(), "class_hash": class_hash_hex });
+        let Some(class) = self.request("starknet_getClass", params)? else {
+            return Ok(None);
+        };
+        if class.get("sierra_program").is_none() {
+            return Ok(Some(deprecated_class_from_json(class)?.into()));
+        }
+
+        let params = json!({ "class_hash": class_hash_hex });
+        let casm = self
+            .request("starknet_getCompiledCasm", params)?
+            .ok_or(StateError::UndeclaredClassHash(class_hash))?;
+        Ok(Some(ContractClassV1::try_from_json_string(&casm.to_string())?.into()))
+    }
+}
+
+impl<T: RpcTransport> StateReader for RpcStateReader<T> {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let fetch = || {
+            let params = json!({
+                "contract_address": felt_to_hex(*contract_address.0.key()),
+                "key": felt_to_hex(*key.0.key()),
+                "block_id": self.block_id.to_json(),
+            });
+            self.request_felt("starknet_getStorageAt", params)
+        };
+        self.get_or_fetch(|cache| &mut cache.storage, (contract_address, key), fetch)
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let fetch = || {
+            let params = json!({
+                "block_id": self.block_id.to_json(),
+                "contract_address": felt_to_hex(*contract_address.0.key()),
+            });
+            Ok(Nonce(self.request_felt("starknet_getNonce", params)?))
+        };
+        self.get_or_fetch(|cache| &mut cache.nonces, contract_address, fetch)
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let fetch = || {
+            let params = json!({
+                "block_id": self.block_id.to_json(),
+                "contract_address": felt_to_hex(*contract_address.0.key()),
+            });
+            Ok(ClassHash(self.request_felt("starknet_getClassHashAt", params)?))
+        };
+        self.get_or_fetch(|cache| &mut cache.class_hashes, contract_address, fetch)
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        self.get_or_fetch(|cache| &mut cache.classes, class_hash, || self.fetch_class(class_hash))?
+            .ok_or(StateError::UndeclaredClassHash(class_hash))
+    }
+
+    /// Compiled class hashes are not served over JSON-RPC; only those of undeclared and Cairo 0
+    /// classes, which are zero, are known.
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        match self.get_compiled_contract_class(class_hash) {
+            Ok(ContractClass::V0(_)) | Err(StateError::UndeclaredClassHash(_)) => {
+                Ok(CompiledClassHash::default())
+            }
+            Ok(ContractClass::V1(_)) => Err(StateError::StateReadError(format!(
+                "The compiled class hash of {class_hash} is not served over JSON-RPC."
+            ))),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Formats a felt as the specification requires: a hex string without leading zeros.
+fn felt_to_hex(felt: StarkFelt) -> String {
+    let hex: String = felt.bytes().iter().map(|byte| format!("{byte:02x}")).collect();
+    let trimmed = hex.trim_start_matches('0');
+    format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+}
+
+fn felt_from_json(value: &Value) -> RpcResult<StarkFelt> {
+    value
+        .as_str()
+        .and_then(|hex| StarkFelt::try_from(hex).ok())
+        .ok_or_else(|| RpcError::InvalidResponse(format!("expected a felt, got {value}")))
+}
+
+/// Converts a Cairo 0 class, as served over JSON-RPC, to an executable class; the served program
+/// is gzipped and base64-encoded.
+fn deprecated_class_from_json(mut class: Value) -> StateResult<ContractClassV0> {
+    let compressed_program = class
+        .get("program")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::InvalidResponse("missing class program".to_string()))?;
+    let gzipped_program = base64::engine::general_purpose::STANDARD
+        .decode(compressed_program)
+        .map_err(|error| RpcError::InvalidResponse(error.to_string()))?;
+    let program: Value = serde_json::from_reader(GzDecoder::new(gzipped_program.as_slice()))
+        .map_err(|error| RpcError::InvalidResponse(error.to_string()))?;
+    class["program"] = program;
+
+    Ok(ContractClassV0::try_from_json_string(&class.to_string())?)
+}
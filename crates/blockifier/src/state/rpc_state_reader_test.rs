@@ -0,0 +1,155 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+use assert_matches::assert_matches;
+use base64::Engine;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::execution::contract_class::ContractClass;
+use crate::state::errors::StateError;
+use crate::state::rpc_state_reader::{
+    parse_response, BlockId, RpcError, RpcResult, RpcStateReader, RpcTransport,
+    CLASS_HASH_NOT_FOUND, CONTRACT_NOT_FOUND,
+};
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::CairoVersion;
+
+type Handler = Box<dyn Fn(&str, &Value) -> RpcResult<Value> + Send + Sync>;
+
+/// Answers requests with the given handler, recording them.
+struct MockTransport {
+    handler: Handler,
+    requests: Mutex<Vec<(String, Value)>>,
+}
+
+impl MockTransport {
+    fn new(handler: impl Fn(&str, &Value) -> RpcResult<Value> + Send + Sync + 'static) -> Self {
+        Self { handler: Box::new(handler), requests: Mutex::default() }
+    }
+}
+
+impl RpcTransport for MockTransport {
+    fn send_request(&self, method: &str, params: Value) -> RpcResult<Value> {
+        let response = (self.handler)(method, &params);
+        self.requests.lock().unwrap().push((method.to_string(), params));
+        response
+    }
+}
+
+fn n_requests(reader: &RpcStateReader<MockTransport>) -> usize {
+    reader.transport.requests.lock().unwrap().len()
+}
+
+fn not_found(code: i64) -> RpcError {
+    RpcError::Node { code, message: "Not found".to_string() }
+}
+
+#[test]
+fn test_parse_response() {
+    let response = json!({ "jsonrpc": "2.0", "id": 0, "result": "0x7" });
+    assert_eq!(parse_response(response).unwrap(), json!("0x7"));
+    let response = json!({ "jsonrpc": "2.0", "id": 0, "error": { "code": 20, "message": "" } });
+    assert_matches!(parse_response(response), Err(RpcError::Node { code: CONTRACT_NOT_FOUND, .. }));
+}
+
+#[test]
+fn test_contract_state_reads() {
+    let deployed_contract = contract_address!("0x100");
+    let transport = MockTransport::new(move |method, params| {
+        assert_eq!(params["block_id"], json!({ "block_number": 5 }));
+        if params["contract_address"] != json!("0x100") {
+            return Err(not_found(CONTRACT_NOT_FOUND));
+        }
+        match method {
+            "starknet_getStorageAt" => {
+                assert_eq!(params["key"], json!("0x5"));
+                Ok(json!("0x7"))
+            }
+            "starknet_getNonce" => Ok(json!("0x2")),
+            "starknet_getClassHashAt" => Ok(json!("0xabc")),
+            _ => panic!("Unexpected method {method}."),
+        }
+    });
+    let reader = RpcStateReader::with_transport(transport, BlockId::Number(BlockNumber(5)));
+
+    let key = StorageKey(patricia_key!("0x5"));
+    assert_eq!(reader.get_storage_at(deployed_contract, key).unwrap(), stark_felt!("0x7"));
+    assert_eq!(reader.get_nonce_at(deployed_contract).unwrap(), Nonce(stark_felt!("0x2")));
+    assert_eq!(reader.get_class_hash_at(deployed_contract).unwrap(), class_hash!("0xabc"));
+    // Undeployed contracts have an empty state.
+    let undeployed_contract = contract_address!("0x200");
+    assert_eq!(reader.get_storage_at(undeployed_contract, key).unwrap(), StarkFelt::ZERO);
+    assert_eq!(reader.get_class_hash_at(undeployed_contract).unwrap(), ClassHash::default());
+    assert_eq!(n_requests(&reader), 5);
+
+    // Repeated reads are served from the cache.
+    reader.get_storage_at(deployed_contract, key).unwrap();
+    reader.get_nonce_at(deployed_contract).unwrap();
+    reader.get_class_hash_at(undeployed_contract).unwrap();
+    assert_eq!(n_requests(&reader), 5);
+}
+
+#[test]
+fn test_contract_class_reads() {
+    let cairo0_contract = FeatureContract::TestContract(CairoVersion::Cairo0);
+    let cairo1_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let (cairo0_class_hash, cairo1_class_hash) = (class_hash!("0x10"), class_hash!("0x11"));
+
+    // Cairo 0 programs are served gzipped and base64-encoded.
+    let mut cairo0_class: Value = serde_json::from_str(&cairo0_contract.get_raw_class()).unwrap();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(cairo0_class["program"].to_string().as_bytes()).unwrap();
+    let compressed_program =
+        base64::engine::general_purpose::STANDARD.encode(encoder.finish().unwrap());
+    cairo0_class["program"] = json!(compressed_program);
+    let cairo1_casm: Value = serde_json::from_str(&cairo1_contract.get_raw_class()).unwrap();
+
+    let transport = MockTransport::new(move |method, params| {
+        match (method, params["class_hash"].as_str().unwrap()) {
+            ("starknet_getClass", "0x10") => Ok(cairo0_class.clone()),
+            ("starknet_getClass", "0x11") => Ok(json!({ "sierra_program": [] })),
+            ("starknet_getCompiledCasm", "0x11") => Ok(cairo1_casm.clone()),
+            _ => Err(not_found(CLASS_HASH_NOT_FOUND)),
+        }
+    });
+    let reader = RpcStateReader::with_transport(transport, BlockId::Latest);
+
+    assert_eq!(
+        reader.get_compiled_contract_class(cairo0_class_hash).unwrap(),
+        cairo0_contract.get_class()
+    );
+    assert_eq!(
+        reader.get_compiled_contract_class(cairo1_class_hash).unwrap(),
+        cairo1_contract.get_class()
+    );
+    let undeclared_class_hash = class_hash!("0x12");
+    assert_matches!(
+        reader.get_compiled_contract_class(undeclared_class_hash),
+        Err(StateError::UndeclaredClassHash(class_hash)) if class_hash == undeclared_class_hash
+    );
+    assert_eq!(n_requests(&reader), 4);
+
+    // Compiled class hashes are derived from the cached classes.
+    let compiled_class_hash = reader.get_compiled_class_hash(cairo0_class_hash).unwrap();
+    assert_eq!(compiled_class_hash, CompiledClassHash::default());
+    let compiled_class_hash = reader.get_compiled_class_hash(undeclared_class_hash).unwrap();
+    assert_eq!(compiled_class_hash, CompiledClassHash::default());
+    assert_matches!(
+        reader.get_compiled_class_hash(cairo1_class_hash),
+        Err(StateError::StateReadError(_))
+    );
+    assert_matches!(
+        reader.get_compiled_contract_class(cairo1_class_hash),
+        Ok(ContractClass::V1(_))
+    );
+    assert_eq!(n_requests(&reader), 4);
+}
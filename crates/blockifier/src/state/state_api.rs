@@ -64,6 +64,45 @@ pub trait StateReader {
     }
 }
 
+/// A state reader selected at runtime (e.g., in-memory, database or RPC-backed), to avoid
+/// propagating the reader type through the application; e.g., `CachedState<DynStateReader>`.
+pub type DynStateReader = Box<dyn StateReader + Send>;
+
+/// Proxies the boxed reader, to allow using trait objects wherever a `StateReader` is expected.
+impl<S: StateReader + ?Sized> StateReader for Box<S> {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        (**self).get_storage_at(contract_address, key)
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        (**self).get_nonce_at(contract_address)
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        (**self).get_class_hash_at(contract_address)
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        (**self).get_compiled_contract_class(class_hash)
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        (**self).get_compiled_class_hash(class_hash)
+    }
+
+    fn get_fee_token_balance(
+        &mut self,
+        contract_address: ContractAddress,
+        fee_token_address: ContractAddress,
+    ) -> Result<(StarkFelt, StarkFelt), StateError> {
+        (**self).get_fee_token_balance(contract_address, fee_token_address)
+    }
+}
+
 /// A class defining the API for writing to Starknet global state.
 ///
 /// Reader functionality should be delegated to the associated type; which is passed in by
@@ -108,3 +147,47 @@ pub trait State: StateReader {
     //   entry points do not affect the final set of PCs.
     fn add_visited_pcs(&mut self, class_hash: ClassHash, pcs: &HashSet<usize>);
 }
+
+/// Proxies the boxed state, to allow using trait objects wherever a `State` is expected.
+impl<S: State + ?Sized> State for Box<S> {
+    fn set_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        value: StarkFelt,
+    ) -> StateResult<()> {
+        (**self).set_storage_at(contract_address, key, value)
+    }
+
+    fn increment_nonce(&mut self, contract_address: ContractAddress) -> StateResult<()> {
+        (**self).increment_nonce(contract_address)
+    }
+
+    fn set_class_hash_at(
+        &mut self,
+        contract_address: ContractAddress,
+        class_hash: ClassHash,
+    ) -> StateResult<()> {
+        (**self).set_class_hash_at(contract_address, class_hash)
+    }
+
+    fn set_contract_class(
+        &mut self,
+        class_hash: ClassHash,
+        contract_class: ContractClass,
+    ) -> StateResult<()> {
+        (**self).set_contract_class(class_hash, contract_class)
+    }
+
+    fn set_compiled_class_hash(
+        &mut self,
+        class_hash: ClassHash,
+        compiled_class_hash: CompiledClassHash,
+    ) -> StateResult<()> {
+        (**self).set_compiled_class_hash(class_hash, compiled_class_hash)
+    }
+
+    fn add_visited_pcs(&mut self, class_hash: ClassHash, pcs: &HashSet<usize>) {
+        (**self).add_visited_pcs(class_hash, pcs)
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockHash;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+
+use crate::state::cached_state::CommitmentStateDiff;
+use crate::state::state_api::{StateReader, StateResult};
+
+#[cfg(test)]
+#[path = "state_update_test.rs"]
+pub mod test;
+
+/// The state update of a block, as in the Starknet JSON-RPC `STATE_UPDATE` (the result of
+/// `starknet_getStateUpdate`).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateUpdate {
+    pub block_hash: BlockHash,
+    pub new_root: StarkHash,
+    pub old_root: StarkHash,
+    pub state_diff: StateUpdateDiff,
+}
+
+/// The state diff of a block, as in the Starknet JSON-RPC `STATE_DIFF`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateUpdateDiff {
+    pub storage_diffs: Vec<ContractStorageDiff>,
+    pub deprecated_declared_classes: Vec<ClassHash>,
+    pub declared_classes: Vec<DeclaredClass>,
+    pub deployed_contracts: Vec<DeployedContract>,
+    pub replaced_classes: Vec<ReplacedClass>,
+    pub nonces: Vec<NonceUpdate>,
+}
+
+/// The storage changes of a contract, as in the Starknet JSON-RPC `CONTRACT_STORAGE_DIFF_ITEM`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ContractStorageDiff {
+    pub address: ContractAddress,
+    pub storage_entries: Vec<StorageEntryUpdate>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StorageEntryUpdate {
+    pub key: StorageKey,
+    pub value: StarkFelt,
+}
+
+/// A class declared in the block (Cairo 1 only).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DeclaredClass {
+    pub class_hash: ClassHash,
+    pub compiled_class_hash: CompiledClassHash,
+}
+
+/// A contract deployed in the block.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DeployedContract {
+    pub address: ContractAddress,
+    pub class_hash: ClassHash,
+}
+
+/// A contract whose class was replaced in the block.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplacedClass {
+    pub contract_address: ContractAddress,
+    pub class_hash: ClassHash,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NonceUpdate {
+    pub contract_address: ContractAddress,
+    pub nonce: Nonce,
+}
+
+impl StateUpdateDiff {
+    /// Converts the squashed state diff of a block into its JSON-RPC form.
+    /// The state diff does not include Cairo 0 declarations, which are given separately; a class
+    /// hash update is a deployment if the contract was not deployed in the pre-block state, and a
+    /// class replacement otherwise.
+    pub fn new(
+        state_diff: &CommitmentStateDiff,
+        deprecated_declared_classes: impl IntoIterator<Item = ClassHash>,
+        pre_block_state: &impl StateReader,
+    ) -> StateResult<Self> {
+        let mut deployed_contracts = Vec::new();
+        let mut replaced_classes = Vec::new();
+        for (&address, &class_hash) in &state_diff.address_to_class_hash {
+            if pre_block_state.get_class_hash_at(address)? == ClassHash::default() {
+                deployed_contracts.push(DeployedContract { address, class_hash });
+            } else {
+                replaced_classes.push(ReplacedClass { contract_address: address, class_hash });
+            }
+        }
+
+        Ok(Self {
+            storage_diffs: state_diff
+                .storage_updates
+                .iter()
+                .map(|(&address, storage)| ContractStorageDiff {
+                    address,
+                    storage_entries: storage
+                        .iter()
+                        .map(|(&key, &value)| StorageEntryUpdate { key, value })
+                        .collect(),
+                })
+                .collect(),
+            deprecated_declared_classes: deprecated_declared_classes.into_iter().collect(),
+            declared_classes: state_diff
+                .class_hash_to_compiled_class_hash
+                .iter()
+                .map(|(&class_hash, &compiled_class_hash)| DeclaredClass {
+                    class_hash,
+                    compiled_class_hash,
+                })
+                .collect(),
+            deployed_contracts,
+            replaced_classes,
+            nonces: state_diff
+                .address_to_nonce
+                .iter()
+                .map(|(&contract_address, &nonce)| NonceUpdate { contract_address, nonce })
+                .collect(),
+        })
+    }
+}
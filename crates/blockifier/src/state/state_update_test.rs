@@ -0,0 +1,77 @@
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::State;
+use crate::state::state_update::{
+    ContractStorageDiff, DeclaredClass, DeployedContract, NonceUpdate, ReplacedClass,
+    StateUpdateDiff, StorageEntryUpdate,
+};
+use crate::test_utils::dict_state_reader::DictStateReader;
+
+#[test]
+fn test_state_update_diff() {
+    let existing_address = contract_address!("0x1000");
+    let new_address = contract_address!("0x2000");
+    let pre_block_state = DictStateReader {
+        address_to_class_hash: [(existing_address, class_hash!("0x1"))].into(),
+        ..Default::default()
+    };
+    let mut state = CachedState::from(pre_block_state);
+    state.set_class_hash_at(existing_address, class_hash!("0x2")).unwrap();
+    state.set_class_hash_at(new_address, class_hash!("0x3")).unwrap();
+    state.increment_nonce(new_address).unwrap();
+    state.set_storage_at(new_address, StorageKey(patricia_key!("0x5")), stark_felt!(7_u8)).unwrap();
+    state
+        .set_compiled_class_hash(class_hash!("0x3"), CompiledClassHash(stark_felt!("0x33")))
+        .unwrap();
+
+    let state_update_diff =
+        StateUpdateDiff::new(&state.to_state_diff(), [class_hash!("0x4")], &state.state).unwrap();
+    assert_eq!(
+        state_update_diff,
+        StateUpdateDiff {
+            storage_diffs: vec![ContractStorageDiff {
+                address: new_address,
+                storage_entries: vec![StorageEntryUpdate {
+                    key: StorageKey(patricia_key!("0x5")),
+                    value: stark_felt!(7_u8),
+                }],
+            }],
+            deprecated_declared_classes: vec![class_hash!("0x4")],
+            declared_classes: vec![DeclaredClass {
+                class_hash: class_hash!("0x3"),
+                compiled_class_hash: CompiledClassHash(stark_felt!("0x33")),
+            }],
+            deployed_contracts: vec![DeployedContract {
+                address: new_address,
+                class_hash: class_hash!("0x3"),
+            }],
+            replaced_classes: vec![ReplacedClass {
+                contract_address: existing_address,
+                class_hash: class_hash!("0x2"),
+            }],
+            nonces: vec![NonceUpdate {
+                contract_address: new_address,
+                nonce: Nonce(stark_felt!(1_u8)),
+            }],
+        }
+    );
+
+    // The JSON form follows the JSON-RPC field names.
+    let json = serde_json::to_value(&state_update_diff).unwrap();
+    for field in [
+        "storage_diffs",
+        "deprecated_declared_classes",
+        "declared_classes",
+        "deployed_contracts",
+        "replaced_classes",
+        "nonces",
+    ] {
+        assert!(json.get(field).is_some(), "Missing field: {field}.");
+    }
+    assert_eq!(serde_json::from_value::<StateUpdateDiff>(json).unwrap(), state_update_diff);
+}
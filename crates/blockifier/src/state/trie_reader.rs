@@ -0,0 +1,100 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::hash::StarkFelt;
+
+use crate::state::cached_state::StorageEntry;
+use crate::state::state_api::StateResult;
+
+#[cfg(test)]
+#[path = "trie_reader_test.rs"]
+mod test;
+
+/// A leaf in one of the Starknet global state tries, as accessed during execution.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TrieKey {
+    Storage(StorageEntry),
+    Nonce(ContractAddress),
+    ClassHash(ContractAddress),
+    CompiledClassHash(ClassHash),
+}
+
+/// The sibling hashes (or serialized nodes, depending on the backend) proving the membership of a
+/// leaf in its trie, ordered from the root down.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MerkleProof(pub Vec<StarkFelt>);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrieReadWitness {
+    pub value: StarkFelt,
+    pub proof: MerkleProof,
+}
+
+/// The proofs of all leaves read from the underlying state during execution; this is the input
+/// the prover needs in order to re-execute against the pre-state root.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExecutionWitness {
+    pub reads: HashMap<TrieKey, TrieReadWitness>,
+}
+
+impl ExecutionWitness {
+    pub fn len(&self) -> usize {
+        self.reads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reads.is_empty()
+    }
+
+    pub fn extend(&mut self, other: ExecutionWitness) {
+        self.reads.extend(other.reads);
+    }
+}
+
+/// A hook into an external trie implementation, able to prove the value of a leaf against the
+/// state root the execution is based on.
+pub trait TrieReader: Send + Sync {
+    /// Returns the value of the given leaf, alongside its Merkle proof.
+    fn get_with_proof(&self, key: TrieKey) -> StateResult<(StarkFelt, MerkleProof)>;
+}
+
+/// Collects a proof for every leaf read for the first time by a `CachedState`.
+#[derive(Clone)]
+pub struct ProofCollector {
+    trie_reader: Arc<dyn TrieReader>,
+    witness: RefCell<ExecutionWitness>,
+}
+
+impl ProofCollector {
+    pub fn new(trie_reader: Arc<dyn TrieReader>) -> Self {
+        Self { trie_reader, witness: RefCell::new(ExecutionWitness::default()) }
+    }
+
+    /// Fetches and records the proof of the given leaf, unless it was already recorded.
+    pub fn record(&self, key: TrieKey) -> StateResult<()> {
+        if self.witness.borrow().reads.contains_key(&key) {
+            return Ok(());
+        }
+
+        let (value, proof) = self.trie_reader.get_with_proof(key)?;
+        self.witness.borrow_mut().reads.insert(key, TrieReadWitness { value, proof });
+        Ok(())
+    }
+
+    pub fn witness(&self) -> ExecutionWitness {
+        self.witness.borrow().clone()
+    }
+
+    pub fn take_witness(&mut self) -> ExecutionWitness {
+        self.witness.take()
+    }
+}
+
+impl fmt::Debug for ProofCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProofCollector").field("witness", &self.witness).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{contract_address, patricia_key, stark_felt};
+
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::{State, StateReader, StateResult};
+use crate::state::trie_reader::{MerkleProof, TrieKey, TrieReadWitness, TrieReader};
+use crate::test_utils::dict_state_reader::DictStateReader;
+
+/// A trie reader that proves every leaf with a single dummy node.
+struct DummyTrieReader {
+    storage: HashMap<(ContractAddress, StorageKey), StarkFelt>,
+}
+
+impl TrieReader for DummyTrieReader {
+    fn get_with_proof(&self, key: TrieKey) -> StateResult<(StarkFelt, MerkleProof)> {
+        let value = match key {
+            TrieKey::Storage(entry) => self.storage.get(&entry).copied().unwrap_or_default(),
+            _ => StarkFelt::ZERO,
+        };
+        Ok((value, MerkleProof(vec![stark_felt!("0x1234")])))
+    }
+}
+
+#[test]
+fn proofs_are_collected_for_first_time_reads() {
+    let contract_address = contract_address!("0x100");
+    let read_key = StorageKey(patricia_key!("0x10"));
+    let written_key = StorageKey(patricia_key!("0x20"));
+    let storage = HashMap::from([((contract_address, read_key), stark_felt!("0x7"))]);
+    let trie_reader = Arc::new(DummyTrieReader { storage: storage.clone() });
+    let mut state =
+        CachedState::from(DictStateReader { storage_view: storage, ..Default::default() })
+            .with_trie_reader(trie_reader);
+
+    // Repeated reads are proven once.
+    state.get_storage_at(contract_address, read_key).unwrap();
+    state.get_storage_at(contract_address, read_key).unwrap();
+    state.get_nonce_at(contract_address).unwrap();
+    // Write-only accesses are proven once their initial value is fetched.
+    state.set_storage_at(contract_address, written_key, stark_felt!("0x8")).unwrap();
+    state.to_state_diff();
+
+    let witness = state.execution_witness().unwrap();
+    assert_eq!(witness.len(), 3);
+    assert_eq!(
+        witness.reads[&TrieKey::Storage((contract_address, read_key))],
+        TrieReadWitness {
+            value: stark_felt!("0x7"),
+            proof: MerkleProof(vec![stark_felt!("0x1234")])
+        }
+    );
+    assert!(witness.reads.contains_key(&TrieKey::Nonce(contract_address)));
+    assert!(witness.reads.contains_key(&TrieKey::Storage((contract_address, written_key))));
+    assert_eq!(state.get_nonce_at(contract_address).unwrap(), Nonce::default());
+
+    assert_eq!(state.take_execution_witness().unwrap().len(), 3);
+    assert!(state.execution_witness().unwrap().is_empty());
+}
+
+#[test]
+fn no_witness_without_trie_reader() {
+    let state = CachedState::from(DictStateReader::default());
+    state.get_nonce_at(contract_address!("0x100")).unwrap();
+    assert_eq!(state.execution_witness(), None);
+}
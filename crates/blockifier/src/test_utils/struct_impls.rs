@@ -2,12 +2,15 @@ use std::sync::Arc;
 
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use starknet_api::block::{BlockNumber, BlockTimestamp};
-use starknet_api::core::{ChainId, ContractAddress, PatriciaKey};
+use starknet_api::core::{ContractAddress, PatriciaKey};
 use starknet_api::hash::StarkHash;
 use starknet_api::{contract_address, patricia_key};
 
 use crate::blockifier::block::{BlockInfo, GasPrices};
-use crate::context::{BlockContext, ChainInfo, FeeTokenAddresses, TransactionContext};
+use crate::context::{
+    BlockContext, ChainId, ChainInfo, ExecutionExtensions, FeeTokenAddresses, ProtectedAddresses,
+    TransactionContext,
+};
 use crate::execution::call_info::{CallExecution, CallInfo, Retdata};
 use crate::execution::contract_class::{ContractClassV0, ContractClassV1};
 use crate::execution::entry_point::{
@@ -40,8 +43,7 @@ impl CallEntryPoint {
         tx_info: TransactionInfo,
         limit_steps_by_resources: bool,
     ) -> EntryPointExecutionResult<CallInfo> {
-        let tx_context =
-            TransactionContext { block_context: BlockContext::create_for_testing(), tx_info };
+        let tx_context = TransactionContext::new(BlockContext::create_for_testing(), tx_info);
         let mut context =
             EntryPointExecutionContext::new_invoke(Arc::new(tx_context), limit_steps_by_resources)
                 .unwrap();
@@ -67,8 +69,7 @@ impl CallEntryPoint {
         tx_info: TransactionInfo,
         limit_steps_by_resources: bool,
     ) -> EntryPointExecutionResult<CallInfo> {
-        let tx_context =
-            TransactionContext { block_context: BlockContext::create_for_testing(), tx_info };
+        let tx_context = TransactionContext::new(BlockContext::create_for_testing(), tx_info);
         let mut context = EntryPointExecutionContext::new_validate(
             Arc::new(tx_context),
             limit_steps_by_resources,
@@ -87,11 +88,12 @@ impl VersionedConstants {
 impl ChainInfo {
     pub fn create_for_testing() -> Self {
         Self {
-            chain_id: ChainId(CHAIN_ID_NAME.to_string()),
+            chain_id: ChainId::new(CHAIN_ID_NAME.to_string()).unwrap(),
             fee_token_addresses: FeeTokenAddresses {
                 eth_fee_token_address: contract_address!(TEST_ERC20_CONTRACT_ADDRESS),
                 strk_fee_token_address: contract_address!(TEST_ERC20_CONTRACT_ADDRESS2),
             },
+            protected_addresses: ProtectedAddresses::default(),
         }
     }
 }
@@ -109,6 +111,7 @@ impl BlockInfo {
                 strk_l1_data_gas_price: DEFAULT_STRK_L1_DATA_GAS_PRICE.try_into().unwrap(),
             },
             use_kzg_da: false,
+            fee_recipients: None,
         }
     }
 
@@ -122,7 +125,8 @@ impl BlockContext {
         Self {
             block_info: BlockInfo::create_for_testing(),
             chain_info: ChainInfo::create_for_testing(),
-            versioned_constants: VersionedConstants::create_for_testing(),
+            versioned_constants: Arc::new(VersionedConstants::create_for_testing()),
+            extensions: ExecutionExtensions::default(),
         }
     }
 
@@ -130,7 +134,8 @@ impl BlockContext {
         Self {
             block_info: BlockInfo::create_for_testing(),
             chain_info: ChainInfo::create_for_testing(),
-            versioned_constants: VersionedConstants::create_for_account_testing(),
+            versioned_constants: Arc::new(VersionedConstants::create_for_account_testing()),
+            extensions: ExecutionExtensions::default(),
         }
     }
 
@@ -1,7 +1,11 @@
 pub mod account_transaction;
 pub mod constants;
+pub mod custom_transaction;
 pub mod errors;
 pub mod objects;
+pub mod outside_execution;
+pub mod paymaster;
+pub mod simulation;
 #[cfg(any(feature = "testing", test))]
 pub mod test_utils;
 pub mod transaction_execution;
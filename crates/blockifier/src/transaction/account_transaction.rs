@@ -5,16 +5,19 @@ use starknet_api::calldata;
 use starknet_api::core::{ContractAddress, EntryPointSelector};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
-use starknet_api::transaction::{Calldata, Fee, ResourceBounds, TransactionVersion};
+use starknet_api::transaction::{Calldata, Fee, Resource, ResourceBounds, TransactionVersion};
 
 use crate::abi::abi_utils::selector_from_name;
 use crate::context::{BlockContext, TransactionContext};
-use crate::execution::call_info::{CallInfo, Retdata};
+use crate::execution::call_info::{CallInfo, Retdata, TopLevelCall};
 use crate::execution::contract_class::ContractClass;
 use crate::execution::entry_point::{CallEntryPoint, CallType, EntryPointExecutionContext};
-use crate::fee::actual_cost::{ActualCost, ActualCostBuilder};
+use crate::fee::actual_cost::{ActualCost, ActualCostBuilder, FeeEstimate};
 use crate::fee::fee_checks::{FeeCheckReportFields, PostExecutionReport};
-use crate::fee::fee_utils::{get_fee_by_gas_vector, verify_can_pay_committed_bounds};
+use crate::fee::fee_sharding::FeeAccumulator;
+use crate::fee::fee_utils::{
+    calculate_tx_gas_breakdown, get_fee_by_gas_vector, verify_can_pay_committed_bounds,
+};
 use crate::fee::gas_usage::{compute_discounted_gas_from_gas_vector, estimate_minimal_gas_vector};
 use crate::retdata;
 use crate::state::cached_state::{CachedState, TransactionalState};
@@ -24,9 +27,11 @@ use crate::transaction::errors::{
     TransactionExecutionError, TransactionFeeError, TransactionPreValidationError,
 };
 use crate::transaction::objects::{
-    HasRelatedFeeType, ResourcesMapping, TransactionExecutionInfo, TransactionExecutionResult,
-    TransactionInfo, TransactionInfoCreator, TransactionPreValidationResult,
+    FeeChargeMode, HasRelatedFeeType, ResourcesMapping, RevertReason, TransactionExecutionInfo,
+    TransactionExecutionResult, TransactionFeeResult, TransactionInfo, TransactionInfoCreator,
+    TransactionPreValidationResult,
 };
+use crate::transaction::paymaster::PaymasterConfig;
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transaction_utils::update_remaining_gas;
 use crate::transaction::transactions::{
@@ -47,7 +52,7 @@ mod flavors_test;
 mod post_execution_test;
 
 /// Represents a paid Starknet transaction.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum AccountTransaction {
     Declare(DeclareTransaction),
     DeployAccount(DeployAccountTransaction),
@@ -97,12 +102,11 @@ impl AccountTransaction {
         match self {
             Self::Declare(tx) => calldata![tx.class_hash().0],
             Self::DeployAccount(tx) => Calldata(
-                [
-                    vec![tx.class_hash().0, tx.contract_address_salt().0],
-                    (*tx.constructor_calldata().0).clone(),
-                ]
-                .concat()
-                .into(),
+                [tx.class_hash().0, tx.contract_address_salt().0]
+                    .into_iter()
+                    .chain(tx.constructor_calldata().0.iter().copied())
+                    .collect::<Vec<_>>()
+                    .into(),
             ),
             // Calldata for validation is the same calldata as for the execution itself.
             Self::Invoke(tx) => tx.calldata(),
@@ -129,7 +133,10 @@ impl AccountTransaction {
         signature.0.len()
     }
 
-    fn verify_tx_version(&self, version: TransactionVersion) -> TransactionExecutionResult<()> {
+    pub(crate) fn verify_tx_version(
+        &self,
+        version: TransactionVersion,
+    ) -> TransactionExecutionResult<()> {
         let allowed_versions: Vec<TransactionVersion> = match self {
             // Support `Declare` of version 0 in order to allow bootstrapping of a new system.
             Self::Declare(_) => {
@@ -185,35 +192,20 @@ impl AccountTransaction {
         let minimal_l1_gas_amount =
             compute_discounted_gas_from_gas_vector(&minimal_l1_gas_amount_vector, tx_context);
 
-        let TransactionContext { block_context, tx_info } = tx_context;
+        let TransactionContext { block_context, tx_info, .. } = tx_context;
         let block_info = &block_context.block_info;
         let fee_type = &tx_info.fee_type();
         match tx_info {
             TransactionInfo::Current(context) => {
-                let ResourceBounds {
-                    max_amount: max_l1_gas_amount,
-                    max_price_per_unit: max_l1_gas_price,
-                } = context.l1_resource_bounds()?;
-
-                let max_l1_gas_amount_as_u128: u128 = max_l1_gas_amount.into();
-                if max_l1_gas_amount_as_u128 < minimal_l1_gas_amount {
-                    return Err(TransactionFeeError::MaxL1GasAmountTooLow {
-                        max_l1_gas_amount,
-                        // TODO(Ori, 1/2/2024): Write an indicative expect message explaining why
-                        // the convertion works.
-                        minimal_l1_gas_amount: (minimal_l1_gas_amount
-                            .try_into()
-                            .expect("Failed to convert u128 to u64.")),
-                    })?;
-                }
-
+                // L1 data gas is charged as (discounted) L1 gas, and L2 gas is not priced by the
+                // block; hence, only the L1 gas bounds are checked.
                 let actual_l1_gas_price = block_info.gas_prices.get_gas_price_by_fee_type(fee_type);
-                if max_l1_gas_price < actual_l1_gas_price.into() {
-                    return Err(TransactionFeeError::MaxL1GasPriceTooLow {
-                        max_l1_gas_price,
-                        actual_l1_gas_price: actual_l1_gas_price.into(),
-                    })?;
-                }
+                check_resource_bounds(
+                    Resource::L1Gas,
+                    context.l1_resource_bounds()?,
+                    minimal_l1_gas_amount,
+                    actual_l1_gas_price.into(),
+                )?;
             }
             TransactionInfo::Deprecated(context) => {
                 let max_fee = context.max_fee;
@@ -254,6 +246,8 @@ impl AccountTransaction {
         })
     }
 
+    /// Runs the sender's validation, followed by the paymaster's, if any; returns their call
+    /// infos.
     fn handle_validate_tx(
         &self,
         state: &mut dyn State,
@@ -262,12 +256,67 @@ impl AccountTransaction {
         remaining_gas: &mut u64,
         validate: bool,
         limit_steps_by_resources: bool,
-    ) -> TransactionExecutionResult<Option<CallInfo>> {
-        if validate {
-            self.validate_tx(state, resources, tx_context, remaining_gas, limit_steps_by_resources)
-        } else {
-            Ok(None)
+    ) -> TransactionExecutionResult<(Option<CallInfo>, Option<CallInfo>)> {
+        if !validate {
+            return Ok((None, None));
         }
+
+        let validate_call_info = self.validate_tx(
+            state,
+            resources,
+            tx_context.clone(),
+            remaining_gas,
+            limit_steps_by_resources,
+        )?;
+        let paymaster_call_info = Self::validate_paymaster(
+            state,
+            resources,
+            tx_context,
+            remaining_gas,
+            limit_steps_by_resources,
+        )?;
+        Ok((validate_call_info, paymaster_call_info))
+    }
+
+    /// Calls the validation entry point of the transaction's paymaster (see `PaymasterConfig`);
+    /// `None` if the transaction is not sponsored.
+    pub(crate) fn validate_paymaster(
+        state: &mut dyn State,
+        resources: &mut ExecutionResources,
+        tx_context: Arc<TransactionContext>,
+        remaining_gas: &mut u64,
+        limit_steps_by_resources: bool,
+    ) -> TransactionExecutionResult<Option<CallInfo>> {
+        let (Some(config), Some(paymaster)) =
+            (tx_context.extensions.get::<PaymasterConfig>(), tx_context.paymaster()?)
+        else {
+            return Ok(None);
+        };
+
+        let mut context =
+            EntryPointExecutionContext::new_validate(tx_context, limit_steps_by_resources)?;
+        context.record_step_deltas(TopLevelCall::PaymasterValidate);
+        let storage_address = paymaster.address;
+        let validate_paymaster_call = CallEntryPoint {
+            entry_point_type: EntryPointType::External,
+            entry_point_selector: config.validate_entry_point_selector,
+            calldata: paymaster.calldata,
+            class_hash: None,
+            code_address: None,
+            storage_address,
+            caller_address: context.tx_context.tx_info.sender_address(),
+            call_type: CallType::Call,
+            initial_gas: *remaining_gas,
+        };
+
+        let paymaster_call_info =
+            validate_paymaster_call.execute(state, resources, &mut context).map_err(|error| {
+                TransactionExecutionError::ValidatePaymasterError { error, storage_address }
+            })?;
+        verify_validate_retdata(state, storage_address, &paymaster_call_info)?;
+        update_remaining_gas(remaining_gas, &paymaster_call_info);
+
+        Ok(Some(paymaster_call_info))
     }
 
     fn handle_fee(
@@ -298,35 +347,42 @@ impl AccountTransaction {
         // The most significant 128 bits of the amount transferred.
         let msb_amount = StarkFelt::from(0_u8);
 
-        let TransactionContext { block_context, tx_info } = tx_context.as_ref();
+        let TransactionContext { block_context, tx_info, .. } = tx_context.as_ref();
 
         // TODO(Gilad): add test that correct fee address is taken, once we add V3 test support.
         let storage_address = block_context.chain_info.fee_token_address(&tx_info.fee_type());
+        // Fees accrue to an accumulator when fee sharding is enabled, or when the block splits its
+        // fees; they are moved to the fee recipients of the block at its end (see
+        // `ConsolidateFees`).
+        let recipient = FeeAccumulator::for_block(block_context)
+            .map_or(block_context.block_info.sequencer_address, |accumulator| accumulator.address);
         let fee_transfer_call = CallEntryPoint {
             class_hash: None,
             code_address: None,
             entry_point_type: EntryPointType::External,
             entry_point_selector: selector_from_name(constants::TRANSFER_ENTRY_POINT_NAME),
             calldata: calldata![
-                *block_context.block_info.sequencer_address.0.key(), // Recipient.
+                *recipient.0.key(), // Recipient.
                 lsb_amount,
                 msb_amount
             ],
             storage_address,
-            caller_address: tx_info.sender_address(),
+            // The fee-token contract transfers from its caller.
+            caller_address: tx_context.fee_payer_address()?,
             call_type: CallType::Call,
             // The fee-token contract is a Cairo 0 contract, hence the initial gas is irrelevant.
-            initial_gas: block_context.versioned_constants.gas_cost("initial_gas_cost"),
+            initial_gas: block_context.versioned_constants.gas_costs().initial_gas_cost,
         };
 
         let mut context = EntryPointExecutionContext::new_invoke(tx_context, true)?;
+        context.record_step_deltas(TopLevelCall::FeeTransfer);
 
         Ok(fee_transfer_call
             .execute(state, &mut ExecutionResources::default(), &mut context)
             .map_err(TransactionFeeError::ExecuteFeeTransferError)?)
     }
 
-    fn run_execute<S: State>(
+    pub(crate) fn run_execute<S: State>(
         &self,
         state: &mut S,
         resources: &mut ExecutionResources,
@@ -346,10 +402,11 @@ impl AccountTransaction {
         tx_context: Arc<TransactionContext>,
         remaining_gas: &mut u64,
         validate: bool,
-        charge_fee: bool,
+        fee_charge_mode: FeeChargeMode,
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
+        let charge_fee = fee_charge_mode.charge_fee();
         let mut resources = ExecutionResources::default();
-        let validate_call_info: Option<CallInfo>;
+        let (validate_call_info, paymaster_call_info): (Option<CallInfo>, Option<CallInfo>);
         let execute_call_info: Option<CallInfo>;
         if matches!(self, Self::DeployAccount(_)) {
             // Handle `DeployAccount` transactions separately, due to different order of things.
@@ -357,9 +414,10 @@ impl AccountTransaction {
             // validation context.
             let mut execution_context =
                 EntryPointExecutionContext::new_validate(tx_context.clone(), charge_fee)?;
+            execution_context.record_step_deltas(TopLevelCall::Execute);
             execute_call_info =
                 self.run_execute(state, &mut resources, &mut execution_context, remaining_gas)?;
-            validate_call_info = self.handle_validate_tx(
+            (validate_call_info, paymaster_call_info) = self.handle_validate_tx(
                 state,
                 &mut resources,
                 tx_context.clone(),
@@ -370,7 +428,8 @@ impl AccountTransaction {
         } else {
             let mut execution_context =
                 EntryPointExecutionContext::new_invoke(tx_context.clone(), charge_fee)?;
-            validate_call_info = self.handle_validate_tx(
+            execution_context.record_step_deltas(TopLevelCall::Execute);
+            (validate_call_info, paymaster_call_info) = self.handle_validate_tx(
                 state,
                 &mut resources,
                 tx_context.clone(),
@@ -384,7 +443,9 @@ impl AccountTransaction {
 
         let (actual_cost, bouncer_resources) = self
             .to_actual_cost_builder(tx_context.clone())?
+            .with_fee_charge_mode(fee_charge_mode)
             .with_validate_call_info(&validate_call_info)
+            .with_paymaster_call_info(&paymaster_call_info)
             .with_execute_call_info(&execute_call_info)
             .try_add_state_changes(state)?
             .build(&resources)?;
@@ -395,6 +456,7 @@ impl AccountTransaction {
             Some(error) => Err(error.into()),
             None => Ok(ValidateExecuteCallInfo::new_accepted(
                 validate_call_info,
+                paymaster_call_info,
                 execute_call_info,
                 actual_cost,
                 bouncer_resources,
@@ -408,13 +470,15 @@ impl AccountTransaction {
         tx_context: Arc<TransactionContext>,
         remaining_gas: &mut u64,
         validate: bool,
-        charge_fee: bool,
+        fee_charge_mode: FeeChargeMode,
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
+        let charge_fee = fee_charge_mode.charge_fee();
         let mut resources = ExecutionResources::default();
         let mut execution_context =
             EntryPointExecutionContext::new_invoke(tx_context.clone(), charge_fee)?;
+        execution_context.record_step_deltas(TopLevelCall::Execute);
         // Run the validation, and if execution later fails, only keep the validation diff.
-        let validate_call_info = self.handle_validate_tx(
+        let (validate_call_info, paymaster_call_info) = self.handle_validate_tx(
             state,
             &mut resources,
             tx_context.clone(),
@@ -423,6 +487,9 @@ impl AccountTransaction {
             charge_fee,
         )?;
 
+        execution_context.subtract_steps(
+            paymaster_call_info.as_ref().map_or(0, |call_info| call_info.resources.n_steps),
+        );
         let n_allotted_execution_steps = execution_context.subtract_validation_and_overhead_steps(
             &validate_call_info,
             &self.tx_type(),
@@ -431,10 +498,13 @@ impl AccountTransaction {
 
         // Save the state changes resulting from running `validate_tx`, to be used later for
         // resource and fee calculation.
-        let actual_cost_builder_with_validation_changes = self
+        let actual_cost_builder_with_validation_calls = self
             .to_actual_cost_builder(tx_context.clone())?
+            .with_fee_charge_mode(fee_charge_mode)
             .with_validate_call_info(&validate_call_info)
-            .try_add_state_changes(state)?;
+            .with_paymaster_call_info(&paymaster_call_info);
+        let actual_cost_builder_with_validation_changes =
+            actual_cost_builder_with_validation_calls.clone().try_add_state_changes(state)?;
 
         // Create copies of state and resources for the execution.
         // Both will be rolled back if the execution is reverted or committed upon success.
@@ -451,10 +521,24 @@ impl AccountTransaction {
         // Pre-compute cost in case of revert.
         let execution_steps_consumed =
             n_allotted_execution_steps - execution_context.n_remaining_steps();
-        let (revert_cost, bouncer_revert_resources) = actual_cost_builder_with_validation_changes
-            .clone()
-            .with_reverted_steps(execution_steps_consumed)
-            .build(&resources)?;
+        // A nonce bump undone on revert (see `execute_raw`) is neither charged nor accounted.
+        let revert_policy = tx_context.block_context.versioned_constants.revert_policy;
+        let revert_cost_builder = if revert_policy.bump_nonce {
+            actual_cost_builder_with_validation_changes.clone()
+        } else {
+            actual_cost_builder_with_validation_changes.clone().without_sender_nonce_update()
+        };
+        let (revert_cost, bouncer_revert_resources) =
+            revert_cost_builder.with_reverted_steps(execution_steps_consumed).build(&resources)?;
+        // The bouncer accounts for the kept state changes, even if they are not charged.
+        let revert_cost = if revert_policy.charge_state_changes {
+            revert_cost
+        } else {
+            actual_cost_builder_with_validation_calls
+                .with_reverted_steps(execution_steps_consumed)
+                .build(&resources)?
+                .0
+        };
 
         match execution_result {
             Ok(execute_call_info) => {
@@ -484,9 +568,12 @@ impl AccountTransaction {
                         execution_state.abort();
                         Ok(ValidateExecuteCallInfo::new_reverted(
                             validate_call_info,
+                            paymaster_call_info,
                             post_execution_error.to_string(),
+                            RevertReason::FeeShortfall,
                             ActualCost {
-                                actual_fee: post_execution_report.recommended_fee(),
+                                actual_fee: revert_policy
+                                    .charged_fee(post_execution_report.recommended_fee()),
                                 ..revert_cost
                             },
                             bouncer_revert_resources,
@@ -497,6 +584,7 @@ impl AccountTransaction {
                         execution_state.commit();
                         Ok(ValidateExecuteCallInfo::new_accepted(
                             validate_call_info,
+                            paymaster_call_info,
                             execute_call_info,
                             actual_cost,
                             bouncer_resources,
@@ -504,16 +592,25 @@ impl AccountTransaction {
                     }
                 }
             }
-            Err(_) => {
+            Err(error) if execution_context.timed_out().is_some() => {
+                // Timeouts depend on the sequencer's clock and cannot be reproduced by other
+                // nodes; reject the transaction rather than revert it.
+                Err(error)
+            }
+            Err(error) => {
                 // Error during execution. Revert, even if the error is sequencer-related.
                 execution_state.abort();
                 let post_execution_report =
                     PostExecutionReport::new(state, &tx_context, &revert_cost, charge_fee)?;
+                let revert_reason = RevertReason::from_revert(&error);
                 Ok(ValidateExecuteCallInfo::new_reverted(
                     validate_call_info,
+                    paymaster_call_info,
                     execution_context.error_trace(),
+                    revert_reason,
                     ActualCost {
-                        actual_fee: post_execution_report.recommended_fee(),
+                        actual_fee: revert_policy
+                            .charged_fee(post_execution_report.recommended_fee()),
                         ..revert_cost
                     },
                     bouncer_revert_resources,
@@ -542,13 +639,19 @@ impl AccountTransaction {
         remaining_gas: &mut u64,
         tx_context: Arc<TransactionContext>,
         validate: bool,
-        charge_fee: bool,
+        fee_charge_mode: FeeChargeMode,
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
         if self.is_non_revertible(&tx_context.tx_info) {
-            return self.run_non_revertible(state, tx_context, remaining_gas, validate, charge_fee);
+            return self.run_non_revertible(
+                state,
+                tx_context,
+                remaining_gas,
+                validate,
+                fee_charge_mode,
+            );
         }
 
-        self.run_revertible(state, tx_context, remaining_gas, validate, charge_fee)
+        self.run_revertible(state, tx_context, remaining_gas, validate, fee_charge_mode)
     }
 
     pub fn to_actual_cost_builder(
@@ -566,19 +669,29 @@ impl AccountTransaction {
         }
         Ok(actual_cost_builder)
     }
-}
 
-impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
-    fn execute_raw(
+    /// Same as [`ExecutableTransaction::execute_raw`], with finer control over the fee handling;
+    /// e.g., [`FeeChargeMode::ComputeOnly`] returns the full actual cost without transferring
+    /// the fee.
+    pub fn execute_raw_with_fee_charge_mode<S: StateReader>(
         self,
         state: &mut TransactionalState<'_, S>,
         block_context: &BlockContext,
-        charge_fee: bool,
+        fee_charge_mode: FeeChargeMode,
         validate: bool,
     ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        let charge_fee = fee_charge_mode.charge_fee();
         let tx_context = Arc::new(block_context.to_tx_context(&self));
         self.verify_tx_version(tx_context.tx_info.version())?;
 
+        // Kept to undo the nonce bump of a reverted transaction, if so required by the policy.
+        let sender_address = tx_context.tx_info.sender_address();
+        let initial_nonce = if block_context.versioned_constants.revert_policy.bump_nonce {
+            None
+        } else {
+            Some(state.get_nonce_at(sender_address)?)
+        };
+
         // Nonce and fee check should be done before running user code.
         let strict_nonce_check = true;
         self.perform_pre_validation_stage(state, &tx_context, charge_fee, strict_nonce_check)?;
@@ -587,13 +700,17 @@ impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
         let mut remaining_gas = block_context.versioned_constants.tx_initial_gas();
         let ValidateExecuteCallInfo {
             validate_call_info,
+            paymaster_call_info,
             execute_call_info,
             revert_error,
+            revert_reason,
             final_cost:
                 ActualCost {
                     actual_fee: final_fee,
                     da_gas: final_da_gas,
                     actual_resources: final_resources,
+                    fee_trace,
+                    message_resources,
                 },
             bouncer_resources,
         } = self.run_or_revert(
@@ -601,23 +718,84 @@ impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
             &mut remaining_gas,
             tx_context.clone(),
             validate,
-            charge_fee,
+            fee_charge_mode,
         )?;
+        if let Some(initial_nonce) = initial_nonce.filter(|_| revert_error.is_some()) {
+            state.set_nonce(sender_address, initial_nonce);
+        }
 
         let fee_transfer_call_info = self.handle_fee(state, tx_context, final_fee, charge_fee)?;
 
         let tx_execution_info = TransactionExecutionInfo {
             validate_call_info,
+            paymaster_call_info,
             execute_call_info,
             fee_transfer_call_info,
             actual_fee: final_fee,
             da_gas: final_da_gas,
             actual_resources: final_resources,
             revert_error,
+            revert_reason,
             bouncer_resources,
+            fee_trace,
+            message_resources,
         };
         Ok(tx_execution_info)
     }
+
+    /// Estimates the cost of the transaction: executes it, without checking or charging its fee,
+    /// and without applying its state changes.
+    pub fn estimate_fee<S: StateReader>(
+        self,
+        state: &mut CachedState<S>,
+        block_context: &BlockContext,
+        validate: bool,
+    ) -> TransactionExecutionResult<FeeEstimate> {
+        let mut transactional_state = CachedState::create_transactional(state);
+        let execution_result = self.execute_raw_with_fee_charge_mode(
+            &mut transactional_state,
+            block_context,
+            FeeChargeMode::ComputeOnly,
+            validate,
+        );
+        transactional_state.abort();
+
+        let TransactionExecutionInfo {
+            actual_fee,
+            da_gas,
+            actual_resources,
+            fee_trace,
+            message_resources,
+            revert_error,
+            ..
+        } = execution_result?;
+        let gas_breakdown =
+            calculate_tx_gas_breakdown(&actual_resources, &block_context.versioned_constants)?;
+        Ok(FeeEstimate {
+            actual_cost: ActualCost {
+                actual_fee,
+                da_gas,
+                actual_resources,
+                fee_trace,
+                message_resources,
+            },
+            gas_vector: gas_breakdown.total(),
+            gas_breakdown,
+            revert_error,
+        })
+    }
+}
+
+impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
+    fn execute_raw(
+        self,
+        state: &mut TransactionalState<'_, S>,
+        block_context: &BlockContext,
+        charge_fee: bool,
+        validate: bool,
+    ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        self.execute_raw_with_fee_charge_mode(state, block_context, charge_fee.into(), validate)
+    }
 }
 
 impl TransactionInfoCreator for AccountTransaction {
@@ -633,8 +811,10 @@ impl TransactionInfoCreator for AccountTransaction {
 /// Represents a bundle of validate-execute stage execution effects.
 struct ValidateExecuteCallInfo {
     validate_call_info: Option<CallInfo>,
+    paymaster_call_info: Option<CallInfo>,
     execute_call_info: Option<CallInfo>,
     revert_error: Option<String>,
+    revert_reason: Option<RevertReason>,
     final_cost: ActualCost,
     bouncer_resources: ResourcesMapping,
 }
@@ -642,14 +822,17 @@ struct ValidateExecuteCallInfo {
 impl ValidateExecuteCallInfo {
     pub fn new_accepted(
         validate_call_info: Option<CallInfo>,
+        paymaster_call_info: Option<CallInfo>,
         execute_call_info: Option<CallInfo>,
         final_cost: ActualCost,
         bouncer_resources: ResourcesMapping,
     ) -> Self {
         Self {
             validate_call_info,
+            paymaster_call_info,
             execute_call_info,
             revert_error: None,
+            revert_reason: None,
             final_cost,
             bouncer_resources,
         }
@@ -657,14 +840,18 @@ impl ValidateExecuteCallInfo {
 
     pub fn new_reverted(
         validate_call_info: Option<CallInfo>,
+        paymaster_call_info: Option<CallInfo>,
         revert_error: String,
+        revert_reason: RevertReason,
         final_cost: ActualCost,
         bouncer_resources: ResourcesMapping,
     ) -> Self {
         Self {
             validate_call_info,
+            paymaster_call_info,
             execute_call_info: None,
             revert_error: Some(revert_error),
+            revert_reason: Some(revert_reason),
             final_cost,
             bouncer_resources,
         }
@@ -682,6 +869,7 @@ impl ValidatableTransaction for AccountTransaction {
     ) -> TransactionExecutionResult<Option<CallInfo>> {
         let mut context =
             EntryPointExecutionContext::new_validate(tx_context, limit_steps_by_resources)?;
+        context.record_step_deltas(TopLevelCall::Validate);
         let tx_info = &context.tx_context.tx_info;
         if tx_info.is_v0() {
             return Ok(None);
@@ -705,22 +893,54 @@ impl ValidatableTransaction for AccountTransaction {
                 TransactionExecutionError::ValidateTransactionError { error, storage_address }
             })?;
 
-        // Validate return data.
-        let class_hash = state.get_class_hash_at(storage_address)?;
-        let contract_class = state.get_compiled_contract_class(class_hash)?;
-        if let ContractClass::V1(_) = contract_class {
-            // The account contract class is a Cairo 1.0 contract; the `validate` entry point should
-            // return `VALID`.
-            let expected_retdata = retdata![StarkFelt::try_from(constants::VALIDATE_RETDATA)?];
-            if validate_call_info.execution.retdata != expected_retdata {
-                return Err(TransactionExecutionError::InvalidValidateReturnData {
-                    actual: validate_call_info.execution.retdata,
-                });
-            }
-        }
-
+        verify_validate_retdata(state, storage_address, &validate_call_info)?;
         update_remaining_gas(remaining_gas, &validate_call_info);
 
         Ok(Some(validate_call_info))
     }
 }
+
+/// Validates the return data of a validation entry point of the given contract.
+fn verify_validate_retdata(
+    state: &mut dyn State,
+    storage_address: ContractAddress,
+    validate_call_info: &CallInfo,
+) -> TransactionExecutionResult<()> {
+    let class_hash = state.get_class_hash_at(storage_address)?;
+    let contract_class = state.get_compiled_contract_class(class_hash)?;
+    if let ContractClass::V1(_) = contract_class {
+        // The contract class is a Cairo 1.0 contract; the `validate` entry point should return
+        // `VALID`.
+        let expected_retdata = retdata![StarkFelt::try_from(constants::VALIDATE_RETDATA)?];
+        if validate_call_info.execution.retdata != expected_retdata {
+            return Err(TransactionExecutionError::InvalidValidateReturnData {
+                actual: validate_call_info.execution.retdata.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the sender's bounds on the given resource against the minimal amount of the resource
+/// required by the transaction, and against its actual price.
+fn check_resource_bounds(
+    resource: Resource,
+    bounds: ResourceBounds,
+    minimal_amount: u128,
+    actual_price: u128,
+) -> TransactionFeeResult<()> {
+    let ResourceBounds { max_amount, max_price_per_unit: max_price } = bounds;
+    if u128::from(max_amount) < minimal_amount {
+        return Err(TransactionFeeError::MaxGasAmountTooLow {
+            resource,
+            max_amount,
+            minimal_amount,
+        });
+    }
+    if max_price < actual_price {
+        return Err(TransactionFeeError::MaxGasPriceTooLow { resource, max_price, actual_price });
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU128;
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
@@ -9,6 +10,7 @@ use rstest::rstest;
 use starknet_api::core::{
     calculate_contract_address, ClassHash, ContractAddress, Nonce, PatriciaKey,
 };
+use starknet_api::data_availability::DataAvailabilityMode;
 use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
@@ -22,12 +24,15 @@ use crate::abi::abi_utils::{
 };
 use crate::abi::constants as abi_constants;
 use crate::abi::constants::N_STEPS_RESOURCE;
+use crate::blockifier::block::{EndOfBlockHook, FeeRecipient, FeeRecipients};
 use crate::context::BlockContext;
 use crate::execution::contract_class::{ContractClass, ContractClassV1};
 use crate::execution::entry_point::EntryPointExecutionContext;
 use crate::execution::errors::EntryPointExecutionError;
 use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
 use crate::execution::syscalls::SyscallSelector;
+use crate::fee::actual_cost::RevertPolicy;
+use crate::fee::fee_sharding::{AccruedFees, ConsolidateFees, FeeAccumulator};
 use crate::fee::fee_utils::{calculate_tx_gas_vector, get_fee_by_gas_vector};
 use crate::fee::gas_usage::estimate_minimal_gas_vector;
 use crate::state::cached_state::{CachedState, StateChangesCount};
@@ -45,12 +50,15 @@ use crate::test_utils::{
 use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::constants::TRANSFER_ENTRY_POINT_NAME;
 use crate::transaction::errors::TransactionExecutionError;
-use crate::transaction::objects::{FeeType, HasRelatedFeeType, TransactionInfoCreator};
+use crate::transaction::objects::{
+    FeeChargeMode, FeeType, HasRelatedFeeType, RevertReason, TransactionExecutionInfo,
+    TransactionInfoCreator,
+};
 use crate::transaction::test_utils::{
     account_invoke_tx, block_context, calculate_class_info_for_testing,
     create_account_tx_for_validate_test, create_test_init_data, deploy_and_fund_account,
-    l1_resource_bounds, max_fee, max_resource_bounds, run_invoke_tx, FaultyAccountTxCreatorArgs,
-    TestInitData, INVALID,
+    l1_resource_bounds, max_fee, max_resource_bounds, run_invoke_tx, trivial_invoke_setup,
+    FaultyAccountTxCreatorArgs, TestInitData, INVALID,
 };
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transactions::{DeclareTransaction, ExecutableTransaction};
@@ -107,6 +115,82 @@ fn test_enforce_fee_false_works(block_context: BlockContext, #[case] version: Tr
     assert_eq!(tx_execution_info.actual_fee, Fee(0));
 }
 
+#[rstest]
+fn test_fee_charge_mode_compute_only(
+    block_context: BlockContext,
+    #[values(TransactionVersion::ONE, TransactionVersion::THREE)] version: TransactionVersion,
+) {
+    let TestInitData { mut state, account_address, contract_address, mut nonce_manager } =
+        create_test_init_data(&block_context.chain_info, CairoVersion::Cairo0);
+    let account_tx = account_invoke_tx(invoke_tx_args! {
+        max_fee: Fee(0),
+        resource_bounds: l1_resource_bounds(0, DEFAULT_STRK_L1_GAS_PRICE),
+        sender_address: account_address,
+        calldata: create_trivial_calldata(contract_address),
+        version,
+        nonce: nonce_manager.next(account_address),
+    });
+    let fee_token_address = block_context.chain_info.fee_token_address(&account_tx.fee_type());
+
+    let mut transactional_state = CachedState::create_transactional(&mut state);
+    let tx_execution_info = account_tx
+        .execute_raw_with_fee_charge_mode(
+            &mut transactional_state,
+            &block_context,
+            FeeChargeMode::ComputeOnly,
+            true,
+        )
+        .unwrap();
+    transactional_state.commit();
+
+    // The fee is computed, although the transaction does not enforce it, but not transferred.
+    assert!(!tx_execution_info.is_reverted());
+    assert!(tx_execution_info.actual_fee > Fee(0));
+    assert!(tx_execution_info.fee_transfer_call_info.is_none());
+    assert_eq!(
+        state.get_fee_token_balance(account_address, fee_token_address).unwrap(),
+        (stark_felt!(BALANCE), stark_felt!(0_u8))
+    );
+}
+
+#[rstest]
+fn test_estimate_fee(
+    block_context: BlockContext,
+    #[values(TransactionVersion::ONE, TransactionVersion::THREE)] version: TransactionVersion,
+    #[values(true, false)] validate: bool,
+) {
+    let TestInitData { mut state, account_address, contract_address, mut nonce_manager } =
+        create_test_init_data(&block_context.chain_info, CairoVersion::Cairo0);
+    let account_tx = account_invoke_tx(invoke_tx_args! {
+        max_fee: Fee(0),
+        resource_bounds: l1_resource_bounds(0, DEFAULT_STRK_L1_GAS_PRICE),
+        sender_address: account_address,
+        calldata: create_trivial_calldata(contract_address),
+        version,
+        nonce: nonce_manager.next(account_address),
+    });
+    let fee_type = account_tx.fee_type();
+
+    let fee_estimate = account_tx.estimate_fee(&mut state, &block_context, validate).unwrap();
+
+    // The estimate is consistent with the fee computation, and the state is untouched.
+    assert!(fee_estimate.revert_error.is_none());
+    let actual_cost = &fee_estimate.actual_cost;
+    assert!(actual_cost.actual_fee > Fee(0));
+    assert_eq!(
+        fee_estimate.gas_vector,
+        calculate_tx_gas_vector(&actual_cost.actual_resources, &block_context.versioned_constants)
+            .unwrap()
+    );
+    assert_eq!(fee_estimate.gas_breakdown.total(), fee_estimate.gas_vector);
+    assert!(fee_estimate.gas_breakdown.vm_resources[N_STEPS_RESOURCE].l1_gas > 0);
+    assert_eq!(
+        get_fee_by_gas_vector(&block_context.block_info, fee_estimate.gas_vector, &fee_type),
+        actual_cost.actual_fee
+    );
+    assert_eq!(state.get_nonce_at(account_address).unwrap(), Nonce::default());
+}
+
 // TODO(Dori, 15/9/2023): Convert version variance to attribute macro.
 // TODO(Dori, 10/10/2023): Add V3 case once `create_tx_info` is supported for V3.
 #[rstest]
@@ -195,7 +279,7 @@ fn test_infinite_recursion(
     mut block_context: BlockContext,
 ) {
     // Limit the number of execution steps (so we quickly hit the limit).
-    block_context.versioned_constants.invoke_tx_max_n_steps = 4000;
+    Arc::make_mut(&mut block_context.versioned_constants).invoke_tx_max_n_steps = 4000;
 
     let TestInitData { mut state, account_address, contract_address, mut nonce_manager } =
         create_test_init_data(&block_context.chain_info, CairoVersion::Cairo0);
@@ -501,6 +585,156 @@ fn test_revert_invoke(
     );
 }
 
+#[rstest]
+#[case::starknet(RevertPolicy::default())]
+#[case::waive_fee(RevertPolicy::waive_fee())]
+#[case::keep_nonce(RevertPolicy { bump_nonce: false, ..RevertPolicy::default() })]
+#[case::waive_steps(RevertPolicy { charge_reverted_steps: false, ..RevertPolicy::default() })]
+fn test_revert_policy(
+    mut block_context: BlockContext,
+    max_fee: Fee,
+    #[case] policy: RevertPolicy,
+) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo0);
+    let chain_info = &block_context.chain_info;
+    let setup = || trivial_invoke_setup(chain_info, CairoVersion::Cairo0);
+    let new_state = || setup().0;
+    let invoke_args = invoke_tx_args! {
+        max_fee,
+        calldata: create_calldata(
+            test_contract.get_instance_address(0),
+            "write_and_revert",
+            &[stark_felt!(9_u8), stark_felt!(99_u8)]
+        ),
+        version: TransactionVersion::ONE,
+        nonce: Nonce::default(),
+        ..setup().1
+    };
+    let account_address = invoke_args.sender_address;
+    let default_execution_info =
+        run_invoke_tx(&mut new_state(), &block_context, invoke_args.clone()).unwrap();
+
+    Arc::make_mut(&mut block_context.versioned_constants).revert_policy = policy;
+    let state = &mut new_state();
+    let tx_execution_info = run_invoke_tx(state, &block_context, invoke_args).unwrap();
+    assert!(tx_execution_info.revert_error.is_some());
+
+    let reverted_steps = |tx_execution_info: &TransactionExecutionInfo| {
+        tx_execution_info.actual_resources.0[N_STEPS_RESOURCE]
+            - tx_execution_info.bouncer_resources.0[N_STEPS_RESOURCE]
+    };
+    assert_eq!(reverted_steps(&tx_execution_info) > 0, policy.charge_reverted_steps);
+    // An undone nonce bump is not accounted.
+    assert_eq!(
+        tx_execution_info.bouncer_resources == default_execution_info.bouncer_resources,
+        policy.bump_nonce
+    );
+    if !policy.charge_fee {
+        assert_eq!(tx_execution_info.actual_fee, Fee(0));
+        assert!(tx_execution_info.fee_transfer_call_info.is_none());
+    } else if policy.charge_reverted_steps && policy.bump_nonce {
+        assert_eq!(tx_execution_info.actual_fee, default_execution_info.actual_fee);
+    } else {
+        // Waived steps, or an undone nonce bump, are not charged.
+        assert!(tx_execution_info.actual_fee < default_execution_info.actual_fee);
+    }
+    assert_eq!(
+        state
+            .get_fee_token_balance(account_address, chain_info.fee_token_address(&FeeType::Eth))
+            .unwrap(),
+        (stark_felt!(BALANCE - tx_execution_info.actual_fee.0), stark_felt!(0_u8))
+    );
+    let expected_nonce = Nonce(stark_felt!(u8::from(policy.bump_nonce)));
+    assert_eq!(state.get_nonce_at(account_address).unwrap(), expected_nonce);
+}
+
+#[rstest]
+fn test_l2_data_availability(
+    mut block_context: BlockContext,
+    max_resource_bounds: ResourceBoundsMapping,
+    #[values(false, true)] enable_l2_data_availability: bool,
+) {
+    Arc::make_mut(&mut block_context.versioned_constants).enable_l2_data_availability =
+        enable_l2_data_availability;
+    let actual_fee = |data_availability_mode: DataAvailabilityMode| {
+        let (mut state, invoke_args) =
+            trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo0);
+        let invoke_args = invoke_tx_args! {
+            version: TransactionVersion::THREE,
+            resource_bounds: max_resource_bounds.clone(),
+            nonce_data_availability_mode: data_availability_mode,
+            fee_data_availability_mode: data_availability_mode,
+            ..invoke_args
+        };
+        run_invoke_tx(&mut state, &block_context, invoke_args).unwrap().actual_fee
+    };
+
+    let (l1_da_fee, l2_da_fee) =
+        (actual_fee(DataAvailabilityMode::L1), actual_fee(DataAvailabilityMode::L2));
+    if enable_l2_data_availability {
+        // The nonce and fee token balance updates are not charged as L1 data availability.
+        assert!(l2_da_fee < l1_da_fee);
+    } else {
+        // By default, all state changes are priced as L1 data availability.
+        assert_eq!(l2_da_fee, l1_da_fee);
+    }
+}
+
+#[rstest]
+fn test_split_fees(mut block_context: BlockContext, max_fee: Fee) {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo0);
+    let account = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo0);
+    let chain_info = block_context.chain_info.clone();
+    let state = &mut test_state(&chain_info, BALANCE, &[(test_contract, 1), (account, 1)]);
+    let sequencer_address = block_context.block_info.sequencer_address;
+    let treasury_address = contract_address!("0x7ea5");
+    let recipient =
+        |address, share| FeeRecipient { address, share: NonZeroU128::new(share).unwrap() };
+    block_context.block_info.fee_recipients = Some(
+        FeeRecipients::new(vec![recipient(sequencer_address, 3), recipient(treasury_address, 1)])
+            .unwrap(),
+    );
+
+    let tx_execution_info = run_invoke_tx(
+        state,
+        &block_context,
+        invoke_tx_args! {
+            max_fee,
+            sender_address: account.get_instance_address(0),
+            calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+            version: TransactionVersion::ONE,
+            nonce: Nonce::default(),
+        },
+    )
+    .unwrap();
+
+    // A single transfer, to the default accumulator; the fee is split at the end of the block,
+    // and the remainder of the split goes to the first recipient.
+    let fee_token_address = chain_info.fee_token_address(&FeeType::Eth);
+    let accumulator = FeeAccumulator::new(0);
+    let fee_transfer_call_info = tx_execution_info.fee_transfer_call_info.unwrap();
+    assert_eq!(fee_transfer_call_info.call.calldata.0[0], *accumulator.address.0.key());
+    let actual_fee = tx_execution_info.actual_fee;
+    let mut accrued_fees = AccruedFees::default();
+    accrued_fees.add(fee_token_address, accumulator, actual_fee);
+    ConsolidateFees(accrued_fees).apply(state, &block_context).unwrap();
+
+    let actual_fee = actual_fee.0;
+    let treasury_fee = actual_fee / 4;
+    for (address, fee) in
+        [(sequencer_address, actual_fee - treasury_fee), (treasury_address, treasury_fee)]
+    {
+        assert_eq!(
+            state.get_fee_token_balance(address, fee_token_address).unwrap(),
+            (stark_felt!(fee), stark_felt!(0_u8))
+        );
+    }
+    assert_eq!(
+        state.get_fee_token_balance(accumulator.address, fee_token_address).unwrap(),
+        (stark_felt!(0_u8), stark_felt!(0_u8))
+    );
+}
+
 #[rstest]
 /// Tests that failing account deployment should not change state (no fee charge or nonce bump).
 fn test_fail_deploy_account(
@@ -625,7 +859,7 @@ fn test_reverted_reach_steps_limit(
         create_test_init_data(&block_context.chain_info, cairo_version);
 
     // Limit the number of execution steps (so we quickly hit the limit).
-    block_context.versioned_constants.invoke_tx_max_n_steps = 5000;
+    Arc::make_mut(&mut block_context.versioned_constants).invoke_tx_max_n_steps = 5000;
     let recursion_base_args = invoke_tx_args! {
         max_fee,
         resource_bounds: max_resource_bounds,
@@ -948,6 +1182,7 @@ fn test_insufficient_max_fee_reverts(
     .unwrap();
     assert!(tx_execution_info2.is_reverted());
     assert!(tx_execution_info2.actual_fee == actual_fee_depth1);
+    assert_eq!(tx_execution_info2.revert_reason, Some(RevertReason::FeeShortfall));
     assert!(tx_execution_info2.revert_error.unwrap().starts_with("Insufficient max fee"));
 
     // Invoke the `recurse` function with depth of 824 and the actual fee of depth 1 as max_fee.
@@ -966,6 +1201,7 @@ fn test_insufficient_max_fee_reverts(
     .unwrap();
     assert!(tx_execution_info3.is_reverted());
     assert!(tx_execution_info3.actual_fee == actual_fee_depth1);
+    assert_eq!(tx_execution_info3.revert_reason, Some(RevertReason::OutOfSteps));
     assert!(
         tx_execution_info3.revert_error.unwrap().contains("RunResources has no remaining steps.")
     );
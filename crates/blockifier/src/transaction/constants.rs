@@ -3,6 +3,7 @@ pub const TRANSFER_ENTRY_POINT_NAME: &str = "transfer";
 pub const VALIDATE_ENTRY_POINT_NAME: &str = "__validate__";
 pub const VALIDATE_DECLARE_ENTRY_POINT_NAME: &str = "__validate_declare__";
 pub const VALIDATE_DEPLOY_ENTRY_POINT_NAME: &str = "__validate_deploy__";
+pub const VALIDATE_PAYMASTER_ENTRY_POINT_NAME: &str = "__validate_paymaster__";
 
 pub const TRANSFER_EVENT_NAME: &str = "Transfer";
 
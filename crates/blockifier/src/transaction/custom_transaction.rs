@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::transaction::{
+    Calldata, Fee, TransactionHash, TransactionSignature, TransactionVersion,
+};
+
+use crate::execution::call_info::CallInfo;
+use crate::execution::entry_point::EntryPointExecutionContext;
+use crate::state::state_api::State;
+use crate::transaction::objects::{
+    CommonAccountFields, DeprecatedTransactionInfo, HasRelatedFeeType, TransactionExecutionResult,
+    TransactionInfo, TransactionInfoCreator,
+};
+use crate::transaction::transaction_types::TransactionType;
+
+#[cfg(test)]
+#[path = "custom_transaction_test.rs"]
+mod test;
+
+/// A chain-specific transaction (e.g., a privileged system transaction of an appchain), executed by
+/// the handler registered for its type in the `CustomTransactionRegistry` of the block context
+/// extensions. Custom transactions are not validated nor charged a fee; their resources include the
+/// OS resources of their type, which must be set in the versioned constants, under
+/// `os_resources.execute_txs_inner["Custom(<type_id>)"]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CustomTransaction {
+    pub type_id: u8,
+    pub tx_hash: TransactionHash,
+    /// The address in whose context the transaction executes (e.g., a system contract).
+    pub sender_address: ContractAddress,
+    pub calldata: Calldata,
+}
+
+impl CustomTransaction {
+    pub fn tx_type(&self) -> TransactionType {
+        TransactionType::Custom(self.type_id)
+    }
+}
+
+impl HasRelatedFeeType for CustomTransaction {
+    fn version(&self) -> TransactionVersion {
+        TransactionVersion::ZERO
+    }
+
+    fn is_l1_handler(&self) -> bool {
+        false
+    }
+}
+
+impl TransactionInfoCreator for CustomTransaction {
+    fn create_tx_info(&self) -> TransactionInfo {
+        TransactionInfo::Deprecated(DeprecatedTransactionInfo {
+            common_fields: CommonAccountFields {
+                transaction_hash: self.tx_hash,
+                version: TransactionVersion::ZERO,
+                signature: TransactionSignature::default(),
+                nonce: Nonce::default(),
+                sender_address: self.sender_address,
+                only_query: false,
+            },
+            max_fee: Fee::default(),
+        })
+    }
+}
+
+/// Executes the custom transactions of a given type; the counterpart of the `__execute__` call of
+/// account transactions. The handler may make any calls, and modify the state directly.
+pub trait CustomTransactionHandler: Send + Sync {
+    /// Returns the call info of the top-level call made by the transaction, if any.
+    fn execute(
+        &self,
+        tx: &CustomTransaction,
+        state: &mut dyn State,
+        resources: &mut ExecutionResources,
+        context: &mut EntryPointExecutionContext,
+        remaining_gas: &mut u64,
+    ) -> TransactionExecutionResult<Option<CallInfo>>;
+}
+
+/// The handlers of the custom transaction types supported by the chain; an execution extension.
+/// Custom transactions of types without a registered handler are unsupported.
+#[derive(Clone, Default)]
+pub struct CustomTransactionRegistry {
+    handlers: HashMap<u8, Arc<dyn CustomTransactionHandler>>,
+}
+
+impl CustomTransactionRegistry {
+    /// Registers the handler of the given type, returning the previous one, if any.
+    pub fn register(
+        &mut self,
+        type_id: u8,
+        handler: impl CustomTransactionHandler + 'static,
+    ) -> Option<Arc<dyn CustomTransactionHandler>> {
+        self.handlers.insert(type_id, Arc::new(handler))
+    }
+
+    pub fn handler(&self, type_id: u8) -> Option<Arc<dyn CustomTransactionHandler>> {
+        self.handlers.get(&type_id).cloned()
+    }
+}
@@ -0,0 +1,133 @@
+use assert_matches::assert_matches;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::transaction::{Fee, TransactionHash};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+use strum::IntoEnumIterator;
+
+use crate::abi::abi_utils::get_storage_var_address;
+use crate::abi::constants::N_STEPS_RESOURCE;
+use crate::blockifier::block::BlockInfo;
+use crate::context::{BlockContext, ChainInfo};
+use crate::execution::call_info::CallInfo;
+use crate::execution::entry_point::EntryPointExecutionContext;
+use crate::state::state_api::{State, StateReader};
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::BALANCE;
+use crate::transaction::custom_transaction::{
+    CustomTransaction, CustomTransactionHandler, CustomTransactionRegistry,
+};
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::TransactionExecutionResult;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transaction_types::TransactionType;
+use crate::transaction::transactions::ExecutableTransaction;
+use crate::versioned_constants::VersionedConstants;
+
+const TYPE_ID: u8 = 7;
+
+/// Stores the first calldata felt in the sender's `system_value` storage variable.
+struct SetSystemValue;
+
+impl CustomTransactionHandler for SetSystemValue {
+    fn execute(
+        &self,
+        tx: &CustomTransaction,
+        state: &mut dyn State,
+        _resources: &mut ExecutionResources,
+        _context: &mut EntryPointExecutionContext,
+        _remaining_gas: &mut u64,
+    ) -> TransactionExecutionResult<Option<CallInfo>> {
+        let key = get_storage_var_address("system_value", &[]);
+        state.set_storage_at(tx.sender_address, key, tx.calldata.0[0])?;
+        Ok(None)
+    }
+}
+
+fn versioned_constants_with_custom_os_resources() -> VersionedConstants {
+    let params = json!({
+        "constant": { "n_steps": 100, "builtin_instance_counter": {}, "n_memory_holes": 0 },
+        "calldata_factor": { "n_steps": 10, "builtin_instance_counter": {}, "n_memory_holes": 0 },
+    });
+    VersionedConstants::builder()
+        .with_overrides(json!({
+            "os_resources": {
+                "execute_txs_inner": {
+                    "Custom(7)": {
+                        "deprecated_resources": params,
+                        "resources": params,
+                    },
+                },
+            },
+        }))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_custom_transaction_type() {
+    let tx_type = TransactionType::Custom(TYPE_ID);
+    assert_eq!(serde_json::to_value(tx_type).unwrap(), json!("Custom(7)"));
+    assert_eq!(serde_json::from_value::<TransactionType>(json!("Custom(7)")).unwrap(), tx_type);
+    assert_eq!(serde_json::to_value(TransactionType::L1Handler).unwrap(), json!("L1Handler"));
+    assert!("Custom(256)".parse::<TransactionType>().is_err());
+
+    // Custom types are not Starknet transaction types.
+    assert_eq!(TransactionType::iter().count(), 4);
+    assert!(!VersionedConstants::latest_constants().has_os_resources_for_tx_type(&tx_type));
+}
+
+#[test]
+fn test_custom_transaction_execution() {
+    let sender_address = contract_address!("0x1234");
+    let tx = CustomTransaction {
+        type_id: TYPE_ID,
+        tx_hash: TransactionHash(stark_felt!(1_u8)),
+        sender_address,
+        calldata: calldata![stark_felt!(5_u8), stark_felt!(6_u8)],
+    };
+    let execute = |block_context: &BlockContext| {
+        let mut state = test_state(&block_context.chain_info, BALANCE, &[]);
+        let result = Transaction::Custom(tx.clone()).execute(&mut state, block_context, true, true);
+        (result, state)
+    };
+    let mut registry = CustomTransactionRegistry::default();
+    assert!(registry.register(TYPE_ID, SetSystemValue).is_none());
+
+    // Types without a registered handler are unsupported.
+    let block_context = BlockContext::new_unchecked(
+        &BlockInfo::create_for_testing(),
+        &ChainInfo::create_for_testing(),
+        &versioned_constants_with_custom_os_resources(),
+    );
+    assert_matches!(
+        execute(&block_context).0.unwrap_err(),
+        TransactionExecutionError::UnsupportedTransactionType(tx_type) if tx_type == "Custom(7)"
+    );
+
+    // Registered types must have OS resources.
+    let block_context = BlockContext::create_for_account_testing();
+    block_context.extensions().insert(registry.clone());
+    assert_matches!(
+        execute(&block_context).0.unwrap_err(),
+        TransactionExecutionError::MissingOsResources { tx_type }
+        if tx_type == TransactionType::Custom(TYPE_ID)
+    );
+
+    let block_context = BlockContext::new_unchecked(
+        &BlockInfo::create_for_testing(),
+        &ChainInfo::create_for_testing(),
+        &versioned_constants_with_custom_os_resources(),
+    );
+    block_context.extensions().insert(registry);
+    let (result, mut state) = execute(&block_context);
+    let tx_execution_info = result.unwrap();
+    assert_eq!(tx_execution_info.actual_fee, Fee(0));
+    assert_eq!(tx_execution_info.actual_resources.0[N_STEPS_RESOURCE], 100 + 10 * 2);
+    assert_eq!(
+        state.get_storage_at(sender_address, get_storage_var_address("system_value", &[])).unwrap(),
+        stark_felt!(5_u8)
+    );
+}
@@ -1,13 +1,40 @@
+use starknet_api::block::BlockTimestamp;
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
-use starknet_api::transaction::{Fee, TransactionVersion};
+use starknet_api::transaction::{Fee, Resource, TransactionVersion};
 use starknet_api::StarknetApiError;
 use thiserror::Error;
 
 use crate::execution::call_info::Retdata;
-use crate::execution::errors::{gen_transaction_execution_error_trace, EntryPointExecutionError};
+use crate::execution::errors::{
+    gen_transaction_execution_error_trace, ContractClassError, EntryPointExecutionError,
+};
 use crate::fee::fee_checks::FeeCheckError;
 use crate::state::errors::StateError;
+use crate::transaction::transaction_types::TransactionType;
+
+#[derive(Debug, Error)]
+pub enum OutsideExecutionError {
+    #[error("Outside execution caller {caller:?} does not match the relayer {relayer:?}.")]
+    InvalidCaller { caller: ContractAddress, relayer: ContractAddress },
+    #[error("Invalid value in outside execution calldata: {0:?}.")]
+    InvalidValue(StarkFelt),
+    #[error("Malformed outside execution calldata of length {length}.")]
+    MalformedCalldata { length: usize },
+    #[error("Outside execution nonce {nonce:?} was already used by account {account:?}.")]
+    NonceAlreadyUsed { account: ContractAddress, nonce: StarkFelt },
+    #[error(
+        "Block timestamp {block_timestamp:?} is outside of the execution window \
+         ({execute_after:?}, {execute_before:?})."
+    )]
+    OutsideExecutionWindow {
+        block_timestamp: BlockTimestamp,
+        execute_after: BlockTimestamp,
+        execute_before: BlockTimestamp,
+    },
+    #[error(transparent)]
+    StarknetApiError(#[from] StarknetApiError),
+}
 
 #[derive(Debug, Error)]
 pub enum TransactionFeeError {
@@ -15,10 +42,22 @@ pub enum TransactionFeeError {
     CairoResourcesNotContainedInFeeCosts,
     #[error(transparent)]
     ExecuteFeeTransferError(#[from] EntryPointExecutionError),
+    #[error("Accumulated fees overflow the sequencer balance in fee token {fee_token_address:?}.")]
+    FeeAccumulationOverflow { fee_token_address: ContractAddress },
     #[error("Actual fee ({actual_fee:?}) exceeded max fee ({max_fee:?}).")]
     FeeTransferError { max_fee: Fee, actual_fee: Fee },
+    #[error(
+        "Fee accumulator {accumulator_address:?} holds less than its accrued fees in fee token \
+         {fee_token_address:?}."
+    )]
+    InsufficientAccumulatorBalance {
+        accumulator_address: ContractAddress,
+        fee_token_address: ContractAddress,
+    },
     #[error("Actual fee ({actual_fee:?}) exceeded paid fee on L1 ({paid_fee:?}).")]
     InsufficientL1Fee { paid_fee: Fee, actual_fee: Fee },
+    #[error("Invalid paymaster address in paymaster data: {0:?}.")]
+    InvalidPaymasterAddress(StarkFelt),
     #[error(
         "L1 gas bounds (max amount: {max_amount:?}, max price: {max_price:?}) exceed balance \
          (Uint256({balance_low:?}, {balance_high:?}))."
@@ -34,15 +73,14 @@ pub enum TransactionFeeError {
     #[error("Max fee ({max_fee:?}) is too low. Minimum fee: {min_fee:?}.")]
     MaxFeeTooLow { min_fee: Fee, max_fee: Fee },
     #[error(
-        "Max L1 gas price ({max_l1_gas_price:?}) is lower than the actual gas price: \
-         {actual_l1_gas_price:?}."
+        "Max {resource:?} amount ({max_amount}) is lower than the minimal gas amount: \
+         {minimal_amount}."
     )]
-    MaxL1GasPriceTooLow { max_l1_gas_price: u128, actual_l1_gas_price: u128 },
+    MaxGasAmountTooLow { resource: Resource, max_amount: u64, minimal_amount: u128 },
     #[error(
-        "Max L1 gas amount ({max_l1_gas_amount:?}) is lower than the minimal gas amount: \
-         {minimal_l1_gas_amount:?}."
+        "Max {resource:?} price ({max_price}) is lower than the actual gas price: {actual_price}."
     )]
-    MaxL1GasAmountTooLow { max_l1_gas_amount: u64, minimal_l1_gas_amount: u64 },
+    MaxGasPriceTooLow { resource: Resource, max_price: u128, actual_price: u128 },
     #[error("Missing L1 gas bounds in resource bounds.")]
     MissingL1GasBounds,
     #[error(transparent)]
@@ -51,6 +89,15 @@ pub enum TransactionFeeError {
 
 #[derive(Debug, Error)]
 pub enum TransactionExecutionError {
+    #[error(
+        "Calldata length {calldata_length} exceeds the maximal calldata length: \
+         {max_calldata_length}."
+    )]
+    CalldataTooLong { calldata_length: usize, max_calldata_length: usize },
+    #[error("Classes referenced by the transaction are not declared: {class_hashes:?}.")]
+    ClassNotFound { class_hashes: Vec<ClassHash> },
+    #[error(transparent)]
+    ContractClassError(#[from] ContractClassError),
     #[error(
         "Declare transaction version {declare_version:?} must have a contract class of Cairo \
          version {cairo_version:?}."
@@ -80,6 +127,10 @@ pub enum TransactionExecutionError {
          {allowed_versions:?}."
     )]
     InvalidVersion { version: TransactionVersion, allowed_versions: Vec<TransactionVersion> },
+    #[error("The OS resources of {tx_type} transactions are not set in the versioned constants.")]
+    MissingOsResources { tx_type: TransactionType },
+    #[error("{tx_type:?} transaction must be created with {missing_input}.")]
+    MissingTransactionInput { tx_type: TransactionType, missing_input: String },
     #[error(transparent)]
     StarknetApiError(#[from] StarknetApiError),
     #[error(transparent)]
@@ -88,11 +139,15 @@ pub enum TransactionExecutionError {
     TransactionFeeError(#[from] TransactionFeeError),
     #[error(transparent)]
     TransactionPreValidationError(#[from] TransactionPreValidationError),
+    #[error("Unsupported transaction type: {0}.")]
+    UnsupportedTransactionType(String),
     #[error("Unexpected holes in the {object} order. No object with the order: {order}.")]
     UnexpectedHoles { object: String, order: usize },
     #[error(transparent)]
     TryFromIntError(#[from] std::num::TryFromIntError),
     // TODO(Zuphit): add `gen_transaction_execution_error_trace` if needed.
+    #[error("Paymaster validation has failed: {error}")]
+    ValidatePaymasterError { error: EntryPointExecutionError, storage_address: ContractAddress },
     #[error("Transaction validation has failed: {error}")]
     ValidateTransactionError { error: EntryPointExecutionError, storage_address: ContractAddress },
     #[error(
@@ -115,6 +170,14 @@ pub enum TransactionPreValidationError {
     TransactionFeeError(#[from] TransactionFeeError),
 }
 
+#[derive(Debug, Error)]
+#[error("Simulation of transaction {tx_index} has failed: {error}")]
+pub struct TransactionSimulationError {
+    /// The index of the failed transaction in the simulated batch.
+    pub tx_index: usize,
+    pub error: TransactionExecutionError,
+}
+
 #[derive(Debug, Error)]
 pub enum ParseError {
     #[error("Unsupported transaction type: {0}")]
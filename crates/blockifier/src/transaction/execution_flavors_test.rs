@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use assert_matches::assert_matches;
 use cairo_felt::Felt252;
 use pretty_assertions::assert_eq;
@@ -234,7 +236,7 @@ fn test_simulate_validate_charge_fee_pre_validate(
                 result.unwrap_err(),
                 TransactionExecutionError::TransactionPreValidationError(
                     TransactionPreValidationError::TransactionFeeError(
-                        TransactionFeeError::MaxL1GasAmountTooLow { .. }
+                        TransactionFeeError::MaxGasAmountTooLow { .. }
                     )
                 )
             );
@@ -308,7 +310,7 @@ fn test_simulate_validate_charge_fee_pre_validate(
                 result.unwrap_err(),
                 TransactionExecutionError::TransactionPreValidationError(
                     TransactionPreValidationError::TransactionFeeError(
-                        TransactionFeeError::MaxL1GasPriceTooLow { .. }
+                        TransactionFeeError::MaxGasPriceTooLow { .. }
                     )
                 )
             );
@@ -507,7 +509,7 @@ fn test_simulate_validate_charge_fee_mid_execution(
     // Third scenario: only limit is block bounds. Expect resources consumed to be identical,
     // whether or not `charge_fee` is true.
     let mut low_step_block_context = block_context.clone();
-    low_step_block_context.versioned_constants.invoke_tx_max_n_steps = 10000;
+    Arc::make_mut(&mut low_step_block_context.versioned_constants).invoke_tx_max_n_steps = 10000;
     let (huge_gas_limit, huge_fee) = gas_and_fee(100000, validate, &fee_type);
     // Gas usage does not depend on `validate` flag in this scenario, because we reach the block
     // step limit during execution anyway. The actual limit when execution phase starts is slightly
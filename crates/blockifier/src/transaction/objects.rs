@@ -1,30 +1,38 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use cairo_felt::Felt252;
 use num_traits::Pow;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::data_availability::DataAvailabilityMode;
+use starknet_api::hash::{pedersen_hash_array, StarkFelt, StarkHash};
 use starknet_api::transaction::{
-    AccountDeploymentData, Fee, PaymasterData, Resource, ResourceBounds, ResourceBoundsMapping,
-    Tip, TransactionHash, TransactionSignature, TransactionVersion,
+    AccountDeploymentData, EventContent, Fee, PaymasterData, Resource, ResourceBounds,
+    ResourceBoundsMapping, Tip, TransactionHash, TransactionSignature, TransactionVersion,
 };
 use strum_macros::EnumIter;
 
 use crate::context::BlockContext;
-use crate::execution::call_info::{CallInfo, ExecutionSummary, MessageL1CostInfo, OrderedEvent};
+use crate::execution::call_info::{
+    CallExecution, CallInfo, CallPath, ExecutionSummary, MessageL1CostInfo, MessageToL1,
+    OrderedEvent, OrderedL2ToL1Message, TopLevelCall,
+};
+use crate::execution::cairo_traceback::{CairoTraceback, DecodedRevertReason};
 use crate::execution::contract_class::ClassInfo;
 use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use crate::fee::actual_cost::{FeeTrace, MessageResources};
 use crate::fee::eth_gas_constants;
 use crate::fee::fee_utils::calculate_tx_fee;
-use crate::fee::gas_usage::{get_da_gas_cost, get_messages_gas_usage};
-use crate::state::cached_state::StateChangesCount;
+use crate::fee::gas_usage::{
+    get_da_gas_cost, get_messages_gas_usage, get_onchain_data_segment_length,
+};
+use crate::state::cached_state::{StateChanges, StateChangesCount};
 use crate::transaction::constants;
 use crate::transaction::errors::{
     TransactionExecutionError, TransactionFeeError, TransactionPreValidationError,
 };
-use crate::utils::u128_from_usize;
-use crate::versioned_constants::VersionedConstants;
+use crate::utils::{u128_from_usize, usize_from_u128};
+use crate::versioned_constants::{ResourceCost, VersionedConstants};
 
 #[cfg(test)]
 #[path = "objects_test.rs"]
@@ -72,6 +80,23 @@ impl TransactionInfo {
         self.version() == TransactionVersion::ZERO
     }
 
+    /// The DA mode of the sender's nonce update; always L1 for deprecated transactions.
+    pub fn nonce_data_availability_mode(&self) -> DataAvailabilityMode {
+        match self {
+            Self::Current(context) => context.nonce_data_availability_mode,
+            Self::Deprecated(_) => DataAvailabilityMode::L1,
+        }
+    }
+
+    /// The DA mode of the sender's fee token balance update; always L1 for deprecated
+    /// transactions.
+    pub fn fee_data_availability_mode(&self) -> DataAvailabilityMode {
+        match self {
+            Self::Current(context) => context.fee_data_availability_mode,
+            Self::Deprecated(_) => DataAvailabilityMode::L1,
+        }
+    }
+
     pub fn signed_version(&self) -> TransactionVersion {
         let version = self.version();
         if !self.only_query() {
@@ -133,7 +158,16 @@ pub struct DeprecatedTransactionInfo {
 }
 
 #[derive(
-    derive_more::Add, derive_more::Sum, Clone, Copy, Debug, Default, Eq, PartialEq, Serialize,
+    derive_more::Add,
+    derive_more::Sum,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Serialize,
 )]
 pub struct GasVector {
     pub l1_gas: u128,
@@ -179,6 +213,71 @@ impl GasVector {
     }
 }
 
+/// The L1 gas consumed by a transaction, per resource.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GasVectorBreakdown {
+    /// The gas consumed directly on L1 (e.g., by messages to L1 and by the state diff).
+    pub l1_usage: GasVector,
+    /// The gas of each Cairo resource (steps and builtins); only the heaviest one is charged.
+    pub vm_resources: HashMap<String, GasVector>,
+}
+
+impl GasVectorBreakdown {
+    /// Returns the gas charged for the transaction.
+    pub fn total(&self) -> GasVector {
+        let vm_l1_gas = self
+            .vm_resources
+            .values()
+            .map(|gas_vector| gas_vector.l1_gas)
+            .max()
+            .unwrap_or_default();
+        self.l1_usage + GasVector::from_l1_gas(vm_l1_gas)
+    }
+}
+
+/// A resource a transaction is charged for.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeResource {
+    Steps,
+    Builtin(String),
+    CalldataAndSignature,
+    EventKeys,
+    EventData,
+    Messages,
+    StateDiff,
+    CodeBytes,
+    CodeSegments,
+}
+
+/// The L1 gas consumed by a single resource of a transaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceGasUsage {
+    pub resource: FeeResource,
+    /// The amount of the resource used: steps, builtin instances, felts or bytes.
+    pub count: usize,
+    /// The gas charged per unit of the resource. The gas of messages and of the state diff is not
+    /// linear in their size; for those, this is the average gas per felt.
+    pub weight: ResourceCost,
+    /// The gas charged for the resource, rounded as in the fee computation.
+    pub gas: GasVector,
+}
+
+impl ResourceGasUsage {
+    fn linear(resource: FeeResource, count: usize, weight: ResourceCost) -> Self {
+        let gas = GasVector::from_l1_gas((weight * u128_from_usize(count)).to_integer());
+        Self { resource, count, weight, gas }
+    }
+
+    fn averaged(resource: FeeResource, count: usize, gas: GasVector) -> Self {
+        let weight = match count {
+            0 => ResourceCost::default(),
+            _ => ResourceCost::new(gas.l1_gas + gas.l1_data_gas, u128_from_usize(count)),
+        };
+        Self { resource, count, weight, gas }
+    }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct CommonAccountFields {
     pub transaction_hash: TransactionHash,
@@ -190,10 +289,15 @@ pub struct CommonAccountFields {
 }
 
 /// Contains the information gathered by the execution of a transaction.
-#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+/// Serialized (e.g., to JSON) with the field names below, which are not renamed between versions.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct TransactionExecutionInfo {
     /// Transaction validation call info; [None] for `L1Handler`.
     pub validate_call_info: Option<CallInfo>,
+    /// Paymaster validation call info; [None] unless the transaction is sponsored by a paymaster
+    /// (see `PaymasterConfig`).
+    #[serde(default)]
+    pub paymaster_call_info: Option<CallInfo>,
     /// Transaction execution call info; [None] for `Declare`.
     pub execute_call_info: Option<CallInfo>,
     /// Fee transfer call info; [None] for `L1Handler`.
@@ -209,14 +313,22 @@ pub struct TransactionExecutionInfo {
     // TODO(Dori, 1/8/2023): If the `Eq` and `PartialEq` traits are removed, or implemented on all
     //   internal structs in this enum, this field should be `Option<TransactionExecutionError>`.
     pub revert_error: Option<String>,
+    /// The class of the revert error; [None] if transaction execution was successful.
+    pub revert_reason: Option<RevertReason>,
     /// If not None, contains the resources to account for in the bouncer.
     pub bouncer_resources: ResourcesMapping,
+    /// A detailed breakdown of the fee; [None] unless `DetailedFeeTrace` is enabled.
+    pub fee_trace: Option<FeeTrace>,
+    /// The messaging component of the resources the transaction is charged for.
+    #[serde(default)]
+    pub message_resources: MessageResources,
 }
 
 impl TransactionExecutionInfo {
     pub fn non_optional_call_infos(&self) -> impl Iterator<Item = &CallInfo> {
         self.validate_call_info
             .iter()
+            .chain(self.paymaster_call_info.iter())
             .chain(self.execute_call_info.iter())
             .chain(self.fee_transfer_call_info.iter())
     }
@@ -225,15 +337,210 @@ impl TransactionExecutionInfo {
         self.revert_error.is_some()
     }
 
+    /// Returns the events emitted by the transaction, with their emitting contracts, in emission
+    /// order; i.e., those of the validation, paymaster validation, execution and fee transfer
+    /// calls, in that order.
+    pub fn events(&self) -> impl Iterator<Item = (ContractAddress, &EventContent)> {
+        self.non_optional_call_infos().flat_map(CallInfo::events)
+    }
+
+    /// Returns the L2-to-L1 messages sent by the transaction, with their sending contracts, in the
+    /// order in which they were sent.
+    pub fn l2_to_l1_messages(&self) -> impl Iterator<Item = (ContractAddress, &MessageToL1)> {
+        self.non_optional_call_infos().flat_map(CallInfo::l2_to_l1_messages)
+    }
+
     /// Returns a summary of transaction execution, including executed class hashes, visited storage
     /// entries, and the number of emitted events.
     pub fn summarize(&self) -> ExecutionSummary {
         self.non_optional_call_infos().map(|call_info| call_info.summarize()).sum()
     }
+
+    /// Returns a deterministic hash of the semantic outputs of the transaction: its revert status,
+    /// the return data, events and L2-to-L1 messages of its calls, its actual fee, and the given
+    /// state changes (which are not part of the execution info), so that independent executors
+    /// can cheaply compare their results.
+    /// Implementation details (e.g., resources and revert error messages) are not hashed.
+    pub fn summary_hash(&self, state_changes: &StateChanges) -> StarkHash {
+        let mut felts = vec![StarkFelt::from(u8::from(self.is_reverted()))];
+        let usize_felt = |len: usize| StarkFelt::from(u128_from_usize(len));
+
+        for call_info in self.non_optional_call_infos().flat_map(|call_info| call_info.iter()) {
+            let CallExecution { retdata, events, l2_to_l1_messages, .. } = &call_info.execution;
+            felts.push(*call_info.call.storage_address.0.key());
+            felts.push(usize_felt(retdata.0.len()));
+            felts.extend(&retdata.0);
+            felts.push(usize_felt(events.len()));
+            for OrderedEvent { order, event } in events {
+                felts.push(usize_felt(*order));
+                felts.push(usize_felt(event.keys.len()));
+                felts.extend(event.keys.iter().map(|key| key.0));
+                felts.push(usize_felt(event.data.0.len()));
+                felts.extend(&event.data.0);
+            }
+            felts.push(usize_felt(l2_to_l1_messages.len()));
+            for OrderedL2ToL1Message { order, message } in l2_to_l1_messages {
+                let mut to_address = [0_u8; 32];
+                to_address[12..].copy_from_slice(message.to_address.0.as_bytes());
+                let to_address = StarkFelt::new(to_address).expect("An L1 address fits in a felt.");
+                felts.extend([usize_felt(*order), to_address]);
+                felts.push(usize_felt(message.payload.0.len()));
+                felts.extend(&message.payload.0);
+            }
+        }
+
+        felts.push(StarkFelt::from(self.actual_fee.0));
+
+        let StateChanges {
+            storage_updates,
+            nonce_updates,
+            class_hash_updates,
+            compiled_class_hash_updates,
+        } = state_changes;
+        let storage_updates: BTreeMap<_, _> = storage_updates.iter().collect();
+        felts.push(usize_felt(storage_updates.len()));
+        for ((address, key), value) in storage_updates {
+            felts.extend([*address.0.key(), *key.0.key(), *value]);
+        }
+        let nonce_updates: BTreeMap<_, _> = nonce_updates.iter().collect();
+        felts.push(usize_felt(nonce_updates.len()));
+        for (address, nonce) in nonce_updates {
+            felts.extend([*address.0.key(), nonce.0]);
+        }
+        let class_hash_updates: BTreeMap<_, _> = class_hash_updates.iter().collect();
+        felts.push(usize_felt(class_hash_updates.len()));
+        for (address, class_hash) in class_hash_updates {
+            felts.extend([*address.0.key(), class_hash.0]);
+        }
+        let compiled_class_hash_updates: BTreeMap<_, _> =
+            compiled_class_hash_updates.iter().collect();
+        felts.push(usize_felt(compiled_class_hash_updates.len()));
+        for (class_hash, compiled_class_hash) in compiled_class_hash_updates {
+            felts.extend([class_hash.0, compiled_class_hash.0]);
+        }
+
+        pedersen_hash_array(&felts)
+    }
+
+    /// Drops the execution traces, keeping only the entry points, results and aggregated resources
+    /// (including the syscalls of their inner calls) of the top-level calls.
+    pub fn drop_traces(&mut self) {
+        let top_level_call_infos = [
+            &mut self.validate_call_info,
+            &mut self.paymaster_call_info,
+            &mut self.execute_call_info,
+            &mut self.fee_transfer_call_info,
+        ];
+        for call_info in top_level_call_infos.into_iter().flatten() {
+            let syscall_counter = call_info.summarize().syscall_counter;
+            let CallInfo { call, execution, resources, .. } = std::mem::take(call_info);
+            *call_info = CallInfo {
+                call,
+                execution: CallExecution {
+                    retdata: execution.retdata,
+                    failed: execution.failed,
+                    gas_consumed: execution.gas_consumed,
+                    ..Default::default()
+                },
+                resources,
+                syscall_counter,
+                ..Default::default()
+            };
+        }
+    }
+
+    /// Returns the sub-trace rooted at the given call, dropping the rest of the trace; `None` if
+    /// the call was not made (e.g., the transaction was reverted before making it).
+    pub fn into_sub_trace(self, call_path: &CallPath) -> Option<CallInfo> {
+        let top_level_call_info = match call_path.top_level_call {
+            TopLevelCall::Validate => self.validate_call_info,
+            TopLevelCall::PaymasterValidate => self.paymaster_call_info,
+            TopLevelCall::Execute => self.execute_call_info,
+            TopLevelCall::FeeTransfer => self.fee_transfer_call_info,
+        }?;
+        top_level_call_info.into_descendant(&call_path.inner_call_indices)
+    }
+}
+
+/// The class of error that caused a transaction to be reverted or rejected.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum RevertReason {
+    /// The account's validation entry point failed; such transactions are rejected, never
+    /// reverted.
+    ValidationFailure,
+    /// Execution ran out of steps.
+    OutOfSteps,
+    /// Execution failed, e.g., the called contract panicked.
+    Panic,
+    /// The fee bounds or the fee token balance do not cover the fee.
+    FeeShortfall,
+    /// Any other error, e.g., an invalid nonce or transaction version.
+    Other,
+}
+
+impl RevertReason {
+    /// Classifies the execution error of a reverted transaction, by the innermost failure of its
+    /// Cairo traceback.
+    pub fn from_revert(error: &TransactionExecutionError) -> Self {
+        match error.cairo_traceback() {
+            Some(CairoTraceback { reason: DecodedRevertReason::OutOfSteps, .. }) => {
+                Self::OutOfSteps
+            }
+            _ => Self::Panic,
+        }
+    }
+
+    /// Classifies the error of a transaction that was rejected (i.e., not included in the block).
+    pub fn from_rejection(error: &TransactionExecutionError) -> Self {
+        match error {
+            TransactionExecutionError::ValidateTransactionError { .. }
+            | TransactionExecutionError::ValidatePaymasterError { .. }
+            | TransactionExecutionError::InvalidValidateReturnData { .. } => {
+                Self::ValidationFailure
+            }
+            TransactionExecutionError::ExecutionError { .. }
+            | TransactionExecutionError::ContractConstructorExecutionFailed { .. } => Self::Panic,
+            TransactionExecutionError::FeeCheckError(_)
+            | TransactionExecutionError::TransactionFeeError(_)
+            | TransactionExecutionError::TransactionPreValidationError(
+                TransactionPreValidationError::TransactionFeeError(_),
+            ) => Self::FeeShortfall,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Determines how the fee of an account transaction is handled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FeeChargeMode {
+    /// Enforce the fee bounds and the balance checks, and transfer the fee to the sequencer.
+    #[default]
+    Charge,
+    /// Compute the actual fee (even if the transaction does not enforce a fee), but skip the
+    /// fee checks and the fee transfer; e.g., for trusted transactions and fee sponsorship.
+    ComputeOnly,
+    /// Skip the fee checks and the fee transfer; used in simulations and tests.
+    Skip,
+}
+
+impl FeeChargeMode {
+    pub fn charge_fee(&self) -> bool {
+        matches!(self, Self::Charge)
+    }
+
+    pub fn always_compute_fee(&self) -> bool {
+        matches!(self, Self::ComputeOnly)
+    }
+}
+
+impl From<bool> for FeeChargeMode {
+    fn from(charge_fee: bool) -> Self {
+        if charge_fee { Self::Charge } else { Self::Skip }
+    }
 }
 
 /// A mapping from a transaction execution resource to its actual usage.
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ResourcesMapping(pub HashMap<String, usize>);
 
 impl ResourcesMapping {
@@ -262,6 +569,7 @@ pub struct StarknetResources {
     pub l1_handler_payload_size: Option<usize>,
     signature_length: usize,
     code_size: usize,
+    n_code_segments: usize,
     total_event_keys: u128,
     total_event_data_size: u128,
 }
@@ -279,6 +587,7 @@ impl StarknetResources {
             calldata_length,
             signature_length,
             code_size: StarknetResources::calculate_code_size(class_info),
+            n_code_segments: StarknetResources::calculate_n_code_segments(class_info),
             state_changes_count,
             l1_handler_payload_size,
             ..Default::default()
@@ -301,9 +610,11 @@ impl StarknetResources {
     }
 
     /// Sets the code_size field from a ClassInfo from (Sierra, Casm and ABI). Each code felt costs
-    /// a fixed and configurable amount of gas. The cost is 0 for non-Declare transactions.
+    /// a fixed and configurable amount of gas, and so does each bytecode segment. The cost is 0 for
+    /// non-Declare transactions.
     pub fn set_code_size(&mut self, class_info: Option<&ClassInfo>) {
         self.code_size = StarknetResources::calculate_code_size(class_info);
+        self.n_code_segments = StarknetResources::calculate_n_code_segments(class_info);
     }
 
     /// Sets the l2_to_l1_payload_lengths, message_segment_length, total_event_keys,
@@ -368,9 +679,11 @@ impl StarknetResources {
 
     /// Returns the gas cost of declared class codes.
     pub fn get_code_cost(&self, versioned_constants: &VersionedConstants) -> GasVector {
+        let l2_resource_gas_costs = &versioned_constants.l2_resource_gas_costs;
         GasVector::from_l1_gas(
-            (versioned_constants.l2_resource_gas_costs.gas_per_code_byte
-                * u128_from_usize(self.code_size))
+            (l2_resource_gas_costs.gas_per_code_byte * u128_from_usize(self.code_size)
+                + l2_resource_gas_costs.gas_per_code_segment
+                    * u128_from_usize(self.n_code_segments))
             .to_integer(),
         )
     }
@@ -393,17 +706,69 @@ impl StarknetResources {
         GasVector::from_l1_gas(l1_gas)
     }
 
+    /// Returns the gas usage of each of the starknet resources. Unlike in `to_gas_vector`, the gas
+    /// of event keys and data, and of code bytes and segments, is rounded separately.
+    pub fn gas_usage_breakdown(
+        &self,
+        versioned_constants: &VersionedConstants,
+        use_kzg_da: bool,
+    ) -> Vec<ResourceGasUsage> {
+        let l2_resource_gas_costs = &versioned_constants.l2_resource_gas_costs;
+        let data_word_cost = l2_resource_gas_costs.gas_per_data_felt;
+        let to_usize = |value: u128| {
+            usize_from_u128(value)
+                .expect("This conversion should not fail as the value is a converted usize.")
+        };
+        let n_message_felts = self.message_cost_info.message_segment_length
+            + self.l1_handler_payload_size.unwrap_or_default();
+
+        vec![
+            ResourceGasUsage::linear(
+                FeeResource::CalldataAndSignature,
+                self.calldata_length + self.signature_length,
+                data_word_cost,
+            ),
+            ResourceGasUsage::linear(
+                FeeResource::EventKeys,
+                to_usize(self.total_event_keys),
+                data_word_cost * l2_resource_gas_costs.event_key_factor,
+            ),
+            ResourceGasUsage::linear(
+                FeeResource::EventData,
+                to_usize(self.total_event_data_size),
+                data_word_cost,
+            ),
+            ResourceGasUsage::averaged(
+                FeeResource::Messages,
+                n_message_felts,
+                self.get_messages_cost(),
+            ),
+            ResourceGasUsage::averaged(
+                FeeResource::StateDiff,
+                get_onchain_data_segment_length(&self.state_changes_count),
+                self.get_state_changes_cost(use_kzg_da),
+            ),
+            ResourceGasUsage::linear(
+                FeeResource::CodeBytes,
+                self.code_size,
+                l2_resource_gas_costs.gas_per_code_byte,
+            ),
+            ResourceGasUsage::linear(
+                FeeResource::CodeSegments,
+                self.n_code_segments,
+                l2_resource_gas_costs.gas_per_code_segment,
+            ),
+        ]
+    }
+
     /// Private and static method that calculates the code size from ClassInfo.
     fn calculate_code_size(class_info: Option<&ClassInfo>) -> usize {
-        if let Some(class_info) = class_info {
-            (class_info.bytecode_length()
-                + class_info.sierra_program_length())
-                    // We assume each felt is a word.
-                    * eth_gas_constants::WORD_WIDTH
-                + class_info.abi_length()
-        } else {
-            0
-        }
+        class_info.map_or(0, ClassInfo::code_size)
+    }
+
+    /// Private and static method that calculates the number of bytecode segments from ClassInfo.
+    fn calculate_n_code_segments(class_info: Option<&ClassInfo>) -> usize {
+        class_info.map_or(0, |class_info| class_info.size_metrics().n_bytecode_segments())
     }
 }
 
@@ -1,12 +1,19 @@
+use std::collections::HashMap;
+
 use rstest::rstest;
-use starknet_api::core::ClassHash;
-use starknet_api::hash::StarkFelt;
-use starknet_api::stark_felt;
+use starknet_api::core::{ClassHash, ContractAddress, EthAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{EventContent, EventData, EventKey, Fee};
+use starknet_api::{contract_address, patricia_key, stark_felt};
 
 use crate::execution::call_info::{
-    CallExecution, CallInfo, ExecutionSummary, OrderedEvent, TestExecutionSummary,
+    CallExecution, CallInfo, ExecutionSummary, MessageToL1, OrderedEvent, OrderedL2ToL1Message,
+    TestExecutionSummary,
 };
 use crate::execution::entry_point::CallEntryPoint;
+use crate::execution::syscalls::SyscallSelector;
+use crate::state::cached_state::StateChanges;
 use crate::transaction::objects::TransactionExecutionInfo;
 
 fn shared_call_info() -> CallInfo {
@@ -152,6 +159,7 @@ fn test_summarize(
         n_events: validate_params.num_of_events
             + execute_params.num_of_events
             + fee_transfer_params.num_of_events,
+        ..Default::default()
     };
 
     // Call the summarize method
@@ -162,3 +170,165 @@ fn test_summarize(
     assert_eq!(actual_summary.visited_storage_entries, expected_summary.visited_storage_entries);
     assert_eq!(actual_summary.n_events, expected_summary.n_events);
 }
+
+#[test]
+fn test_summarize_syscall_counter() {
+    let storage_call_info = CallInfo {
+        syscall_counter: HashMap::from([
+            (SyscallSelector::StorageRead, 2),
+            (SyscallSelector::StorageWrite, 1),
+        ]),
+        ..shared_call_info()
+    };
+    let execute_call_info = CallInfo {
+        inner_calls: vec![storage_call_info],
+        syscall_counter: HashMap::from([
+            (SyscallSelector::CallContract, 1),
+            (SyscallSelector::StorageRead, 1),
+        ]),
+        ..shared_call_info()
+    };
+    let validate_call_info = CallInfo {
+        syscall_counter: HashMap::from([(SyscallSelector::GetExecutionInfo, 1)]),
+        ..shared_call_info()
+    };
+    let transaction_execution_info = TransactionExecutionInfo {
+        validate_call_info: Some(validate_call_info),
+        execute_call_info: Some(execute_call_info),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        transaction_execution_info.summarize().syscall_counter,
+        HashMap::from([
+            (SyscallSelector::GetExecutionInfo, 1),
+            (SyscallSelector::CallContract, 1),
+            (SyscallSelector::StorageRead, 3),
+            (SyscallSelector::StorageWrite, 1),
+        ])
+    );
+}
+
+#[test]
+fn test_summary_hash() {
+    let execution_info = |actual_fee: u128| TransactionExecutionInfo {
+        execute_call_info: Some(CallInfo {
+            execution: CallExecution {
+                events: vec![OrderedEvent {
+                    order: 0,
+                    event: EventContent {
+                        keys: vec![EventKey(stark_felt!(1_u8))],
+                        data: EventData(vec![stark_felt!(2_u8)]),
+                    },
+                }],
+                ..Default::default()
+            },
+            ..shared_call_info()
+        }),
+        actual_fee: Fee(actual_fee),
+        ..Default::default()
+    };
+    let storage_updates = [
+        ((contract_address!("0x100"), StorageKey(patricia_key!("0x1"))), stark_felt!(3_u8)),
+        ((contract_address!("0x200"), StorageKey(patricia_key!("0x2"))), stark_felt!(4_u8)),
+    ];
+    let state_changes = StateChanges {
+        storage_updates: HashMap::from(storage_updates),
+        nonce_updates: HashMap::from([(contract_address!("0x100"), Nonce(stark_felt!(1_u8)))]),
+        ..Default::default()
+    };
+    let hash = execution_info(10).summary_hash(&state_changes);
+
+    // The hash does not depend on the iteration order of the state changes.
+    let mut reordered_state_changes = StateChanges {
+        storage_updates: HashMap::from([storage_updates[1]]),
+        ..state_changes.clone()
+    };
+    reordered_state_changes.storage_updates.insert(storage_updates[0].0, storage_updates[0].1);
+    assert_eq!(execution_info(10).summary_hash(&reordered_state_changes), hash);
+
+    // Any semantic difference changes the hash.
+    assert_ne!(execution_info(11).summary_hash(&state_changes), hash);
+    assert_ne!(execution_info(10).summary_hash(&StateChanges::default()), hash);
+    assert_ne!(TransactionExecutionInfo::default().summary_hash(&state_changes), hash);
+    assert_ne!(hash, StarkHash::default());
+}
+
+#[test]
+fn test_events_and_messages_order() {
+    let (account_address, contract_address, fee_token_address) =
+        (contract_address!("0x100"), contract_address!("0x200"), contract_address!("0x300"));
+    let event = |order, key| OrderedEvent {
+        order,
+        event: EventContent { keys: vec![EventKey(stark_felt!(key))], data: EventData::default() },
+    };
+    let message = |order, to_address: u8| OrderedL2ToL1Message {
+        order,
+        message: MessageToL1 {
+            to_address: EthAddress::try_from(stark_felt!(to_address)).unwrap(),
+            ..Default::default()
+        },
+    };
+    let call_info = |storage_address, events, l2_to_l1_messages, inner_calls| CallInfo {
+        call: CallEntryPoint { storage_address, ..Default::default() },
+        execution: CallExecution { events, l2_to_l1_messages, ..Default::default() },
+        inner_calls,
+        ..Default::default()
+    };
+    // Orders restart in each top-level call; the inner call emits its event and sends its message
+    // between those of its caller.
+    let inner_call_info =
+        call_info(contract_address, vec![event(1, 2_u8)], vec![message(1, 2)], vec![]);
+    let tx_execution_info = TransactionExecutionInfo {
+        validate_call_info: Some(call_info(
+            account_address,
+            vec![event(0, 0_u8)],
+            vec![message(0, 0)],
+            vec![],
+        )),
+        execute_call_info: Some(call_info(
+            account_address,
+            vec![event(2, 3_u8), event(0, 1_u8)],
+            vec![message(2, 3), message(0, 1)],
+            vec![inner_call_info],
+        )),
+        fee_transfer_call_info: Some(call_info(
+            fee_token_address,
+            vec![event(0, 4_u8)],
+            vec![],
+            vec![],
+        )),
+        ..Default::default()
+    };
+
+    let expected_events = [
+        (account_address, 0, 0_u8),
+        (account_address, 0, 1_u8),
+        (contract_address, 1, 2_u8),
+        (account_address, 2, 3_u8),
+        (fee_token_address, 0, 4_u8),
+    ]
+    .map(|(from_address, order, key)| (from_address, event(order, key).event));
+    assert_eq!(
+        tx_execution_info
+            .events()
+            .map(|(from_address, content)| (from_address, content.clone()))
+            .collect::<Vec<_>>(),
+        expected_events
+    );
+
+    let expected_messages = [
+        (account_address, 0, 0),
+        (account_address, 0, 1),
+        (contract_address, 1, 2),
+        (account_address, 2, 3),
+    ]
+    .map(|(from_address, order, to_address)| (from_address, message(order, to_address).message));
+    assert_eq!(
+        tx_execution_info
+            .l2_to_l1_messages()
+            .map(|(from_address, message)| (from_address, message.clone()))
+            .collect::<Vec<_>>(),
+        expected_messages
+    );
+}
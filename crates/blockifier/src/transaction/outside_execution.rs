@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use num_traits::ToPrimitive;
+use starknet_api::block::BlockTimestamp;
+use starknet_api::core::{ContractAddress, EntryPointSelector, PatriciaKey};
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Calldata;
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::execution::call_info::CallInfo;
+use crate::execution::execution_utils::stark_felt_to_felt;
+use crate::transaction::errors::OutsideExecutionError;
+
+#[cfg(test)]
+#[path = "outside_execution_test.rs"]
+mod test;
+
+pub type OutsideExecutionResult<T> = Result<T, OutsideExecutionError>;
+
+/// The entry points of accounts supporting outside execution (SNIP-9), by version.
+pub const EXECUTE_FROM_OUTSIDE_ENTRY_POINT_NAME: &str = "execute_from_outside";
+pub const EXECUTE_FROM_OUTSIDE_V2_ENTRY_POINT_NAME: &str = "execute_from_outside_v2";
+/// The short string 'ANY_CALLER'; allows any contract to relay the outside execution.
+pub const ANY_CALLER: &str = "0x414e595f43414c4c4552";
+
+/// A call the account makes on behalf of the signer of the outside execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutsideCall {
+    pub to: ContractAddress,
+    pub selector: EntryPointSelector,
+    pub calldata: Calldata,
+}
+
+/// An outside execution (meta-transaction) envelope, as passed to `execute_from_outside`: a set of
+/// calls signed by the account owner, to be relayed by another contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutsideExecution {
+    pub caller: ContractAddress,
+    pub nonce: StarkFelt,
+    pub execute_after: BlockTimestamp,
+    pub execute_before: BlockTimestamp,
+    pub calls: Vec<OutsideCall>,
+    pub signature: Vec<StarkFelt>,
+}
+
+impl OutsideExecution {
+    /// Returns whether the given selector is one of the outside execution entry points.
+    pub fn is_entry_point(selector: EntryPointSelector) -> bool {
+        [EXECUTE_FROM_OUTSIDE_ENTRY_POINT_NAME, EXECUTE_FROM_OUTSIDE_V2_ENTRY_POINT_NAME]
+            .into_iter()
+            .any(|name| selector_from_name(name) == selector)
+    }
+
+    /// Parses the calldata of an outside execution entry point: the serialized envelope, followed
+    /// by its signature.
+    pub fn from_calldata(calldata: &[StarkFelt]) -> OutsideExecutionResult<Self> {
+        let mut reader = CalldataReader { calldata, offset: 0 };
+        let caller = ContractAddress(PatriciaKey::try_from(reader.read()?)?);
+        let nonce = reader.read()?;
+        let execute_after = BlockTimestamp(reader.read_u64()?);
+        let execute_before = BlockTimestamp(reader.read_u64()?);
+        let calls = (0..reader.read_len()?)
+            .map(|_| {
+                Ok(OutsideCall {
+                    to: ContractAddress(PatriciaKey::try_from(reader.read()?)?),
+                    selector: EntryPointSelector(reader.read()?),
+                    calldata: Calldata(reader.read_array()?.into()),
+                })
+            })
+            .collect::<OutsideExecutionResult<_>>()?;
+        let signature = reader.read_array()?;
+        if reader.offset != calldata.len() {
+            return Err(OutsideExecutionError::MalformedCalldata { length: calldata.len() });
+        }
+
+        Ok(Self { caller, nonce, execute_after, execute_before, calls, signature })
+    }
+
+    /// Validates the envelope as the account would when relayed by `relayer` at the given block
+    /// timestamp: the timestamp must be strictly within the execution window, the relayer must be
+    /// the designated caller (unless any caller is allowed), and the nonce must not have been used
+    /// by the account before.
+    pub fn validate(
+        &self,
+        account: ContractAddress,
+        relayer: ContractAddress,
+        block_timestamp: BlockTimestamp,
+        used_nonces: &OutsideExecutionNonces,
+    ) -> OutsideExecutionResult<()> {
+        if !(self.execute_after < block_timestamp && block_timestamp < self.execute_before) {
+            return Err(OutsideExecutionError::OutsideExecutionWindow {
+                block_timestamp,
+                execute_after: self.execute_after,
+                execute_before: self.execute_before,
+            });
+        }
+        if *self.caller.0.key() != any_caller() && self.caller != relayer {
+            return Err(OutsideExecutionError::InvalidCaller { caller: self.caller, relayer });
+        }
+        if used_nonces.contains(account, self.nonce) {
+            return Err(OutsideExecutionError::NonceAlreadyUsed { account, nonce: self.nonce });
+        }
+
+        Ok(())
+    }
+}
+
+fn any_caller() -> StarkFelt {
+    StarkFelt::try_from(ANY_CALLER).expect("'ANY_CALLER' is a valid felt.")
+}
+
+/// The outside execution nonces used by each account. Unlike transaction nonces, these are not
+/// sequential: each nonce may be used once, in any order.
+#[derive(Clone, Debug, Default)]
+pub struct OutsideExecutionNonces(HashSet<(ContractAddress, StarkFelt)>);
+
+impl OutsideExecutionNonces {
+    pub fn contains(&self, account: ContractAddress, nonce: StarkFelt) -> bool {
+        self.0.contains(&(account, nonce))
+    }
+
+    /// Marks the nonce as used by the account; returns false if it was already used.
+    pub fn insert(&mut self, account: ContractAddress, nonce: StarkFelt) -> bool {
+        self.0.insert((account, nonce))
+    }
+}
+
+/// An outside execution found in a trace: a logical sub-transaction of the relaying transaction.
+#[derive(Debug)]
+pub struct OutsideExecutionCallInfo<'a> {
+    pub account: ContractAddress,
+    pub relayer: ContractAddress,
+    pub outside_execution: OutsideExecution,
+    /// The calls made by the account on behalf of the signer.
+    pub inner_calls: &'a [CallInfo],
+}
+
+/// Returns the outside executions relayed in the given call tree, in execution order. Calls to an
+/// outside execution entry point whose calldata is not a valid envelope are skipped.
+pub fn find_outside_executions(call_info: &CallInfo) -> Vec<OutsideExecutionCallInfo<'_>> {
+    call_info
+        .iter()
+        .filter(|call_info| OutsideExecution::is_entry_point(call_info.call.entry_point_selector))
+        .filter_map(|call_info| {
+            let outside_execution =
+                OutsideExecution::from_calldata(&call_info.call.calldata.0).ok()?;
+            Some(OutsideExecutionCallInfo {
+                account: call_info.call.storage_address,
+                relayer: call_info.call.caller_address,
+                outside_execution,
+                inner_calls: &call_info.inner_calls,
+            })
+        })
+        .collect()
+}
+
+struct CalldataReader<'a> {
+    calldata: &'a [StarkFelt],
+    offset: usize,
+}
+
+impl CalldataReader<'_> {
+    fn read(&mut self) -> OutsideExecutionResult<StarkFelt> {
+        let felt = self
+            .calldata
+            .get(self.offset)
+            .ok_or(OutsideExecutionError::MalformedCalldata { length: self.calldata.len() })?;
+        self.offset += 1;
+        Ok(*felt)
+    }
+
+    fn read_u64(&mut self) -> OutsideExecutionResult<u64> {
+        let felt = self.read()?;
+        stark_felt_to_felt(felt).to_u64().ok_or(OutsideExecutionError::InvalidValue(felt))
+    }
+
+    fn read_len(&mut self) -> OutsideExecutionResult<usize> {
+        let felt = self.read()?;
+        stark_felt_to_felt(felt)
+            .to_usize()
+            .filter(|len| *len <= self.calldata.len() - self.offset)
+            .ok_or(OutsideExecutionError::InvalidValue(felt))
+    }
+
+    fn read_array(&mut self) -> OutsideExecutionResult<Vec<StarkFelt>> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read()).collect()
+    }
+}
@@ -0,0 +1,157 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::block::BlockTimestamp;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::execution::call_info::CallInfo;
+use crate::execution::entry_point::CallEntryPoint;
+use crate::transaction::errors::OutsideExecutionError;
+use crate::transaction::outside_execution::{
+    find_outside_executions, OutsideCall, OutsideExecution, OutsideExecutionNonces, ANY_CALLER,
+    EXECUTE_FROM_OUTSIDE_V2_ENTRY_POINT_NAME,
+};
+
+const ACCOUNT: &str = "0x100";
+const RELAYER: &str = "0x200";
+
+fn outside_execution_calldata(caller: StarkFelt) -> Calldata {
+    calldata![
+        caller,
+        stark_felt!(7_u8),      // Nonce.
+        stark_felt!(10_u8),     // Execute after.
+        stark_felt!(20_u8),     // Execute before.
+        stark_felt!(1_u8),      // Calls length.
+        stark_felt!(0x300_u16), // To.
+        selector_from_name("transfer").0,
+        stark_felt!(2_u8), // Calldata length.
+        stark_felt!(1_u8),
+        stark_felt!(2_u8),
+        stark_felt!(1_u8), // Signature length.
+        stark_felt!(3_u8)
+    ]
+}
+
+fn outside_execution() -> OutsideExecution {
+    OutsideExecution::from_calldata(&outside_execution_calldata(stark_felt!(RELAYER)).0).unwrap()
+}
+
+#[test]
+fn test_from_calldata() {
+    assert_eq!(
+        outside_execution(),
+        OutsideExecution {
+            caller: contract_address!(RELAYER),
+            nonce: stark_felt!(7_u8),
+            execute_after: BlockTimestamp(10),
+            execute_before: BlockTimestamp(20),
+            calls: vec![OutsideCall {
+                to: contract_address!("0x300"),
+                selector: selector_from_name("transfer"),
+                calldata: calldata![stark_felt!(1_u8), stark_felt!(2_u8)],
+            }],
+            signature: vec![stark_felt!(3_u8)],
+        }
+    );
+
+    let calldata = outside_execution_calldata(stark_felt!(RELAYER));
+    for length in [0, 5, calldata.0.len() - 1] {
+        assert_matches!(
+            OutsideExecution::from_calldata(&calldata.0[..length]),
+            Err(OutsideExecutionError::MalformedCalldata { .. })
+                | Err(OutsideExecutionError::InvalidValue(_))
+        );
+    }
+}
+
+#[rstest]
+#[case::valid(RELAYER, 15, None)]
+#[case::any_caller(ANY_CALLER, 15, None)]
+#[case::too_early(RELAYER, 10, Some("window"))]
+#[case::too_late(RELAYER, 20, Some("window"))]
+#[case::wrong_caller("0x201", 15, Some("caller"))]
+fn test_validate(
+    #[case] caller: &str,
+    #[case] block_timestamp: u64,
+    #[case] expected_error: Option<&str>,
+) {
+    let outside_execution =
+        OutsideExecution::from_calldata(&outside_execution_calldata(stark_felt!(caller)).0)
+            .unwrap();
+    let result = outside_execution.validate(
+        contract_address!(ACCOUNT),
+        contract_address!(RELAYER),
+        BlockTimestamp(block_timestamp),
+        &OutsideExecutionNonces::default(),
+    );
+    match expected_error {
+        None => result.unwrap(),
+        Some("window") => {
+            assert_matches!(result, Err(OutsideExecutionError::OutsideExecutionWindow { .. }))
+        }
+        Some(_) => assert_matches!(result, Err(OutsideExecutionError::InvalidCaller { .. })),
+    }
+}
+
+#[test]
+fn test_validate_nonce() {
+    let outside_execution = outside_execution();
+    let mut used_nonces = OutsideExecutionNonces::default();
+    assert!(used_nonces.insert(contract_address!(ACCOUNT), outside_execution.nonce));
+    assert!(!used_nonces.insert(contract_address!(ACCOUNT), outside_execution.nonce));
+
+    assert_matches!(
+        outside_execution.validate(
+            contract_address!(ACCOUNT),
+            contract_address!(RELAYER),
+            BlockTimestamp(15),
+            &used_nonces,
+        ),
+        Err(OutsideExecutionError::NonceAlreadyUsed { .. })
+    );
+    // Nonces are tracked per account.
+    outside_execution
+        .validate(
+            contract_address!("0x101"),
+            contract_address!(RELAYER),
+            BlockTimestamp(15),
+            &used_nonces,
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_find_outside_executions() {
+    let outside_execution_call = CallInfo {
+        call: CallEntryPoint {
+            storage_address: contract_address!(ACCOUNT),
+            caller_address: contract_address!(RELAYER),
+            entry_point_selector: selector_from_name(EXECUTE_FROM_OUTSIDE_V2_ENTRY_POINT_NAME),
+            calldata: outside_execution_calldata(stark_felt!(RELAYER)),
+            ..Default::default()
+        },
+        inner_calls: vec![CallInfo::default()],
+        ..Default::default()
+    };
+    let relayer_call = CallInfo {
+        call: CallEntryPoint {
+            storage_address: contract_address!(RELAYER),
+            entry_point_selector: selector_from_name("__execute__"),
+            ..Default::default()
+        },
+        inner_calls: vec![outside_execution_call],
+        ..Default::default()
+    };
+
+    let outside_executions = find_outside_executions(&relayer_call);
+    assert_eq!(outside_executions.len(), 1);
+    let found = &outside_executions[0];
+    assert_eq!(found.account, contract_address!(ACCOUNT));
+    assert_eq!(found.relayer, contract_address!(RELAYER));
+    assert_eq!(found.outside_execution, outside_execution());
+    assert_eq!(found.inner_calls.len(), 1);
+}
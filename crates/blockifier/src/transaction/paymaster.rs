@@ -0,0 +1,62 @@
+use starknet_api::core::{ContractAddress, EntryPointSelector, PatriciaKey};
+use starknet_api::transaction::Calldata;
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::transaction::constants;
+use crate::transaction::errors::TransactionFeeError;
+use crate::transaction::objects::{TransactionFeeResult, TransactionInfo};
+
+#[cfg(test)]
+#[path = "paymaster_test.rs"]
+mod test;
+
+/// Enables the paymaster flow of V3 transactions, when registered in the block context
+/// extensions; otherwise, paymaster data is ignored, as on Starknet.
+/// The paymaster data of sponsored transactions is laid out as
+/// `[paymaster_address, ...paymaster_calldata]`: once the sender's validation passes, the
+/// paymaster's validation entry point is called with the paymaster calldata, and must return
+/// `VALID` (for Cairo 1 paymasters). The paymaster then pays the transaction fee instead of the
+/// sender.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymasterConfig {
+    pub validate_entry_point_selector: EntryPointSelector,
+}
+
+impl Default for PaymasterConfig {
+    fn default() -> Self {
+        Self::new(selector_from_name(constants::VALIDATE_PAYMASTER_ENTRY_POINT_NAME))
+    }
+}
+
+impl PaymasterConfig {
+    pub fn new(validate_entry_point_selector: EntryPointSelector) -> Self {
+        Self { validate_entry_point_selector }
+    }
+}
+
+/// The paymaster sponsoring a transaction, decoded from its paymaster data.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Paymaster {
+    pub address: ContractAddress,
+    /// The calldata of the paymaster's validation entry point.
+    pub calldata: Calldata,
+}
+
+impl Paymaster {
+    /// Returns `None` for transactions without paymaster data.
+    pub fn from_tx_info(tx_info: &TransactionInfo) -> TransactionFeeResult<Option<Self>> {
+        let TransactionInfo::Current(context) = tx_info else {
+            return Ok(None);
+        };
+        let Some((&address, calldata)) = context.paymaster_data.0.split_first() else {
+            return Ok(None);
+        };
+
+        let address = PatriciaKey::try_from(address)
+            .map_err(|_| TransactionFeeError::InvalidPaymasterAddress(address))?;
+        Ok(Some(Self {
+            address: ContractAddress(address),
+            calldata: Calldata(calldata.to_vec().into()),
+        }))
+    }
+}
@@ -0,0 +1,176 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{
+    Calldata, PaymasterData, ResourceBoundsMapping, TransactionVersion,
+};
+use starknet_api::{contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::context::BlockContext;
+use crate::execution::call_info::{CallPath, TopLevelCall};
+use crate::invoke_tx_args;
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::StateReader;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::dict_state_reader::DictStateReader;
+use crate::test_utils::initial_test_state::fund_account;
+use crate::test_utils::{CairoVersion, BALANCE};
+use crate::transaction::constants;
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
+use crate::transaction::objects::{
+    CurrentTransactionInfo, FeeType, TransactionExecutionInfo, TransactionExecutionResult,
+    TransactionInfo,
+};
+use crate::transaction::paymaster::{Paymaster, PaymasterConfig};
+use crate::transaction::test_utils::{
+    block_context, max_resource_bounds, run_invoke_tx, trivial_invoke_setup,
+};
+
+fn tx_info_with_paymaster_data(paymaster_data: Vec<StarkFelt>) -> TransactionInfo {
+    TransactionInfo::Current(CurrentTransactionInfo {
+        common_fields: Default::default(),
+        resource_bounds: ResourceBoundsMapping::default(),
+        tip: Default::default(),
+        nonce_data_availability_mode: Default::default(),
+        fee_data_availability_mode: Default::default(),
+        paymaster_data: PaymasterData(paymaster_data),
+        account_deployment_data: Default::default(),
+    })
+}
+
+#[test]
+fn test_paymaster_from_tx_info() {
+    assert_eq!(Paymaster::from_tx_info(&tx_info_with_paymaster_data(vec![])).unwrap(), None);
+    assert_eq!(
+        Paymaster::from_tx_info(&tx_info_with_paymaster_data(vec![
+            stark_felt!(0x100_u16),
+            stark_felt!(7_u8)
+        ]))
+        .unwrap(),
+        Some(Paymaster {
+            address: contract_address!("0x100"),
+            calldata: Calldata(vec![stark_felt!(7_u8)].into()),
+        })
+    );
+
+    let invalid_address =
+        StarkFelt::try_from("0x800000000000011000000000000000000000000000000000000000000000000")
+            .unwrap();
+    assert_matches!(
+        Paymaster::from_tx_info(&tx_info_with_paymaster_data(vec![invalid_address])),
+        Err(TransactionFeeError::InvalidPaymasterAddress(address)) if address == invalid_address
+    );
+}
+
+/// Sends a V3 invoke from one account, with paymaster data naming another account as paymaster.
+fn run_sponsored_invoke(
+    block_context: &BlockContext,
+    resource_bounds: ResourceBoundsMapping,
+) -> (
+    CachedState<DictStateReader>,
+    [ContractAddress; 2],
+    TransactionExecutionResult<TransactionExecutionInfo>,
+) {
+    let (mut state, invoke_args) =
+        trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo1);
+    let sender_address = invoke_args.sender_address;
+    // Deploy and fund a second instance of the account, as the paymaster.
+    let account_contract = FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1);
+    let paymaster_address = account_contract.get_instance_address(1);
+    state.state.address_to_class_hash.insert(paymaster_address, account_contract.get_class_hash());
+    fund_account(&block_context.chain_info, paymaster_address, BALANCE, &mut state);
+
+    let tx_execution_result = run_invoke_tx(
+        &mut state,
+        block_context,
+        invoke_tx_args! {
+            version: TransactionVersion::THREE,
+            resource_bounds,
+            paymaster_data: PaymasterData(vec![*paymaster_address.0.key(), stark_felt!(7_u8)]),
+            ..invoke_args
+        },
+    );
+    (state, [sender_address, paymaster_address], tx_execution_result)
+}
+
+fn strk_balance(
+    state: &mut CachedState<DictStateReader>,
+    block_context: &BlockContext,
+    address: ContractAddress,
+) -> StarkFelt {
+    let fee_token_address = block_context.chain_info.fee_token_address(&FeeType::Strk);
+    state.get_fee_token_balance(address, fee_token_address).unwrap().0
+}
+
+#[rstest]
+fn test_sponsored_transaction(
+    block_context: BlockContext,
+    max_resource_bounds: ResourceBoundsMapping,
+) {
+    // The test account has no `__validate_paymaster__` entry point; use another validation entry
+    // point taking a single felt.
+    block_context.extensions().insert(PaymasterConfig::new(selector_from_name(
+        constants::VALIDATE_DECLARE_ENTRY_POINT_NAME,
+    )));
+    let (mut state, [sender_address, paymaster_address], tx_execution_result) =
+        run_sponsored_invoke(&block_context, max_resource_bounds);
+    let tx_execution_info = tx_execution_result.unwrap();
+    assert!(!tx_execution_info.is_reverted());
+
+    let paymaster_call_info = tx_execution_info.paymaster_call_info.as_ref().unwrap();
+    assert_eq!(paymaster_call_info.call.storage_address, paymaster_address);
+    assert_eq!(paymaster_call_info.call.caller_address, sender_address);
+    assert_eq!(paymaster_call_info.call.calldata, Calldata(vec![stark_felt!(7_u8)].into()));
+
+    // The paymaster pays the fee.
+    let fee_transfer_call_info = tx_execution_info.fee_transfer_call_info.as_ref().unwrap();
+    assert_eq!(fee_transfer_call_info.call.caller_address, paymaster_address);
+    assert_eq!(strk_balance(&mut state, &block_context, sender_address), stark_felt!(BALANCE));
+    assert_eq!(
+        strk_balance(&mut state, &block_context, paymaster_address),
+        stark_felt!(BALANCE - tx_execution_info.actual_fee.0)
+    );
+
+    // The paymaster's validation is a top-level call of the trace.
+    let call_path =
+        CallPath { top_level_call: TopLevelCall::PaymasterValidate, inner_call_indices: vec![] };
+    let paymaster_call_info = tx_execution_info.into_sub_trace(&call_path).unwrap();
+    assert_eq!(paymaster_call_info.call.storage_address, paymaster_address);
+}
+
+#[rstest]
+fn test_paymaster_flow_disabled(
+    block_context: BlockContext,
+    max_resource_bounds: ResourceBoundsMapping,
+) {
+    let (mut state, [sender_address, paymaster_address], tx_execution_result) =
+        run_sponsored_invoke(&block_context, max_resource_bounds);
+    let tx_execution_info = tx_execution_result.unwrap();
+
+    // The paymaster data is ignored.
+    assert_eq!(tx_execution_info.paymaster_call_info, None);
+    assert_eq!(
+        strk_balance(&mut state, &block_context, sender_address),
+        stark_felt!(BALANCE - tx_execution_info.actual_fee.0)
+    );
+    assert_eq!(strk_balance(&mut state, &block_context, paymaster_address), stark_felt!(BALANCE));
+}
+
+#[rstest]
+fn test_failed_paymaster_validation(
+    block_context: BlockContext,
+    max_resource_bounds: ResourceBoundsMapping,
+) {
+    block_context.extensions().insert(PaymasterConfig::default());
+    let (_, [_, paymaster_address], tx_execution_result) =
+        run_sponsored_invoke(&block_context, max_resource_bounds);
+
+    assert_matches!(
+        tx_execution_result.unwrap_err(),
+        TransactionExecutionError::ValidatePaymasterError { storage_address, .. }
+        if storage_address == paymaster_address
+    );
+}
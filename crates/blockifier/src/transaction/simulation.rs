@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use starknet_api::transaction::Fee;
+
+use crate::context::BlockContext;
+use crate::fee::fee_utils::{calculate_tx_fee, calculate_tx_gas_vector};
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::StateReader;
+use crate::transaction::errors::TransactionSimulationError;
+use crate::transaction::objects::{
+    FeeType, HasRelatedFeeType, TransactionExecutionInfo, TransactionExecutionResult,
+};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[cfg(test)]
+#[path = "simulation_test.rs"]
+pub mod test;
+
+/// A simulation flag, as in the Starknet JSON-RPC `starknet_simulateTransactions` method.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SimulationFlag {
+    SkipValidate,
+    SkipFeeCharge,
+}
+
+/// Determines which stages of a simulated transaction are skipped.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SimulationFlags {
+    pub skip_validate: bool,
+    pub skip_fee_charge: bool,
+}
+
+impl FromIterator<SimulationFlag> for SimulationFlags {
+    fn from_iter<I: IntoIterator<Item = SimulationFlag>>(flags: I) -> Self {
+        let mut simulation_flags = Self::default();
+        for flag in flags {
+            match flag {
+                SimulationFlag::SkipValidate => simulation_flags.skip_validate = true,
+                SimulationFlag::SkipFeeCharge => simulation_flags.skip_fee_charge = true,
+            }
+        }
+
+        simulation_flags
+    }
+}
+
+/// The unit of a fee, as in the Starknet JSON-RPC `PRICE_UNIT`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PriceUnit {
+    Wei,
+    Fri,
+}
+
+impl From<FeeType> for PriceUnit {
+    fn from(fee_type: FeeType) -> Self {
+        match fee_type {
+            FeeType::Eth => Self::Wei,
+            FeeType::Strk => Self::Fri,
+        }
+    }
+}
+
+/// The fee estimate of a simulated transaction, as in the Starknet JSON-RPC `FEE_ESTIMATE`.
+/// The fee is estimated even if its charge was skipped.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SimulationFeeEstimate {
+    pub gas_consumed: u128,
+    pub gas_price: u128,
+    pub data_gas_consumed: u128,
+    pub data_gas_price: u128,
+    pub overall_fee: Fee,
+    pub unit: PriceUnit,
+}
+
+impl SimulationFeeEstimate {
+    fn new(
+        tx_execution_info: &TransactionExecutionInfo,
+        fee_type: FeeType,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<Self> {
+        let resources = &tx_execution_info.actual_resources;
+        let gas_vector = calculate_tx_gas_vector(resources, &block_context.versioned_constants)?;
+        let gas_prices = &block_context.block_info.gas_prices;
+        Ok(Self {
+            gas_consumed: gas_vector.l1_gas,
+            gas_price: gas_prices.get_gas_price_by_fee_type(&fee_type).get(),
+            data_gas_consumed: gas_vector.l1_data_gas,
+            data_gas_price: gas_prices.get_data_gas_price_by_fee_type(&fee_type).get(),
+            overall_fee: calculate_tx_fee(resources, block_context, &fee_type)?,
+            unit: fee_type.into(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SimulatedTransaction {
+    /// The execution info of the transaction, including its trace.
+    pub transaction_trace: TransactionExecutionInfo,
+    pub fee_estimation: SimulationFeeEstimate,
+}
+
+/// Simulates the given transactions, as the Starknet JSON-RPC `starknet_simulateTransactions`
+/// method: executes them in order, each over the state changes of the previous ones, without
+/// applying any of the changes to the given state. Fails on the first transaction that fails
+/// (reverted transactions do not fail).
+pub fn simulate_transactions<S: StateReader>(
+    txs: Vec<Transaction>,
+    state: &mut CachedState<S>,
+    block_context: &BlockContext,
+    flags: SimulationFlags,
+) -> Result<Vec<SimulatedTransaction>, TransactionSimulationError> {
+    let (charge_fee, validate) = (!flags.skip_fee_charge, !flags.skip_validate);
+    let mut forked_state = CachedState::create_transactional(state);
+    let mut simulated_txs = Vec::with_capacity(txs.len());
+    for (tx_index, tx) in txs.into_iter().enumerate() {
+        let fee_type = match &tx {
+            Transaction::AccountTransaction(account_tx) => account_tx.fee_type(),
+            Transaction::L1HandlerTransaction(l1_handler_tx) => l1_handler_tx.fee_type(),
+            Transaction::Custom(custom_tx) => custom_tx.fee_type(),
+        };
+        let simulation_result = tx
+            .execute(&mut forked_state, block_context, charge_fee, validate)
+            .and_then(|tx_execution_info| {
+                let fee_estimation =
+                    SimulationFeeEstimate::new(&tx_execution_info, fee_type, block_context)?;
+                Ok(SimulatedTransaction { transaction_trace: tx_execution_info, fee_estimation })
+            });
+        match simulation_result {
+            Ok(simulated_tx) => simulated_txs.push(simulated_tx),
+            Err(error) => {
+                forked_state.abort();
+                return Err(TransactionSimulationError { tx_index, error });
+            }
+        }
+    }
+
+    forked_state.abort();
+    Ok(simulated_txs)
+}
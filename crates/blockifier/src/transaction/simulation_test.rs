@@ -0,0 +1,87 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::Fee;
+
+use crate::context::BlockContext;
+use crate::invoke_tx_args;
+use crate::state::state_api::StateReader;
+use crate::test_utils::CairoVersion;
+use crate::transaction::errors::{TransactionExecutionError, TransactionSimulationError};
+use crate::transaction::simulation::{
+    simulate_transactions, PriceUnit, SimulationFlag, SimulationFlags,
+};
+use crate::transaction::test_utils::{account_invoke_tx, block_context, trivial_invoke_setup};
+use crate::transaction::transaction_execution::Transaction;
+
+#[test]
+fn test_simulation_flags_serde() {
+    let flags: Vec<SimulationFlag> =
+        serde_json::from_str(r#"["SKIP_VALIDATE", "SKIP_FEE_CHARGE"]"#).unwrap();
+    assert_eq!(
+        flags.into_iter().collect::<SimulationFlags>(),
+        SimulationFlags { skip_validate: true, skip_fee_charge: true }
+    );
+    assert_eq!(serde_json::to_string(&PriceUnit::Wei).unwrap(), r#""WEI""#);
+}
+
+#[rstest]
+fn test_simulate_transactions(
+    block_context: BlockContext,
+    #[values(false, true)] skip_validate: bool,
+    #[values(false, true)] skip_fee_charge: bool,
+) {
+    let (mut state, invoke_args) =
+        trivial_invoke_setup(&block_context.chain_info, CairoVersion::Cairo1);
+    let sender_address = invoke_args.sender_address;
+    let invoke_tx = |nonce: u8| {
+        Transaction::AccountTransaction(account_invoke_tx(invoke_tx_args! {
+            nonce: Nonce(stark_felt!(nonce)),
+            ..invoke_args.clone()
+        }))
+    };
+    let flags = SimulationFlags { skip_validate, skip_fee_charge };
+
+    // Each transaction is executed over the state changes of the previous ones.
+    let simulated_txs =
+        simulate_transactions(vec![invoke_tx(0), invoke_tx(1)], &mut state, &block_context, flags)
+            .unwrap();
+    assert_eq!(simulated_txs.len(), 2);
+    for simulated_tx in &simulated_txs {
+        let (trace, fee_estimation) =
+            (&simulated_tx.transaction_trace, simulated_tx.fee_estimation);
+        assert!(!trace.is_reverted());
+        assert_eq!(trace.validate_call_info.is_none(), skip_validate);
+        assert_eq!(trace.fee_transfer_call_info.is_none(), skip_fee_charge);
+        // The fee is estimated even if its charge is skipped.
+        assert!(fee_estimation.overall_fee > Fee(0));
+        assert_eq!(fee_estimation.unit, PriceUnit::Wei);
+        assert_eq!(
+            fee_estimation.overall_fee.0,
+            fee_estimation.gas_consumed * fee_estimation.gas_price
+                + fee_estimation.data_gas_consumed * fee_estimation.data_gas_price
+        );
+        if !skip_fee_charge {
+            assert_eq!(fee_estimation.overall_fee, trace.actual_fee);
+        }
+    }
+
+    // The given state is left untouched.
+    assert_eq!(state.get_nonce_at(sender_address).unwrap(), Nonce::default());
+
+    // The failed transaction is reported by its index.
+    let error =
+        simulate_transactions(vec![invoke_tx(0), invoke_tx(0)], &mut state, &block_context, flags)
+            .unwrap_err();
+    assert_matches!(
+        error,
+        TransactionSimulationError {
+            tx_index: 1,
+            error: TransactionExecutionError::TransactionPreValidationError(_)
+        }
+    );
+    assert_eq!(state.get_nonce_at(sender_address).unwrap(), Nonce::default());
+}
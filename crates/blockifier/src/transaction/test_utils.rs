@@ -21,8 +21,8 @@ use crate::test_utils::dict_state_reader::DictStateReader;
 use crate::test_utils::initial_test_state::test_state;
 use crate::test_utils::invoke::{invoke_tx, InvokeTxArgs};
 use crate::test_utils::{
-    create_calldata, CairoVersion, NonceManager, BALANCE, MAX_FEE, MAX_L1_GAS_AMOUNT,
-    MAX_L1_GAS_PRICE,
+    create_calldata, create_trivial_calldata, CairoVersion, NonceManager, BALANCE, MAX_FEE,
+    MAX_L1_GAS_AMOUNT, MAX_L1_GAS_PRICE,
 };
 use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::constants;
@@ -126,6 +126,24 @@ pub fn create_test_init_data(chain_info: &ChainInfo, cairo_version: CairoVersion
     }
 }
 
+/// Initializes a state with an account without validations and a test contract, of the given
+/// Cairo version; returns it with the arguments of an invoke transaction from the account, calling
+/// the test contract trivially.
+pub fn trivial_invoke_setup(
+    chain_info: &ChainInfo,
+    cairo_version: CairoVersion,
+) -> (CachedState<DictStateReader>, InvokeTxArgs) {
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let account = FeatureContract::AccountWithoutValidations(cairo_version);
+    let state = test_state(chain_info, BALANCE, &[(test_contract, 1), (account, 1)]);
+    let invoke_args = invoke_tx_args! {
+        sender_address: account.get_instance_address(0),
+        calldata: create_trivial_calldata(test_contract.get_instance_address(0)),
+    };
+
+    (state, invoke_args)
+}
+
 pub struct FaultyAccountTxCreatorArgs {
     pub tx_type: TransactionType,
     pub scenario: u64,
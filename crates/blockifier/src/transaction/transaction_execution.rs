@@ -1,33 +1,59 @@
 use std::sync::Arc;
 
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
-use starknet_api::core::{calculate_contract_address, ContractAddress};
+use starknet_api::core::{calculate_contract_address, ClassHash, ContractAddress};
 use starknet_api::transaction::{Fee, Transaction as StarknetApiTransaction, TransactionHash};
 
 use crate::context::BlockContext;
+use crate::execution::call_info::TopLevelCall;
 use crate::execution::contract_class::ClassInfo;
 use crate::execution::entry_point::EntryPointExecutionContext;
-use crate::fee::actual_cost::ActualCost;
+use crate::fee::actual_cost::{ActualCost, ActualCostBuilder};
 use crate::state::cached_state::TransactionalState;
-use crate::state::state_api::StateReader;
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
 use crate::transaction::account_transaction::AccountTransaction;
-use crate::transaction::errors::TransactionFeeError;
+use crate::transaction::custom_transaction::{CustomTransaction, CustomTransactionRegistry};
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
 use crate::transaction::objects::{
     TransactionExecutionInfo, TransactionExecutionResult, TransactionInfo, TransactionInfoCreator,
 };
+use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transactions::{
     DeclareTransaction, DeployAccountTransaction, Executable, ExecutableTransaction,
     InvokeTransaction, L1HandlerTransaction,
 };
 
+#[cfg(test)]
+#[path = "transaction_execution_test.rs"]
+pub mod test;
+
+/// An execution extension; when set, transactions verify that all the classes they statically
+/// reference are declared before executing, failing fast with `ClassNotFound` instead of failing
+/// mid-execution.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassAvailabilityPreflight;
+
 // TODO: Move into transaction.rs, makes more sense to be defined there.
-#[derive(Debug, derive_more::From)]
+#[derive(Clone, Debug, derive_more::From)]
 pub enum Transaction {
     AccountTransaction(AccountTransaction),
     L1HandlerTransaction(L1HandlerTransaction),
+    Custom(CustomTransaction),
 }
 
 impl Transaction {
+    /// Converts a `starknet_api` transaction into an executable transaction.
+    ///
+    /// * `class_info` is required for `Declare` transactions, and can be assembled from the raw
+    ///   compiled class using `ClassInfo::from_raw_contract_class`.
+    /// * `paid_fee_on_l1` is required for `L1Handler` transactions.
+    /// * `deployed_contract_address` is optional for `DeployAccount` transactions; if not given, it
+    ///   is calculated from the transaction fields.
+    /// * `only_query` marks account transactions as query-only (simulation/fee estimation).
+    ///
+    /// The transaction version is validated against the versions supported by its type; inputs
+    /// irrelevant to the transaction type are ignored.
     pub fn from_api(
         tx: StarknetApiTransaction,
         tx_hash: TransactionHash,
@@ -36,25 +62,34 @@ impl Transaction {
         deployed_contract_address: Option<ContractAddress>,
         only_query: bool,
     ) -> TransactionExecutionResult<Self> {
-        match tx {
+        let account_tx = match tx {
             StarknetApiTransaction::L1Handler(l1_handler) => {
-                Ok(Self::L1HandlerTransaction(L1HandlerTransaction {
+                let paid_fee_on_l1 = paid_fee_on_l1.ok_or_else(|| {
+                    TransactionExecutionError::MissingTransactionInput {
+                        tx_type: TransactionType::L1Handler,
+                        missing_input: "the fee paid on L1".to_string(),
+                    }
+                })?;
+                return Ok(Self::L1HandlerTransaction(L1HandlerTransaction {
                     tx: l1_handler,
                     tx_hash,
-                    paid_fee_on_l1: paid_fee_on_l1
-                        .expect("L1Handler should be created with the fee paid on L1"),
-                }))
+                    paid_fee_on_l1,
+                }));
             }
             StarknetApiTransaction::Declare(declare) => {
-                let non_optional_class_info =
-                    class_info.expect("Declare should be created with a ClassInfo.");
+                let non_optional_class_info = class_info.ok_or_else(|| {
+                    TransactionExecutionError::MissingTransactionInput {
+                        tx_type: TransactionType::Declare,
+                        missing_input: "a class info".to_string(),
+                    }
+                })?;
                 let declare_tx = match only_query {
                     true => {
                         DeclareTransaction::new_for_query(declare, tx_hash, non_optional_class_info)
                     }
                     false => DeclareTransaction::new(declare, tx_hash, non_optional_class_info),
                 };
-                Ok(Self::AccountTransaction(AccountTransaction::Declare(declare_tx?)))
+                AccountTransaction::Declare(declare_tx?)
             }
             StarknetApiTransaction::DeployAccount(deploy_account) => {
                 let contract_address = match deployed_contract_address {
@@ -76,16 +111,71 @@ impl Transaction {
                         DeployAccountTransaction::new(deploy_account, tx_hash, contract_address)
                     }
                 };
-                Ok(Self::AccountTransaction(AccountTransaction::DeployAccount(deploy_account_tx)))
+                AccountTransaction::DeployAccount(deploy_account_tx)
             }
             StarknetApiTransaction::Invoke(invoke) => {
                 let invoke_tx = match only_query {
                     true => InvokeTransaction::new_for_query(invoke, tx_hash),
                     false => InvokeTransaction::new(invoke, tx_hash),
                 };
-                Ok(Self::AccountTransaction(AccountTransaction::Invoke(invoke_tx)))
+                AccountTransaction::Invoke(invoke_tx)
             }
-            _ => unimplemented!(),
+            StarknetApiTransaction::Deploy(_) => {
+                return Err(TransactionExecutionError::UnsupportedTransactionType(
+                    "Deploy".to_string(),
+                ));
+            }
+        };
+
+        account_tx.verify_tx_version(account_tx.create_tx_info().version())?;
+        Ok(Self::AccountTransaction(account_tx))
+    }
+
+    pub fn tx_hash(&self) -> TransactionHash {
+        match self {
+            Self::AccountTransaction(AccountTransaction::Declare(tx)) => tx.tx_hash(),
+            Self::AccountTransaction(AccountTransaction::DeployAccount(tx)) => tx.tx_hash,
+            Self::AccountTransaction(AccountTransaction::Invoke(tx)) => tx.tx_hash,
+            Self::L1HandlerTransaction(tx) => tx.tx_hash,
+            Self::Custom(tx) => tx.tx_hash,
+        }
+    }
+
+    /// Returns the hashes of the classes the transaction statically references: the class of the
+    /// sender account (or of the deployed account), or the class of the L1 handler's contract.
+    /// The class declared by a `Declare` transaction is carried by the transaction itself, and
+    /// contracts which are not deployed are left for the execution to report.
+    pub fn referenced_class_hashes(&self, state: &dyn StateReader) -> StateResult<Vec<ClassHash>> {
+        let class_hash = match self {
+            Self::AccountTransaction(AccountTransaction::DeployAccount(tx)) => tx.class_hash(),
+            Self::AccountTransaction(account_tx) => {
+                state.get_class_hash_at(account_tx.create_tx_info().sender_address())?
+            }
+            Self::L1HandlerTransaction(tx) => state.get_class_hash_at(tx.tx.contract_address)?,
+            Self::Custom(tx) => state.get_class_hash_at(tx.sender_address)?,
+        };
+
+        Ok(match class_hash == ClassHash::default() {
+            true => vec![],
+            false => vec![class_hash],
+        })
+    }
+
+    /// Verifies that all the classes referenced by the transaction are declared in the given
+    /// state; otherwise, fails with the list of missing classes.
+    pub fn preflight_classes(&self, state: &dyn StateReader) -> TransactionExecutionResult<()> {
+        let mut class_hashes = vec![];
+        for class_hash in self.referenced_class_hashes(state)? {
+            match state.get_compiled_contract_class(class_hash) {
+                Ok(_) => {}
+                Err(StateError::UndeclaredClassHash(_)) => class_hashes.push(class_hash),
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        match class_hashes.is_empty() {
+            true => Ok(()),
+            false => Err(TransactionExecutionError::ClassNotFound { class_hashes }),
         }
     }
 }
@@ -95,6 +185,7 @@ impl TransactionInfoCreator for Transaction {
         match self {
             Self::AccountTransaction(account_tx) => account_tx.create_tx_info(),
             Self::L1HandlerTransaction(l1_handler_tx) => l1_handler_tx.create_tx_info(),
+            Self::Custom(custom_tx) => custom_tx.create_tx_info(),
         }
     }
 }
@@ -111,16 +202,20 @@ impl<S: StateReader> ExecutableTransaction<S> for L1HandlerTransaction {
 
         let mut execution_resources = ExecutionResources::default();
         let mut context = EntryPointExecutionContext::new_invoke(tx_context.clone(), true)?;
+        context.record_step_deltas(TopLevelCall::Execute);
         let mut remaining_gas = block_context.versioned_constants.tx_initial_gas();
         let execute_call_info =
             self.run_execute(state, &mut execution_resources, &mut context, &mut remaining_gas)?;
         let l1_handler_payload_size = self.payload_size();
 
-        let (ActualCost { actual_fee, da_gas, actual_resources }, _bouncer_resources) =
-            ActualCost::builder_for_l1_handler(tx_context, l1_handler_payload_size)?
-                .with_execute_call_info(&execute_call_info)
-                .try_add_state_changes(state)?
-                .build(&execution_resources)?;
+        let (
+            ActualCost { actual_fee, da_gas, actual_resources, fee_trace, message_resources },
+            _bouncer_resources,
+        ) = ActualCost::builder_for_l1_handler(tx_context, l1_handler_payload_size)?
+            .with_consumed_l1_to_l2_nonce(self.tx.nonce)
+            .with_execute_call_info(&execute_call_info)
+            .try_add_state_changes(state)?
+            .build(&execution_resources)?;
 
         let paid_fee = self.paid_fee_on_l1;
         // For now, assert only that any amount of fee was paid.
@@ -131,13 +226,75 @@ impl<S: StateReader> ExecutableTransaction<S> for L1HandlerTransaction {
 
         Ok(TransactionExecutionInfo {
             validate_call_info: None,
+            paymaster_call_info: None,
             execute_call_info,
             fee_transfer_call_info: None,
             actual_fee: Fee::default(),
             da_gas,
             actual_resources: actual_resources.clone(),
             revert_error: None,
+            revert_reason: None,
             bouncer_resources: actual_resources,
+            fee_trace,
+            message_resources,
+        })
+    }
+}
+
+impl<S: StateReader> ExecutableTransaction<S> for CustomTransaction {
+    fn execute_raw(
+        self,
+        state: &mut TransactionalState<'_, S>,
+        block_context: &BlockContext,
+        _charge_fee: bool,
+        _validate: bool,
+    ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        let tx_type = self.tx_type();
+        let handler = block_context
+            .extensions()
+            .get::<CustomTransactionRegistry>()
+            .and_then(|registry| registry.handler(self.type_id))
+            .ok_or_else(|| {
+                TransactionExecutionError::UnsupportedTransactionType(tx_type.to_string())
+            })?;
+        if !block_context.versioned_constants.has_os_resources_for_tx_type(&tx_type) {
+            return Err(TransactionExecutionError::MissingOsResources { tx_type });
+        }
+        let tx_context = Arc::new(block_context.to_tx_context(&self));
+
+        let mut execution_resources = ExecutionResources::default();
+        let mut context = EntryPointExecutionContext::new_invoke(tx_context.clone(), true)?;
+        context.record_step_deltas(TopLevelCall::Execute);
+        let mut remaining_gas = block_context.versioned_constants.tx_initial_gas();
+        let execute_call_info = handler.execute(
+            &self,
+            state,
+            &mut execution_resources,
+            &mut context,
+            &mut remaining_gas,
+        )?;
+
+        let signature_length = 0;
+        let (ActualCost { da_gas, actual_resources, fee_trace, message_resources, .. }, _) =
+            ActualCostBuilder::new(tx_context, tx_type, self.calldata.0.len(), signature_length)?
+                .without_sender_address()
+                .with_execute_call_info(&execute_call_info)
+                .try_add_state_changes(state)?
+                .build(&execution_resources)?;
+
+        Ok(TransactionExecutionInfo {
+            validate_call_info: None,
+            paymaster_call_info: None,
+            execute_call_info,
+            fee_transfer_call_info: None,
+            actual_fee: Fee::default(),
+            da_gas,
+            actual_resources: actual_resources.clone(),
+            revert_error: None,
+            revert_reason: None,
+            bouncer_resources: actual_resources,
+            fee_trace,
+            message_resources,
         })
     }
 }
@@ -150,6 +307,10 @@ impl<S: StateReader> ExecutableTransaction<S> for Transaction {
         charge_fee: bool,
         validate: bool,
     ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        if block_context.extensions().contains::<ClassAvailabilityPreflight>() {
+            self.preflight_classes(state)?;
+        }
+
         match self {
             Self::AccountTransaction(account_tx) => {
                 account_tx.execute_raw(state, block_context, charge_fee, validate)
@@ -157,6 +318,7 @@ impl<S: StateReader> ExecutableTransaction<S> for Transaction {
             Self::L1HandlerTransaction(tx) => {
                 tx.execute_raw(state, block_context, charge_fee, validate)
             }
+            Self::Custom(tx) => tx.execute_raw(state, block_context, charge_fee, validate),
         }
     }
 }
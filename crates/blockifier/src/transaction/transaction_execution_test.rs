@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::transaction::Fee;
+
+use crate::context::{BlockContext, ChainInfo};
+use crate::state::cached_state::CachedState;
+use crate::test_utils::contracts::FeatureContract;
+use crate::test_utils::dict_state_reader::DictStateReader;
+use crate::test_utils::initial_test_state::test_state;
+use crate::test_utils::CairoVersion;
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::transaction_execution::{ClassAvailabilityPreflight, Transaction};
+use crate::transaction::transactions::{ExecutableTransaction, L1HandlerTransaction};
+
+#[test]
+fn test_class_availability_preflight() {
+    let test_contract = FeatureContract::TestContract(CairoVersion::Cairo1);
+    let contract_address = test_contract.get_instance_address(0);
+    let class_hash = test_contract.get_class_hash();
+    let l1_handler_tx =
+        || Transaction::from(L1HandlerTransaction::create_for_testing(Fee(1), contract_address));
+
+    // The contract is deployed and its class is declared.
+    let state = test_state(&ChainInfo::create_for_testing(), 0, &[(test_contract, 1)]);
+    assert_eq!(l1_handler_tx().referenced_class_hashes(&state).unwrap(), vec![class_hash]);
+    l1_handler_tx().preflight_classes(&state).unwrap();
+
+    // The contract is deployed, but its class is not declared.
+    let mut state = CachedState::from(DictStateReader {
+        address_to_class_hash: HashMap::from([(contract_address, class_hash)]),
+        ..Default::default()
+    });
+    assert_matches!(
+        l1_handler_tx().preflight_classes(&state).unwrap_err(),
+        TransactionExecutionError::ClassNotFound { class_hashes }
+        if class_hashes == vec![class_hash]
+    );
+
+    // The preflight is performed on execution only if enabled.
+    let block_context = BlockContext::create_for_testing();
+    block_context.extensions().insert(ClassAvailabilityPreflight);
+    assert_matches!(
+        l1_handler_tx().execute(&mut state, &block_context, true, true).unwrap_err(),
+        TransactionExecutionError::ClassNotFound { .. }
+    );
+}
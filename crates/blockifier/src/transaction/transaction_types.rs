@@ -1,16 +1,30 @@
+use std::fmt;
 use std::str::FromStr;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use strum_macros::EnumIter;
 
 use crate::transaction::errors::ParseError;
 
-#[derive(Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
 pub enum TransactionType {
     Declare,
     DeployAccount,
     InvokeFunction,
     L1Handler,
+    /// A chain-specific transaction type (see `CustomTransaction`); not a Starknet transaction
+    /// type, hence not iterated over.
+    #[strum(disabled)]
+    Custom(u8),
+}
+
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Custom(type_id) => write!(f, "Custom({type_id})"),
+            tx_type => write!(f, "{tx_type:?}"),
+        }
+    }
 }
 
 impl FromStr for TransactionType {
@@ -22,7 +36,27 @@ impl FromStr for TransactionType {
             "DeployAccount" | "DEPLOY_ACCOUNT" => Ok(TransactionType::DeployAccount),
             "InvokeFunction" | "INVOKE_FUNCTION" => Ok(TransactionType::InvokeFunction),
             "L1Handler" | "L1_HANDLER" => Ok(TransactionType::L1Handler),
-            unknown_tx_type => Err(ParseError::UnknownTransactionType(unknown_tx_type.to_string())),
+            unknown_tx_type => unknown_tx_type
+                .strip_prefix("Custom(")
+                .and_then(|type_id| type_id.strip_suffix(')'))
+                .and_then(|type_id| type_id.parse().ok())
+                .map(TransactionType::Custom)
+                .ok_or_else(|| ParseError::UnknownTransactionType(unknown_tx_type.to_string())),
         }
     }
 }
+
+// Transaction types are (de)serialized as strings, as they key the OS resources of the versioned
+// constants; e.g., `"InvokeFunction"` or `"Custom(1)"`.
+impl Serialize for TransactionType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TransactionType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tx_type = String::deserialize(deserializer)?;
+        tx_type.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use starknet_api::calldata;
-use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, EthAddress, Nonce};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
 use starknet_api::transaction::{
@@ -15,17 +15,19 @@ use crate::context::{BlockContext, TransactionContext};
 use crate::execution::call_info::CallInfo;
 use crate::execution::contract_class::{ClassInfo, ContractClass};
 use crate::execution::entry_point::{
-    CallEntryPoint, CallType, ConstructorContext, EntryPointExecutionContext,
+    CallEntryPoint, CallType, ConstructorContext, EntryPointExecutionContext, MemoryLimits,
 };
 use crate::execution::execution_utils::execute_deployment;
+use crate::fee::actual_cost::ActualCost;
 use crate::state::cached_state::{CachedState, TransactionalState};
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateReader};
 use crate::transaction::constants;
-use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
 use crate::transaction::objects::{
     CommonAccountFields, CurrentTransactionInfo, DeprecatedTransactionInfo, HasRelatedFeeType,
-    TransactionExecutionInfo, TransactionExecutionResult, TransactionInfo, TransactionInfoCreator,
+    TransactionExecutionInfo, TransactionExecutionResult, TransactionFeeResult, TransactionInfo,
+    TransactionInfoCreator,
 };
 use crate::transaction::transaction_utils::{update_remaining_gas, verify_contract_class_version};
 
@@ -57,10 +59,13 @@ pub trait ExecutableTransaction<S: StateReader>: Sized {
             self.execute_raw(&mut transactional_state, block_context, charge_fee, validate);
 
         match execution_result {
-            Ok(value) => {
+            Ok(mut tx_execution_info) => {
                 transactional_state.commit();
                 log::debug!("Transaction execution complete and committed.");
-                Ok(value)
+                if MemoryLimits::is_traceless(block_context) {
+                    tx_execution_info.drop_traces();
+                }
+                Ok(tx_execution_info)
             }
             Err(error) => {
                 log::debug!("Transaction execution failed with: {error}");
@@ -105,7 +110,7 @@ pub trait ValidatableTransaction {
     ) -> TransactionExecutionResult<Option<CallInfo>>;
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DeclareTransaction {
     pub tx: starknet_api::transaction::DeclareTransaction,
     pub tx_hash: TransactionHash,
@@ -166,7 +171,7 @@ impl<S: State> Executable<S> for DeclareTransaction {
         &self,
         state: &mut S,
         _resources: &mut ExecutionResources,
-        _context: &mut EntryPointExecutionContext,
+        context: &mut EntryPointExecutionContext,
         _remaining_gas: &mut u64,
     ) -> TransactionExecutionResult<Option<CallInfo>> {
         let class_hash = self.class_hash();
@@ -175,6 +180,10 @@ impl<S: State> Executable<S> for DeclareTransaction {
             // No class commitment, so no need to check if the class is already declared.
             starknet_api::transaction::DeclareTransaction::V0(_)
             | starknet_api::transaction::DeclareTransaction::V1(_) => {
+                if let ContractClass::V0(contract_class) = self.contract_class() {
+                    let versioned_constants = &context.tx_context.block_context.versioned_constants;
+                    contract_class.validate_hints(&versioned_constants.deprecated_hint_policy)?;
+                }
                 state.set_contract_class(class_hash, self.contract_class())?;
                 Ok(None)
             }
@@ -457,7 +466,20 @@ impl TransactionInfoCreator for InvokeTransaction {
     }
 }
 
-#[derive(Debug)]
+/// A message sent from L1 to L2, as logged by the Starknet core contract (the `LogMessageToL2`
+/// event); handled by an L1 handler transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L1ToL2Message {
+    pub from_address: EthAddress,
+    pub to_address: ContractAddress,
+    pub selector: EntryPointSelector,
+    pub payload: Vec<StarkFelt>,
+    pub nonce: Nonce,
+    /// The fee paid on L1 for handling the message.
+    pub fee: Fee,
+}
+
+#[derive(Clone, Debug)]
 pub struct L1HandlerTransaction {
     pub tx: starknet_api::transaction::L1HandlerTransaction,
     pub tx_hash: TransactionHash,
@@ -470,6 +492,37 @@ impl L1HandlerTransaction {
         self.tx.calldata.0.len() - 1
     }
 
+    /// Builds the transaction handling the given L1 message; its hash is computed by the caller.
+    pub fn from_l1_message(message: L1ToL2Message, tx_hash: TransactionHash) -> Self {
+        let L1ToL2Message { from_address, to_address, selector, payload, nonce, fee } = message;
+        let mut from_address_bytes = [0_u8; 32];
+        from_address_bytes[12..].copy_from_slice(from_address.0.as_bytes());
+        let from_address =
+            StarkFelt::new(from_address_bytes).expect("An L1 address fits in a felt.");
+        // The calldata of L1 handlers starts with the sender of the message.
+        let calldata: Vec<StarkFelt> = std::iter::once(from_address).chain(payload).collect();
+        let tx = starknet_api::transaction::L1HandlerTransaction {
+            version: TransactionVersion::ZERO,
+            nonce,
+            contract_address: to_address,
+            entry_point_selector: selector,
+            calldata: Calldata(calldata.into()),
+        };
+        Self { tx, tx_hash, paid_fee_on_l1: fee }
+    }
+
+    /// Verifies that the fee paid on L1 covers the actual fee of the transaction.
+    /// Note: the execution of L1 handler transactions only requires a positive fee paid on L1.
+    pub fn verify_paid_fee_on_l1(&self, actual_cost: &ActualCost) -> TransactionFeeResult<()> {
+        let paid_fee = self.paid_fee_on_l1;
+        let actual_fee = actual_cost.actual_fee;
+        if paid_fee < actual_fee {
+            return Err(TransactionFeeError::InsufficientL1Fee { paid_fee, actual_fee });
+        }
+
+        Ok(())
+    }
+
     pub fn create_for_testing(l1_fee: Fee, contract_address: ContractAddress) -> Self {
         let calldata = calldata![
             StarkFelt::from_u128(0x123), // from_address.
@@ -14,7 +14,10 @@ use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
-    Calldata, EventContent, EventData, EventKey, Fee, L2ToL1Payload, TransactionSignature,
+    Calldata, DeclareTransaction as StarknetApiDeclareTransaction, DeclareTransactionV0V1,
+    EventContent, EventData, EventKey, Fee,
+    L1HandlerTransaction as StarknetApiL1HandlerTransaction, L2ToL1Payload, Resource,
+    Transaction as StarknetApiTransaction, TransactionHash, TransactionSignature,
     TransactionVersion,
 };
 use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
@@ -35,6 +38,7 @@ use crate::execution::errors::EntryPointExecutionError;
 use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
 use crate::execution::syscalls::hint_processor::EmitEventError;
 use crate::execution::syscalls::SyscallSelector;
+use crate::fee::actual_cost::{ActualCost, MessageResources};
 use crate::fee::fee_utils::calculate_tx_fee;
 use crate::fee::gas_usage::{
     estimate_minimal_gas_vector, get_da_gas_cost, get_onchain_data_segment_length,
@@ -71,8 +75,11 @@ use crate::transaction::test_utils::{
     GET_BLOCK_NUMBER, GET_BLOCK_TIMESTAMP, GET_EXECUTION_INFO, GET_SEQUENCER_ADDRESS, INVALID,
     VALID,
 };
+use crate::transaction::transaction_execution::Transaction;
 use crate::transaction::transaction_types::TransactionType;
-use crate::transaction::transactions::{ExecutableTransaction, L1HandlerTransaction};
+use crate::transaction::transactions::{
+    ExecutableTransaction, L1HandlerTransaction, L1ToL2Message,
+};
 use crate::utils::usize_from_u128;
 use crate::versioned_constants::VersionedConstants;
 use crate::{
@@ -244,6 +251,13 @@ fn expected_fee_transfer_call_info(
             sequencer_balance_key_low,
             sequencer_balance_key_high,
         ]),
+        // Each write starts with a read (see above).
+        syscall_counter: HashMap::from([
+            (SyscallSelector::GetCallerAddress, 1),
+            (SyscallSelector::StorageRead, 4),
+            (SyscallSelector::StorageWrite, 4),
+            (SyscallSelector::EmitEvent, 1),
+        ]),
         ..Default::default()
     })
 }
@@ -467,6 +481,14 @@ fn test_invoke_tx(
         ..expected_validate_call_info.as_ref().unwrap().call.clone()
     };
     let expected_return_result_retdata = Retdata(expected_return_result_calldata);
+    let expected_execute_syscall_counter = match account_cairo_version {
+        CairoVersion::Cairo0 => HashMap::from([(SyscallSelector::CallContract, 1)]),
+        // The Cairo 1.0 account also validates its caller.
+        CairoVersion::Cairo1 => HashMap::from([
+            (SyscallSelector::GetExecutionInfo, 1),
+            (SyscallSelector::CallContract, 1),
+        ]),
+    };
     let expected_execute_call_info = Some(CallInfo {
         call: expected_execute_call,
         execution: CallExecution {
@@ -481,6 +503,7 @@ fn test_invoke_tx(
             resources: ExecutionResources { n_steps: 23, n_memory_holes: 0, ..Default::default() },
             ..Default::default()
         }],
+        syscall_counter: expected_execute_syscall_counter,
         ..Default::default()
     });
 
@@ -507,13 +530,17 @@ fn test_invoke_tx(
     let actual_resources = get_actual_resources(expected_cairo_resources, gas_uage_vector);
     let mut expected_execution_info = TransactionExecutionInfo {
         validate_call_info: expected_validate_call_info,
+        paymaster_call_info: None,
         execute_call_info: expected_execute_call_info,
         fee_transfer_call_info: expected_fee_transfer_call_info,
         actual_fee: expected_actual_fee,
         da_gas,
         actual_resources: actual_resources.clone(),
         revert_error: None,
+        revert_reason: None,
         bouncer_resources: actual_resources,
+        fee_trace: None,
+        message_resources: MessageResources::default(),
     };
 
     add_kzg_da_resources(
@@ -940,17 +967,13 @@ fn test_insufficient_resource_bounds(account_cairo_version: CairoVersion) {
         ..valid_invoke_tx_args.clone()
     });
     let execution_error = invalid_v3_tx.execute(state, block_context, true, true).unwrap_err();
-    // TODO(Ori, 1/2/2024): Write an indicative expect message explaining why the conversion works.
-    let minimal_l1_gas_as_u64 =
-        u64::try_from(minimal_l1_gas).expect("Failed to convert u128 to u64.");
     assert_matches!(
         execution_error,
         TransactionExecutionError::TransactionPreValidationError(
             TransactionPreValidationError::TransactionFeeError(
-                TransactionFeeError::MaxL1GasAmountTooLow{
-                    max_l1_gas_amount, minimal_l1_gas_amount }))
-        if max_l1_gas_amount == insufficient_max_l1_gas_amount &&
-        minimal_l1_gas_amount == minimal_l1_gas_as_u64
+                TransactionFeeError::MaxGasAmountTooLow{
+                    resource: Resource::L1Gas, max_amount, minimal_amount }))
+        if max_amount == insufficient_max_l1_gas_amount && minimal_amount == minimal_l1_gas
     );
 
     // Max L1 gas price too low.
@@ -967,9 +990,10 @@ fn test_insufficient_resource_bounds(account_cairo_version: CairoVersion) {
         execution_error,
         TransactionExecutionError::TransactionPreValidationError(
             TransactionPreValidationError::TransactionFeeError(
-                TransactionFeeError::MaxL1GasPriceTooLow{ max_l1_gas_price, actual_l1_gas_price }))
-        if max_l1_gas_price == insufficient_max_l1_gas_price &&
-        actual_l1_gas_price == actual_strk_l1_gas_price.into()
+                TransactionFeeError::MaxGasPriceTooLow{
+                    resource: Resource::L1Gas, max_price, actual_price }))
+        if max_price == insufficient_max_l1_gas_price &&
+        actual_price == actual_strk_l1_gas_price.into()
     );
 }
 
@@ -1198,13 +1222,17 @@ fn test_declare_tx(
     let actual_resources = get_actual_resources(expected_cairo_resources, gas_usage_vector);
     let mut expected_execution_info = TransactionExecutionInfo {
         validate_call_info: expected_validate_call_info,
+        paymaster_call_info: None,
         execute_call_info: None,
         fee_transfer_call_info: expected_fee_transfer_call_info,
         actual_fee: expected_actual_fee,
         da_gas,
         revert_error: None,
+        revert_reason: None,
         actual_resources: actual_resources.clone(),
         bouncer_resources: actual_resources,
+        fee_trace: None,
+        message_resources: MessageResources::default(),
     };
 
     add_kzg_da_resources(
@@ -1337,13 +1365,17 @@ fn test_deploy_account_tx(
     let actual_resources = get_actual_resources(expected_cairo_resources, da_gas);
     let mut expected_execution_info = TransactionExecutionInfo {
         validate_call_info: expected_validate_call_info,
+        paymaster_call_info: None,
         execute_call_info: expected_execute_call_info,
         fee_transfer_call_info: expected_fee_transfer_call_info,
         actual_fee: expected_actual_fee,
         da_gas,
         revert_error: None,
+        revert_reason: None,
         actual_resources: actual_resources.clone(),
         bouncer_resources: actual_resources,
+        fee_trace: None,
+        message_resources: MessageResources::default(),
     };
 
     add_kzg_da_resources(
@@ -1715,6 +1747,7 @@ fn test_l1_handler(#[values(false, true)] use_kzg_da: bool) {
     let key = calldata.0[1];
     let value = calldata.0[2];
     let payload_size = tx.payload_size();
+    let l1_to_l2_nonce = tx.tx.nonce;
 
     let actual_execution_info = tx.execute(state, block_context, true, true).unwrap();
 
@@ -1743,6 +1776,7 @@ fn test_l1_handler(#[values(false, true)] use_kzg_da: bool) {
             builtin_instance_counter: HashMap::from([(RANGE_CHECK_BUILTIN_NAME.to_string(), 6)]),
         },
         accessed_storage_keys: HashSet::from_iter(vec![accessed_storage_key]),
+        syscall_counter: HashMap::from([(SyscallSelector::StorageWrite, 1)]),
         ..Default::default()
     };
 
@@ -1791,13 +1825,28 @@ fn test_l1_handler(#[values(false, true)] use_kzg_da: bool) {
     // Build the expected execution info.
     let expected_execution_info = TransactionExecutionInfo {
         validate_call_info: None,
+        paymaster_call_info: None,
         execute_call_info: Some(expected_call_info),
         fee_transfer_call_info: None,
         actual_fee: Fee(0),
         da_gas: expected_da_gas,
         actual_resources: expected_resource_mapping.clone(),
         revert_error: None,
+        revert_reason: None,
         bouncer_resources: expected_resource_mapping,
+        fee_trace: None,
+        message_resources: MessageResources::new(
+            &StarknetResources::new(
+                0,
+                0,
+                None,
+                StateChangesCount::default(),
+                Some(payload_size),
+                std::iter::empty(),
+            )
+            .unwrap(),
+            Some(l1_to_l2_nonce),
+        ),
     };
 
     // Check the actual returned execution info.
@@ -1834,6 +1883,39 @@ fn test_l1_handler(#[values(false, true)] use_kzg_da: bool) {
     );
 }
 
+#[test]
+fn test_l1_handler_from_l1_message() {
+    let contract_address = contract_address!("0x1234");
+    let paid_fee_on_l1 = Fee(5);
+    let nonce = Nonce(stark_felt!(3_u8));
+    let message = L1ToL2Message {
+        from_address: EthAddress::try_from(stark_felt!(0x123_u16)).unwrap(),
+        to_address: contract_address,
+        selector: selector_from_name("l1_handler_set_value"),
+        payload: vec![stark_felt!(0x876_u16), stark_felt!(0x44_u8)],
+        nonce,
+        fee: paid_fee_on_l1,
+    };
+    let tx_hash = TransactionHash(stark_felt!(0x9_u8));
+    let tx = L1HandlerTransaction::from_l1_message(message, tx_hash);
+
+    // The test transaction handles the same message.
+    let expected_tx = L1HandlerTransaction::create_for_testing(paid_fee_on_l1, contract_address);
+    assert_eq!(tx.tx, StarknetApiL1HandlerTransaction { nonce, ..expected_tx.tx });
+    assert_eq!(tx.tx_hash, tx_hash);
+    assert_eq!(tx.paid_fee_on_l1, paid_fee_on_l1);
+    assert_eq!(tx.payload_size(), 2);
+
+    let actual_cost = ActualCost { actual_fee: paid_fee_on_l1, ..Default::default() };
+    tx.verify_paid_fee_on_l1(&actual_cost).unwrap();
+    let actual_cost = ActualCost { actual_fee: Fee(paid_fee_on_l1.0 + 1), ..Default::default() };
+    assert_matches!(
+        tx.verify_paid_fee_on_l1(&actual_cost).unwrap_err(),
+        TransactionFeeError::InsufficientL1Fee { paid_fee, actual_fee }
+        if paid_fee == paid_fee_on_l1 && actual_fee == actual_cost.actual_fee
+    );
+}
+
 #[test]
 fn test_execute_tx_with_invalid_transaction_version() {
     let cairo_version = CairoVersion::Cairo0;
@@ -1875,11 +1957,20 @@ fn max_event_data() -> usize {
     VERSIONED_CONSTANTS.tx_event_limits.max_data_length
 }
 
+fn max_total_event_keys() -> usize {
+    VERSIONED_CONSTANTS.tx_event_limits.max_total_keys_length
+}
+
 #[rstest]
 #[case::positive_flow(
+    vec![stark_felt!(1_u16)],
+    vec![stark_felt!(2_u16)],
+    max_n_emitted_events(),
+    None)]
+#[case::positive_flow_max_event_size(
     vec![stark_felt!(1_u16); max_event_keys()],
     vec![stark_felt!(2_u16); max_event_data()],
-    max_n_emitted_events(),
+    1,
     None)]
 #[case::exceeds_max_number_of_events(
     vec![stark_felt!(1_u16)],
@@ -1905,6 +1996,14 @@ fn max_event_data() -> usize {
         data_length: max_event_data() + 1,
         max_data_length: max_event_data(),
     }))]
+#[case::exceeds_max_total_keys_length(
+    vec![stark_felt!(7_u16); max_event_keys()],
+    vec![stark_felt!(8_u16)],
+    max_total_event_keys() / max_event_keys() + 1,
+    Some(EmitEventError::ExceedsMaxTotalKeysLength{
+        total_keys_length: (max_total_event_keys() / max_event_keys() + 1) * max_event_keys(),
+        max_total_keys_length: max_total_event_keys(),
+    }))]
 fn test_emit_event_exceeds_limit(
     #[case] event_keys: Vec<StarkFelt>,
     #[case] event_data: Vec<StarkFelt>,
@@ -1967,3 +2066,69 @@ fn test_emit_event_exceeds_limit(
         }
     }
 }
+
+#[test]
+fn test_transaction_from_api() {
+    // Account transactions.
+    let invoke = invoke_tx(invoke_tx_args! { version: TransactionVersion::THREE }).tx;
+    let tx = Transaction::from_api(
+        StarknetApiTransaction::Invoke(invoke),
+        TransactionHash::default(),
+        None,
+        None,
+        None,
+        false,
+    )
+    .unwrap();
+    assert_matches!(tx, Transaction::AccountTransaction(AccountTransaction::Invoke(_)));
+
+    let declare = StarknetApiDeclareTransaction::V1(DeclareTransactionV0V1::default());
+    let error = Transaction::from_api(
+        StarknetApiTransaction::Declare(declare),
+        TransactionHash::default(),
+        None,
+        None,
+        None,
+        false,
+    )
+    .unwrap_err();
+    assert_matches!(
+        error,
+        TransactionExecutionError::MissingTransactionInput {
+            tx_type: TransactionType::Declare,
+            ..
+        }
+    );
+
+    // L1 handler transactions.
+    let l1_handler = StarknetApiL1HandlerTransaction::default();
+    let error = Transaction::from_api(
+        StarknetApiTransaction::L1Handler(l1_handler.clone()),
+        TransactionHash::default(),
+        None,
+        None,
+        None,
+        false,
+    )
+    .unwrap_err();
+    assert_matches!(
+        error,
+        TransactionExecutionError::MissingTransactionInput {
+            tx_type: TransactionType::L1Handler,
+            ..
+        }
+    );
+    let tx = Transaction::from_api(
+        StarknetApiTransaction::L1Handler(l1_handler),
+        TransactionHash::default(),
+        None,
+        Some(Fee(1)),
+        None,
+        false,
+    )
+    .unwrap();
+    assert_matches!(
+        tx,
+        Transaction::L1HandlerTransaction(L1HandlerTransaction { paid_fee_on_l1: Fee(1), .. })
+    );
+}
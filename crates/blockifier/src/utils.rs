@@ -17,6 +17,18 @@ where
     lhs.iter().filter(|(k, v)| rhs.get(k) != Some(v)).map(|(k, v)| (k.clone(), v.clone())).collect()
 }
 
+/// Adds the values of `rhs` into `lhs`, key by key; keys missing from `lhs` are inserted.
+/// Usage: Aggregate counters (e.g., syscall counts) over several executions.
+pub fn add_mappings<K, V>(lhs: &mut HashMap<K, V>, rhs: &HashMap<K, V>)
+where
+    K: Clone + Eq + std::hash::Hash,
+    V: Copy + Default + std::ops::AddAssign,
+{
+    for (key, value) in rhs {
+        *lhs.entry(key.clone()).or_default() += *value;
+    }
+}
+
 /// Returns the max value of two constants, at compile time.
 pub const fn const_max(a: u128, b: u128) -> u128 {
     #[allow(clippy::as_conversions)]
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use pretty_assertions::assert_eq;
 
-use crate::utils::subtract_mappings;
+use crate::utils::{add_mappings, subtract_mappings};
 
 #[test]
 fn test_subtract_mappings() {
@@ -18,3 +18,13 @@ fn test_subtract_mappings() {
     let expected = HashMap::from([("red", 1), ("blue", 3)]);
     assert_eq!(expected, subtract_mappings(&map1, &map2));
 }
+
+#[test]
+fn test_add_mappings() {
+    let mut map = HashMap::from([("red", 1), ("green", 2)]);
+    add_mappings(&mut map, &HashMap::default());
+    assert_eq!(map, HashMap::from([("red", 1), ("green", 2)]));
+
+    add_mappings(&mut map, &HashMap::from([("green", 3), ("blue", 4)]));
+    assert_eq!(map, HashMap::from([("red", 1), ("green", 5), ("blue", 4)]));
+}
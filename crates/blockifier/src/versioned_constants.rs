@@ -1,31 +1,52 @@
 use std::collections::{HashMap, HashSet};
-use std::io;
 use std::path::Path;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::{fmt, io};
 
 use cairo_vm::vm::runners::builtin_runner;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use indexmap::{IndexMap, IndexSet};
 use num_rational::Ratio;
+use num_traits::{CheckedDiv, CheckedMul};
 use once_cell::sync::Lazy;
 use serde::de::Error as DeserializationError;
 use serde::{Deserialize, Deserializer};
 use serde_json::{Map, Number, Value};
 use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 use thiserror::Error;
 
+use crate::bouncer::BouncerConfig;
 use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
 use crate::execution::errors::PostExecutionError;
 use crate::execution::execution_utils::poseidon_hash_many_cost;
 use crate::execution::syscalls::SyscallSelector;
+use crate::fee::actual_cost::RevertPolicy;
 use crate::transaction::errors::TransactionExecutionError;
 use crate::transaction::transaction_types::TransactionType;
+use crate::versioned_constants::os_resources_calibration::{OsResourcesEntry, ReferenceExecution};
+
+pub mod os_resources_calibration;
 
 #[cfg(test)]
 #[path = "versioned_constants_test.rs"]
 pub mod test;
 
 const DEFAULT_CONSTANTS_JSON: &str = include_str!("../resources/versioned_constants.json");
+const CONSTANTS_JSON_0_13_0: &str = include_str!("../resources/versioned_constants_13_0.json");
+static CONSTANTS_0_13_0: Lazy<VersionedConstants> = Lazy::new(|| {
+    serde_json::from_str(CONSTANTS_JSON_0_13_0).expect("Versioned constants JSON file is malformed")
+});
+static GLOBAL_VERSIONED_CONSTANTS_MAP: Lazy<VersionedConstantsMap> =
+    Lazy::new(VersionedConstantsMap::default);
+
+const STRICT_DEPRECATED_HINT_WHITELIST_JSON: &str =
+    include_str!("../resources/deprecated_hint_whitelist.json");
+static STRICT_DEPRECATED_HINT_WHITELIST: Lazy<HashSet<String>> = Lazy::new(|| {
+    serde_json::from_str(STRICT_DEPRECATED_HINT_WHITELIST_JSON)
+        .expect("Deprecated hint whitelist JSON file is malformed")
+});
 static DEFAULT_CONSTANTS: Lazy<VersionedConstants> = Lazy::new(|| {
     serde_json::from_str(DEFAULT_CONSTANTS_JSON)
         .expect("Versioned constants JSON file is malformed")
@@ -33,20 +54,73 @@ static DEFAULT_CONSTANTS: Lazy<VersionedConstants> = Lazy::new(|| {
 
 pub type ResourceCost = Ratio<u128>;
 
+/// The top-level keys accepted when parsing in `ParsingMode::Strict`: the fields of
+/// `VersionedConstants`, and the keys not used by the Blockifier but included in the shipped file.
+const STRICT_MODE_KNOWN_KEYS: [&str; 17] = [
+    "bouncer_config",
+    "deprecated_hint_policy",
+    "enable_l2_data_availability",
+    "failed_call_state_policy",
+    "gateway",
+    "inner_call_failure_policy",
+    "invoke_tx_max_n_steps",
+    "l2_resource_gas_costs",
+    "max_calldata_length",
+    "max_recursion_depth",
+    "missing_selector_policy",
+    "os_constants",
+    "os_resources",
+    "revert_policy",
+    "tx_event_limits",
+    "validate_max_n_steps",
+    "vm_resource_fee_cost",
+];
+
+/// Determines how a versioned constants file is parsed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ParsingMode {
+    /// Unknown keys are ignored.
+    #[default]
+    Lenient,
+    /// Unknown top-level keys and suspicious values (e.g., zero step limits) are rejected; used to
+    /// catch typos in operator-customized files at startup.
+    Strict,
+    /// As lenient, and the OS resources are not validated to cover all transaction types and
+    /// syscalls; for test fixtures only. The OS resources are validated in all other modes.
+    Unvalidated,
+}
+
 /// Contains constants for the Blockifier that may vary between versions.
 /// Additional constants in the JSON file, not used by Blockifier but included for transparency, are
 /// automatically ignored during deserialization.
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct VersionedConstants {
     // Limits.
+    #[serde(default)]
+    pub bouncer_config: BouncerConfig,
     #[serde(default = "EventLimits::max")]
     pub tx_event_limits: EventLimits,
     pub invoke_tx_max_n_steps: u32,
     #[serde(default)]
     pub l2_resource_gas_costs: L2ResourceGasCosts,
+    // The maximal calldata (or L1 handler payload) length accounted for in the OS resources.
+    #[serde(default = "VersionedConstants::unlimited_calldata_length")]
+    pub max_calldata_length: usize,
     pub max_recursion_depth: usize,
     pub validate_max_n_steps: u32,
 
+    // Execution policies.
+    #[serde(default)]
+    pub missing_selector_policy: MissingSelectorPolicy,
+    #[serde(default)]
+    pub deprecated_hint_policy: DeprecatedHintPolicy,
+    #[serde(default)]
+    pub inner_call_failure_policy: InnerCallFailurePolicy,
+    #[serde(default)]
+    pub failed_call_state_policy: FailedCallStatePolicy,
+    #[serde(default)]
+    pub revert_policy: RevertPolicy,
+
     // Cairo OS constants.
     // Note: if loaded from a json file, there are some assumptions made on its structure.
     // See the struct's docstring for more details.
@@ -56,6 +130,11 @@ pub struct VersionedConstants {
     os_resources: Arc<OsResources>,
 
     // Fee related.
+    // Whether transactions may publish their nonce and fee token balance updates on L2 (volition),
+    // which are then not charged as L1 data availability; otherwise, all state changes are priced
+    // as L1 data availability, regardless of the data availability modes of the transaction.
+    #[serde(default)]
+    pub enable_l2_data_availability: bool,
     // TODO: Consider making this a struct, this will require change the way we access these
     // values.
     vm_resource_fee_cost: Arc<HashMap<String, ResourceCost>>,
@@ -63,39 +142,153 @@ pub struct VersionedConstants {
 
 impl VersionedConstants {
     /// Get the constants that shipped with the current version of the Blockifier.
-    /// To use custom constants, initialize the struct from a file using `try_from`.
+    /// To use custom constants, initialize the struct from a file using `try_from` or `from_path`.
+    /// To resolve constants by protocol version, use `get_for_version`.
     pub fn latest_constants() -> &'static Self {
         &DEFAULT_CONSTANTS
     }
 
-    /// Returns the initial gas of any transaction to run with.
+    /// Returns the constants of the given protocol version, as currently registered in the global
+    /// map (see `VersionedConstantsMap::global`).
+    pub fn get_for_version(
+        version: StarknetVersion,
+    ) -> Result<Arc<VersionedConstants>, VersionedConstantsError> {
+        VersionedConstantsMap::global().get(version)
+    }
+
+    /// Loads the constants from the given JSON file, in the given parsing mode.
+    pub fn from_path(path: &Path, mode: ParsingMode) -> Result<Self, VersionedConstantsError> {
+        Self::from_json_str(&std::fs::read_to_string(path)?, mode)
+    }
+
+    /// Returns the latest constants, with the given sparse override document applied (see
+    /// `VersionedConstantsBuilder::with_overrides`); parsed in strict mode.
+    pub fn latest_with_overrides(overrides: Value) -> Result<Self, VersionedConstantsError> {
+        Self::builder().with_overrides(overrides).build()
+    }
+
+    /// Returns a builder of constants, starting from the latest ones.
+    pub fn builder() -> VersionedConstantsBuilder {
+        VersionedConstantsBuilder::new()
+    }
+
+    /// Parses the constants from the given JSON string, in the given parsing mode.
+    pub fn from_json_str(json: &str, mode: ParsingMode) -> Result<Self, VersionedConstantsError> {
+        match mode {
+            ParsingMode::Lenient => Ok(serde_json::from_str(json)?),
+            ParsingMode::Strict | ParsingMode::Unvalidated => {
+                Self::from_json_value(serde_json::from_str(json)?, mode)
+            }
+        }
+    }
+
+    /// Parses the constants from the given JSON document, in the given parsing mode.
+    pub fn from_json_value(
+        json: Value,
+        mode: ParsingMode,
+    ) -> Result<Self, VersionedConstantsError> {
+        match mode {
+            ParsingMode::Lenient => Ok(serde_json::from_value(json)?),
+            ParsingMode::Strict => {
+                let raw_json_data: Map<String, Value> = serde_json::from_value(json)?;
+                let unknown_keys: Vec<String> = raw_json_data
+                    .keys()
+                    .filter(|key| !STRICT_MODE_KNOWN_KEYS.contains(&key.as_str()))
+                    .cloned()
+                    .collect();
+                if !unknown_keys.is_empty() {
+                    return Err(VersionedConstantsError::UnknownKeys(unknown_keys));
+                }
+
+                let versioned_constants: Self =
+                    serde_json::from_value(Value::Object(raw_json_data))?;
+                versioned_constants.validate_values()?;
+                Ok(versioned_constants)
+            }
+            ParsingMode::Unvalidated => {
+                let mut raw_json_data: Map<String, Value> = serde_json::from_value(json)?;
+                let Some(os_resources) = raw_json_data.remove("os_resources") else {
+                    // Fails on the missing field.
+                    return Ok(serde_json::from_value(Value::Object(raw_json_data))?);
+                };
+                // Parse the rest of the constants along with the (valid) latest OS resources, and
+                // then replace them with the unvalidated ones.
+                let mut latest_json: Value = serde_json::from_str(DEFAULT_CONSTANTS_JSON)?;
+                raw_json_data
+                    .insert("os_resources".to_string(), latest_json["os_resources"].take());
+                let versioned_constants: Self =
+                    serde_json::from_value(Value::Object(raw_json_data))?;
+                let os_resources = OsResources::deserialize(os_resources)?;
+                Ok(Self { os_resources: Arc::new(os_resources), ..versioned_constants })
+            }
+        }
+    }
+
+    /// Rejects values that are valid, but are most likely a misconfiguration.
+    fn validate_values(&self) -> Result<(), VersionedConstantsError> {
+        let suspicious_value = |key: &str, value: String| {
+            Err(VersionedConstantsError::SuspiciousValue { key: key.to_string(), value })
+        };
+        if self.invoke_tx_max_n_steps == 0 {
+            return suspicious_value("invoke_tx_max_n_steps", "0".to_string());
+        }
+        if self.validate_max_n_steps == 0 {
+            return suspicious_value("validate_max_n_steps", "0".to_string());
+        }
+        if self.max_recursion_depth == 0 {
+            return suspicious_value("max_recursion_depth", "0".to_string());
+        }
+        if self.max_calldata_length == 0 {
+            return suspicious_value("max_calldata_length", "0".to_string());
+        }
+        if !self.vm_resource_fee_cost.contains_key(crate::abi::constants::N_STEPS_RESOURCE) {
+            return suspicious_value(
+                "vm_resource_fee_cost",
+                format!("missing '{}'", crate::abi::constants::N_STEPS_RESOURCE),
+            );
+        }
+        if self.revert_policy.charge_fee && !self.revert_policy.bump_nonce {
+            // Reverted transactions could be resubmitted, charging their senders again.
+            return suspicious_value(
+                "revert_policy",
+                "charges fees without bumping the nonce".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the initial gas of any transaction to run with: `initial_gas_cost` minus the gas
+    /// the OS spends on the transaction itself.
     pub fn tx_initial_gas(&self) -> u64 {
-        let os_consts = &self.os_constants;
-        os_consts.gas_costs["initial_gas_cost"] - os_consts.gas_costs["transaction_gas_cost"]
+        let gas_costs = self.gas_costs();
+        gas_costs.initial_gas_cost - gas_costs.transaction_gas_cost
+    }
+
+    pub fn gas_costs(&self) -> &GasCosts {
+        &self.os_constants.gas_costs
     }
 
     pub fn vm_resource_fee_cost(&self) -> &HashMap<String, ResourceCost> {
         &self.vm_resource_fee_cost
     }
 
+    /// Looks a gas cost up by name; prefer the typed fields of `gas_costs`, unless the name is
+    /// only known at runtime.
     pub fn gas_cost(&self, name: &str) -> u64 {
-        match self.os_constants.gas_costs.get(name) {
-            Some(&cost) => cost,
-            None if OSConstants::ALLOWED_GAS_COST_NAMES.contains(&name) => {
-                panic!(
-                    "{} is present in `OSConstants::GAS_COSTS` but not in `self`; was validation \
-                     skipped?",
-                    name
-                )
-            }
-            None => {
-                panic!(
-                    "Only gas costs listed in `{0:?}` should be requested, got: {1}",
-                    OSConstants::ALLOWED_GAS_COST_NAMES,
-                    name,
-                )
-            }
-        }
+        self.gas_costs().get(name).unwrap_or_else(|| {
+            panic!(
+                "Only gas costs listed in `{0:?}` should be requested, got: {1}",
+                GasCosts::NAMES,
+                name,
+            )
+        })
+    }
+
+    /// Whether the OS resources of the given transaction type are set; always true for the Starknet
+    /// transaction types, which are validated on deserialization.
+    pub fn has_os_resources_for_tx_type(&self, tx_type: &TransactionType) -> bool {
+        self.os_resources.execute_txs_inner.contains_key(tx_type)
     }
 
     pub fn os_resources_for_tx_type(
@@ -117,6 +310,12 @@ impl VersionedConstants {
         data_segment_length: usize,
         use_kzg_da: bool,
     ) -> Result<ExecutionResources, TransactionExecutionError> {
+        if calldata_length > self.max_calldata_length {
+            return Err(TransactionExecutionError::CalldataTooLong {
+                calldata_length,
+                max_calldata_length: self.max_calldata_length,
+            });
+        }
         self.os_resources.get_additional_os_tx_resources(
             tx_type,
             calldata_length,
@@ -132,6 +331,45 @@ impl VersionedConstants {
         self.os_resources.get_additional_os_syscall_resources(syscall_counter)
     }
 
+    /// Converts an amount between Sierra gas, VM steps and L1 gas, using the OS `step_gas_cost`
+    /// and the VM steps fee cost. The result is exact; callers choose the rounding (e.g., round
+    /// L1 gas up, as done when charging fees). Fails if either unit has no cost per step, or if the
+    /// converted amount does not fit in a `u128` ratio.
+    pub fn convert(
+        &self,
+        amount: u128,
+        from: ExecutionUnit,
+        to: ExecutionUnit,
+    ) -> Result<ResourceCost, ExecutionUnitConversionError> {
+        let overflow = || ExecutionUnitConversionError::Overflow { amount, from, to };
+        let n_steps = ResourceCost::from_integer(amount)
+            .checked_mul(&self.steps_per_unit(from)?)
+            .ok_or_else(overflow)?;
+        n_steps.checked_div(&self.steps_per_unit(to)?).ok_or_else(overflow)
+    }
+
+    /// Returns the number of VM steps a single unit of the given kind is worth.
+    fn steps_per_unit(
+        &self,
+        unit: ExecutionUnit,
+    ) -> Result<ResourceCost, ExecutionUnitConversionError> {
+        let cost_per_step = match unit {
+            ExecutionUnit::VmSteps => ResourceCost::from_integer(1),
+            ExecutionUnit::SierraGas => {
+                ResourceCost::from_integer(self.gas_costs().step_gas_cost.into())
+            }
+            ExecutionUnit::L1Gas => self
+                .vm_resource_fee_cost
+                .get(crate::abi::constants::N_STEPS_RESOURCE)
+                .copied()
+                .unwrap_or_default(),
+        };
+        if cost_per_step == ResourceCost::from_integer(0) {
+            return Err(ExecutionUnitConversionError::ZeroCostPerStep(unit));
+        }
+        Ok(cost_per_step.recip())
+    }
+
     pub fn get_validate_block_number_rounding(&self) -> u64 {
         self.os_constants.validate_rounding_consts.validate_block_number_rounding
     }
@@ -140,6 +378,10 @@ impl VersionedConstants {
         self.os_constants.validate_rounding_consts.validate_timestamp_rounding
     }
 
+    fn unlimited_calldata_length() -> usize {
+        usize::MAX
+    }
+
     #[cfg(any(feature = "testing", test))]
     pub fn create_for_account_testing() -> Self {
         let vm_resource_fee_cost = Arc::new(HashMap::from([
@@ -182,10 +424,247 @@ impl TryFrom<&Path> for VersionedConstants {
     type Error = VersionedConstantsError;
 
     fn try_from(path: &Path) -> Result<Self, Self::Error> {
-        Ok(serde_json::from_reader(std::fs::File::open(path)?)?)
+        Self::from_path(path, ParsingMode::Lenient)
+    }
+}
+
+/// Builds constants from the JSON document of the latest ones, with sparse overrides applied;
+/// custom chains typically change a handful of constants, rather than maintaining a full file.
+/// Derived values (e.g., composed gas costs) are computed from the overridden document.
+#[derive(Clone, Debug)]
+pub struct VersionedConstantsBuilder {
+    json: Value,
+    mode: ParsingMode,
+}
+
+impl VersionedConstantsBuilder {
+    /// Starts from the latest constants, to be parsed in strict mode.
+    pub fn new() -> Self {
+        Self {
+            json: serde_json::from_str(DEFAULT_CONSTANTS_JSON)
+                .expect("Versioned constants JSON file is malformed"),
+            mode: ParsingMode::Strict,
+        }
+    }
+
+    /// Applies a sparse override document: objects are merged key by key, and any other value
+    /// replaces the one it overrides.
+    pub fn with_overrides(mut self, overrides: Value) -> Self {
+        merge_json(&mut self.json, overrides);
+        self
+    }
+
+    /// Overrides a single constant, given by its dot-separated path (e.g.,
+    /// `os_constants.step_gas_cost`).
+    pub fn with_constant(self, path: &str, value: impl Into<Value>) -> Self {
+        let overrides = path.rsplit('.').fold(value.into(), |value, key| {
+            Value::Object(Map::from_iter([(key.to_string(), value)]))
+        });
+        self.with_overrides(overrides)
+    }
+
+    pub fn with_parsing_mode(mut self, mode: ParsingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn build(self) -> Result<VersionedConstants, VersionedConstantsError> {
+        VersionedConstants::from_json_value(self.json, self.mode)
+    }
+}
+
+impl Default for VersionedConstantsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn merge_json(json: &mut Value, overrides: Value) {
+    match (json, overrides) {
+        (Value::Object(json), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge_json(json.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (json, overrides) => *json = overrides,
     }
 }
 
+/// A Starknet protocol version, formatted as `major.minor.patch`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct StarknetVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl StarknetVersion {
+    pub const V0_13_0: Self = Self::new(0, 13, 0);
+    pub const V0_13_1: Self = Self::new(0, 13, 1);
+    /// The version of the constants returned by `VersionedConstants::latest_constants`.
+    pub const LATEST: Self = Self::V0_13_1;
+
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Returns the constants shipped with the Blockifier for this version, if any.
+    pub fn embedded_constants(&self) -> Option<&'static VersionedConstants> {
+        ConstantsPreset::all()
+            .find(|preset| preset.starknet_version() == *self)
+            .map(|preset| preset.constants())
+    }
+}
+
+impl fmt::Display for StarknetVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for StarknetVersion {
+    type Err = VersionedConstantsError;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid_version = || VersionedConstantsError::InvalidStarknetVersion(version.into());
+        let parts = version
+            .split('.')
+            .map(|part| part.parse::<u8>().map_err(|_| invalid_version()))
+            .collect::<Result<Vec<u8>, _>>()?;
+        match parts[..] {
+            [major, minor, patch] => Ok(Self::new(major, minor, patch)),
+            _ => Err(invalid_version()),
+        }
+    }
+}
+
+/// The constants shipped with the Blockifier, selectable by name at runtime (e.g., by node
+/// operators) without loading constants files.
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
+pub enum ConstantsPreset {
+    Mainnet0_13_0,
+    Mainnet0_13_1,
+}
+
+impl ConstantsPreset {
+    /// Returns the presets, from the oldest protocol version to the latest.
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::iter()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mainnet0_13_0 => "mainnet_0_13_0",
+            Self::Mainnet0_13_1 => "mainnet_0_13_1",
+        }
+    }
+
+    pub fn starknet_version(&self) -> StarknetVersion {
+        match self {
+            Self::Mainnet0_13_0 => StarknetVersion::V0_13_0,
+            Self::Mainnet0_13_1 => StarknetVersion::V0_13_1,
+        }
+    }
+
+    pub fn constants(&self) -> &'static VersionedConstants {
+        match self {
+            Self::Mainnet0_13_0 => &CONSTANTS_0_13_0,
+            Self::Mainnet0_13_1 => &DEFAULT_CONSTANTS,
+        }
+    }
+}
+
+impl fmt::Display for ConstantsPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ConstantsPreset {
+    type Err = VersionedConstantsError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .find(|preset| preset.name() == name)
+            .ok_or_else(|| VersionedConstantsError::UnknownConstantsPreset(name.into()))
+    }
+}
+
+/// Resolves versioned constants by protocol version: custom overrides registered at runtime take
+/// precedence over the constants shipped with the Blockifier. Overrides may be replaced at any time
+/// (e.g., on reloading a modified file), affecting only the constants resolved afterwards; block
+/// contexts hold their own copy.
+#[derive(Debug, Default)]
+pub struct VersionedConstantsMap {
+    overrides: RwLock<VersionedConstantsOverrides>,
+}
+
+type VersionedConstantsOverrides = HashMap<StarknetVersion, Arc<VersionedConstants>>;
+
+impl VersionedConstantsMap {
+    /// The map backing `VersionedConstants::get_for_version`.
+    pub fn global() -> &'static Self {
+        &GLOBAL_VERSIONED_CONSTANTS_MAP
+    }
+
+    pub fn get(
+        &self,
+        version: StarknetVersion,
+    ) -> Result<Arc<VersionedConstants>, VersionedConstantsError> {
+        if let Some(constants) = self.read_overrides().get(&version) {
+            return Ok(constants.clone());
+        }
+
+        version
+            .embedded_constants()
+            .map(|constants| Arc::new(constants.clone()))
+            .ok_or(VersionedConstantsError::UnsupportedStarknetVersion(version))
+    }
+
+    /// Registers custom constants for the given version, returning the previous override, if any.
+    pub fn register(
+        &self,
+        version: StarknetVersion,
+        versioned_constants: VersionedConstants,
+    ) -> Option<Arc<VersionedConstants>> {
+        self.write_overrides().insert(version, Arc::new(versioned_constants))
+    }
+
+    /// Loads custom constants for the given version from a JSON file, and registers them.
+    /// The current override (if any) is kept if loading fails.
+    pub fn register_from_path(
+        &self,
+        version: StarknetVersion,
+        path: &Path,
+        mode: ParsingMode,
+    ) -> Result<(), VersionedConstantsError> {
+        let versioned_constants = VersionedConstants::from_path(path, mode)?;
+        self.register(version, versioned_constants);
+        Ok(())
+    }
+
+    /// Removes the override of the given version, reverting to the shipped constants (if any).
+    pub fn unregister(&self, version: StarknetVersion) -> Option<Arc<VersionedConstants>> {
+        self.write_overrides().remove(&version)
+    }
+
+    fn read_overrides(&self) -> RwLockReadGuard<'_, VersionedConstantsOverrides> {
+        self.overrides.read().expect("Versioned constants lock is poisoned.")
+    }
+
+    fn write_overrides(&self) -> RwLockWriteGuard<'_, VersionedConstantsOverrides> {
+        self.overrides.write().expect("Versioned constants lock is poisoned.")
+    }
+}
+
+/// A unit in which execution cost can be expressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExecutionUnit {
+    SierraGas,
+    VmSteps,
+    L1Gas,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
 pub struct L2ResourceGasCosts {
     // TODO(barak, 18/03/2024): Once we start charging per byte change to milligas_per_data_byte,
@@ -193,6 +672,76 @@ pub struct L2ResourceGasCosts {
     pub gas_per_data_felt: ResourceCost,
     pub event_key_factor: ResourceCost,
     pub gas_per_code_byte: ResourceCost,
+    // Charged per bytecode segment of a declared class, on top of its code bytes.
+    #[serde(default)]
+    pub gas_per_code_segment: ResourceCost,
+}
+
+/// Determines how a call to a selector that is not exposed by the called class is handled.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingSelectorPolicy {
+    /// Route the call to the `__default__` entry point, if the class exposes one (e.g., Cairo 0
+    /// proxies); fail otherwise.
+    #[default]
+    RouteToDefaultEntryPoint,
+    /// Always fail the call.
+    Fail,
+}
+
+/// Determines which failures of an inner call (`call_contract` or `library_call`) are returned to
+/// the calling Cairo 1 code, to be handled by it (e.g., by an account executing a multicall),
+/// rather than failing the caller. What happens to the state modifications of a returned failure
+/// is determined by the `FailedCallStatePolicy`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InnerCallFailurePolicy {
+    /// Return only panics of the called code, with their panic data; fail the caller on any other
+    /// failure (e.g., a missing entry point or an undeployed contract).
+    #[default]
+    ReturnPanics,
+    /// Also return any other failure of the called code, with the `ENTRYPOINT_FAILED` error data;
+    /// except for exhausting the limits of the transaction (e.g., its deadline) and internal
+    /// errors, which still fail the caller.
+    ReturnAllFailures,
+}
+
+/// Determines what happens to the state modifications of a failed inner call (a call or a
+/// deployment), when the failure does not fail the whole transaction.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedCallStatePolicy {
+    /// Apply the modifications made up to the failure; the behavior of versions before the
+    /// modifications of inner calls were buffered.
+    #[default]
+    Keep,
+    /// Discard all modifications of the failed call, including those of its own inner calls.
+    Discard,
+}
+
+/// Determines which hints a Cairo 0 (deprecated) class may contain; a class containing other hints
+/// cannot be declared.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeprecatedHintPolicy {
+    /// Accept any hint; hints unknown to the hint processor fail only when executed.
+    #[default]
+    Permissive,
+    /// Accept only the syscall hints and the common library hints listed in
+    /// `resources/deprecated_hint_whitelist.json`.
+    Strict,
+    /// Accept only the given hints (e.g., the whitelist of the network being replayed).
+    Custom(HashSet<String>),
+}
+
+impl DeprecatedHintPolicy {
+    pub fn is_allowed(&self, hint_code: &str) -> bool {
+        match self {
+            Self::Permissive => true,
+            Self::Strict => STRICT_DEPRECATED_HINT_WHITELIST.contains(hint_code),
+            Self::Custom(whitelist) => whitelist.contains(hint_code),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
@@ -200,6 +749,12 @@ pub struct EventLimits {
     pub max_data_length: usize,
     pub max_keys_length: usize,
     pub max_n_emitted_events: usize,
+    // Cumulative limits, over all the events emitted by a transaction execution phase. Versions
+    // predating them are unlimited.
+    #[serde(default = "EventLimits::unlimited")]
+    pub max_total_data_length: usize,
+    #[serde(default = "EventLimits::unlimited")]
+    pub max_total_keys_length: usize,
 }
 
 impl EventLimits {
@@ -208,8 +763,14 @@ impl EventLimits {
             max_data_length: usize::MAX,
             max_keys_length: usize::MAX,
             max_n_emitted_events: usize::MAX,
+            max_total_data_length: usize::MAX,
+            max_total_keys_length: usize::MAX,
         }
     }
+
+    fn unlimited() -> usize {
+        usize::MAX
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -358,9 +919,7 @@ impl<'de> Deserialize<'de> for OsResources {
     {
         let os_resources = Self::deserialize(deserializer)?;
 
-        // Validations.
-
-        #[cfg(not(test))]
+        // Validations; skipped only when parsing in `ParsingMode::Unvalidated`.
         os_resources.validate::<D>()?;
 
         Ok(os_resources)
@@ -369,98 +928,107 @@ impl<'de> Deserialize<'de> for OsResources {
 
 // Below, serde first deserializes the json into a regular IndexMap wrapped by the newtype
 // `OsConstantsRawJson`, then calls the `try_from` of the newtype, which handles the
-// conversion into actual values.
+// conversion into actual values, and then into the typed `GasCosts`.
 // Assumption: if the json has a value that contains the expression "FOO * 2", then the key `FOO`
 // must appear before this value in the JSON.
 // FIXME: JSON doesn't guarantee order, serde seems to work for this use-case, buit there is no
 // guarantee that it will stay that way. Seriously consider switching to serde_yaml/other format.
-// FIXME FOLLOWUP: if we switch from JSON, we can switch to strongly typed fields, instead of an
-// internal indexmap: using strongly typed fields breaks the order under serialization, making
-// testing very difficult.
 // TODO: consider encoding the * and + operations inside the json file, instead of hardcoded below
 // in the `try_from`.
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(try_from = "OsConstantsRawJson")]
 pub struct OSConstants {
     validate_rounding_consts: ValidateRoundingConsts,
+    gas_costs: GasCosts,
+}
 
-    // Invariant: fixed keys.
-    gas_costs: IndexMap<String, u64>,
-}
-
-impl OSConstants {
-    // List of all gas cost constants that *must* be present in the JSON file, all other consts are
-    // ignored. See documentation in core/os/constants.cairo.
-    const ALLOWED_GAS_COST_NAMES: [&'static str; 31] = [
-        "step_gas_cost",
-        "range_check_gas_cost",
-        "memory_hole_gas_cost",
-        // An estimation of the initial gas for a transaction to run with. This solution is
-        // temporary and this value will become a field of the transaction.
-        "initial_gas_cost",
-        // ** Compiler gas costs **
-        "entry_point_initial_budget",
-        // The initial gas budget for a system call (this value is hard-coded by the compiler).
-        // This needs to be high enough to cover OS costs in the case of failure due to out of gas.
-        "syscall_base_gas_cost",
-        // ** OS gas costs **
-        "entry_point_gas_cost",
-        "fee_transfer_gas_cost",
-        "transaction_gas_cost",
-        // ** Required gas for each syscall **
-        "call_contract_gas_cost",
-        "deploy_gas_cost",
-        "get_block_hash_gas_cost",
-        "get_execution_info_gas_cost",
-        "library_call_gas_cost",
-        "replace_class_gas_cost",
-        "storage_read_gas_cost",
-        "storage_write_gas_cost",
-        "emit_event_gas_cost",
-        "send_message_to_l1_gas_cost",
-        "secp256k1_add_gas_cost",
-        "secp256k1_get_point_from_x_gas_cost",
-        "secp256k1_get_xy_gas_cost",
-        "secp256k1_mul_gas_cost",
-        "secp256k1_new_gas_cost",
-        "secp256r1_add_gas_cost",
-        "secp256r1_get_point_from_x_gas_cost",
-        "secp256r1_get_xy_gas_cost",
-        "secp256r1_mul_gas_cost",
-        "secp256r1_new_gas_cost",
-        "keccak_gas_cost",
-        "keccak_round_cost_gas_cost",
-    ];
-
-    pub fn validate(&self) -> Result<(), OsConstantsSerdeError> {
-        // Check that all the allowed gas consts set is contained inside the parsed consts,
-        // that is, all consts in the list appeared as keys in the json file.
-        for key in Self::ALLOWED_GAS_COST_NAMES {
-            if !self.gas_costs.contains_key(key) {
-                return Err(OsConstantsSerdeError::ValidationError(format!(
-                    "Starknet os constants is missing the following key: {}",
-                    key
-                )));
+/// Defines `GasCosts`, with a field per gas cost name; all of them *must* be present in the JSON
+/// file, all other consts are ignored.
+macro_rules! define_gas_costs {
+    ($($(#[$field_attr:meta])* $name:ident,)*) => {
+        /// The gas costs of the Starknet OS. See documentation in core/os/constants.cairo.
+        #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+        pub struct GasCosts {
+            $($(#[$field_attr])* pub $name: u64,)*
+        }
+
+        impl GasCosts {
+            pub const NAMES: &'static [&'static str] = &[$(stringify!($name),)*];
+
+            pub fn get(&self, name: &str) -> Option<u64> {
+                match name {
+                    $(stringify!($name) => Some(self.$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Reads the gas costs from their expanded values; fails on missing names.
+            fn from_expanded(
+                gas_costs: &IndexMap<String, u64>,
+            ) -> Result<Self, OsConstantsSerdeError> {
+                let gas_cost = |name: &str| {
+                    gas_costs.get(name).copied().ok_or_else(|| {
+                        OsConstantsSerdeError::ValidationError(format!(
+                            "Starknet os constants is missing the following key: {}",
+                            name
+                        ))
+                    })
+                };
+                Ok(Self { $($name: gas_cost(stringify!($name))?,)* })
             }
         }
+    };
+}
 
-        Ok(())
-    }
+define_gas_costs! {
+    step_gas_cost,
+    range_check_gas_cost,
+    memory_hole_gas_cost,
+    /// An estimation of the initial gas for a transaction to run with. This solution is
+    /// temporary and this value will become a field of the transaction.
+    initial_gas_cost,
+    // ** Compiler gas costs **
+    entry_point_initial_budget,
+    /// The initial gas budget for a system call (this value is hard-coded by the compiler).
+    /// This needs to be high enough to cover OS costs in the case of failure due to out of gas.
+    syscall_base_gas_cost,
+    // ** OS gas costs **
+    entry_point_gas_cost,
+    fee_transfer_gas_cost,
+    transaction_gas_cost,
+    // ** Required gas for each syscall **
+    call_contract_gas_cost,
+    deploy_gas_cost,
+    get_block_hash_gas_cost,
+    get_execution_info_gas_cost,
+    library_call_gas_cost,
+    replace_class_gas_cost,
+    storage_read_gas_cost,
+    storage_write_gas_cost,
+    emit_event_gas_cost,
+    send_message_to_l1_gas_cost,
+    secp256k1_add_gas_cost,
+    secp256k1_get_point_from_x_gas_cost,
+    secp256k1_get_xy_gas_cost,
+    secp256k1_mul_gas_cost,
+    secp256k1_new_gas_cost,
+    secp256r1_add_gas_cost,
+    secp256r1_get_point_from_x_gas_cost,
+    secp256r1_get_xy_gas_cost,
+    secp256r1_mul_gas_cost,
+    secp256r1_new_gas_cost,
+    keccak_gas_cost,
+    keccak_round_cost_gas_cost,
 }
 
 impl TryFrom<OsConstantsRawJson> for OSConstants {
     type Error = OsConstantsSerdeError;
 
     fn try_from(raw_json_data: OsConstantsRawJson) -> Result<Self, Self::Error> {
-        let gas_costs = raw_json_data.get_gas_costs()?;
+        let gas_costs = GasCosts::from_expanded(&raw_json_data.get_gas_costs()?)?;
         let validate_rounding_consts = raw_json_data.validate_rounding_consts;
-        let os_constants = OSConstants { gas_costs, validate_rounding_consts };
-
-        // Skip validation in testing: to test validation run validate manually.
-        #[cfg(not(test))]
-        os_constants.validate()?;
 
-        Ok(os_constants)
+        Ok(OSConstants { gas_costs, validate_rounding_consts })
     }
 }
 
@@ -477,8 +1045,7 @@ struct OsConstantsRawJson {
 impl OsConstantsRawJson {
     fn get_gas_costs(&self) -> Result<IndexMap<String, u64>, OsConstantsSerdeError> {
         let mut gas_costs = IndexMap::new();
-        let gas_cost_whitelist: IndexSet<_> =
-            OSConstants::ALLOWED_GAS_COST_NAMES.iter().copied().collect();
+        let gas_cost_whitelist: IndexSet<_> = GasCosts::NAMES.iter().copied().collect();
         for (key, value) in &self.raw_json_file_as_dict {
             if !gas_cost_whitelist.contains(key.as_str()) {
                 // Ignore non-whitelist consts.
@@ -560,6 +1127,36 @@ pub enum VersionedConstantsError {
     IoError(#[from] io::Error),
     #[error("JSON file cannot be serialized into VersionedConstants: {0}")]
     ParseError(#[from] serde_json::Error),
+    #[error("Suspicious value for key '{key}': {value}.")]
+    SuspiciousValue { key: String, value: String },
+    #[error("Unknown keys in versioned constants: {0:?}.")]
+    UnknownKeys(Vec<String>),
+    #[error("Invalid Starknet version: '{0}'.")]
+    InvalidStarknetVersion(String),
+    #[error("No versioned constants for Starknet version {0}.")]
+    UnsupportedStarknetVersion(StarknetVersion),
+    #[error("Unknown versioned constants preset: '{0}'.")]
+    UnknownConstantsPreset(String),
+}
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum ExecutionUnitConversionError {
+    #[error("{0:?} has no cost per VM step.")]
+    ZeroCostPerStep(ExecutionUnit),
+    #[error("Converting {amount} {from:?} to {to:?} overflows.")]
+    Overflow { amount: u128, from: ExecutionUnit, to: ExecutionUnit },
+}
+
+#[derive(Debug, Error)]
+pub enum OsResourcesCalibrationError {
+    #[error("Failed to measure {execution:?}: {error}")]
+    MeasurementFailed { execution: ReferenceExecution, error: String },
+    #[error("The OS resources of {0:?} transactions are not linear in the calldata length.")]
+    NonLinearCalldataCost(TransactionType),
+    #[error("The measured OS resources of {0:?} are negative.")]
+    NonMonotonicMeasurement(OsResourcesEntry),
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,256 @@
+//! Calibration of the `os_resources` section of the versioned constants from measured executions.
+//!
+//! The resources the OS spends on a transaction (beyond its entry points) are not measured by the
+//! Blockifier, but charged from the `os_resources` constants. This module derives candidate values
+//! for these constants from reference executions run through the OS, and reports how they differ
+//! from the current ones.
+
+use std::collections::{HashMap, HashSet};
+
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use serde_json::{json, Value};
+use strum::IntoEnumIterator;
+
+use crate::execution::syscalls::SyscallSelector;
+use crate::transaction::transaction_types::TransactionType;
+use crate::versioned_constants::{
+    OsResourcesCalibrationError, ResourcesParams, VersionedConstants,
+};
+
+#[cfg(test)]
+#[path = "os_resources_calibration_test.rs"]
+pub mod test;
+
+pub type OsResourcesCalibrationResult<T> = Result<T, OsResourcesCalibrationError>;
+
+/// The calldata length of the longer reference transaction of each type; the calldata factor is
+/// the difference from the empty-calldata transaction, divided by this length.
+pub const REFERENCE_CALLDATA_LENGTH: usize = 8;
+
+/// An execution run through the OS to measure its resources.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ReferenceExecution {
+    /// A transaction of the given type and calldata length, whose entry points invoke no
+    /// syscalls.
+    Transaction { tx_type: TransactionType, calldata_length: usize },
+    /// An invoke transaction with empty calldata, whose entry point invokes the given syscall the
+    /// given number of times.
+    Syscall { selector: SyscallSelector, n_invocations: usize },
+}
+
+/// Runs reference executions through the OS (e.g., the Starknet OS program over cairo-vm), and
+/// measures the VM resources spent by the OS itself, i.e., excluding the resources of the executed
+/// entry points. The OS is not part of the Blockifier; calibrating tools implement this trait.
+pub trait OsResourcesMeter {
+    fn measure(
+        &mut self,
+        execution: ReferenceExecution,
+    ) -> OsResourcesCalibrationResult<ExecutionResources>;
+}
+
+/// An `os_resources` entry derived from measurements.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OsResourcesEntry {
+    Syscall(SyscallSelector),
+    TxConstant(TransactionType),
+    TxCalldataFactor(TransactionType),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OsResourcesDiff {
+    pub entry: OsResourcesEntry,
+    pub current: ExecutionResources,
+    pub candidate: ExecutionResources,
+}
+
+/// Candidate `os_resources` values, derived from measured executions.
+#[derive(Clone, Debug, Default)]
+pub struct OsResourcesCalibration {
+    pub execute_syscalls: HashMap<SyscallSelector, ExecutionResources>,
+    pub execute_txs_inner: HashMap<TransactionType, ResourcesParams>,
+}
+
+impl OsResourcesCalibration {
+    /// Measures the reference executions of every transaction type and syscall:
+    /// * A syscall's resources are the difference between invoking it twice and invoking it once,
+    ///   which cancels out the resources of the surrounding transaction.
+    /// * A transaction type's constant is measured with empty calldata; its calldata factor is
+    ///   derived from a second measurement, with `REFERENCE_CALLDATA_LENGTH` calldata.
+    pub fn measure(meter: &mut dyn OsResourcesMeter) -> OsResourcesCalibrationResult<Self> {
+        let mut calibration = Self::default();
+
+        for selector in SyscallSelector::iter() {
+            let once = meter.measure(ReferenceExecution::Syscall { selector, n_invocations: 1 })?;
+            let twice =
+                meter.measure(ReferenceExecution::Syscall { selector, n_invocations: 2 })?;
+            let resources = checked_sub(&twice, &once).ok_or(
+                OsResourcesCalibrationError::NonMonotonicMeasurement(OsResourcesEntry::Syscall(
+                    selector,
+                )),
+            )?;
+            calibration.execute_syscalls.insert(selector, resources);
+        }
+
+        for tx_type in TransactionType::iter() {
+            let constant =
+                meter.measure(ReferenceExecution::Transaction { tx_type, calldata_length: 0 })?;
+            let with_calldata = meter.measure(ReferenceExecution::Transaction {
+                tx_type,
+                calldata_length: REFERENCE_CALLDATA_LENGTH,
+            })?;
+            let calldata_resources = checked_sub(&with_calldata, &constant).ok_or(
+                OsResourcesCalibrationError::NonMonotonicMeasurement(
+                    OsResourcesEntry::TxCalldataFactor(tx_type),
+                ),
+            )?;
+            let calldata_factor = exact_div(&calldata_resources, REFERENCE_CALLDATA_LENGTH)
+                .ok_or(OsResourcesCalibrationError::NonLinearCalldataCost(tx_type))?;
+            calibration
+                .execute_txs_inner
+                .insert(tx_type, ResourcesParams { constant, calldata_factor });
+        }
+
+        Ok(calibration)
+    }
+
+    /// Returns the entries whose candidate values differ from the given constants.
+    pub fn diff(&self, constants: &VersionedConstants) -> Vec<OsResourcesDiff> {
+        let mut diffs = Vec::new();
+        let mut push_if_changed =
+            |entry: OsResourcesEntry,
+             current: &ExecutionResources,
+             candidate: &ExecutionResources| {
+                if normalized(current) != normalized(candidate) {
+                    diffs.push(OsResourcesDiff {
+                        entry,
+                        current: current.clone(),
+                        candidate: candidate.clone(),
+                    });
+                }
+            };
+
+        for selector in SyscallSelector::iter() {
+            if let (Some(current), Some(candidate)) = (
+                constants.os_resources.execute_syscalls.get(&selector),
+                self.execute_syscalls.get(&selector),
+            ) {
+                push_if_changed(OsResourcesEntry::Syscall(selector), current, candidate);
+            }
+        }
+        for tx_type in TransactionType::iter() {
+            if let (Some(resources_by_version), Some(candidate)) = (
+                constants.os_resources.execute_txs_inner.get(&tx_type),
+                self.execute_txs_inner.get(&tx_type),
+            ) {
+                let current = &resources_by_version.deprecated_resources;
+                push_if_changed(
+                    OsResourcesEntry::TxConstant(tx_type),
+                    &current.constant,
+                    &candidate.constant,
+                );
+                push_if_changed(
+                    OsResourcesEntry::TxCalldataFactor(tx_type),
+                    &current.calldata_factor,
+                    &candidate.calldata_factor,
+                );
+            }
+        }
+
+        diffs
+    }
+
+    /// Returns the candidate `os_resources` section of the versioned constants JSON: the given
+    /// constants, with the measured entries replaced. The measured transaction resources replace
+    /// the `deprecated_resources`, by which fees are currently charged.
+    pub fn to_json(&self, constants: &VersionedConstants) -> OsResourcesCalibrationResult<Value> {
+        let os_resources = &constants.os_resources;
+        let mut execute_syscalls = os_resources.execute_syscalls.clone();
+        execute_syscalls.extend(self.execute_syscalls.clone());
+
+        let mut execute_txs_inner = serde_json::Map::new();
+        for (tx_type, resources_by_version) in &os_resources.execute_txs_inner {
+            let deprecated_resources = self
+                .execute_txs_inner
+                .get(tx_type)
+                .unwrap_or(&resources_by_version.deprecated_resources);
+            execute_txs_inner.insert(
+                tx_type_key(*tx_type)?,
+                json!({
+                    "resources": resources_params_to_json(&resources_by_version.resources)?,
+                    "deprecated_resources": resources_params_to_json(deprecated_resources)?,
+                }),
+            );
+        }
+
+        Ok(json!({
+            "execute_syscalls": serde_json::to_value(execute_syscalls)?,
+            "execute_txs_inner": execute_txs_inner,
+            "compute_os_kzg_commitment_info":
+                serde_json::to_value(&os_resources.compute_os_kzg_commitment_info)?,
+        }))
+    }
+}
+
+fn tx_type_key(tx_type: TransactionType) -> OsResourcesCalibrationResult<String> {
+    match serde_json::to_value(tx_type)? {
+        Value::String(key) => Ok(key),
+        value => unreachable!("Transaction types are serialized as strings; got: {value}."),
+    }
+}
+
+fn resources_params_to_json(params: &ResourcesParams) -> OsResourcesCalibrationResult<Value> {
+    Ok(json!({
+        "constant": serde_json::to_value(&params.constant)?,
+        "calldata_factor": serde_json::to_value(&params.calldata_factor)?,
+    }))
+}
+
+/// Returns the resources without unused builtins, for comparison.
+fn normalized(resources: &ExecutionResources) -> ExecutionResources {
+    ExecutionResources {
+        builtin_instance_counter: resources
+            .builtin_instance_counter
+            .iter()
+            .filter(|(_, count)| **count != 0)
+            .map(|(name, count)| (name.clone(), *count))
+            .collect(),
+        ..resources.clone()
+    }
+}
+
+/// Subtracts `rhs` from `lhs`, field by field; returns `None` if any field would be negative.
+fn checked_sub(lhs: &ExecutionResources, rhs: &ExecutionResources) -> Option<ExecutionResources> {
+    let builtin_names: HashSet<&String> =
+        lhs.builtin_instance_counter.keys().chain(rhs.builtin_instance_counter.keys()).collect();
+    let count = |resources: &ExecutionResources, name: &String| {
+        resources.builtin_instance_counter.get(name).copied().unwrap_or_default()
+    };
+    let mut builtin_instance_counter = HashMap::new();
+    for name in builtin_names {
+        let diff = count(lhs, name).checked_sub(count(rhs, name))?;
+        if diff != 0 {
+            builtin_instance_counter.insert(name.clone(), diff);
+        }
+    }
+
+    Some(ExecutionResources {
+        n_steps: lhs.n_steps.checked_sub(rhs.n_steps)?,
+        n_memory_holes: lhs.n_memory_holes.checked_sub(rhs.n_memory_holes)?,
+        builtin_instance_counter,
+    })
+}
+
+/// Divides the resources by `divisor`, field by field; returns `None` unless all fields are
+/// divisible.
+fn exact_div(resources: &ExecutionResources, divisor: usize) -> Option<ExecutionResources> {
+    let div = |value: usize| (value % divisor == 0).then(|| value / divisor);
+    Some(ExecutionResources {
+        n_steps: div(resources.n_steps)?,
+        n_memory_holes: div(resources.n_memory_holes)?,
+        builtin_instance_counter: resources
+            .builtin_instance_counter
+            .iter()
+            .map(|(name, count)| Some((name.clone(), div(*count)?)))
+            .collect::<Option<_>>()?,
+    })
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use pretty_assertions::assert_eq;
+
+use crate::execution::syscalls::SyscallSelector;
+use crate::transaction::transaction_types::TransactionType;
+use crate::versioned_constants::os_resources_calibration::{
+    OsResourcesCalibration, OsResourcesCalibrationResult, OsResourcesDiff, OsResourcesEntry,
+    OsResourcesMeter, ReferenceExecution,
+};
+use crate::versioned_constants::{OsResources, OsResourcesCalibrationError, VersionedConstants};
+
+/// Measures the OS resources charged by the given constants, with optional overrides.
+struct ConstantsMeter<'a> {
+    constants: &'a VersionedConstants,
+    syscall_overrides: HashMap<SyscallSelector, ExecutionResources>,
+    extra_calldata_steps: usize,
+}
+
+impl<'a> ConstantsMeter<'a> {
+    fn new(constants: &'a VersionedConstants) -> Self {
+        Self { constants, syscall_overrides: HashMap::new(), extra_calldata_steps: 0 }
+    }
+}
+
+impl OsResourcesMeter for ConstantsMeter<'_> {
+    fn measure(
+        &mut self,
+        execution: ReferenceExecution,
+    ) -> OsResourcesCalibrationResult<ExecutionResources> {
+        Ok(match execution {
+            ReferenceExecution::Transaction { tx_type, calldata_length } => {
+                let mut resources =
+                    self.constants.os_resources_for_tx_type(&tx_type, calldata_length);
+                if calldata_length > 0 {
+                    resources.n_steps += self.extra_calldata_steps;
+                }
+                resources
+            }
+            ReferenceExecution::Syscall { selector, n_invocations } => {
+                let tx_resources =
+                    self.constants.os_resources_for_tx_type(&TransactionType::InvokeFunction, 0);
+                let syscall_resources = match self.syscall_overrides.get(&selector) {
+                    Some(resources) => resources * n_invocations,
+                    None => self
+                        .constants
+                        .get_additional_os_syscall_resources(&HashMap::from([(
+                            selector,
+                            n_invocations,
+                        )]))
+                        .unwrap(),
+                };
+                &tx_resources + &syscall_resources
+            }
+        })
+    }
+}
+
+fn constants_from_json(
+    candidate: serde_json::Value,
+    constants: &VersionedConstants,
+) -> VersionedConstants {
+    let os_resources: OsResources = serde_json::from_value(candidate).unwrap();
+    VersionedConstants { os_resources: Arc::new(os_resources), ..constants.clone() }
+}
+
+#[test]
+fn test_calibration_of_current_constants() {
+    let constants = VersionedConstants::latest_constants();
+    let calibration = OsResourcesCalibration::measure(&mut ConstantsMeter::new(constants)).unwrap();
+    assert_eq!(calibration.diff(constants), vec![]);
+
+    let candidate_constants =
+        constants_from_json(calibration.to_json(constants).unwrap(), constants);
+    assert_eq!(calibration.diff(&candidate_constants), vec![]);
+}
+
+#[test]
+fn test_calibration_diff() {
+    let constants = VersionedConstants::latest_constants();
+    let storage_read_resources = ExecutionResources {
+        n_steps: 100,
+        n_memory_holes: 0,
+        builtin_instance_counter: HashMap::from([("range_check_builtin".to_string(), 2)]),
+    };
+    let mut meter = ConstantsMeter::new(constants);
+    meter.syscall_overrides.insert(SyscallSelector::StorageRead, storage_read_resources.clone());
+
+    let calibration = OsResourcesCalibration::measure(&mut meter).unwrap();
+    let current = constants
+        .get_additional_os_syscall_resources(&HashMap::from([(SyscallSelector::StorageRead, 1)]))
+        .unwrap();
+    assert_eq!(
+        calibration.diff(constants),
+        vec![OsResourcesDiff {
+            entry: OsResourcesEntry::Syscall(SyscallSelector::StorageRead),
+            current,
+            candidate: storage_read_resources.clone(),
+        }]
+    );
+
+    // The candidate section charges the measured resources.
+    let candidate_constants =
+        constants_from_json(calibration.to_json(constants).unwrap(), constants);
+    assert_eq!(calibration.diff(&candidate_constants), vec![]);
+    assert_eq!(
+        candidate_constants
+            .get_additional_os_syscall_resources(&HashMap::from([(
+                SyscallSelector::StorageRead,
+                1
+            )]))
+            .unwrap(),
+        storage_read_resources
+    );
+}
+
+#[test]
+fn test_non_linear_calldata_cost() {
+    let constants = VersionedConstants::latest_constants();
+    let mut meter = ConstantsMeter::new(constants);
+    meter.extra_calldata_steps = 1;
+
+    assert_matches!(
+        OsResourcesCalibration::measure(&mut meter).unwrap_err(),
+        OsResourcesCalibrationError::NonLinearCalldataCost(_)
+    );
+}
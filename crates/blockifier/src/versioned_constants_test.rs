@@ -1,9 +1,8 @@
+use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
 
 use super::*;
 
-// TODO: Test Starknet OS validation.
-
 #[test]
 fn test_successful_parsing() {
     let json_data = r#"
@@ -21,17 +20,30 @@ fn test_successful_parsing() {
         "ignore the gas string": "GAS!",
         "I look like a gas cost but my name is all wrong": 0
     }"#;
-    let os_constants: Arc<OSConstants> = serde_json::from_str(json_data).unwrap();
+    let os_constants: Arc<OSConstants> =
+        serde_json::from_str(&with_all_gas_costs(json_data)).unwrap();
     let versioned_constants = VersionedConstants { os_constants, ..Default::default() };
+    let gas_costs = versioned_constants.gas_costs();
 
-    assert_eq!(versioned_constants.gas_cost("step_gas_cost"), 2);
-    assert_eq!(versioned_constants.gas_cost("entry_point_initial_budget"), 2 * 3); // step_gas_cost * 3.
+    assert_eq!(gas_costs.step_gas_cost, 2);
+    assert_eq!(gas_costs.entry_point_initial_budget, 2 * 3); // step_gas_cost * 3.
 
     // entry_point_intial_budget * 4 + step_gas_cost * 5.
+    assert_eq!(gas_costs.entry_point_gas_cost, 6 * 4 + 2 * 5);
     assert_eq!(versioned_constants.gas_cost("entry_point_gas_cost"), 6 * 4 + 2 * 5);
 
-    // Only the 3 values asserted against should be present, the rest are ignored.
-    assert_eq!(versioned_constants.os_constants.gas_costs.len(), 3);
+    // The other values are ignored.
+    assert_eq!(versioned_constants.get_validate_block_number_rounding(), 111);
+    assert_eq!(gas_costs.get("I look like a gas cost but my name is all wrong"), None);
+}
+
+/// Completes the given OS constants JSON with all gas costs; the missing ones are set to 1.
+fn with_all_gas_costs(json_data: &str) -> String {
+    let mut json_data_raw: IndexMap<String, Value> =
+        GasCosts::NAMES.iter().map(|name| (name.to_string(), 1.into())).collect();
+    json_data_raw.extend(serde_json::from_str::<IndexMap<String, Value>>(json_data).unwrap());
+
+    serde_json::to_string(&json_data_raw).unwrap()
 }
 
 #[test]
@@ -40,7 +52,6 @@ fn test_default_values() {
     {
         "invoke_tx_max_n_steps": 2,
         "validate_max_n_steps": 1,
-        "os_constants": {},
         "os_resources": {
             "execute_syscalls":{},
             "execute_txs_inner": {
@@ -72,7 +83,11 @@ fn test_default_values() {
         "vm_resource_fee_cost": {},
         "max_recursion_depth": 2
     }"#;
-    let versioned_constants: VersionedConstants = serde_json::from_str(json_data).unwrap();
+    let mut json_data: Value = serde_json::from_str(json_data).unwrap();
+    json_data["os_constants"] = serde_json::from_str(&with_all_gas_costs("{}")).unwrap();
+    // The OS resources cover a single transaction type.
+    let versioned_constants =
+        VersionedConstants::from_json_value(json_data, ParsingMode::Unvalidated).unwrap();
 
     assert_eq!(versioned_constants.get_validate_block_number_rounding(), 1);
     assert_eq!(versioned_constants.get_validate_timestamp_rounding(), 1);
@@ -139,6 +154,15 @@ fn test_missing_key() {
     );
 }
 
+#[test]
+fn test_missing_gas_cost() {
+    check_constants_serde_error(
+        r#"{"step_gas_cost": 2}"#,
+        "Validation failed: Starknet os constants is missing the following key: \
+         range_check_gas_cost",
+    );
+}
+
 #[test]
 fn test_unhandled_value_type() {
     let json_data = r#"
@@ -173,3 +197,321 @@ fn test_invalid_number() {
          cannot be cast into u64",
     );
 }
+
+#[test]
+fn test_convert() {
+    let versioned_constants = VersionedConstants::latest_constants();
+    let step_gas_cost = u128::from(versioned_constants.gas_cost("step_gas_cost"));
+    let l1_gas_per_step =
+        versioned_constants.vm_resource_fee_cost()[crate::abi::constants::N_STEPS_RESOURCE];
+    let n_steps = 10000;
+
+    let convert = |amount, from, to| versioned_constants.convert(amount, from, to).unwrap();
+    assert_eq!(
+        convert(n_steps, ExecutionUnit::VmSteps, ExecutionUnit::SierraGas),
+        ResourceCost::from_integer(n_steps * step_gas_cost)
+    );
+    assert_eq!(
+        convert(n_steps * step_gas_cost, ExecutionUnit::SierraGas, ExecutionUnit::VmSteps),
+        ResourceCost::from_integer(n_steps)
+    );
+    let l1_gas = ResourceCost::from_integer(n_steps) * l1_gas_per_step;
+    assert_eq!(convert(n_steps, ExecutionUnit::VmSteps, ExecutionUnit::L1Gas), l1_gas);
+    assert_eq!(
+        convert(n_steps * step_gas_cost, ExecutionUnit::SierraGas, ExecutionUnit::L1Gas),
+        l1_gas
+    );
+    assert_eq!(
+        convert(7, ExecutionUnit::L1Gas, ExecutionUnit::L1Gas),
+        ResourceCost::from_integer(7)
+    );
+
+    // A zero cost per step cannot be converted from or to.
+    let versioned_constants = VersionedConstants {
+        vm_resource_fee_cost: Arc::new(HashMap::new()),
+        ..versioned_constants.clone()
+    };
+    assert_eq!(
+        versioned_constants.convert(1, ExecutionUnit::VmSteps, ExecutionUnit::L1Gas),
+        Err(ExecutionUnitConversionError::ZeroCostPerStep(ExecutionUnit::L1Gas))
+    );
+
+    // Large amounts fail rather than overflow.
+    assert_eq!(
+        versioned_constants.convert(u128::MAX, ExecutionUnit::VmSteps, ExecutionUnit::SierraGas),
+        Err(ExecutionUnitConversionError::Overflow {
+            amount: u128::MAX,
+            from: ExecutionUnit::VmSteps,
+            to: ExecutionUnit::SierraGas,
+        })
+    );
+}
+
+#[test]
+fn test_max_calldata_length() {
+    let versioned_constants = VersionedConstants::latest_constants();
+    let max_calldata_length = versioned_constants.max_calldata_length;
+    assert!(
+        versioned_constants
+            .get_additional_os_tx_resources(
+                TransactionType::L1Handler,
+                max_calldata_length,
+                0,
+                false
+            )
+            .is_ok()
+    );
+    assert_matches!(
+        versioned_constants.get_additional_os_tx_resources(
+            TransactionType::L1Handler,
+            usize::MAX,
+            0,
+            false
+        ),
+        Err(TransactionExecutionError::CalldataTooLong { calldata_length: usize::MAX, .. })
+    );
+}
+
+fn default_constants_with(key: &str, value: Value) -> String {
+    let mut json_data_raw: IndexMap<String, Value> =
+        serde_json::from_str(DEFAULT_CONSTANTS_JSON).unwrap();
+    json_data_raw.insert(key.to_string(), value);
+    serde_json::to_string(&json_data_raw).unwrap()
+}
+
+#[test]
+fn test_strict_parsing() {
+    // The shipped constants are accepted in strict mode.
+    assert!(VersionedConstants::from_json_str(DEFAULT_CONSTANTS_JSON, ParsingMode::Strict).is_ok());
+
+    // Unknown keys are ignored only in lenient mode.
+    let json_data = default_constants_with("validate_max_n_step", 1000.into());
+    assert!(VersionedConstants::from_json_str(&json_data, ParsingMode::Lenient).is_ok());
+    assert_matches!(
+        VersionedConstants::from_json_str(&json_data, ParsingMode::Strict),
+        Err(VersionedConstantsError::UnknownKeys(keys))
+        if keys == vec!["validate_max_n_step".to_string()]
+    );
+
+    // Suspicious values are rejected only in strict mode.
+    let json_data = default_constants_with("validate_max_n_steps", 0.into());
+    assert!(VersionedConstants::from_json_str(&json_data, ParsingMode::Lenient).is_ok());
+    assert_matches!(
+        VersionedConstants::from_json_str(&json_data, ParsingMode::Strict),
+        Err(VersionedConstantsError::SuspiciousValue { key, .. }) if key == "validate_max_n_steps"
+    );
+}
+
+#[test]
+fn test_os_resources_validation() {
+    let mut json_data: Value = serde_json::from_str(DEFAULT_CONSTANTS_JSON).unwrap();
+    json_data["os_resources"]["execute_syscalls"].as_object_mut().unwrap().remove("StorageRead");
+    let json_data = json_data.to_string();
+
+    // The OS resources are validated unless explicitly requested otherwise.
+    for mode in [ParsingMode::Lenient, ParsingMode::Strict] {
+        assert_matches!(
+            VersionedConstants::from_json_str(&json_data, mode),
+            Err(VersionedConstantsError::ParseError(error))
+            if error.to_string().contains("missing syscall handler: StorageRead")
+        );
+    }
+    let versioned_constants =
+        VersionedConstants::from_json_str(&json_data, ParsingMode::Unvalidated).unwrap();
+    assert_eq!(
+        versioned_constants.os_resources_for_tx_type(&TransactionType::Declare, 0),
+        VersionedConstants::latest_constants()
+            .os_resources_for_tx_type(&TransactionType::Declare, 0)
+    );
+}
+
+#[test]
+fn test_revert_policy_parsing() {
+    assert_eq!(VersionedConstants::latest_constants().revert_policy, RevertPolicy::default());
+    let versioned_constants = VersionedConstants::latest_with_overrides(serde_json::json!({
+        "revert_policy": { "bump_nonce": false, "charge_fee": false },
+    }))
+    .unwrap();
+    assert_eq!(
+        versioned_constants.revert_policy,
+        RevertPolicy { bump_nonce: false, charge_fee: false, ..RevertPolicy::default() }
+    );
+
+    // Charging fees without bumping the nonce lets reverted transactions be resubmitted.
+    assert_matches!(
+        VersionedConstants::latest_with_overrides(serde_json::json!({
+            "revert_policy": { "bump_nonce": false },
+        })),
+        Err(VersionedConstantsError::SuspiciousValue { key, .. }) if key == "revert_policy"
+    );
+}
+
+#[test]
+fn test_overrides() {
+    let latest = VersionedConstants::latest_constants();
+    let versioned_constants = VersionedConstants::latest_with_overrides(serde_json::json!({
+        "invoke_tx_max_n_steps": 5,
+        "tx_event_limits": { "max_data_length": 1 },
+    }))
+    .unwrap();
+    assert_eq!(versioned_constants.invoke_tx_max_n_steps, 5);
+    assert_eq!(versioned_constants.validate_max_n_steps, latest.validate_max_n_steps);
+    // Objects are merged key by key.
+    assert_eq!(
+        versioned_constants.tx_event_limits,
+        EventLimits { max_data_length: 1, ..latest.tx_event_limits }
+    );
+
+    // Composed gas costs are computed from the overridden values.
+    let versioned_constants = VersionedConstants::builder()
+        .with_constant("os_constants.step_gas_cost", 200)
+        .build()
+        .unwrap();
+    let gas_costs = versioned_constants.gas_costs();
+    assert_eq!(gas_costs.step_gas_cost, 200);
+    assert_eq!(gas_costs.entry_point_initial_budget, 200 * 100);
+    assert_eq!(gas_costs.range_check_gas_cost, latest.gas_costs().range_check_gas_cost);
+
+    // Overrides are parsed strictly, unless requested otherwise.
+    let builder = VersionedConstants::builder().with_constant("invoke_tx_max_n_step", 5);
+    assert_matches!(
+        builder.clone().build(),
+        Err(VersionedConstantsError::UnknownKeys(keys))
+        if keys == vec!["invoke_tx_max_n_step".to_string()]
+    );
+    assert!(builder.with_parsing_mode(ParsingMode::Lenient).build().is_ok());
+}
+
+#[test]
+fn test_deprecated_hint_policy_parsing() {
+    assert_eq!(
+        VersionedConstants::latest_constants().deprecated_hint_policy,
+        DeprecatedHintPolicy::Permissive
+    );
+
+    let json_data = default_constants_with("deprecated_hint_policy", "strict".into());
+    let versioned_constants =
+        VersionedConstants::from_json_str(&json_data, ParsingMode::Strict).unwrap();
+    assert_eq!(versioned_constants.deprecated_hint_policy, DeprecatedHintPolicy::Strict);
+
+    let json_data = default_constants_with(
+        "deprecated_hint_policy",
+        serde_json::json!({ "custom": ["vm_enter_scope()"] }),
+    );
+    let versioned_constants =
+        VersionedConstants::from_json_str(&json_data, ParsingMode::Strict).unwrap();
+    assert!(versioned_constants.deprecated_hint_policy.is_allowed("vm_enter_scope()"));
+    assert!(!versioned_constants.deprecated_hint_policy.is_allowed("vm_exit_scope()"));
+}
+
+#[test]
+fn test_starknet_version_parsing() {
+    assert_eq!("0.13.1".parse::<StarknetVersion>().unwrap(), StarknetVersion::V0_13_1);
+    assert_eq!(StarknetVersion::V0_13_0.to_string(), "0.13.0");
+    for invalid_version in ["0.13", "0.13.1.0", "0.x.1", ""] {
+        assert_matches!(
+            invalid_version.parse::<StarknetVersion>(),
+            Err(VersionedConstantsError::InvalidStarknetVersion(_))
+        );
+    }
+}
+
+#[test]
+fn test_bouncer_config_parsing() {
+    let map = VersionedConstantsMap::default();
+    // The 0.13.0 constants predate the bouncer config; their block capacity is unlimited.
+    assert_eq!(map.get(StarknetVersion::V0_13_0).unwrap().bouncer_config, BouncerConfig::max());
+    assert_ne!(VersionedConstants::latest_constants().bouncer_config, BouncerConfig::max());
+}
+
+#[test]
+fn test_failed_call_state_policy_parsing() {
+    let map = VersionedConstantsMap::default();
+    // The shipped constants keep the modifications of failed inner calls, as executed on mainnet.
+    for version in [StarknetVersion::V0_13_0, StarknetVersion::V0_13_1] {
+        assert_eq!(
+            map.get(version).unwrap().failed_call_state_policy,
+            FailedCallStatePolicy::Keep
+        );
+    }
+    let versioned_constants = VersionedConstants::latest_with_overrides(serde_json::json!({
+        "failed_call_state_policy": "discard",
+    }))
+    .unwrap();
+    assert_eq!(versioned_constants.failed_call_state_policy, FailedCallStatePolicy::Discard);
+}
+
+#[test]
+fn test_versioned_constants_map() {
+    let map = VersionedConstantsMap::default();
+    // The 0.13.0 constants predate the L2 resource gas costs.
+    assert_eq!(
+        map.get(StarknetVersion::V0_13_0).unwrap().l2_resource_gas_costs,
+        L2ResourceGasCosts::default()
+    );
+    assert_eq!(
+        map.get(StarknetVersion::LATEST).unwrap().validate_max_n_steps,
+        VersionedConstants::latest_constants().validate_max_n_steps
+    );
+    let future_version = StarknetVersion::new(0, 14, 0);
+    assert_matches!(
+        map.get(future_version),
+        Err(VersionedConstantsError::UnsupportedStarknetVersion(version))
+        if version == future_version
+    );
+
+    // Overrides take precedence, and may be replaced or removed at runtime.
+    let mut custom_constants = VersionedConstants::latest_constants().clone();
+    custom_constants.validate_max_n_steps = 1;
+    assert!(map.register(StarknetVersion::LATEST, custom_constants.clone()).is_none());
+    assert_eq!(map.get(StarknetVersion::LATEST).unwrap().validate_max_n_steps, 1);
+    custom_constants.validate_max_n_steps = 2;
+    assert!(map.register(StarknetVersion::LATEST, custom_constants).is_some());
+    assert_eq!(map.get(StarknetVersion::LATEST).unwrap().validate_max_n_steps, 2);
+    map.unregister(StarknetVersion::LATEST);
+    assert_eq!(
+        map.get(StarknetVersion::LATEST).unwrap().validate_max_n_steps,
+        VersionedConstants::latest_constants().validate_max_n_steps
+    );
+
+    map.register(future_version, VersionedConstants::latest_constants().clone());
+    assert!(map.get(future_version).is_ok());
+}
+
+#[test]
+fn test_constants_presets() {
+    let names: Vec<&str> = ConstantsPreset::all().map(|preset| preset.name()).collect();
+    assert_eq!(names, vec!["mainnet_0_13_0", "mainnet_0_13_1"]);
+    for preset in ConstantsPreset::all() {
+        assert_eq!(preset.to_string().parse::<ConstantsPreset>().unwrap(), preset);
+        let embedded_constants = preset.starknet_version().embedded_constants().unwrap();
+        assert!(std::ptr::eq(embedded_constants, preset.constants()));
+    }
+    assert_eq!(ConstantsPreset::all().last().unwrap().starknet_version(), StarknetVersion::LATEST);
+    assert_matches!(
+        "mainnet".parse::<ConstantsPreset>(),
+        Err(VersionedConstantsError::UnknownConstantsPreset(name)) if name == "mainnet"
+    );
+}
+
+#[test]
+fn test_gas_costs() {
+    let versioned_constants = VersionedConstants::latest_constants();
+    let gas_costs = versioned_constants.gas_costs();
+    let raw_os_constants: IndexMap<String, Value> = serde_json::from_value(
+        serde_json::from_str::<Value>(DEFAULT_CONSTANTS_JSON).unwrap()["os_constants"].clone(),
+    )
+    .unwrap();
+
+    // Plain values are read as is; composed ones are expanded.
+    assert_eq!(gas_costs.entry_point_initial_budget, gas_costs.step_gas_cost * 100);
+    for name in GasCosts::NAMES {
+        if let Value::Number(value) = &raw_os_constants[*name] {
+            assert_eq!(gas_costs.get(name), value.as_u64(), "Unexpected value for {name}.");
+        }
+    }
+    assert_eq!(
+        versioned_constants.tx_initial_gas(),
+        gas_costs.initial_gas_cost - gas_costs.transaction_gas_cost
+    );
+}
@@ -0,0 +1,46 @@
+//! Benchmark module for loading calldata into VM memory. It compares allocating a calldata
+//! segment from an intermediate `MaybeRelocatable` copy against writing the calldata felts
+//! directly into the VM memory, for megabyte-scale calldata.
+//!
+//! Run the benchmarks using `cargo bench --bench calldata_bench`.
+
+use blockifier::execution::execution_utils::{stark_felt_to_felt, ReadOnlySegments};
+use cairo_vm::types::relocatable::MaybeRelocatable;
+use cairo_vm::vm::vm_core::VirtualMachine;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Calldata;
+
+// 1 MB of calldata (32 bytes per felt).
+const N_CALLDATA_FELTS: u64 = 1 << 15;
+
+pub fn calldata_benchmark(c: &mut Criterion) {
+    let calldata = Calldata((0..N_CALLDATA_FELTS).map(StarkFelt::from).collect::<Vec<_>>().into());
+
+    let mut group = c.benchmark_group("calldata_to_vm_memory");
+    group.bench_function("with_intermediate_copy", |benchmark| {
+        benchmark.iter_batched(
+            || VirtualMachine::new(false),
+            |mut vm| {
+                let data: Vec<MaybeRelocatable> = calldata
+                    .0
+                    .iter()
+                    .map(|&felt| MaybeRelocatable::from(stark_felt_to_felt(felt)))
+                    .collect();
+                ReadOnlySegments::default().allocate(&mut vm, &data).unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("direct", |benchmark| {
+        benchmark.iter_batched(
+            || VirtualMachine::new(false),
+            |mut vm| ReadOnlySegments::default().allocate_felts(&mut vm, &calldata.0).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, calldata_benchmark);
+criterion_main!(benches);
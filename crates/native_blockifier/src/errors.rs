@@ -1,4 +1,5 @@
 use blockifier::blockifier::transaction_executor::TransactionExecutorError;
+use blockifier::context::ChainIdError;
 use blockifier::execution::errors::ContractClassError;
 use blockifier::state::errors::StateError;
 use blockifier::transaction::errors::{
@@ -81,6 +82,8 @@ pub enum NativeBlockifierInputError {
     #[error("Max validate steps per tx out of range: {0}")]
     MaxValidateStepsPerTxOutOfRange(u32),
     #[error(transparent)]
+    ChainIdError(#[from] ChainIdError),
+    #[error(transparent)]
     InvalidNativeBlockifierInputError(#[from] InvalidNativeBlockifierInputError),
     #[error(transparent)]
     ParseError(#[from] ParseError),
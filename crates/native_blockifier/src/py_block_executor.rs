@@ -4,9 +4,10 @@ use blockifier::blockifier::block::{
     pre_process_block as pre_process_block_blockifier, BlockInfo, BlockNumberHashPair, GasPrices,
 };
 use blockifier::blockifier::transaction_executor::TransactionExecutor;
-use blockifier::context::{BlockContext, ChainInfo, FeeTokenAddresses};
+use blockifier::context::{BlockContext, ChainInfo, FeeTokenAddresses, ProtectedAddresses};
 use blockifier::execution::call_info::CallInfo;
 use blockifier::state::cached_state::{CachedState, GlobalContractCache};
+use blockifier::state::papyrus_state_reader::PapyrusReader;
 use blockifier::state::state_api::State;
 use blockifier::transaction::objects::{GasVector, ResourcesMapping, TransactionExecutionInfo};
 use blockifier::transaction::transaction_execution::Transaction;
@@ -26,7 +27,6 @@ use crate::py_state_diff::{PyBlockInfo, PyStateDiff};
 use crate::py_transaction::{py_tx, PyClassInfo};
 use crate::py_transaction_execution_info::PyBouncerInfo;
 use crate::py_utils::{int_to_chain_id, versioned_constants_with_overrides, PyFelt};
-use crate::state_readers::papyrus_state::PapyrusReader;
 use crate::storage::{PapyrusStorage, Storage, StorageConfig};
 
 pub(crate) type RawTransactionExecutionInfo = Vec<u8>;
@@ -202,6 +202,17 @@ impl PyBlockExecutor {
         Ok(finalized_state)
     }
 
+    /// Returns the bloom filters of the block's committed transactions, over the called contract
+    /// addresses, the emitted event keys and the declared class hashes.
+    pub fn get_bloom_filters(&mut self) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let bloom_filters = &self.tx_executor().bloom_filters;
+        (
+            bloom_filters.contract_addresses.as_bytes().to_vec(),
+            bloom_filters.event_keys.as_bytes().to_vec(),
+            bloom_filters.class_hashes.as_bytes().to_vec(),
+        )
+    }
+
     pub fn commit_tx(&mut self) {
         self.tx_executor().commit()
     }
@@ -271,9 +282,10 @@ impl PyBlockExecutor {
     /// (this is true for every partial existence of information at tables).
     #[pyo3(signature = (block_number))]
     pub fn revert_block(&mut self, block_number: u64) -> NativeBlockifierResult<()> {
-        // Clear global class cache, to peroperly revert classes declared in the reverted block.
-        self.global_contract_cache.clear();
-        self.storage.revert_block(block_number)
+        let reverted_class_hashes = self.storage.revert_block(block_number)?;
+        // Invalidate the classes declared in the reverted block, so they are not executed.
+        self.global_contract_cache.invalidate_classes(&reverted_class_hashes);
+        Ok(())
     }
 
     /// Deallocate the transaction executor and close storage connections.
@@ -347,7 +359,7 @@ impl TryFrom<PyOsConfig> for ChainInfo {
 
     fn try_from(py_os_config: PyOsConfig) -> Result<Self, Self::Error> {
         Ok(Self {
-            chain_id: py_os_config.chain_id,
+            chain_id: py_os_config.chain_id.try_into().map_err(NativeBlockifierInputError::from)?,
             fee_token_addresses: FeeTokenAddresses {
                 eth_fee_token_address: ContractAddress::try_from(
                     py_os_config.deprecated_fee_token_address.0,
@@ -356,6 +368,7 @@ impl TryFrom<PyOsConfig> for ChainInfo {
                     py_os_config.fee_token_address.0,
                 )?,
             },
+            protected_addresses: ProtectedAddresses::default(),
         })
     }
 }
@@ -414,6 +427,7 @@ pub fn into_block_context_args(
             )?,
         },
         use_kzg_da: block_info.use_kzg_da,
+        fee_recipients: None,
     };
 
     Ok((block_info, chain_info))
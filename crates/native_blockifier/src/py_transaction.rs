@@ -1,9 +1,8 @@
 use std::collections::BTreeMap;
 
-use blockifier::execution::contract_class::{
-    ClassInfo, ContractClass, ContractClassV0, ContractClassV1,
-};
+use blockifier::execution::contract_class::ClassInfo;
 use blockifier::transaction::account_transaction::AccountTransaction;
+use blockifier::transaction::errors::ParseError;
 use blockifier::transaction::transaction_execution::Transaction;
 use blockifier::transaction::transaction_types::TransactionType;
 use pyo3::exceptions::PyValueError;
@@ -139,6 +138,10 @@ pub fn py_tx(
             AccountTransaction::Invoke(py_invoke_function(tx)?).into()
         }
         TransactionType::L1Handler => py_l1_handler(tx)?.into(),
+        TransactionType::Custom(_) => {
+            let error = ParseError::UnknownTransactionType(tx_type.to_string());
+            return Err(NativeBlockifierInputError::ParseError(error).into());
+        }
     })
 }
 #[derive(FromPyObject)]
@@ -153,21 +156,11 @@ impl PyClassInfo {
         py_class_info: PyClassInfo,
         tx: &starknet_api::transaction::DeclareTransaction,
     ) -> NativeBlockifierResult<ClassInfo> {
-        let contract_class: ContractClass = match tx {
-            starknet_api::transaction::DeclareTransaction::V0(_)
-            | starknet_api::transaction::DeclareTransaction::V1(_) => {
-                ContractClassV0::try_from_json_string(&py_class_info.raw_contract_class)?.into()
-            }
-            starknet_api::transaction::DeclareTransaction::V2(_)
-            | starknet_api::transaction::DeclareTransaction::V3(_) => {
-                ContractClassV1::try_from_json_string(&py_class_info.raw_contract_class)?.into()
-            }
-        };
-        let class_info = ClassInfo::new(
-            &contract_class,
+        Ok(ClassInfo::from_raw_contract_class(
+            tx,
+            &py_class_info.raw_contract_class,
             py_class_info.sierra_program_length,
             py_class_info.abi_length,
-        )?;
-        Ok(class_info)
+        )?)
     }
 }
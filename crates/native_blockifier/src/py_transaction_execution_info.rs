@@ -230,6 +230,8 @@ pub struct PyBouncerInfo {
     pub execution_resources: PyExecutionResources,
     #[pyo3(get)]
     pub n_events: usize,
+    #[pyo3(get)]
+    pub syscall_counter: HashMap<String, usize>,
 }
 
 impl From<BouncerInfo> for PyBouncerInfo {
@@ -240,6 +242,11 @@ impl From<BouncerInfo> for PyBouncerInfo {
             message_segment_length: bouncer_info.message_segment_length,
             execution_resources: PyExecutionResources::from(bouncer_info.execution_resources),
             n_events: bouncer_info.n_events,
+            syscall_counter: bouncer_info
+                .syscall_counter
+                .into_iter()
+                .map(|(selector, count)| (format!("{selector:?}"), count))
+                .collect(),
         }
     }
 }
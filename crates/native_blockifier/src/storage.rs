@@ -97,15 +97,25 @@ impl Storage for PapyrusStorage {
         Ok(block_hash)
     }
 
-    fn revert_block(&mut self, block_number: u64) -> NativeBlockifierResult<()> {
+    fn revert_block(&mut self, block_number: u64) -> NativeBlockifierResult<Vec<ClassHash>> {
         log::debug!("Reverting state diff for {block_number:?}.");
         let block_number = BlockNumber(block_number);
         let revert_txn = self.writer().begin_rw_txn()?;
-        let (revert_txn, _) = revert_txn.revert_state_diff(block_number)?;
+        let (revert_txn, reverted_state_diff) = revert_txn.revert_state_diff(block_number)?;
         let (revert_txn, _, _) = revert_txn.revert_header(block_number)?;
 
         revert_txn.commit()?;
-        Ok(())
+
+        // Collect the classes declared in the reverted block.
+        let reverted_class_hashes = match reverted_state_diff {
+            Some((thin_state_diff, ..)) => thin_state_diff
+                .declared_classes
+                .into_keys()
+                .chain(thin_state_diff.deprecated_declared_classes)
+                .collect(),
+            None => vec![],
+        };
+        Ok(reverted_class_hashes)
     }
 
     // TODO(Gilad): Refactor.
@@ -277,7 +287,8 @@ pub trait Storage {
     fn get_header_marker(&self) -> NativeBlockifierResult<u64>;
     fn get_block_id(&self, block_number: u64) -> NativeBlockifierResult<Option<Vec<u8>>>;
 
-    fn revert_block(&mut self, block_number: u64) -> NativeBlockifierResult<()>;
+    /// Reverts the given block; returns the hashes of the classes declared in it.
+    fn revert_block(&mut self, block_number: u64) -> NativeBlockifierResult<Vec<ClassHash>>;
     fn append_block(
         &mut self,
         block_id: u64,
@@ -21,7 +21,10 @@ impl Storage for MockStorage {
         todo!()
     }
 
-    fn revert_block(&mut self, _block_number: u64) -> NativeBlockifierResult<()> {
+    fn revert_block(
+        &mut self,
+        _block_number: u64,
+    ) -> NativeBlockifierResult<Vec<starknet_api::core::ClassHash>> {
         todo!()
     }
 